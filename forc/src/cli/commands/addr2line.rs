@@ -10,7 +10,7 @@ use annotate_snippets::{
     snippet::{AnnotationType, Slice, Snippet, SourceAnnotation},
 };
 
-use sway_core::source_map::{LocationRange, SourceMap};
+use sway_core::source_map::{LocationRange, SourceMap, SOURCE_MAP_VERSION};
 
 /// Show location and context of an opcode address in its source file
 #[derive(Debug, Parser)]
@@ -30,46 +30,19 @@ pub(crate) struct Command {
 }
 
 pub(crate) fn exec(command: Command) -> Result<()> {
-    let contents = fs::read(&command.sourcemap_path)
-        .map_err(|err| anyhow!("{:?}: could not read: {:?}", command.sourcemap_path, err))?;
-
-    let sm: SourceMap = serde_json::from_slice(&contents).map_err(|err| {
-        anyhow!(
-            "{:?}: invalid source map json: {}",
-            command.sourcemap_path,
-            err
-        )
-    })?;
+    let sm = read_source_map(&command.sourcemap_path)?;
 
-    if let Some((mut path, range)) = sm.addr_to_span(command.opcode_index) {
-        if path.is_relative() {
-            path = command.search_dir.join(path);
-        }
+    if let Some((path, range)) = sm.addr_to_span(command.opcode_index) {
+        print_location(&command, path, range, "here")?;
 
-        let rr = read_range(&path, range, command.context)
-            .map_err(|err| anyhow!("{:?}: could not read: {:?}", path, err))?;
-
-        let path_str = format!("{:?}", path);
-        let snippet = Snippet {
-            title: None,
-            footer: vec![],
-            slices: vec![Slice {
-                source: &rr.source,
-                line_start: rr.source_start_line,
-                origin: Some(&path_str),
-                fold: false,
-                annotations: vec![SourceAnnotation {
-                    label: "here",
-                    annotation_type: AnnotationType::Note,
-                    range: (rr.offset, rr.offset + rr.length),
-                }],
-            }],
-            opt: FormatOptions {
-                color: true,
-                ..Default::default()
-            },
-        };
-        println!("{}", DisplayList::from(snippet));
+        // Show the calls which the opcode's function was inlined at, innermost first.
+        for scope in sm.scopes_at(command.opcode_index) {
+            if let Some(call_site) = &scope.call_site {
+                let (path, range) = sm.resolve(call_site);
+                let label = format!("`{}` inlined here", scope.name);
+                print_location(&command, path, range, &label)?;
+            }
+        }
 
         Ok(())
     } else {
@@ -77,6 +50,61 @@ pub(crate) fn exec(command: Command) -> Result<()> {
     }
 }
 
+/// Reads a source map of the current version or any older one.
+fn read_source_map(path: &Path) -> Result<SourceMap> {
+    let contents =
+        fs::read(path).map_err(|err| anyhow!("{:?}: could not read: {:?}", path, err))?;
+
+    let sm: SourceMap = serde_json::from_slice(&contents)
+        .map_err(|err| anyhow!("{:?}: invalid source map json: {}", path, err))?;
+    if sm.version() > SOURCE_MAP_VERSION {
+        return Err(anyhow!(
+            "{:?}: source map version {} is newer than the supported version {}, try updating forc",
+            path,
+            sm.version(),
+            SOURCE_MAP_VERSION
+        ));
+    }
+    Ok(sm)
+}
+
+fn print_location(
+    command: &Command,
+    mut path: PathBuf,
+    range: LocationRange,
+    label: &str,
+) -> Result<()> {
+    if path.is_relative() {
+        path = command.search_dir.join(path);
+    }
+
+    let rr = read_range(&path, range, command.context)
+        .map_err(|err| anyhow!("{:?}: could not read: {:?}", path, err))?;
+
+    let path_str = format!("{:?}", path);
+    let snippet = Snippet {
+        title: None,
+        footer: vec![],
+        slices: vec![Slice {
+            source: &rr.source,
+            line_start: rr.source_start_line,
+            origin: Some(&path_str),
+            fold: false,
+            annotations: vec![SourceAnnotation {
+                label,
+                annotation_type: AnnotationType::Note,
+                range: (rr.offset, rr.offset + rr.length),
+            }],
+        }],
+        opt: FormatOptions {
+            color: true,
+            ..Default::default()
+        },
+    };
+    println!("{}", DisplayList::from(snippet));
+    Ok(())
+}
+
 struct ReadRange {
     source: String,
     source_start_byte: usize,
@@ -99,6 +127,9 @@ fn read_range<P: AsRef<Path>>(
     for line_num in 0.. {
         let mut buffer = String::new();
         let n = reader.read_line(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
         context_buffer.push_back(buffer);
         if start_pos.is_none() {
             if position + n > range.start {
@@ -139,3 +170,104 @@ fn read_range<P: AsRef<Path>>(
         length,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A file in the temporary directory which is removed when dropped.
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str, contents: &str) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "forc-addr2line-{}-{}",
+                std::process::id(),
+                name
+            ));
+            fs::write(&path, contents).unwrap();
+            TempFile(path)
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn unversioned_source_maps_are_read() {
+        let file = TempFile::new(
+            "unversioned.json",
+            r#"{
+                "dependency_paths": [],
+                "paths": ["src/main.sw"],
+                "map": { "0": { "path": 0, "range": { "start": 3, "end": 7 } } }
+            }"#,
+        );
+        let sm = read_source_map(&file.0).unwrap();
+        assert_eq!(sm.version(), 0);
+        let (path, range) = sm.addr_to_span(0).unwrap();
+        assert_eq!(path, PathBuf::from("src/main.sw"));
+        assert_eq!((range.start, range.end), (3, 7));
+    }
+
+    #[test]
+    fn current_source_maps_are_read() {
+        let file = TempFile::new(
+            "current.json",
+            &serde_json::to_string(&SourceMap::new()).unwrap(),
+        );
+        assert_eq!(
+            read_source_map(&file.0).unwrap().version(),
+            SOURCE_MAP_VERSION
+        );
+    }
+
+    #[test]
+    fn newer_source_maps_are_rejected() {
+        let file = TempFile::new(
+            "newer.json",
+            &format!(
+                r#"{{ "version": {}, "dependency_paths": [], "paths": [], "map": {{}} }}"#,
+                SOURCE_MAP_VERSION + 1
+            ),
+        );
+        let err = read_source_map(&file.0).unwrap_err();
+        assert!(err.to_string().contains("try updating forc"));
+    }
+
+    #[test]
+    fn ranges_are_read_with_context() {
+        let file = TempFile::new(
+            "main.sw",
+            "script;\n\nfn main() {\n    let x = 1;\n}\n\n// end\n",
+        );
+        let start = "script;\n\nfn main() {\n    let ".len();
+        let range = LocationRange {
+            start,
+            end: start + 1,
+        };
+        let rr = read_range(&file.0, range, 1).unwrap();
+        assert_eq!(rr.source, "fn main() {\n    let x = 1;\n}\n");
+        assert_eq!(rr.source_start_line, 3);
+        assert_eq!(&rr.source[rr.offset..rr.offset + rr.length], "x");
+
+        // a range near the end of the file has less context after it
+        let start = "script;\n\nfn main() {\n    let x = 1;\n}\n\n// ".len();
+        let range = LocationRange {
+            start,
+            end: start + 3,
+        };
+        let rr = read_range(&file.0, range, 2).unwrap();
+        assert_eq!(rr.source, "}\n\n// end\n");
+        assert_eq!(&rr.source[rr.offset..rr.offset + rr.length], "end");
+
+        let out_of_range = LocationRange {
+            start: 100,
+            end: 101,
+        };
+        assert!(read_range(&file.0, out_of_range, 1).is_err());
+    }
+}
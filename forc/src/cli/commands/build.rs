@@ -8,14 +8,29 @@ use clap::Parser;
 /// contract projects will produce their bytecode in binary format `<project-name>.bin`. Building
 /// contracts and libraries will also produce the public ABI in JSON format
 /// `<project-name>-abi.json`.
+///
+/// Building a workspace places the artifacts of every member within the workspace's shared output
+/// directory.
 #[derive(Debug, Default, Parser)]
 pub struct Command {
     /// Path to the project, if not specified, current working directory will be used.
     #[clap(short, long)]
     pub path: Option<String>,
+    /// Name of the workspace member to build.
+    ///
+    /// By default, all members are built from the workspace root, while only the enclosing member
+    /// is built from within a member's directory.
+    #[clap(long)]
+    pub package: Option<String>,
     /// Whether to compile using the IR pipeline.
     #[clap(long)]
     pub use_ir: bool,
+    /// The optimization level of the IR pipeline: 0, 1 or 2.  Defaults to 1.
+    #[clap(long, possible_values = &["0", "1", "2"])]
+    pub ir_opt_level: Option<String>,
+    /// Whether to print the time taken by each IR optimization pass.
+    #[clap(long)]
+    pub time_ir_passes: bool,
     /// Whether to compile to bytecode (false) or to print out the generated ASM (true).
     #[clap(long)]
     pub print_finalized_asm: bool,
@@ -50,6 +65,6 @@ pub struct Command {
 }
 
 pub(crate) fn exec(command: Command) -> Result<()> {
-    forc_build::build(command)?;
+    forc_build::build_all(command)?;
     Ok(())
 }
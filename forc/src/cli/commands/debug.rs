@@ -0,0 +1,18 @@
+use crate::ops::forc_debug;
+use anyhow::Result;
+use clap::Parser;
+
+/// Run the debug adapter, for debugging a script from an editor.
+///
+/// The adapter speaks the Debug Adapter Protocol over stdin and stdout. When launched, it compiles
+/// the script at the `program` path, along with the contracts at the `contracts` paths, and runs
+/// them within an in-memory Fuel VM. Breakpoints are mapped to opcodes through each program's
+/// source map.
+///
+/// Dependencies are loaded from `Forc.lock`, so run `forc build` first.
+#[derive(Debug, Parser)]
+pub(crate) struct Command {}
+
+pub(crate) fn exec(_command: Command) -> Result<()> {
+    forc_debug::debug()
+}
@@ -12,6 +12,12 @@ pub struct Command {
     /// Whether to compile using the IR pipeline.
     #[clap(long)]
     pub use_ir: bool,
+    /// The optimization level of the IR pipeline: 0, 1 or 2.  Defaults to 1.
+    #[clap(long, possible_values = &["0", "1", "2"])]
+    pub ir_opt_level: Option<String>,
+    /// Whether to print the time taken by each IR optimization pass.
+    #[clap(long)]
+    pub time_ir_passes: bool,
     /// Whether to compile to bytecode (false) or to print out the generated ASM (true).
     #[clap(long)]
     pub print_finalized_asm: bool,
@@ -2,7 +2,7 @@ use crate::ops::forc_fmt;
 use anyhow::{bail, Result};
 use clap::Parser;
 
-/// Format all Sway files of the current project.
+/// Format all Sway files of the current project or workspace.
 #[derive(Debug, Parser)]
 pub struct Command {
     /// Run in 'check' mode.
@@ -10,6 +10,12 @@ pub struct Command {
     /// Exits with 1 and prints a diff if formatting is required.
     #[clap(short, long)]
     pub check: bool,
+    /// Name of the workspace member to format.
+    ///
+    /// By default, all members are formatted from the workspace root, while only the enclosing
+    /// member is formatted from within a member's directory.
+    #[clap(long)]
+    pub package: Option<String>,
 }
 
 // todo: add formatting options in the command line
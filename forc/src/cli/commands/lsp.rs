@@ -1,11 +1,11 @@
+use crate::ops::forc_lsp;
 use anyhow::Result;
 use clap::Parser;
-use sway_server::start;
 /// Run the LSP server.
 #[derive(Debug, Parser)]
 pub(crate) struct Command {}
 
 pub(crate) async fn exec(_command: Command) -> Result<()> {
-    start().await;
+    forc_lsp::lsp().await;
     Ok(())
 }
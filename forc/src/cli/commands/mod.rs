@@ -2,6 +2,7 @@ pub mod addr2line;
 pub mod build;
 pub mod clean;
 pub mod completions;
+pub mod debug;
 pub mod deploy;
 pub mod explorer;
 pub mod format;
@@ -1,27 +1,61 @@
-use crate::ops::forc_build;
-use anyhow::Result;
+use crate::ops::{forc_build, forc_test};
+use anyhow::{bail, Result};
 use clap::Parser;
 use std::io::{BufRead, BufReader};
 use std::process::Command as ProcessCommand;
 use std::process::Stdio;
 use std::thread;
 
-/// Run Rust-based tests on current project.
-/// As of now, `forc test` is a simple wrapper on
-/// `cargo test`; `forc init` also creates a rust
-/// package under your project, named `tests`.
-/// You can opt to either run these Rust tests by
-/// using `forc test` or going inside the package
-/// and using `cargo test`.
-#[derive(Debug, Parser)]
-pub(crate) struct Command {
+/// Run the Sway unit tests of the current project.
+///
+/// Each function annotated with `#[test]` is compiled to its own script and run within an
+/// in-memory Fuel VM, so no running node is required. A test passes if it runs to completion
+/// without reverting.
+///
+/// The Rust-based tests under the `tests` directory created by `forc init` can be run using
+/// `forc test --cargo`, which is a simple wrapper on `cargo test`.
+#[derive(Debug, Default, Parser)]
+pub struct Command {
     /// If specified, only run tests containing this string in their names
     pub test_name: Option<String>,
+    /// Path to the project, if not specified, current working directory will be used.
+    #[clap(short, long)]
+    pub path: Option<String>,
+    /// Name of the workspace member to test.
+    ///
+    /// By default, all members are tested from the workspace root, while only the enclosing member
+    /// is tested from within a member's directory.
+    #[clap(long)]
+    pub package: Option<String>,
+    /// Whether to compile using the IR pipeline.
+    #[clap(long)]
+    pub use_ir: bool,
+    /// Offline mode, prevents Forc from using the network when managing dependencies.
+    /// Meaning it will only try to use previously downloaded dependencies.
+    #[clap(long = "offline")]
+    pub offline_mode: bool,
+    /// Silent mode. Don't output any warnings or errors to the command line.
+    #[clap(long = "silent", short = 's')]
+    pub silent_mode: bool,
+    /// Run the Rust-based tests with `cargo test` rather than the Sway unit tests.
+    #[clap(long)]
+    pub cargo: bool,
 }
 
 pub(crate) fn exec(command: Command) -> Result<()> {
+    if command.cargo {
+        return exec_cargo(command);
+    }
+    let summary = forc_test::test(command)?;
+    if summary.failed() > 0 {
+        bail!("{} test(s) failed", summary.failed());
+    }
+    Ok(())
+}
+
+fn exec_cargo(command: Command) -> Result<()> {
     // Ensure the project builds before running tests.
-    forc_build::build(Default::default())?;
+    forc_build::build_all(Default::default())?;
 
     // Cargo args setup
     let mut args: Vec<String> = vec!["test".into()];
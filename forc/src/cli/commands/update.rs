@@ -9,10 +9,10 @@ pub struct Command {
     #[clap(short, long)]
     pub path: Option<String>,
 
-    /// Dependency to be updated.
+    /// Package to be updated, keeping all other dependencies at their locked versions.
     /// If not set, all dependencies will be updated.
-    #[clap(short = 'd')]
-    pub target_dependency: Option<String>,
+    #[clap(long)]
+    pub package: Option<String>,
 
     /// Checks if the dependencies have newer versions.
     /// Won't actually perform the update, will output which
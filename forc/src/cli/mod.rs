@@ -3,7 +3,7 @@ use clap::Parser;
 
 mod commands;
 use self::commands::{
-    addr2line, build, clean, completions, deploy, explorer, format, init, json_abi, lsp,
+    addr2line, build, clean, completions, debug, deploy, explorer, format, init, json_abi, lsp,
     parse_bytecode, run, test, update,
 };
 
@@ -11,6 +11,7 @@ use addr2line::Command as Addr2LineCommand;
 pub use build::Command as BuildCommand;
 pub use clean::Command as CleanCommand;
 pub use completions::Command as CompletionsCommand;
+use debug::Command as DebugCommand;
 pub use deploy::Command as DeployCommand;
 pub use explorer::Command as ExplorerCommand;
 pub use format::Command as FormatCommand;
@@ -19,7 +20,7 @@ pub use json_abi::Command as JsonAbiCommand;
 use lsp::Command as LspCommand;
 use parse_bytecode::Command as ParseBytecodeCommand;
 pub use run::Command as RunCommand;
-use test::Command as TestCommand;
+pub use test::Command as TestCommand;
 pub use update::Command as UpdateCommand;
 
 #[derive(Debug, Parser)]
@@ -38,6 +39,7 @@ enum Forc {
     Clean(CleanCommand),
     #[clap(after_help = completions::COMPLETIONS_HELP)]
     Completions(CompletionsCommand),
+    Debug(DebugCommand),
     Deploy(DeployCommand),
     Explorer(ExplorerCommand),
     #[clap(name = "fmt")]
@@ -59,6 +61,7 @@ pub(crate) async fn run_cli() -> Result<()> {
         Forc::Build(command) => build::exec(command),
         Forc::Clean(command) => clean::exec(command),
         Forc::Completions(command) => completions::exec(command),
+        Forc::Debug(command) => debug::exec(command),
         Forc::Deploy(command) => deploy::exec(command).await,
         Forc::Explorer(command) => explorer::exec(command).await,
         Forc::Format(command) => format::exec(command),
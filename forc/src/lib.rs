@@ -3,12 +3,14 @@ mod cli;
 mod lock;
 mod ops;
 mod pkg;
+mod registry;
 mod utils;
+mod workspace;
 
 #[cfg(feature = "test")]
 pub mod test {
-    pub use crate::cli::{BuildCommand, DeployCommand, JsonAbiCommand, RunCommand};
-    pub use crate::ops::{forc_abi_json, forc_build, forc_deploy, forc_run};
+    pub use crate::cli::{BuildCommand, DeployCommand, JsonAbiCommand, RunCommand, TestCommand};
+    pub use crate::ops::{forc_abi_json, forc_build, forc_deploy, forc_run, forc_test};
 }
 
 #[cfg(feature = "util")]
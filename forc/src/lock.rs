@@ -1,4 +1,4 @@
-use crate::pkg;
+use crate::{pkg, utils::helpers::read_manifest};
 use anyhow::{anyhow, Result};
 use petgraph::{visit::EdgeRef, Direction};
 use serde::{Deserialize, Serialize};
@@ -27,9 +27,8 @@ pub(crate) struct Diff<'a> {
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Deserialize, Serialize)]
 pub(crate) struct PkgLock {
     pub(crate) name: String,
-    // TODO: Cargo *always* includes version, whereas we don't even parse it when reading a
-    // project's `Manifest` yet. If we decide to enforce versions, we'll want to remove the
-    // `Option`.
+    // The pinned version for registry packages, otherwise the `version` declared in the package's
+    // manifest if it has one.
     version: Option<semver::Version>,
     source: Option<String>,
     // Dependency string is "<name> <source_string>". The source string is included in order to be
@@ -44,7 +43,7 @@ pub fn source_to_string(source: &pkg::SourcePinned) -> Option<String> {
     match source {
         pkg::SourcePinned::Path => None,
         pkg::SourcePinned::Git(git) => Some(git.to_string()),
-        pkg::SourcePinned::Registry(_reg) => unimplemented!("pkg registries not yet implemented"),
+        pkg::SourcePinned::Registry(reg) => Some(reg.to_string()),
     }
 }
 
@@ -53,7 +52,9 @@ pub fn source_from_str(s: &str) -> Result<pkg::SourcePinned> {
     if let Ok(src) = pkg::SourceGitPinned::from_str(s) {
         return Ok(pkg::SourcePinned::Git(src));
     }
-    // TODO: Try parse registry source.
+    if let Ok(src) = pkg::SourceRegistryPinned::from_str(s) {
+        return Ok(pkg::SourcePinned::Registry(src));
+    }
     Err(anyhow!(
         "Unable to parse valid pinned source from given string {}",
         s
@@ -62,12 +63,15 @@ pub fn source_from_str(s: &str) -> Result<pkg::SourcePinned> {
 
 impl PkgLock {
     /// Construct a package lock given a package's entry in the package graph.
-    pub fn from_node(graph: &pkg::Graph, node: pkg::NodeIx) -> Self {
+    pub fn from_node(graph: &pkg::Graph, path_map: &pkg::PathMap, node: pkg::NodeIx) -> Self {
         let pinned = &graph[node];
         let name = pinned.name.clone();
         let version = match &pinned.source {
-            pkg::SourcePinned::Registry(reg) => Some(reg.source.version.clone()),
-            _ => None,
+            pkg::SourcePinned::Registry(reg) => Some(reg.version.clone()),
+            _ => path_map
+                .get(&pinned.id())
+                .and_then(|path| read_manifest(path).ok())
+                .and_then(|manifest| manifest.project.version),
         };
         let source = source_to_string(&pinned.source);
         let mut dependencies: Vec<String> = graph
@@ -104,10 +108,10 @@ impl Lock {
 
     /// Given a graph of pinned packages, create a `Lock` representing the `Forc.lock` file
     /// structure.
-    pub fn from_graph(graph: &pkg::Graph) -> Self {
+    pub fn from_graph(graph: &pkg::Graph, path_map: &pkg::PathMap) -> Self {
         let package: BTreeSet<_> = graph
             .node_indices()
-            .map(|node| PkgLock::from_node(graph, node))
+            .map(|node| PkgLock::from_node(graph, path_map, node))
             .collect();
         Self { package }
     }
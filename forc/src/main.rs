@@ -3,7 +3,9 @@ mod cli;
 mod lock;
 mod ops;
 mod pkg;
+mod registry;
 mod utils;
+mod workspace;
 
 use anyhow::Result;
 
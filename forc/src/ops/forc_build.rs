@@ -2,7 +2,8 @@ use crate::{
     cli::BuildCommand,
     lock::Lock,
     pkg,
-    utils::helpers::{default_output_directory, lock_path, print_lock_diff, read_manifest},
+    utils::helpers::{default_output_directory, print_lock_diff},
+    workspace::Members,
 };
 use anyhow::{anyhow, bail, Result};
 use std::{
@@ -11,13 +12,41 @@ use std::{
     path::PathBuf,
 };
 use sway_core::source_map::SourceMap;
-use sway_utils::{find_manifest_dir, MANIFEST_FILE_NAME};
 
+/// Build the selected package, returning its compiled output.
+///
+/// Within a workspace, a single member must be selected, either with `--package` or by building
+/// from within the member's directory.
 pub fn build(command: BuildCommand) -> Result<pkg::Compiled> {
+    let members = Members::find(command.path.as_deref(), command.package.as_deref())?;
+    if members.selected.len() != 1 {
+        bail!(
+            "the workspace at {} has multiple members, select one with `--package`",
+            members.root_dir.display()
+        );
+    }
+    let (_, compiled) = build_members(&members, command)?
+        .pop()
+        .expect("a single member was selected");
+    Ok(compiled)
+}
+
+/// Build all selected packages, returning the compiled output of each by name.
+pub fn build_all(command: BuildCommand) -> Result<Vec<(String, pkg::Compiled)>> {
+    let members = Members::find(command.path.as_deref(), command.package.as_deref())?;
+    build_members(&members, command)
+}
+
+/// Build the selected members along with their dependencies, placing their build artifacts in
+/// the shared output directory.
+fn build_members(members: &Members, command: BuildCommand) -> Result<Vec<(String, pkg::Compiled)>> {
     let BuildCommand {
-        path,
+        path: _,
+        package: _,
         binary_outfile,
         use_ir,
+        ir_opt_level,
+        time_ir_passes,
         debug_outfile,
         print_finalized_asm,
         print_intermediate_asm,
@@ -28,129 +57,161 @@ pub fn build(command: BuildCommand) -> Result<pkg::Compiled> {
         minify_json_abi,
     } = command;
 
+    if members.selected.len() > 1 && (binary_outfile.is_some() || debug_outfile.is_some()) {
+        bail!("`-o` and `-g` can only be used when building a single package");
+    }
+
     let build_conf = pkg::BuildConf {
         use_ir,
+        ir_opt_level,
+        time_ir_passes,
         print_ir,
         print_finalized_asm,
         print_intermediate_asm,
     };
 
-    // find manifest directory, even if in subdirectory
-    let this_dir = if let Some(ref path) = path {
-        PathBuf::from(path)
-    } else {
-        std::env::current_dir()?
-    };
+    let plan = load_build_plan(members, offline)?;
+    let selected = selected_nodes(&plan, members)?;
+    let mut source_map = SourceMap::new();
+    let compiled_members =
+        compile_selected(&plan, &selected, &build_conf, &mut source_map, silent)?;
 
-    let manifest_dir = match find_manifest_dir(&this_dir) {
-        Some(dir) => dir,
-        None => {
-            bail!(
-                "could not find `{}` in `{}` or any parent directory",
-                MANIFEST_FILE_NAME,
-                this_dir.display(),
-            );
+    // TODO: We may support custom build profiles in the future.
+    let profile = "debug";
+
+    // Create the output directory for build artifacts.
+    let output_dir = output_directory
+        .map(PathBuf::from)
+        .unwrap_or_else(|| default_output_directory(&members.root_dir).join(profile));
+    if !output_dir.exists() {
+        fs::create_dir_all(&output_dir)?;
+    }
+
+    for (name, compiled) in &compiled_members {
+        if let Some(outfile) = &binary_outfile {
+            let mut file = File::create(outfile)?;
+            file.write_all(compiled.bytecode.as_slice())?;
         }
-    };
-    let manifest = read_manifest(&manifest_dir)?;
-    let lock_path = lock_path(&manifest_dir);
 
-    // Load the build plan from the lock file.
-    let plan_result = pkg::BuildPlan::from_lock_file(&lock_path);
+        if let Some(outfile) = &debug_outfile {
+            fs::write(
+                outfile,
+                &serde_json::to_vec(&source_map).expect("JSON serialization failed"),
+            )?;
+        }
 
-    // Retrieve the old lock file state so we can produce a diff.
-    let old_lock = plan_result
-        .as_ref()
-        .ok()
-        .map(|plan| Lock::from_graph(&plan.graph))
-        .unwrap_or_default();
+        // Place build artifacts into the output directory.
+        let bin_path = output_dir.join(name).with_extension("bin");
+        std::fs::write(&bin_path, compiled.bytecode.as_slice())?;
+        if !compiled.json_abi.is_empty() {
+            let json_abi_stem = format!("{}-abi", name);
+            let json_abi_path = output_dir.join(&json_abi_stem).with_extension("json");
+            let file = File::create(json_abi_path)?;
+            let res = if minify_json_abi {
+                serde_json::to_writer(&file, &compiled.json_abi)
+            } else {
+                serde_json::to_writer_pretty(&file, &compiled.json_abi)
+            };
+            res?;
+        }
 
-    // Validate the loaded build plan for the current manifest.
-    let plan_result = plan_result.and_then(|plan| plan.validate(&manifest).map(|_| plan));
+        if members.selected.len() > 1 {
+            println!(
+                "  Bytecode size of {} is {} bytes.",
+                name,
+                compiled.bytecode.len()
+            );
+        } else {
+            println!("  Bytecode size is {} bytes.", compiled.bytecode.len());
+        }
+    }
 
-    // If necessary, construct a new build plan.
-    let plan: pkg::BuildPlan = plan_result.or_else(|e| -> Result<pkg::BuildPlan> {
-        println!("  Creating a new `Forc.lock` file");
-        println!("    Cause: {}", e);
-        let plan = pkg::BuildPlan::new(&manifest_dir, offline)?;
-        let lock = Lock::from_graph(&plan.graph);
-        let diff = lock.diff(&old_lock);
-        print_lock_diff(&manifest.project.name, &diff);
-        let string = toml::ser::to_string_pretty(&lock)
-            .map_err(|e| anyhow!("failed to serialize lock file: {}", e))?;
-        fs::write(&lock_path, &string).map_err(|e| anyhow!("failed to write lock file: {}", e))?;
-        println!("   Created new lock file at {}", lock_path.display());
-        Ok(plan)
-    })?;
+    Ok(compiled_members)
+}
 
-    // Iterate over and compile all packages.
+/// Compile the selected packages of the plan along with their dependencies, returning the
+/// compiled output of each selected package by name.
+pub(crate) fn compile_selected(
+    plan: &pkg::BuildPlan,
+    selected: &[pkg::NodeIx],
+    build_conf: &pkg::BuildConf,
+    source_map: &mut SourceMap,
+    silent: bool,
+) -> Result<Vec<(String, pkg::Compiled)>> {
+    let required = plan.with_dependencies(selected);
     let mut namespace_map = Default::default();
-    let mut source_map = SourceMap::new();
-    let mut json_abi = vec![];
-    let mut bytecode = vec![];
-    for &node in &plan.compilation_order {
+    let mut compiled_members = vec![];
+    for &node in plan
+        .compilation_order
+        .iter()
+        .filter(|n| required.contains(n))
+    {
         let dep_namespace =
             pkg::dependency_namespace(&namespace_map, &plan.graph, &plan.compilation_order, node);
         let pkg = &plan.graph[node];
         let path = &plan.path_map[&pkg.id()];
-        let res = pkg::compile(
-            pkg,
-            path,
-            &build_conf,
-            dep_namespace,
-            &mut source_map,
-            silent,
-        )?;
+        let res = pkg::compile(pkg, path, build_conf, dep_namespace, source_map, silent)?;
         let (compiled, maybe_namespace) = res;
         if let Some(namespace) = maybe_namespace {
             namespace_map.insert(node, namespace);
         }
-        json_abi.extend(compiled.json_abi);
-        bytecode = compiled.bytecode;
         source_map.insert_dependency(path.clone());
+        if selected.contains(&node) {
+            compiled_members.push((pkg.name.clone(), compiled));
+        }
     }
+    Ok(compiled_members)
+}
 
-    if let Some(outfile) = binary_outfile {
-        let mut file = File::create(outfile)?;
-        file.write_all(bytecode.as_slice())?;
-    }
+/// The graph nodes of the selected members.
+pub(crate) fn selected_nodes(plan: &pkg::BuildPlan, members: &Members) -> Result<Vec<pkg::NodeIx>> {
+    members
+        .selected
+        .iter()
+        .map(|name| {
+            plan.member_node(name)
+                .ok_or_else(|| anyhow!("member \"{}\" is missing from the build plan", name))
+        })
+        .collect()
+}
 
-    if let Some(outfile) = debug_outfile {
-        fs::write(
-            outfile,
-            &serde_json::to_vec(&source_map).expect("JSON serialization failed"),
-        )?;
-    }
+/// Load the build plan for the members from their shared lock file, creating a new plan and
+/// writing a fresh lock file if the existing one is missing or out of date.
+pub(crate) fn load_build_plan(members: &Members, offline: bool) -> Result<pkg::BuildPlan> {
+    let lock_path = members.lock_path();
 
-    // TODO: We may support custom build profiles in the future.
-    let profile = "debug";
+    // Load the build plan from the lock file.
+    let plan_result = pkg::BuildPlan::from_lock_file(members);
 
-    // Create the output directory for build artifacts.
-    let output_dir = output_directory
-        .map(PathBuf::from)
-        .unwrap_or_else(|| default_output_directory(&manifest_dir).join(profile));
-    if !output_dir.exists() {
-        fs::create_dir_all(&output_dir)?;
-    }
+    // Retrieve the old lock file state so we can produce a diff.
+    let old_lock = plan_result
+        .as_ref()
+        .ok()
+        .map(|plan| Lock::from_graph(&plan.graph, &plan.path_map))
+        .unwrap_or_default();
 
-    // Place build artifacts into the output directory.
-    let bin_path = output_dir
-        .join(&manifest.project.name)
-        .with_extension("bin");
-    std::fs::write(&bin_path, bytecode.as_slice())?;
-    if !json_abi.is_empty() {
-        let json_abi_stem = format!("{}-abi", manifest.project.name);
-        let json_abi_path = output_dir.join(&json_abi_stem).with_extension("json");
-        let file = File::create(json_abi_path)?;
-        let res = if minify_json_abi {
-            serde_json::to_writer(&file, &json_abi)
-        } else {
-            serde_json::to_writer_pretty(&file, &json_abi)
-        };
-        res?;
-    }
+    // Keep the old pinned sources where possible so that only changed dependencies are re-pinned.
+    let locked = plan_result
+        .as_ref()
+        .ok()
+        .map(|plan| pkg::Locked::from_graph(&plan.graph, None))
+        .unwrap_or_default();
 
-    println!("  Bytecode size is {} bytes.", bytecode.len());
+    // Validate the loaded build plan for the members' current manifests.
+    let plan_result = plan_result.and_then(|plan| plan.validate(members).map(|_| plan));
 
-    Ok(pkg::Compiled { bytecode, json_abi })
+    // If necessary, construct a new build plan.
+    plan_result.or_else(|e| -> Result<pkg::BuildPlan> {
+        println!("  Creating a new `Forc.lock` file");
+        println!("    Cause: {}", e);
+        let plan = pkg::BuildPlan::with_locked(members, &locked, offline)?;
+        let lock = Lock::from_graph(&plan.graph, &plan.path_map);
+        let diff = lock.diff(&old_lock);
+        print_lock_diff(members, &diff);
+        let string = toml::ser::to_string_pretty(&lock)
+            .map_err(|e| anyhow!("failed to serialize lock file: {}", e))?;
+        fs::write(&lock_path, &string).map_err(|e| anyhow!("failed to write lock file: {}", e))?;
+        println!("   Created new lock file at {}", lock_path.display());
+        Ok(plan)
+    })
 }
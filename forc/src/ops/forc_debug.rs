@@ -0,0 +1,1105 @@
+//! A Debug Adapter Protocol server for stepping through Sway programs.
+//!
+//! A script, along with any contracts it calls, is compiled and run within an in-memory Fuel VM.
+//! Source lines are mapped to opcodes through each program's `SourceMap`, so that breakpoints may
+//! be set on lines and the call stack and local variables shown in terms of the source.
+//!
+//! The VM may only be stopped at breakpoints, so stepping is implemented by setting temporary
+//! breakpoints on the first opcode of every other line at which execution may next stop.
+
+use crate::{
+    ops::{forc_build, forc_deploy},
+    pkg::{self, BuildPlan},
+    utils::helpers::{get_main_file, read_manifest},
+    workspace::Members,
+};
+use anyhow::{anyhow, bail, Result};
+use fuel_asm::Opcode;
+use fuel_tx::{Output, Transaction};
+use fuel_vm::{
+    consts::{REG_FP, REG_IS, REG_PC, VM_REGISTER_COUNT},
+    interpreter::Interpreter,
+    prelude::*,
+};
+use serde_json::{json, Value};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+};
+use sway_core::{
+    parse,
+    source_map::{SourceMap, VariableLocation},
+    TreeType,
+};
+
+/// The single thread of execution reported to the client.
+const THREAD_ID: u64 = 1;
+
+/// The names of the VM's reserved registers, by index.
+const REGISTER_NAMES: [&str; 16] = [
+    "$zero", "$one", "$of", "$pc", "$ssp", "$sp", "$fp", "$hp", "$err", "$ggas", "$cgas", "$bal",
+    "$is", "$ret", "$retl", "$flag",
+];
+
+/// The reserved registers holding addresses in memory.
+const POINTER_REGISTERS: [&str; 5] = ["$ssp", "$sp", "$fp", "$hp", "$is"];
+
+/// The offset of the caller's registers within a call frame, following the called contract's ID
+/// and the forwarded asset's ID.
+const CALL_FRAME_REGISTERS_OFFSET: usize = 64;
+
+/// Serve the Debug Adapter Protocol over stdin and stdout until the client disconnects.
+pub fn debug() -> Result<()> {
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let mut server = Server {
+        output: io::stdout(),
+        seq: 0,
+        debugger: None,
+    };
+    while let Some(request) = read_message(&mut input)? {
+        if !server.handle(request)? {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// The location of an opcode within the source.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Location {
+    path: PathBuf,
+    /// The one-based line.
+    line: usize,
+    /// The one-based column.
+    column: usize,
+}
+
+impl Location {
+    fn is_same_line(&self, other: &Location) -> bool {
+        self.path == other.path && self.line == other.line
+    }
+}
+
+/// A program compiled for debugging, along with the source location of its opcodes.
+struct Program {
+    name: String,
+    /// The ID of a contract, or `None` for the script.
+    contract_id: Option<ContractId>,
+    bytecode: Vec<u8>,
+    storage_slots: Vec<sway_core::StorageSlot>,
+    /// The location of each mapped opcode, by index.
+    locations: BTreeMap<usize, Location>,
+    /// The index of each opcode at which a line begins, i.e. the first opcode of a run of opcodes
+    /// on the same line.
+    line_starts: Vec<usize>,
+    /// The functions and variables of the program.
+    source_map: SourceMap,
+}
+
+impl Program {
+    /// The location of the opcode at the given index, or of the closest mapped opcode before it.
+    fn location(&self, index: usize) -> Option<&Location> {
+        self.locations
+            .range(..=index)
+            .next_back()
+            .map(|(_, location)| location)
+    }
+
+    /// The opcodes at which the given line of the file begins.
+    fn line_starts_of(&self, path: &Path, line: usize) -> Vec<usize> {
+        self.line_starts
+            .iter()
+            .copied()
+            .filter(|index| {
+                let location = &self.locations[index];
+                location.path == path && location.line == line
+            })
+            .collect()
+    }
+}
+
+/// A frame of the call stack: the script, or a call to a contract.
+struct Frame {
+    /// The index of the program being run, or `None` for a contract that was not compiled.
+    program: Option<usize>,
+    /// The index of the current opcode within the program.
+    index: usize,
+    /// The registers of the frame, which are saved within the call frame above it.
+    registers: Vec<Word>,
+}
+
+#[derive(Clone, Copy)]
+enum Step {
+    In,
+    Over,
+    Out,
+}
+
+/// How running the script most recently came to an end.
+enum Outcome {
+    /// The VM stopped at a breakpoint, for the given reason.
+    Stopped(&'static str),
+    /// The script ran to completion, with the given state and receipts.
+    Finished(ProgramState, Vec<Receipt>),
+    /// The VM was unable to run the script.
+    Failed(String),
+}
+
+/// The state of the VM while debugging a script and the contracts it calls.
+struct Debugger {
+    /// The script, followed by the contracts.
+    programs: Vec<Program>,
+    script_data: Vec<u8>,
+    interpreter: Interpreter<MemoryStorage>,
+    /// Whether to stop at the start of the script.
+    stop_on_entry: bool,
+    started: bool,
+    /// The opcodes at which breakpoints were requested, by program.
+    breakpoints: HashSet<(usize, usize)>,
+    /// The opcodes at which breakpoints were set for stepping, by program.
+    temporary: HashSet<(usize, usize)>,
+}
+
+impl Debugger {
+    fn new(programs: Vec<Program>, script_data: Vec<u8>, stop_on_entry: bool) -> Self {
+        Debugger {
+            programs,
+            script_data,
+            interpreter: Interpreter::with_storage(MemoryStorage::default()),
+            stop_on_entry,
+            started: false,
+            breakpoints: HashSet::new(),
+            temporary: HashSet::new(),
+        }
+    }
+
+    fn vm_breakpoint(&self, (program, index): (usize, usize)) -> Breakpoint {
+        let contract_id = self.programs[program].contract_id.unwrap_or_default();
+        Breakpoint::new(contract_id, index as Word)
+    }
+
+    /// Replace the breakpoints of the file with those on the given lines, returning the line of
+    /// each breakpoint that could be set.
+    ///
+    /// A breakpoint on a line without code is moved to the next line with code.
+    fn set_breakpoints(&mut self, path: &Path, lines: Vec<usize>) -> Vec<Option<usize>> {
+        let previous: Vec<_> = self
+            .breakpoints
+            .iter()
+            .copied()
+            .filter(|&(program, index)| self.programs[program].locations[&index].path == path)
+            .collect();
+        for breakpoint in previous {
+            self.breakpoints.remove(&breakpoint);
+            if !self.temporary.contains(&breakpoint) {
+                self.interpreter
+                    .remove_breakpoint(&self.vm_breakpoint(breakpoint));
+            }
+        }
+
+        let mut set_lines = vec![];
+        for line in lines {
+            let next_line = self
+                .programs
+                .iter()
+                .flat_map(|program| program.line_starts.iter().map(|ix| &program.locations[ix]))
+                .filter(|location| location.path == path && location.line >= line)
+                .map(|location| location.line)
+                .min();
+            if let Some(next_line) = next_line {
+                let breakpoints: Vec<_> = self
+                    .programs
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(ix, program)| {
+                        let indices = program.line_starts_of(path, next_line);
+                        indices.into_iter().map(move |index| (ix, index))
+                    })
+                    .collect();
+                for breakpoint in breakpoints {
+                    self.breakpoints.insert(breakpoint);
+                    self.interpreter
+                        .set_breakpoint(self.vm_breakpoint(breakpoint));
+                }
+            }
+            set_lines.push(next_line);
+        }
+        set_lines
+    }
+
+    /// The frames of the call stack, innermost first.
+    fn frames(&self) -> Vec<Frame> {
+        let memory = self.interpreter.memory();
+        let mut registers = self.interpreter.registers().to_vec();
+        let mut frames = vec![];
+        loop {
+            let fp = registers[REG_FP] as usize;
+            let program = if fp == 0 {
+                Some(0)
+            } else {
+                memory
+                    .get(fp..fp + ContractId::LEN)
+                    .and_then(|bytes| <[u8; ContractId::LEN]>::try_from(bytes).ok())
+                    .map(ContractId::new)
+                    .and_then(|id| {
+                        self.programs
+                            .iter()
+                            .position(|program| program.contract_id == Some(id))
+                    })
+            };
+            let index = registers[REG_PC].saturating_sub(registers[REG_IS]) as usize / Opcode::LEN;
+            frames.push(Frame {
+                program,
+                index,
+                registers: registers.clone(),
+            });
+            if fp == 0 {
+                break;
+            }
+            // The registers of the caller are saved within the call frame.
+            let saved = fp + CALL_FRAME_REGISTERS_OFFSET;
+            registers = match memory.get(saved..saved + VM_REGISTER_COUNT * 8) {
+                Some(bytes) => bytes
+                    .chunks(8)
+                    .map(|word| Word::from_be_bytes(word.try_into().expect("chunks of 8 bytes")))
+                    .collect(),
+                None => break,
+            };
+        }
+        frames
+    }
+
+    /// Run the script from the start, or resume it, until it stops or finishes.
+    fn run(&mut self) -> Outcome {
+        let state = if self.started {
+            self.interpreter.resume()
+        } else {
+            self.started = true;
+            if let Err(e) = self.deploy_contracts() {
+                return Outcome::Failed(e.to_string());
+            }
+            let tx = self.script_tx();
+            self.interpreter
+                .transact(tx)
+                .map(|transition| *transition.state())
+        };
+
+        let outcome = match state {
+            Ok(ProgramState::RunProgram(DebugEval::Breakpoint(_))) => {
+                let frame = &self.frames()[0];
+                let stopped_at = frame.program.map(|program| (program, frame.index));
+                match stopped_at {
+                    Some(stopped_at) if self.breakpoints.contains(&stopped_at) => {
+                        Outcome::Stopped("breakpoint")
+                    }
+                    _ => Outcome::Stopped("step"),
+                }
+            }
+            Ok(state) => Outcome::Finished(state, self.interpreter.receipts().to_vec()),
+            Err(e) => Outcome::Failed(e.to_string()),
+        };
+
+        for breakpoint in std::mem::take(&mut self.temporary) {
+            if !self.breakpoints.contains(&breakpoint) {
+                self.interpreter
+                    .remove_breakpoint(&self.vm_breakpoint(breakpoint));
+            }
+        }
+        outcome
+    }
+
+    /// Run until the start of another line.
+    ///
+    /// Stepping in may stop within any program, stepping over only within the current one, and
+    /// stepping out only within another one.
+    fn step(&mut self, step: Step) -> Outcome {
+        let frame = &self.frames()[0];
+        let current = frame.program;
+        let current_location = current
+            .and_then(|program| self.programs[program].location(frame.index))
+            .cloned();
+        let mut targets = vec![];
+        for (ix, program) in self.programs.iter().enumerate() {
+            let is_current = current == Some(ix);
+            let include = match step {
+                Step::In => true,
+                Step::Over => is_current,
+                Step::Out => !is_current,
+            };
+            if !include {
+                continue;
+            }
+            for &index in &program.line_starts {
+                let location = &program.locations[&index];
+                let is_current_line = is_current
+                    && current_location
+                        .as_ref()
+                        .map_or(false, |current| current.is_same_line(location));
+                if !is_current_line {
+                    targets.push((ix, index));
+                }
+            }
+        }
+        self.stop_at(targets);
+        self.run()
+    }
+
+    fn stop_at(&mut self, targets: Vec<(usize, usize)>) {
+        for target in targets {
+            if !self.breakpoints.contains(&target) {
+                self.interpreter.set_breakpoint(self.vm_breakpoint(target));
+            }
+            self.temporary.insert(target);
+        }
+    }
+
+    /// Deploy the contracts to the VM's storage, ready to be called by the script.
+    fn deploy_contracts(&mut self) -> Result<()> {
+        for program in &self.programs[1..] {
+            let (tx, _) = forc_deploy::create_contract_tx(
+                program.bytecode.clone(),
+                &program.storage_slots,
+                vec![],
+                vec![],
+            );
+            if let Err(e) = self.interpreter.transact(tx) {
+                bail!("failed to deploy contract {}: {}", program.name, e);
+            }
+        }
+        Ok(())
+    }
+
+    fn script_tx(&self) -> Transaction {
+        let gas_price = 0;
+        let gas_limit = fuel_tx::consts::MAX_GAS_PER_TX;
+        let byte_price = 0;
+        let maturity = 0;
+        let contract_ids: Vec<_> = self.programs[1..]
+            .iter()
+            .filter_map(|program| program.contract_id)
+            .collect();
+        let inputs = contract_ids
+            .iter()
+            .map(|contract_id| Input::Contract {
+                utxo_id: UtxoId::new(Bytes32::zeroed(), 0),
+                balance_root: Bytes32::zeroed(),
+                state_root: Bytes32::zeroed(),
+                contract_id: *contract_id,
+            })
+            .collect();
+        let outputs = (0..contract_ids.len())
+            .map(|ix| Output::Contract {
+                input_index: ix as u8,
+                balance_root: Bytes32::zeroed(),
+                state_root: Bytes32::zeroed(),
+            })
+            .collect();
+        let witnesses = vec![];
+        Transaction::script(
+            gas_price,
+            gas_limit,
+            byte_price,
+            maturity,
+            self.programs[0].bytecode.clone(),
+            self.script_data.clone(),
+            inputs,
+            outputs,
+            witnesses,
+        )
+    }
+}
+
+/// Compile the package at the given path for debugging, checking that it is of the expected
+/// program type.
+///
+/// As with the language server, the package's dependencies are loaded from its `Forc.lock` and
+/// never fetched, since anything printed would corrupt the messages sent over stdout.
+fn compile_program(path: &Path, expected: TreeType) -> Result<Program> {
+    let members = Members::find(path.to_str(), None)?;
+    let plan = BuildPlan::from_lock_file(&members)
+        .and_then(|plan| plan.validate(&members).map(|_| plan))
+        .map_err(|e| anyhow!("{}, try running `forc build`", e))?;
+    let selected = forc_build::selected_nodes(&plan, &members)?;
+    if selected.len() != 1 {
+        bail!(
+            "the workspace at {} has multiple members, select one by its path",
+            members.root_dir.display()
+        );
+    }
+    let pkg = &plan.graph[selected[0]];
+    if tree_type_str(&tree_type(&plan.path_map[&pkg.id()])?) != tree_type_str(&expected) {
+        bail!("{} is not a {}", pkg.name, tree_type_str(&expected));
+    }
+
+    let build_conf = pkg::BuildConf {
+        use_ir: false,
+        ir_opt_level: None,
+        time_ir_passes: false,
+        print_ir: false,
+        print_finalized_asm: false,
+        print_intermediate_asm: false,
+    };
+    let mut source_map = SourceMap::new();
+    let (name, output) =
+        forc_build::compile_selected(&plan, &selected, &build_conf, &mut source_map, true)?
+            .pop()
+            .expect("the selected member was compiled");
+    let package_dirs: Vec<_> = plan
+        .with_dependencies(&selected)
+        .into_iter()
+        .map(|node| plan.path_map[&plan.graph[node].id()].clone())
+        .collect();
+
+    let locations = locations(&source_map, &package_dirs);
+    let mut line_starts = vec![];
+    let mut previous: Option<&Location> = None;
+    for (&index, location) in &locations {
+        let is_line_start = match previous {
+            Some(previous) => {
+                !previous.is_same_line(location) || !locations.contains_key(&(index - 1))
+            }
+            None => true,
+        };
+        if is_line_start {
+            line_starts.push(index);
+        }
+        previous = Some(location);
+    }
+
+    let mut program = Program {
+        name,
+        contract_id: None,
+        bytecode: output.bytecode,
+        storage_slots: output.storage_slots,
+        locations,
+        line_starts,
+        source_map,
+    };
+    if let TreeType::Contract = expected {
+        let storage_slots = forc_deploy::contract_storage_slots(&program.storage_slots);
+        program.contract_id = Some(forc_deploy::contract_id(&program.bytecode, &storage_slots));
+    }
+    Ok(program)
+}
+
+/// The program type declared by the package's main file.
+fn tree_type(pkg_path: &Path) -> Result<TreeType> {
+    let manifest = read_manifest(pkg_path)?;
+    let main_file = get_main_file(&manifest, pkg_path)?;
+    parse(main_file, None)
+        .value
+        .map(|parse_tree| parse_tree.tree_type)
+        .ok_or_else(|| anyhow!("failed to parse {}", pkg_path.display()))
+}
+
+fn tree_type_str(tree_type: &TreeType) -> &'static str {
+    match tree_type {
+        TreeType::Script => "script",
+        TreeType::Contract => "contract",
+        TreeType::Predicate => "predicate",
+        TreeType::Library { .. } => "library",
+    }
+}
+
+/// The source location of each opcode in the source map.
+///
+/// Paths within the source map are relative to the parent of the manifest directory of the
+/// package containing the file.
+fn locations(source_map: &SourceMap, package_dirs: &[PathBuf]) -> BTreeMap<usize, Location> {
+    let mut sources: HashMap<PathBuf, Option<String>> = HashMap::new();
+    let mut locations = BTreeMap::new();
+    for (index, path, range) in source_map.spans() {
+        let path = if path.is_absolute() {
+            Some(path)
+        } else {
+            package_dirs.iter().find_map(|dir| {
+                let path = dir.parent()?.join(&path);
+                if path.starts_with(dir) && path.exists() {
+                    path.canonicalize().ok()
+                } else {
+                    None
+                }
+            })
+        };
+        let path = match path {
+            Some(path) => path,
+            None => continue,
+        };
+        let source = sources
+            .entry(path.clone())
+            .or_insert_with(|| fs::read_to_string(&path).ok());
+        let preceding = match source
+            .as_deref()
+            .and_then(|source| source.get(..range.start))
+        {
+            Some(preceding) => preceding,
+            None => continue,
+        };
+        let line_start = preceding.rfind('\n').map_or(0, |ix| ix + 1);
+        locations.insert(
+            index,
+            Location {
+                path,
+                line: preceding.matches('\n').count() + 1,
+                column: preceding[line_start..].chars().count() + 1,
+            },
+        );
+    }
+    locations
+}
+
+/// The variables held somewhere at the frame's current opcode, where inner ones shadow outer ones
+/// of the same name.
+fn locals(program: &Program, frame: &Frame, memory: &[u8]) -> Vec<Value> {
+    let mut locals: BTreeMap<&str, Value> = BTreeMap::new();
+    for variable in program.source_map.variables_at(frame.index) {
+        let local = match variable.location {
+            VariableLocation::Register(register) => {
+                let value = frame.registers[register as usize];
+                json!({
+                    "name": variable.name,
+                    "value": format!("{:#x}", value),
+                    "variablesReference": 0,
+                    // The register holds the address of values larger than a word.
+                    "memoryReference": format!("{:#x}", value),
+                })
+            }
+            VariableLocation::Memory { register, offset } => {
+                let address = frame.registers[register as usize] as usize + offset as usize;
+                let value = memory
+                    .get(address..address + 8)
+                    .and_then(|bytes| bytes.try_into().ok())
+                    .map(Word::from_be_bytes);
+                json!({
+                    "name": variable.name,
+                    "value": value.map_or_else(|| "<unreadable>".into(), |value| format!("{:#x}", value)),
+                    "variablesReference": 0,
+                    "memoryReference": format!("{:#x}", address),
+                })
+            }
+        };
+        locals.insert(&variable.name, local);
+    }
+    locals.into_values().collect()
+}
+
+/// Serves the requests of a single debugging session, writing messages to `output`.
+struct Server<W> {
+    output: W,
+    /// The sequence number of the last message sent.
+    seq: u64,
+    debugger: Option<Debugger>,
+}
+
+impl<W: Write> Server<W> {
+    /// Handle a request, returning whether or not to continue serving.
+    fn handle(&mut self, request: Value) -> Result<bool> {
+        let command = request["command"].as_str().unwrap_or_default().to_string();
+        let args = request.get("arguments").cloned().unwrap_or(Value::Null);
+        let result = match command.as_str() {
+            "initialize" => Ok(json!({
+                "supportsConfigurationDoneRequest": true,
+                "supportsReadMemoryRequest": true,
+            })),
+            "launch" => self.launch(&args),
+            "setBreakpoints" => self.set_breakpoints(&args),
+            "configurationDone" | "continue" | "next" | "stepIn" | "stepOut" => {
+                match self.debugger.is_some() {
+                    true => Ok(json!({ "allThreadsContinued": true })),
+                    false => Err(anyhow!("no program has been launched")),
+                }
+            }
+            "threads" => Ok(json!({ "threads": [{ "id": THREAD_ID, "name": "main" }] })),
+            "stackTrace" => self.stack_trace(),
+            "scopes" => {
+                // Frames are numbered from zero, while references must be positive.
+                let frame_id = args["frameId"].as_u64().unwrap_or_default();
+                Ok(json!({
+                    "scopes": [{
+                        "name": "Locals",
+                        "presentationHint": "locals",
+                        "variablesReference": frame_id * 2 + 1,
+                        "expensive": false,
+                    }, {
+                        "name": "Registers",
+                        "presentationHint": "registers",
+                        "variablesReference": frame_id * 2 + 2,
+                        "expensive": false,
+                    }],
+                }))
+            }
+            "variables" => self.variables(&args),
+            "readMemory" => self.read_memory(&args),
+            "disconnect" | "terminate" => {
+                self.respond(&request, Ok(Value::Null))?;
+                return Ok(false);
+            }
+            _ => Err(anyhow!("`{}` is not supported", command)),
+        };
+        let succeeded = result.is_ok();
+        self.respond(&request, result)?;
+        if !succeeded {
+            return Ok(true);
+        }
+
+        // Events follow the responses to the requests causing them.
+        let debugger = match self.debugger.as_mut() {
+            Some(debugger) => debugger,
+            None => return Ok(true),
+        };
+        let outcome = match command.as_str() {
+            "launch" => {
+                self.event("initialized", Value::Null)?;
+                return Ok(true);
+            }
+            "configurationDone" => {
+                if debugger.started {
+                    return Ok(true);
+                }
+                if debugger.stop_on_entry {
+                    debugger.stop_at(vec![(0, 0)]);
+                }
+                match debugger.run() {
+                    Outcome::Stopped(_) if debugger.stop_on_entry => Outcome::Stopped("entry"),
+                    outcome => outcome,
+                }
+            }
+            "continue" => debugger.run(),
+            "next" => debugger.step(Step::Over),
+            "stepIn" => debugger.step(Step::In),
+            "stepOut" => debugger.step(Step::Out),
+            _ => return Ok(true),
+        };
+        self.report(outcome)?;
+        Ok(true)
+    }
+
+    fn launch(&mut self, args: &Value) -> Result<Value> {
+        let script_path = match args["program"].as_str() {
+            Some(path) => PathBuf::from(path),
+            None => std::env::current_dir()?,
+        };
+        let mut programs = vec![compile_program(&script_path, TreeType::Script)?];
+        for path in args["contracts"].as_array().into_iter().flatten() {
+            let path = path
+                .as_str()
+                .ok_or_else(|| anyhow!("the paths of contracts must be strings"))?;
+            programs.push(compile_program(Path::new(path), TreeType::Contract)?);
+        }
+        let script_data = match args["scriptData"].as_str() {
+            Some(data) => hex::decode(data.strip_prefix("0x").unwrap_or(data))?,
+            None => vec![],
+        };
+        let stop_on_entry = args["stopOnEntry"].as_bool().unwrap_or(false);
+        self.debugger = Some(Debugger::new(programs, script_data, stop_on_entry));
+        Ok(Value::Null)
+    }
+
+    fn set_breakpoints(&mut self, args: &Value) -> Result<Value> {
+        let debugger = self
+            .debugger
+            .as_mut()
+            .ok_or_else(|| anyhow!("no program has been launched"))?;
+        let path = args["source"]["path"]
+            .as_str()
+            .ok_or_else(|| anyhow!("the source has no path"))?;
+        let path = Path::new(path).canonicalize()?;
+        let lines = args["breakpoints"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|breakpoint| breakpoint["line"].as_u64())
+            .map(|line| line as usize)
+            .collect();
+        let breakpoints: Vec<_> = debugger
+            .set_breakpoints(&path, lines)
+            .into_iter()
+            .map(|line| match line {
+                Some(line) => json!({ "verified": true, "line": line }),
+                None => json!({ "verified": false, "message": "No code on or after this line" }),
+            })
+            .collect();
+        Ok(json!({ "breakpoints": breakpoints }))
+    }
+
+    fn stack_trace(&self) -> Result<Value> {
+        let debugger = self
+            .debugger
+            .as_ref()
+            .ok_or_else(|| anyhow!("no program has been launched"))?;
+        let frames: Vec<_> = debugger
+            .frames()
+            .iter()
+            .enumerate()
+            .map(|(id, frame)| {
+                let program = frame.program.map(|program| &debugger.programs[program]);
+                let function = program.and_then(|program| {
+                    let scopes = program.source_map.scopes_at(frame.index);
+                    scopes.first().map(|scope| scope.name.clone())
+                });
+                let name = match (program, function) {
+                    (Some(program), Some(function)) => format!("{}::{}", program.name, function),
+                    (Some(program), None) => format!("{} @ {}", program.name, frame.index),
+                    (None, _) => format!("<unknown contract> @ {}", frame.index),
+                };
+                match program.and_then(|program| program.location(frame.index)) {
+                    Some(location) => json!({
+                        "id": id,
+                        "name": name,
+                        "source": {
+                            "name": location.path.file_name().map(|name| name.to_string_lossy()),
+                            "path": location.path,
+                        },
+                        "line": location.line,
+                        "column": location.column,
+                        "instructionPointerReference": format!("{:#x}", frame.registers[REG_PC]),
+                    }),
+                    None => json!({ "id": id, "name": name, "line": 0, "column": 0 }),
+                }
+            })
+            .collect();
+        Ok(json!({ "stackFrames": frames, "totalFrames": frames.len() }))
+    }
+
+    fn variables(&self, args: &Value) -> Result<Value> {
+        let debugger = self
+            .debugger
+            .as_ref()
+            .ok_or_else(|| anyhow!("no program has been launched"))?;
+        let reference = args["variablesReference"]
+            .as_u64()
+            .and_then(|reference| reference.checked_sub(1))
+            .ok_or_else(|| anyhow!("invalid variables reference"))?;
+        let frame_id = reference / 2;
+        let frames = debugger.frames();
+        let frame = frames
+            .get(frame_id as usize)
+            .ok_or_else(|| anyhow!("no frame with ID {}", frame_id))?;
+        if reference % 2 == 0 {
+            let program = frame.program.map(|program| &debugger.programs[program]);
+            let locals = match program {
+                Some(program) => locals(program, frame, debugger.interpreter.memory()),
+                None => vec![],
+            };
+            return Ok(json!({ "variables": locals }));
+        }
+        let variables: Vec<_> = frame
+            .registers
+            .iter()
+            .enumerate()
+            .map(|(ix, value)| {
+                let name = match REGISTER_NAMES.get(ix) {
+                    Some(name) => name.to_string(),
+                    None => format!("$r{}", ix),
+                };
+                let mut variable = json!({
+                    "name": name,
+                    "value": format!("{:#x}", value),
+                    "type": "u64",
+                    "variablesReference": 0,
+                });
+                if POINTER_REGISTERS.contains(&name.as_str()) {
+                    variable["memoryReference"] = json!(format!("{:#x}", value));
+                }
+                variable
+            })
+            .collect();
+        Ok(json!({ "variables": variables }))
+    }
+
+    fn read_memory(&self, args: &Value) -> Result<Value> {
+        let debugger = self
+            .debugger
+            .as_ref()
+            .ok_or_else(|| anyhow!("no program has been launched"))?;
+        let reference = args["memoryReference"]
+            .as_str()
+            .ok_or_else(|| anyhow!("missing memory reference"))?;
+        let address = match reference.strip_prefix("0x") {
+            Some(hex) => i64::from_str_radix(hex, 16)?,
+            None => reference.parse()?,
+        };
+        let start = (address + args["offset"].as_i64().unwrap_or(0)).max(0) as usize;
+        let count = args["count"].as_u64().unwrap_or(0) as usize;
+        let memory = debugger.interpreter.memory();
+        let end = (start + count).min(memory.len()).max(start);
+        let bytes = memory.get(start..end).unwrap_or_default();
+        Ok(json!({
+            "address": format!("{:#x}", start),
+            "data": base64::encode(bytes),
+            "unreadableBytes": count - bytes.len(),
+        }))
+    }
+
+    /// Tell the client where the script stopped or how it finished.
+    fn report(&mut self, outcome: Outcome) -> Result<()> {
+        let exit_code = match outcome {
+            Outcome::Stopped(reason) => {
+                return self.event(
+                    "stopped",
+                    json!({ "reason": reason, "threadId": THREAD_ID, "allThreadsStopped": true }),
+                );
+            }
+            Outcome::Finished(state, receipts) => {
+                for receipt in receipts {
+                    self.output(&format!("{:?}\n", receipt))?;
+                }
+                match state {
+                    ProgramState::Revert(code) => {
+                        self.output(&format!("The script reverted with {}\n", code))?;
+                        1
+                    }
+                    _ => 0,
+                }
+            }
+            Outcome::Failed(e) => {
+                self.output(&format!("The script failed to run: {}\n", e))?;
+                1
+            }
+        };
+        self.event("exited", json!({ "exitCode": exit_code }))?;
+        self.event("terminated", Value::Null)
+    }
+
+    fn output(&mut self, text: &str) -> Result<()> {
+        self.event("output", json!({ "category": "stdout", "output": text }))
+    }
+
+    fn respond(&mut self, request: &Value, result: Result<Value>) -> Result<()> {
+        let mut response = json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": result.is_ok(),
+        });
+        match result {
+            Ok(Value::Null) => (),
+            Ok(body) => response["body"] = body,
+            Err(e) => response["message"] = json!(e.to_string()),
+        }
+        self.send(response)
+    }
+
+    fn event(&mut self, event: &str, body: Value) -> Result<()> {
+        let mut message = json!({ "type": "event", "event": event });
+        if !body.is_null() {
+            message["body"] = body;
+        }
+        self.send(message)
+    }
+
+    fn send(&mut self, mut message: Value) -> Result<()> {
+        self.seq += 1;
+        message["seq"] = json!(self.seq);
+        let content = serde_json::to_string(&message)?;
+        write!(
+            self.output,
+            "Content-Length: {}\r\n\r\n{}",
+            content.len(),
+            content
+        )?;
+        self.output.flush()?;
+        Ok(())
+    }
+}
+
+/// Read a message preceded by its `Content-Length` header, or `None` at the end of the input.
+fn read_message(input: &mut impl BufRead) -> Result<Option<Value>> {
+    let mut content_length = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some(length) = line.strip_prefix("Content-Length:") {
+            content_length = Some(length.trim().parse::<usize>()?);
+        }
+    }
+    let content_length =
+        content_length.ok_or_else(|| anyhow!("message is missing its `Content-Length`"))?;
+    let mut content = vec![0; content_length];
+    input.read_exact(&mut content)?;
+    Ok(Some(serde_json::from_slice(&content)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCRIPT: &str = r#"script;
+
+fn main() -> u64 {
+    let start = asm(start) {
+        move start ggas;
+        start: u64
+    };
+    let used = asm(start: start, used) {
+        sub used start ggas;
+        used: u64
+    };
+    used
+}
+"#;
+
+    /// A script package without dependencies in the temporary directory, which is removed when
+    /// dropped.
+    struct ScriptPackage(PathBuf);
+
+    impl ScriptPackage {
+        fn new(name: &str) -> Self {
+            let dir =
+                std::env::temp_dir().join(format!("forc-debug-{}-{}", std::process::id(), name));
+            fs::create_dir_all(dir.join("src")).unwrap();
+            fs::write(
+                dir.join("Forc.toml"),
+                format!(
+                    "[project]\nauthors = [\"Fuel Labs\"]\nentry = \"main.sw\"\n\
+                     license = \"Apache-2.0\"\nname = \"{}\"\n\n[dependencies]\n",
+                    name
+                ),
+            )
+            .unwrap();
+            fs::write(
+                dir.join("Forc.lock"),
+                format!("[[package]]\nname = '{}'\ndependencies = []\n", name),
+            )
+            .unwrap();
+            fs::write(dir.join("src").join("main.sw"), SCRIPT).unwrap();
+            ScriptPackage(dir.canonicalize().unwrap())
+        }
+
+        fn main_path(&self) -> PathBuf {
+            self.0.join("src").join("main.sw")
+        }
+    }
+
+    impl Drop for ScriptPackage {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn frame(message: &Value) -> Vec<u8> {
+        let content = serde_json::to_string(message).unwrap();
+        format!("Content-Length: {}\r\n\r\n{}", content.len(), content).into_bytes()
+    }
+
+    /// Sends a request to the server, returning the messages it sent in response.
+    fn request(server: &mut Server<Vec<u8>>, command: &str, arguments: Value) -> Vec<Value> {
+        let request = json!({
+            "seq": server.seq + 1,
+            "type": "request",
+            "command": command,
+            "arguments": arguments,
+        });
+        assert!(server.handle(request).unwrap());
+        let output = std::mem::take(&mut server.output);
+        let mut input = io::BufReader::new(output.as_slice());
+        let mut messages = vec![];
+        while let Some(message) = read_message(&mut input).unwrap() {
+            messages.push(message);
+        }
+        messages
+    }
+
+    /// The event of the given type sent in response to a request.
+    fn event<'a>(messages: &'a [Value], event: &str) -> Option<&'a Value> {
+        messages.iter().find(|message| message["event"] == event)
+    }
+
+    fn stopped_line(server: &mut Server<Vec<u8>>, messages: &[Value], reason: &str) -> u64 {
+        let stopped = event(messages, "stopped").expect("the script stopped");
+        assert_eq!(stopped["body"]["reason"], reason);
+        let trace = request(server, "stackTrace", json!({ "threadId": THREAD_ID }));
+        let top = &trace[0]["body"]["stackFrames"][0];
+        assert_eq!(top["name"], "debug_stepping::main");
+        top["line"].as_u64().unwrap()
+    }
+
+    #[test]
+    fn messages_are_framed_by_their_length() {
+        let first = json!({ "seq": 1, "type": "request", "command": "initialize" });
+        let second = json!({ "seq": 2, "type": "request", "command": "threads", "text": "a\r\n" });
+        let mut input = frame(&first);
+        // other headers are ignored
+        input.extend(b"Content-Type: application/vscode-jsonrpc; charset=utf-8\r\n");
+        input.extend(frame(&second));
+        let mut input = io::BufReader::new(input.as_slice());
+        assert_eq!(read_message(&mut input).unwrap(), Some(first));
+        assert_eq!(read_message(&mut input).unwrap(), Some(second));
+        assert_eq!(read_message(&mut input).unwrap(), None);
+
+        let mut input = io::BufReader::new(&b"Content-Type: text\r\n\r\n{}"[..]);
+        assert!(read_message(&mut input).is_err());
+    }
+
+    #[test]
+    fn responses_are_framed_and_numbered() {
+        let mut server = Server {
+            output: vec![],
+            seq: 0,
+            debugger: None,
+        };
+        let messages = request(&mut server, "initialize", Value::Null);
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0]["type"], "response");
+        assert_eq!(messages[0]["command"], "initialize");
+        assert_eq!(messages[0]["success"], true);
+        assert_eq!(messages[0]["seq"], 1);
+        assert_eq!(messages[0]["body"]["supportsReadMemoryRequest"], true);
+
+        let messages = request(&mut server, "continue", Value::Null);
+        assert_eq!(messages[0]["success"], false);
+        assert_eq!(messages[0]["message"], "no program has been launched");
+        assert_eq!(messages[0]["seq"], 2);
+    }
+
+    #[test]
+    fn breakpoints_stepping_and_continuing() {
+        let package = ScriptPackage::new("debug_stepping");
+        let mut server = Server {
+            output: vec![],
+            seq: 0,
+            debugger: None,
+        };
+        request(&mut server, "initialize", Value::Null);
+        let messages = request(&mut server, "launch", json!({ "program": package.0 }));
+        assert_eq!(messages[0]["success"], true, "{}", messages[0]);
+        assert!(event(&messages, "initialized").is_some());
+
+        // a breakpoint on a line without code is moved to the next line with code, if any
+        let messages = request(
+            &mut server,
+            "setBreakpoints",
+            json!({
+                "source": { "path": package.main_path() },
+                "breakpoints": [{ "line": 8 }, { "line": 100 }],
+            }),
+        );
+        let breakpoints = &messages[0]["body"]["breakpoints"];
+        assert_eq!(breakpoints[0], json!({ "verified": true, "line": 9 }));
+        assert_eq!(breakpoints[1]["verified"], false);
+
+        let messages = request(&mut server, "configurationDone", Value::Null);
+        assert_eq!(stopped_line(&mut server, &messages, "breakpoint"), 9);
+
+        let messages = request(&mut server, "next", json!({ "threadId": THREAD_ID }));
+        // the return from `main` is attributed to its signature
+        assert_eq!(stopped_line(&mut server, &messages, "step"), 3);
+
+        let messages = request(&mut server, "continue", json!({ "threadId": THREAD_ID }));
+        let exited = event(&messages, "exited").expect("the script ran to completion");
+        assert_eq!(exited["body"]["exitCode"], 0);
+        assert!(event(&messages, "terminated").is_some());
+        assert!(messages.iter().any(|message| message["body"]["output"]
+            .as_str()
+            .map_or(false, |output| output.starts_with("Return"))));
+    }
+}
@@ -1,5 +1,5 @@
 use fuel_gql_client::client::FuelClient;
-use fuel_tx::{Output, Salt, Transaction};
+use fuel_tx::{Output, Salt, StorageSlot, Transaction};
 use fuel_vm::prelude::*;
 use sway_core::{parse, TreeType};
 
@@ -23,6 +23,8 @@ pub async fn deploy(command: DeployCommand) -> Result<fuel_tx::ContractId, CliEr
     let DeployCommand {
         path,
         use_ir,
+        ir_opt_level,
+        time_ir_passes,
         print_finalized_asm,
         print_intermediate_asm,
         print_ir,
@@ -47,7 +49,10 @@ pub async fn deploy(command: DeployCommand) -> Result<fuel_tx::ContractId, CliEr
                     TreeType::Contract => {
                         let build_command = BuildCommand {
                             path,
+                            package: None,
                             use_ir,
+                            ir_opt_level,
+                            time_ir_passes,
                             print_finalized_asm,
                             print_intermediate_asm,
                             print_ir,
@@ -62,9 +67,11 @@ pub async fn deploy(command: DeployCommand) -> Result<fuel_tx::ContractId, CliEr
                         let compiled = forc_build::build(build_command)?;
                         let (tx, contract_id) = create_contract_tx(
                             compiled.bytecode,
+                            &compiled.storage_slots,
                             Vec::<fuel_tx::Input>::new(),
                             Vec::<fuel_tx::Output>::new(),
                         );
+                        println!("Contract id: 0x{}", hex::encode(contract_id));
 
                         let node_url = match &manifest.network {
                             Some(network) => &network.url,
@@ -104,8 +111,11 @@ pub async fn deploy(command: DeployCommand) -> Result<fuel_tx::ContractId, CliEr
     }
 }
 
-fn create_contract_tx(
+/// The transaction creating a contract with the given bytecode and initial storage, along with
+/// the ID of the contract.
+pub(crate) fn create_contract_tx(
     compiled_contract: Vec<u8>,
+    storage_slots: &[sway_core::StorageSlot],
     inputs: Vec<Input>,
     outputs: Vec<Output>,
 ) -> (Transaction, fuel_tx::ContractId) {
@@ -114,17 +124,13 @@ fn create_contract_tx(
     let byte_price = 0;
     let maturity = 0;
     let bytecode_witness_index = 0;
-    let witnesses = vec![compiled_contract.clone().into()];
+    let storage_slots = contract_storage_slots(storage_slots);
+    let id = contract_id(&compiled_contract, &storage_slots);
+    let witnesses = vec![compiled_contract.into()];
 
     let salt = Salt::new([0; 32]);
     let static_contracts = vec![];
-    let storage_slots = vec![];
-
-    let contract = Contract::from(compiled_contract);
-    let root = contract.root();
-    let state_root = Contract::default_state_root();
-    let id = contract.id(&salt, &root, &state_root);
-    println!("Contract id: 0x{}", hex::encode(id));
+    let state_root = Contract::initial_state_root(&storage_slots);
     let outputs = [
         &[Output::ContractCreated {
             contract_id: id,
@@ -151,3 +157,24 @@ fn create_contract_tx(
         id,
     )
 }
+
+/// The initial storage of a contract, in the order required by the VM.
+pub(crate) fn contract_storage_slots(storage_slots: &[sway_core::StorageSlot]) -> Vec<StorageSlot> {
+    let mut storage_slots = storage_slots
+        .iter()
+        .map(|slot| StorageSlot::new(Bytes32::new(slot.key), Bytes32::new(slot.value)))
+        .collect::<Vec<_>>();
+    // The VM requires storage slots to be sorted by key.
+    storage_slots.sort_by(|a, b| a.key().cmp(b.key()));
+    storage_slots
+}
+
+/// The ID of a contract created with the given bytecode and initial storage, which must be
+/// sorted as by [contract_storage_slots].
+pub(crate) fn contract_id(compiled_contract: &[u8], storage_slots: &[StorageSlot]) -> ContractId {
+    let salt = Salt::new([0; 32]);
+    let contract = Contract::from(compiled_contract.to_vec());
+    let root = contract.root();
+    let state_root = Contract::initial_state_root(storage_slots);
+    contract.id(&salt, &root, &state_root)
+}
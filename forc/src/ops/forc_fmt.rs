@@ -1,17 +1,21 @@
 use crate::cli::{BuildCommand, FormatCommand};
 use crate::ops::forc_build;
 use crate::utils::helpers::{println_green, println_red};
+use crate::workspace::Members;
 use prettydiff::{basic::DiffOp, diff_lines};
 use std::default::Default;
 use std::{fmt, fs, io, path::Path, sync::Arc};
 use sway_fmt::{get_formatted_data, FormattingOptions};
-use sway_utils::{constants, find_manifest_dir, get_sway_files};
+use sway_utils::{constants, get_sway_files};
 use taplo::formatter as taplo_fmt;
 
 pub fn format(command: FormatCommand) -> Result<(), FormatError> {
-    let build_command = BuildCommand::default();
+    let build_command = BuildCommand {
+        package: command.package.clone(),
+        ..Default::default()
+    };
 
-    match forc_build::build(build_command) {
+    match forc_build::build_all(build_command) {
         // build is successful, continue to formatting
         Ok(_) => format_after_build(command),
 
@@ -21,72 +25,86 @@ pub fn format(command: FormatCommand) -> Result<(), FormatError> {
 }
 
 fn format_after_build(command: FormatCommand) -> Result<(), FormatError> {
-    let curr_dir = std::env::current_dir()?;
+    let members = Members::find(None, command.package.as_deref())?;
+    let mut contains_edits = false;
 
-    match find_manifest_dir(&curr_dir) {
-        Some(path) => {
-            let mut manifest_file = path.clone();
-            manifest_file.push(constants::MANIFEST_FILE_NAME);
-            let files = get_sway_files(path);
-            let mut contains_edits = false;
+    for name in &members.selected {
+        let dir = &members.dirs[name];
+        contains_edits |= format_sway_files(dir, command.check)?;
+        contains_edits |= format_manifest(&dir.join(constants::MANIFEST_FILE_NAME), command.check)?;
+    }
 
-            for file in files {
-                if let Ok(file_content) = fs::read_to_string(&file) {
-                    // todo read options from manifest file
-                    let formatting_options = FormattingOptions::default();
-                    let file_content: Arc<str> = Arc::from(file_content);
-                    match get_formatted_data(file_content.clone(), formatting_options) {
-                        Ok((_, formatted_content)) => {
-                            if command.check {
-                                if *file_content != *formatted_content {
-                                    contains_edits = true;
-                                    println!("\n{:?}\n", file);
-                                    display_file_diff(&file_content, &formatted_content)?;
-                                }
-                            } else {
-                                format_file(&file, &formatted_content)?;
-                            }
-                        }
-                        Err(err) => {
-                            // there could still be Sway files that are not part of the build
-                            eprintln!("\nThis file: {:?} is not part of the build", file);
-                            eprintln!("{}", err.join("\n"));
+    // The workspace manifest is formatted along with all of its members.
+    if members.is_workspace && members.selected.len() == members.dirs.len() {
+        let manifest_file = members.root_dir.join(constants::MANIFEST_FILE_NAME);
+        contains_edits |= format_manifest(&manifest_file, command.check)?;
+    }
+
+    if command.check && contains_edits {
+        // One or more files are not formatted, exit with error
+        Err("Files contain formatting violations.".into())
+    } else {
+        // All files are formatted, exit cleanly
+        Ok(())
+    }
+}
+
+/// Format all Sway files within the given package directory.
+///
+/// In `check` mode, prints the diff of each file that requires formatting rather than formatting
+/// it. Returns whether or not any file requires formatting.
+fn format_sway_files(dir: &Path, check: bool) -> Result<bool, FormatError> {
+    let mut contains_edits = false;
+    for file in get_sway_files(dir.to_path_buf()) {
+        if let Ok(file_content) = fs::read_to_string(&file) {
+            // todo read options from manifest file
+            let formatting_options = FormattingOptions::default();
+            let file_content: Arc<str> = Arc::from(file_content);
+            match get_formatted_data(file_content.clone(), formatting_options) {
+                Ok((_, formatted_content)) => {
+                    if check {
+                        if *file_content != *formatted_content {
+                            contains_edits = true;
+                            println!("\n{:?}\n", file);
+                            display_file_diff(&file_content, &formatted_content)?;
                         }
+                    } else {
+                        format_file(&file, &formatted_content)?;
                     }
                 }
-            }
-            // format manifest using taplo formatter
-            if let Ok(file_content) = fs::read_to_string(&manifest_file) {
-                let taplo_alphabetize = taplo_fmt::Options {
-                    reorder_keys: true,
-                    ..Default::default()
-                };
-                let formatted_content = taplo_fmt::format(&file_content, taplo_alphabetize);
-                if !command.check {
-                    format_file(&manifest_file, &formatted_content)?;
-                } else if formatted_content != file_content {
-                    contains_edits = true;
-                    eprintln!("\nManifest Forc.toml improperly formatted");
-                    display_file_diff(&file_content, &formatted_content)?;
-                } else {
-                    println!("\nManifest Forc.toml properly formatted")
+                Err(err) => {
+                    // there could still be Sway files that are not part of the build
+                    eprintln!("\nThis file: {:?} is not part of the build", file);
+                    eprintln!("{}", err.join("\n"));
                 }
             }
+        }
+    }
+    Ok(contains_edits)
+}
 
-            if command.check {
-                if contains_edits {
-                    // One or more files are not formatted, exit with error
-                    Err("Files contain formatting violations.".into())
-                } else {
-                    // All files are formatted, exit cleanly
-                    Ok(())
-                }
-            } else {
-                Ok(())
-            }
+/// Format the given manifest using the taplo formatter.
+///
+/// Returns whether or not the manifest requires formatting in `check` mode.
+fn format_manifest(manifest_file: &Path, check: bool) -> Result<bool, FormatError> {
+    let mut contains_edits = false;
+    if let Ok(file_content) = fs::read_to_string(manifest_file) {
+        let taplo_alphabetize = taplo_fmt::Options {
+            reorder_keys: true,
+            ..Default::default()
+        };
+        let formatted_content = taplo_fmt::format(&file_content, taplo_alphabetize);
+        if !check {
+            format_file(manifest_file, &formatted_content)?;
+        } else if formatted_content != file_content {
+            contains_edits = true;
+            eprintln!("\nManifest {:?} improperly formatted", manifest_file);
+            display_file_diff(&file_content, &formatted_content)?;
+        } else {
+            println!("\nManifest {:?} properly formatted", manifest_file)
         }
-        _ => Err("Manifest file does not exist".into()),
     }
+    Ok(contains_edits)
 }
 
 fn display_file_diff(file_content: &str, formatted_content: &str) -> Result<(), FormatError> {
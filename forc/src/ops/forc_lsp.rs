@@ -0,0 +1,78 @@
+use crate::{
+    pkg::BuildPlan,
+    utils::helpers::{find_main_path, read_manifest},
+    workspace::Members,
+};
+use anyhow::{anyhow, Result};
+use std::{collections::HashSet, path::Path};
+use sway_server::{Package, PackageResolver};
+
+/// Resolves the packages of the language server from the project's `Forc.lock`.
+///
+/// Dependencies are never fetched here, as anything printed would corrupt the messages the server
+/// sends over stdout.
+#[derive(Debug)]
+pub struct PkgResolver;
+
+impl PackageResolver for PkgResolver {
+    fn resolve(&self, path: &Path) -> Result<Vec<Package>, String> {
+        resolve(path).map_err(|e| format!("{}, try running `forc build`", e))
+    }
+}
+
+/// Start the language server, resolving packages with the project's build plan.
+pub async fn lsp() {
+    sway_server::start(Box::new(PkgResolver)).await
+}
+
+fn resolve(path: &Path) -> Result<Vec<Package>> {
+    let dir = path.parent().unwrap_or(path);
+    let members = Members::find(dir.to_str(), None)?;
+    let plan = BuildPlan::from_lock_file(&members)?;
+    plan.validate(&members)?;
+
+    // The member containing the file is the one with the deepest manifest directory.
+    let path = path.canonicalize()?;
+    let member = members
+        .dirs
+        .iter()
+        .filter_map(|(name, dir)| Some((name, dir.canonicalize().ok()?)))
+        .filter(|(_, dir)| path.starts_with(dir))
+        .max_by_key(|(_, dir)| dir.components().count())
+        .map(|(name, _)| name)
+        .ok_or_else(|| anyhow!("`{}` is not within a member package", path.display()))?;
+    let member_node = plan
+        .member_node(member)
+        .ok_or_else(|| anyhow!("member \"{}\" is missing from the lock file", member))?;
+
+    // Only the member and its dependencies are type checked, in order of compilation.
+    let nodes = plan.with_dependencies(&[member_node]);
+    let order: Vec<_> = plan
+        .compilation_order
+        .iter()
+        .copied()
+        .filter(|node| nodes.contains(node))
+        .collect();
+
+    let mut packages = Vec::with_capacity(order.len());
+    for &node in &order {
+        let pkg = &plan.graph[node];
+        let manifest_dir = plan.path_map[&pkg.id()].canonicalize()?;
+        let manifest = read_manifest(&manifest_dir)?;
+        let entry_path = find_main_path(&manifest_dir, &manifest);
+        let deps: HashSet<_> = plan.with_dependencies(&[node]);
+        let dependencies = order
+            .iter()
+            .enumerate()
+            .filter(|&(_, dep)| *dep != node && deps.contains(dep))
+            .map(|(ix, _)| ix)
+            .collect();
+        packages.push(Package {
+            name: pkg.name.clone(),
+            manifest_dir,
+            entry_path,
+            dependencies,
+        });
+    }
+    Ok(packages)
+}
@@ -39,7 +39,10 @@ pub async fn run(command: RunCommand) -> Result<(), CliError> {
 
                         let build_command = BuildCommand {
                             path: command.path,
+                            package: None,
                             use_ir: command.use_ir,
+                            ir_opt_level: command.ir_opt_level,
+                            time_ir_passes: command.time_ir_passes,
                             print_finalized_asm: command.print_finalized_asm,
                             print_intermediate_asm: command.print_intermediate_asm,
                             print_ir: command.print_ir,
@@ -0,0 +1,228 @@
+use crate::{
+    cli::TestCommand,
+    ops::forc_build,
+    pkg,
+    utils::helpers::{println_green, println_red},
+    workspace::Members,
+};
+use anyhow::Result;
+use fuel_tx::Transaction;
+use fuel_vm::interpreter::Interpreter;
+use fuel_vm::prelude::*;
+use sway_core::source_map::SourceMap;
+
+/// The outcome of running a single `#[test]` function.
+#[derive(Debug)]
+pub enum TestOutcome {
+    /// The test ran to completion without reverting.
+    Passed,
+    /// The test reverted with the given revert code.
+    Reverted(u64),
+    /// The VM was unable to execute the test.
+    Errored(String),
+}
+
+#[derive(Debug)]
+pub struct TestResult {
+    pub name: String,
+    pub outcome: TestOutcome,
+}
+
+impl TestResult {
+    pub fn passed(&self) -> bool {
+        matches!(self.outcome, TestOutcome::Passed)
+    }
+}
+
+/// The results of all tests that were run, along with the number of tests that were skipped due
+/// to the name filter.
+#[derive(Debug)]
+pub struct TestSummary {
+    pub results: Vec<TestResult>,
+    pub filtered_out: usize,
+}
+
+impl TestSummary {
+    pub fn passed(&self) -> usize {
+        self.results.iter().filter(|res| res.passed()).count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.results.len() - self.passed()
+    }
+}
+
+pub fn test(command: TestCommand) -> Result<TestSummary> {
+    let TestCommand {
+        test_name,
+        path,
+        package,
+        use_ir,
+        offline_mode: offline,
+        silent_mode: silent,
+        ..
+    } = command;
+
+    let build_conf = pkg::BuildConf {
+        use_ir,
+        ir_opt_level: None,
+        time_ir_passes: false,
+        print_ir: false,
+        print_finalized_asm: false,
+        print_intermediate_asm: false,
+    };
+
+    let members = Members::find(path.as_deref(), package.as_deref())?;
+    let plan = forc_build::load_build_plan(&members, offline)?;
+    let selected = forc_build::selected_nodes(&plan, &members)?;
+
+    // Compile all dependencies of the selected members as usual, and the tests of each selected
+    // member. A selected member may also be a dependency of another.
+    let required = plan.with_dependencies(&selected);
+    let mut namespace_map = Default::default();
+    let mut source_map = SourceMap::new();
+    let mut tests = vec![];
+    for &node in plan
+        .compilation_order
+        .iter()
+        .filter(|n| required.contains(n))
+    {
+        let pkg = &plan.graph[node];
+        let path = &plan.path_map[&pkg.id()];
+        if selected.contains(&node) {
+            let dep_namespace = pkg::dependency_namespace(
+                &namespace_map,
+                &plan.graph,
+                &plan.compilation_order,
+                node,
+            );
+            let member_tests = pkg::compile_tests(
+                pkg,
+                path,
+                &build_conf,
+                dep_namespace,
+                &mut source_map,
+                silent,
+            )?;
+            // Qualify test names by their member when testing more than one.
+            tests.extend(member_tests.into_iter().map(|mut test| {
+                if selected.len() > 1 {
+                    test.name = format!("{}::{}", pkg.name, test.name);
+                }
+                test
+            }));
+        }
+        // Packages depended upon by others are compiled as usual to produce their namespace.
+        let is_dependency = plan
+            .graph
+            .neighbors_directed(node, petgraph::Direction::Incoming)
+            .any(|dependent| required.contains(&dependent));
+        if is_dependency {
+            let dep_namespace = pkg::dependency_namespace(
+                &namespace_map,
+                &plan.graph,
+                &plan.compilation_order,
+                node,
+            );
+            let (_, maybe_namespace) = pkg::compile(
+                pkg,
+                path,
+                &build_conf,
+                dep_namespace,
+                &mut source_map,
+                silent,
+            )?;
+            if let Some(namespace) = maybe_namespace {
+                namespace_map.insert(node, namespace);
+            }
+            source_map.insert_dependency(path.clone());
+        }
+    }
+
+    // Only run the tests whose names contain the filter, if one was given.
+    let total_tests = tests.len();
+    let tests = tests
+        .into_iter()
+        .filter(|test| match test_name {
+            Some(ref filter) => test.name.contains(filter.as_str()),
+            None => true,
+        })
+        .collect::<Vec<_>>();
+    let filtered_out = total_tests - tests.len();
+
+    println!();
+    println!(
+        "running {} test{}",
+        tests.len(),
+        if tests.len() == 1 { "" } else { "s" }
+    );
+    let results = tests
+        .into_iter()
+        .map(|test| {
+            let outcome = run_test(test.bytecode);
+            match outcome {
+                TestOutcome::Passed => println!("test {} ... ok", test.name),
+                TestOutcome::Reverted(code) => {
+                    println!("test {} ... FAILED (reverted with {})", test.name, code)
+                }
+                TestOutcome::Errored(ref e) => println!("test {} ... FAILED ({})", test.name, e),
+            }
+            TestResult {
+                name: test.name,
+                outcome,
+            }
+        })
+        .collect();
+
+    let summary = TestSummary {
+        results,
+        filtered_out,
+    };
+    println!();
+    let counts = format!(
+        "{} passed; {} failed; {} filtered out",
+        summary.passed(),
+        summary.failed(),
+        summary.filtered_out
+    );
+    if summary.failed() == 0 {
+        println_green(&format!("test result: ok. {}", counts))?;
+    } else {
+        println_red(&format!("test result: FAILED. {}", counts))?;
+    }
+
+    Ok(summary)
+}
+
+/// Runs a compiled test as a script within a fresh, in-memory instance of the VM. No node is
+/// required.
+fn run_test(script: Vec<u8>) -> TestOutcome {
+    let gas_price = 0;
+    let gas_limit = fuel_tx::consts::MAX_GAS_PER_TX;
+    let byte_price = 0;
+    let maturity = 0;
+    let script_data = vec![];
+    let inputs = vec![];
+    let outputs = vec![];
+    let witnesses = vec![];
+    let tx = Transaction::script(
+        gas_price,
+        gas_limit,
+        byte_price,
+        maturity,
+        script,
+        script_data,
+        inputs,
+        outputs,
+        witnesses,
+    );
+
+    let mut interpreter = Interpreter::with_storage(MemoryStorage::default());
+    match interpreter.transact(tx) {
+        Ok(transition) => match *transition.state() {
+            ProgramState::Revert(code) => TestOutcome::Reverted(code),
+            _ => TestOutcome::Passed,
+        },
+        Err(e) => TestOutcome::Errored(e.to_string()),
+    }
+}
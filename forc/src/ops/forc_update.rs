@@ -1,12 +1,8 @@
 use crate::{
-    cli::UpdateCommand,
-    lock::Lock,
-    pkg,
-    utils::helpers::{lock_path, print_lock_diff, read_manifest},
+    cli::UpdateCommand, lock::Lock, pkg, utils::helpers::print_lock_diff, workspace::Members,
 };
-use anyhow::{anyhow, Result};
-use std::{fs, path::PathBuf};
-use sway_utils::find_manifest_dir;
+use anyhow::{anyhow, bail, Result};
+use std::fs;
 
 /// Running `forc update` will check for updates for the entire dependency graph and commit new
 /// semver-compatible versions to the `Forc.lock` file. For git dependencies, the commit is updated
@@ -25,33 +21,47 @@ pub async fn update(command: UpdateCommand) -> Result<()> {
     let UpdateCommand {
         path,
         check,
-        // TODO: Use `package` here rather than `target_dependency`
-        target_dependency: _,
-        ..
+        package,
     } = command;
 
-    let this_dir = match path {
-        Some(path) => PathBuf::from(path),
-        None => std::env::current_dir()?,
-    };
-    let manifest_dir = match find_manifest_dir(&this_dir) {
-        Some(dir) => dir,
-        None => {
-            return Err(anyhow!(
-                "No manifest file found in this directory or any parent directories of it: {:?}",
-                this_dir
-            ))
+    // All members of a workspace share a single lock file, so the whole workspace is updated.
+    let members = Members::find(path.as_deref(), None)?;
+    let lock_path = members.lock_path();
+    let old_plan = pkg::BuildPlan::from_lock_file(&members).ok();
+    let old_lock = old_plan
+        .as_ref()
+        .map(|plan| Lock::from_graph(&plan.graph, &plan.path_map))
+        .unwrap_or_default();
+
+    // When updating a single package, all other packages keep their pinned sources.
+    let locked = match &package {
+        Some(package) => {
+            let old_plan = match &old_plan {
+                Some(plan) => plan,
+                None => bail!(
+                    "cannot update package '{}': no valid lock file found at {}",
+                    package,
+                    lock_path.display()
+                ),
+            };
+            if !old_plan
+                .graph
+                .node_weights()
+                .any(|pkg| pkg.name == *package)
+            {
+                bail!("package '{}' is not a dependency of the project", package);
+            }
+            pkg::Locked::from_graph(&old_plan.graph, Some(package))
         }
+        None => pkg::Locked::default(),
     };
 
-    let manifest = read_manifest(&manifest_dir).map_err(|e| anyhow!("{}", e))?;
-    let lock_path = lock_path(&manifest_dir);
-    let old_lock = Lock::from_path(&lock_path).ok().unwrap_or_default();
     let offline = false;
-    let new_plan = pkg::BuildPlan::new(&manifest_dir, offline).map_err(|e| anyhow!("{}", e))?;
-    let new_lock = Lock::from_graph(&new_plan.graph);
+    let new_plan =
+        pkg::BuildPlan::with_locked(&members, &locked, offline).map_err(|e| anyhow!("{}", e))?;
+    let new_lock = Lock::from_graph(&new_plan.graph, &new_plan.path_map);
     let diff = new_lock.diff(&old_lock);
-    print_lock_diff(&manifest.project.name, &diff);
+    print_lock_diff(&members, &diff);
 
     // If we're not only `check`ing, write the updated lock file.
     if !check {
@@ -1,26 +1,30 @@
 use crate::{
     lock::Lock,
+    registry,
     utils::{
         dependency::Dependency,
         helpers::{
             find_file_name, find_main_path, get_main_file, git_checkouts_directory,
-            print_on_failure, print_on_success, print_on_success_library, read_manifest,
+            print_on_failure, print_on_success, print_on_success_library, println_yellow_err,
+            read_manifest,
         },
         manifest::Manifest,
     },
+    workspace::Members,
 };
 use anyhow::{anyhow, bail, Result};
 use petgraph::{self, visit::EdgeRef, Directed, Direction};
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::{hash_map, BTreeSet, HashMap, HashSet},
+    cmp::Ordering,
+    collections::{hash_map, BTreeMap, HashMap, HashSet},
     hash::{Hash, Hasher},
     path::{Path, PathBuf},
     str::FromStr,
 };
 use sway_core::{
     source_map::SourceMap, BuildConfig, BytecodeCompilationResult, CompileAstResult, NamespaceRef,
-    NamespaceWrapper, TreeType, TypedParseTree,
+    NamespaceWrapper, StorageSlot, TreeType, TypedParseTree,
 };
 use sway_types::JsonABI;
 use url::Url;
@@ -42,6 +46,13 @@ pub struct PinnedId(u64);
 pub struct Compiled {
     pub json_abi: JsonABI,
     pub bytecode: Vec<u8>,
+    pub storage_slots: Vec<StorageSlot>,
+}
+
+/// A single `#[test]` function, compiled to a standalone script.
+pub struct CompiledTest {
+    pub name: String,
+    pub bytecode: Vec<u8>,
 }
 
 /// A package uniquely identified by name along with its source.
@@ -73,7 +84,7 @@ pub enum Source {
     Git(SourceGit),
     /// A path to a directory with a `Forc.toml` manifest at its root.
     Path(PathBuf),
-    /// A forc project hosted on the official registry.
+    /// A forc project hosted on a package registry.
     Registry(SourceRegistry),
 }
 
@@ -86,11 +97,13 @@ pub struct SourceGit {
     pub reference: String,
 }
 
-/// A package from the official registry.
-#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Deserialize, Serialize)]
+/// A package from a registry.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 pub struct SourceRegistry {
-    /// The base version specified for the package.
-    pub version: semver::Version,
+    /// The URL of the registry's root directory.
+    pub registry: Url,
+    /// The version requirement specified for the package.
+    pub version: semver::VersionReq,
 }
 
 /// A pinned instance of a git source.
@@ -103,12 +116,17 @@ pub struct SourceGitPinned {
 }
 
 /// A pinned instance of the registry source.
+///
+/// The version requirement is not a part of the pinned source, so that all packages depending on
+/// the same registry package share a single node in the graph once their requirements are unified.
 #[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 pub struct SourceRegistryPinned {
-    /// The registry package with base version.
-    pub source: SourceRegistry,
+    /// The URL of the registry's root directory.
+    pub registry: Url,
     /// The pinned version.
     pub version: semver::Version,
+    /// The hex-encoded sha256 checksum of the pinned version's tarball.
+    pub checksum: String,
 }
 
 /// A pinned instance of the package source.
@@ -122,6 +140,36 @@ pub enum SourcePinned {
     Registry(SourceRegistryPinned),
 }
 
+/// The pinned sources of a previous build plan.
+///
+/// When creating a new build plan, locked sources are preferred over pinning afresh so that only
+/// those packages whose requirements have changed, or that are being explicitly updated, change.
+#[derive(Clone, Debug, Default)]
+pub struct Locked {
+    git: HashMap<(String, SourceGit), SourceGitPinned>,
+    registry: HashMap<(String, Url), semver::Version>,
+}
+
+/// A version requirement on a registry package, along with the path of packages through which it
+/// was required, starting from the project.
+struct Requirement {
+    version: semver::VersionReq,
+    dep_path: Vec<String>,
+}
+
+/// Fetches and pins the dependency graph, unifying the versions of registry packages.
+struct Fetcher {
+    offline_mode: bool,
+    /// Git sources pinned so far, so that each is only pinned once.
+    git_pins: HashMap<(String, SourceGit), SourceGitPinned>,
+    /// The indices of the registry packages fetched so far.
+    indices: HashMap<(String, Url), registry::Index>,
+    /// The version selected for each registry package.
+    selected: HashMap<(String, Url), semver::Version>,
+    /// The requirements on each registry package collected while building the graph.
+    requirements: BTreeMap<(String, Url), Vec<Requirement>>,
+}
+
 /// Represents the full build plan for a project.
 #[derive(Clone)]
 pub(crate) struct BuildPlan {
@@ -133,6 +181,8 @@ pub(crate) struct BuildPlan {
 // Parameters to pass through to the `BuildConfig` during compilation.
 pub(crate) struct BuildConf {
     pub(crate) use_ir: bool,
+    pub(crate) ir_opt_level: Option<String>,
+    pub(crate) time_ir_passes: bool,
     pub(crate) print_ir: bool,
     pub(crate) print_finalized_asm: bool,
     pub(crate) print_intermediate_asm: bool,
@@ -147,11 +197,25 @@ pub enum SourceGitPinnedParseError {
     CommitHash,
 }
 
+/// Error returned upon failed parsing of `SourceRegistryPinned::from_str`.
+#[derive(Clone, Debug)]
+pub enum SourceRegistryPinnedParseError {
+    Prefix,
+    Url,
+    Checksum,
+    Version,
+}
+
 impl BuildPlan {
-    /// Create a new build plan for the project by fetching and pinning dependenies.
-    pub fn new(manifest_dir: &Path, offline: bool) -> Result<Self> {
-        let manifest = read_manifest(manifest_dir)?;
-        let (graph, path_map) = fetch_deps(manifest_dir.to_path_buf(), &manifest, offline)?;
+    /// Create a new build plan for the members by fetching and pinning dependenies.
+    pub fn new(members: &Members, offline: bool) -> Result<Self> {
+        Self::with_locked(members, &Locked::default(), offline)
+    }
+
+    /// Create a new build plan for the members, reusing the pinned sources in `locked` wherever
+    /// they still satisfy the manifests.
+    pub fn with_locked(members: &Members, locked: &Locked, offline: bool) -> Result<Self> {
+        let (graph, path_map) = fetch_deps(members, offline, locked)?;
         let compilation_order = compilation_order(&graph)?;
         Ok(Self {
             graph,
@@ -161,10 +225,10 @@ impl BuildPlan {
     }
 
     /// Attempt to load the build plan from the `Lock`.
-    pub fn from_lock(proj_path: &Path, lock: &Lock) -> Result<Self> {
+    pub fn from_lock(members: &Members, lock: &Lock) -> Result<Self> {
         let graph = lock.to_graph()?;
         let compilation_order = compilation_order(&graph)?;
-        let path_map = graph_to_path_map(proj_path, &graph, &compilation_order)?;
+        let path_map = graph_to_path_map(members, &graph, &compilation_order)?;
         Ok(Self {
             graph,
             path_map,
@@ -172,62 +236,123 @@ impl BuildPlan {
         })
     }
 
-    /// Attempt to load the build plan from the `Forc.lock` file.
-    pub fn from_lock_file(lock_path: &Path) -> Result<Self> {
-        let proj_path = lock_path.parent().unwrap();
-        let lock = Lock::from_path(lock_path)?;
-        Self::from_lock(proj_path, &lock)
+    /// Attempt to load the build plan from the members' shared `Forc.lock` file.
+    pub fn from_lock_file(members: &Members) -> Result<Self> {
+        let lock = Lock::from_path(&members.lock_path())?;
+        Self::from_lock(members, &lock)
     }
 
-    /// Ensure that the build plan is valid for the given manifest.
-    pub fn validate(&self, manifest: &Manifest) -> Result<()> {
-        // Retrieve project's graph node.
-        let proj_node = *self
-            .compilation_order
-            .last()
-            .ok_or_else(|| anyhow!("Invalid Graph"))?;
+    /// The graph node of the member package with the given name.
+    pub fn member_node(&self, name: &str) -> Option<NodeIx> {
+        self.graph.node_indices().find(|&node| {
+            let pinned = &self.graph[node];
+            pinned.name == name && pinned.source == SourcePinned::Path
+        })
+    }
+
+    /// The given nodes along with all of their dependencies, direct and indirect.
+    pub fn with_dependencies(&self, nodes: &[NodeIx]) -> HashSet<NodeIx> {
+        use petgraph::visit::{Dfs, Walker};
+        nodes
+            .iter()
+            .flat_map(|&node| Dfs::new(&self.graph, node).iter(&self.graph))
+            .collect()
+    }
+
+    /// Ensure that the build plan is valid for the manifests of the given members.
+    pub fn validate(&self, members: &Members) -> Result<()> {
+        // Every package at the root of the graph must be a member.
+        for node in self.graph.externals(Direction::Incoming) {
+            let name = &self.graph[node].name;
+            if !members.dirs.contains_key(name) {
+                bail!("Package \"{}\" is no longer a member", name);
+            }
+        }
 
-        // Collect dependency `Source`s from graph.
-        let plan_dep_pkgs: BTreeSet<_> = self
+        for (name, dir) in &members.dirs {
+            let node = self
+                .member_node(name)
+                .ok_or_else(|| anyhow!("Member \"{}\" is missing from the lock file", name))?;
+            let manifest = read_manifest(dir)?;
+            self.validate_deps(node, &manifest)?;
+        }
+
+        Ok(())
+    }
+
+    /// Ensure that the dependencies of the given node match those in its manifest.
+    fn validate_deps(&self, node: NodeIx, manifest: &Manifest) -> Result<()> {
+        // Collect the pinned dependencies from the graph.
+        let plan_deps: Vec<&Pinned> = self
             .graph
-            .edges_directed(proj_node, Direction::Outgoing)
-            .map(|e| self.graph[e.target()].unpinned(&self.path_map))
+            .edges_directed(node, Direction::Outgoing)
+            .map(|e| &self.graph[e.target()])
             .collect();
 
-        // Collect dependency `Source`s from manifest.
-        let proj_id = self.graph[proj_node].id();
-        let proj_path = &self.path_map[&proj_id];
-        let manifest_dep_pkgs = manifest
+        // Ensure every dependency in the manifest is satisfied by one of the pinned dependencies.
+        let pkg_path = &self.path_map[&self.graph[node].id()];
+        let manifest_deps: Vec<_> = manifest
             .dependencies
-            .as_ref()
-            .into_iter()
+            .iter()
             .flat_map(|deps| deps.iter())
-            .map(|(name, dep)| {
-                // NOTE: Temporarily warn about `version` until we have support for registries.
-                if let Dependency::Detailed(det) = dep {
-                    if det.version.is_some() {
-                        crate::utils::helpers::println_yellow_err(&format!(
-                            "  WARNING! Dependency \"{}\" specifies the unused `version` field: \
-                            consider using `branch` or `tag` instead",
-                            name
-                        ))
-                        .unwrap();
-                    }
-                }
-
-                let name = name.clone();
-                let source = dep_to_source(proj_path, dep)?;
-                Ok(Pkg { name, source })
-            })
-            .collect::<Result<BTreeSet<_>>>()?;
-
-        // Ensure both `pkg::Source` are equal. If not, error.
-        if plan_dep_pkgs != manifest_dep_pkgs {
+            .collect();
+        if plan_deps.len() != manifest_deps.len() {
             bail!("Manifest dependencies do not match");
         }
+        for (name, dep) in manifest_deps {
+            let source = dep_to_source(pkg_path, dep)?;
+            let pinned = plan_deps
+                .iter()
+                .find(|pinned| pinned.name == *name && self.satisfies(pinned, &source))
+                .ok_or_else(|| anyhow!("Manifest dependency \"{}\" does not match", name))?;
+            if let Some(req) = dep_version_req(dep)? {
+                let dep_path = [manifest.project.name.clone()];
+                check_pkg_version(&self.path_map[&pinned.id()], name, &req, &dep_path)?;
+            }
+        }
 
         Ok(())
     }
+
+    /// Whether or not the given pinned package satisfies the given source.
+    fn satisfies(&self, pinned: &Pinned, source: &Source) -> bool {
+        match (&pinned.source, source) {
+            (SourcePinned::Git(git), Source::Git(source)) => git.source == *source,
+            (SourcePinned::Path, Source::Path(path)) => {
+                same_path(&self.path_map[&pinned.id()], path)
+            }
+            (SourcePinned::Registry(reg), Source::Registry(source)) => {
+                reg.registry == source.registry && source.version.matches(&reg.version)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Locked {
+    /// Collect the pinned sources of every package in the graph, except for the package named
+    /// `excluded` which will be pinned afresh.
+    pub fn from_graph(graph: &Graph, excluded: Option<&str>) -> Self {
+        let mut locked = Self::default();
+        for pinned in graph.node_weights() {
+            if Some(pinned.name.as_str()) == excluded {
+                continue;
+            }
+            let name = pinned.name.clone();
+            match &pinned.source {
+                SourcePinned::Git(git) => {
+                    locked.git.insert((name, git.source.clone()), git.clone());
+                }
+                SourcePinned::Registry(reg) => {
+                    locked
+                        .registry
+                        .insert((name, reg.registry.clone()), reg.version.clone());
+                }
+                SourcePinned::Path => (),
+            }
+        }
+        locked
+    }
 }
 
 impl Pinned {
@@ -237,18 +362,6 @@ impl Pinned {
     pub fn id(&self) -> PinnedId {
         PinnedId::new(&self.name, &self.source)
     }
-
-    /// Retrieve the unpinned version of this source.
-    pub fn unpinned(&self, path_map: &PathMap) -> Pkg {
-        let id = self.id();
-        let source = match &self.source {
-            SourcePinned::Git(git) => Source::Git(git.source.clone()),
-            SourcePinned::Path => Source::Path(path_map[&id].to_path_buf()),
-            SourcePinned::Registry(reg) => Source::Registry(reg.source.clone()),
-        };
-        let name = self.name.clone();
-        Pkg { name, source }
-    }
 }
 
 impl PinnedId {
@@ -261,6 +374,20 @@ impl PinnedId {
     }
 }
 
+impl PartialOrd for SourceRegistry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SourceRegistry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // `VersionReq` has no ordering of its own, so we compare its string representation.
+        (&self.registry, self.version.to_string())
+            .cmp(&(&other.registry, other.version.to_string()))
+    }
+}
+
 impl ToString for SourceGitPinned {
     fn to_string(&self) -> String {
         // git+<url/to/repo>?reference=<reference>#<commit>
@@ -317,6 +444,57 @@ impl FromStr for SourceGitPinned {
     }
 }
 
+impl ToString for SourceRegistryPinned {
+    fn to_string(&self) -> String {
+        // registry+<url/to/registry>?checksum=<checksum>#<version>
+        format!(
+            "registry+{}?checksum={}#{}",
+            self.registry, self.checksum, self.version,
+        )
+    }
+}
+
+impl FromStr for SourceRegistryPinned {
+    type Err = SourceRegistryPinnedParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // registry+<url/to/registry>?checksum=<checksum>#<version>
+        let s = s.trim();
+
+        // Check for "registry+" at the start.
+        const PREFIX: &str = "registry+";
+        if s.find(PREFIX) != Some(0) {
+            return Err(SourceRegistryPinnedParseError::Prefix);
+        }
+        let s = &s[PREFIX.len()..];
+
+        // Parse the `registry` URL.
+        let (registry_str, s) = s
+            .split_once('?')
+            .ok_or(SourceRegistryPinnedParseError::Url)?;
+        let registry = Url::parse(registry_str).map_err(|_| SourceRegistryPinnedParseError::Url)?;
+
+        // Parse the "checksum=" string.
+        const CHECKSUM: &str = "checksum=";
+        if s.find(CHECKSUM) != Some(0) {
+            return Err(SourceRegistryPinnedParseError::Checksum);
+        }
+        let s = &s[CHECKSUM.len()..];
+
+        // And now retrieve the `checksum` and pinned `version`.
+        let (checksum, version_str) = s
+            .split_once('#')
+            .ok_or(SourceRegistryPinnedParseError::Version)?;
+        let version = semver::Version::parse(version_str)
+            .map_err(|_| SourceRegistryPinnedParseError::Version)?;
+
+        Ok(Self {
+            registry,
+            version,
+            checksum: checksum.to_string(),
+        })
+    }
+}
+
 /// The `pkg::Graph` is of *a -> b* where *a* depends on *b*. We can determine compilation order by
 /// performing a toposort of the graph with reversed weights. The resulting order ensures all
 /// dependencies are always compiled before their dependents.
@@ -327,29 +505,23 @@ pub fn compilation_order(graph: &Graph) -> Result<Vec<NodeIx>> {
         .map_err(|e| anyhow!("dependency cycle detected: {:?}", e))
 }
 
-/// Given graph of pinned dependencies and the directory for the root node, produce a path map
-/// containing the path to the local source for every node in the graph.
+/// Given graph of pinned dependencies and the members at its roots, produce a path map containing
+/// the path to the local source for every node in the graph.
 pub fn graph_to_path_map(
-    proj_manifest_dir: &Path,
+    members: &Members,
     graph: &Graph,
     compilation_order: &[NodeIx],
 ) -> Result<PathMap> {
-    let mut path_map = PathMap::new();
-
-    // We resolve all paths in reverse compilation order.
-    // That is, we follow paths starting from the project root.
-    let mut path_resolve_order = compilation_order.iter().cloned().rev();
+    // Add the members' packages to the map.
+    let mut path_map = member_path_map(members);
 
-    // Add the project's package to the map.
-    let proj_node = path_resolve_order
-        .next()
-        .ok_or_else(|| anyhow!("graph must contain at least the project node"))?;
-    let proj_id = graph[proj_node].id();
-    path_map.insert(proj_id, proj_manifest_dir.to_path_buf());
-
-    // Resolve all following dependencies, knowing their parents' paths will already be resolved.
-    for dep_node in path_resolve_order {
+    // We resolve all remaining paths in reverse compilation order. That is, we follow paths
+    // starting from the members, knowing each package's parents will already be resolved.
+    for &dep_node in compilation_order.iter().rev() {
         let dep = &graph[dep_node];
+        if path_map.contains_key(&dep.id()) {
+            continue;
+        }
         let dep_path = match &dep.source {
             SourcePinned::Git(git) => {
                 git_commit_path(&dep.name, &git.source.repo, &git.commit_hash)
@@ -358,7 +530,7 @@ pub fn graph_to_path_map(
                 let parent_node = graph
                     .edges_directed(dep_node, Direction::Incoming)
                     .next()
-                    .ok_or_else(|| anyhow!("more than one root package detected in graph"))?
+                    .ok_or_else(|| anyhow!("package \"{}\" is not a member", dep.name))?
                     .source();
                 let parent = &graph[parent_node];
                 let parent_path = &path_map[&parent.id()];
@@ -377,9 +549,7 @@ pub fn graph_to_path_map(
                     .ok_or_else(|| anyhow!("missing path info for dependency: {}", dep.name))?;
                 parent_path.join(rel_dep_path)
             }
-            SourcePinned::Registry(_reg) => {
-                bail!("registry dependencies are not yet supported");
-            }
+            SourcePinned::Registry(reg) => registry::pkg_path(&dep.name, reg),
         };
         if !dep_path.exists() {
             match &dep.source {
@@ -390,8 +560,9 @@ pub fn graph_to_path_map(
                     println!("  Fetching {}", git.to_string());
                     fetch_git(&dep.name, git)?;
                 }
-                SourcePinned::Registry(_reg) => {
-                    bail!("registry dependencies are not yet supported");
+                SourcePinned::Registry(reg) => {
+                    println!("  Fetching {}", reg.to_string());
+                    registry::fetch(&dep.name, reg)?;
                 }
             }
         }
@@ -401,71 +572,343 @@ pub fn graph_to_path_map(
     Ok(path_map)
 }
 
+/// A path map containing the manifest directory of each member.
+fn member_path_map(members: &Members) -> PathMap {
+    members
+        .dirs
+        .iter()
+        .map(|(name, dir)| {
+            let name = name.clone();
+            let source = SourcePinned::Path;
+            (Pinned { name, source }.id(), dir.clone())
+        })
+        .collect()
+}
+
+/// Whether or not the two paths refer to the same location, resolving `..` and symbolic links
+/// where possible.
+fn same_path(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// The maximum number of times the graph is rebuilt while unifying registry package versions.
+const MAX_RESOLUTION_ROUNDS: usize = 16;
+
 /// Fetch all depedencies and produce the dependency graph along with a map from each node's unique
 /// ID to its local fetched path.
 ///
-/// This will determine pinned versions and commits for remote dependencies during traversal.
+/// This will determine pinned versions and commits for remote dependencies during traversal,
+/// preferring those in `locked` where they still satisfy the manifests.
 pub(crate) fn fetch_deps(
-    proj_manifest_dir: PathBuf,
-    proj_manifest: &Manifest,
+    members: &Members,
     offline_mode: bool,
+    locked: &Locked,
 ) -> Result<(Graph, PathMap)> {
-    let mut graph = Graph::new();
-    let mut path_map = PathMap::new();
+    let mut fetcher = Fetcher {
+        offline_mode,
+        git_pins: locked.git.clone(),
+        indices: HashMap::new(),
+        selected: locked.registry.clone(),
+        requirements: BTreeMap::new(),
+    };
+
+    // A registry package may be required by many packages within the graph, though only a single
+    // version may be selected. As a newly selected version may have different dependencies of its
+    // own, we rebuild the graph until the selected versions satisfy every requirement.
+    for _ in 0..MAX_RESOLUTION_ROUNDS {
+        fetcher.requirements.clear();
+        let (graph, path_map) = fetcher.fetch_graph(members)?;
+        if !fetcher.unify_versions(&graph)? {
+            return Ok((graph, path_map));
+        }
+    }
+    bail!(
+        "failed to resolve dependency versions after {} attempts",
+        MAX_RESOLUTION_ROUNDS
+    )
+}
+
+impl Fetcher {
+    /// Build the graph with the members as its root nodes, pinning dependencies as they are found.
+    fn fetch_graph(&mut self, members: &Members) -> Result<(Graph, PathMap)> {
+        let mut graph = Graph::new();
+        let mut path_map = member_path_map(members);
+        let mut visited = HashMap::new();
+
+        // Add each member to the graph, unless it has already been added as a path dependency of
+        // another member, and recursively fetch its children.
+        // TODO: Convert this recursion to use loop & stack to ensure deps can't cause stack overflow.
+        for name in members.dirs.keys() {
+            let name = name.clone();
+            let source = SourcePinned::Path;
+            let pkg = Pinned { name, source };
+            if visited.contains_key(&pkg) {
+                continue;
+            }
+            let dep_path = vec![pkg.name.clone()];
+            let node = graph.add_node(pkg.clone());
+            visited.insert(pkg, node);
+            self.fetch_children(node, &dep_path, &mut graph, &mut path_map, &mut visited)?;
+        }
+
+        Ok((graph, path_map))
+    }
+
+    /// Fetch children nodes of the given node and add unvisited nodes to the graph.
+    ///
+    /// The `dep_path` is the path of package names from the project to the given node.
+    fn fetch_children(
+        &mut self,
+        node: NodeIx,
+        dep_path: &[String],
+        graph: &mut Graph,
+        path_map: &mut PathMap,
+        visited: &mut HashMap<Pinned, NodeIx>,
+    ) -> Result<()> {
+        let parent = &graph[node];
+        let parent_path = path_map[&parent.id()].clone();
+        let manifest = read_manifest(&parent_path)?;
+        let deps = match &manifest.dependencies {
+            None => return Ok(()),
+            Some(deps) => deps,
+        };
+        for (name, dep) in deps {
+            let name = name.clone();
+            let source = dep_to_source(&parent_path, dep)?;
+            if self.offline_mode && !matches!(source, Source::Path(_)) {
+                bail!("Unable to fetch pkg {:?} in offline mode", source);
+            }
+            let pkg = Pkg { name, source };
+            let pinned = self.pin_pkg(&pkg, dep_path, path_map)?;
+            if let Some(req) = dep_version_req(dep)? {
+                check_pkg_version(&path_map[&pinned.id()], &pkg.name, &req, dep_path)?;
+            }
+            let dep_node = if let hash_map::Entry::Vacant(entry) = visited.entry(pinned.clone()) {
+                let node = graph.add_node(pinned);
+                entry.insert(node);
+                let mut child_dep_path = dep_path.to_vec();
+                child_dep_path.push(pkg.name);
+                self.fetch_children(node, &child_dep_path, graph, path_map, visited)?;
+                node
+            } else {
+                visited[&pinned]
+            };
+            graph.add_edge(node, dep_node, ());
+        }
+        Ok(())
+    }
 
-    // Add the project to the graph as the root node.
-    let name = proj_manifest.project.name.clone();
-    let path = proj_manifest_dir;
-    let source = SourcePinned::Path;
-    let pkg = Pinned { name, source };
-    path_map.insert(pkg.id(), path);
-    let root = graph.add_node(pkg);
+    /// Given a package source, attempt to determine the pinned version or commit.
+    ///
+    /// Also updates the `path_map` with a path to the local copy of the source.
+    fn pin_pkg(
+        &mut self,
+        pkg: &Pkg,
+        dep_path: &[String],
+        path_map: &mut PathMap,
+    ) -> Result<Pinned> {
+        let name = pkg.name.clone();
+        let pinned = match &pkg.source {
+            Source::Path(path) => {
+                let source = SourcePinned::Path;
+                let pinned = Pinned { name, source };
+                let id = pinned.id();
+                // Members are always located at their own manifest directory.
+                path_map.entry(id).or_insert_with(|| path.clone());
+                pinned
+            }
+            Source::Git(ref source) => {
+                let key = (name.clone(), source.clone());
+                let pinned_git = match self.git_pins.get(&key) {
+                    Some(pinned_git) => pinned_git.clone(),
+                    None => {
+                        let pinned_git = pin_git(&name, source.clone())?;
+                        self.git_pins.insert(key, pinned_git.clone());
+                        pinned_git
+                    }
+                };
+                let path = git_commit_path(&name, &pinned_git.source.repo, &pinned_git.commit_hash);
+                let source = SourcePinned::Git(pinned_git.clone());
+                let pinned = Pinned { name, source };
+                let id = pinned.id();
+                if let hash_map::Entry::Vacant(entry) = path_map.entry(id) {
+                    // TODO: Here we assume that if the local path already exists, that it contains the full and
+                    // correct source for that commit and hasn't been tampered with. This is probably fine for most
+                    // cases as users should never be touching these directories, however we should add some code
+                    // to validate this. E.g. can we recreate the git hash by hashing the directory or something
+                    // along these lines using git?
+                    if !path.exists() {
+                        println!("  Fetching {}", pinned_git.to_string());
+                        fetch_git(&pinned.name, &pinned_git)?;
+                    }
+                    entry.insert(path);
+                }
+                pinned
+            }
+            Source::Registry(ref source) => {
+                let pinned_reg = self.pin_registry(&name, source, dep_path)?;
+                let path = registry::pkg_path(&name, &pinned_reg);
+                let source = SourcePinned::Registry(pinned_reg.clone());
+                let pinned = Pinned { name, source };
+                let id = pinned.id();
+                if let hash_map::Entry::Vacant(entry) = path_map.entry(id) {
+                    // The checksum is verified when fetching, so an existing unpacked copy is trusted
+                    // in the same way as an existing git checkout.
+                    if !path.exists() {
+                        println!("  Fetching {}", pinned_reg.to_string());
+                        registry::fetch(&pinned.name, &pinned_reg)?;
+                    }
+                    entry.insert(path);
+                }
+                pinned
+            }
+        };
+        Ok(pinned)
+    }
 
-    // The set of visited packages, starting with the root.
-    let mut visited = HashMap::new();
-    visited.insert(graph[root].clone(), root);
+    /// Pin the given registry package, recording its requirement for unification.
+    ///
+    /// The currently selected version is used if it satisfies the requirement, otherwise the
+    /// greatest matching version is pinned until versions are unified after the graph is built.
+    fn pin_registry(
+        &mut self,
+        name: &str,
+        source: &SourceRegistry,
+        dep_path: &[String],
+    ) -> Result<SourceRegistryPinned> {
+        let key = (name.to_string(), source.registry.clone());
+        if let hash_map::Entry::Vacant(entry) = self.indices.entry(key.clone()) {
+            entry.insert(registry::fetch_index(name, &source.registry)?);
+        }
+        let index = &self.indices[&key];
+        let entry = self
+            .selected
+            .get(&key)
+            .filter(|version| source.version.matches(version))
+            .and_then(|version| index.get(version))
+            .or_else(|| index.select(&[&source.version]))
+            .ok_or_else(|| {
+                anyhow!(
+                    "no version of package '{}' in registry '{}' matches '{}' as required by {} \
+                     (available: {})",
+                    name,
+                    source.registry,
+                    source.version,
+                    dep_path.join(" -> "),
+                    index.available()
+                )
+            })?;
+        let pinned = SourceRegistryPinned {
+            registry: source.registry.clone(),
+            version: entry.version.clone(),
+            checksum: entry.checksum.clone(),
+        };
+        let requirement = Requirement {
+            version: source.version.clone(),
+            dep_path: dep_path.to_vec(),
+        };
+        self.requirements.entry(key).or_default().push(requirement);
+        Ok(pinned)
+    }
 
-    // Recursively fetch children and add them to the graph.
-    // TODO: Convert this recursion to use loop & stack to ensure deps can't cause stack overflow.
-    fetch_children(offline_mode, root, &mut graph, &mut path_map, &mut visited)?;
+    /// Select a single version of each registry package that satisfies all of its requirements.
+    ///
+    /// Previously selected versions are kept where possible, otherwise the greatest version
+    /// satisfying every requirement is selected. Returns whether or not the graph must be rebuilt
+    /// as a result of the selection.
+    fn unify_versions(&mut self, graph: &Graph) -> Result<bool> {
+        let mut changed = false;
+        for (key, reqs) in &self.requirements {
+            let (name, registry) = key;
+            let index = &self.indices[key];
+            let satisfies_all =
+                |version: &semver::Version| reqs.iter().all(|req| req.version.matches(version));
+            let version = match self
+                .selected
+                .get(key)
+                .filter(|version| satisfies_all(*version) && index.get(version).is_some())
+            {
+                Some(version) => version.clone(),
+                None => {
+                    let reqs_versions: Vec<_> = reqs.iter().map(|req| &req.version).collect();
+                    match index.select(&reqs_versions) {
+                        Some(entry) => entry.version.clone(),
+                        None => return Err(version_conflict_error(name, registry, reqs, index)),
+                    }
+                }
+            };
+            changed |= graph.node_weights().any(|pinned| match &pinned.source {
+                SourcePinned::Registry(reg) => {
+                    pinned.name == *name && reg.registry == *registry && reg.version != version
+                }
+                _ => false,
+            });
+            self.selected.insert(key.clone(), version);
+        }
+        Ok(changed)
+    }
+}
 
-    Ok((graph, path_map))
+/// Produce an error describing each of the requirements on a registry package for which no single
+/// version could be selected.
+fn version_conflict_error(
+    name: &str,
+    registry: &Url,
+    reqs: &[Requirement],
+    index: &registry::Index,
+) -> anyhow::Error {
+    let mut msg = format!(
+        "failed to select a version of package '{}' from registry '{}' that satisfies all \
+         requirements:",
+        name, registry
+    );
+    for req in reqs {
+        msg.push_str(&format!(
+            "\n  {} requires {} {}",
+            req.dep_path.join(" -> "),
+            name,
+            req.version
+        ));
+    }
+    msg.push_str(&format!("\navailable versions: {}", index.available()));
+    anyhow!(msg)
 }
 
-/// Fetch children nodes of the given node and add unvisited nodes to the graph.
-fn fetch_children(
-    offline_mode: bool,
-    node: NodeIx,
-    graph: &mut Graph,
-    path_map: &mut PathMap,
-    visited: &mut HashMap<Pinned, NodeIx>,
+/// Ensure that the `[project] version` of the package at `pkg_path` matches the requirement
+/// specified for it by the last package in `dep_path`. A package which doesn't declare a version
+/// can't be checked, so only a warning is printed for it.
+fn check_pkg_version(
+    pkg_path: &Path,
+    name: &str,
+    req: &semver::VersionReq,
+    dep_path: &[String],
 ) -> Result<()> {
-    let parent = &graph[node];
-    let parent_path = path_map[&parent.id()].clone();
-    let manifest = read_manifest(&parent_path)?;
-    let deps = match &manifest.dependencies {
-        None => return Ok(()),
-        Some(deps) => deps,
-    };
-    for (name, dep) in deps {
-        let name = name.clone();
-        let source = dep_to_source(&parent_path, dep)?;
-        if offline_mode && !matches!(source, Source::Path(_)) {
-            bail!("Unable to fetch pkg {:?} in offline mode", source);
+    let manifest = read_manifest(pkg_path)?;
+    match &manifest.project.version {
+        Some(version) if req.matches(version) => Ok(()),
+        Some(version) => bail!(
+            "{} requires {} {}, but the version of {} is {}",
+            dep_path.join(" -> "),
+            name,
+            req,
+            name,
+            version
+        ),
+        None => {
+            let _ = println_yellow_err(&format!(
+                "  Warning: {} requires {} {}, but {} does not specify a `version` in its \
+                manifest, so the requirement is not checked.",
+                dep_path.join(" -> "),
+                name,
+                req,
+                name,
+            ));
+            Ok(())
         }
-        let pkg = Pkg { name, source };
-        let pinned = pin_pkg(&pkg, path_map)?;
-        let dep_node = if let hash_map::Entry::Vacant(entry) = visited.entry(pinned.clone()) {
-            let node = graph.add_node(pinned);
-            entry.insert(node);
-            fetch_children(offline_mode, node, graph, path_map, visited)?;
-            node
-        } else {
-            visited[&pinned]
-        };
-        graph.add_edge(node, dep_node, ());
     }
-    Ok(())
 }
 
 /// The name to use for a package's git repository under the user's forc directory.
@@ -474,7 +917,7 @@ fn git_repo_dir_name(name: &str, repo: &Url) -> String {
     format!("{}-{:x}", name, repo_url_hash)
 }
 
-fn hash_url(url: &Url) -> u64 {
+pub(crate) fn hash_url(url: &Url) -> u64 {
     let mut hasher = hash_map::DefaultHasher::new();
     url.hash(&mut hasher);
     hasher.finish()
@@ -561,48 +1004,6 @@ fn pin_git(name: &str, source: SourceGit) -> Result<SourceGitPinned> {
     })
 }
 
-/// Given a package source, attempt to determine the pinned version or commit.
-///
-/// Also updates the `path_map` with a path to the local copy of the source.
-fn pin_pkg(pkg: &Pkg, path_map: &mut PathMap) -> Result<Pinned> {
-    let name = pkg.name.clone();
-    let pinned = match &pkg.source {
-        Source::Path(path) => {
-            let source = SourcePinned::Path;
-            let pinned = Pinned { name, source };
-            let id = pinned.id();
-            path_map.insert(id, path.clone());
-            pinned
-        }
-        Source::Git(ref source) => {
-            let pinned_git = pin_git(&name, source.clone())?;
-            let path = git_commit_path(&name, &pinned_git.source.repo, &pinned_git.commit_hash);
-            let source = SourcePinned::Git(pinned_git.clone());
-            let pinned = Pinned { name, source };
-            let id = pinned.id();
-            if let hash_map::Entry::Vacant(entry) = path_map.entry(id) {
-                // TODO: Here we assume that if the local path already exists, that it contains the full and
-                // correct source for that commit and hasn't been tampered with. This is probably fine for most
-                // cases as users should never be touching these directories, however we should add some code
-                // to validate this. E.g. can we recreate the git hash by hashing the directory or something
-                // along these lines using git?
-                if !path.exists() {
-                    println!("  Fetching {}", pinned_git.to_string());
-                    fetch_git(&pinned.name, &pinned_git)?;
-                }
-                entry.insert(path);
-            }
-            pinned
-        }
-        Source::Registry(ref _source) => {
-            // TODO: determine registry pkg git URL, fetch to determine latest available
-            // semver-compatible version
-            bail!("registry dependencies are not yet supported");
-        }
-    };
-    Ok(pinned)
-}
-
 /// The path to which a git package commit should be checked out.
 ///
 /// The resulting directory is:
@@ -650,7 +1051,7 @@ fn fetch_git(name: &str, pinned: &SourceGitPinned) -> Result<PathBuf> {
 /// produce the `Source` for that dependendency.
 fn dep_to_source(pkg_path: &Path, dep: &Dependency) -> Result<Source> {
     let source = match dep {
-        Dependency::Simple(ref _ver_str) => unimplemented!(),
+        Dependency::Simple(ref ver_str) => registry_source(pkg_path, ver_str, None)?,
         Dependency::Detailed(ref det) => {
             match (&det.path, &det.version, &det.git, &det.branch, &det.tag) {
                 (Some(relative_path), _, _, _, _) => {
@@ -668,6 +1069,9 @@ fn dep_to_source(pkg_path: &Path, dep: &Dependency) -> Result<Source> {
                     let source = SourceGit { repo, reference };
                     Source::Git(source)
                 }
+                (_, Some(version), _, _, _) => {
+                    registry_source(pkg_path, version, det.registry.as_deref())?
+                }
                 _ => {
                     bail!("unsupported set of arguments for dependency: {:?}", dep);
                 }
@@ -677,6 +1081,33 @@ fn dep_to_source(pkg_path: &Path, dep: &Dependency) -> Result<Source> {
     Ok(source)
 }
 
+/// Produce the `Source` for a dependency on the given version of a registry package.
+fn registry_source(pkg_path: &Path, version: &str, registry: Option<&str>) -> Result<Source> {
+    let registry = registry::registry_url(pkg_path, registry)?;
+    let version = parse_version_req(version)?;
+    Ok(Source::Registry(SourceRegistry { registry, version }))
+}
+
+/// The version requirement of a `path` or `git` dependency.
+///
+/// Unlike registry dependencies, for which the requirement is used to select a version, the
+/// requirement is checked against the `version` declared in the dependency's own manifest.
+fn dep_version_req(dep: &Dependency) -> Result<Option<semver::VersionReq>> {
+    match dep {
+        Dependency::Detailed(det) if det.path.is_some() || det.git.is_some() => {
+            det.version.as_deref().map(parse_version_req).transpose()
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Parse a version requirement, allowing a leading `v` as in a git tag, e.g. `v0.1.0`.
+fn parse_version_req(version: &str) -> Result<semver::VersionReq> {
+    let trimmed = version.trim();
+    semver::VersionReq::parse(trimmed.strip_prefix('v').unwrap_or(trimmed))
+        .map_err(|e| anyhow!("invalid version requirement \"{}\": {}", version, e))
+}
+
 pub(crate) fn build_config(
     path: PathBuf,
     manifest: &Manifest,
@@ -692,7 +1123,12 @@ pub(crate) fn build_config(
     .use_ir(build_conf.use_ir || build_conf.print_ir) // --print-ir implies --use-ir.
     .print_finalized_asm(build_conf.print_finalized_asm)
     .print_intermediate_asm(build_conf.print_intermediate_asm)
-    .print_ir(build_conf.print_ir);
+    .print_ir(build_conf.print_ir)
+    .time_ir_passes(build_conf.time_ir_passes);
+    let build_config = match &build_conf.ir_opt_level {
+        Some(level) => build_config.ir_pipeline(format!("O{}", level)),
+        None => build_config,
+    };
     Ok(build_config)
 }
 
@@ -765,6 +1201,7 @@ pub(crate) fn compile(
             warnings,
         } => {
             let json_abi = generate_json_abi(&*parse_tree);
+            let storage_slots = parse_tree.storage_slots();
             match tree_type {
                 // If we're compiling a library, we don't need to compile any further.
                 // Instead, we update the namespace with the library's top-level module.
@@ -772,7 +1209,11 @@ pub(crate) fn compile(
                     print_on_success_library(silent_mode, &pkg.name, warnings);
                     let bytecode = vec![];
                     let lib_namespace = parse_tree.clone().get_namespace_ref();
-                    let compiled = Compiled { json_abi, bytecode };
+                    let compiled = Compiled {
+                        json_abi,
+                        bytecode,
+                        storage_slots,
+                    };
                     Ok((compiled, Some(lib_namespace)))
                 }
 
@@ -785,7 +1226,11 @@ pub(crate) fn compile(
                         BytecodeCompilationResult::Success { bytes, warnings } => {
                             print_on_success(silent_mode, &pkg.name, &warnings, &tree_type);
                             let bytecode = bytes;
-                            let compiled = Compiled { json_abi, bytecode };
+                            let compiled = Compiled {
+                                json_abi,
+                                bytecode,
+                                storage_slots,
+                            };
                             Ok((compiled, None))
                         }
                         BytecodeCompilationResult::Library { .. } => {
@@ -802,6 +1247,49 @@ pub(crate) fn compile(
     }
 }
 
+/// Compiles each of the `#[test]` functions declared within the given package.
+///
+/// Every test is compiled to its own script, with the test function as the script's entry point.
+/// This allows for running each test in isolation, regardless of the package's program type.
+pub(crate) fn compile_tests(
+    pkg: &Pinned,
+    pkg_path: &Path,
+    build_conf: &BuildConf,
+    namespace: NamespaceRef,
+    source_map: &mut SourceMap,
+    silent_mode: bool,
+) -> Result<Vec<CompiledTest>> {
+    let manifest = read_manifest(pkg_path)?;
+    let source = get_main_file(&manifest, pkg_path)?;
+    let build_config = build_config(pkg_path.to_path_buf(), &manifest, build_conf)?;
+
+    let parse_tree = match sway_core::compile_to_ast(source, namespace, &build_config) {
+        CompileAstResult::Failure { warnings, errors } => {
+            print_on_failure(silent_mode, &warnings, &errors);
+            bail!("Failed to compile {}", pkg.name);
+        }
+        CompileAstResult::Success { parse_tree, .. } => parse_tree,
+    };
+
+    let mut tests = vec![];
+    for (name, asm_res) in sway_core::ast_to_test_asm(&parse_tree, &build_config) {
+        match sway_core::asm_to_bytecode(asm_res, source_map) {
+            BytecodeCompilationResult::Success { bytes, .. } => tests.push(CompiledTest {
+                name,
+                bytecode: bytes,
+            }),
+            BytecodeCompilationResult::Library { .. } => {
+                unreachable!("tests are always compiled as scripts")
+            }
+            BytecodeCompilationResult::Failure { errors, warnings } => {
+                print_on_failure(silent_mode, &warnings, &errors);
+                bail!("Failed to compile test `{}` in {}", name, pkg.name);
+            }
+        }
+    }
+    Ok(tests)
+}
+
 // TODO: Update this to match behaviour described in the `compile` doc comment above.
 fn generate_json_abi(ast: &TypedParseTree) -> JsonABI {
     match ast {
@@ -851,3 +1339,116 @@ fn test_source_git_pinned_parsing() {
         assert_eq!(&serialized, string);
     }
 }
+
+#[test]
+fn test_source_registry_pinned_parsing() {
+    let strings = [
+        "registry+https://example.com/registry/?checksum=ab12#0.1.4",
+        "registry+file:///home/user/registry/?checksum=cd34#1.2.0-rc.1",
+    ];
+
+    let expected = [
+        SourceRegistryPinned {
+            registry: Url::parse("https://example.com/registry/").unwrap(),
+            version: semver::Version::parse("0.1.4").unwrap(),
+            checksum: "ab12".to_string(),
+        },
+        SourceRegistryPinned {
+            registry: Url::parse("file:///home/user/registry/").unwrap(),
+            version: semver::Version::parse("1.2.0-rc.1").unwrap(),
+            checksum: "cd34".to_string(),
+        },
+    ];
+
+    for (&string, expected) in strings.iter().zip(&expected) {
+        let parsed = SourceRegistryPinned::from_str(string).unwrap();
+        assert_eq!(&parsed, expected);
+        let serialized = expected.to_string();
+        assert_eq!(&serialized, string);
+    }
+}
+
+#[test]
+fn test_version_conflict_error() {
+    let index: registry::Index = toml::de::from_str(
+        r#"
+        [[version]]
+        version = "1.0.0"
+        checksum = "a"
+
+        [[version]]
+        version = "2.0.0"
+        checksum = "b"
+        "#,
+    )
+    .unwrap();
+    let requirement = |version: &str, dep_path: &[&str]| Requirement {
+        version: semver::VersionReq::parse(version).unwrap(),
+        dep_path: dep_path.iter().map(|name| name.to_string()).collect(),
+    };
+    let reqs = [
+        requirement("^1.0", &["proj", "a"]),
+        requirement("^2.0", &["proj", "b", "c"]),
+    ];
+    let registry = Url::parse("https://example.com/registry/").unwrap();
+    let err = version_conflict_error("foo", &registry, &reqs, &index);
+    assert_eq!(
+        err.to_string(),
+        "failed to select a version of package 'foo' from registry \
+         'https://example.com/registry/' that satisfies all requirements:\n  \
+         proj -> a requires foo ^1.0\n  \
+         proj -> b -> c requires foo ^2.0\n\
+         available versions: 1.0.0, 2.0.0"
+    );
+}
+
+#[test]
+fn test_parse_version_req() {
+    for (version, expected) in [
+        ("0.1.0", "^0.1.0"),
+        ("v0.1.0", "^0.1.0"),
+        ("v0.0.1", "^0.0.1"),
+    ] {
+        let req = parse_version_req(version);
+        assert_eq!(
+            req.map(|req| req.to_string()).ok().as_deref(),
+            Some(expected)
+        );
+    }
+    assert!(parse_version_req("version one").is_err());
+}
+
+#[test]
+fn test_check_pkg_version() {
+    let pkg_path =
+        std::env::temp_dir().join(format!("forc-check-pkg-version-{}", std::process::id()));
+    std::fs::create_dir_all(&pkg_path).unwrap();
+    let write_manifest = |version: &str| {
+        let manifest = format!(
+            "[project]\nauthors = [\"Fuel Labs\"]\nentry = \"main.sw\"\nlicense = \"Apache-2.0\"\n\
+             name = \"foo\"\n{}",
+            version
+        );
+        std::fs::write(
+            pkg_path.join(sway_utils::constants::MANIFEST_FILE_NAME),
+            manifest,
+        )
+        .unwrap();
+    };
+    let req = parse_version_req("v0.1").unwrap();
+    let dep_path = ["proj".to_string()];
+
+    write_manifest("version = \"0.1.3\"\n");
+    assert!(check_pkg_version(&pkg_path, "foo", &req, &dep_path).is_ok());
+    write_manifest("version = \"0.2.0\"\n");
+    let err = check_pkg_version(&pkg_path, "foo", &req, &dep_path).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "proj requires foo ^0.1, but the version of foo is 0.2.0"
+    );
+    // a package without a version is not checked
+    write_manifest("");
+    assert!(check_pkg_version(&pkg_path, "foo", &req, &dep_path).is_ok());
+
+    std::fs::remove_dir_all(&pkg_path).unwrap();
+}
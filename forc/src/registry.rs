@@ -0,0 +1,242 @@
+//! Fetching packages from a forc package registry.
+//!
+//! A registry is a directory, either local (a `file://` URL or a plain path) or served by a
+//! simple HTTP server, with the following layout:
+//!
+//! ```ignore
+//! index/<name>.toml
+//! packages/<name>/<name>-<version>.tar.gz
+//! ```
+//!
+//! The index file of a package lists every published version along with the hex-encoded sha256
+//! checksum of its tarball:
+//!
+//! ```toml
+//! [[version]]
+//! version = "0.1.0"
+//! checksum = "<sha256>"
+//! ```
+//!
+//! Each tarball is gzipped and contains the package's `Forc.toml` at its root.
+
+use crate::{
+    pkg::{hash_url, SourceRegistryPinned},
+    utils::helpers::registry_cache_directory,
+};
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+};
+use url::Url;
+
+/// The environment variable specifying the registry used by dependencies without a `registry`.
+pub const DEFAULT_REGISTRY_ENV_VAR: &str = "FORC_REGISTRY";
+
+/// Every published version of a package.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub(crate) struct Index {
+    // Named `version` so that each entry serializes under `[[version]]`.
+    #[serde(default)]
+    pub(crate) version: Vec<IndexEntry>,
+}
+
+/// A single published version of a package.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub(crate) struct IndexEntry {
+    pub(crate) version: semver::Version,
+    /// The hex-encoded sha256 checksum of the package's tarball.
+    pub(crate) checksum: String,
+}
+
+impl Index {
+    /// The greatest version matching all of the given requirements.
+    pub(crate) fn select(&self, reqs: &[&semver::VersionReq]) -> Option<&IndexEntry> {
+        self.version
+            .iter()
+            .filter(|entry| reqs.iter().all(|req| req.matches(&entry.version)))
+            .max_by(|a, b| a.version.cmp(&b.version))
+    }
+
+    /// The entry for the given version, if it has been published.
+    pub(crate) fn get(&self, version: &semver::Version) -> Option<&IndexEntry> {
+        self.version.iter().find(|entry| entry.version == *version)
+    }
+
+    /// A comma-separated list of the published versions for use in error messages.
+    pub(crate) fn available(&self) -> String {
+        if self.version.is_empty() {
+            return "none".to_string();
+        }
+        self.version
+            .iter()
+            .map(|entry| entry.version.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Determine the registry for a dependency of the package at `pkg_path`.
+///
+/// This is the dependency's own `registry` if it has one, which may be a URL or a path relative
+/// to the package, or otherwise the registry given by the `FORC_REGISTRY` environment variable.
+pub(crate) fn registry_url(pkg_path: &Path, registry: Option<&str>) -> Result<Url> {
+    let registry = match registry {
+        Some(registry) => registry.to_string(),
+        None => std::env::var(DEFAULT_REGISTRY_ENV_VAR).map_err(|_| {
+            anyhow!(
+                "no registry specified: add a `registry` to the dependency or set the {} \
+                 environment variable",
+                DEFAULT_REGISTRY_ENV_VAR
+            )
+        })?,
+    };
+    let mut url = match Url::parse(&registry) {
+        Ok(url) => url,
+        Err(_) => {
+            let path = pkg_path.join(&registry);
+            let path = path
+                .canonicalize()
+                .map_err(|e| anyhow!("failed to find registry {}: {}", path.display(), e))?;
+            Url::from_directory_path(&path)
+                .map_err(|_| anyhow!("invalid registry path: {}", path.display()))?
+        }
+    };
+    // Ensure the registry is treated as a directory when joining paths onto it.
+    if !url.path().ends_with('/') {
+        let path = format!("{}/", url.path());
+        url.set_path(&path);
+    }
+    Ok(url)
+}
+
+/// The path to which a registry package version is unpacked.
+///
+/// The resulting directory is:
+///
+/// ```ignore
+/// $HOME/.forc/registry/cache/name-<registry_url_hash>/<version>
+/// ```
+pub(crate) fn pkg_path(name: &str, pinned: &SourceRegistryPinned) -> PathBuf {
+    let registry_dir_name = format!("{}-{:x}", name, hash_url(&pinned.registry));
+    registry_cache_directory()
+        .join(registry_dir_name)
+        .join(pinned.version.to_string())
+}
+
+/// Download the pinned package's tarball, verify its checksum and unpack it into the cache.
+///
+/// Returns the location of the unpacked package.
+pub(crate) fn fetch(name: &str, pinned: &SourceRegistryPinned) -> Result<PathBuf> {
+    let tarball_path = format!("packages/{}/{}-{}.tar.gz", name, name, pinned.version);
+    let bytes = read_registry_file(&pinned.registry, &tarball_path)?;
+    let checksum = hex::encode(Sha256::digest(&bytes));
+    if checksum != pinned.checksum {
+        bail!(
+            "checksum mismatch for package '{}' version {}: expected {}, found {}",
+            name,
+            pinned.version,
+            pinned.checksum,
+            checksum
+        );
+    }
+
+    // Unpack to a temporary directory first so that a failure never leaves a partial package.
+    let path = pkg_path(name, pinned);
+    let tmp_path = path.with_file_name(format!("{}.tmp", pinned.version));
+    if tmp_path.exists() {
+        let _ = fs::remove_dir_all(&tmp_path);
+    }
+    fs::create_dir_all(&tmp_path)?;
+    tar::Archive::new(flate2::read::GzDecoder::new(&bytes[..]))
+        .unpack(&tmp_path)
+        .map_err(|e| anyhow!("failed to unpack package '{}': {}", name, e))?;
+    if path.exists() {
+        let _ = fs::remove_dir_all(&path);
+    }
+    fs::rename(&tmp_path, &path)?;
+
+    Ok(path)
+}
+
+/// Fetch the index listing every published version of the named package.
+pub(crate) fn fetch_index(name: &str, registry: &Url) -> Result<Index> {
+    let bytes = read_registry_file(registry, &format!("index/{}.toml", name))?;
+    let string = String::from_utf8(bytes)
+        .map_err(|e| anyhow!("invalid index for package '{}': {}", name, e))?;
+    toml::de::from_str(&string).map_err(|e| anyhow!("invalid index for package '{}': {}", name, e))
+}
+
+/// Read the file at the given path relative to the registry root.
+fn read_registry_file(registry: &Url, rel_path: &str) -> Result<Vec<u8>> {
+    let url = registry.join(rel_path)?;
+    match url.scheme() {
+        "file" => {
+            let path = url
+                .to_file_path()
+                .map_err(|_| anyhow!("invalid registry file URL: {}", url))?;
+            fs::read(&path).map_err(|e| anyhow!("failed to read {}: {}", path.display(), e))
+        }
+        "http" | "https" => {
+            let response = ureq::get(url.as_str())
+                .call()
+                .map_err(|e| anyhow!("failed to fetch {}: {}", url, e))?;
+            let mut bytes = vec![];
+            response
+                .into_reader()
+                .read_to_end(&mut bytes)
+                .map_err(|e| anyhow!("failed to fetch {}: {}", url, e))?;
+            Ok(bytes)
+        }
+        scheme => bail!(
+            "unsupported registry URL scheme '{}' for registry {}",
+            scheme,
+            registry
+        ),
+    }
+}
+
+#[test]
+fn test_index_select() {
+    let index: Index = toml::de::from_str(
+        r#"
+        [[version]]
+        version = "0.1.0"
+        checksum = "a"
+
+        [[version]]
+        version = "0.2.1"
+        checksum = "b"
+
+        [[version]]
+        version = "0.2.3"
+        checksum = "c"
+
+        [[version]]
+        version = "1.0.0"
+        checksum = "d"
+        "#,
+    )
+    .unwrap();
+
+    let select = |reqs: &[&str]| {
+        let reqs: Vec<_> = reqs
+            .iter()
+            .map(|req| semver::VersionReq::parse(req).unwrap())
+            .collect();
+        let reqs: Vec<_> = reqs.iter().collect();
+        index.select(&reqs).map(|entry| entry.checksum.as_str())
+    };
+    assert_eq!(select(&["0.2"]), Some("c"));
+    assert_eq!(select(&["=0.2.1"]), Some("b"));
+    assert_eq!(select(&["0.1.0"]), Some("a"));
+    assert_eq!(select(&[">=0.1"]), Some("d"));
+    assert_eq!(select(&["2"]), None);
+    assert_eq!(select(&[">=0.1", "0.2"]), Some("c"));
+    assert_eq!(select(&["0.2", "<0.2.2"]), Some("b"));
+    assert_eq!(select(&["0.1", "0.2"]), None);
+    assert_eq!(index.available(), "0.1.0, 0.2.1, 0.2.3, 1.0.0");
+}
@@ -9,6 +9,7 @@ authors = ["{real_name}"]
 entry = "main.sw"
 license = "Apache-2.0"
 name = "{project_name}"
+version = "0.1.0"
 
 [dependencies]
 core = {{ git = "http://github.com/FuelLabs/sway-lib-core" }}
@@ -76,3 +77,17 @@ target
 "#
     .into()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{default_manifest, default_tests_manifest};
+
+    #[test]
+    fn default_manifests_are_valid_toml() {
+        let manifest: toml::Value = toml::from_str(&default_manifest("test_proj")).unwrap();
+        assert_eq!(manifest["project"]["version"].as_str(), Some("0.1.0"));
+        let tests_manifest: toml::Value =
+            toml::from_str(&default_tests_manifest("test_proj")).unwrap();
+        assert_eq!(tests_manifest["package"]["version"].as_str(), Some("0.1.0"));
+    }
+}
@@ -24,6 +24,8 @@ pub struct DependencyDetails {
     pub(crate) git: Option<String>,
     pub(crate) branch: Option<String>,
     pub(crate) tag: Option<String>,
+    /// The URL or local directory of the registry to fetch a `version` from, if not the default.
+    pub(crate) registry: Option<String>,
 }
 pub enum OfflineMode {
     Yes,
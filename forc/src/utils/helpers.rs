@@ -1,5 +1,5 @@
-use super::manifest::Manifest;
-use crate::utils::restricted_names;
+use super::manifest::{Manifest, WorkspaceManifest};
+use crate::{utils::restricted_names, workspace::Members};
 use annotate_snippets::{
     display_list::{DisplayList, FormatOptions},
     snippet::{Annotation, AnnotationType, Slice, Snippet, SourceAnnotation},
@@ -64,6 +64,31 @@ pub fn read_manifest(manifest_dir: &Path) -> Result<Manifest> {
     validate_manifest(manifest)
 }
 
+/// Read the workspace manifest in the given directory.
+///
+/// Returns `None` if there is no manifest in the directory, or if it declares a project rather than
+/// a workspace.
+pub fn read_workspace_manifest(manifest_dir: &Path) -> Result<Option<WorkspaceManifest>> {
+    let manifest_path = manifest_dir.join(constants::MANIFEST_FILE_NAME);
+    let manifest = match std::fs::read_to_string(&manifest_path) {
+        Ok(o) => o,
+        Err(_) => return Ok(None),
+    };
+    let value: toml::Value = toml::from_str(&manifest)
+        .map_err(|e| anyhow!("Error parsing manifest {:?}: {}.", manifest_path, e))?;
+    if value.get("workspace").is_none() {
+        return Ok(None);
+    }
+    let manifest = value.try_into().map_err(|e| {
+        anyhow!(
+            "Error parsing workspace manifest {:?}: {}.",
+            manifest_path,
+            e
+        )
+    })?;
+    Ok(Some(manifest))
+}
+
 // Using (https://github.com/rust-lang/cargo/blob/489b66f2e458404a10d7824194d3ded94bc1f4e4/src/cargo/util/toml/mod.rs +
 // https://github.com/rust-lang/cargo/blob/489b66f2e458404a10d7824194d3ded94bc1f4e4/src/cargo/ops/cargo_new.rs) for reference
 
@@ -147,6 +172,11 @@ pub fn git_checkouts_directory() -> PathBuf {
     user_forc_directory().join("git").join("checkouts")
 }
 
+/// The location at which `forc` will unpack packages fetched from registries.
+pub fn registry_cache_directory() -> PathBuf {
+    user_forc_directory().join("registry").join("cache")
+}
+
 pub fn print_on_success(
     silent_mode: bool,
     proj_name: &str,
@@ -218,28 +248,28 @@ pub fn print_on_failure(silent_mode: bool, warnings: &[CompileWarning], errors:
     .unwrap();
 }
 
-pub(crate) fn print_lock_diff(proj_name: &str, diff: &crate::lock::Diff) {
-    print_removed_pkgs(proj_name, diff.removed.iter().cloned());
-    print_added_pkgs(proj_name, diff.added.iter().cloned());
+pub(crate) fn print_lock_diff(members: &Members, diff: &crate::lock::Diff) {
+    print_removed_pkgs(members, diff.removed.iter().cloned());
+    print_added_pkgs(members, diff.added.iter().cloned());
 }
 
-pub(crate) fn print_removed_pkgs<'a, I>(proj_name: &str, removed: I)
+pub(crate) fn print_removed_pkgs<'a, I>(members: &Members, removed: I)
 where
     I: IntoIterator<Item = &'a crate::lock::PkgLock>,
 {
     for pkg in removed {
-        if pkg.name != proj_name {
+        if !members.dirs.contains_key(&pkg.name) {
             let _ = println_red(&format!("  Removing {}", pkg.unique_string()));
         }
     }
 }
 
-pub(crate) fn print_added_pkgs<'a, I>(proj_name: &str, removed: I)
+pub(crate) fn print_added_pkgs<'a, I>(members: &Members, removed: I)
 where
     I: IntoIterator<Item = &'a crate::lock::PkgLock>,
 {
     for pkg in removed {
-        if pkg.name != proj_name {
+        if !members.dirs.contains_key(&pkg.name) {
             let _ = println_green(&format!("    Adding {}", pkg.unique_string()));
         }
     }
@@ -1,6 +1,6 @@
 use crate::utils::dependency::Dependency;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, path::PathBuf};
 
 use sway_utils::constants::DEFAULT_NODE_URL;
 
@@ -24,12 +24,28 @@ pub struct Project {
     pub author: Option<String>,
     pub authors: Option<Vec<String>>,
     pub name: String,
+    pub version: Option<semver::Version>,
     pub organization: Option<String>,
     pub license: String,
     #[serde(default = "default_entry")]
     pub entry: String,
 }
 
+/// A manifest declaring a workspace, i.e. a `Forc.toml` with a `[workspace]` section in place of
+/// the `[project]` section.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct WorkspaceManifest {
+    pub workspace: Workspace,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct Workspace {
+    /// Paths to the member packages, relative to the workspace root.
+    pub members: Vec<PathBuf>,
+}
+
 fn default_entry() -> String {
     "main.sw".into()
 }
@@ -45,6 +61,21 @@ fn default_url() -> String {
     DEFAULT_NODE_URL.into()
 }
 
+#[test]
+fn try_parse_workspace() {
+    let manifest = toml::from_str::<WorkspaceManifest>(
+        r#"
+        [workspace]
+        members = ["contracts/token", "libs/math"]
+        "#,
+    )
+    .unwrap();
+    assert_eq!(
+        manifest.workspace.members,
+        vec![PathBuf::from("contracts/token"), PathBuf::from("libs/math")]
+    );
+}
+
 #[test]
 fn try_parse() {
     println!(
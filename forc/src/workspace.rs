@@ -0,0 +1,157 @@
+//! Determining the packages upon which a command operates.
+//!
+//! A `Forc.toml` may declare a workspace in place of a project:
+//!
+//! ```toml
+//! [workspace]
+//! members = ["contracts/token", "libs/math"]
+//! ```
+//!
+//! All members of a workspace share a single `Forc.lock` and `out` directory at the workspace
+//! root. A project that is not a member of any workspace is treated as the sole member of its own.
+
+use crate::utils::{
+    helpers::{lock_path, read_manifest, read_workspace_manifest},
+    manifest::WorkspaceManifest,
+};
+use anyhow::{anyhow, bail, Result};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+use sway_utils::{find_manifest_dir, MANIFEST_FILE_NAME};
+
+/// The packages upon which a command operates: either a single project, or the members of a
+/// workspace.
+#[derive(Clone, Debug)]
+pub(crate) struct Members {
+    /// The directory containing the shared `Forc.lock` and `out` directory.
+    pub(crate) root_dir: PathBuf,
+    /// Whether or not `root_dir` is a workspace root rather than a project.
+    pub(crate) is_workspace: bool,
+    /// The manifest directory of every member, keyed by package name.
+    pub(crate) dirs: BTreeMap<String, PathBuf>,
+    /// The names of the members selected by the command.
+    pub(crate) selected: Vec<String>,
+}
+
+impl Members {
+    /// Find the members for the project or workspace containing the given path, or the current
+    /// directory if no path is given.
+    ///
+    /// Within a workspace, `package` selects a single member. Otherwise every member is selected
+    /// from the workspace root, or only the enclosing member from within a member's directory.
+    pub(crate) fn find(path: Option<&str>, package: Option<&str>) -> Result<Self> {
+        let this_dir = match path {
+            Some(path) => PathBuf::from(path),
+            None => std::env::current_dir()?,
+        };
+        let manifest_dir = find_manifest_dir(&this_dir).ok_or_else(|| {
+            anyhow!(
+                "could not find `{}` in `{}` or any parent directory",
+                MANIFEST_FILE_NAME,
+                this_dir.display(),
+            )
+        })?;
+
+        // The manifest may declare a workspace itself.
+        if let Some(workspace) = read_workspace_manifest(&manifest_dir)? {
+            let dirs = member_dirs(&manifest_dir, &workspace)?;
+            let selected = match package {
+                Some(package) => vec![select(&manifest_dir, &dirs, package)?],
+                None => dirs.keys().cloned().collect(),
+            };
+            return Ok(Self {
+                root_dir: manifest_dir,
+                is_workspace: true,
+                dirs,
+                selected,
+            });
+        }
+
+        // Otherwise, check whether the project is a member of an enclosing workspace.
+        let name = read_manifest(&manifest_dir)?.project.name;
+        for ancestor in manifest_dir.ancestors().skip(1) {
+            let workspace = match read_workspace_manifest(ancestor) {
+                Ok(Some(workspace)) => workspace,
+                _ => continue,
+            };
+            let dirs = member_dirs(ancestor, &workspace)?;
+            if dirs.get(&name) != Some(&manifest_dir) {
+                continue;
+            }
+            let selected = match package {
+                Some(package) => vec![select(ancestor, &dirs, package)?],
+                None => vec![name],
+            };
+            return Ok(Self {
+                root_dir: ancestor.to_path_buf(),
+                is_workspace: true,
+                dirs,
+                selected,
+            });
+        }
+
+        // A standalone project.
+        if let Some(package) = package {
+            if package != name {
+                bail!(
+                    "package `{}` not found: `{}` is not a member of a workspace",
+                    package,
+                    manifest_dir.display()
+                );
+            }
+        }
+        let dirs = std::iter::once((name.clone(), manifest_dir.clone())).collect();
+        Ok(Self {
+            root_dir: manifest_dir,
+            is_workspace: false,
+            dirs,
+            selected: vec![name],
+        })
+    }
+
+    /// The path to the shared lock file.
+    pub(crate) fn lock_path(&self) -> PathBuf {
+        lock_path(&self.root_dir)
+    }
+}
+
+/// Collect the manifest directory of each member of the workspace at `root_dir`, keyed by name.
+fn member_dirs(
+    root_dir: &Path,
+    workspace: &WorkspaceManifest,
+) -> Result<BTreeMap<String, PathBuf>> {
+    let mut dirs = BTreeMap::new();
+    for member in &workspace.workspace.members {
+        let dir = root_dir.join(member);
+        let dir = dir.canonicalize().map_err(|e| {
+            anyhow!(
+                "failed to find workspace member at {}: {}",
+                dir.display(),
+                e
+            )
+        })?;
+        let name = read_manifest(&dir)?.project.name;
+        if let Some(other) = dirs.insert(name.clone(), dir) {
+            bail!(
+                "multiple workspace members are named `{}`, including the member at {}",
+                name,
+                other.display()
+            );
+        }
+    }
+    Ok(dirs)
+}
+
+/// Ensure the workspace has a member with the given name.
+fn select(root_dir: &Path, dirs: &BTreeMap<String, PathBuf>, package: &str) -> Result<String> {
+    if !dirs.contains_key(package) {
+        bail!(
+            "package `{}` is not a member of the workspace at {}",
+            package,
+            root_dir.display()
+        );
+    }
+    Ok(package.to_string())
+}
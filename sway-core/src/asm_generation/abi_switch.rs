@@ -0,0 +1,446 @@
+//! The contract switch statement, or function selector, which jumps to the ABI method whose
+//! selector matches the one the contract was called with.
+//!
+//! Small ABIs compare the input selector against the selector of each method in turn. The gas
+//! spent that way grows linearly with the number of methods, so larger ABIs instead binary search
+//! the selectors of their methods, sorted by value, down to a few methods which are compared in
+//! turn.
+
+use super::{DataSection, RegisterSequencer};
+use crate::{
+    asm_lang::{virtual_register::*, Label, Op, OrganizationalOp, VirtualImmediate12, VirtualOp},
+    parse_tree::Literal,
+};
+
+use either::Either;
+
+/// ABIs with more methods than this are dispatched with a binary search over their selectors.
+const MAX_LINEAR_SELECTORS: usize = 8;
+
+/// The binary search stops splitting the selectors once there are at most this many left to
+/// compare in turn.
+const MAX_SEARCHED_SELECTORS: usize = 4;
+
+/// Builds the contract switch statement, or function selector, which takes the selector
+/// stored in the call frame (see https://github.com/FuelLabs/sway/issues/97#issuecomment-870150684
+/// for an explanation of its location)
+pub(crate) fn build_contract_abi_switch(
+    register_sequencer: &mut RegisterSequencer,
+    data_section: &mut DataSection,
+    selectors_and_labels: Vec<([u8; 4], Label)>,
+) -> Vec<Op> {
+    let input_selector_register = register_sequencer.next();
+    let mut asm_buf = vec![Op {
+        opcode: Either::Right(OrganizationalOp::Comment),
+        comment: "Begin contract ABI selector switch".into(),
+        owning_span: None,
+    }];
+    // load the selector from the call frame
+    asm_buf.push(Op {
+        opcode: Either::Left(VirtualOp::LW(
+            input_selector_register.clone(),
+            VirtualRegister::Constant(ConstantRegister::FramePointer),
+            // see https://github.com/FuelLabs/fuel-specs/pull/193#issuecomment-876496372
+            // We expect the last four bytes of this word to contain the selector, and the first
+            // four bytes to all be 0.
+            VirtualImmediate12::new_unchecked(73, "constant infallible value"),
+        )),
+        comment: "load input function selector".into(),
+        owning_span: None,
+    });
+
+    asm_buf.append(&mut if selectors_and_labels.len() <= MAX_LINEAR_SELECTORS {
+        build_linear_dispatch(
+            register_sequencer,
+            data_section,
+            &input_selector_register,
+            selectors_and_labels,
+        )
+    } else {
+        build_binary_search_dispatch(
+            register_sequencer,
+            data_section,
+            &input_selector_register,
+            selectors_and_labels,
+        )
+    });
+
+    asm_buf
+}
+
+/// Jumps to the label of the selector in `input_selector_register` by comparing it against each
+/// selector in turn, reverting if none of them match.
+fn build_linear_dispatch(
+    register_sequencer: &mut RegisterSequencer,
+    data_section: &mut DataSection,
+    input_selector_register: &VirtualRegister,
+    selectors_and_labels: Vec<([u8; 4], Label)>,
+) -> Vec<Op> {
+    let mut asm_buf = compare_selectors(
+        register_sequencer,
+        data_section,
+        input_selector_register,
+        &selectors_and_labels,
+    );
+    asm_buf.push(revert_op());
+    asm_buf
+}
+
+/// Jumps to the label of the selector in `input_selector_register` by binary searching the
+/// selectors sorted by value, reverting if none of them match.
+fn build_binary_search_dispatch(
+    register_sequencer: &mut RegisterSequencer,
+    data_section: &mut DataSection,
+    input_selector_register: &VirtualRegister,
+    mut selectors_and_labels: Vec<([u8; 4], Label)>,
+) -> Vec<Op> {
+    // the selector is loaded as a big-endian word, so the selectors must be ordered by the
+    // value of that word
+    selectors_and_labels.sort_by_key(|(selector, _)| u32::from_be_bytes(*selector));
+    let revert_label = register_sequencer.get_label();
+    let mut asm_buf = search_selectors(
+        register_sequencer,
+        data_section,
+        input_selector_register,
+        &selectors_and_labels,
+        &revert_label,
+    );
+    asm_buf.push(Op::unowned_jump_label(revert_label));
+    asm_buf.push(revert_op());
+    asm_buf
+}
+
+/// Narrows the sorted selectors down to the half which could hold the input selector, until few
+/// enough are left to compare in turn. Jumps to `revert_label` if none of them match.
+fn search_selectors(
+    register_sequencer: &mut RegisterSequencer,
+    data_section: &mut DataSection,
+    input_selector_register: &VirtualRegister,
+    selectors_and_labels: &[([u8; 4], Label)],
+    revert_label: &Label,
+) -> Vec<Op> {
+    if selectors_and_labels.len() <= MAX_SEARCHED_SELECTORS {
+        let mut asm_buf = compare_selectors(
+            register_sequencer,
+            data_section,
+            input_selector_register,
+            selectors_and_labels,
+        );
+        asm_buf.push(Op::jump_to_label_comment(
+            revert_label.clone(),
+            "no selectors matched",
+        ));
+        return asm_buf;
+    }
+
+    let (lower, upper) = selectors_and_labels.split_at(selectors_and_labels.len() / 2);
+    let lower_label = register_sequencer.get_label();
+    let data_label = data_section.insert_data_value(&Literal::U32(u32::from_be_bytes(upper[0].0)));
+    let pivot_register = register_sequencer.next();
+    let comparison_result_register = register_sequencer.next();
+    let mut asm_buf = vec![
+        Op {
+            opcode: Either::Left(VirtualOp::LWDataId(pivot_register.clone(), data_label)),
+            comment: "load fn selector to search from".into(),
+            owning_span: None,
+        },
+        Op {
+            opcode: Either::Left(VirtualOp::LT(
+                comparison_result_register.clone(),
+                input_selector_register.clone(),
+                pivot_register,
+            )),
+            comment: "function selector search comparison".into(),
+            owning_span: None,
+        },
+        Op {
+            // if the input selector is less than the pivot, it can only be in the lower half
+            opcode: Either::Right(OrganizationalOp::JumpIfNotEq(
+                VirtualRegister::Constant(ConstantRegister::Zero),
+                comparison_result_register,
+                lower_label.clone(),
+            )),
+            comment: "search lower fn selectors".into(),
+            owning_span: None,
+        },
+    ];
+    asm_buf.append(&mut search_selectors(
+        register_sequencer,
+        data_section,
+        input_selector_register,
+        upper,
+        revert_label,
+    ));
+    asm_buf.push(Op::unowned_jump_label(lower_label));
+    asm_buf.append(&mut search_selectors(
+        register_sequencer,
+        data_section,
+        input_selector_register,
+        lower,
+        revert_label,
+    ));
+    asm_buf
+}
+
+/// Compares the input selector against each selector in turn, jumping to the label of the one
+/// which is equal, or falling through if none are.
+fn compare_selectors(
+    register_sequencer: &mut RegisterSequencer,
+    data_section: &mut DataSection,
+    input_selector_register: &VirtualRegister,
+    selectors_and_labels: &[([u8; 4], Label)],
+) -> Vec<Op> {
+    let mut asm_buf = vec![];
+    for (selector, label) in selectors_and_labels {
+        // put the selector in the data section
+        let data_label =
+            data_section.insert_data_value(&Literal::U32(u32::from_be_bytes(*selector)));
+        // load the data into a register for comparison
+        let prog_selector_register = register_sequencer.next();
+        asm_buf.push(Op {
+            opcode: Either::Left(VirtualOp::LWDataId(
+                prog_selector_register.clone(),
+                data_label,
+            )),
+            comment: "load fn selector for comparison".into(),
+            owning_span: None,
+        });
+        // compare with the input selector
+        let comparison_result_register = register_sequencer.next();
+        asm_buf.push(Op {
+            opcode: Either::Left(VirtualOp::EQ(
+                comparison_result_register.clone(),
+                input_selector_register.clone(),
+                prog_selector_register,
+            )),
+            comment: "function selector comparison".into(),
+            owning_span: None,
+        });
+
+        // jump to the function label if the selector was equal
+        asm_buf.push(Op {
+            // if the comparison result is _not_ equal to 0, then it was indeed equal.
+            opcode: Either::Right(OrganizationalOp::JumpIfNotEq(
+                VirtualRegister::Constant(ConstantRegister::Zero),
+                comparison_result_register,
+                label.clone(),
+            )),
+            comment: "jump to selected function".into(),
+            owning_span: None,
+        });
+    }
+    asm_buf
+}
+
+/// If none of the selectors matched, then revert.
+fn revert_op() -> Op {
+    Op {
+        // see https://github.com/FuelLabs/sway/issues/97#issuecomment-875674105
+        // and https://github.com/FuelLabs/sway/issues/444#issuecomment-1012507337
+        opcode: Either::Left(VirtualOp::RVRT(VirtualRegister::Constant(
+            ConstantRegister::Zero,
+        ))),
+        comment: "revert if no selectors matched".into(),
+        owning_span: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        asm_generation::{build_preamble, AbstractInstructionSet, SwayAsmSet},
+        source_map::SourceMap,
+    };
+
+    use fuel_tx::Transaction;
+    use fuel_vm::{
+        interpreter::Interpreter,
+        prelude::{MemoryStorage, ProgramState},
+    };
+    use sha2::{Digest, Sha256};
+
+    type Dispatch = fn(
+        &mut RegisterSequencer,
+        &mut DataSection,
+        &VirtualRegister,
+        Vec<([u8; 4], Label)>,
+    ) -> Vec<Op>;
+
+    fn selectors(count: usize) -> Vec<[u8; 4]> {
+        (0..count)
+            .map(|ix| {
+                let hash = Sha256::digest(format!("method_{}()", ix).as_bytes());
+                [hash[0], hash[1], hash[2], hash[3]]
+            })
+            .collect()
+    }
+
+    /// Runs a script which dispatches `input_selector` to one of `selectors`, returning the index
+    /// of the selector jumped to along with the gas spent getting there, or the state the script
+    /// ended with if it didn't return.
+    fn dispatch(
+        build_dispatch: Dispatch,
+        selectors: &[[u8; 4]],
+        input_selector: [u8; 4],
+    ) -> Result<(usize, u64), ProgramState> {
+        let mut register_sequencer = RegisterSequencer::new();
+        let mut data_section = DataSection::default();
+        let mut ops = build_preamble(&mut register_sequencer).to_vec();
+
+        let gas_register = register_sequencer.next();
+        ops.push(Op::unowned_register_move(
+            gas_register.clone(),
+            VirtualRegister::Constant(ConstantRegister::GlobalGas),
+        ));
+        let input_selector_register = register_sequencer.next();
+        let data_label =
+            data_section.insert_data_value(&Literal::U32(u32::from_be_bytes(input_selector)));
+        ops.push(Op::unowned_load_data_comment(
+            input_selector_register.clone(),
+            data_label,
+            "",
+        ));
+
+        let labels = selectors
+            .iter()
+            .map(|_| register_sequencer.get_label())
+            .collect::<Vec<_>>();
+        ops.append(&mut build_dispatch(
+            &mut register_sequencer,
+            &mut data_section,
+            &input_selector_register,
+            selectors
+                .iter()
+                .copied()
+                .zip(labels.iter().cloned())
+                .collect(),
+        ));
+
+        // each method returns its index in the upper half of the word and the gas spent in the
+        // lower half
+        for (ix, label) in labels.into_iter().enumerate() {
+            let gas_spent_register = register_sequencer.next();
+            let index_register = register_sequencer.next();
+            let result_register = register_sequencer.next();
+            ops.append(&mut vec![
+                Op::unowned_jump_label(label),
+                Op::unowned_new_with_comment(
+                    VirtualOp::SUB(
+                        gas_spent_register.clone(),
+                        gas_register.clone(),
+                        VirtualRegister::Constant(ConstantRegister::GlobalGas),
+                    ),
+                    "",
+                ),
+                Op::unowned_new_with_comment(
+                    VirtualOp::ADDI(
+                        index_register.clone(),
+                        VirtualRegister::Constant(ConstantRegister::Zero),
+                        VirtualImmediate12::new_unchecked(ix as u64, "small index"),
+                    ),
+                    "",
+                ),
+                Op::unowned_new_with_comment(
+                    VirtualOp::SLLI(
+                        index_register.clone(),
+                        index_register.clone(),
+                        VirtualImmediate12::new_unchecked(32, "constant infallible value"),
+                    ),
+                    "",
+                ),
+                Op::unowned_new_with_comment(
+                    VirtualOp::ADD(result_register.clone(), gas_spent_register, index_register),
+                    "",
+                ),
+                Op::unowned_new_with_comment(VirtualOp::RET(result_register), ""),
+            ]);
+        }
+
+        let mut source_map = SourceMap::new();
+        let script = SwayAsmSet::ScriptMain {
+            data_section,
+            program_section: AbstractInstructionSet { ops },
+        }
+        .remove_unnecessary_jumps()
+        .allocate_registers(&mut register_sequencer)
+        .optimize()
+        .0
+        .to_bytecode_mut(&mut source_map)
+        .value
+        .unwrap();
+
+        let tx = Transaction::script(
+            10,
+            fuel_tx::consts::MAX_GAS_PER_TX,
+            0,
+            0,
+            script,
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+        );
+        tx.validate((u32::MAX >> 1) as u64).unwrap();
+        let mut interpreter = Interpreter::with_storage(MemoryStorage::default());
+        match *interpreter.transact(tx).unwrap().state() {
+            ProgramState::Return(result) => Ok(((result >> 32) as usize, result & 0xffff_ffff)),
+            state => Err(state),
+        }
+    }
+
+    /// Dispatches every selector, checking the right one is jumped to and that an unknown
+    /// selector reverts, returning the worst case and total gas spent dispatching.
+    fn measure_gas(build_dispatch: Dispatch, selectors: &[[u8; 4]]) -> (u64, u64) {
+        let mut worst_gas = 0;
+        let mut total_gas = 0;
+        for (ix, selector) in selectors.iter().enumerate() {
+            let (selected, gas) = dispatch(build_dispatch, selectors, *selector).unwrap();
+            assert_eq!(selected, ix);
+            worst_gas = worst_gas.max(gas);
+            total_gas += gas;
+        }
+        assert!(matches!(
+            dispatch(build_dispatch, selectors, [0xff; 4]),
+            Err(ProgramState::Revert(0))
+        ));
+        (worst_gas, total_gas)
+    }
+
+    #[test]
+    fn binary_search_dispatch_selects_every_method() {
+        for count in [1, 2, 5, 9, 17] {
+            measure_gas(build_binary_search_dispatch, &selectors(count));
+        }
+    }
+
+    #[test]
+    fn binary_search_dispatch_spends_less_gas_on_large_abis() {
+        let mut linear_worst_gas = vec![];
+        let mut search_worst_gas = vec![];
+        for count in [16, 32, 64] {
+            let selectors = selectors(count);
+            let (linear_worst, linear_total) = measure_gas(build_linear_dispatch, &selectors);
+            let (search_worst, search_total) =
+                measure_gas(build_binary_search_dispatch, &selectors);
+            assert!(search_worst < linear_worst);
+            assert!(search_total < linear_total);
+            linear_worst_gas.push(linear_worst);
+            search_worst_gas.push(search_worst);
+        }
+
+        // doubling the methods doubles the comparisons made by linear dispatch at worst, but only
+        // adds a level to the binary search
+        let linear_growth = [
+            linear_worst_gas[1] - linear_worst_gas[0],
+            linear_worst_gas[2] - linear_worst_gas[1],
+        ];
+        assert_eq!(linear_growth[1], 2 * linear_growth[0]);
+        let search_growth = [
+            search_worst_gas[1] - search_worst_gas[0],
+            search_worst_gas[2] - search_worst_gas[1],
+        ];
+        assert_eq!(search_growth[0], search_growth[1]);
+        assert!(search_growth[0] < linear_growth[0]);
+    }
+}
@@ -10,7 +10,13 @@ pub(crate) const SIX_BITS: u64 = 0b11_1111;
 /// reservation must be made.
 /// So far, the compiler-reserved registers are:
 /// 1. DATA_SECTION_BEGIN
-const NUM_COMPILER_RESERVED_REGISTERS: u8 = 1;
-pub(crate) const DATA_SECTION_REGISTER: u8 = NUM_FREE_REGISTERS - 2;
+/// 2. CALL_FRAME
+const NUM_COMPILER_RESERVED_REGISTERS: u8 = 2;
+/// The VM register holding the start of the data section. This is the last of the free registers,
+/// which follow the 16 reserved by the VM, so that it's never handed out by the allocator.
+pub(crate) const DATA_SECTION_REGISTER: u8 = 16 + NUM_FREE_REGISTERS - 1;
+/// The VM register holding the address of the registers saved by the innermost recursive call,
+/// the free register before the data section register.
+pub(crate) const CALL_FRAME_REGISTER: u8 = 16 + NUM_FREE_REGISTERS - 2;
 pub(crate) const NUM_ALLOCATABLE_REGISTERS: u8 =
     NUM_FREE_REGISTERS - NUM_COMPILER_RESERVED_REGISTERS;
@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+
+use sway_types::span::Span;
+
+use super::{register_allocator::SpillArea, RegisterPool};
+use crate::{
+    asm_lang::{allocated_ops::AllocatedRegister, DebugMarker, RealizedOp, VirtualRegister},
+    source_map::{PcRange, SourceMap, VariableLocation},
+};
+
+/// Function scopes and variable locations of an [InstructionSet](super::InstructionSet), in terms
+/// of the indices of its ops.
+#[derive(Clone, Default)]
+pub(crate) struct DebugInfo {
+    scopes: Vec<DebugScope>,
+    variables: Vec<DebugVariable>,
+}
+
+#[derive(Clone)]
+struct DebugScope {
+    name: String,
+    span: Option<Span>,
+    call_site: Option<Span>,
+    parent: Option<usize>,
+    start: usize,
+    end: usize,
+}
+
+#[derive(Clone)]
+struct DebugVariable {
+    name: String,
+    span: Option<Span>,
+    scope: Option<usize>,
+    register: AllocatedRegister,
+    offset_in_words: Option<u64>,
+    start: usize,
+    end: usize,
+}
+
+impl DebugInfo {
+    /// Resolves the markers which were found before the op at each index, given the ops left after
+    /// coalescing and spilling registers, the index each op was moved to, the register each
+    /// coalesced register was replaced with, where registers were spilled, and the registers
+    /// allocated.
+    pub(crate) fn new(
+        markers: Vec<(usize, DebugMarker)>,
+        ops: &[RealizedOp],
+        op_map: &[usize],
+        reg_map: &HashMap<VirtualRegister, VirtualRegister>,
+        spill_area: &SpillArea,
+        pool: &RegisterPool,
+    ) -> Self {
+        let mut scopes: Vec<DebugScope> = vec![];
+        let mut open_scopes = vec![];
+        let mut variables = vec![];
+        for (ix, marker) in markers {
+            let ix = op_map.get(ix).copied().unwrap_or(ops.len());
+            match marker {
+                DebugMarker::ScopeBegin {
+                    name,
+                    span,
+                    call_site,
+                } => {
+                    open_scopes.push(scopes.len());
+                    scopes.push(DebugScope {
+                        name,
+                        span,
+                        call_site,
+                        parent: open_scopes.iter().rev().nth(1).copied(),
+                        start: ix,
+                        end: ops.len(),
+                    });
+                }
+                DebugMarker::ScopeEnd => {
+                    if let Some(scope) = open_scopes.pop() {
+                        scopes[scope].end = ix;
+                    }
+                }
+                DebugMarker::Variable {
+                    name,
+                    span,
+                    register,
+                    offset_in_words,
+                } => {
+                    let register = reg_map.get(&register).cloned().unwrap_or(register);
+                    variables.push((
+                        ix,
+                        name,
+                        span,
+                        open_scopes.last().copied(),
+                        register,
+                        offset_in_words,
+                    ));
+                }
+            }
+        }
+
+        // A variable is held at its location until the last use of its register, within its scope
+        // and before it's shadowed. A spilled register stays in its slot until then.
+        let mut debug_variables = vec![];
+        for (ix, (start, name, span, scope, register, offset_in_words)) in
+            variables.iter().enumerate()
+        {
+            let shadowed_at = variables[ix + 1..]
+                .iter()
+                .find(|(_, other, _, other_scope, ..)| other == name && other_scope == scope)
+                .map(|(start, ..)| *start);
+            let limit = scope
+                .map_or(ops.len(), |scope| scopes[scope].end)
+                .min(shadowed_at.unwrap_or(ops.len()));
+            let (register, offset_in_words, end) = match spill_area.slot(register) {
+                // The address of a value in memory can't itself be followed through memory.
+                Some(_) if offset_in_words.is_some() => continue,
+                Some(slot) => match spill_area.base() {
+                    Some(base) => (base, Some(slot), Some(limit)),
+                    None => continue,
+                },
+                None => {
+                    let last_use = (*start..limit)
+                        .rev()
+                        .find(|ix| ops[*ix].opcode.registers().contains(register));
+                    (register, *offset_in_words, last_use.map(|ix| ix + 1))
+                }
+            };
+            let allocated = match register {
+                VirtualRegister::Virtual(_) => pool.get_register(register),
+                VirtualRegister::Constant(constant) => {
+                    Some(AllocatedRegister::Constant(constant.clone()))
+                }
+            };
+            if let (Some(end), Some(allocated)) = (end, allocated) {
+                debug_variables.push(DebugVariable {
+                    name: name.clone(),
+                    span: span.clone(),
+                    scope: *scope,
+                    register: allocated,
+                    offset_in_words,
+                    start: *start,
+                    end,
+                });
+            }
+        }
+
+        DebugInfo {
+            scopes,
+            variables: debug_variables,
+        }
+    }
+
+    /// Updates the indices of the ops delimiting each scope and variable once some ops are removed,
+    /// given the new index of each op and of the end of the previous ops. A removed op is mapped
+    /// to the op following it.
+    pub(crate) fn remap(&mut self, new_op_map: &[usize]) {
+        for scope in &mut self.scopes {
+            scope.start = new_op_map[scope.start];
+            scope.end = new_op_map[scope.end];
+        }
+        for variable in &mut self.variables {
+            variable.start = new_op_map[variable.start];
+            variable.end = new_op_map[variable.end];
+        }
+    }
+
+    /// The declaration of the innermost function containing the op at an index.
+    pub(crate) fn scope_span(&self, ix: usize) -> Option<&Span> {
+        self.scopes
+            .iter()
+            .rev()
+            .find(|scope| scope.start <= ix && ix < scope.end)
+            .and_then(|scope| scope.span.as_ref())
+    }
+
+    /// Adds the scopes and variables to a source map, given the opcode index of each op and of
+    /// the end of the program.
+    pub(crate) fn insert_into(&self, source_map: &mut SourceMap, pcs: &[usize]) {
+        let range = |start: usize, end: usize| PcRange {
+            start: pcs[start],
+            end: pcs[end],
+        };
+        let mut scope_indices = vec![];
+        for scope in &self.scopes {
+            let index = source_map.insert_scope(
+                scope.name.clone(),
+                scope.span.as_ref(),
+                scope.call_site.as_ref(),
+                scope.parent.map(|parent| scope_indices[parent]),
+                range(scope.start, scope.end),
+            );
+            scope_indices.push(index);
+        }
+        for variable in &self.variables {
+            let register = variable.register.to_register_id() as u8;
+            let location = match variable.offset_in_words {
+                Some(offset_in_words) => VariableLocation::Memory {
+                    register,
+                    offset: offset_in_words * 8,
+                },
+                None => VariableLocation::Register(register),
+            };
+            source_map.insert_variable(
+                variable.name.clone(),
+                variable.span.as_ref(),
+                variable.scope.map(|scope| scope_indices[scope]),
+                location,
+                range(variable.start, variable.end),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        asm_generation::FinalizedAsm,
+        compile_to_asm, create_module,
+        source_map::{SourceMap, SourceMapSpan, VariableLocation},
+        BuildConfig, CompilationResult,
+    };
+    use std::{path::PathBuf, sync::Arc};
+
+    const SCRIPT: &str = r#"script;
+
+fn double(x: u64) -> u64 {
+    let y = x;
+    y
+}
+
+fn main() -> u64 {
+    let a = double(2);
+    let b = double(a);
+    b
+}
+"#;
+
+    /// Compiles `SCRIPT`, returning its source map and the number of opcodes before the data
+    /// section.
+    fn compile(use_ir: bool) -> (SourceMap, usize) {
+        let build_config = BuildConfig::root_from_file_name_and_manifest_path(
+            PathBuf::from("main.sw"),
+            PathBuf::from("/project"),
+        )
+        .use_ir(use_ir);
+        let mut asm = match compile_to_asm(Arc::from(SCRIPT), create_module(), build_config) {
+            CompilationResult::Success { asm, .. } => asm,
+            CompilationResult::Failure { errors, .. } => panic!("failed to compile: {:?}", errors),
+            CompilationResult::Library { .. } => panic!("compiled a library"),
+        };
+        let mut source_map = SourceMap::new();
+        let bytecode = asm.to_bytecode_mut(&mut source_map).value.unwrap();
+        let data_section_len = match &asm {
+            FinalizedAsm::ScriptMain { data_section, .. } => {
+                data_section.serialize_to_bytes().len()
+            }
+            _ => panic!("compiled something other than a script"),
+        };
+        (source_map, (bytecode.len() - data_section_len) / 4)
+    }
+
+    #[test]
+    fn every_opcode_is_mapped_to_a_span() {
+        for use_ir in [false, true] {
+            let (source_map, opcode_count) = compile(use_ir);
+            let mut pcs = source_map.spans().map(|(pc, ..)| pc).collect::<Vec<_>>();
+            pcs.sort_unstable();
+            assert_eq!(pcs, (0..opcode_count).collect::<Vec<_>>());
+            for (_, path, range) in source_map.spans() {
+                assert_eq!(path, PathBuf::from("main.sw"));
+                assert!(range.start < range.end && range.end <= SCRIPT.len());
+            }
+        }
+    }
+
+    fn source_text(source_map: &SourceMap, span: &Option<SourceMapSpan>) -> &'static str {
+        let (_, range) = source_map.resolve(span.as_ref().unwrap());
+        &SCRIPT[range.start..range.end]
+    }
+
+    #[test]
+    fn inlined_calls_are_scoped_at_their_call_sites() {
+        let (source_map, opcode_count) = compile(false);
+        let scopes = source_map.scopes();
+        let names = scopes
+            .iter()
+            .map(|scope| scope.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["main", "double", "double"]);
+
+        assert_eq!(source_text(&source_map, &scopes[0].span), "main");
+        assert!(scopes[0].call_site.is_none());
+        assert!(scopes[0].parent.is_none());
+        assert!(scopes[0].range.end <= opcode_count);
+        for (scope, call_site) in scopes[1..].iter().zip(["double(2)", "double(a)"]) {
+            assert_eq!(source_text(&source_map, &scope.span), "double");
+            assert_eq!(source_text(&source_map, &scope.call_site), call_site);
+            assert_eq!(scope.parent, Some(0));
+            assert!(scopes[0].range.start <= scope.range.start);
+            assert!(scope.range.end <= scopes[0].range.end);
+        }
+    }
+
+    #[test]
+    fn variables_are_held_within_their_scope() {
+        let (source_map, _) = compile(false);
+        let main_range = source_map.scopes()[0].range;
+        let variables = source_map.variables();
+        let names = variables
+            .iter()
+            .map(|variable| variable.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["a", "b"]);
+        for variable in variables {
+            assert_eq!(source_text(&source_map, &variable.span), variable.name);
+            assert_eq!(variable.scope, Some(0));
+            assert!(matches!(variable.location, VariableLocation::Register(_)));
+            assert!(main_range.start <= variable.range.start);
+            assert!(variable.range.start < variable.range.end);
+            assert!(variable.range.end <= main_range.end);
+            assert!(source_map
+                .variables_at(variable.range.start)
+                .any(|held| held.name == variable.name));
+        }
+    }
+}
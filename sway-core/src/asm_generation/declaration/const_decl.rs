@@ -19,6 +19,14 @@ pub(crate) fn convert_constant_decl_to_asm(
         &val_register,
         register_sequencer,
     );
-    namespace.insert_variable(const_decl.name.clone(), val_register);
-    initialization
+    namespace.insert_variable(const_decl.name.clone(), val_register.clone());
+    initialization.map(|mut ops| {
+        ops.push(Op::debug_variable(
+            const_decl.name.as_str(),
+            Some(const_decl.name.span().clone()),
+            val_register,
+            None,
+        ));
+        ops
+    })
 }
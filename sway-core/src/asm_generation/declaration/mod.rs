@@ -8,7 +8,7 @@ mod var_decl;
 
 pub(crate) use const_decl::convert_constant_decl_to_asm;
 pub(crate) use fn_decl::convert_fn_decl_to_asm;
-pub(crate) use reassignment::convert_reassignment_to_asm;
+pub(crate) use reassignment::{convert_reassignment_to_asm, convert_storage_reassignment_to_asm};
 pub(crate) use var_decl::convert_variable_decl_to_asm;
 
 pub(crate) fn convert_decl_to_asm(
@@ -38,6 +38,11 @@ pub(crate) fn convert_decl_to_asm(
         TypedDeclaration::Reassignment(reassignment) => {
             convert_reassignment_to_asm(reassignment, namespace, register_sequencer)
         }
+        TypedDeclaration::StorageReassignment(reassignment) => {
+            convert_storage_reassignment_to_asm(reassignment, namespace, register_sequencer)
+        }
+        // the initial values of storage are part of the deployment, not the bytecode
+        TypedDeclaration::StorageDeclaration(_) => ok(vec![], vec![], vec![]),
         _ => err(
             vec![],
             vec![CompileError::Unimplemented(
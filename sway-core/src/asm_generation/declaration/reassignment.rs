@@ -1,12 +1,14 @@
 use super::*;
 use crate::{
     asm_generation::{
-        convert_expression_to_asm, expression::get_contiguous_memory_layout, AsmNamespace,
-        RegisterSequencer,
+        convert_expression_to_asm, expression::get_contiguous_memory_layout, load_storage_key,
+        AsmNamespace, RegisterSequencer,
     },
     asm_lang::{VirtualImmediate12, VirtualOp},
     constants::VM_WORD_SIZE,
-    semantic_analysis::ast_node::{OwnedTypedStructField, ReassignmentLhs, TypedReassignment},
+    semantic_analysis::ast_node::{
+        OwnedTypedStructField, ReassignmentLhs, TypedReassignment, TypedStorageReassignment,
+    },
     type_engine::*,
     type_engine::{resolve_type, TypeInfo},
 };
@@ -234,3 +236,64 @@ pub(crate) fn convert_reassignment_to_asm(
 
     ok(buf, warnings, errors)
 }
+
+pub(crate) fn convert_storage_reassignment_to_asm(
+    reassignment: &TypedStorageReassignment,
+    namespace: &mut AsmNamespace,
+    register_sequencer: &mut RegisterSequencer,
+) -> CompileResult<Vec<Op>> {
+    // 0. evaluate the RHS of the reassignment
+    // 1. load the address of the storage key
+    // 2. write the RHS to storage, either directly from the register if it is word sized or
+    //    from the memory it points to if it is a b256
+    let mut buf = vec![];
+    let mut warnings = vec![];
+    let mut errors = vec![];
+    // step 0
+    let return_register = register_sequencer.next();
+    let mut rhs = check!(
+        convert_expression_to_asm(
+            &reassignment.rhs,
+            namespace,
+            &return_register,
+            register_sequencer
+        ),
+        vec![],
+        warnings,
+        errors
+    );
+    buf.append(&mut rhs);
+
+    // step 1
+    let key_register = register_sequencer.next();
+    buf.append(&mut load_storage_key(
+        &reassignment.access,
+        namespace,
+        &key_register,
+        reassignment.span.clone(),
+    ));
+
+    // step 2
+    let (opcode, comment) = if reassignment.access.is_word_sized() {
+        (
+            VirtualOp::SWW(key_register, return_register),
+            "store word to storage",
+        )
+    } else {
+        (
+            VirtualOp::SWWQ(key_register, return_register),
+            "store quad word to storage",
+        )
+    };
+    buf.push(Op {
+        opcode: Either::Left(opcode),
+        comment: format!(
+            "{} (storage.{} reassignment)",
+            comment,
+            reassignment.access.field_name.as_str()
+        ),
+        owning_span: Some(reassignment.span.clone()),
+    });
+
+    ok(buf, warnings, errors)
+}
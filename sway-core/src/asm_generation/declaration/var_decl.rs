@@ -15,6 +15,14 @@ pub(crate) fn convert_variable_decl_to_asm(
     let var_register = register_sequencer.next();
     let initialization =
         convert_expression_to_asm(&var_decl.body, namespace, &var_register, register_sequencer);
-    namespace.insert_variable(var_decl.name.clone(), var_register);
-    initialization
+    namespace.insert_variable(var_decl.name.clone(), var_register.clone());
+    initialization.map(|mut ops| {
+        ops.push(Op::debug_variable(
+            var_decl.name.as_str(),
+            Some(var_decl.name.span().clone()),
+            var_register,
+            None,
+        ));
+        ops
+    })
 }
@@ -0,0 +1,151 @@
+use super::{convert_expression_to_asm, convert_literal_to_asm};
+use crate::{
+    asm_generation::{
+        function_calls::convert_call_with_registers_to_asm, AsmNamespace, RegisterSequencer,
+    },
+    asm_lang::{ConstantRegister, Op, VirtualOp, VirtualRegister},
+    error::*,
+    parse_tree::Literal,
+    semantic_analysis::{FunctionKey, TypedExpression, TypedFunctionDeclaration},
+};
+use sway_types::span::Span;
+
+/// A function value is the index of the function in the program's function table, so referencing
+/// a function just loads that index.
+pub(crate) fn convert_fn_reference_to_asm(
+    function: &TypedFunctionDeclaration,
+    namespace: &mut AsmNamespace,
+    return_register: &VirtualRegister,
+    register_sequencer: &mut RegisterSequencer,
+    span: Span,
+) -> CompileResult<Vec<Op>> {
+    match namespace.function_table.index_of(function) {
+        Some(ix) => ok(
+            convert_literal_to_asm(
+                &Literal::U64(ix),
+                namespace,
+                return_register,
+                register_sequencer,
+                span,
+            ),
+            vec![],
+            vec![],
+        ),
+        None => err(
+            vec![],
+            vec![CompileError::Internal(
+                "Function reference is missing from the function table.",
+                span,
+            )],
+        ),
+    }
+}
+
+/// Calls a function value. The VM can only jump to an address given as an immediate, so the
+/// value is compared against the index of every function with a matching type to jump to the
+/// entry of the one it refers to. Each function is compiled once and called with the calling
+/// convention of [convert_call_with_registers_to_asm], however many call sites it has:
+///
+/// ```ignore
+/// jnei $function $index_0 next_0
+/// <call function 0>
+/// ji end
+/// next_0:
+/// jnei $function $index_1 next_1
+/// ...
+/// next_n:
+/// rvrt $one
+/// end:
+/// ```
+pub(crate) fn convert_indirect_fn_app_to_asm(
+    function: &TypedExpression,
+    arguments: &[TypedExpression],
+    parent_namespace: &mut AsmNamespace,
+    return_register: &VirtualRegister,
+    register_sequencer: &mut RegisterSequencer,
+    span: Span,
+) -> CompileResult<Vec<Op>> {
+    let mut warnings = vec![];
+    let mut errors = vec![];
+    let mut asm_buf = vec![Op::new_comment("indirect fn call")];
+    let mut namespace = parent_namespace.clone();
+
+    // evaluate the function value and the arguments once, before dispatching
+    let function_register = register_sequencer.next();
+    asm_buf.append(&mut check!(
+        convert_expression_to_asm(
+            function,
+            &mut namespace,
+            &function_register,
+            register_sequencer
+        ),
+        return err(warnings, errors),
+        warnings,
+        errors
+    ));
+    let mut argument_registers = vec![];
+    for arg in arguments {
+        let arg_register = register_sequencer.next();
+        asm_buf.append(&mut check!(
+            convert_expression_to_asm(arg, &mut namespace, &arg_register, register_sequencer),
+            return err(warnings, errors),
+            warnings,
+            errors
+        ));
+        argument_registers.push(arg_register);
+    }
+    parent_namespace.leave_scope(namespace);
+
+    let function_table = parent_namespace.function_table.clone();
+    let end_label = register_sequencer.get_label();
+    for (ix, candidate) in function_table.candidates(function.return_type) {
+        let next_label = register_sequencer.get_label();
+        let index_register = register_sequencer.next();
+        asm_buf.append(&mut convert_literal_to_asm(
+            &Literal::U64(ix),
+            parent_namespace,
+            &index_register,
+            register_sequencer,
+            span.clone(),
+        ));
+        asm_buf.push(Op::jump_if_not_equal(
+            function_register.clone(),
+            index_register,
+            next_label.clone(),
+        ));
+        let parameter_names = candidate
+            .parameters
+            .iter()
+            .map(|param| param.name.clone())
+            .collect::<Vec<_>>();
+        asm_buf.append(&mut convert_call_with_registers_to_asm(
+            FunctionKey::of_decl(candidate),
+            &candidate.name,
+            &parameter_names,
+            argument_registers.clone(),
+            &candidate.body,
+            candidate.return_type,
+            parent_namespace,
+            return_register,
+            register_sequencer,
+            span.clone(),
+        ));
+        asm_buf.push(Op::jump_to_label(end_label.clone()));
+        asm_buf.push(Op::unowned_jump_label(next_label));
+    }
+
+    // the value didn't match any function of the right type, which type checking rules out
+    asm_buf.push(Op {
+        opcode: either::Either::Left(VirtualOp::RVRT(VirtualRegister::Constant(
+            ConstantRegister::One,
+        ))),
+        owning_span: Some(span),
+        comment: "no function matches the function pointer".into(),
+    });
+    asm_buf.push(Op::unowned_jump_label_comment(
+        end_label,
+        "end of indirect fn call",
+    ));
+
+    ok(asm_buf, warnings, errors)
+}
@@ -15,17 +15,23 @@ use sway_types::span::Span;
 mod array;
 mod contract_call;
 mod enums;
+mod function_pointer;
 mod if_exp;
 mod lazy_op;
+mod storage;
 mod structs;
 pub(crate) mod subfield;
+mod wide_integer_op;
 use contract_call::convert_contract_call_to_asm;
 use enums::convert_enum_instantiation_to_asm;
+use function_pointer::{convert_fn_reference_to_asm, convert_indirect_fn_app_to_asm};
 use if_exp::convert_if_exp_to_asm;
+pub(crate) use storage::{convert_storage_access_to_asm, load_storage_key};
 pub(crate) use structs::{
     convert_struct_expression_to_asm, convert_tuple_expression_to_asm, get_contiguous_memory_layout,
 };
 use subfield::convert_subfield_expression_to_asm;
+use wide_integer_op::convert_wide_integer_operator_to_asm;
 
 /// Given a [TypedExpression], convert it to assembly and put its return value, if any, in the
 /// `return_register`.
@@ -51,10 +57,12 @@ pub(crate) fn convert_expression_to_asm(
         ),
         TypedExpressionVariant::FunctionApplication {
             name,
+            decl_name,
             contract_call_params,
             arguments,
             function_body,
             selector,
+            ..
         } => {
             if let Some(metadata) = selector {
                 convert_contract_call_to_asm(
@@ -67,16 +75,51 @@ pub(crate) fn convert_expression_to_asm(
                     exp.span.clone(),
                 )
             } else {
-                convert_fn_app_to_asm(
-                    name,
-                    arguments,
-                    function_body,
-                    namespace,
-                    return_register,
-                    register_sequencer,
-                )
+                let key = FunctionKey::of_call(decl_name, arguments, exp.return_type);
+                if namespace.call_graph.should_inline(&key) {
+                    convert_fn_app_to_asm(
+                        name,
+                        decl_name,
+                        arguments,
+                        function_body,
+                        namespace,
+                        return_register,
+                        register_sequencer,
+                        exp.span.clone(),
+                    )
+                } else {
+                    convert_fn_call_to_asm(
+                        key,
+                        decl_name,
+                        arguments,
+                        function_body,
+                        exp.return_type,
+                        namespace,
+                        return_register,
+                        register_sequencer,
+                        exp.span.clone(),
+                    )
+                }
             }
         }
+        TypedExpressionVariant::FunctionReference { function, .. } => convert_fn_reference_to_asm(
+            function,
+            namespace,
+            return_register,
+            register_sequencer,
+            exp.span.clone(),
+        ),
+        TypedExpressionVariant::IndirectFunctionApplication {
+            function,
+            arguments,
+        } => convert_indirect_fn_app_to_asm(
+            function,
+            arguments,
+            namespace,
+            return_register,
+            register_sequencer,
+            exp.span.clone(),
+        ),
         TypedExpressionVariant::LazyOperator { op, lhs, rhs } => {
             lazy_op::convert_lazy_operator_to_asm(
                 op,
@@ -87,6 +130,17 @@ pub(crate) fn convert_expression_to_asm(
                 register_sequencer,
             )
         }
+        TypedExpressionVariant::WideIntegerOperator { op, lhs, rhs } => {
+            convert_wide_integer_operator_to_asm(
+                *op,
+                lhs,
+                rhs,
+                return_register,
+                namespace,
+                register_sequencer,
+                exp.span.clone(),
+            )
+        }
         TypedExpressionVariant::VariableExpression { name } => {
             let var = check!(
                 namespace.look_up_variable(name),
@@ -360,6 +414,17 @@ pub(crate) fn convert_expression_to_asm(
             register_sequencer,
             exp.span.clone(),
         ),
+        TypedExpressionVariant::StorageAccess(access) => ok(
+            convert_storage_access_to_asm(
+                access,
+                namespace,
+                return_register,
+                register_sequencer,
+                exp.span.clone(),
+            ),
+            warnings,
+            errors,
+        ),
         _ => {
             errors.push(CompileError::Unimplemented(
                 "ASM generation has not yet been implemented for this.",
@@ -487,14 +552,17 @@ fn convert_size_of_expression_to_asm(
     ok(asm_buf, warnings, errors)
 }
 
-/// For now, all functions are handled by inlining at the time of application.
+/// Inlines the body of the called function at the call site.
+#[allow(clippy::too_many_arguments)]
 fn convert_fn_app_to_asm(
     name: &CallPath,
+    decl_name: &Ident,
     arguments: &[(Ident, TypedExpression)],
     function_body: &TypedCodeBlock,
     parent_namespace: &mut AsmNamespace,
     return_register: &VirtualRegister,
     register_sequencer: &mut RegisterSequencer,
+    span: Span,
 ) -> CompileResult<Vec<Op>> {
     let mut warnings = vec![];
     let mut errors = vec![];
@@ -516,8 +584,20 @@ fn convert_fn_app_to_asm(
         args_and_registers.insert(name.clone(), return_register);
     }
 
+    asm_buf.push(Op::debug_scope_begin(
+        decl_name.as_str(),
+        Some(decl_name.span().clone()),
+        Some(span),
+    ));
+
     // insert the arguments into the asm namespace with their registers mapped
     for (name, reg) in args_and_registers {
+        asm_buf.push(Op::debug_variable(
+            name.as_str(),
+            Some(name.span().clone()),
+            reg.clone(),
+            None,
+        ));
         namespace.insert_variable(name, reg);
     }
 
@@ -534,7 +614,8 @@ fn convert_fn_app_to_asm(
         errors
     );
     asm_buf.append(&mut body);
-    parent_namespace.data_section = namespace.data_section;
+    asm_buf.push(Op::debug_scope_end());
+    parent_namespace.leave_scope(namespace);
 
     // the return  value is already put in its proper register via the above statement, so the buf
     // is done
@@ -561,9 +642,20 @@ pub(crate) fn convert_abi_fn_to_asm(
     // scope
     let mut namespace = parent_namespace.clone();
     let return_register = register_sequencer.next();
+    asm_buf.push(Op::debug_scope_begin(
+        decl.name.as_str(),
+        Some(decl.name.span().clone()),
+        None,
+    ));
 
     // insert the arguments into the asm namespace with their registers mapped
     for arg in arguments {
+        asm_buf.push(Op::debug_variable(
+            arg.0.as_str(),
+            Some(arg.0.span().clone()),
+            arg.1.clone(),
+            None,
+        ));
         namespace.insert_variable(arg.clone().0, arg.clone().1);
     }
     // evaluate the function body
@@ -587,8 +679,9 @@ pub(crate) fn convert_abi_fn_to_asm(
         warnings,
         errors
     ));
+    asm_buf.push(Op::debug_scope_end());
 
-    parent_namespace.data_section = namespace.data_section;
+    parent_namespace.leave_scope(namespace);
 
     // the return  value is already put in its proper register via the above statement, so the buf
     // is done
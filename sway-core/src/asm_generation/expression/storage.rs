@@ -0,0 +1,68 @@
+use crate::{
+    asm_generation::{AsmNamespace, RegisterSequencer},
+    asm_lang::{ConstantRegister, Op, VirtualImmediate24, VirtualOp, VirtualRegister},
+    semantic_analysis::ast_node::TypedStorageAccess,
+};
+use sway_types::span::Span;
+
+use either::Either;
+
+/// Reads the storage field described by `access` into `return_register`. Word sized values are
+/// read directly into the register, while a `b256` is read into freshly allocated stack memory
+/// and `return_register` holds its address.
+pub(crate) fn convert_storage_access_to_asm(
+    access: &TypedStorageAccess,
+    namespace: &mut AsmNamespace,
+    return_register: &VirtualRegister,
+    register_sequencer: &mut RegisterSequencer,
+    span: Span,
+) -> Vec<Op> {
+    let mut asm_buf = vec![Op::new_comment(format!(
+        "storage.{} read",
+        access.field_name.as_str()
+    ))];
+    let key_register = register_sequencer.next();
+    asm_buf.append(&mut load_storage_key(
+        access,
+        namespace,
+        &key_register,
+        span.clone(),
+    ));
+    if access.is_word_sized() {
+        asm_buf.push(Op {
+            opcode: Either::Left(VirtualOp::SRW(return_register.clone(), key_register)),
+            comment: "load word from storage".into(),
+            owning_span: Some(span),
+        });
+    } else {
+        asm_buf.push(Op::unowned_register_move(
+            return_register.clone(),
+            VirtualRegister::Constant(ConstantRegister::StackPointer),
+        ));
+        asm_buf.push(Op::unowned_stack_allocate_memory(
+            VirtualImmediate24::new_unchecked(32, "32 is less than 2^24"),
+        ));
+        asm_buf.push(Op {
+            opcode: Either::Left(VirtualOp::SRWQ(return_register.clone(), key_register)),
+            comment: "load quad word from storage".into(),
+            owning_span: Some(span),
+        });
+    }
+    asm_buf
+}
+
+/// Loads the address of the storage key for `access` into `key_register`. The key itself lives
+/// in the data section.
+pub(crate) fn load_storage_key(
+    access: &TypedStorageAccess,
+    namespace: &mut AsmNamespace,
+    key_register: &VirtualRegister,
+    span: Span,
+) -> Vec<Op> {
+    let data_id = namespace.insert_data_value(&access.storage_key_literal());
+    vec![Op {
+        opcode: Either::Left(VirtualOp::LWDataId(key_register.clone(), data_id)),
+        comment: format!("storage key for {}", access.field_name.as_str()),
+        owning_span: Some(span),
+    }]
+}
@@ -0,0 +1,66 @@
+use crate::{
+    asm_generation::{
+        convert_expression_to_asm, wide_integer::convert_wide_integer_op_to_asm, AsmNamespace,
+        RegisterSequencer,
+    },
+    asm_lang::{Op, VirtualRegister},
+    error::*,
+    semantic_analysis::{ast_node::WideIntegerOp, TypedExpression},
+    type_engine::{look_up_type_id, TypeInfo},
+    CompileResult,
+};
+use sway_types::span::Span;
+
+pub(crate) fn convert_wide_integer_operator_to_asm(
+    op: WideIntegerOp,
+    lhs: &TypedExpression,
+    rhs: &TypedExpression,
+    return_register: &VirtualRegister,
+    namespace: &mut AsmNamespace,
+    register_sequencer: &mut RegisterSequencer,
+    span: Span,
+) -> CompileResult<Vec<Op>> {
+    let mut warnings = vec![];
+    let mut errors = vec![];
+    let mut asm_ops = vec![];
+
+    // Both operands evaluate to the address of their words.
+    let lhs_register = register_sequencer.next();
+    let mut lhs_asm_ops = check!(
+        convert_expression_to_asm(lhs, namespace, &lhs_register, register_sequencer),
+        return err(warnings, errors),
+        warnings,
+        errors
+    );
+    asm_ops.append(&mut lhs_asm_ops);
+    let rhs_register = register_sequencer.next();
+    let mut rhs_asm_ops = check!(
+        convert_expression_to_asm(rhs, namespace, &rhs_register, register_sequencer),
+        return err(warnings, errors),
+        warnings,
+        errors
+    );
+    asm_ops.append(&mut rhs_asm_ops);
+
+    let size_in_words = match look_up_type_id(lhs.return_type) {
+        TypeInfo::UnsignedInteger(bits) => bits.size_in_words(),
+        _ => {
+            errors.push(CompileError::Internal(
+                "Wide integer operator applied to a value which isn't an integer.",
+                span,
+            ));
+            return err(warnings, errors);
+        }
+    };
+    asm_ops.append(&mut convert_wide_integer_op_to_asm(
+        op,
+        size_in_words,
+        &lhs_register,
+        &rhs_register,
+        return_register,
+        register_sequencer,
+        Some(span),
+    ));
+
+    ok(asm_ops, warnings, errors)
+}
@@ -93,15 +93,26 @@ fn to_bytecode_mut(
     // each op is four bytes, so the length of the buf is the number of ops times four.
     let mut buf = vec![0; (program_section.ops.len() * 4) + 4];
 
+    // The source location of each opcode, and the opcode index of each op.
+    let mut spans = vec![];
+    let mut pcs = vec![];
     let mut half_word_ix = 0;
-    for op in program_section.ops.iter() {
-        let span = op.owning_span.clone();
+    for (ix, op) in program_section.ops.iter().enumerate() {
+        pcs.push(half_word_ix);
+        // Ops which the compiler introduced for a function belong to its declaration.
+        let span = op
+            .owning_span
+            .as_ref()
+            .or_else(|| program_section.debug_info.scope_span(ix))
+            .filter(|span| span.path.is_some())
+            .cloned();
         let op = op.to_fuel_asm(offset_to_data_section_in_bytes, data_section);
         match op {
             Either::Right(data) => {
                 for i in 0..data.len() {
                     buf[(half_word_ix * 4) + i] = data[i];
                 }
+                spans.extend([span.clone(), span]);
                 half_word_ix += 2;
             }
             Either::Left(ops) => {
@@ -109,9 +120,7 @@ fn to_bytecode_mut(
                     buf.resize(buf.len() + ((ops.len() - 1) * 4), 0);
                 }
                 for mut op in ops {
-                    if let Some(span) = &span {
-                        source_map.insert(half_word_ix, span);
-                    }
+                    spans.push(span.clone());
                     op.read_exact(&mut buf[half_word_ix * 4..])
                         .expect("Failed to write to in-memory buffer.");
                     half_word_ix += 1;
@@ -120,9 +129,77 @@ fn to_bytecode_mut(
         }
     }
 
+    pcs.push(half_word_ix);
+
+    fill_spans(&mut spans);
+    for (pc, span) in spans.iter().enumerate() {
+        if let Some(span) = span {
+            source_map.insert(pc, span);
+        }
+    }
+    program_section.debug_info.insert_into(source_map, &pcs);
+
     let mut data_section = data_section.serialize_to_bytes();
 
     buf.append(&mut data_section);
 
     ok(buf, vec![], errors)
 }
+
+/// Gives every opcode without a span one, so that every opcode is mapped as long as any is. Those
+/// outside of any function, like the preamble, belong to the code they lead into, and those after
+/// the last function belong to it.
+fn fill_spans(spans: &mut [Option<Span>]) {
+    let mut next_span = None;
+    for span in spans.iter_mut().rev() {
+        match span {
+            Some(span) => next_span = Some(span.clone()),
+            None => *span = next_span.clone(),
+        }
+    }
+    let mut previous_span = None;
+    for span in spans.iter_mut() {
+        match span {
+            Some(span) => previous_span = Some(span.clone()),
+            None => *span = previous_span.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opcodes_without_a_span_are_given_one() {
+        let source: std::sync::Arc<str> = "preamble first second".into();
+        let span = |start, end| {
+            Some(Span {
+                span: pest::Span::new(source.clone(), start, end).unwrap(),
+                path: None,
+            })
+        };
+        let mut spans = vec![None, None, span(9, 14), None, span(15, 21), None, None];
+        fill_spans(&mut spans);
+        let spans = spans
+            .iter()
+            .map(|span| span.as_ref().map(|span| span.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            spans,
+            [
+                Some("first"),
+                Some("first"),
+                Some("first"),
+                Some("second"),
+                Some("second"),
+                Some("second"),
+                Some("second"),
+            ]
+        );
+
+        let mut spans = vec![None, None];
+        fill_spans(&mut spans);
+        assert!(spans.iter().all(Option::is_none));
+    }
+}
@@ -7,17 +7,19 @@
 // But this is not ideal and needs to be refactored:
 // - AsmNamespace is tied to data structures from other stages like Ident and Literal.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::{
     asm_generation::{
         build_contract_abi_switch, build_preamble, finalized_asm::FinalizedAsm,
-        register_sequencer::RegisterSequencer, AbstractInstructionSet, DataId, DataSection,
+        function_calls::call_site_index_op, register_sequencer::RegisterSequencer,
+        wide_integer::convert_wide_integer_op_to_asm, AbstractInstructionSet, DataId, DataSection,
         SwayAsmSet,
     },
     asm_lang::{virtual_register::*, Label, Op, VirtualImmediate12, VirtualImmediate24, VirtualOp},
     error::*,
     parse_tree::Literal,
+    semantic_analysis::ast_node::WideIntegerOp,
     BuildConfig,
 };
 
@@ -62,13 +64,14 @@ pub fn compile_ir_to_asm(ir: &Context, build_config: &BuildConfig) -> CompileRes
         println!("{}", asm);
     }
 
-    let finalized_asm = asm
+    let (finalized_asm, peephole_report) = asm
         .remove_unnecessary_jumps()
         .allocate_registers(&mut reg_seqr)
         .optimize();
 
     if build_config.print_finalized_asm {
         println!("{}", finalized_asm);
+        print!("{}", peephole_report);
     }
 
     check!(
@@ -89,13 +92,14 @@ fn compile_module_to_asm(
     let mut builder = AsmBuilder::new(DataSection::default(), reg_seqr, context);
     match module.get_kind(context) {
         Kind::Script => {
-            // We can't do function calls yet, so we expect everything to be inlined into `main`.
+            // The calls which weren't inlined into `main` are compiled after it.
             let function = module
                 .function_iter(context)
                 .find(|func| &context.functions[func.0].name == "main")
                 .expect("Can't find main function!");
             builder
                 .compile_function(function)
+                .flat_map(|_| builder.compile_called_functions())
                 .flat_map(|_| builder.finalize())
         }
         Kind::Contract => {
@@ -118,6 +122,12 @@ fn compile_module_to_asm(
                     selectors_and_labels.push((selector, label));
                 }
             }
+            check!(
+                builder.compile_called_functions(),
+                return err(warnings, errors),
+                warnings,
+                errors
+            );
             let (mut data_section, mut funcs_bytecode, mut reg_seqr) = check!(
                 builder.finalize(),
                 return err(warnings, errors),
@@ -177,6 +187,15 @@ struct AsmBuilder<'ir> {
     // IR context we're compiling.
     context: &'ir Context,
 
+    // The functions inlined at the instruction being compiled, outermost first, with the
+    // metadata of the call each was inlined at.  Used to mark their scopes for debug info.
+    inlined_scopes: Vec<(String, MetadataIndex)>,
+
+    // The functions which are called rather than inlined, in the order of their first call, and
+    // the one whose body is being compiled, if it isn't an entry point.
+    called_functions: Vec<CalledFunction>,
+    current_function: Option<Function>,
+
     // Final resulting VM bytecode ops.
     bytecode: Vec<Op>,
 }
@@ -186,6 +205,26 @@ struct FieldLayout {
     size_in_bytes: u64,   // Use bytes because CFEI/MCP do.
 }
 
+// A called function has a register of its own for each argument, its return value and its return
+// address.  The caller sets the return address to the index of its call site, which the function
+// compares against each call site after its body to jump back to the right one.  A call which may
+// lead back to the calling function saves the registers it needs around it.
+//
+// Like the locals of the entry points, the locals of a called function are allocated on the stack
+// at its entry and never freed, so aggregates returned by pointer remain valid.
+struct CalledFunction {
+    function: Function,
+    entry_label: Label,
+    arg_regs: Vec<VirtualRegister>,
+    ret_reg: VirtualRegister,
+    ret_addr_reg: VirtualRegister,
+    // Where `ret` jumps to, to find the call site to return to.
+    return_dispatch_label: Label,
+    // The label after each call site, indexed by the value of the return address.
+    return_labels: Vec<Label>,
+    compiled: bool,
+}
+
 // NOTE: For stack storage we need to be aware:
 // - sizes are in bytes; CFEI reserves in bytes.
 // - offsets are in 64-bit words; LW/SW reads/writes to word offsets. XXX Wrap in a WordOffset struct.
@@ -197,6 +236,12 @@ pub(super) enum Storage {
     Stack(u64), // Storage in the runtime stack starting at an absolute word offset.  Essentially a global.
 }
 
+#[derive(Clone, Copy, Debug)]
+enum StateAccessKind {
+    Load,
+    Store,
+}
+
 impl<'ir> AsmBuilder<'ir> {
     fn new(data_section: DataSection, reg_seqr: RegisterSequencer, context: &'ir Context) -> Self {
         AsmBuilder {
@@ -208,6 +253,9 @@ impl<'ir> AsmBuilder<'ir> {
             stack_base_reg: None,
             aggregate_layouts: HashMap::new(),
             context,
+            inlined_scopes: Vec::new(),
+            called_functions: Vec::new(),
+            current_function: None,
             bytecode: Vec::new(),
         }
     }
@@ -240,10 +288,14 @@ impl<'ir> AsmBuilder<'ir> {
                 self.ptr_map.insert(*ptr, Storage::Data(data_id));
             } else {
                 match ptr_content.ty {
-                    Type::Unit | Type::Bool | Type::Uint(_) => {
+                    Type::Unit | Type::Bool | Type::Uint(0..=64) => {
                         let reg = self.reg_seqr.next();
                         self.ptr_map.insert(*ptr, Storage::Register(reg));
                     }
+                    Type::Uint(nbits) => {
+                        self.ptr_map.insert(*ptr, Storage::Stack(stack_base));
+                        stack_base += nbits as u64 / 64;
+                    }
                     Type::B256 => {
                         self.ptr_map.insert(*ptr, Storage::Stack(stack_base));
                         stack_base += 4;
@@ -312,6 +364,46 @@ impl<'ir> AsmBuilder<'ir> {
             }
             self.stack_base_reg = Some(base_reg);
         }
+
+        // Locals in the data section are constants, and don't need to be found at run time.
+        for (name, ptr) in function.locals_iter(self.context) {
+            let (register, offset_in_words) = match self.ptr_map.get(ptr) {
+                Some(Storage::Register(reg)) => (reg.clone(), None),
+                Some(Storage::Stack(word_offs)) => match &self.stack_base_reg {
+                    Some(base_reg) => (base_reg.clone(), Some(*word_offs)),
+                    None => continue,
+                },
+                Some(Storage::Data(_)) | None => continue,
+            };
+            self.bytecode
+                .push(Op::debug_variable(name, None, register, offset_in_words));
+        }
+    }
+
+    // Close the scopes of the functions inlined at the previous instruction but not at this one,
+    // and open those inlined at this one but not at the previous.  Instructions without a span
+    // don't change the scope.
+    fn update_inlined_scopes(&mut self, instr_val: &Value) {
+        let chain = match self.context.values[instr_val.0].span_md_idx {
+            Some(span_md_idx) => span_md_idx.inlined_chain(self.context),
+            None => return,
+        };
+        let common = self
+            .inlined_scopes
+            .iter()
+            .zip(chain.iter())
+            .take_while(|(open, inlined)| open == inlined)
+            .count();
+        for _ in common..self.inlined_scopes.len() {
+            self.bytecode.push(Op::debug_scope_end());
+        }
+        self.inlined_scopes.truncate(common);
+        for (function, call_site_md_idx) in chain.into_iter().skip(common) {
+            let call_site = call_site_md_idx.to_span(self.context).ok();
+            self.bytecode
+                .push(Op::debug_scope_begin(function.clone(), None, call_site));
+            self.inlined_scopes.push((function, call_site_md_idx));
+        }
     }
 
     fn add_block_label(&mut self, block: Block) {
@@ -337,6 +429,12 @@ impl<'ir> AsmBuilder<'ir> {
     }
 
     fn compile_function(&mut self, function: Function) -> CompileResult<()> {
+        self.bytecode.push(Op::debug_scope_begin(
+            function.get_name(self.context),
+            None,
+            None,
+        ));
+
         // Compile instructions.
         self.add_locals(function);
 
@@ -345,6 +443,7 @@ impl<'ir> AsmBuilder<'ir> {
         for block in function.block_iter(self.context) {
             self.add_block_label(block);
             for instr_val in block.instruction_iter(self.context) {
+                self.update_inlined_scopes(&instr_val);
                 check!(
                     self.compile_instruction(&block, &instr_val),
                     return err(warnings, errors),
@@ -353,6 +452,75 @@ impl<'ir> AsmBuilder<'ir> {
                 );
             }
         }
+
+        for _ in self.inlined_scopes.drain(..) {
+            self.bytecode.push(Op::debug_scope_end());
+        }
+        self.bytecode.push(Op::debug_scope_end());
+        ok((), warnings, errors)
+    }
+
+    // Compile the body of each function called from the code compiled so far, and of each function
+    // called from these in turn, followed by the jumps back to their call sites.  These can only be
+    // added once every body is compiled, as calls may be made from any of them.
+    fn compile_called_functions(&mut self) -> CompileResult<()> {
+        let mut warnings = Vec::new();
+        let mut errors = Vec::new();
+        while let Some(called) = self
+            .called_functions
+            .iter_mut()
+            .find(|called| !called.compiled)
+        {
+            called.compiled = true;
+            let (function, entry_label) = (called.function, called.entry_label.clone());
+            self.bytecode.push(Op::unowned_jump_label_comment(
+                entry_label,
+                format!("{} fn", function.get_name(self.context)),
+            ));
+            self.current_function = Some(function);
+            check!(
+                self.compile_function(function),
+                return err(warnings, errors),
+                warnings,
+                errors
+            );
+            self.current_function = None;
+        }
+
+        for called in &self.called_functions {
+            let name = called.function.get_name(self.context);
+            self.bytecode
+                .push(Op::unowned_jump_label(called.return_dispatch_label.clone()));
+            let (last_label, labels) = called
+                .return_labels
+                .split_last()
+                .expect("Functions are registered when they are called.");
+            for (call_site, return_label) in labels.iter().enumerate() {
+                let call_site_reg = self.reg_seqr.next();
+                let next_label = self.reg_seqr.get_label();
+                self.bytecode.push(call_site_index_op(
+                    call_site_reg.clone(),
+                    call_site as u64,
+                    &mut self.data_section,
+                    None,
+                    format!("call site {}", call_site),
+                ));
+                self.bytecode.push(Op::jump_if_not_equal(
+                    called.ret_addr_reg.clone(),
+                    call_site_reg,
+                    next_label.clone(),
+                ));
+                self.bytecode.push(Op::jump_to_label_comment(
+                    return_label.clone(),
+                    format!("return from {}", name),
+                ));
+                self.bytecode.push(Op::unowned_jump_label(next_label));
+            }
+            self.bytecode.push(Op::jump_to_label_comment(
+                last_label.clone(),
+                format!("return from {}", name),
+            ));
+        }
         ok((), warnings, errors)
     }
 
@@ -369,15 +537,23 @@ impl<'ir> AsmBuilder<'ir> {
                         errors
                     )
                 }
+                Instruction::BinaryOp { op, arg1, arg2 } => {
+                    check!(
+                        self.compile_binary_op(instr_val, op, arg1, arg2),
+                        return err(warnings, errors),
+                        warnings,
+                        errors
+                    )
+                }
                 Instruction::Branch(to_block) => self.compile_branch(block, to_block),
-                Instruction::Call(..) => {
-                    errors.push(CompileError::Internal(
-                        "Calls are not yet supported.",
-                        instr_val
-                            .get_span(self.context)
-                            .unwrap_or_else(Self::empty_span),
-                    ));
-                    return err(warnings, errors);
+                Instruction::Call(function, args) => self.compile_call(instr_val, function, args),
+                Instruction::CallIndirect {
+                    target,
+                    callees,
+                    args,
+                } => self.compile_call_indirect(instr_val, target, callees, args),
+                Instruction::Cmp(pred, lhs_value, rhs_value) => {
+                    self.compile_cmp(instr_val, pred, lhs_value, rhs_value)
                 }
                 Instruction::ConditionalBranch {
                     cond_value,
@@ -414,11 +590,21 @@ impl<'ir> AsmBuilder<'ir> {
                     errors
                 ),
                 Instruction::Nop => (),
-                Instruction::Phi(_) => (), // Managing the phi value is done in br and cbr compilation.
+                Instruction::Phi(..) => (), // Managing the phi value is done in br and cbr compilation.
                 Instruction::PointerCast(..) => todo!(),
                 Instruction::Ret(ret_val, ty) => self.compile_ret(instr_val, ret_val, ty),
-                Instruction::StateLoad { .. } => todo!(),
-                Instruction::StateStore { .. } => todo!(),
+                Instruction::StateLoad { load_val, key } => check!(
+                    self.compile_state_access(instr_val, load_val, key, StateAccessKind::Load),
+                    return err(warnings, errors),
+                    warnings,
+                    errors
+                ),
+                Instruction::StateStore { stored_val, key } => check!(
+                    self.compile_state_access(instr_val, stored_val, key, StateAccessKind::Store),
+                    return err(warnings, errors),
+                    warnings,
+                    errors
+                ),
                 Instruction::Store {
                     dst_val,
                     stored_val,
@@ -579,6 +765,96 @@ impl<'ir> AsmBuilder<'ir> {
         ok((), warnings, errors)
     }
 
+    fn compile_binary_op(
+        &mut self,
+        instr_val: &Value,
+        op: &BinaryOpKind,
+        arg1: &Value,
+        arg2: &Value,
+    ) -> CompileResult<()> {
+        if let Some(size_in_words) = self.wide_uint_size_in_words(arg1) {
+            let wide_op = match op {
+                BinaryOpKind::Add => WideIntegerOp::Add,
+                BinaryOpKind::Sub => WideIntegerOp::Sub,
+                BinaryOpKind::Mul => WideIntegerOp::Mul,
+                BinaryOpKind::Div => WideIntegerOp::Div,
+                BinaryOpKind::Mod => WideIntegerOp::Mod,
+                BinaryOpKind::And
+                | BinaryOpKind::Or
+                | BinaryOpKind::Xor
+                | BinaryOpKind::Shl
+                | BinaryOpKind::Shr => {
+                    return err(
+                        Vec::new(),
+                        vec![CompileError::Internal(
+                            "Bitwise operations are not supported for integers wider than 64 bits.",
+                            instr_val
+                                .get_span(self.context)
+                                .unwrap_or_else(Self::empty_span),
+                        )],
+                    );
+                }
+            };
+            self.compile_wide_uint_op(instr_val, wide_op, size_in_words, arg1, arg2);
+            return ok((), Vec::new(), Vec::new());
+        }
+        let val1_reg = self.value_to_register(arg1);
+        let val2_reg = self.value_to_register(arg2);
+        let res_reg = self.reg_seqr.next();
+        let opcode = match op {
+            BinaryOpKind::Add => VirtualOp::ADD(res_reg.clone(), val1_reg, val2_reg),
+            BinaryOpKind::Sub => VirtualOp::SUB(res_reg.clone(), val1_reg, val2_reg),
+            BinaryOpKind::Mul => VirtualOp::MUL(res_reg.clone(), val1_reg, val2_reg),
+            BinaryOpKind::Div => VirtualOp::DIV(res_reg.clone(), val1_reg, val2_reg),
+            BinaryOpKind::Mod => VirtualOp::MOD(res_reg.clone(), val1_reg, val2_reg),
+            BinaryOpKind::And => VirtualOp::AND(res_reg.clone(), val1_reg, val2_reg),
+            BinaryOpKind::Or => VirtualOp::OR(res_reg.clone(), val1_reg, val2_reg),
+            BinaryOpKind::Xor => VirtualOp::XOR(res_reg.clone(), val1_reg, val2_reg),
+            BinaryOpKind::Shl => VirtualOp::SLL(res_reg.clone(), val1_reg, val2_reg),
+            BinaryOpKind::Shr => VirtualOp::SRL(res_reg.clone(), val1_reg, val2_reg),
+        };
+        self.bytecode.push(Op {
+            opcode: Either::Left(opcode),
+            comment: String::new(),
+            owning_span: instr_val.get_span(self.context),
+        });
+        self.reg_map.insert(*instr_val, res_reg);
+        ok((), Vec::new(), Vec::new())
+    }
+
+    /// Integers wider than 64 bits are passed around by address, like b256, and their arithmetic
+    /// and comparisons are expanded into sequences of word operations.
+    fn wide_uint_size_in_words(&self, value: &Value) -> Option<u64> {
+        match value.get_type(self.context) {
+            Some(Type::Uint(nbits)) if nbits > 64 => Some(nbits as u64 / 64),
+            _otherwise => None,
+        }
+    }
+
+    fn compile_wide_uint_op(
+        &mut self,
+        instr_val: &Value,
+        op: WideIntegerOp,
+        size_in_words: u64,
+        lhs_value: &Value,
+        rhs_value: &Value,
+    ) {
+        let lhs_reg = self.value_to_register(lhs_value);
+        let rhs_reg = self.value_to_register(rhs_value);
+        let res_reg = self.reg_seqr.next();
+        let mut ops = convert_wide_integer_op_to_asm(
+            op,
+            size_in_words,
+            &lhs_reg,
+            &rhs_reg,
+            &res_reg,
+            &mut self.reg_seqr,
+            instr_val.get_span(self.context),
+        );
+        self.bytecode.append(&mut ops);
+        self.reg_map.insert(*instr_val, res_reg);
+    }
+
     fn compile_branch(&mut self, from_block: &Block, to_block: &Block) {
         self.compile_branch_to_phi_value(from_block, to_block);
 
@@ -586,6 +862,242 @@ impl<'ir> AsmBuilder<'ir> {
         self.bytecode.push(Op::jump_to_label(label));
     }
 
+    fn compile_call(&mut self, instr_val: &Value, function: &Function, args: &[Value]) {
+        let span = instr_val.get_span(self.context);
+
+        // If the callee may call the function making this call then it would overwrite the
+        // registers of this function, so they're saved to the stack around the call.
+        let is_recursive = self.call_is_recursive(&[*function]);
+        if is_recursive {
+            self.bytecode.push(self.save_registers());
+        }
+        let arg_regs = self.copy_args_to_temporaries(args);
+        let res_reg = self.reg_seqr.next();
+        self.compile_call_to(*function, &arg_regs, &res_reg, span);
+        if is_recursive {
+            self.bytecode.push(Op::restore_registers());
+        }
+        self.reg_map.insert(*instr_val, res_reg);
+    }
+
+    // A function value is the index of the function in the program's function table.  The VM can
+    // only jump to an address given as an immediate, so the target is compared against the index
+    // of each callee in turn to jump to the entry of the one it refers to, using the same calling
+    // convention as a direct call.  Each callee is compiled once, however many call sites it has.
+    //
+    //     jnei $target $index_0 next_0
+    //     <call callee 0>
+    //     ji end
+    // next_0:
+    //     ...
+    // next_n-1:
+    //     <call callee n>
+    // end:
+    //
+    // The last callee is called for any index which didn't match the others.
+    fn compile_call_indirect(
+        &mut self,
+        instr_val: &Value,
+        target: &Value,
+        callees: &[(u64, Function)],
+        args: &[Value],
+    ) {
+        let span = instr_val.get_span(self.context);
+        let callee_fns = callees.iter().map(|(_, f)| *f).collect::<Vec<_>>();
+        let is_recursive = self.call_is_recursive(&callee_fns);
+        if is_recursive {
+            self.bytecode.push(self.save_registers());
+        }
+        let target_reg = self.value_to_register(target);
+        let arg_regs = self.copy_args_to_temporaries(args);
+        let res_reg = self.reg_seqr.next();
+        let end_label = self.reg_seqr.get_label();
+        if let Some(((_, last_callee), other_callees)) = callees.split_last() {
+            for (idx, callee) in other_callees {
+                let idx_reg = self.reg_seqr.next();
+                let next_label = self.reg_seqr.get_label();
+                self.bytecode.push(Op {
+                    opcode: Either::Left(VirtualOp::ADDI(
+                        idx_reg.clone(),
+                        VirtualRegister::Constant(ConstantRegister::Zero),
+                        VirtualImmediate12::new_unchecked(
+                            *idx,
+                            "Programs with more than 2^12 functions used as values are \
+                             unsupported right now",
+                        ),
+                    )),
+                    comment: format!("index of {}", callee.get_name(self.context)),
+                    owning_span: span.clone(),
+                });
+                self.bytecode.push(Op::jump_if_not_equal(
+                    target_reg.clone(),
+                    idx_reg,
+                    next_label.clone(),
+                ));
+                self.compile_call_to(*callee, &arg_regs, &res_reg, span.clone());
+                self.bytecode.push(Op::jump_to_label(end_label.clone()));
+                self.bytecode.push(Op::unowned_jump_label(next_label));
+            }
+            self.compile_call_to(*last_callee, &arg_regs, &res_reg, span);
+        }
+        self.bytecode.push(Op::unowned_jump_label_comment(
+            end_label,
+            "end of indirect call",
+        ));
+        if is_recursive {
+            self.bytecode.push(Op::restore_registers());
+        }
+        self.reg_map.insert(*instr_val, res_reg);
+    }
+
+    // Whether calling any of `callees` may lead back to the function being compiled.
+    fn call_is_recursive(&self, callees: &[Function]) -> bool {
+        match self.current_function {
+            Some(current_function) => callees
+                .iter()
+                .any(|callee| self.calls_reach(*callee, current_function)),
+            None => false,
+        }
+    }
+
+    // Marks the start of a recursive call, around which the registers of the function being
+    // compiled are saved.  Its args and return address are saved whether or not they're live
+    // across the call, as a later call to it may set them again before they're used.
+    fn save_registers(&self) -> Op {
+        let registers = match self.current_function {
+            Some(function) => {
+                let called = self.called_function(function);
+                called
+                    .arg_regs
+                    .iter()
+                    .cloned()
+                    .chain(std::iter::once(called.ret_addr_reg.clone()))
+                    .collect()
+            }
+            None => Vec::new(),
+        };
+        Op::save_registers(registers)
+    }
+
+    // The args must all be set simultaneously, since an arg may be passed in place of another in
+    // a recursive call.  So the values are copied to temporaries first.
+    fn copy_args_to_temporaries(&mut self, args: &[Value]) -> Vec<VirtualRegister> {
+        args.iter()
+            .map(|arg| {
+                let arg_reg = self.value_to_register(arg);
+                let tmp_reg = self.reg_seqr.next();
+                self.bytecode
+                    .push(Op::unowned_register_move(tmp_reg.clone(), arg_reg));
+                tmp_reg
+            })
+            .collect()
+    }
+
+    // Jump to the entry of `function` with `args` in its arg registers, and move its return value
+    // into `res_reg` once it has returned.  Saving the registers of a recursive call is left to
+    // the caller.
+    fn compile_call_to(
+        &mut self,
+        function: Function,
+        args: &[VirtualRegister],
+        res_reg: &VirtualRegister,
+        span: Option<Span>,
+    ) {
+        let name = function.get_name(self.context).to_owned();
+
+        // The callee is compiled later, by compile_called_functions(), on its first call.
+        if !self
+            .called_functions
+            .iter()
+            .any(|called| called.function == function)
+        {
+            let arg_regs = function
+                .args_iter(self.context)
+                .map(|(_, arg_val)| self.value_to_register(arg_val))
+                .collect();
+            let called = CalledFunction {
+                function,
+                entry_label: self.reg_seqr.get_label(),
+                arg_regs,
+                ret_reg: self.reg_seqr.next(),
+                ret_addr_reg: self.reg_seqr.next(),
+                return_dispatch_label: self.reg_seqr.get_label(),
+                return_labels: Vec::new(),
+                compiled: false,
+            };
+            self.called_functions.push(called);
+        }
+        let return_label = self.reg_seqr.get_label();
+        let (entry_label, arg_regs, ret_reg, ret_addr_reg, call_site) = {
+            let called = self.called_function_mut(function);
+            called.return_labels.push(return_label.clone());
+            (
+                called.entry_label.clone(),
+                called.arg_regs.clone(),
+                called.ret_reg.clone(),
+                called.ret_addr_reg.clone(),
+                called.return_labels.len() as u64 - 1,
+            )
+        };
+
+        for (arg_reg, tmp_reg) in arg_regs.into_iter().zip(args) {
+            self.bytecode
+                .push(Op::unowned_register_move(arg_reg, tmp_reg.clone()));
+        }
+        self.bytecode.push(call_site_index_op(
+            ret_addr_reg,
+            call_site,
+            &mut self.data_section,
+            span.clone(),
+            "set return address",
+        ));
+        self.bytecode.push(Op::jump_to_label_comment(
+            entry_label,
+            format!("call {}", name),
+        ));
+        self.bytecode.push(Op::unowned_jump_label(return_label));
+
+        // The return value is moved out of the callee's register before the registers of the
+        // caller are restored, which may include it.
+        self.bytecode.push(Op {
+            opcode: Either::Left(VirtualOp::MOVE(res_reg.clone(), ret_reg)),
+            comment: format!("return value from {}", name),
+            owning_span: span,
+        });
+    }
+
+    fn compile_cmp(
+        &mut self,
+        instr_val: &Value,
+        pred: &Predicate,
+        lhs_value: &Value,
+        rhs_value: &Value,
+    ) {
+        if let Some(size_in_words) = self.wide_uint_size_in_words(lhs_value) {
+            let wide_op = match pred {
+                Predicate::Equal => WideIntegerOp::Eq,
+                Predicate::LessThan => WideIntegerOp::Lt,
+                Predicate::GreaterThan => WideIntegerOp::Gt,
+            };
+            self.compile_wide_uint_op(instr_val, wide_op, size_in_words, lhs_value, rhs_value);
+            return;
+        }
+        let lhs_reg = self.value_to_register(lhs_value);
+        let rhs_reg = self.value_to_register(rhs_value);
+        let res_reg = self.reg_seqr.next();
+        let opcode = match pred {
+            Predicate::Equal => VirtualOp::EQ(res_reg.clone(), lhs_reg, rhs_reg),
+            Predicate::LessThan => VirtualOp::LT(res_reg.clone(), lhs_reg, rhs_reg),
+            Predicate::GreaterThan => VirtualOp::GT(res_reg.clone(), lhs_reg, rhs_reg),
+        };
+        self.bytecode.push(Op {
+            opcode: Either::Left(opcode),
+            comment: String::new(),
+            owning_span: instr_val.get_span(self.context),
+        });
+        self.reg_map.insert(*instr_val, res_reg);
+    }
+
     fn compile_conditional_branch(
         &mut self,
         cond_value: &Value,
@@ -593,28 +1105,92 @@ impl<'ir> AsmBuilder<'ir> {
         true_block: &Block,
         false_block: &Block,
     ) {
-        self.compile_branch_to_phi_value(from_block, true_block);
-        self.compile_branch_to_phi_value(from_block, false_block);
+        // Usually the phi values for both destinations can be set before branching.  But if a
+        // destination is a loop header for this block then its phis may still be live along the
+        // other edge, and they may only be set once we know we're taking that edge.
+        let true_split = self.needs_split_edge(from_block, true_block);
+        let false_split = self.needs_split_edge(from_block, false_block);
+        if !true_split {
+            self.compile_branch_to_phi_value(from_block, true_block);
+        }
+        if !false_split {
+            self.compile_branch_to_phi_value(from_block, false_block);
+        }
 
         let cond_reg = self.value_to_register(cond_value);
 
         let false_label = self.block_to_label(false_block);
+        let split_label = if false_split {
+            Some(self.reg_seqr.get_label())
+        } else {
+            None
+        };
         self.bytecode.push(Op::jump_if_not_equal(
             cond_reg,
             VirtualRegister::Constant(ConstantRegister::One),
-            false_label,
+            split_label.clone().unwrap_or_else(|| false_label.clone()),
         ));
 
+        if true_split {
+            self.compile_branch_to_phi_value(from_block, true_block);
+        }
         let true_label = self.block_to_label(true_block);
         self.bytecode.push(Op::jump_to_label(true_label));
+
+        if let Some(split_label) = split_label {
+            self.bytecode.push(Op::unowned_jump_label(split_label));
+            self.compile_branch_to_phi_value(from_block, false_block);
+            self.bytecode.push(Op::jump_to_label(false_label));
+        }
+    }
+
+    fn needs_split_edge(&self, from_block: &Block, to_block: &Block) -> bool {
+        if to_block
+            .get_phi_vals_coming_from(self.context, from_block)
+            .is_empty()
+        {
+            return false;
+        }
+
+        // Search for a path from `to_block` back to `from_block`.
+        let mut visited = HashSet::new();
+        let mut worklist = vec![*to_block];
+        while let Some(block) = worklist.pop() {
+            if block == *from_block {
+                return true;
+            }
+            if visited.insert(block) {
+                worklist.append(&mut block.successors(self.context));
+            }
+        }
+        false
     }
 
     fn compile_branch_to_phi_value(&mut self, from_block: &Block, to_block: &Block) {
-        if let Some(local_val) = to_block.get_phi_val_coming_from(self.context, from_block) {
-            let local_reg = self.value_to_register(&local_val);
-            let phi_reg = self.value_to_register(&to_block.get_phi(self.context));
+        let phi_vals = to_block.get_phi_vals_coming_from(self.context, from_block);
+        if let [(phi_val, local_val)] = phi_vals.as_slice() {
+            let local_reg = self.value_to_register(local_val);
+            let phi_reg = self.value_to_register(phi_val);
             self.bytecode
                 .push(Op::unowned_register_move(phi_reg, local_reg));
+        } else {
+            // The phis must all be set simultaneously, since a value passed to one phi may be
+            // another phi in the same block.  So copy the values to temporaries first.
+            let tmp_regs = phi_vals
+                .iter()
+                .map(|(_, local_val)| {
+                    let local_reg = self.value_to_register(local_val);
+                    let tmp_reg = self.reg_seqr.next();
+                    self.bytecode
+                        .push(Op::unowned_register_move(tmp_reg.clone(), local_reg));
+                    tmp_reg
+                })
+                .collect::<Vec<_>>();
+            for ((phi_val, _), tmp_reg) in phi_vals.iter().zip(tmp_regs) {
+                let phi_reg = self.value_to_register(phi_val);
+                self.bytecode
+                    .push(Op::unowned_register_move(phi_reg, tmp_reg));
+            }
         }
     }
 
@@ -637,10 +1213,12 @@ impl<'ir> AsmBuilder<'ir> {
         let instr_reg = self.reg_seqr.next();
         let elem_size = self.ir_type_size_in_bytes(&ty.get_elem_type(self.context).unwrap());
         if elem_size <= 8 {
+            // The index may still be live, so the offset goes in a register of its own.
+            let elem_index_offs_reg = self.reg_seqr.next();
             self.bytecode.push(Op {
                 opcode: Either::Left(VirtualOp::MULI(
-                    index_reg.clone(),
-                    index_reg.clone(),
+                    elem_index_offs_reg.clone(),
+                    index_reg,
                     VirtualImmediate12 { value: 8 },
                 )),
                 comment: "extract_element relative offset".into(),
@@ -648,7 +1226,11 @@ impl<'ir> AsmBuilder<'ir> {
             });
             let elem_offs_reg = self.reg_seqr.next();
             self.bytecode.push(Op {
-                opcode: Either::Left(VirtualOp::ADD(elem_offs_reg.clone(), base_reg, index_reg)),
+                opcode: Either::Left(VirtualOp::ADD(
+                    elem_offs_reg.clone(),
+                    base_reg,
+                    elem_index_offs_reg,
+                )),
                 comment: "extract_element absolute offset".into(),
                 owning_span: instr_val.get_span(self.context),
             });
@@ -859,10 +1441,12 @@ impl<'ir> AsmBuilder<'ir> {
 
         let elem_size = self.ir_type_size_in_bytes(&ty.get_elem_type(self.context).unwrap());
         if elem_size <= 8 {
+            // The index may still be live, so the offset goes in a register of its own.
+            let elem_index_offs_reg = self.reg_seqr.next();
             self.bytecode.push(Op {
                 opcode: Either::Left(VirtualOp::MULI(
-                    index_reg.clone(),
-                    index_reg.clone(),
+                    elem_index_offs_reg.clone(),
+                    index_reg,
                     VirtualImmediate12 { value: 8 },
                 )),
                 comment: "insert_element relative offset".into(),
@@ -873,7 +1457,7 @@ impl<'ir> AsmBuilder<'ir> {
                 opcode: Either::Left(VirtualOp::ADD(
                     elem_offs_reg.clone(),
                     base_reg.clone(),
-                    index_reg,
+                    elem_index_offs_reg,
                 )),
                 comment: "insert_element absolute offset".into(),
                 owning_span: instr_val.get_span(self.context),
@@ -1141,7 +1725,26 @@ impl<'ir> AsmBuilder<'ir> {
     }
 
     fn compile_ret(&mut self, instr_val: &Value, ret_val: &Value, ret_type: &Type) {
-        if ret_type == &Type::Unit {
+        if let Some(function) = self.current_function {
+            // A called function puts its return value in its return register and jumps back to
+            // the call site.
+            let (ret_reg, return_dispatch_label) = {
+                let called = self.called_function(function);
+                (called.ret_reg.clone(), called.return_dispatch_label.clone())
+            };
+            if ret_type != &Type::Unit {
+                let val_reg = self.value_to_register(ret_val);
+                self.bytecode.push(Op {
+                    opcode: Either::Left(VirtualOp::MOVE(ret_reg, val_reg)),
+                    comment: "set return value".into(),
+                    owning_span: instr_val.get_span(self.context),
+                });
+            }
+            self.bytecode.push(Op::jump_to_label_comment(
+                return_dispatch_label,
+                format!("return from {}", function.get_name(self.context)),
+            ));
+        } else if ret_type == &Type::Unit {
             // Unit returns should always be zero, although because they can be omitted from
             // functions, the register is sometimes uninitialized. Manually return zero in this
             // case.
@@ -1185,6 +1788,64 @@ impl<'ir> AsmBuilder<'ir> {
         }
     }
 
+    fn compile_state_access(
+        &mut self,
+        instr_val: &Value,
+        val: &Value,
+        key: &Value,
+        kind: StateAccessKind,
+    ) -> CompileResult<()> {
+        let ptr = self.resolve_ptr(val);
+        if ptr.value.is_none() {
+            return ptr.map(|_| ());
+        }
+        let ptr = ptr.value.unwrap();
+
+        // Word sized locals live in registers, and `get_ptr` resolves to that register, so
+        // SRW/SWW can use it directly.  B256s live in memory so `get_ptr` resolves to their
+        // address, which is what SRWQ/SWWQ expect.
+        let is_quad = match ptr.get_type(self.context) {
+            Type::Unit | Type::Bool | Type::Uint(0..=64) => false,
+            Type::B256 => true,
+            _otherwise => {
+                return err(
+                    Vec::new(),
+                    vec![CompileError::Internal(
+                        "Storage access is only supported for word sized types and b256.",
+                        instr_val
+                            .get_span(self.context)
+                            .unwrap_or_else(Self::empty_span),
+                    )],
+                );
+            }
+        };
+        let val_reg = self.value_to_register(val);
+        // The key is always a b256, so this is the address of the key.
+        let key_reg = self.value_to_register(key);
+        let (opcode, comment) = match (kind, is_quad) {
+            (StateAccessKind::Load, false) => {
+                (VirtualOp::SRW(val_reg, key_reg), "load word from storage")
+            }
+            (StateAccessKind::Load, true) => (
+                VirtualOp::SRWQ(val_reg, key_reg),
+                "load quad word from storage",
+            ),
+            (StateAccessKind::Store, false) => {
+                (VirtualOp::SWW(key_reg, val_reg), "store word to storage")
+            }
+            (StateAccessKind::Store, true) => (
+                VirtualOp::SWWQ(key_reg, val_reg),
+                "store quad word to storage",
+            ),
+        };
+        self.bytecode.push(Op {
+            opcode: Either::Left(opcode),
+            comment: comment.into(),
+            owning_span: instr_val.get_span(self.context),
+        });
+        ok((), Vec::new(), Vec::new())
+    }
+
     fn compile_store(
         &mut self,
         instr_val: &Value,
@@ -1435,6 +2096,7 @@ impl<'ir> AsmBuilder<'ir> {
                             | ConstantValue::Unit
                             | ConstantValue::Bool(_)
                             | ConstantValue::Uint(_)
+                            | ConstantValue::WideUint(_)
                             | ConstantValue::B256(_)
                             | ConstantValue::String(_) => {
                                 // Get the constant into the namespace.
@@ -1530,6 +2192,7 @@ impl<'ir> AsmBuilder<'ir> {
             ConstantValue::Unit => 8,
             ConstantValue::Bool(_) => 8,
             ConstantValue::Uint(_) => 8,
+            ConstantValue::WideUint(_) => self.ir_type_size_in_bytes(&constant.ty),
             ConstantValue::B256(_) => 32,
             ConstantValue::String(s) => s.len() as u64, // String::len() returns the byte size, not char count.
             ConstantValue::Array(elems) => {
@@ -1561,6 +2224,7 @@ impl<'ir> AsmBuilder<'ir> {
             ConstantValue::Unit
             | ConstantValue::Bool(_)
             | ConstantValue::Uint(_)
+            | ConstantValue::WideUint(_)
             | ConstantValue::B256(_) => {
                 // Get the constant into the namespace.
                 let lit = ir_constant_to_ast_literal(constant);
@@ -1574,9 +2238,10 @@ impl<'ir> AsmBuilder<'ir> {
                     owning_span: span.clone(),
                 });
 
-                // Write the initialiser to memory.  Most Literals are 1 word, B256 is 32 bytes and
-                // needs to use a MCP instruction.
-                if matches!(lit, Literal::B256(_)) {
+                // Write the initialiser to memory.  Most Literals are 1 word, B256 and the wide
+                // integers are bigger and need to use a MCP instruction.
+                let lit_size_in_words = size_bytes_in_words!(self.constant_size_in_bytes(constant));
+                if lit_size_in_words > 1 {
                     let offs_reg = self.reg_seqr.next();
                     if offs_in_words * 8 > crate::asm_generation::compiler_constants::TWELVE_BITS {
                         self.number_to_reg(offs_in_words * 8, &offs_reg, span.clone());
@@ -1606,13 +2271,15 @@ impl<'ir> AsmBuilder<'ir> {
                         opcode: Either::Left(VirtualOp::MCPI(
                             offs_reg,
                             init_reg,
-                            VirtualImmediate12 { value: 32 },
+                            VirtualImmediate12 {
+                                value: (lit_size_in_words * 8) as u16,
+                            },
                         )),
                         comment: "initialise aggregate field".into(),
                         owning_span: span,
                     });
 
-                    4 // 32 bytes is 4 words.
+                    lit_size_in_words
                 } else {
                     if offs_in_words > crate::asm_generation::compiler_constants::TWELVE_BITS {
                         let offs_reg = self.reg_seqr.next();
@@ -1677,6 +2344,46 @@ impl<'ir> AsmBuilder<'ir> {
         }
     }
 
+    fn called_function(&self, function: Function) -> &CalledFunction {
+        self.called_functions
+            .iter()
+            .find(|called| called.function == function)
+            .expect("Called functions are registered at their first call.")
+    }
+
+    fn called_function_mut(&mut self, function: Function) -> &mut CalledFunction {
+        self.called_functions
+            .iter_mut()
+            .find(|called| called.function == function)
+            .expect("Called functions are registered at their first call.")
+    }
+
+    // Whether a call to `from` may lead to a call to `to`.
+    fn calls_reach(&self, from: Function, to: Function) -> bool {
+        let mut visited = HashSet::new();
+        let mut worklist = vec![from];
+        while let Some(caller) = worklist.pop() {
+            for (_, ins) in caller.instruction_iter(self.context) {
+                let callees = match &self.context.values[ins.0].value {
+                    ValueDatum::Instruction(Instruction::Call(callee, _)) => vec![*callee],
+                    ValueDatum::Instruction(Instruction::CallIndirect { callees, .. }) => {
+                        callees.iter().map(|(_, f)| *f).collect()
+                    }
+                    _ => Vec::new(),
+                };
+                for callee in callees {
+                    if callee == to {
+                        return true;
+                    }
+                    if visited.insert(callee) {
+                        worklist.push(callee);
+                    }
+                }
+            }
+        }
+        false
+    }
+
     fn block_to_label(&mut self, block: &Block) -> Label {
         match self.label_map.get(block) {
             Some(label) => label.clone(),
@@ -1776,7 +2483,8 @@ impl<'ir> AsmBuilder<'ir> {
 
     fn ir_type_size_in_bytes(&mut self, ty: &Type) -> u64 {
         match ty {
-            Type::Unit | Type::Bool | Type::Uint(_) => 8,
+            Type::Unit | Type::Bool | Type::Uint(0..=64) => 8,
+            Type::Uint(nbits) => *nbits as u64 / 8,
             Type::B256 => 32,
             Type::String(n) => *n,
             Type::Array(aggregate) | Type::Struct(aggregate) => {
@@ -1804,6 +2512,14 @@ fn ir_constant_to_ast_literal(constant: &Constant) -> Literal {
         ConstantValue::Unit => Literal::U64(0), // No unit.
         ConstantValue::Bool(b) => Literal::Boolean(*b),
         ConstantValue::Uint(n) => Literal::U64(*n),
+        ConstantValue::WideUint(bs) => match constant.ty {
+            Type::Uint(128) => {
+                let mut low_bytes = [0; 16];
+                low_bytes.copy_from_slice(&bs[16..]);
+                Literal::U128(u128::from_be_bytes(low_bytes))
+            }
+            _otherwise => Literal::U256(*bs),
+        },
         ConstantValue::B256(bs) => Literal::B256(*bs),
         ConstantValue::String(_) => Literal::String(crate::span::Span {
             span: pest::Span::new(
@@ -1872,7 +2588,10 @@ mod tests {
                 print_intermediate_asm: false,
                 print_finalized_asm: false,
                 print_ir: false,
+                ir_pipeline: "O1".to_owned(),
+                time_ir_passes: false,
                 generated_names: std::sync::Arc::new(std::sync::Mutex::new(vec![])),
+                source_overrides: Default::default(),
             },
         );
 
@@ -0,0 +1,481 @@
+//! Functions which are called rather than inlined at each call site.
+//!
+//! A called function is compiled once, after the entry points of the program, and is jumped to
+//! from each call site. The calling convention is:
+//! * Each parameter and the return value are held in a register of their own for the function.
+//! * The caller moves the index of its call site into the return address register of the
+//!   function, which the function compares against each call site to jump back after its body.
+//! * When the call may lead back to the function making it, the registers live across the call
+//!   and the parameters and return address of the function making it are saved to a frame on the
+//!   stack around it, see
+//!   [save_registers_across_calls](super::register_allocator::save_registers_across_calls).
+//!
+//! Calls through a function pointer use the same convention, jumping to the entry of whichever
+//! candidate the pointer refers to.
+use std::collections::HashMap;
+
+use super::{
+    compiler_constants::TWELVE_BITS, convert_code_block_to_asm, convert_expression_to_asm,
+    AsmNamespace, DataSection, RegisterSequencer,
+};
+use crate::{
+    asm_lang::{virtual_register::*, Label, Op, VirtualImmediate12, VirtualOp},
+    error::*,
+    parse_tree::Literal,
+    semantic_analysis::{ast_node::TypedCodeBlock, FunctionKey, TypedExpression},
+    type_engine::{look_up_type_id, TypeId},
+    Ident, TypeInfo,
+};
+
+use sway_types::span::Span;
+
+#[derive(Clone, Debug)]
+struct CalledFunction {
+    key: FunctionKey,
+    name: Ident,
+    body: TypedCodeBlock,
+    entry_label: Label,
+    parameters: Vec<(Ident, VirtualRegister)>,
+    return_value: VirtualRegister,
+    return_address: VirtualRegister,
+    /// The label to jump back to for each call site, indexed by the value of the return address.
+    return_labels: Vec<Label>,
+    compiled: bool,
+}
+
+/// The functions called so far, in the order of their first call.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CalledFunctions {
+    functions: Vec<CalledFunction>,
+}
+
+impl CalledFunctions {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.functions.is_empty()
+    }
+
+    /// The registers set by each call to the function `key` before jumping to it, i.e. its
+    /// parameters and return address.
+    fn call_registers(&self, key: &FunctionKey) -> Vec<VirtualRegister> {
+        self.functions
+            .iter()
+            .filter(|function| function.key == *key)
+            .flat_map(|function| {
+                function
+                    .parameters
+                    .iter()
+                    .map(|(_, register)| register.clone())
+                    .chain(std::iter::once(function.return_address.clone()))
+            })
+            .collect()
+    }
+}
+
+/// Compiles a call to the function `key`, putting its return value in `return_register`. The
+/// function is registered to be compiled by [compile_called_functions] on its first call.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn convert_fn_call_to_asm(
+    key: FunctionKey,
+    decl_name: &Ident,
+    arguments: &[(Ident, TypedExpression)],
+    function_body: &TypedCodeBlock,
+    return_type: TypeId,
+    parent_namespace: &mut AsmNamespace,
+    return_register: &VirtualRegister,
+    register_sequencer: &mut RegisterSequencer,
+    span: Span,
+) -> CompileResult<Vec<Op>> {
+    let mut warnings = vec![];
+    let mut errors = vec![];
+    let mut asm_buf = vec![Op::new_comment(format!("{} fn call", decl_name.as_str()))];
+    let mut namespace = parent_namespace.clone();
+    let mut argument_registers = vec![];
+    for (_, arg) in arguments {
+        let arg_register = register_sequencer.next();
+        asm_buf.append(&mut check!(
+            convert_expression_to_asm(arg, &mut namespace, &arg_register, register_sequencer),
+            return err(warnings, errors),
+            warnings,
+            errors
+        ));
+        argument_registers.push(arg_register);
+    }
+    parent_namespace.leave_scope(namespace);
+
+    let parameter_names = arguments
+        .iter()
+        .map(|(name, _)| name.clone())
+        .collect::<Vec<_>>();
+    asm_buf.append(&mut convert_call_with_registers_to_asm(
+        key,
+        decl_name,
+        &parameter_names,
+        argument_registers,
+        function_body,
+        return_type,
+        parent_namespace,
+        return_register,
+        register_sequencer,
+        span,
+    ));
+    ok(asm_buf, warnings, errors)
+}
+
+/// Compiles a call to the function `key` with the value of each of its parameters, named
+/// `parameter_names`, already evaluated into `argument_registers`, putting its return value in
+/// `return_register`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn convert_call_with_registers_to_asm(
+    key: FunctionKey,
+    decl_name: &Ident,
+    parameter_names: &[Ident],
+    argument_registers: Vec<VirtualRegister>,
+    function_body: &TypedCodeBlock,
+    return_type: TypeId,
+    parent_namespace: &mut AsmNamespace,
+    return_register: &VirtualRegister,
+    register_sequencer: &mut RegisterSequencer,
+    span: Span,
+) -> Vec<Op> {
+    let mut asm_buf = vec![];
+    let function_ix = match parent_namespace
+        .called_functions
+        .functions
+        .iter()
+        .position(|function| function.key == key)
+    {
+        Some(ix) => ix,
+        None => {
+            let body = match parent_namespace.call_graph.body(&key) {
+                Some(body) if function_body.contents.is_empty() => body.clone(),
+                _ => function_body.clone(),
+            };
+            parent_namespace
+                .called_functions
+                .functions
+                .push(CalledFunction {
+                    key: key.clone(),
+                    name: decl_name.clone(),
+                    body,
+                    entry_label: register_sequencer.get_label(),
+                    parameters: parameter_names
+                        .iter()
+                        .map(|name| (name.clone(), register_sequencer.next()))
+                        .collect(),
+                    return_value: register_sequencer.next(),
+                    return_address: register_sequencer.next(),
+                    return_labels: vec![],
+                    compiled: false,
+                });
+            parent_namespace.called_functions.functions.len() - 1
+        }
+    };
+    let return_label = register_sequencer.get_label();
+    let function = &mut parent_namespace.called_functions.functions[function_ix];
+    let call_site = function.return_labels.len() as u64;
+    function.return_labels.push(return_label.clone());
+    let function = function.clone();
+
+    // a call which may lead back to the calling function would overwrite its registers. Those set
+    // by a call to the calling function are always saved, as a later call from it may set them
+    // again before they're used.
+    let saved_registers = match &parent_namespace.current_function {
+        Some(current_function) if parent_namespace.call_graph.reaches(&key, current_function) => {
+            Some(
+                parent_namespace
+                    .called_functions
+                    .call_registers(current_function),
+            )
+        }
+        _ => None,
+    };
+    let is_recursive = saved_registers.is_some();
+    if let Some(saved_registers) = saved_registers {
+        asm_buf.push(Op::save_registers(saved_registers));
+    }
+    for ((_, param_register), arg_register) in function.parameters.iter().zip(argument_registers) {
+        asm_buf.push(Op::register_move(
+            param_register.clone(),
+            arg_register,
+            span.clone(),
+        ));
+    }
+    asm_buf.push(call_site_index_op(
+        function.return_address.clone(),
+        call_site,
+        &mut parent_namespace.data_section,
+        Some(span.clone()),
+        "set return address",
+    ));
+    asm_buf.push(Op::jump_to_label_comment(
+        function.entry_label,
+        format!("call {}", decl_name.as_str()),
+    ));
+    asm_buf.push(Op::unowned_jump_label(return_label));
+
+    let returns_unit =
+        matches!(look_up_type_id(return_type), TypeInfo::Tuple(fields) if fields.is_empty());
+    if is_recursive {
+        // the return value is moved out of the function's register before the registers of the
+        // caller are restored, which may include it
+        let return_value = register_sequencer.next();
+        if !returns_unit {
+            asm_buf.push(Op::register_move(
+                return_value.clone(),
+                function.return_value,
+                span.clone(),
+            ));
+        }
+        asm_buf.push(Op::restore_registers());
+        if !returns_unit {
+            asm_buf.push(Op::register_move(
+                return_register.clone(),
+                return_value,
+                span,
+            ));
+        }
+    } else if !returns_unit {
+        asm_buf.push(Op::register_move(
+            return_register.clone(),
+            function.return_value,
+            span,
+        ));
+    }
+
+    asm_buf
+}
+
+/// Compiles the body of every function called from the code compiled so far with `namespace`, and
+/// of every function called from these in turn. `global_variables` are the variables visible from
+/// every function, i.e. the constants declared at the top level.
+pub(crate) fn compile_called_functions(
+    namespace: &mut AsmNamespace,
+    global_variables: &HashMap<Ident, VirtualRegister>,
+    register_sequencer: &mut RegisterSequencer,
+) -> CompileResult<Vec<Op>> {
+    let mut warnings = vec![];
+    let mut errors = vec![];
+    let mut bodies = vec![];
+    while let Some(function) = namespace
+        .called_functions
+        .functions
+        .iter_mut()
+        .find(|function| !function.compiled)
+    {
+        function.compiled = true;
+        let function = function.clone();
+
+        let mut asm_buf = vec![
+            Op::unowned_jump_label_comment(
+                function.entry_label.clone(),
+                format!("{} fn", function.name.as_str()),
+            ),
+            Op::debug_scope_begin(
+                function.name.as_str(),
+                Some(function.name.span().clone()),
+                None,
+            ),
+        ];
+        let mut function_namespace = namespace.clone();
+        function_namespace.variables = global_variables.clone();
+        function_namespace.loop_labels = vec![];
+        function_namespace.current_function = Some(function.key.clone());
+        for (name, register) in &function.parameters {
+            asm_buf.push(Op::debug_variable(
+                name.as_str(),
+                Some(name.span().clone()),
+                register.clone(),
+                None,
+            ));
+            function_namespace.insert_variable(name.clone(), register.clone());
+        }
+        asm_buf.append(&mut check!(
+            convert_code_block_to_asm(
+                &function.body,
+                &mut function_namespace,
+                register_sequencer,
+                Some(&function.return_value),
+            ),
+            return err(warnings, errors),
+            warnings,
+            errors
+        ));
+        asm_buf.push(Op::debug_scope_end());
+        namespace.leave_scope(function_namespace);
+        bodies.push((function.key, asm_buf));
+    }
+
+    // Jump back to the call site once the body is done. This is only known once every function
+    // body has been compiled, as calls may be made from any of them.
+    let mut asm_buf = vec![];
+    for (key, mut body) in bodies {
+        asm_buf.append(&mut body);
+        let function = namespace
+            .called_functions
+            .functions
+            .iter()
+            .find(|function| function.key == key)
+            .expect("compiled functions are registered");
+        let (last_label, labels) = function
+            .return_labels
+            .split_last()
+            .expect("functions are registered when they are called");
+        for (call_site, return_label) in labels.iter().enumerate() {
+            let call_site_register = register_sequencer.next();
+            let next_label = register_sequencer.get_label();
+            asm_buf.push(call_site_index_op(
+                call_site_register.clone(),
+                call_site as u64,
+                &mut namespace.data_section,
+                None,
+                format!("call site {}", call_site),
+            ));
+            asm_buf.push(Op::jump_if_not_equal(
+                function.return_address.clone(),
+                call_site_register,
+                next_label.clone(),
+            ));
+            asm_buf.push(Op::jump_to_label_comment(
+                return_label.clone(),
+                format!("return from {}", function.name.as_str()),
+            ));
+            asm_buf.push(Op::unowned_jump_label(next_label));
+        }
+        asm_buf.push(Op::jump_to_label_comment(
+            last_label.clone(),
+            format!("return from {}", function.name.as_str()),
+        ));
+    }
+
+    ok(asm_buf, warnings, errors)
+}
+
+/// Moves the index of `call_site` into `register`, either to set the return address of a call or
+/// to compare against it. An index too large for the immediate of `addi` is loaded from the data
+/// section instead.
+pub(crate) fn call_site_index_op(
+    register: VirtualRegister,
+    call_site: u64,
+    data_section: &mut DataSection,
+    owning_span: Option<Span>,
+    comment: impl Into<String>,
+) -> Op {
+    let opcode = if call_site <= TWELVE_BITS {
+        VirtualOp::ADDI(
+            register,
+            VirtualRegister::Constant(ConstantRegister::Zero),
+            VirtualImmediate12::new_unchecked(call_site, "checked above"),
+        )
+    } else {
+        VirtualOp::LWDataId(
+            register,
+            data_section.insert_data_value(&Literal::U64(call_site)),
+        )
+    };
+    Op {
+        opcode: either::Either::Left(opcode),
+        comment: comment.into(),
+        owning_span,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        asm_generation::{build_preamble, AbstractInstructionSet, SwayAsmSet},
+        source_map::SourceMap,
+    };
+
+    use fuel_tx::Transaction;
+    use fuel_vm::{
+        interpreter::Interpreter,
+        prelude::{MemoryStorage, ProgramState},
+    };
+
+    /// Runs a script which returns the index of `call_site` as moved into a register.
+    fn run_call_site_index(call_site: u64) -> ProgramState {
+        let mut register_sequencer = RegisterSequencer::new();
+        let mut data_section = DataSection::default();
+        let mut ops = build_preamble(&mut register_sequencer).to_vec();
+
+        let register = register_sequencer.next();
+        ops.push(call_site_index_op(
+            register.clone(),
+            call_site,
+            &mut data_section,
+            None,
+            "",
+        ));
+        ops.push(Op::unowned_new_with_comment(VirtualOp::RET(register), ""));
+
+        let mut source_map = SourceMap::new();
+        let script = SwayAsmSet::ScriptMain {
+            data_section,
+            program_section: AbstractInstructionSet { ops },
+        }
+        .remove_unnecessary_jumps()
+        .allocate_registers(&mut register_sequencer)
+        .optimize()
+        .0
+        .to_bytecode_mut(&mut source_map)
+        .value
+        .unwrap();
+
+        let tx = Transaction::script(
+            10,
+            fuel_tx::consts::MAX_GAS_PER_TX,
+            0,
+            0,
+            script,
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+        );
+        let mut interpreter = Interpreter::with_storage(MemoryStorage::default());
+        *interpreter.transact(tx).unwrap().state()
+    }
+
+    #[test]
+    fn small_call_site_index_is_an_immediate() {
+        let mut data_section = DataSection::default();
+        let op = call_site_index_op(
+            VirtualRegister::Constant(ConstantRegister::Zero),
+            TWELVE_BITS,
+            &mut data_section,
+            None,
+            "",
+        );
+        assert!(matches!(
+            op.opcode,
+            either::Either::Left(VirtualOp::ADDI(..))
+        ));
+        assert_eq!(
+            run_call_site_index(TWELVE_BITS),
+            ProgramState::Return(TWELVE_BITS)
+        );
+    }
+
+    #[test]
+    fn large_call_site_index_is_loaded_from_the_data_section() {
+        let mut data_section = DataSection::default();
+        let op = call_site_index_op(
+            VirtualRegister::Constant(ConstantRegister::Zero),
+            TWELVE_BITS + 1,
+            &mut data_section,
+            None,
+            "",
+        );
+        assert!(matches!(
+            op.opcode,
+            either::Either::Left(VirtualOp::LWDataId(..))
+        ));
+        for call_site in [TWELVE_BITS + 1, 100_000] {
+            assert_eq!(
+                run_call_site_index(call_site),
+                ProgramState::Return(call_site)
+            );
+        }
+    }
+}
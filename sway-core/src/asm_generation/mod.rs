@@ -1,6 +1,7 @@
 use std::{
     collections::{BTreeSet, HashMap},
     fmt,
+    rc::Rc,
 };
 
 use crate::semantic_analysis::ast_node::{TypedVariableDeclaration, VariableMutability};
@@ -10,13 +11,15 @@ use crate::{
     asm_lang::{
         allocated_ops::{AllocatedOp, AllocatedRegister},
         virtual_register::*,
-        Label, Op, OrganizationalOp, RealizedOp, VirtualImmediate12, VirtualImmediate24, VirtualOp,
+        DebugMarker, Label, Op, OrganizationalOp, RealizedOp, VirtualImmediate12,
+        VirtualImmediate24, VirtualOp,
     },
     error::*,
     parse_tree::Literal,
     semantic_analysis::{
-        ast_node::OwnedTypedStructField, read_module, TypedAstNode, TypedAstNodeContent,
-        TypedDeclaration, TypedFunctionDeclaration, TypedParseTree,
+        ast_node::OwnedTypedStructField, read_module, CallGraph, FunctionKey, FunctionTable,
+        TypedAstNode, TypedAstNodeContent, TypedDeclaration, TypedFunctionDeclaration,
+        TypedParseTree,
     },
     types::ResolvedType,
     BuildConfig, Ident, TypeInfo,
@@ -25,19 +28,28 @@ pub(crate) use expression::subfield::{convert_subfield_to_asm, get_subfields_for
 
 use either::Either;
 
+mod abi_switch;
 pub(crate) mod checks;
 pub(crate) mod compiler_constants;
+mod debug_info;
 mod declaration;
 mod expression;
 mod finalized_asm;
 pub(crate) mod from_ir;
+mod function_calls;
+mod peephole;
 pub(crate) mod register_allocator;
 mod register_sequencer;
 mod while_loop;
+mod wide_integer;
 
+use abi_switch::build_contract_abi_switch;
+use debug_info::DebugInfo;
 pub(crate) use declaration::*;
 pub(crate) use expression::*;
 pub use finalized_asm::FinalizedAsm;
+use function_calls::{compile_called_functions, convert_fn_call_to_asm, CalledFunctions};
+use peephole::PeepholeReport;
 pub(crate) use register_sequencer::*;
 
 use while_loop::convert_while_loop_to_asm;
@@ -103,6 +115,11 @@ pub struct AbstractInstructionSet {
 /// ops or labels. In this struct, they are all "realized" to offsets.
 pub struct RealizedAbstractInstructionSet {
     ops: Vec<RealizedOp>,
+    /// The debug markers which preceded the op at each index.
+    debug_markers: Vec<(usize, DebugMarker)>,
+    /// The indices of the ops before which registers are saved and restored around each
+    /// recursive call, and the registers saved whether or not they're live across it.
+    call_frames: Vec<(usize, usize, Vec<VirtualRegister>)>,
 }
 
 impl RealizedAbstractInstructionSet {
@@ -112,31 +129,86 @@ impl RealizedAbstractInstructionSet {
     /// the algorithm are thoroughly explained in register_allocator.rs.
     ///
     fn allocate_registers(self, register_sequencer: &mut RegisterSequencer) -> InstructionSet {
-        // Step 1: Liveness Analysis.
-        let live_out = register_allocator::liveness_analysis(&self.ops);
-
-        // Step 2: Construct the interference graph.
-        let (mut interference_graph, mut reg_to_node_ix) =
-            register_allocator::create_interference_graph(&self.ops, &live_out);
-
-        // Step 3: Remove redundant MOVE instructions using the interference graph.
-        let reduced_ops = register_allocator::coalesce_registers(
-            &self.ops,
-            &mut interference_graph,
-            &mut reg_to_node_ix,
-            register_sequencer,
-        );
+        let mut ops = self.ops;
+        // The index of each original op, and the register each coalesced register was replaced
+        // with, in the current list of ops. Debug information is resolved using these.
+        let mut op_map: Vec<usize> = (0..ops.len()).collect();
+        let mut reg_map: HashMap<VirtualRegister, VirtualRegister> = HashMap::new();
+        let mut spill_area = register_allocator::SpillArea::default();
+
+        // Step 0: Save the registers live across each recursive call, which the call would
+        // otherwise overwrite.
+        if !self.call_frames.is_empty() {
+            let (saving_ops, saving_op_map) = register_allocator::save_registers_across_calls(
+                &ops,
+                &self.call_frames,
+                register_sequencer,
+            );
+            remap_ops(&mut op_map, &saving_op_map, saving_ops.len());
+            ops = saving_ops;
+        }
 
-        // Step 4: Simplify - i.e. color the interference graph and return a stack that contains
-        // each colorable node and its neighbors.
-        let mut stack = register_allocator::color_interference_graph(
-            &mut interference_graph,
-            compiler_constants::NUM_ALLOCATABLE_REGISTERS,
-        );
+        let (reduced_ops, mut stack) = loop {
+            // Step 1: Liveness Analysis.
+            let live_out = register_allocator::liveness_analysis(&ops);
+
+            // Step 2: Construct the interference graph.
+            let (mut interference_graph, mut reg_to_node_ix) =
+                register_allocator::create_interference_graph(&ops, &live_out);
+
+            // Step 3: Remove redundant MOVE instructions using the interference graph.
+            let (reduced_ops, reduced_op_map, reduced_reg_map) =
+                register_allocator::coalesce_registers(
+                    &ops,
+                    &mut interference_graph,
+                    &mut reg_to_node_ix,
+                    register_sequencer,
+                );
+            remap_ops(&mut op_map, &reduced_op_map, reduced_ops.len());
+            for reg in reg_map.values_mut() {
+                if let Some(new_reg) = reduced_reg_map.get(reg) {
+                    *reg = new_reg.clone();
+                }
+            }
+            for (reg, new_reg) in reduced_reg_map {
+                reg_map.entry(reg).or_insert(new_reg);
+            }
+
+            // Step 4: Simplify - i.e. color the interference graph and return a stack that
+            // contains each colorable node and its neighbors.
+            match register_allocator::color_interference_graph(
+                &mut interference_graph,
+                compiler_constants::NUM_ALLOCATABLE_REGISTERS,
+                &spill_area.use_densities(&reduced_ops),
+            ) {
+                Ok(stack) => break (reduced_ops, stack),
+                // Step 4.5: Spill the registers which could not be colored to memory, and start
+                // over with the new ops.
+                Err(spilled) => {
+                    let (spilled_ops, spilled_op_map) = register_allocator::spill_registers(
+                        &reduced_ops,
+                        &spilled,
+                        &mut spill_area,
+                        register_sequencer,
+                    );
+                    remap_ops(&mut op_map, &spilled_op_map, spilled_ops.len());
+                    ops = spilled_ops;
+                }
+            }
+        };
 
         // Step 5: Use the stack to assign a register for each virtual register.
         let pool = register_allocator::assign_registers(&mut stack);
 
+        let debug_info = DebugInfo::new(
+            self.debug_markers,
+            &reduced_ops,
+            &op_map,
+            &reg_map,
+            &spill_area,
+            &pool,
+        );
+
         // Steph 6: Update all instructions to use the resulting register pool.
         let mut buf = vec![];
         for op in &reduced_ops {
@@ -147,7 +219,18 @@ impl RealizedAbstractInstructionSet {
             })
         }
 
-        InstructionSet { ops: buf }
+        InstructionSet {
+            ops: buf,
+            debug_info,
+        }
+    }
+}
+
+/// Updates the index of each op in `op_map` to its index in a new list of `len` ops, given the new
+/// index of each op in the previous list.
+fn remap_ops(op_map: &mut [usize], new_op_map: &[usize], len: usize) {
+    for ix in op_map {
+        *ix = new_op_map.get(*ix).copied().unwrap_or(len);
     }
 }
 
@@ -155,6 +238,7 @@ impl RealizedAbstractInstructionSet {
 #[derive(Clone)]
 pub struct InstructionSet {
     ops: Vec<AllocatedOp>,
+    debug_info: DebugInfo,
 }
 
 type Data = Literal;
@@ -168,10 +252,11 @@ impl AbstractInstructionSet {
                 ..
             } = self.ops[i]
             {
-                if let Op {
+                // debug markers don't end up as ops, so they don't separate a jump from its label
+                if let Some(Op {
                     opcode: Either::Right(OrganizationalOp::Label(ref label2)),
                     ..
-                } = self.ops[i + 1]
+                }) = self.ops[i + 1..].iter().find(|op| !op.is_debug_marker())
                 {
                     if label == label2 {
                         // this is a jump to the next line
@@ -235,7 +320,12 @@ impl AbstractInstructionSet {
                 | Either::Left(_) => {
                     counter += 1;
                 }
-                Either::Right(OrganizationalOp::Comment) => (),
+                Either::Right(OrganizationalOp::Comment)
+                | Either::Right(OrganizationalOp::Debug(_)) => (),
+                // the ops saving and restoring registers are added once registers are allocated,
+                // which updates the offsets
+                Either::Right(OrganizationalOp::SaveRegisters(_))
+                | Either::Right(OrganizationalOp::RestoreRegisters) => (),
                 Either::Right(OrganizationalOp::DataSectionOffsetPlaceholder) => {
                     // If the placeholder is 32 bits, this is 1. if 64, this should be 2. We use LW
                     // to load the data, which loads a whole word, so for now this is 2.
@@ -245,6 +335,9 @@ impl AbstractInstructionSet {
         }
 
         let mut realized_ops = vec![];
+        let mut debug_markers = vec![];
+        let mut call_frames = vec![];
+        let mut open_call_frames = vec![];
         for (
             ix,
             Op {
@@ -295,12 +388,28 @@ impl AbstractInstructionSet {
                             offset,
                         });
                     }
+                    OrganizationalOp::Debug(marker) => {
+                        debug_markers.push((realized_ops.len(), marker));
+                    }
+                    OrganizationalOp::SaveRegisters(registers) => {
+                        open_call_frames.push((realized_ops.len(), registers))
+                    }
+                    OrganizationalOp::RestoreRegisters => {
+                        let (save_ix, registers) = open_call_frames
+                            .pop()
+                            .expect("registers are only restored after being saved");
+                        call_frames.push((save_ix, realized_ops.len(), registers));
+                    }
                     OrganizationalOp::Comment => continue,
                     OrganizationalOp::Label(..) => continue,
                 },
             };
         }
-        RealizedAbstractInstructionSet { ops: realized_ops }
+        RealizedAbstractInstructionSet {
+            ops: realized_ops,
+            debug_markers,
+            call_frames,
+        }
     }
 }
 
@@ -318,7 +427,7 @@ pub(crate) struct RegisterPool {
 impl RegisterPool {
     fn init() -> Self {
         let reg_pool: Vec<RegisterAllocationStatus> = (0
-            // - 1 because we reserve the final register for the data_section begin
+            // the final registers are reserved by the compiler
             ..compiler_constants::NUM_ALLOCATABLE_REGISTERS)
             .map(|x| RegisterAllocationStatus {
                 reg: AllocatedRegister::Allocated(x),
@@ -421,6 +530,14 @@ impl fmt::Display for DataSection {
                 Literal::U16(num) => format!(".u16 {:#04x}", num),
                 Literal::U32(num) => format!(".u32 {:#04x}", num),
                 Literal::U64(num) => format!(".u64 {:#04x}", num),
+                Literal::U128(num) => format!(".u128 {:#04x}", num),
+                Literal::U256(b) => format!(
+                    ".u256 0x{}",
+                    b.iter()
+                        .map(|x| format!("{:02x}", x))
+                        .collect::<Vec<_>>()
+                        .join("")
+                ),
                 Literal::Numeric(num) => format!(".u64 {:#04x}", num),
                 Literal::Boolean(b) => format!(".bool {}", if *b { "0x01" } else { "0x00" }),
                 Literal::String(st) => format!(".str \"{}\"", st.as_str()),
@@ -563,6 +680,17 @@ impl fmt::Display for InstructionSet {
 pub(crate) struct AsmNamespace {
     data_section: DataSection,
     variables: HashMap<Ident, VirtualRegister>,
+    /// The functions which may be called through function pointers.
+    function_table: Rc<FunctionTable>,
+    /// The labels to jump to for `continue` and `break` in each loop being compiled, innermost
+    /// last.
+    loop_labels: Vec<(Label, Label)>,
+    /// The calls made between the functions of the program.
+    call_graph: Rc<CallGraph>,
+    /// The function being compiled, if it is called rather than inlined.
+    current_function: Option<FunctionKey>,
+    /// The functions which are called rather than inlined.
+    called_functions: CalledFunctions,
 }
 
 /// An address which refers to a value in the data section of the asm.
@@ -576,6 +704,19 @@ impl fmt::Display for DataId {
 }
 
 impl AsmNamespace {
+    pub(crate) fn new(function_table: Rc<FunctionTable>, call_graph: Rc<CallGraph>) -> Self {
+        AsmNamespace {
+            function_table,
+            call_graph,
+            ..Default::default()
+        }
+    }
+    /// Takes back the state shared by the whole program from `scope`, a namespace cloned from
+    /// this one to compile a nested scope.
+    pub(crate) fn leave_scope(&mut self, scope: AsmNamespace) {
+        self.data_section = scope.data_section;
+        self.called_functions = scope.called_functions;
+    }
     pub(crate) fn insert_variable(&mut self, var_name: Ident, register_location: VirtualRegister) {
         self.variables.insert(var_name, register_location);
     }
@@ -607,6 +748,8 @@ pub(crate) fn compile_ast_to_asm(
     let mut register_sequencer = RegisterSequencer::new();
     let mut warnings = vec![];
     let mut errors = vec![];
+    let function_table = Rc::new(FunctionTable::from_parse_tree(&ast));
+    let call_graph = Rc::new(CallGraph::from_parse_tree(&ast, &function_table));
     let (asm, _asm_namespace) = match ast {
         TypedParseTree::Script {
             main_function,
@@ -614,7 +757,7 @@ pub(crate) fn compile_ast_to_asm(
             declarations: _,
             ..
         } => {
-            let mut namespace: AsmNamespace = Default::default();
+            let mut namespace = AsmNamespace::new(function_table.clone(), call_graph.clone());
             let mut asm_buf = build_preamble(&mut register_sequencer).to_vec();
             // generate any const decls
             read_module(
@@ -648,13 +791,25 @@ pub(crate) fn compile_ast_to_asm(
                             errors
                         );
                         asm_buf.append(&mut buf);
+                        asm_buf.push(Op::debug_variable(
+                            name.as_str(),
+                            Some(name.span().clone()),
+                            return_register.clone(),
+                            None,
+                        ));
                         namespace.insert_variable(name.clone(), return_register);
                     }
                     ok((), warnings, errors)
                 },
                 ast_namespace,
             );
+            let global_variables = namespace.variables.clone();
             // start generating from the main function
+            asm_buf.push(Op::debug_scope_begin(
+                main_function.name.as_str(),
+                Some(main_function.name.span().clone()),
+                None,
+            ));
             let return_register = register_sequencer.next();
             let mut body = check!(
                 convert_code_block_to_asm(
@@ -680,6 +835,17 @@ pub(crate) fn compile_ast_to_asm(
                 warnings,
                 errors
             ));
+            asm_buf.push(Op::debug_scope_end());
+            asm_buf.append(&mut check!(
+                compile_called_functions(
+                    &mut namespace,
+                    &global_variables,
+                    &mut register_sequencer
+                ),
+                return err(warnings, errors),
+                warnings,
+                errors
+            ));
 
             (
                 SwayAsmSet::ScriptMain {
@@ -695,7 +861,7 @@ pub(crate) fn compile_ast_to_asm(
             declarations: _,
             ..
         } => {
-            let mut namespace: AsmNamespace = Default::default();
+            let mut namespace = AsmNamespace::new(function_table.clone(), call_graph.clone());
             let mut asm_buf = build_preamble(&mut register_sequencer).to_vec();
             read_module(
                 |ns| -> CompileResult<()> {
@@ -728,13 +894,25 @@ pub(crate) fn compile_ast_to_asm(
                             errors
                         );
                         asm_buf.append(&mut buf);
+                        asm_buf.push(Op::debug_variable(
+                            name.as_str(),
+                            Some(name.span().clone()),
+                            return_register.clone(),
+                            None,
+                        ));
                         namespace.insert_variable(name.clone(), return_register);
                     }
                     ok((), warnings, errors)
                 },
                 ast_namespace,
             );
+            let global_variables = namespace.variables.clone();
             // start generating from the main function
+            let mut main_buf = vec![Op::debug_scope_begin(
+                main_function.name.as_str(),
+                Some(main_function.name.span().clone()),
+                None,
+            )];
             let mut body = check!(
                 convert_code_block_to_asm(
                     &main_function.body,
@@ -746,7 +924,26 @@ pub(crate) fn compile_ast_to_asm(
                 warnings,
                 errors
             );
-            asm_buf.append(&mut body);
+            main_buf.append(&mut body);
+            main_buf.push(Op::debug_scope_end());
+            // the predicate ends with the main function, so the functions it calls are put
+            // before it
+            if !namespace.called_functions.is_empty() {
+                let main_label = register_sequencer.get_label();
+                asm_buf.push(Op::jump_to_label(main_label.clone()));
+                asm_buf.append(&mut check!(
+                    compile_called_functions(
+                        &mut namespace,
+                        &global_variables,
+                        &mut register_sequencer
+                    ),
+                    return err(warnings, errors),
+                    warnings,
+                    errors
+                ));
+                asm_buf.push(Op::unowned_jump_label(main_label));
+            }
+            asm_buf.append(&mut main_buf);
 
             (
                 SwayAsmSet::PredicateMain {
@@ -762,7 +959,7 @@ pub(crate) fn compile_ast_to_asm(
             declarations: _,
             ..
         } => {
-            let mut namespace: AsmNamespace = Default::default();
+            let mut namespace = AsmNamespace::new(function_table.clone(), call_graph.clone());
             let mut asm_buf = build_preamble(&mut register_sequencer).to_vec();
             read_module(
                 |ns| -> CompileResult<()> {
@@ -795,12 +992,19 @@ pub(crate) fn compile_ast_to_asm(
                             errors
                         );
                         asm_buf.append(&mut buf);
+                        asm_buf.push(Op::debug_variable(
+                            name.as_str(),
+                            Some(name.span().clone()),
+                            return_register.clone(),
+                            None,
+                        ));
                         namespace.insert_variable(name.clone(), return_register);
                     }
                     ok((), warnings, errors)
                 },
                 ast_namespace,
             );
+            let global_variables = namespace.variables.clone();
             let (selectors_and_labels, mut contract_asm) = check!(
                 compile_contract_to_selectors(abi_entries, &mut namespace, &mut register_sequencer),
                 return err(warnings, errors),
@@ -813,6 +1017,16 @@ pub(crate) fn compile_ast_to_asm(
                 selectors_and_labels,
             ));
             asm_buf.append(&mut contract_asm);
+            asm_buf.append(&mut check!(
+                compile_called_functions(
+                    &mut namespace,
+                    &global_variables,
+                    &mut register_sequencer
+                ),
+                return err(warnings, errors),
+                warnings,
+                errors
+            ));
 
             (
                 SwayAsmSet::ContractAbi {
@@ -829,13 +1043,14 @@ pub(crate) fn compile_ast_to_asm(
         println!("{}", asm);
     }
 
-    let finalized_asm = asm
+    let (finalized_asm, peephole_report) = asm
         .remove_unnecessary_jumps()
         .allocate_registers(&mut register_sequencer)
         .optimize();
 
     if build_config.print_finalized_asm {
         println!("{}", finalized_asm);
+        print!("{}", peephole_report);
     }
 
     check!(
@@ -957,14 +1172,16 @@ pub enum RegisterAllocatedAsmSet {
 }
 
 impl RegisterAllocatedAsmSet {
-    fn optimize(self) -> FinalizedAsm {
-        // TODO implement this -- noop for now
+    /// Runs the peephole passes over the program section, returning the finalized asm along with
+    /// the bytes saved by each pass.
+    fn optimize(self) -> (FinalizedAsm, PeepholeReport) {
         match self {
-            RegisterAllocatedAsmSet::Library => FinalizedAsm::Library,
+            RegisterAllocatedAsmSet::Library => (FinalizedAsm::Library, PeepholeReport::default()),
             RegisterAllocatedAsmSet::ScriptMain {
                 mut program_section,
                 data_section,
             } => {
+                let report = peephole::optimize(&mut program_section, &data_section);
                 // ensure there's an even number of ops so the
                 // data section offset is valid
                 if program_section.ops.len() & 1 != 0 {
@@ -974,15 +1191,19 @@ impl RegisterAllocatedAsmSet {
                         owning_span: None,
                     });
                 }
-                FinalizedAsm::ScriptMain {
-                    program_section,
-                    data_section,
-                }
+                (
+                    FinalizedAsm::ScriptMain {
+                        program_section,
+                        data_section,
+                    },
+                    report,
+                )
             }
             RegisterAllocatedAsmSet::PredicateMain {
                 mut program_section,
                 data_section,
             } => {
+                let report = peephole::optimize(&mut program_section, &data_section);
                 // ensure there's an even number of ops so the
                 // data section offset is valid
                 if program_section.ops.len() & 1 != 0 {
@@ -992,15 +1213,19 @@ impl RegisterAllocatedAsmSet {
                         owning_span: None,
                     });
                 }
-                FinalizedAsm::PredicateMain {
-                    program_section,
-                    data_section,
-                }
+                (
+                    FinalizedAsm::PredicateMain {
+                        program_section,
+                        data_section,
+                    },
+                    report,
+                )
             }
             RegisterAllocatedAsmSet::ContractAbi {
                 mut program_section,
                 data_section,
             } => {
+                let report = peephole::optimize(&mut program_section, &data_section);
                 // ensure there's an even number of ops so the
                 // data section offset is valid
                 if program_section.ops.len() & 1 != 0 {
@@ -1010,10 +1235,13 @@ impl RegisterAllocatedAsmSet {
                         owning_span: None,
                     });
                 }
-                FinalizedAsm::ContractAbi {
-                    program_section,
-                    data_section,
-                }
+                (
+                    FinalizedAsm::ContractAbi {
+                        program_section,
+                        data_section,
+                    },
+                    report,
+                )
             }
         }
     }
@@ -1045,6 +1273,29 @@ fn convert_node_to_asm(
             );
             ok(NodeAsmResult::JustAsm(res), warnings, errors)
         }
+        TypedAstNodeContent::Break | TypedAstNodeContent::Continue => {
+            match namespace.loop_labels.last() {
+                Some((continue_label, break_label)) => {
+                    let label = if matches!(node.content, TypedAstNodeContent::Break) {
+                        break_label
+                    } else {
+                        continue_label
+                    };
+                    ok(
+                        NodeAsmResult::JustAsm(vec![Op::jump_to_label(label.clone())]),
+                        warnings,
+                        errors,
+                    )
+                }
+                None => {
+                    errors.push(CompileError::Internal(
+                        "Loop control flow found outside of a loop.",
+                        node.span.clone(),
+                    ));
+                    err(warnings, errors)
+                }
+            }
+        }
         TypedAstNodeContent::Declaration(typed_decl) => {
             let res = check!(
                 convert_decl_to_asm(typed_decl, namespace, register_sequencer),
@@ -1177,87 +1428,6 @@ fn build_preamble(register_sequencer: &mut RegisterSequencer) -> [Op; 6] {
     ]
 }
 
-/// Builds the contract switch statement, or function selector, which takes the selector
-/// stored in the call frame (see https://github.com/FuelLabs/sway/issues/97#issuecomment-870150684
-/// for an explanation of its location)
-fn build_contract_abi_switch(
-    register_sequencer: &mut RegisterSequencer,
-    data_section: &mut DataSection,
-    selectors_and_labels: Vec<([u8; 4], Label)>,
-) -> Vec<Op> {
-    let input_selector_register = register_sequencer.next();
-    let mut asm_buf = vec![Op {
-        opcode: Either::Right(OrganizationalOp::Comment),
-        comment: "Begin contract ABI selector switch".into(),
-        owning_span: None,
-    }];
-    // load the selector from the call frame
-    asm_buf.push(Op {
-        opcode: Either::Left(VirtualOp::LW(
-            input_selector_register.clone(),
-            VirtualRegister::Constant(ConstantRegister::FramePointer),
-            // see https://github.com/FuelLabs/fuel-specs/pull/193#issuecomment-876496372
-            // We expect the last four bytes of this word to contain the selector, and the first
-            // four bytes to all be 0.
-            VirtualImmediate12::new_unchecked(73, "constant infallible value"),
-        )),
-        comment: "load input function selector".into(),
-        owning_span: None,
-    });
-
-    for (selector, label) in selectors_and_labels {
-        // put the selector in the data section
-        let data_label =
-            data_section.insert_data_value(&Literal::U32(u32::from_be_bytes(selector)));
-        // load the data into a register for comparison
-        let prog_selector_register = register_sequencer.next();
-        asm_buf.push(Op {
-            opcode: Either::Left(VirtualOp::LWDataId(
-                prog_selector_register.clone(),
-                data_label,
-            )),
-            comment: "load fn selector for comparison".into(),
-            owning_span: None,
-        });
-        // compare with the input selector
-        let comparison_result_register = register_sequencer.next();
-        asm_buf.push(Op {
-            opcode: Either::Left(VirtualOp::EQ(
-                comparison_result_register.clone(),
-                input_selector_register.clone(),
-                prog_selector_register,
-            )),
-            comment: "function selector comparison".into(),
-            owning_span: None,
-        });
-
-        // jump to the function label if the selector was equal
-        asm_buf.push(Op {
-            // if the comparison result is _not_ equal to 0, then it was indeed equal.
-            opcode: Either::Right(OrganizationalOp::JumpIfNotEq(
-                VirtualRegister::Constant(ConstantRegister::Zero),
-                comparison_result_register,
-                label,
-            )),
-            comment: "jump to selected function".into(),
-            owning_span: None,
-        });
-    }
-
-    // if none of the selectors matched, then revert
-    asm_buf.push(Op {
-        // see https://github.com/FuelLabs/sway/issues/97#issuecomment-875674105
-        // and https://github.com/FuelLabs/sway/issues/444#issuecomment-1012507337
-        opcode: Either::Left(VirtualOp::RVRT(VirtualRegister::Constant(
-            ConstantRegister::Zero,
-        ))),
-        comment: "revert if no selectors matched".into(),
-        owning_span: None,
-    });
-
-    asm_buf
-}
-
 /// The function selector value and corresponding label.
 type JumpDestination = Vec<([u8; 4], Label)>;
 /// A vector of opcodes representing the body of a contract ABI function.
@@ -0,0 +1,815 @@
+//! Peephole optimizations over the ops of an [InstructionSet] once registers are allocated.
+//!
+//! Each pass looks at a single op or at the ops of a basic block and removes, or simplifies, those
+//! which don't change the result of the program. The passes are run until none of them removes
+//! any more ops. Jumps go to the offset of an op, so the immediate values of the jumps and the op
+//! indices in the debug info are updated whenever ops are removed.
+//!
+//! The preamble, up to and including the load of the data section register, is never changed as
+//! the offset to the data section is placed at a fixed position within it.
+//!
+//! Most arithmetic ops set `$of` and `$err`, so programs which read either of them only have
+//! their jumps optimized.
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use super::{compiler_constants::TWELVE_BITS, DataSection, DebugInfo, InstructionSet};
+use crate::asm_lang::{
+    allocated_ops::{AllocatedOp, AllocatedOpcode, AllocatedRegister},
+    ConstantRegister, VirtualImmediate12, VirtualImmediate24,
+};
+
+/// A pass returns whether each op is kept, and may simplify the ops in place.
+type Pass = fn(&mut Peephole) -> Vec<bool>;
+
+const PASSES: [(&str, Pass); 5] = [
+    ("no-ops", remove_noops),
+    ("loads/stores", forward_loads_and_stores),
+    ("moves", remove_redundant_moves),
+    ("jumps", thread_jumps),
+    ("dead writes", remove_dead_writes),
+];
+
+/// The number of bytes of the program removed by each peephole pass.
+pub(crate) struct PeepholeReport {
+    bytes_saved: Vec<(&'static str, u64)>,
+}
+
+impl Default for PeepholeReport {
+    fn default() -> Self {
+        PeepholeReport {
+            bytes_saved: PASSES.iter().map(|(name, _)| (*name, 0)).collect(),
+        }
+    }
+}
+
+impl fmt::Display for PeepholeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<16} {:>12}", "peephole pass", "bytes saved")?;
+        for (name, bytes_saved) in &self.bytes_saved {
+            writeln!(f, "{:<16} {:>12}", name, bytes_saved)?;
+        }
+        let total: u64 = self
+            .bytes_saved
+            .iter()
+            .map(|(_, bytes_saved)| bytes_saved)
+            .sum();
+        writeln!(f, "{:<16} {:>12}", "total", total)
+    }
+}
+
+/// Runs the peephole passes over `program_section`, which loads its data from `data_section`.
+pub(crate) fn optimize(
+    program_section: &mut InstructionSet,
+    data_section: &DataSection,
+) -> PeepholeReport {
+    let mut report = PeepholeReport::default();
+    let ops = std::mem::take(&mut program_section.ops);
+    let start = ops
+        .iter()
+        .position(|op| {
+            matches!(
+                op.opcode,
+                AllocatedOpcode::DataSectionRegisterLoadPlaceholder
+            )
+        })
+        .map_or(0, |ix| ix + 1);
+    let preserve_flags = ops.iter().any(|op| {
+        op.opcode.use_registers().into_iter().any(|reg| {
+            *reg == AllocatedRegister::Constant(ConstantRegister::Overflow)
+                || *reg == AllocatedRegister::Constant(ConstantRegister::Error)
+        })
+    });
+    let mut peephole = Peephole {
+        ops,
+        debug_info: std::mem::take(&mut program_section.debug_info),
+        data_section,
+        start,
+        preserve_flags,
+    };
+
+    // the ops can only be moved around if every jump goes to the start of an op
+    if peephole.jump_targets().is_some() {
+        loop {
+            let mut bytes_saved = 0;
+            for ((_, pass), (_, pass_bytes_saved)) in PASSES.iter().zip(&mut report.bytes_saved) {
+                let keep = pass(&mut peephole);
+                let bytes = peephole.remove(&keep);
+                *pass_bytes_saved += bytes;
+                bytes_saved += bytes;
+            }
+            if bytes_saved == 0 {
+                break;
+            }
+        }
+    }
+
+    program_section.ops = peephole.ops;
+    program_section.debug_info = peephole.debug_info;
+    report
+}
+
+struct Peephole<'a> {
+    ops: Vec<AllocatedOp>,
+    debug_info: DebugInfo,
+    data_section: &'a DataSection,
+    /// The index of the first op after the preamble.
+    start: usize,
+    /// Whether `$of` or `$err` are read anywhere in the program.
+    preserve_flags: bool,
+}
+
+impl Peephole<'_> {
+    /// The number of instructions an op is realized as.
+    fn size_of(&self, opcode: &AllocatedOpcode) -> u64 {
+        match opcode {
+            AllocatedOpcode::LWDataId(_, data_id)
+                if matches!(
+                    self.data_section.type_of_data(data_id),
+                    Some(ty) if ty.stack_size_of() > 1
+                ) =>
+            {
+                2
+            }
+            AllocatedOpcode::DataSectionOffsetPlaceholder => 2,
+            _ => 1,
+        }
+    }
+
+    /// The offset of each op, in instructions, followed by the offset of the end of the ops.
+    fn offsets(&self) -> Vec<u64> {
+        let mut offsets = Vec::with_capacity(self.ops.len() + 1);
+        let mut offset = 0;
+        for op in &self.ops {
+            offsets.push(offset);
+            offset += self.size_of(&op.opcode);
+        }
+        offsets.push(offset);
+        offsets
+    }
+
+    /// The index of the op each jump goes to, which is the number of ops for a jump to the end of
+    /// the ops. Returns `None` if a jump doesn't go to the start of an op.
+    fn jump_targets(&self) -> Option<Vec<Option<usize>>> {
+        let offset_to_ix: HashMap<u64, usize> = self
+            .offsets()
+            .into_iter()
+            .enumerate()
+            .map(|(ix, offset)| (offset, ix))
+            .collect();
+        self.ops
+            .iter()
+            .map(|op| match &op.opcode {
+                AllocatedOpcode::JI(imm) => {
+                    offset_to_ix.get(&(imm.value as u64)).map(|ix| Some(*ix))
+                }
+                AllocatedOpcode::JNEI(_, _, imm) => {
+                    offset_to_ix.get(&(imm.value as u64)).map(|ix| Some(*ix))
+                }
+                _ => Some(None),
+            })
+            .collect()
+    }
+
+    fn targets(&self) -> Vec<Option<usize>> {
+        self.jump_targets()
+            .expect("Jumps go to the start of an op once checked.")
+    }
+
+    fn set_jump_target(&mut self, ix: usize, offset: u64) {
+        match &mut self.ops[ix].opcode {
+            AllocatedOpcode::JI(imm) => {
+                *imm = VirtualImmediate24::new_unchecked(
+                    offset,
+                    "Programs with more than 2^24 labels are unsupported right now",
+                )
+            }
+            AllocatedOpcode::JNEI(_, _, imm) => {
+                *imm = VirtualImmediate12::new_unchecked(
+                    offset,
+                    "Programs with more than 2^12 labels are unsupported right now",
+                )
+            }
+            _ => unreachable!("Only jumps have a target."),
+        }
+    }
+
+    /// Removes the ops which aren't kept, returning the number of bytes saved. The jumps to a
+    /// removed op go to the op following it instead.
+    fn remove(&mut self, keep: &[bool]) -> u64 {
+        if keep.iter().all(|keep| *keep) {
+            return 0;
+        }
+        let targets = self.targets();
+        let size = self.offsets()[self.ops.len()];
+
+        // The new index of each op, which for a removed op is the index of the op following it.
+        let mut new_op_map = Vec::with_capacity(self.ops.len() + 1);
+        let mut num_kept = 0;
+        for keep in keep {
+            new_op_map.push(num_kept);
+            if *keep {
+                num_kept += 1;
+            }
+        }
+        new_op_map.push(num_kept);
+
+        let mut new_targets = vec![];
+        for ((op, keep), target) in std::mem::take(&mut self.ops)
+            .into_iter()
+            .zip(keep)
+            .zip(targets)
+        {
+            if *keep {
+                self.ops.push(op);
+                new_targets.push(target.map(|target| new_op_map[target]));
+            }
+        }
+        let offsets = self.offsets();
+        for (ix, target) in new_targets.into_iter().enumerate() {
+            if let Some(target) = target {
+                self.set_jump_target(ix, offsets[target]);
+            }
+        }
+        self.debug_info.remap(&new_op_map);
+
+        (size - offsets[self.ops.len()]) * 4
+    }
+}
+
+/// The indices of the ops which are jumped to.
+fn jumped_to(targets: &[Option<usize>]) -> HashSet<usize> {
+    targets.iter().flatten().copied().collect()
+}
+
+/// Whether a register only changes when an op writes to it.
+fn is_stable(reg: &AllocatedRegister) -> bool {
+    match reg {
+        AllocatedRegister::Allocated(_) => true,
+        AllocatedRegister::Constant(constant) => matches!(
+            constant,
+            ConstantRegister::Zero
+                | ConstantRegister::One
+                | ConstantRegister::DataSectionStart
+                | ConstantRegister::CallFrame
+        ),
+    }
+}
+
+/// Whether the op following `opcode` is never run right after it.
+fn is_unconditional_jump(opcode: &AllocatedOpcode) -> bool {
+    use AllocatedOpcode::*;
+    matches!(opcode, JI(_) | RET(_) | RETD(..) | RVRT(_))
+}
+
+/// Whether the op following `opcode` may be run without what's known about the registers and
+/// memory right after it. A contract call runs other code before returning.
+fn ends_block(opcode: &AllocatedOpcode) -> bool {
+    use AllocatedOpcode::*;
+    matches!(
+        opcode,
+        JI(_) | JNEI(..) | RET(_) | RETD(..) | RVRT(_) | CALL(..)
+    )
+}
+
+/// Whether `opcode` leaves the memory as it is, and changes no stable register other than the one
+/// it writes to.
+fn preserves_memory(opcode: &AllocatedOpcode) -> bool {
+    use AllocatedOpcode::*;
+    matches!(
+        opcode,
+        ADD(..)
+            | ADDI(..)
+            | AND(..)
+            | ANDI(..)
+            | DIV(..)
+            | DIVI(..)
+            | EQ(..)
+            | EXP(..)
+            | EXPI(..)
+            | GT(..)
+            | LT(..)
+            | MLOG(..)
+            | MROO(..)
+            | MOD(..)
+            | MODI(..)
+            | MOVE(..)
+            | MUL(..)
+            | MULI(..)
+            | NOT(..)
+            | OR(..)
+            | ORI(..)
+            | SLL(..)
+            | SLLI(..)
+            | SRL(..)
+            | SRLI(..)
+            | SUB(..)
+            | SUBI(..)
+            | XOR(..)
+            | XORI(..)
+            | LB(..)
+            | LW(..)
+            | LWDataId(..)
+            | MEQ(..)
+            | BAL(..)
+            | BHEI(..)
+            | CSIZ(..)
+            | GM(..)
+            | FLAG(..)
+            | NOOP
+            | JI(..)
+            | JNEI(..)
+            | RET(..)
+            | RETD(..)
+            | RVRT(..)
+    )
+}
+
+/// Whether `opcode` has no effect other than writing to a register and to the flags. Ops which
+/// may panic, such as a division by zero or a load out of bounds, aren't.
+fn is_pure(opcode: &AllocatedOpcode) -> bool {
+    use AllocatedOpcode::*;
+    matches!(
+        opcode,
+        ADD(..)
+            | ADDI(..)
+            | AND(..)
+            | ANDI(..)
+            | EQ(..)
+            | GT(..)
+            | LT(..)
+            | MOVE(..)
+            | MUL(..)
+            | MULI(..)
+            | NOT(..)
+            | OR(..)
+            | ORI(..)
+            | SLL(..)
+            | SLLI(..)
+            | SRL(..)
+            | SRLI(..)
+            | SUB(..)
+            | SUBI(..)
+            | XOR(..)
+            | XORI(..)
+            | LWDataId(..)
+            | BHEI(..)
+    )
+}
+
+/// Removes the ops which leave their register as it is, such as `MOVE $r0 $r0` or
+/// `ADDI $r0 $r0 i0`, and the stack changes of zero bytes.
+fn remove_noops(peephole: &mut Peephole) -> Vec<bool> {
+    use AllocatedOpcode::*;
+    let mut keep = vec![true; peephole.ops.len()];
+    for (ix, op) in peephole.ops.iter().enumerate().skip(peephole.start) {
+        keep[ix] = !match &op.opcode {
+            CFEI(imm) | CFSI(imm) => imm.value == 0,
+            _ if peephole.preserve_flags => false,
+            MOVE(r1, r2) => r1 == r2,
+            ADDI(r1, r2, imm)
+            | SUBI(r1, r2, imm)
+            | ORI(r1, r2, imm)
+            | XORI(r1, r2, imm)
+            | SLLI(r1, r2, imm)
+            | SRLI(r1, r2, imm) => r1 == r2 && imm.value == 0,
+            MULI(r1, r2, imm) | DIVI(r1, r2, imm) => r1 == r2 && imm.value == 1,
+            _ => false,
+        };
+    }
+    keep
+}
+
+/// What's known about the values of registers and memory within a basic block.
+#[derive(Default)]
+struct KnownValues {
+    /// The register holding the word at each base register and offset.
+    words: HashMap<(AllocatedRegister, u16), AllocatedRegister>,
+    /// The data each register was loaded with.
+    data: HashMap<AllocatedRegister, u32>,
+}
+
+impl KnownValues {
+    /// Forgets the values involving `reg` once it's written to.
+    fn forget(&mut self, reg: &AllocatedRegister) {
+        self.words
+            .retain(|(base, _), value| base != reg && value != reg);
+        self.data.remove(reg);
+    }
+
+    fn clear(&mut self) {
+        self.words.clear();
+        self.data.clear();
+    }
+}
+
+/// Within each basic block, replaces the load of a word which a register is known to hold with a
+/// move from that register, and removes the stores of a word which is known to be in memory
+/// already and the loads of data into a register which holds it already.
+fn forward_loads_and_stores(peephole: &mut Peephole) -> Vec<bool> {
+    use AllocatedOpcode::*;
+    let mut keep = vec![true; peephole.ops.len()];
+    if peephole.preserve_flags {
+        return keep;
+    }
+    let jumped_to = jumped_to(&peephole.targets());
+    let mut known = KnownValues::default();
+    for (ix, keep) in keep.iter_mut().enumerate().skip(peephole.start) {
+        if jumped_to.contains(&ix) {
+            known.clear();
+        }
+        let opcode = peephole.ops[ix].opcode.clone();
+        match &opcode {
+            LW(dest, base, offset) if is_stable(dest) && is_stable(base) => {
+                let slot = (base.clone(), offset.value);
+                match known.words.get(&slot).cloned() {
+                    Some(value) if value == *dest => *keep = false,
+                    Some(value) => {
+                        peephole.ops[ix].opcode = MOVE(dest.clone(), value);
+                        known.forget(dest);
+                    }
+                    None => {
+                        known.forget(dest);
+                        if dest != base {
+                            known.words.insert(slot, dest.clone());
+                        }
+                    }
+                }
+            }
+            SW(base, value, offset) if is_stable(base) && is_stable(value) => {
+                let slot = (base.clone(), offset.value);
+                if known.words.get(&slot) == Some(value) {
+                    *keep = false;
+                } else {
+                    // another base register may point to the same address as this one
+                    known.words.retain(|(other_base, other_offset), _| {
+                        other_base == base && *other_offset != offset.value
+                    });
+                    known.words.insert(slot, value.clone());
+                }
+            }
+            LWDataId(dest, data_id) if is_stable(dest) => {
+                if known.data.get(dest) == Some(&data_id.0) {
+                    *keep = false;
+                } else {
+                    known.forget(dest);
+                    known.data.insert(dest.clone(), data_id.0);
+                }
+            }
+            _ => {
+                if !preserves_memory(&opcode) {
+                    known.words.clear();
+                }
+                for reg in opcode.def_registers() {
+                    known.forget(reg);
+                }
+            }
+        }
+        if ends_block(&opcode) {
+            known.clear();
+        }
+    }
+    keep
+}
+
+/// Within each basic block, removes the moves between two registers which are known to hold the
+/// same value already.
+fn remove_redundant_moves(peephole: &mut Peephole) -> Vec<bool> {
+    let mut keep = vec![true; peephole.ops.len()];
+    if peephole.preserve_flags {
+        return keep;
+    }
+    let jumped_to = jumped_to(&peephole.targets());
+    // The pairs of registers holding the same value.
+    let mut copies: Vec<(AllocatedRegister, AllocatedRegister)> = vec![];
+    for (ix, keep) in keep.iter_mut().enumerate().skip(peephole.start) {
+        if jumped_to.contains(&ix) {
+            copies.clear();
+        }
+        let opcode = &peephole.ops[ix].opcode;
+        match opcode {
+            AllocatedOpcode::MOVE(dest, src) if is_stable(dest) && is_stable(src) => {
+                if dest == src
+                    || copies
+                        .iter()
+                        .any(|(a, b)| (a == dest && b == src) || (a == src && b == dest))
+                {
+                    *keep = false;
+                } else {
+                    copies.retain(|(a, b)| a != dest && b != dest);
+                    copies.push((dest.clone(), src.clone()));
+                }
+            }
+            _ => {
+                for reg in opcode.def_registers() {
+                    copies.retain(|(a, b)| a != reg && b != reg);
+                }
+            }
+        }
+        if ends_block(opcode) {
+            copies.clear();
+        }
+    }
+    keep
+}
+
+/// Retargets the jumps to an unconditional jump to where that jump goes, then removes the jumps to
+/// the op following them and the ops following an unconditional jump or a return which are never
+/// jumped to.
+fn thread_jumps(peephole: &mut Peephole) -> Vec<bool> {
+    let len = peephole.ops.len();
+    let offsets = peephole.offsets();
+    let mut targets = peephole.targets();
+    for ix in peephole.start..len {
+        let target = match targets[ix] {
+            Some(target) => target,
+            None => continue,
+        };
+        let mut new_target = target;
+        let mut visited = HashSet::new();
+        while let Some(AllocatedOpcode::JI(_)) = peephole.ops.get(new_target).map(|op| &op.opcode) {
+            // a loop of jumps never goes anywhere else
+            if !visited.insert(new_target) {
+                break;
+            }
+            new_target = targets[new_target].expect("Jumps have a target.");
+        }
+        let fits = match peephole.ops[ix].opcode {
+            AllocatedOpcode::JNEI(..) => offsets[new_target] <= TWELVE_BITS,
+            _ => true,
+        };
+        if new_target != target && fits {
+            peephole.set_jump_target(ix, offsets[new_target]);
+            targets[ix] = Some(new_target);
+        }
+    }
+
+    let jumped_to = jumped_to(&targets);
+    let mut keep = vec![true; len];
+    let mut reachable = true;
+    for (ix, keep) in keep.iter_mut().enumerate().skip(peephole.start) {
+        reachable |= jumped_to.contains(&ix);
+        *keep = reachable;
+        if is_unconditional_jump(&peephole.ops[ix].opcode) {
+            reachable = false;
+        }
+    }
+    for ix in peephole.start..len {
+        if keep[ix] && targets[ix].is_some() {
+            let next = (ix + 1..len).find(|next| keep[*next]).unwrap_or(len);
+            if targets[ix] == Some(next) {
+                keep[ix] = false;
+            }
+        }
+    }
+    keep
+}
+
+/// Removes the ops which only write to a register which is written to again before it's read.
+fn remove_dead_writes(peephole: &mut Peephole) -> Vec<bool> {
+    use AllocatedOpcode::*;
+    let len = peephole.ops.len();
+    let mut keep = vec![true; len];
+    if peephole.preserve_flags {
+        return keep;
+    }
+    let targets = peephole.targets();
+    let successors: Vec<Vec<usize>> = (0..len)
+        .map(|ix| {
+            let target = targets[ix].filter(|target| *target < len);
+            let next = Some(ix + 1).filter(|next| *next < len);
+            match &peephole.ops[ix].opcode {
+                JI(_) => target.into_iter().collect(),
+                JNEI(..) => target.into_iter().chain(next).collect(),
+                RET(_) | RETD(..) | RVRT(_) => vec![],
+                _ => next.into_iter().collect(),
+            }
+        })
+        .collect();
+
+    // The allocated registers live before and after each op.
+    let mut live_in: Vec<HashSet<u8>> = vec![HashSet::new(); len];
+    let mut live_out: Vec<HashSet<u8>> = vec![HashSet::new(); len];
+    let mut modified = true;
+    while modified {
+        modified = false;
+        for ix in (0..len).rev() {
+            let out: HashSet<u8> = successors[ix]
+                .iter()
+                .flat_map(|succ| live_in[*succ].iter().copied())
+                .collect();
+            let mut r#in = out.clone();
+            for reg in peephole.ops[ix].opcode.def_registers() {
+                if let AllocatedRegister::Allocated(reg) = reg {
+                    r#in.remove(reg);
+                }
+            }
+            for reg in peephole.ops[ix].opcode.use_registers() {
+                if let AllocatedRegister::Allocated(reg) = reg {
+                    r#in.insert(*reg);
+                }
+            }
+            if r#in != live_in[ix] {
+                live_in[ix] = r#in;
+                modified = true;
+            }
+            live_out[ix] = out;
+        }
+    }
+
+    for ix in peephole.start..len {
+        let opcode = &peephole.ops[ix].opcode;
+        if !is_pure(opcode) {
+            continue;
+        }
+        if let [AllocatedRegister::Allocated(reg)] = opcode.def_registers().as_slice() {
+            keep[ix] = live_out[ix].contains(reg);
+        }
+    }
+    keep
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use AllocatedOpcode::*;
+
+    fn reg(ix: u8) -> AllocatedRegister {
+        AllocatedRegister::Allocated(ix)
+    }
+
+    fn constant(reg: ConstantRegister) -> AllocatedRegister {
+        AllocatedRegister::Constant(reg)
+    }
+
+    fn imm12(value: u64) -> VirtualImmediate12 {
+        VirtualImmediate12::new_unchecked(value, "")
+    }
+
+    /// Optimizes `ops` following the preamble, whose first op is at offset 6, returning the ops
+    /// left after the preamble and the bytes saved by each pass.
+    fn optimize_ops(ops: Vec<AllocatedOpcode>) -> (Vec<String>, Vec<(&'static str, u64)>) {
+        let preamble = vec![
+            JI(VirtualImmediate24::new_unchecked(4, "")),
+            NOOP,
+            DataSectionOffsetPlaceholder,
+            DataSectionRegisterLoadPlaceholder,
+            ADD(
+                constant(ConstantRegister::DataSectionStart),
+                constant(ConstantRegister::DataSectionStart),
+                constant(ConstantRegister::InstructionStart),
+            ),
+        ];
+        let to_op = |opcode| AllocatedOp {
+            opcode,
+            comment: String::new(),
+            owning_span: None,
+        };
+        let mut program_section = InstructionSet {
+            ops: preamble.iter().cloned().chain(ops).map(to_op).collect(),
+            debug_info: DebugInfo::default(),
+        };
+        let report = optimize(&mut program_section, &DataSection::default());
+
+        let rendered: Vec<String> = program_section
+            .ops
+            .iter()
+            .map(|op| op.to_string())
+            .collect();
+        let preamble: Vec<String> = preamble
+            .into_iter()
+            .map(|op| to_op(op).to_string())
+            .collect();
+        assert_eq!(rendered[..5], preamble[..]);
+        (rendered[5..].to_vec(), report.bytes_saved)
+    }
+
+    fn bytes_saved(report: &[(&'static str, u64)], pass: &str) -> u64 {
+        report
+            .iter()
+            .find(|(name, _)| *name == pass)
+            .map(|(_, bytes_saved)| *bytes_saved)
+            .unwrap()
+    }
+
+    #[test]
+    fn forward_stored_words() {
+        let (ops, report) = optimize_ops(vec![
+            SW(reg(0), reg(1), imm12(0)),
+            LW(reg(2), reg(0), imm12(0)),
+            SW(reg(0), reg(1), imm12(0)),
+            ADD(reg(3), reg(2), reg(1)),
+            RET(reg(3)),
+        ]);
+        assert_eq!(
+            ops,
+            [
+                "sw   $r0 $r1 i0",
+                "move $r2 $r1",
+                "add  $r3 $r2 $r1",
+                "ret  $r3"
+            ]
+        );
+        assert_eq!(bytes_saved(&report, "loads/stores"), 4);
+    }
+
+    #[test]
+    fn forget_words_stored_through_another_register() {
+        let ops = vec![
+            SW(reg(0), reg(1), imm12(0)),
+            SW(reg(2), reg(3), imm12(0)),
+            LW(reg(4), reg(0), imm12(0)),
+            RET(reg(4)),
+        ];
+        let expected: Vec<String> = ops
+            .iter()
+            .map(|opcode| {
+                AllocatedOp {
+                    opcode: opcode.clone(),
+                    comment: String::new(),
+                    owning_span: None,
+                }
+                .to_string()
+            })
+            .collect();
+        assert_eq!(optimize_ops(ops).0, expected);
+    }
+
+    #[test]
+    fn remove_noops_and_redundant_moves() {
+        let (ops, report) = optimize_ops(vec![
+            MOVE(reg(1), reg(0)),
+            ADDI(reg(2), reg(1), imm12(1)),
+            ADDI(reg(2), reg(2), imm12(0)),
+            MOVE(reg(0), reg(1)),
+            ADD(reg(3), reg(0), reg(2)),
+            RET(reg(3)),
+        ]);
+        assert_eq!(
+            ops,
+            [
+                "move $r1 $r0",
+                "addi $r2 $r1 i1",
+                "add  $r3 $r0 $r2",
+                "ret  $r3"
+            ]
+        );
+        assert_eq!(bytes_saved(&report, "no-ops"), 4);
+        assert_eq!(bytes_saved(&report, "moves"), 4);
+    }
+
+    #[test]
+    fn thread_jumps_and_remove_unreachable_ops() {
+        let (ops, report) = optimize_ops(vec![
+            JNEI(reg(0), constant(ConstantRegister::One), imm12(9)),
+            ADDI(reg(1), constant(ConstantRegister::Zero), imm12(1)),
+            RET(reg(1)),
+            JI(VirtualImmediate24::new_unchecked(11, "")),
+            ADDI(reg(1), constant(ConstantRegister::Zero), imm12(2)),
+            RET(reg(0)),
+        ]);
+        assert_eq!(
+            ops,
+            [
+                "jnei $r0 $one i9",
+                "addi $r1 $zero i1",
+                "ret  $r1",
+                "ret  $r0"
+            ]
+        );
+        assert_eq!(bytes_saved(&report, "jumps"), 8);
+    }
+
+    #[test]
+    fn remove_jumps_to_the_next_op() {
+        let (ops, report) = optimize_ops(vec![
+            JNEI(reg(0), constant(ConstantRegister::One), imm12(7)),
+            RET(reg(0)),
+        ]);
+        assert_eq!(ops, ["ret  $r0"]);
+        assert_eq!(bytes_saved(&report, "jumps"), 4);
+    }
+
+    #[test]
+    fn remove_dead_writes() {
+        let (ops, report) = optimize_ops(vec![
+            MOVE(reg(0), constant(ConstantRegister::StackPointer)),
+            ADDI(reg(0), constant(ConstantRegister::Zero), imm12(5)),
+            RET(reg(0)),
+        ]);
+        assert_eq!(ops, ["addi $r0 $zero i5", "ret  $r0"]);
+        assert_eq!(bytes_saved(&report, "dead writes"), 4);
+    }
+
+    #[test]
+    fn keep_ops_setting_flags_which_are_read() {
+        let (ops, _) = optimize_ops(vec![
+            ADD(reg(0), reg(1), reg(2)),
+            MOVE(reg(0), constant(ConstantRegister::Overflow)),
+            RET(reg(0)),
+        ]);
+        assert_eq!(ops, ["add  $r0 $r1 $r2", "move $r0 $of", "ret  $r0"]);
+    }
+}
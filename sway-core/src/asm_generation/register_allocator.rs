@@ -1,7 +1,9 @@
 use crate::asm_generation::{
     register_sequencer::RegisterSequencer, RegisterAllocationStatus, RegisterPool,
 };
-use crate::asm_lang::{virtual_register::*, RealizedOp, VirtualOp};
+use crate::asm_lang::{
+    virtual_register::*, RealizedOp, VirtualImmediate12, VirtualImmediate24, VirtualOp,
+};
 use petgraph::graph::NodeIndex;
 use std::collections::{BTreeSet, HashMap};
 
@@ -110,7 +112,7 @@ pub(crate) fn liveness_analysis(ops: &[RealizedOp]) -> HashMap<usize, BTreeSet<V
             }
 
             // Did anything change in this iteration?
-            modified = (prev_live_in_op != *live_in_op) || (prev_live_out_op != *live_out_op);
+            modified |= (prev_live_in_op != *live_in_op) || (prev_live_out_op != *live_out_op);
         }
         modified
     } {}
@@ -207,12 +209,20 @@ pub(crate) fn create_interference_graph(
 ///   updated, as well as the immediate values for some or all jump instructions (`ji` and `jnei`
 ///   for now).
 ///
+/// Alongside the reduced list, this returns the index in it of each original instruction (or of
+/// the one following it, if it was removed), and the register each coalesced register was
+/// replaced with, so that debug information can follow along.
+///
 pub(crate) fn coalesce_registers(
     ops: &[RealizedOp],
     interference_graph: &mut InterferenceGraph,
     reg_to_node_map: &mut HashMap<VirtualRegister, NodeIndex>,
     register_sequencer: &mut RegisterSequencer,
-) -> Vec<RealizedOp> {
+) -> (
+    Vec<RealizedOp>,
+    Vec<usize>,
+    HashMap<VirtualRegister, VirtualRegister>,
+) {
     // A map from the virtual registers that are removed to the virtual registers that they are
     // replaced with during the coalescing process.
     let mut reg_to_reg_map: HashMap<VirtualRegister, VirtualRegister> = HashMap::new();
@@ -225,7 +235,9 @@ pub(crate) fn coalesce_registers(
     let mut offset_map: HashMap<u64, u64> = HashMap::new();
     let mut num_moves_removed = 0;
 
+    let mut op_map = Vec::with_capacity(ops.len());
     for op in ops {
+        op_map.push(reduced_ops.len());
         let new_op = RealizedOp {
             opcode: op.opcode.clone(),
             owning_span: op.owning_span.clone(),
@@ -346,7 +358,7 @@ pub(crate) fn coalesce_registers(
         new_op.opcode = new_op.opcode.update_register(&final_reg_to_reg_map);
     }
 
-    reduced_ops
+    (reduced_ops, op_map, final_reg_to_reg_map)
 }
 
 /// Given an interference graph and a integer k, figure out if the graph k-colorable. Graph
@@ -360,40 +372,422 @@ pub(crate) fn coalesce_registers(
 /// 2. Remove node n and all its edges from the graph
 ///    - This may make some new nodes have fewer than k neighbours which is nice.
 /// 3. If some vertex n still has k or more neighbors, then the graph is not k colorable, and we
-///    have to spill. For now, we will just error out, but we may be able to do better in the
-///    future.
+///    have to spill. Pick the spillable node n with the lowest use density per neighbor, remove it
+///    from the graph and go back to 1.
 /// ===============================================================================================
 ///
+/// If any node had to be spilled, the virtual registers spilled are returned as an error instead
+/// of the stack. `use_densities` holds the use density of each virtual register which may be
+/// spilled, see [SpillArea::use_densities].
+///
 pub(crate) fn color_interference_graph(
     interference_graph: &mut InterferenceGraph,
     k: u8,
-) -> Vec<(VirtualRegister, BTreeSet<VirtualRegister>)> {
+    use_densities: &HashMap<VirtualRegister, f64>,
+) -> Result<Vec<(VirtualRegister, BTreeSet<VirtualRegister>)>, Vec<VirtualRegister>> {
     let mut stack: Vec<(VirtualRegister, BTreeSet<VirtualRegister>)> = vec![];
+    let mut spilled = vec![];
+
+    loop {
+        while let Some(node) = pick_node(interference_graph, k) {
+            let neighbors = interference_graph
+                .neighbors(node)
+                .map(|n| interference_graph[n].clone())
+                .collect();
+            stack.push((
+                interference_graph
+                    .remove_node(node)
+                    .expect("Node must exist"),
+                neighbors,
+            ));
+        }
+
+        // If any nodes are left in the graph, then they must still have a degree larger than k.
+        // In this case, spill the node which is cheapest to keep in memory and try again.
+        if interference_graph.node_count() == 0 {
+            break;
+        }
+        let candidate = interference_graph
+            .node_indices()
+            .filter_map(|n| {
+                let density = use_densities.get(&interference_graph[n])?;
+                Some((n, density / interference_graph.neighbors(n).count() as f64))
+            })
+            .min_by(|(_, a), (_, b)| a.partial_cmp(b).expect("densities are never NaN"))
+            .map(|(n, _)| n);
+        match candidate {
+            Some(node) => spilled.push(
+                interference_graph
+                    .remove_node(node)
+                    .expect("Node must exist"),
+            ),
+            // Only registers created for spilling are left, and they are live for at most one
+            // instruction each. This can't happen with fewer than k registers per instruction.
+            None => {
+                unreachable!("The allocator cannot resolve a register mapping for this program.")
+            }
+        }
+    }
+
+    if spilled.is_empty() {
+        Ok(stack)
+    } else {
+        Err(spilled)
+    }
+}
+
+/// The memory that virtual registers which could not be allocated are spilled to. It's reserved
+/// on the stack at the beginning of the program, and its address is held in a virtual register of
+/// its own for the whole program.
+#[derive(Default)]
+pub(crate) struct SpillArea {
+    base: Option<VirtualRegister>,
+    /// The slot, in words from the base, of each spilled register.
+    slots: HashMap<VirtualRegister, u64>,
+    /// The registers created to load and store spilled registers, which must not be spilled.
+    temporaries: BTreeSet<VirtualRegister>,
+}
+
+impl SpillArea {
+    /// The register holding the address of the spill area, if anything was spilled.
+    pub(crate) fn base(&self) -> Option<&VirtualRegister> {
+        self.base.as_ref()
+    }
+
+    /// The slot, in words from the base, which a register was spilled to.
+    pub(crate) fn slot(&self, reg: &VirtualRegister) -> Option<u64> {
+        self.slots.get(reg).copied()
+    }
+
+    /// Computes the use density of each virtual register in `ops` which may be spilled: the number
+    /// of instructions which use or define it, divided by the number of instructions from the
+    /// first to the last of these. Registers used rarely over a long stretch of the program are
+    /// the cheapest to spill, since few loads and stores are needed to free a register for long.
+    pub(crate) fn use_densities(&self, ops: &[RealizedOp]) -> HashMap<VirtualRegister, f64> {
+        let mut occurrences: HashMap<&VirtualRegister, (usize, usize, usize)> = HashMap::new();
+        for (ix, op) in ops.iter().enumerate() {
+            for reg in op.opcode.registers() {
+                if !matches!(reg, VirtualRegister::Virtual(_))
+                    || self.base.as_ref() == Some(reg)
+                    || self.temporaries.contains(reg)
+                {
+                    continue;
+                }
+                let (_, last, count) = occurrences.entry(reg).or_insert((ix, ix, 0));
+                *last = ix;
+                *count += 1;
+            }
+        }
+        occurrences
+            .into_iter()
+            .map(|(reg, (first, last, count))| {
+                (reg.clone(), count as f64 / (last - first + 1) as f64)
+            })
+            .collect()
+    }
+}
 
-    while let Some(node) = pick_node(interference_graph, k) {
-        let neighbors = interference_graph
-            .neighbors(node)
-            .map(|n| interference_graph[n].clone())
-            .collect();
-        stack.push((
-            interference_graph
-                .remove_node(node)
-                .expect("Node must exist"),
-            neighbors,
+/// Given a list of instructions `ops` and the virtual registers which could not be allocated,
+/// generate a new list of instructions where each spilled register lives in its own slot of the
+/// spill area instead:
+/// * Before any instruction using a spilled register, its value is loaded from its slot into a new
+///   virtual register, which the instruction uses instead.
+/// * After any instruction defining a spilled register, the new virtual register is stored to its
+///   slot.
+///
+/// When the first registers are spilled, instructions setting the base of the spill area and
+/// reserving it on the stack are added right after the preamble. The size reserved is updated
+/// whenever more registers are spilled.
+///
+/// Like [coalesce_registers], this updates the offset of each instruction and the immediate values
+/// of jump instructions, and also returns the index in the new list of each original instruction
+/// (or of the first load added before it).
+///
+pub(crate) fn spill_registers(
+    ops: &[RealizedOp],
+    spilled: &[VirtualRegister],
+    spill_area: &mut SpillArea,
+    register_sequencer: &mut RegisterSequencer,
+) -> (Vec<RealizedOp>, Vec<usize>) {
+    for reg in spilled {
+        let slot = spill_area.slots.len() as u64;
+        spill_area.slots.insert(reg.clone(), slot);
+    }
+
+    // The spill area is set up right after the preamble, which ends by adding $is to $ds.
+    let setup_ix = match spill_area.base {
+        Some(_) => None,
+        None => Some(
+            ops.iter()
+                .position(|op| matches!(op.opcode, VirtualOp::DataSectionRegisterLoadPlaceholder))
+                .map_or(0, |ix| ix + 2),
+        ),
+    };
+    let base = spill_area
+        .base
+        .get_or_insert_with(|| register_sequencer.next())
+        .clone();
+
+    let mut spilled_ops: Vec<RealizedOp> = vec![];
+    let mut offset_map: HashMap<u64, u64> = HashMap::new();
+    let mut op_map = Vec::with_capacity(ops.len());
+    let mut num_ops_added = 0;
+    for (ix, op) in ops.iter().enumerate() {
+        offset_map.insert(op.offset, op.offset + num_ops_added);
+        op_map.push(spilled_ops.len());
+
+        if setup_ix == Some(ix) {
+            for (opcode, comment) in [
+                (
+                    VirtualOp::MOVE(
+                        base.clone(),
+                        VirtualRegister::Constant(ConstantRegister::StackPointer),
+                    ),
+                    "save base of spill area",
+                ),
+                (
+                    VirtualOp::CFEI(VirtualImmediate24::new_unchecked(0, "")),
+                    "allocate spill area",
+                ),
+            ] {
+                spilled_ops.push(RealizedOp {
+                    opcode,
+                    comment: comment.into(),
+                    owning_span: None,
+                    offset: op.offset + num_ops_added,
+                });
+                num_ops_added += 1;
+            }
+        }
+
+        let mut loads = vec![];
+        let mut stores = vec![];
+        let spill_op = |opcode, comment: &str, offset| RealizedOp {
+            opcode,
+            comment: comment.into(),
+            owning_span: op.owning_span.clone(),
+            offset,
+        };
+        let mut reg_to_reg_map = HashMap::new();
+        for reg in op.opcode.registers() {
+            if let Some(slot) = spill_area.slots.get(reg) {
+                let temp = register_sequencer.next();
+                spill_area.temporaries.insert(temp.clone());
+                let imm = VirtualImmediate12::new_unchecked(
+                    *slot,
+                    "Programs spilling more than 2^12 registers are unsupported right now",
+                );
+                if op.opcode.use_registers().contains(reg) {
+                    loads.push(VirtualOp::LW(temp.clone(), base.clone(), imm.clone()));
+                }
+                if op.opcode.def_registers().contains(reg) {
+                    stores.push(VirtualOp::SW(base.clone(), temp.clone(), imm));
+                }
+                reg_to_reg_map.insert(reg.clone(), temp);
+            }
+        }
+
+        for load in loads {
+            spilled_ops.push(spill_op(
+                load,
+                "restore spilled register",
+                op.offset + num_ops_added,
+            ));
+            num_ops_added += 1;
+        }
+        spilled_ops.push(RealizedOp {
+            opcode: op.opcode.clone().update_register(&reg_to_reg_map),
+            comment: op.comment.clone(),
+            owning_span: op.owning_span.clone(),
+            offset: op.offset + num_ops_added,
+        });
+        for store in stores {
+            num_ops_added += 1;
+            spilled_ops.push(spill_op(store, "spill register", op.offset + num_ops_added));
+        }
+    }
+
+    // Update immediate values for jump instructions using offset_map
+    for new_op in &mut spilled_ops {
+        new_op.opcode = new_op.opcode.update_jump_immediate_values(&offset_map);
+    }
+
+    // Reserve a word for each slot in the spill area.
+    let spill_area_size = spill_area.slots.len() as u64 * 8;
+    if let Some(ix) = spilled_ops.iter().position(|op| match &op.opcode {
+        VirtualOp::MOVE(reg, VirtualRegister::Constant(ConstantRegister::StackPointer)) => {
+            *reg == base
+        }
+        _ => false,
+    }) {
+        spilled_ops[ix + 1].opcode = VirtualOp::CFEI(VirtualImmediate24::new_unchecked(
+            spill_area_size,
+            "constant infallible value",
         ));
     }
 
-    // If any nodes are left in the graph, then the must still have a degree larger than k. In this
-    // case, the graph is not colorable.
-    if interference_graph.node_count() > 0 {
-        unimplemented!(
-            "The allocator cannot resolve a register mapping for this program. 
-                This is a temporary artifact of the extremely early stage version of this language. 
-                Try to lower the number of variables you use."
+    (spilled_ops, op_map)
+}
+
+/// Given a list of instructions `ops` and the indices of the instructions before which registers
+/// are saved and restored around each recursive call, generate a new list of instructions which
+/// saves the registers live across each call to a frame on the stack:
+/// * Before the call, a frame with a word for each register and one for the address of the
+///   previous frame is reserved on the stack, and the address of the frame is moved to the call
+///   frame register `$$cf`.
+/// * After the call, the registers are loaded back from the frame and `$$cf` is reset to the
+///   previous frame.
+///
+/// The registers live across a call are those live once it returns, except the ones defined after
+/// the jump back from the called function, which hold its return value. The registers given with
+/// each call are saved whether or not they're live. Like the rest of the stack, the frames are
+/// never freed.
+///
+/// Like [spill_registers], this updates the offset of each instruction and the immediate values of
+/// jump instructions, and also returns the index in the new list of each original instruction.
+///
+pub(crate) fn save_registers_across_calls(
+    ops: &[RealizedOp],
+    call_frames: &[(usize, usize, Vec<VirtualRegister>)],
+    register_sequencer: &mut RegisterSequencer,
+) -> (Vec<RealizedOp>, Vec<usize>) {
+    // Liveness is found as if each call fell through to its return, rather than going through the
+    // called function, where the registers of the caller belong to another call of it.
+    let offset_to_ix: HashMap<u64, usize> = ops
+        .iter()
+        .enumerate()
+        .map(|(ix, op)| (op.offset, ix))
+        .collect();
+    let mut caller_ops = ops.to_vec();
+    for (save_ix, restore_ix, _) in call_frames {
+        for op in &mut caller_ops[*save_ix..*restore_ix] {
+            if let VirtualOp::JI(imm) = &op.opcode {
+                let leaves_frame = !matches!(
+                    offset_to_ix.get(&(imm.value as u64)),
+                    Some(ix) if (*save_ix..*restore_ix).contains(ix)
+                );
+                if leaves_frame {
+                    op.opcode = VirtualOp::NOOP;
+                }
+            }
+        }
+    }
+    let live_out = liveness_analysis(&caller_ops);
+    let virtual_registers = |regs: BTreeSet<&VirtualRegister>| {
+        regs.into_iter()
+            .filter(|reg| matches!(reg, VirtualRegister::Virtual(_)))
+            .cloned()
+            .collect::<BTreeSet<_>>()
+    };
+    let live_in = |ix: usize| {
+        let op = &ops[ix];
+        let defs = virtual_registers(op.opcode.def_registers());
+        let mut live = virtual_registers(op.opcode.use_registers());
+        live.extend(live_out[&ix].difference(&defs).cloned());
+        live
+    };
+
+    // The ops to add before the op at each index.
+    let mut added_ops: HashMap<usize, Vec<(VirtualOp, &str)>> = HashMap::new();
+    let frame_register = VirtualRegister::Constant(ConstantRegister::CallFrame);
+    for (save_ix, restore_ix, registers) in call_frames {
+        let return_ix = ops[*save_ix..*restore_ix]
+            .iter()
+            .rposition(|op| matches!(op.opcode, VirtualOp::JI(_)))
+            .map_or(*save_ix, |ix| save_ix + ix + 1);
+        let mut saved = live_in(*restore_ix);
+        saved.extend(registers.iter().cloned());
+        for op in &ops[return_ix..*restore_ix] {
+            for reg in op.opcode.def_registers() {
+                saved.remove(reg);
+            }
+        }
+
+        let frame = register_sequencer.next();
+        let frame_size = VirtualImmediate24::new_unchecked(
+            (saved.len() as u64 + 1) * 8,
+            "Calls saving more than 2^21 registers are unsupported right now",
         );
+        let mut save_ops = vec![
+            (
+                VirtualOp::MOVE(
+                    frame.clone(),
+                    VirtualRegister::Constant(ConstantRegister::StackPointer),
+                ),
+                "save base of call frame",
+            ),
+            (VirtualOp::CFEI(frame_size), "allocate call frame"),
+            (
+                VirtualOp::SW(
+                    frame.clone(),
+                    frame_register.clone(),
+                    VirtualImmediate12::new_unchecked(0, ""),
+                ),
+                "save previous call frame",
+            ),
+        ];
+        let mut restore_ops = vec![];
+        for (slot, reg) in saved.into_iter().enumerate() {
+            let imm = VirtualImmediate12::new_unchecked(
+                slot as u64 + 1,
+                "Calls saving more than 2^12 registers are unsupported right now",
+            );
+            save_ops.push((
+                VirtualOp::SW(frame.clone(), reg.clone(), imm.clone()),
+                "save register",
+            ));
+            restore_ops.push((
+                VirtualOp::LW(reg, frame_register.clone(), imm),
+                "restore register",
+            ));
+        }
+        save_ops.push((
+            VirtualOp::MOVE(frame_register.clone(), frame),
+            "set call frame",
+        ));
+        restore_ops.push((
+            VirtualOp::LW(
+                frame_register.clone(),
+                frame_register.clone(),
+                VirtualImmediate12::new_unchecked(0, ""),
+            ),
+            "restore previous call frame",
+        ));
+        added_ops.entry(*save_ix).or_default().append(&mut save_ops);
+        added_ops
+            .entry(*restore_ix)
+            .or_default()
+            .append(&mut restore_ops);
+    }
+
+    let mut saving_ops: Vec<RealizedOp> = vec![];
+    let mut offset_map: HashMap<u64, u64> = HashMap::new();
+    let mut op_map = Vec::with_capacity(ops.len());
+    let mut num_ops_added = 0;
+    for (ix, op) in ops.iter().enumerate() {
+        offset_map.insert(op.offset, op.offset + num_ops_added);
+        op_map.push(saving_ops.len());
+        for (opcode, comment) in added_ops.remove(&ix).unwrap_or_default() {
+            saving_ops.push(RealizedOp {
+                opcode,
+                comment: comment.into(),
+                owning_span: None,
+                offset: op.offset + num_ops_added,
+            });
+            num_ops_added += 1;
+        }
+        saving_ops.push(RealizedOp {
+            offset: op.offset + num_ops_added,
+            ..op.clone()
+        });
+    }
+
+    // Update immediate values for jump instructions using offset_map
+    for new_op in &mut saving_ops {
+        new_op.opcode = new_op.opcode.update_jump_immediate_values(&offset_map);
     }
 
-    stack
+    (saving_ops, op_map)
 }
 
 /// Use the stack generated by the coloring algorithm to figure out a register assignment for each
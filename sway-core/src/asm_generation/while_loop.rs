@@ -53,13 +53,18 @@ pub(super) fn convert_while_loop_to_asm(
 
     // the implicit return value of a while loop block, if any, should be ignored,
     // so we pass None into the final argument of code block conversion
-    // step 3: run the loop body
+    // step 3: run the loop body, in which `continue` jumps back to the beginning and `break`
+    // jumps to the exit label
+    namespace
+        .loop_labels
+        .push((label.clone(), exit_label.clone()));
     let mut body = check!(
         convert_code_block_to_asm(&r#loop.body, namespace, register_sequencer, None),
         vec![],
         warnings,
         errors
     );
+    namespace.loop_labels.pop();
     buf.append(&mut body);
 
     // step 4: jump back to beginning to re-evaluate the condition
@@ -0,0 +1,743 @@
+//! Arithmetic and comparisons on `u128` and `u256`, which are too wide for a register.
+//!
+//! Like a `b256`, a wide integer lives in memory as big-endian words, most significant word first,
+//! and the register holding it holds its address instead. The operations load the words of both
+//! operands into registers and compute the result a word at a time, so that the flags are left the
+//! way the single word instruction would leave them:
+//!
+//! * Addition, subtraction and multiplication wrap around, setting `$of` to 1 if they overflowed
+//!   and to 0 otherwise.
+//! * Division and modulo by zero give 0, setting `$err` to 1.
+//! * Comparisons clear both flags.
+//!
+//! Arithmetic results are written to memory allocated on the stack, so they never alias either
+//! operand.
+
+use super::RegisterSequencer;
+use crate::{
+    asm_lang::{virtual_register::*, Op, VirtualImmediate12, VirtualImmediate24, VirtualOp},
+    semantic_analysis::ast_node::WideIntegerOp,
+};
+
+use sway_types::span::Span;
+
+use either::Either;
+
+/// Computes `op` on the wide integers of `size_in_words` words at the addresses in `lhs` and
+/// `rhs`. An arithmetic result is written to newly allocated stack memory, whose address is put in
+/// `return_register`, whereas a comparison puts the resulting bool in `return_register`.
+pub(crate) fn convert_wide_integer_op_to_asm(
+    op: WideIntegerOp,
+    size_in_words: u64,
+    lhs: &VirtualRegister,
+    rhs: &VirtualRegister,
+    return_register: &VirtualRegister,
+    register_sequencer: &mut RegisterSequencer,
+    owning_span: Option<Span>,
+) -> Vec<Op> {
+    let mut asm = WideIntegerAsm {
+        register_sequencer,
+        ops: vec![],
+        owning_span,
+    };
+    let lhs = asm.load_words(lhs, size_in_words);
+    let rhs = asm.load_words(rhs, size_in_words);
+    match op {
+        WideIntegerOp::Add => asm.add(&lhs, &rhs, return_register),
+        WideIntegerOp::Sub => asm.sub(&lhs, &rhs, return_register),
+        WideIntegerOp::Mul => asm.mul(&lhs, &rhs, return_register),
+        WideIntegerOp::Div => asm.div_mod(&lhs, &rhs, return_register, false),
+        WideIntegerOp::Mod => asm.div_mod(&lhs, &rhs, return_register, true),
+        WideIntegerOp::Eq => {
+            let eq = asm.eq(&lhs, &rhs);
+            asm.move_to(return_register, eq);
+        }
+        WideIntegerOp::Neq => {
+            let eq = asm.eq(&lhs, &rhs);
+            asm.not_to(return_register, eq);
+        }
+        WideIntegerOp::Lt => {
+            let lt = asm.lt(&lhs, &rhs);
+            asm.move_to(return_register, lt);
+        }
+        WideIntegerOp::Gt => {
+            let gt = asm.lt(&rhs, &lhs);
+            asm.move_to(return_register, gt);
+        }
+        WideIntegerOp::Le => {
+            let gt = asm.lt(&rhs, &lhs);
+            asm.not_to(return_register, gt);
+        }
+        WideIntegerOp::Ge => {
+            let lt = asm.lt(&lhs, &rhs);
+            asm.not_to(return_register, lt);
+        }
+    }
+    asm.ops
+}
+
+fn zero() -> VirtualRegister {
+    VirtualRegister::Constant(ConstantRegister::Zero)
+}
+
+fn imm12(value: u64) -> VirtualImmediate12 {
+    VirtualImmediate12::new_unchecked(value, "constant infallible value")
+}
+
+type BinaryOp = fn(VirtualRegister, VirtualRegister, VirtualRegister) -> VirtualOp;
+type ImmediateOp = fn(VirtualRegister, VirtualRegister, VirtualImmediate12) -> VirtualOp;
+
+struct WideIntegerAsm<'a> {
+    register_sequencer: &'a mut RegisterSequencer,
+    ops: Vec<Op>,
+    owning_span: Option<Span>,
+}
+
+impl WideIntegerAsm<'_> {
+    fn push(&mut self, opcode: VirtualOp, comment: &str) {
+        self.ops.push(Op {
+            opcode: Either::Left(opcode),
+            comment: comment.into(),
+            owning_span: self.owning_span.clone(),
+        });
+    }
+
+    /// Applies `op` to `lhs` and `rhs`, returning the register holding the result.
+    fn binary(
+        &mut self,
+        op: BinaryOp,
+        lhs: &VirtualRegister,
+        rhs: &VirtualRegister,
+        comment: &str,
+    ) -> VirtualRegister {
+        let result = self.register_sequencer.next();
+        self.push(op(result.clone(), lhs.clone(), rhs.clone()), comment);
+        result
+    }
+
+    fn immediate(
+        &mut self,
+        op: ImmediateOp,
+        lhs: &VirtualRegister,
+        rhs: u64,
+        comment: &str,
+    ) -> VirtualRegister {
+        let result = self.register_sequencer.next();
+        self.push(op(result.clone(), lhs.clone(), imm12(rhs)), comment);
+        result
+    }
+
+    /// Copies `$of`, which must be done straight after the op setting it since every arithmetic
+    /// op writes to it.
+    fn overflow_flag(&mut self) -> VirtualRegister {
+        let result = self.register_sequencer.next();
+        self.push(
+            VirtualOp::MOVE(
+                result.clone(),
+                VirtualRegister::Constant(ConstantRegister::Overflow),
+            ),
+            "save overflow flag",
+        );
+        result
+    }
+
+    fn move_to(&mut self, dest: &VirtualRegister, src: VirtualRegister) {
+        self.push(VirtualOp::MOVE(dest.clone(), src), "");
+    }
+
+    /// Puts the negation of the bool `src` in `dest`.
+    fn not_to(&mut self, dest: &VirtualRegister, src: VirtualRegister) {
+        self.push(
+            VirtualOp::XORI(dest.clone(), src, imm12(1)),
+            "negate comparison",
+        );
+    }
+
+    fn load_words(
+        &mut self,
+        address: &VirtualRegister,
+        size_in_words: u64,
+    ) -> Vec<VirtualRegister> {
+        (0..size_in_words)
+            .map(|ix| {
+                let word = self.register_sequencer.next();
+                self.push(
+                    VirtualOp::LW(word.clone(), address.clone(), imm12(ix)),
+                    "load word of wide integer",
+                );
+                word
+            })
+            .collect()
+    }
+
+    /// Allocates memory on the stack for the words in `result` and stores them there, putting its
+    /// address in `return_register`.
+    fn store_result(&mut self, result: &[VirtualRegister], return_register: &VirtualRegister) {
+        self.allocate_result(result.len(), return_register);
+        self.store_words(result, return_register);
+    }
+
+    /// Allocates memory on the stack for a result of `size_in_words`, putting its address in
+    /// `return_register`. This clears the flags.
+    fn allocate_result(&mut self, size_in_words: usize, return_register: &VirtualRegister) {
+        self.push(
+            VirtualOp::MOVE(
+                return_register.clone(),
+                VirtualRegister::Constant(ConstantRegister::StackPointer),
+            ),
+            "save register for temporary stack value",
+        );
+        let size_in_bytes = size_in_words as u64 * 8;
+        self.push(
+            VirtualOp::CFEI(VirtualImmediate24::new_unchecked(
+                size_in_bytes,
+                "wide integers are small",
+            )),
+            &format!("allocate {} bytes for wide integer", size_in_bytes),
+        );
+    }
+
+    /// Stores the words in `result` at the address in `return_register`.
+    fn store_words(&mut self, result: &[VirtualRegister], return_register: &VirtualRegister) {
+        for (ix, word) in result.iter().enumerate() {
+            self.push(
+                VirtualOp::SW(return_register.clone(), word.clone(), imm12(ix as u64)),
+                "store word of wide integer",
+            );
+        }
+    }
+
+    /// Sets `$of` to the bool in `overflowed` and clears `$err`, by subtracting it from zero.
+    fn set_overflow_flag(&mut self, overflowed: &VirtualRegister) {
+        self.binary(VirtualOp::SUB, &zero(), overflowed, "set overflow flag");
+    }
+
+    /// Adds `lhs` and `rhs` word by word from the least significant word up, or subtracts `rhs`,
+    /// carrying the overflow of each word into the next one. Returns the result and the final
+    /// carry.
+    fn add_or_sub(
+        &mut self,
+        op: BinaryOp,
+        lhs: &[VirtualRegister],
+        rhs: &[VirtualRegister],
+    ) -> (Vec<VirtualRegister>, VirtualRegister) {
+        let mut result = vec![zero(); lhs.len()];
+        let mut carry: Option<VirtualRegister> = None;
+        for ix in (0..lhs.len()).rev() {
+            let word = self.binary(op, &lhs[ix], &rhs[ix], "wide integer word");
+            let overflowed = self.overflow_flag();
+            result[ix] = match carry {
+                Some(prev_carry) => {
+                    let word = self.binary(op, &word, &prev_carry, "carry into word");
+                    // at most one of the two can overflow
+                    let carried_overflow = self.overflow_flag();
+                    carry = Some(self.binary(VirtualOp::OR, &overflowed, &carried_overflow, ""));
+                    word
+                }
+                None => {
+                    carry = Some(overflowed);
+                    word
+                }
+            };
+        }
+        (result, carry.expect("wide integers have words"))
+    }
+
+    fn add(
+        &mut self,
+        lhs: &[VirtualRegister],
+        rhs: &[VirtualRegister],
+        return_register: &VirtualRegister,
+    ) {
+        let (result, carry) = self.add_or_sub(VirtualOp::ADD, lhs, rhs);
+        self.store_result(&result, return_register);
+        self.set_overflow_flag(&carry);
+    }
+
+    fn sub(
+        &mut self,
+        lhs: &[VirtualRegister],
+        rhs: &[VirtualRegister],
+        return_register: &VirtualRegister,
+    ) {
+        let (result, borrow) = self.add_or_sub(VirtualOp::SUB, lhs, rhs);
+        self.store_result(&result, return_register);
+        self.set_overflow_flag(&borrow);
+    }
+
+    /// Splits the words into their 32 bit halves, least significant half first.
+    fn half_words(&mut self, words: &[VirtualRegister]) -> Vec<VirtualRegister> {
+        let mut halves = vec![];
+        for word in words.iter().rev() {
+            let shifted = self.immediate(VirtualOp::SLLI, word, 32, "");
+            halves.push(self.immediate(VirtualOp::SRLI, &shifted, 32, "lower half word"));
+            halves.push(self.immediate(VirtualOp::SRLI, word, 32, "upper half word"));
+        }
+        halves
+    }
+
+    /// Long multiplication on 32 bit half words, whose products, plus the partial result and the
+    /// carry, always fit in a word. The product overflows if any half word product lands beyond
+    /// the most significant half word or a carry is left over.
+    fn mul(
+        &mut self,
+        lhs: &[VirtualRegister],
+        rhs: &[VirtualRegister],
+        return_register: &VirtualRegister,
+    ) {
+        let lhs = self.half_words(lhs);
+        let rhs = self.half_words(rhs);
+        let num_halves = lhs.len();
+        let mut partial: Vec<Option<VirtualRegister>> = vec![None; num_halves];
+        let mut overflow: Option<VirtualRegister> = None;
+        let mut note_overflow = |asm: &mut Self, value: VirtualRegister| {
+            overflow = Some(match overflow.take() {
+                Some(overflow) => asm.binary(VirtualOp::OR, &overflow, &value, ""),
+                None => value,
+            });
+        };
+        for (i, lhs_half) in lhs.iter().enumerate() {
+            let mut carry: Option<VirtualRegister> = None;
+            for (j, rhs_half) in rhs.iter().enumerate() {
+                let product =
+                    self.binary(VirtualOp::MUL, lhs_half, rhs_half, "multiply half words");
+                if i + j >= num_halves {
+                    note_overflow(self, product);
+                    continue;
+                }
+                let mut sum = product;
+                if let Some(prev) = &partial[i + j] {
+                    sum = self.binary(VirtualOp::ADD, &sum, prev, "add partial product");
+                }
+                if let Some(prev_carry) = &carry {
+                    sum = self.binary(VirtualOp::ADD, &sum, prev_carry, "add carry");
+                }
+                let shifted = self.immediate(VirtualOp::SLLI, &sum, 32, "");
+                partial[i + j] = Some(self.immediate(VirtualOp::SRLI, &shifted, 32, ""));
+                carry = Some(self.immediate(VirtualOp::SRLI, &sum, 32, "carry upper half"));
+            }
+            if let Some(carry) = carry {
+                note_overflow(self, carry);
+            }
+        }
+
+        let partial = partial
+            .into_iter()
+            .map(|half| half.expect("every half word has a partial product"))
+            .collect::<Vec<_>>();
+        let result = partial
+            .chunks(2)
+            .rev()
+            .map(|halves| {
+                let upper = self.immediate(VirtualOp::SLLI, &halves[1], 32, "");
+                self.binary(VirtualOp::OR, &upper, &halves[0], "join half words")
+            })
+            .collect::<Vec<_>>();
+        self.store_result(&result, return_register);
+        let overflow = overflow.expect("wide integers have more than one half word");
+        let overflowed = self.binary(VirtualOp::GT, &overflow, &zero(), "");
+        self.set_overflow_flag(&overflowed);
+    }
+
+    fn eq(&mut self, lhs: &[VirtualRegister], rhs: &[VirtualRegister]) -> VirtualRegister {
+        let mut eq = self.binary(VirtualOp::EQ, &lhs[0], &rhs[0], "compare words");
+        for (lhs, rhs) in lhs.iter().zip(rhs).skip(1) {
+            let word_eq = self.binary(VirtualOp::EQ, lhs, rhs, "compare words");
+            eq = self.binary(VirtualOp::AND, &eq, &word_eq, "");
+        }
+        eq
+    }
+
+    /// Working up from the least significant word, `lhs` is less than `rhs` if its word is less
+    /// or the words are equal and the rest of `lhs` is less.
+    fn lt(&mut self, lhs: &[VirtualRegister], rhs: &[VirtualRegister]) -> VirtualRegister {
+        let last = lhs.len() - 1;
+        let mut lt = self.binary(VirtualOp::LT, &lhs[last], &rhs[last], "compare words");
+        for ix in (0..last).rev() {
+            let word_lt = self.binary(VirtualOp::LT, &lhs[ix], &rhs[ix], "compare words");
+            let word_eq = self.binary(VirtualOp::EQ, &lhs[ix], &rhs[ix], "");
+            let lower_lt = self.binary(VirtualOp::AND, &word_eq, &lt, "");
+            lt = self.binary(VirtualOp::OR, &word_lt, &lower_lt, "");
+        }
+        lt
+    }
+
+    /// Restoring division, a bit at a time. The dividend is shifted into the remainder from the
+    /// top, and the divisor subtracted from the remainder whenever it fits, setting that bit of
+    /// the quotient. The dividend registers are reused for the quotient as it's shifted out.
+    fn div_mod(
+        &mut self,
+        lhs: &[VirtualRegister],
+        rhs: &[VirtualRegister],
+        return_register: &VirtualRegister,
+        is_mod: bool,
+    ) {
+        let size_in_words = lhs.len();
+        let quotient = lhs;
+        let remainder = (0..size_in_words)
+            .map(|_| {
+                let word = self.register_sequencer.next();
+                self.push(VirtualOp::MOVE(word.clone(), zero()), "clear remainder");
+                word
+            })
+            .collect::<Vec<_>>();
+        let mut divisor_bits = rhs[0].clone();
+        for word in &rhs[1..] {
+            divisor_bits = self.binary(VirtualOp::OR, &divisor_bits, word, "");
+        }
+        let divisor_is_nonzero = self.binary(VirtualOp::GT, &divisor_bits, &zero(), "");
+        let counter = self.immediate(
+            VirtualOp::ADDI,
+            &zero(),
+            size_in_words as u64 * 64,
+            "number of bits to divide",
+        );
+
+        let loop_label = self.register_sequencer.get_label();
+        self.ops.push(Op::unowned_jump_label_comment(
+            loop_label.clone(),
+            "divide wide integer loop",
+        ));
+
+        // shift the remainder and the dividend left by one bit, as a single integer
+        let shifted = remainder.iter().chain(quotient.iter()).collect::<Vec<_>>();
+        for (ix, word) in shifted.iter().enumerate() {
+            self.push(
+                VirtualOp::SLLI((*word).clone(), (*word).clone(), imm12(1)),
+                "shift dividend into remainder",
+            );
+            if let Some(next) = shifted.get(ix + 1) {
+                let top_bit = self.immediate(VirtualOp::SRLI, next, 63, "");
+                self.push(VirtualOp::OR((*word).clone(), (*word).clone(), top_bit), "");
+            }
+        }
+
+        // subtract the divisor if it fits, masked to zero if it doesn't
+        let lt = self.lt(&remainder, rhs);
+        let fits = self.immediate(VirtualOp::XORI, &lt, 1, "divisor fits in remainder");
+        let mask = self.binary(VirtualOp::SUB, &zero(), &fits, "");
+        let mut borrow: Option<VirtualRegister> = None;
+        for ix in (0..size_in_words).rev() {
+            let masked = self.binary(VirtualOp::AND, &rhs[ix], &mask, "");
+            self.push(
+                VirtualOp::SUB(remainder[ix].clone(), remainder[ix].clone(), masked),
+                "subtract divisor from remainder",
+            );
+            // the borrow out of the most significant word is never needed, as the divisor is
+            // only subtracted if it fits
+            let overflowed = (ix > 0).then(|| self.overflow_flag());
+            if let Some(prev_borrow) = borrow.take() {
+                self.push(
+                    VirtualOp::SUB(remainder[ix].clone(), remainder[ix].clone(), prev_borrow),
+                    "borrow from word",
+                );
+                if let Some(overflowed) = overflowed {
+                    let borrowed_overflow = self.overflow_flag();
+                    borrow = Some(self.binary(VirtualOp::OR, &overflowed, &borrowed_overflow, ""));
+                }
+            } else {
+                borrow = overflowed;
+            }
+        }
+        let last = size_in_words - 1;
+        self.push(
+            VirtualOp::OR(quotient[last].clone(), quotient[last].clone(), fits),
+            "set quotient bit",
+        );
+
+        self.push(
+            VirtualOp::SUBI(counter.clone(), counter.clone(), imm12(1)),
+            "",
+        );
+        self.ops
+            .push(Op::jump_if_not_equal(counter, zero(), loop_label));
+
+        // dividing by zero gives zero
+        let result_mask = self.binary(VirtualOp::SUB, &zero(), &divisor_is_nonzero, "");
+        let words = if is_mod { &remainder[..] } else { quotient };
+        let (last_word, rest) = words.split_last().expect("wide integers have words");
+        let mut result = rest
+            .iter()
+            .map(|word| self.binary(VirtualOp::AND, word, &result_mask, ""))
+            .collect::<Vec<_>>();
+        self.allocate_result(size_in_words, return_register);
+        // dividing the last word by the bool gives it back, or gives zero and sets `$err` when
+        // dividing by zero, like `DIV` does. Only stores follow, so the flag is kept.
+        result.push(self.binary(
+            VirtualOp::DIV,
+            last_word,
+            &divisor_is_nonzero,
+            "set error flag",
+        ));
+        self.store_words(&result, return_register);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        asm_generation::{build_preamble, AbstractInstructionSet, DataSection, SwayAsmSet},
+        parse_tree::Literal,
+        source_map::SourceMap,
+    };
+
+    use fuel_tx::Transaction;
+    use fuel_vm::{
+        interpreter::Interpreter,
+        prelude::{MemoryStorage, ProgramState},
+    };
+
+    /// What running an op in the VM gave: whether an arithmetic op gave the expected result, or
+    /// the bool a comparison gave, along with the flags left by the op.
+    #[derive(Debug, PartialEq)]
+    struct Outcome {
+        result: bool,
+        overflow: bool,
+        error: bool,
+    }
+
+    fn outcome(result: bool, overflow: bool, error: bool) -> Outcome {
+        Outcome {
+            result,
+            overflow,
+            error,
+        }
+    }
+
+    fn u256(high: u128, low: u128) -> Literal {
+        let mut bytes = [0; 32];
+        bytes[..16].copy_from_slice(&high.to_be_bytes());
+        bytes[16..].copy_from_slice(&low.to_be_bytes());
+        Literal::U256(bytes)
+    }
+
+    fn load_literal(
+        literal: &Literal,
+        register_sequencer: &mut RegisterSequencer,
+        data_section: &mut DataSection,
+        ops: &mut Vec<Op>,
+    ) -> VirtualRegister {
+        let register = register_sequencer.next();
+        let data_label = data_section.insert_data_value(literal);
+        ops.push(Op::unowned_load_data_comment(
+            register.clone(),
+            data_label,
+            "",
+        ));
+        register
+    }
+
+    /// Runs a script computing `op` on `lhs` and `rhs`, comparing an arithmetic result against
+    /// `expected` in the script itself.
+    fn run(op: WideIntegerOp, lhs: Literal, rhs: Literal, expected: Option<Literal>) -> Outcome {
+        let size_in_words = lhs.as_type().stack_size_of();
+        let mut register_sequencer = RegisterSequencer::new();
+        let mut data_section = DataSection::default();
+        let mut ops = build_preamble(&mut register_sequencer).to_vec();
+
+        let lhs = load_literal(&lhs, &mut register_sequencer, &mut data_section, &mut ops);
+        let rhs = load_literal(&rhs, &mut register_sequencer, &mut data_section, &mut ops);
+        // reading a flag with `MOVE` clears both of them, so the op is run again for `$err`
+        let mut run_op = |flag: ConstantRegister, ops: &mut Vec<Op>| {
+            let op_result = register_sequencer.next();
+            ops.append(&mut convert_wide_integer_op_to_asm(
+                op,
+                size_in_words,
+                &lhs,
+                &rhs,
+                &op_result,
+                &mut register_sequencer,
+                None,
+            ));
+            let flag_value = register_sequencer.next();
+            ops.push(Op::unowned_register_move(
+                flag_value.clone(),
+                VirtualRegister::Constant(flag),
+            ));
+            (op_result, flag_value)
+        };
+        let (op_result, overflow) = run_op(ConstantRegister::Overflow, &mut ops);
+        let (_, error) = run_op(ConstantRegister::Error, &mut ops);
+        let result = match expected {
+            Some(expected) => {
+                let expected = load_literal(
+                    &expected,
+                    &mut register_sequencer,
+                    &mut data_section,
+                    &mut ops,
+                );
+                let is_expected = register_sequencer.next();
+                ops.append(&mut convert_wide_integer_op_to_asm(
+                    WideIntegerOp::Eq,
+                    size_in_words,
+                    &op_result,
+                    &expected,
+                    &is_expected,
+                    &mut register_sequencer,
+                    None,
+                ));
+                is_expected
+            }
+            None => op_result,
+        };
+
+        // the script returns the result in bit 0, `$of` in bit 1 and `$err` in bit 2
+        ops.append(&mut vec![
+            Op::unowned_new_with_comment(
+                VirtualOp::SLLI(overflow.clone(), overflow.clone(), imm12(1)),
+                "",
+            ),
+            Op::unowned_new_with_comment(
+                VirtualOp::SLLI(error.clone(), error.clone(), imm12(2)),
+                "",
+            ),
+            Op::unowned_new_with_comment(
+                VirtualOp::OR(result.clone(), result.clone(), overflow),
+                "",
+            ),
+            Op::unowned_new_with_comment(VirtualOp::OR(result.clone(), result.clone(), error), ""),
+            Op::unowned_new_with_comment(VirtualOp::RET(result), ""),
+        ]);
+
+        let mut source_map = SourceMap::new();
+        let script = SwayAsmSet::ScriptMain {
+            data_section,
+            program_section: AbstractInstructionSet { ops },
+        }
+        .remove_unnecessary_jumps()
+        .allocate_registers(&mut register_sequencer)
+        .optimize()
+        .0
+        .to_bytecode_mut(&mut source_map)
+        .value
+        .unwrap();
+
+        let tx = Transaction::script(
+            10,
+            fuel_tx::consts::MAX_GAS_PER_TX,
+            0,
+            0,
+            script,
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+        );
+        tx.validate((u32::MAX >> 1) as u64).unwrap();
+        let mut interpreter = Interpreter::with_storage(MemoryStorage::default());
+        match *interpreter.transact(tx).unwrap().state() {
+            ProgramState::Return(returned) => {
+                outcome(returned & 1 == 1, returned & 2 == 2, returned & 4 == 4)
+            }
+            state => panic!("script didn't return: {:?}", state),
+        }
+    }
+
+    fn arithmetic(op: WideIntegerOp, lhs: Literal, rhs: Literal, expected: Literal) -> Outcome {
+        run(op, lhs, rhs, Some(expected))
+    }
+
+    fn compare(op: WideIntegerOp, lhs: Literal, rhs: Literal) -> Outcome {
+        run(op, lhs, rhs, None)
+    }
+
+    #[test]
+    fn u128_arithmetic() {
+        use Literal::U128;
+        use WideIntegerOp::*;
+        let big = 0x1234_5678_9abc_def0_0fed_cba9_8765_4321;
+        for (op, lhs, rhs, expected, overflow) in [
+            (Add, big, 1 << 64, big + (1 << 64), false),
+            (Add, u128::MAX, 1, 0, true),
+            (Sub, big, u64::MAX as u128, big - u64::MAX as u128, false),
+            (Sub, 0, 1, u128::MAX, true),
+            (
+                Mul,
+                u64::MAX as u128,
+                u64::MAX as u128,
+                (u64::MAX as u128).pow(2),
+                false,
+            ),
+            (Mul, big, 1 << 8, big << 8, true),
+            (Div, big, 0x1_0000_0003, big / 0x1_0000_0003, false),
+            (Mod, big, 0x1_0000_0003, big % 0x1_0000_0003, false),
+            (Div, 7, big, 0, false),
+        ] {
+            assert_eq!(
+                arithmetic(op, U128(lhs), U128(rhs), U128(expected)),
+                outcome(true, overflow, false),
+                "{:?} {:#x} {:#x}",
+                op,
+                lhs,
+                rhs,
+            );
+        }
+    }
+
+    #[test]
+    fn u256_arithmetic() {
+        use WideIntegerOp::*;
+        for (op, lhs, rhs, expected, overflow) in [
+            (Add, u256(0, u128::MAX), u256(0, 1), u256(1, 0), false),
+            (
+                Add,
+                u256(u128::MAX, u128::MAX),
+                u256(0, 1),
+                u256(0, 0),
+                true,
+            ),
+            (Sub, u256(1, 0), u256(0, 1), u256(0, u128::MAX), false),
+            (Sub, u256(0, 5), u256(1, 0), u256(u128::MAX, 5), true),
+            (Mul, u256(1, 0), u256(0, 1 << 127), u256(1 << 127, 0), false),
+            (Mul, u256(1, 0), u256(1, 0), u256(0, 0), true),
+            (Div, u256(1 << 127, 0), u256(1, 0), u256(0, 1 << 127), false),
+            (Mod, u256(1 << 72, 5), u256(0, 1 << 64), u256(0, 5), false),
+        ] {
+            assert_eq!(
+                arithmetic(op, lhs, rhs, expected),
+                outcome(true, overflow, false),
+                "{:?}",
+                op,
+            );
+        }
+    }
+
+    #[test]
+    fn division_by_zero_sets_the_error_flag() {
+        use WideIntegerOp::*;
+        for op in [Div, Mod] {
+            assert_eq!(
+                arithmetic(op, Literal::U128(42), Literal::U128(0), Literal::U128(0)),
+                outcome(true, false, true),
+            );
+            assert_eq!(
+                arithmetic(op, u256(42, 0), u256(0, 0), u256(0, 0)),
+                outcome(true, false, true),
+            );
+        }
+    }
+
+    #[test]
+    fn comparisons() {
+        use WideIntegerOp::*;
+        let high = || u256(1, 0);
+        let low = || u256(0, u128::MAX);
+        for (op, less, equal, greater) in [
+            (Eq, false, true, false),
+            (Neq, true, false, true),
+            (Lt, true, false, false),
+            (Gt, false, false, true),
+            (Le, true, true, false),
+            (Ge, false, true, true),
+        ] {
+            assert_eq!(compare(op, low(), high()), outcome(less, false, false));
+            assert_eq!(compare(op, high(), high()), outcome(equal, false, false));
+            assert_eq!(compare(op, high(), low()), outcome(greater, false, false));
+            assert_eq!(
+                compare(op, Literal::U128(1 << 64), Literal::U128(u64::MAX as u128)),
+                outcome(greater, false, false),
+            );
+        }
+    }
+}
@@ -223,6 +223,81 @@ impl Op {
         }
     }
 
+    /// Marks the start of the body of function `name`, which was inlined at `call_site` unless it
+    /// is the program's entry point.
+    pub(crate) fn debug_scope_begin(
+        name: impl Into<String>,
+        span: Option<Span>,
+        call_site: Option<Span>,
+    ) -> Self {
+        let name = name.into();
+        Op {
+            comment: format!("begin {}", name),
+            opcode: Either::Right(OrganizationalOp::Debug(DebugMarker::ScopeBegin {
+                name,
+                span,
+                call_site,
+            })),
+            owning_span: None,
+        }
+    }
+
+    /// Marks the end of the body of the innermost function.
+    pub(crate) fn debug_scope_end() -> Self {
+        Op {
+            opcode: Either::Right(OrganizationalOp::Debug(DebugMarker::ScopeEnd)),
+            comment: "end".into(),
+            owning_span: None,
+        }
+    }
+
+    /// Marks the point from which variable `name` is held in `register`, or in memory at
+    /// `offset_in_words` from the address in `register`.
+    pub(crate) fn debug_variable(
+        name: impl Into<String>,
+        span: Option<Span>,
+        register: VirtualRegister,
+        offset_in_words: Option<u64>,
+    ) -> Self {
+        let name = name.into();
+        Op {
+            comment: format!("variable {}", name),
+            opcode: Either::Right(OrganizationalOp::Debug(DebugMarker::Variable {
+                name,
+                span,
+                register,
+                offset_in_words,
+            })),
+            owning_span: None,
+        }
+    }
+
+    /// Marks the start of a call which may lead back to the function making it. The registers
+    /// live across the call are saved here, once registers are allocated, along with `registers`,
+    /// which the call itself overwrites before they're needed again by the calling function.
+    pub(crate) fn save_registers(registers: Vec<VirtualRegister>) -> Self {
+        Op {
+            opcode: Either::Right(OrganizationalOp::SaveRegisters(registers)),
+            comment: "save registers".into(),
+            owning_span: None,
+        }
+    }
+
+    /// Marks the end of the call started by the last [Op::save_registers], where the saved
+    /// registers are restored.
+    pub(crate) fn restore_registers() -> Self {
+        Op {
+            opcode: Either::Right(OrganizationalOp::RestoreRegisters),
+            comment: "restore registers".into(),
+            owning_span: None,
+        }
+    }
+
+    /// Whether this op only carries debug information.
+    pub(crate) fn is_debug_marker(&self) -> bool {
+        matches!(self.opcode, Either::Right(OrganizationalOp::Debug(_)))
+    }
+
     pub(crate) fn jump_to_label(label: Label) -> Self {
         Op {
             opcode: Either::Right(OrganizationalOp::Jump(label)),
@@ -1365,6 +1440,9 @@ impl fmt::Display for Op {
                 OrganizationalOp::DataSectionOffsetPlaceholder => {
                     "data section offset placeholder".into()
                 }
+                OrganizationalOp::Debug(_) => "".into(),
+                SaveRegisters(_) => "save registers".into(),
+                RestoreRegisters => "restore registers".into(),
             },
         };
         // we want the comment to always be 40 characters offset to the right
@@ -1395,6 +1473,35 @@ pub(crate) enum OrganizationalOp {
     JumpIfNotEq(VirtualRegister, VirtualRegister, Label),
     // placeholder for the DataSection offset
     DataSectionOffsetPlaceholder,
+    // Debug information for the op which follows it, which doesn't compile to any op
+    Debug(DebugMarker),
+    // Saves the registers live across a recursive call and the given ones, expanded once
+    // registers are allocated
+    SaveRegisters(Vec<VirtualRegister>),
+    // Restores the registers saved by the last SaveRegisters
+    RestoreRegisters,
+}
+
+/// Debug information carried through the op stream, so that it stays attached to the right op
+/// while labels are realized and registers are allocated.
+#[derive(Clone)]
+pub(crate) enum DebugMarker {
+    /// The start of the body of a function, which was inlined at `call_site` if it was called.
+    ScopeBegin {
+        name: String,
+        span: Option<Span>,
+        call_site: Option<Span>,
+    },
+    /// The end of the body of the innermost function.
+    ScopeEnd,
+    /// A variable which is held in `register`, or in memory at `offset_in_words` from the address
+    /// in `register`, from this point on.
+    Variable {
+        name: String,
+        span: Option<Span>,
+        register: VirtualRegister,
+        offset_in_words: Option<u64>,
+    },
 }
 impl fmt::Display for OrganizationalOp {
     fn fmt(&self, fmtr: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -1405,7 +1512,9 @@ impl fmt::Display for OrganizationalOp {
             match self {
                 Label(lab) => format!("{}", lab),
                 Jump(lab) => format!("ji  {}", lab),
-                Comment => "".into(),
+                Comment | Debug(_) => "".into(),
+                SaveRegisters(_) => "save registers".into(),
+                RestoreRegisters => "restore registers".into(),
                 JumpIfNotEq(r1, r2, lab) => format!("jnei {} {} {}", r1, r2, lab),
                 DataSectionOffsetPlaceholder =>
                     "DATA SECTION OFFSET[0..32]\nDATA SECTION OFFSET[32..64]".into(),
@@ -1418,7 +1527,13 @@ impl OrganizationalOp {
     pub(crate) fn registers(&self) -> HashSet<&VirtualRegister> {
         use OrganizationalOp::*;
         (match self {
-            Label(_) | Comment | Jump(_) | DataSectionOffsetPlaceholder => vec![],
+            Label(_)
+            | Comment
+            | Jump(_)
+            | DataSectionOffsetPlaceholder
+            | Debug(_)
+            | SaveRegisters(_)
+            | RestoreRegisters => vec![],
             JumpIfNotEq(r1, r2, _) => vec![r1, r2],
         })
         .into_iter()
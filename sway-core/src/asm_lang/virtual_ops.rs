@@ -1151,7 +1151,7 @@ fn update_reg(
     }
 }
 
-#[derive(Clone, Eq, PartialEq, Hash)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
 /// A label for a spot in the bytecode, to be later compiled to an offset.
 pub(crate) struct Label(pub(crate) usize);
 impl fmt::Display for Label {
@@ -1,4 +1,5 @@
 use std::{
+    collections::HashMap,
     path::PathBuf,
     sync::{Arc, Mutex},
 };
@@ -13,7 +14,12 @@ pub struct BuildConfig {
     pub(crate) print_intermediate_asm: bool,
     pub(crate) print_finalized_asm: bool,
     pub(crate) print_ir: bool,
+    pub(crate) ir_pipeline: String,
+    pub(crate) time_ir_passes: bool,
     pub(crate) generated_names: Arc<Mutex<Vec<&'static str>>>,
+    /// Sources used in place of the contents of the files at the given paths, e.g. the unsaved
+    /// contents of files open in an editor.
+    pub(crate) source_overrides: Arc<HashMap<PathBuf, Arc<str>>>,
 }
 
 impl BuildConfig {
@@ -33,7 +39,10 @@ impl BuildConfig {
             print_intermediate_asm: false,
             print_finalized_asm: false,
             print_ir: false,
+            ir_pipeline: "O1".to_owned(),
+            time_ir_passes: false,
             generated_names: Arc::new(Mutex::new(vec![])),
+            source_overrides: Default::default(),
         }
     }
 
@@ -62,6 +71,31 @@ impl BuildConfig {
         }
     }
 
+    /// Set the name of the pass pipeline, e.g. `O2`, run over the IR when compiling using the IR
+    /// pipeline.  Defaults to `O1`.
+    pub fn ir_pipeline(self, a: String) -> Self {
+        Self {
+            ir_pipeline: a,
+            ..self
+        }
+    }
+
+    pub fn time_ir_passes(self, a: bool) -> Self {
+        Self {
+            time_ir_passes: a,
+            ..self
+        }
+    }
+
+    /// Use the given sources in place of reading the files at their paths when importing
+    /// dependencies with the `dep` keyword.
+    pub fn source_overrides(self, sources: HashMap<PathBuf, Arc<str>>) -> Self {
+        Self {
+            source_overrides: Arc::new(sources),
+            ..self
+        }
+    }
+
     pub fn path(&self) -> Arc<PathBuf> {
         self.file_name.clone()
     }
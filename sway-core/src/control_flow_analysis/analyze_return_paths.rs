@@ -24,6 +24,7 @@ impl ControlFlowGraph {
             graph: Graph::new(),
             entry_points: vec![],
             namespace: Default::default(),
+            loops: vec![],
         };
         // do a depth first traversal and cover individual inner ast nodes
         let mut leaves = vec![];
@@ -166,7 +167,19 @@ fn connect_node(
             }
             NodeConnection::NextStep(vec![entry])
         }
-        TypedAstNodeContent::SideEffect => NodeConnection::NextStep(leaves.to_vec()),
+        TypedAstNodeContent::Break | TypedAstNodeContent::Continue => {
+            // Loop bodies are abridged to a single node above, so a break or continue leaves the
+            // loop like any other path through it: neither one returns from the function, and
+            // whatever comes after the loop is still to be checked.
+            let this_index = graph.add_node(node.into());
+            for leaf in leaves {
+                graph.add_edge(*leaf, this_index, "".into());
+            }
+            NodeConnection::NextStep(vec![this_index])
+        }
+        TypedAstNodeContent::SideEffect
+        | TypedAstNodeContent::IncludeStatement(_)
+        | TypedAstNodeContent::UseStatement(_) => NodeConnection::NextStep(leaves.to_vec()),
         TypedAstNodeContent::Declaration(decl) => {
             NodeConnection::NextStep(connect_declaration(node, decl, graph, span, leaves))
         }
@@ -184,6 +197,7 @@ fn connect_declaration(
     match decl {
         TraitDeclaration(_)
         | AbiDeclaration(_)
+        | StorageDeclaration(_)
         | StructDeclaration(_)
         | EnumDeclaration(_)
         | GenericTypeForFunctionScope { .. } => leaves.to_vec(),
@@ -202,7 +216,7 @@ fn connect_declaration(
             connect_typed_fn_decl(fn_decl, graph, entry_node, span);
             leaves.to_vec()
         }
-        Reassignment(TypedReassignment { .. }) => {
+        Reassignment(TypedReassignment { .. }) | StorageReassignment(_) => {
             let entry_node = graph.add_node(node.into());
             for leaf in leaves {
                 graph.add_edge(*leaf, entry_node, "".into());
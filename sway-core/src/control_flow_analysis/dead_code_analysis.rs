@@ -7,8 +7,8 @@ use crate::{
             SizeOfVariant, TypedAbiDeclaration, TypedCodeBlock, TypedConstantDeclaration,
             TypedDeclaration, TypedEnumDeclaration, TypedExpression, TypedExpressionVariant,
             TypedFunctionDeclaration, TypedReassignment, TypedReturnStatement,
-            TypedStructDeclaration, TypedStructExpressionField, TypedTraitDeclaration,
-            TypedVariableDeclaration, TypedWhileLoop,
+            TypedStorageReassignment, TypedStructDeclaration, TypedStructExpressionField,
+            TypedTraitDeclaration, TypedVariableDeclaration, TypedWhileLoop,
         },
         TypedAstNode, TypedAstNodeContent, TypedParseTree,
     },
@@ -177,6 +177,27 @@ impl ControlFlowGraph {
                 })
                 .collect(),
         };
+
+        // test functions are entry points too, as the test runner calls them directly
+        let test_functions = graph
+            .graph
+            .node_indices()
+            .filter(|i| {
+                matches!(
+                    graph.graph[*i],
+                    ControlFlowGraphNode::ProgramNode(TypedAstNode {
+                        content: TypedAstNodeContent::Declaration(
+                            TypedDeclaration::FunctionDeclaration(TypedFunctionDeclaration {
+                                is_test: true,
+                                ..
+                            })
+                        ),
+                        ..
+                    })
+                )
+            })
+            .collect::<Vec<_>>();
+        graph.entry_points.extend(test_functions);
         Ok(())
     }
 }
@@ -261,8 +282,10 @@ fn connect_node(
                 "condition is initially false".into(),
             );
             let mut leaves = vec![entry];
+            graph.loops.push((entry, while_loop_exit));
             let (l_leaves, _l_exit_node) =
                 depth_first_insertion_code_block(body, graph, &leaves, exit_node, tree_type)?;
+            graph.loops.pop();
             // insert edges from end of block back to beginning of it
             for leaf in &l_leaves {
                 graph.add_edge(*leaf, entry, "loop repeats".into());
@@ -299,7 +322,26 @@ fn connect_node(
                 exit_node,
             )
         }
-        TypedAstNodeContent::SideEffect => (leaves.to_vec(), exit_node),
+        TypedAstNodeContent::Break | TypedAstNodeContent::Continue => {
+            let this_index = graph.add_node(node.into());
+            for leaf_ix in leaves {
+                graph.add_edge(*leaf_ix, this_index, "".into());
+            }
+            // jump to the end or the start of the innermost loop, so nothing following this node
+            // is reachable from it
+            if let Some((loop_entry, loop_exit)) = graph.loops.last().copied() {
+                if matches!(node.content, TypedAstNodeContent::Break) {
+                    graph.add_edge(this_index, loop_exit, "break".into());
+                } else {
+                    graph.add_edge(this_index, loop_entry, "continue".into());
+                }
+            }
+            (vec![], exit_node)
+        }
+        // The included file's nodes were already appended to the graph when it was imported.
+        TypedAstNodeContent::SideEffect
+        | TypedAstNodeContent::IncludeStatement(_)
+        | TypedAstNodeContent::UseStatement(_) => (leaves.to_vec(), exit_node),
         TypedAstNodeContent::Declaration(decl) => {
             // all leaves connect to this node, then this node is the singular leaf
             let decl_node = graph.add_node(node.into());
@@ -367,6 +409,17 @@ fn connect_declaration(
             tree_type,
             rhs.clone().span,
         ),
+        StorageReassignment(TypedStorageReassignment { rhs, .. }) => connect_expression(
+            &rhs.expression,
+            graph,
+            &[entry_node],
+            exit_node,
+            "storage reassignment",
+            tree_type,
+            rhs.clone().span,
+        ),
+        // storage fields are not tracked for dead code, since they are persistent across calls
+        StorageDeclaration(_) => Ok(leaves.to_vec()),
         ImplTrait {
             trait_name,
             methods,
@@ -663,7 +716,47 @@ fn connect_expression(
                 Ok(vec![fn_entrypoint])
             }
         }
-        LazyOperator { lhs, rhs, .. } => {
+        FunctionReference { name, .. } => {
+            let node = graph.add_node(format!("\"{}\" fn reference", name.suffix.as_str()).into());
+            for leaf in leaves {
+                graph.add_edge(*leaf, node, label.into());
+            }
+            // referencing a function counts as a use of it, since it may be called through the
+            // reference later on
+            if let Some(FunctionNamespaceEntry { entry_point, .. }) =
+                graph.namespace.get_function(&name.suffix).cloned()
+            {
+                graph.add_edge(node, entry_point, "fn reference".into());
+            }
+            Ok(vec![node])
+        }
+        IndirectFunctionApplication {
+            function,
+            arguments,
+        } => {
+            let mut current_leaf = connect_expression(
+                &function.expression,
+                graph,
+                leaves,
+                exit_node,
+                label,
+                tree_type,
+                function.span.clone(),
+            )?;
+            for arg in arguments {
+                current_leaf = connect_expression(
+                    &arg.expression,
+                    graph,
+                    &current_leaf,
+                    exit_node,
+                    "arg eval",
+                    tree_type,
+                    arg.span.clone(),
+                )?;
+            }
+            Ok(current_leaf)
+        }
+        LazyOperator { lhs, rhs, .. } | WideIntegerOperator { lhs, rhs, .. } => {
             let lhs_expr = connect_expression(
                 &lhs.expression,
                 graph,
@@ -753,7 +846,9 @@ fn connect_expression(
                     else_expr.clone().span,
                 )?
             } else {
-                vec![]
+                // without an else branch, control flows on from the condition when it is false,
+                // even if the then branch breaks, continues or returns
+                condition_expr
             };
 
             Ok([then_expr, else_expr].concat())
@@ -970,6 +1065,13 @@ fn connect_expression(
                 Ok(exp)
             }
         },
+        StorageAccess(access) => {
+            let node = graph.add_node(format!("storage.{}", access.field_name.as_str()).into());
+            for leaf in leaves {
+                graph.add_edge(*leaf, node, "".into());
+            }
+            Ok(vec![node])
+        }
         a => {
             println!("Unimplemented: {:?}", a);
             Err(CompileError::Unimplemented(
@@ -1062,6 +1164,12 @@ fn construct_dead_code_warning_from_node(node: &TypedAstNode) -> Option<CompileW
             content: TypedAstNodeContent::Declaration(TypedDeclaration::AbiDeclaration { .. }),
             ..
         } => return None,
+        // storage is part of a contract's interface with the outside world, even if it is never
+        // accessed from within the contract itself
+        TypedAstNode {
+            content: TypedAstNodeContent::Declaration(TypedDeclaration::StorageDeclaration(..)),
+            ..
+        } => return None,
         TypedAstNode {
             content: TypedAstNodeContent::Declaration(..),
             span,
@@ -25,6 +25,9 @@ pub struct ControlFlowGraph {
     pub(crate) graph: Graph,
     pub(crate) entry_points: Vec<NodeIndex>,
     pub(crate) namespace: ControlFlowNamespace,
+    /// The entry and exit nodes of the loops surrounding the nodes currently being connected,
+    /// innermost last. `continue` and `break` are connected to these.
+    pub(crate) loops: Vec<(NodeIndex, NodeIndex)>,
 }
 
 pub type Graph = petgraph::Graph<ControlFlowGraphNode, ControlFlowGraphEdge>;
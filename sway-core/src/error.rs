@@ -176,6 +176,11 @@ impl CompileWarning {
         self.warning_content.to_string()
     }
 
+    /// A stable code identifying the kind of warning, e.g. `W0001`.
+    pub fn code(&self) -> &'static str {
+        self.warning_content.code()
+    }
+
     pub fn span(&self) -> (usize, usize) {
         (self.span.start(), self.span.end())
     }
@@ -245,6 +250,58 @@ pub enum Warning {
     ShadowingReservedRegister {
         reg_name: Ident,
     },
+    MatchArmUnreachable,
+}
+
+impl Warning {
+    /// A stable code identifying the kind of warning, e.g. `W0001`.
+    ///
+    /// Codes are never reused: a new kind of warning takes the next unused code, and the code of
+    /// a removed one is retired.
+    pub fn code(&self) -> &'static str {
+        use Warning::*;
+        match self {
+            NonClassCaseStructName { .. } => "W0001",
+            NonClassCaseTraitName { .. } => "W0002",
+            NonClassCaseEnumName { .. } => "W0003",
+            NonClassCaseEnumVariantName { .. } => "W0004",
+            NonSnakeCaseStructFieldName { .. } => "W0005",
+            NonSnakeCaseFunctionName { .. } => "W0006",
+            NonScreamingSnakeCaseConstName { .. } => "W0007",
+            LossOfPrecision { .. } => "W0008",
+            UnusedReturnValue { .. } => "W0009",
+            SimilarMethodFound { .. } => "W0010",
+            ShadowsOtherSymbol { .. } => "W0011",
+            OverridingTraitImplementation => "W0012",
+            DeadDeclaration => "W0013",
+            DeadFunctionDeclaration => "W0014",
+            DeadStructDeclaration => "W0015",
+            DeadTrait => "W0016",
+            UnreachableCode => "W0017",
+            DeadEnumVariant { .. } => "W0018",
+            DeadMethod => "W0019",
+            StructFieldNeverRead => "W0020",
+            ShadowingReservedRegister { .. } => "W0021",
+            MatchArmUnreachable => "W0022",
+        }
+    }
+
+    /// The idiomatic name suggested by a warning about a naming convention.
+    pub fn suggested_name(&self) -> Option<String> {
+        use Warning::*;
+        match self {
+            NonClassCaseStructName { struct_name: name }
+            | NonClassCaseTraitName { name }
+            | NonClassCaseEnumName { enum_name: name }
+            | NonClassCaseEnumVariantName { variant_name: name } => {
+                Some(to_upper_camel_case(name.as_str()))
+            }
+            NonSnakeCaseStructFieldName { field_name: name }
+            | NonSnakeCaseFunctionName { name } => Some(to_snake_case(name.as_str())),
+            NonScreamingSnakeCaseConstName { name } => Some(to_screaming_snake_case(name.as_str())),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for Warning {
@@ -350,6 +407,11 @@ impl fmt::Display for Warning {
                 "This register declaration shadows the reserved register, \"{}\".",
                 reg_name
             ),
+            MatchArmUnreachable => write!(
+                f,
+                "This match arm is unreachable, as every value it matches is matched by an \
+                 earlier arm."
+            ),
         }
     }
 }
@@ -480,8 +542,13 @@ pub enum CompileError {
         kind: &'static str,
         span: Span,
     },
-    #[error("Assignment to immutable variable. Variable {0} is not declared as mutable.")]
-    AssignmentToNonMutable(String, Span),
+    #[error("Assignment to immutable variable. Variable {name} is not declared as mutable.")]
+    AssignmentToNonMutable {
+        name: String,
+        /// The name of the variable at its declaration.
+        decl_span: Span,
+        span: Span,
+    },
     #[error(
         "Generic type \"{name}\" is not in scope. Perhaps you meant to specify type parameters in \
          the function signature? For example: \n`fn \
@@ -521,6 +588,8 @@ pub enum CompileError {
     #[error("Functions are missing from this trait implementation: {missing_functions}")]
     MissingInterfaceSurfaceMethods {
         missing_functions: String,
+        /// The signatures of the missing functions as written in the trait.
+        missing_signatures: Vec<String>,
         span: Span,
     },
     #[error("Expected {expected} type arguments, but instead found {given}.")]
@@ -769,16 +838,6 @@ pub enum CompileError {
         should_be: String,
         provided: String,
     },
-    #[error("Function {fn_name} is recursive, which is unsupported at this time.")]
-    RecursiveCall { fn_name: Ident, span: Span },
-    #[error(
-        "Function {fn_name} is recursive via {call_chain}, which is unsupported at this time."
-    )]
-    RecursiveCallChain {
-        fn_name: Ident,
-        call_chain: String, // Pretty list of symbols, e.g., "a, b and c".
-        span: Span,
-    },
     #[error(
         "The size of this type is not known. Try putting it on the heap or changing the type."
     )]
@@ -855,6 +914,63 @@ pub enum CompileError {
     UnrecognizedContractParam { param_name: String, span: Span },
     #[error("Attempting to specify a contract method parameter for a non-contract function call")]
     CallParamForNonContractCallMethod { span: Span },
+    #[error("Storage declarations are only allowed in contracts.")]
+    StorageDeclarationInNonContract { span: Span },
+    #[error("Multiple storage declarations were found. A contract may only declare storage once.")]
+    MultipleStorageDeclarations { span: Span },
+    #[error(
+        "Storage field \"{field_name}\" does not exist in this contract's storage declaration."
+    )]
+    StorageFieldDoesNotExist { field_name: String, span: Span },
+    #[error("Attempted to access storage, but this contract does not declare any storage.")]
+    NoDeclaredStorage { span: Span },
+    #[error("Storage access is only allowed in impure functions. Try making the surrounding function impure by prepending \"impure\" to the function declaration.")]
+    StorageAccessInPureContext { span: Span },
+    #[error("Storage field \"{field_name}\" has type \"{ty}\", but only word-sized types and b256 are supported in storage right now.")]
+    UnsupportedStorageFieldType {
+        field_name: String,
+        ty: String,
+        span: Span,
+    },
+    #[error("Storage field initializers must be literals.")]
+    NonConstantStorageInitializer { span: Span },
+    #[error("Unknown attribute \"{attribute}\". The only supported attribute is \"test\".")]
+    UnknownAttribute { attribute: String, span: Span },
+    #[error("Methods cannot be tests. Only free functions may be annotated with \"#[test]\".")]
+    TestAttributeOnMethod { span: Span },
+    #[error("Test function \"{name}\" must take no parameters, have no type parameters and return \"()\".")]
+    InvalidTestFunctionSignature { name: String, span: Span },
+    #[error("\"break\" can only be used inside of a loop.")]
+    BreakOutsideLoop { span: Span },
+    #[error("\"continue\" can only be used inside of a loop.")]
+    ContinueOutsideLoop { span: Span },
+    #[error("Only ranges and arrays can be iterated over, but this expression has type \"{ty}\".")]
+    NotIterable { ty: String, span: Span },
+    #[error("Non-exhaustive match expression. Missing patterns: {missing_patterns}.")]
+    MatchExpressionNonExhaustive {
+        missing_patterns: String,
+        span: Span,
+    },
+    #[error(
+        "Multiple methods named \"{method_name}\" for type \"{type_name}\" fit this call: \
+         {candidates}. Try annotating the type this call is expected to return."
+    )]
+    AmbiguousMethod {
+        method_name: String,
+        type_name: String,
+        candidates: String,
+        span: Span,
+    },
+    #[error(
+        "None of the methods named \"{method_name}\" for type \"{type_name}\" fit this call: \
+         {candidates}."
+    )]
+    NoMatchingMethod {
+        method_name: String,
+        type_name: String,
+        candidates: String,
+        span: Span,
+    },
 }
 
 impl std::convert::From<TypeError> for CompileError {
@@ -931,6 +1047,150 @@ impl CompileError {
         }
     }
 
+    /// A stable code identifying the kind of error, e.g. `E0001`.
+    ///
+    /// Codes are never reused: a new kind of error takes the next unused code, and the code of a
+    /// removed one is retired.
+    pub fn code(&self) -> &'static str {
+        use CompileError::*;
+        match self {
+            UnknownVariable { .. } => "E0001",
+            UnknownVariablePath { .. } => "E0002",
+            UnknownFunction { .. } => "E0003",
+            NotAVariable { .. } => "E0004",
+            NotAFunction { .. } => "E0005",
+            Unimplemented { .. } => "E0006",
+            PatternMatchingAlgorithmFailure { .. } => "E0007",
+            TypeError(self::TypeError::MismatchedType { .. }) => "E0008",
+            ParseFailure { .. } => "E0009",
+            ParseError { .. } => "E0010",
+            InvalidTopLevelItem { .. } => "E0011",
+            Internal { .. } => "E0012",
+            InternalOwned { .. } => "E0013",
+            UnimplementedRule { .. } => "E0014",
+            InvalidByteLiteralLength { .. } => "E0015",
+            ExpectedExprAfterOp { .. } => "E0016",
+            ExpectedOp { .. } => "E0017",
+            UnexpectedWhereClause { .. } => "E0018",
+            UndeclaredGenericTypeInWhereClause { .. } => "E0019",
+            MultipleContracts { .. } => "E0020",
+            MultipleScripts { .. } => "E0021",
+            MultiplePredicates { .. } => "E0022",
+            ConstrainedNonExistentType { .. } => "E0023",
+            MultiplePredicateMainFunctions { .. } => "E0024",
+            NoPredicateMainFunction { .. } => "E0025",
+            PredicateMainDoesNotReturnBool { .. } => "E0026",
+            NoScriptMainFunction { .. } => "E0027",
+            MultipleScriptMainFunctions { .. } => "E0028",
+            ReassignmentToNonVariable { .. } => "E0029",
+            AssignmentToNonMutable { .. } => "E0030",
+            TypeParameterNotInTypeScope { .. } => "E0031",
+            MultipleImmediates { .. } => "E0032",
+            MismatchedTypeInTrait { .. } => "E0033",
+            NotATrait { .. } => "E0034",
+            UnknownTrait { .. } => "E0035",
+            FunctionNotAPartOfInterfaceSurface { .. } => "E0036",
+            MissingInterfaceSurfaceMethods { .. } => "E0037",
+            IncorrectNumberOfTypeArguments { .. } => "E0038",
+            StructNotFound { .. } => "E0039",
+            DeclaredNonStructAsStruct { .. } => "E0040",
+            AccessedFieldOfNonStruct { .. } => "E0041",
+            MethodOnNonValue { .. } => "E0042",
+            StructMissingField { .. } => "E0043",
+            StructDoesNotHaveField { .. } => "E0044",
+            MethodNotFound { .. } => "E0045",
+            ModuleNotFound { .. } => "E0046",
+            NotAStruct { .. } => "E0047",
+            NotATuple { .. } => "E0048",
+            NotAnEnum { .. } => "E0049",
+            FieldNotFound { .. } => "E0050",
+            SymbolNotFound { .. } => "E0051",
+            ImportPrivateSymbol { .. } => "E0052",
+            NoElseBranch { .. } => "E0053",
+            UnqualifiedSelfType { .. } => "E0054",
+            NotAType { .. } => "E0055",
+            MissingEnumInstantiator { .. } => "E0056",
+            PathDoesNotReturn { .. } => "E0057",
+            ExpectedImplicitReturnFromBlockWithType { .. } => "E0058",
+            ExpectedImplicitReturnFromBlock { .. } => "E0059",
+            UnknownRegister { .. } => "E0060",
+            MissingImmediate { .. } => "E0061",
+            InvalidImmediateValue { .. } => "E0062",
+            InvalidAssemblyMismatchedReturn { .. } => "E0063",
+            UnknownEnumVariant { .. } => "E0064",
+            UnrecognizedOp { .. } => "E0065",
+            TypeMustBeKnown { .. } => "E0066",
+            Immediate06TooLarge { .. } => "E0067",
+            Immediate12TooLarge { .. } => "E0068",
+            Immediate18TooLarge { .. } => "E0069",
+            Immediate24TooLarge { .. } => "E0070",
+            DisallowedJnei { .. } => "E0071",
+            DisallowedJi { .. } => "E0072",
+            DisallowedLw { .. } => "E0073",
+            IncorrectNumberOfAsmRegisters { .. } => "E0074",
+            UnnecessaryImmediate { .. } => "E0075",
+            AmbiguousPath { .. } => "E0076",
+            InvalidStrType { .. } => "E0077",
+            UnknownType { .. } => "E0078",
+            TooManyInstructions { .. } => "E0079",
+            FileNotFound { .. } => "E0080",
+            FileCouldNotBeRead { .. } => "E0081",
+            ImportMustBeLibrary { .. } => "E0082",
+            MoreThanOneEnumInstantiator { .. } => "E0083",
+            UnnecessaryEnumInstantiator { .. } => "E0084",
+            TraitNotFound { .. } => "E0085",
+            InvalidExpressionOnLhs { .. } => "E0086",
+            TooManyArgumentsForFunction { .. } => "E0087",
+            TooFewArgumentsForFunction { .. } => "E0088",
+            InvalidAbiType { .. } => "E0089",
+            NotAnAbi { .. } => "E0090",
+            ImplAbiForNonContract { .. } => "E0091",
+            IncorrectNumberOfInterfaceSurfaceFunctionParameters { .. } => "E0092",
+            ArgumentParameterTypeMismatch { .. } => "E0093",
+            TypeWithUnknownSize { .. } => "E0096",
+            InfiniteDependencies { .. } => "E0097",
+            GMFromExternalContract { .. } => "E0098",
+            MintFromExternalContext { .. } => "E0099",
+            BurnFromExternalContext { .. } => "E0100",
+            ContractStorageFromExternalContext { .. } => "E0101",
+            ArrayOutOfBounds { .. } => "E0102",
+            TupleOutOfBounds { .. } => "E0103",
+            ShadowsOtherSymbol { .. } => "E0104",
+            StarImportShadowsOtherSymbol { .. } => "E0105",
+            MatchWrongType { .. } => "E0106",
+            PureCalledImpure { .. } => "E0107",
+            ImpureInNonContract { .. } => "E0108",
+            IntegerTooLarge { .. } => "E0109",
+            IntegerTooSmall { .. } => "E0110",
+            IntegerContainsInvalidDigit { .. } => "E0111",
+            AsteriskWithAlias { .. } => "E0112",
+            AbiAsSupertrait { .. } => "E0113",
+            NameDefinedMultipleTimesForTrait { .. } => "E0114",
+            SupertraitImplMissing { .. } => "E0115",
+            SupertraitImplRequired { .. } => "E0116",
+            ContractCallParamRepeated { .. } => "E0117",
+            UnrecognizedContractParam { .. } => "E0118",
+            CallParamForNonContractCallMethod { .. } => "E0119",
+            StorageDeclarationInNonContract { .. } => "E0120",
+            MultipleStorageDeclarations { .. } => "E0121",
+            StorageFieldDoesNotExist { .. } => "E0122",
+            NoDeclaredStorage { .. } => "E0123",
+            StorageAccessInPureContext { .. } => "E0124",
+            UnsupportedStorageFieldType { .. } => "E0125",
+            NonConstantStorageInitializer { .. } => "E0126",
+            UnknownAttribute { .. } => "E0127",
+            TestAttributeOnMethod { .. } => "E0128",
+            InvalidTestFunctionSignature { .. } => "E0129",
+            BreakOutsideLoop { .. } => "E0130",
+            ContinueOutsideLoop { .. } => "E0131",
+            NotIterable { .. } => "E0132",
+            MatchExpressionNonExhaustive { .. } => "E0133",
+            TypeError(self::TypeError::UnknownType { .. }) => "E0134",
+            AmbiguousMethod { .. } => "E0135",
+            NoMatchingMethod { .. } => "E0136",
+        }
+    }
+
     pub fn span(&self) -> (usize, usize) {
         let sp = self.internal_span();
         (sp.start(), sp.end())
@@ -971,7 +1231,7 @@ impl CompileError {
             NoScriptMainFunction(span) => span,
             MultipleScriptMainFunctions(span) => span,
             ReassignmentToNonVariable { span, .. } => span,
-            AssignmentToNonMutable(_, span) => span,
+            AssignmentToNonMutable { span, .. } => span,
             TypeParameterNotInTypeScope { span, .. } => span,
             MultipleImmediates(span) => span,
             MismatchedTypeInTrait { span, .. } => span,
@@ -1034,8 +1294,6 @@ impl CompileError {
             ImplAbiForNonContract { span, .. } => span,
             IncorrectNumberOfInterfaceSurfaceFunctionParameters { span, .. } => span,
             ArgumentParameterTypeMismatch { span, .. } => span,
-            RecursiveCall { span, .. } => span,
-            RecursiveCallChain { span, .. } => span,
             TypeWithUnknownSize { span, .. } => span,
             InfiniteDependencies { span, .. } => span,
             GMFromExternalContract { span, .. } => span,
@@ -1062,6 +1320,22 @@ impl CompileError {
             ContractCallParamRepeated { span, .. } => span,
             UnrecognizedContractParam { span, .. } => span,
             CallParamForNonContractCallMethod { span, .. } => span,
+            StorageDeclarationInNonContract { span, .. } => span,
+            MultipleStorageDeclarations { span, .. } => span,
+            StorageFieldDoesNotExist { span, .. } => span,
+            NoDeclaredStorage { span, .. } => span,
+            StorageAccessInPureContext { span, .. } => span,
+            UnsupportedStorageFieldType { span, .. } => span,
+            NonConstantStorageInitializer { span, .. } => span,
+            UnknownAttribute { span, .. } => span,
+            TestAttributeOnMethod { span, .. } => span,
+            InvalidTestFunctionSignature { span, .. } => span,
+            BreakOutsideLoop { span, .. } => span,
+            ContinueOutsideLoop { span, .. } => span,
+            NotIterable { span, .. } => span,
+            MatchExpressionNonExhaustive { span, .. } => span,
+            AmbiguousMethod { span, .. } => span,
+            NoMatchingMethod { span, .. } => span,
         }
     }
 
@@ -30,15 +30,17 @@ use pest::iterators::Pair;
 use pest::Parser;
 use std::collections::HashMap;
 use std::sync::Arc;
+use sway_ir::pass_manager::PassManager;
 
 pub use semantic_analysis::{
-    create_module, retrieve_module, Namespace, NamespaceRef, NamespaceWrapper, TreeType,
-    TypedDeclaration, TypedFunctionDeclaration, TypedParseTree,
+    abi_methods, create_module, import_paths, namespace_items, path_items, retrieve_module,
+    struct_fields, type_members, NamedItem, Namespace, NamespaceRef, NamespaceWrapper, StorageSlot,
+    Symbol, SymbolKind, TreeType, TypedDeclaration, TypedFunctionDeclaration, TypedParseTree,
 };
 pub mod types;
 pub use crate::parse_tree::{Declaration, Expression, UseStatement, WhileLoop, *};
 
-pub use error::{CompileError, CompileResult, CompileWarning};
+pub use error::{CompileError, CompileResult, CompileWarning, Warning};
 use sway_types::{ident::Ident, span};
 pub use type_engine::TypeInfo;
 
@@ -94,6 +96,12 @@ pub enum AstNodeContent {
     /// A control flow element which loops continually until some boolean expression evaluates as
     /// `false`.
     WhileLoop(WhileLoop),
+    /// A control flow element which loops once for every value in a range or array.
+    ForLoop(ForLoop),
+    /// A `break;` statement, which exits the innermost loop.
+    Break,
+    /// A `continue;` statement, which skips to the next iteration of the innermost loop.
+    Continue,
     /// A statement of the form `dep foo::bar;` which imports/includes another source file.
     IncludeStatement(IncludeStatement),
 }
@@ -231,6 +239,7 @@ fn get_end(err: &pest::error::Error<Rule>) -> usize {
 pub(crate) struct InnerDependencyCompileResult {
     name: Ident,
     namespace: Namespace,
+    include: semantic_analysis::ast_node::TypedIncludeStatement,
 }
 /// For internal compiler use.
 /// Compiles an included file and returns its control flow and dead code graphs.
@@ -293,10 +302,17 @@ pub(crate) fn compile_inner_dependency(
         errors.push(e)
     };
 
+    let nodes = typed_parse_tree.all_nodes().to_vec();
+    let namespace = typed_parse_tree.get_namespace_ref();
     ok(
         InnerDependencyCompileResult {
             name: library_name.clone(),
-            namespace: typed_parse_tree.into_namespace(),
+            namespace: retrieve_module(namespace),
+            include: semantic_analysis::ast_node::TypedIncludeStatement {
+                path: build_config.path(),
+                namespace,
+                nodes,
+            },
         },
         warnings,
         errors,
@@ -320,6 +336,7 @@ pub fn compile_to_ast(
         graph: Graph::new(),
         entry_points: vec![],
         namespace: Default::default(),
+        loops: vec![],
     };
 
     let typed_parse_tree = check!(
@@ -385,7 +402,7 @@ pub fn ast_to_asm(ast_res: CompileAstResult, build_config: &BuildConfig) -> Comp
                 TreeType::Contract | TreeType::Script | TreeType::Predicate => {
                     let asm = check!(
                         if build_config.use_ir {
-                            compile_ast_to_ir_to_asm(*parse_tree, tree_type, build_config)
+                            compile_ast_to_ir_to_asm(*parse_tree, build_config)
                         } else {
                             compile_ast_to_asm(*parse_tree, build_config)
                         },
@@ -408,14 +425,31 @@ pub fn ast_to_asm(ast_res: CompileAstResult, build_config: &BuildConfig) -> Comp
     }
 }
 
-use sway_ir::{context::Context, function::Function};
+/// Given a type-checked AST, compile each of its `#[test]` functions into a standalone script
+/// which runs that test. Returns the name of each test along with its [CompilationResult].
+pub fn ast_to_test_asm(
+    parse_tree: &TypedParseTree,
+    build_config: &BuildConfig,
+) -> Vec<(String, CompilationResult)> {
+    parse_tree
+        .test_scripts()
+        .into_iter()
+        .map(|(name, script)| {
+            let ast_res = CompileAstResult::Success {
+                parse_tree: Box::new(script),
+                tree_type: TreeType::Script,
+                warnings: vec![],
+            };
+            (name.as_str().to_string(), ast_to_asm(ast_res, build_config))
+        })
+        .collect()
+}
 
 pub(crate) fn compile_ast_to_ir_to_asm(
     ast: TypedParseTree,
-    tree_type: TreeType,
     build_config: &BuildConfig,
 ) -> CompileResult<FinalizedAsm> {
-    let mut warnings = Vec::new();
+    let warnings = Vec::new();
     let mut errors = Vec::new();
 
     let mut ir = match optimize::compile_ast(ast) {
@@ -432,33 +466,24 @@ pub(crate) fn compile_ast_to_ir_to_asm(
         }
     };
 
-    // Inline function calls since we don't support them yet.  For scripts and predicates we inline
-    // into main(), and for contracts we inline into ABI impls, which are found due to them having
-    // a selector.
-    let mut functions_to_inline_to = Vec::new();
-    for (idx, fc) in &ir.functions {
-        if (matches!(tree_type, TreeType::Script | TreeType::Predicate) && fc.name == "main")
-            || (tree_type == TreeType::Contract && fc.selector.is_some())
-        {
-            functions_to_inline_to.push(::sway_ir::function::Function(idx));
-        }
+    // Lower the IR into a form the backend can compile and optimize it.  Every pipeline lowers
+    // calls through function pointers to direct calls and then inlines the calls to functions
+    // which aren't recursive and are called once or are small.
+    let mut pass_mgr = PassManager::default();
+    pass_mgr.set_time_passes(build_config.time_ir_passes);
+    if let Err(ir_error) = pass_mgr.run_pipeline(&mut ir, &build_config.ir_pipeline) {
+        errors.push(CompileError::InternalOwned(
+            ir_error.to_string(),
+            span::Span {
+                span: pest::Span::new("".into(), 0, 0).unwrap(),
+                path: None,
+            },
+        ));
+        return err(warnings, errors);
+    }
+    if build_config.time_ir_passes {
+        print!("{}", pass_mgr.timings_report());
     }
-    check!(
-        inline_function_calls(&mut ir, &functions_to_inline_to),
-        return err(warnings, errors),
-        warnings,
-        errors
-    );
-
-    // The only other optimisation we have at the moment is constant combining.  In lieu of a
-    // forthcoming pass manager we can just call it here now.  We can re-use the inline functions
-    // list.
-    check!(
-        combine_constants(&mut ir, &functions_to_inline_to),
-        return err(warnings, errors),
-        warnings,
-        errors
-    );
 
     if build_config.print_ir {
         println!("{}", ir);
@@ -467,42 +492,6 @@ pub(crate) fn compile_ast_to_ir_to_asm(
     crate::asm_generation::from_ir::compile_ir_to_asm(&ir, build_config)
 }
 
-fn inline_function_calls(ir: &mut Context, functions: &[Function]) -> CompileResult<()> {
-    for function in functions {
-        if let Err(ir_error) = sway_ir::optimize::inline_all_function_calls(ir, function) {
-            return err(
-                Vec::new(),
-                vec![CompileError::InternalOwned(
-                    ir_error.to_string(),
-                    span::Span {
-                        span: pest::Span::new("".into(), 0, 0).unwrap(),
-                        path: None,
-                    },
-                )],
-            );
-        }
-    }
-    ok((), Vec::new(), Vec::new())
-}
-
-fn combine_constants(ir: &mut Context, functions: &[Function]) -> CompileResult<()> {
-    for function in functions {
-        if let Err(ir_error) = sway_ir::optimize::combine_constants(ir, function) {
-            return err(
-                Vec::new(),
-                vec![CompileError::InternalOwned(
-                    ir_error.to_string(),
-                    span::Span {
-                        span: pest::Span::new("".into(), 0, 0).unwrap(),
-                        path: None,
-                    },
-                )],
-            );
-        }
-    }
-    ok((), Vec::new(), Vec::new())
-}
-
 /// Given input Sway source code, compile to a [BytecodeCompilationResult] which contains the asm in
 /// bytecode form.
 pub fn compile_to_bytecode(
@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use std::iter::FromIterator;
+use std::rc::Rc;
 
 use crate::{
     parse_tree::{AsmOp, AsmRegister, LazyOp, Literal, Visibility},
@@ -18,13 +19,22 @@ use sway_ir::*;
 
 pub(crate) fn compile_ast(ast: TypedParseTree) -> Result<Context, String> {
     let mut ctx = Context::default();
+    let function_pointers = FunctionPointers::new(FunctionTable::from_parse_tree(&ast));
+    let mut callees = Callees::new(CallGraph::from_parse_tree(&ast, &function_pointers.table));
     match ast {
         TypedParseTree::Script {
             namespace,
             main_function,
             declarations,
             all_nodes: _,
-        } => compile_script(&mut ctx, main_function, namespace, declarations),
+        } => compile_script(
+            &mut ctx,
+            main_function,
+            namespace,
+            declarations,
+            &function_pointers,
+            &mut callees,
+        ),
         TypedParseTree::Predicate {
             namespace: _,
             main_function: _,
@@ -36,7 +46,14 @@ pub(crate) fn compile_ast(ast: TypedParseTree) -> Result<Context, String> {
             namespace,
             declarations,
             all_nodes: _,
-        } => compile_contract(&mut ctx, abi_entries, namespace, declarations),
+        } => compile_contract(
+            &mut ctx,
+            abi_entries,
+            namespace,
+            declarations,
+            &function_pointers,
+            &mut callees,
+        ),
         TypedParseTree::Library {
             namespace: _,
             all_nodes: _,
@@ -53,6 +70,8 @@ fn compile_script(
     main_function: TypedFunctionDeclaration,
     namespace: NamespaceRef,
     declarations: Vec<TypedDeclaration>,
+    function_pointers: &FunctionPointers,
+    callees: &mut Callees,
 ) -> Result<Module, String> {
     let module = Module::new(context, Kind::Script);
 
@@ -60,7 +79,15 @@ fn compile_script(
 
     compile_constants(context, module, namespace, false)?;
     compile_declarations(context, module, &mut struct_names, declarations)?;
-    compile_function(context, module, &mut struct_names, main_function)?;
+    compile_function(
+        context,
+        module,
+        &mut struct_names,
+        function_pointers,
+        callees,
+        main_function,
+        None,
+    )?;
 
     Ok(module)
 }
@@ -70,6 +97,8 @@ fn compile_contract(
     abi_entries: Vec<TypedFunctionDeclaration>,
     namespace: NamespaceRef,
     declarations: Vec<TypedDeclaration>,
+    function_pointers: &FunctionPointers,
+    callees: &mut Callees,
 ) -> Result<Module, String> {
     let module = Module::new(context, Kind::Contract);
 
@@ -78,7 +107,14 @@ fn compile_contract(
     compile_constants(context, module, namespace, false)?;
     compile_declarations(context, module, &mut struct_names, declarations)?;
     for decl in abi_entries {
-        compile_abi_method(context, module, &mut struct_names, decl)?;
+        compile_abi_method(
+            context,
+            module,
+            &mut struct_names,
+            function_pointers,
+            callees,
+            decl,
+        )?;
     }
 
     Ok(module)
@@ -201,7 +237,9 @@ fn compile_declarations(
             | TypedDeclaration::EnumDeclaration(_)
             | TypedDeclaration::VariableDeclaration(_)
             | TypedDeclaration::Reassignment(_)
+            | TypedDeclaration::StorageReassignment(_)
             | TypedDeclaration::AbiDeclaration(_)
+            | TypedDeclaration::StorageDeclaration(_)
             | TypedDeclaration::GenericTypeForFunctionScope { .. }
             | TypedDeclaration::ErrorRecovery => (),
         }
@@ -355,7 +393,10 @@ fn compile_function(
     context: &mut Context,
     module: Module,
     struct_names: &mut StructSymbolMap,
+    function_pointers: &FunctionPointers,
+    callees: &mut Callees,
     ast_fn_decl: TypedFunctionDeclaration,
+    callee_key: Option<FunctionKey>,
 ) -> Result<Option<Function>, String> {
     // Currently monomorphisation of generics is inlined into main() and the functions with generic
     // args are still present in the AST declarations, but they can be ignored.
@@ -371,19 +412,34 @@ fn compile_function(
             })
             .collect::<Result<Vec<(String, Type, Span)>, String>>()?;
 
-        compile_fn_with_args(context, module, struct_names, ast_fn_decl, args, None).map(&Some)
+        compile_fn_with_args(
+            context,
+            module,
+            struct_names,
+            function_pointers,
+            callees,
+            ast_fn_decl,
+            args,
+            None,
+            callee_key,
+        )
+        .map(&Some)
     }
 }
 
 // -------------------------------------------------------------------------------------------------
 
+#[allow(clippy::too_many_arguments)]
 fn compile_fn_with_args(
     context: &mut Context,
     module: Module,
     struct_names: &mut StructSymbolMap,
+    function_pointers: &FunctionPointers,
+    callees: &mut Callees,
     ast_fn_decl: TypedFunctionDeclaration,
     args: Vec<(String, Type, Span)>,
     selector: Option<[u8; 4]>,
+    callee_key: Option<FunctionKey>,
 ) -> Result<Function, String> {
     let TypedFunctionDeclaration {
         name,
@@ -408,16 +464,28 @@ fn compile_fn_with_args(
         selector,
         visibility == Visibility::Public,
     );
+    // The function is registered before its body is compiled so that recursive calls find it.
+    if let Some(callee_key) = callee_key {
+        callees.functions.insert(callee_key, func);
+    }
 
     // We clone the struct symbols here, as they contain the globals; any new local declarations
-    // may remain within the function scope.
-    let mut compiler = FnCompiler::new(context, module, func, struct_names.clone());
+    // may remain within the function scope.  The callees are handed back once the body is done.
+    let mut compiler = FnCompiler::new(
+        context,
+        module,
+        func,
+        struct_names.clone(),
+        function_pointers.clone(),
+        std::mem::take(callees),
+    );
 
-    let ret_val = compiler.compile_code_block(context, body)?;
+    let ret_val = compiler.compile_code_block(context, body);
+    *callees = std::mem::take(&mut compiler.callees);
     compiler
         .current_block
         .ins(context)
-        .ret(ret_val, ret_type, None);
+        .ret(ret_val?, ret_type, None);
     Ok(func)
 }
 
@@ -458,6 +526,8 @@ fn compile_abi_method(
     context: &mut Context,
     module: Module,
     struct_names: &mut StructSymbolMap,
+    function_pointers: &FunctionPointers,
+    callees: &mut Callees,
     ast_fn_decl: TypedFunctionDeclaration,
 ) -> Result<Function, String> {
     let selector = ast_fn_decl.to_fn_selector_value().value.ok_or(format!(
@@ -478,20 +548,60 @@ fn compile_abi_method(
         context,
         module,
         struct_names,
+        function_pointers,
+        callees,
         ast_fn_decl,
         args,
         Some(selector),
+        None,
     )
 }
 
 // -------------------------------------------------------------------------------------------------
 
+/// The functions which may be called through function pointers.
+#[derive(Clone, Default)]
+struct FunctionPointers {
+    table: Rc<FunctionTable>,
+}
+
+impl FunctionPointers {
+    fn new(table: FunctionTable) -> Self {
+        FunctionPointers {
+            table: Rc::new(table),
+        }
+    }
+}
+
+/// The function compiled for each function called directly, so that a function called many times
+/// or recursively is compiled only once.  Whether the calls are inlined is left to the optimizer.
+#[derive(Default)]
+struct Callees {
+    call_graph: Rc<CallGraph>,
+    functions: HashMap<FunctionKey, Function>,
+}
+
+impl Callees {
+    fn new(call_graph: CallGraph) -> Self {
+        Callees {
+            call_graph: Rc::new(call_graph),
+            functions: HashMap::new(),
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
 struct FnCompiler {
     module: Module,
     function: Function,
     current_block: Block,
     symbol_map: HashMap<String, String>,
     struct_names: StructSymbolMap,
+    function_pointers: FunctionPointers,
+    callees: Callees,
+    // The blocks to branch to for `continue` and `break` in each enclosing loop, innermost last.
+    loop_blocks: Vec<(Block, Block)>,
 }
 
 impl FnCompiler {
@@ -500,6 +610,8 @@ impl FnCompiler {
         module: Module,
         function: Function,
         struct_names: StructSymbolMap,
+        function_pointers: FunctionPointers,
+        callees: Callees,
     ) -> Self {
         let symbol_map = HashMap::from_iter(
             function
@@ -512,6 +624,9 @@ impl FnCompiler {
             current_block: function.get_entry_block(context),
             symbol_map,
             struct_names,
+            function_pointers,
+            callees,
+            loop_blocks: Vec::new(),
         }
     }
 
@@ -522,6 +637,15 @@ impl FnCompiler {
         context: &mut Context,
         ast_block: TypedCodeBlock,
     ) -> Result<Value, String> {
+        // Like its type, the value of a block is that of its implicit return, if any, and unit
+        // otherwise, whatever the value of its last statement.
+        let has_implicit_return = matches!(
+            ast_block.contents.last(),
+            Some(TypedAstNode {
+                content: TypedAstNodeContent::ImplicitReturnExpression(_),
+                ..
+            })
+        );
         ast_block
             .contents
             .into_iter()
@@ -549,6 +673,9 @@ impl FnCompiler {
                         TypedDeclaration::Reassignment(tr) => {
                             self.compile_reassignment(context, tr, span_md_idx)
                         }
+                        TypedDeclaration::StorageReassignment(tsr) => {
+                            self.compile_storage_reassignment(context, tsr, span_md_idx)
+                        }
                         TypedDeclaration::ImplTrait { span, .. } => {
                             // XXX What if I ignore the trait implementation???  Potentially since
                             // we currently inline everything and below we 'recreate' the functions
@@ -559,6 +686,7 @@ impl FnCompiler {
                             Ok(Constant::get_unit(context, span_md_idx))
                         }
                         TypedDeclaration::AbiDeclaration(_) => Err("abi decl".into()),
+                        TypedDeclaration::StorageDeclaration(_) => Err("storage decl".into()),
                         TypedDeclaration::GenericTypeForFunctionScope { .. } => {
                             Err("gen ty for fn scope".into())
                         }
@@ -574,11 +702,19 @@ impl FnCompiler {
                     TypedAstNodeContent::WhileLoop(twl) => {
                         self.compile_while_loop(context, twl, span_md_idx)
                     }
-                    TypedAstNodeContent::SideEffect => Err("code block side effect".into()),
+                    TypedAstNodeContent::Break => {
+                        self.compile_loop_control_flow(context, true, span_md_idx)
+                    }
+                    TypedAstNodeContent::Continue => {
+                        self.compile_loop_control_flow(context, false, span_md_idx)
+                    }
+                    TypedAstNodeContent::SideEffect
+                    | TypedAstNodeContent::IncludeStatement(_)
+                    | TypedAstNodeContent::UseStatement(_) => Err("code block side effect".into()),
                 }
             })
             .collect::<Result<Vec<_>, String>>()
-            .map(|vals| vals.last().cloned())
+            .map(|vals| vals.last().cloned().filter(|_| has_implicit_return))
             .transpose()
             .unwrap_or_else(|| Ok(Constant::get_unit(context, None)))
     }
@@ -594,20 +730,31 @@ impl FnCompiler {
         match ast_expr.expression {
             TypedExpressionVariant::Literal(l) => Ok(convert_literal_to_value(context, &l, span_md_idx)),
             TypedExpressionVariant::FunctionApplication {
-                name,
+                decl_name,
                 arguments,
                 function_body,
                 ..
             } => self.compile_fn_call(
                 context,
-                name.suffix.as_str(),
+                &decl_name,
                 arguments,
                 Some(function_body),
+                ast_expr.return_type,
                 span_md_idx,
             ),
+            TypedExpressionVariant::FunctionReference { function, .. } => {
+                self.compile_fn_reference(context, &function, span_md_idx)
+            }
+            TypedExpressionVariant::IndirectFunctionApplication {
+                function,
+                arguments,
+            } => self.compile_indirect_fn_call(context, *function, arguments, span_md_idx),
             TypedExpressionVariant::LazyOperator { op, lhs, rhs } => {
                 self.compile_lazy_op(context, op, *lhs, *rhs, span_md_idx)
             }
+            TypedExpressionVariant::WideIntegerOperator { op, lhs, rhs } => {
+                self.compile_wide_integer_op(context, op, *lhs, *rhs, span_md_idx)
+            }
             TypedExpressionVariant::VariableExpression { name } => {
                 self.compile_var_expr(context, name.as_str(), span_md_idx)
             }
@@ -684,6 +831,9 @@ impl FnCompiler {
                     SizeOfVariant::Val(exp) => self.compile_expression(context, *exp)
                 }
             },
+            TypedExpressionVariant::StorageAccess(access) => {
+                self.compile_storage_load(context, access, &ast_expr.span, span_md_idx)
+            }
         }
     }
 
@@ -755,12 +905,64 @@ impl FnCompiler {
 
     // ---------------------------------------------------------------------------------------------
 
+    fn compile_wide_integer_op(
+        &mut self,
+        context: &mut Context,
+        ast_op: WideIntegerOp,
+        ast_lhs: TypedExpression,
+        ast_rhs: TypedExpression,
+        span_md_idx: Option<MetadataIndex>,
+    ) -> Result<Value, String> {
+        let lhs_val = self.compile_expression(context, ast_lhs)?;
+        let rhs_val = self.compile_expression(context, ast_rhs)?;
+        let (binary_op, predicate, negate) = match ast_op {
+            WideIntegerOp::Add => (Some(BinaryOpKind::Add), None, false),
+            WideIntegerOp::Sub => (Some(BinaryOpKind::Sub), None, false),
+            WideIntegerOp::Mul => (Some(BinaryOpKind::Mul), None, false),
+            WideIntegerOp::Div => (Some(BinaryOpKind::Div), None, false),
+            WideIntegerOp::Mod => (Some(BinaryOpKind::Mod), None, false),
+            WideIntegerOp::Eq => (None, Some(Predicate::Equal), false),
+            WideIntegerOp::Neq => (None, Some(Predicate::Equal), true),
+            WideIntegerOp::Lt => (None, Some(Predicate::LessThan), false),
+            WideIntegerOp::Gt => (None, Some(Predicate::GreaterThan), false),
+            WideIntegerOp::Le => (None, Some(Predicate::GreaterThan), true),
+            WideIntegerOp::Ge => (None, Some(Predicate::LessThan), true),
+        };
+        if let Some(op) = binary_op {
+            return Ok(self.current_block.ins(context).binary_op(
+                op,
+                lhs_val,
+                rhs_val,
+                span_md_idx,
+            ));
+        }
+        let cmp_val =
+            self.current_block
+                .ins(context)
+                .cmp(predicate.unwrap(), lhs_val, rhs_val, span_md_idx);
+        if negate {
+            // There's no `not` instruction, so `!x` is `x == false`.
+            let false_val = Constant::get_bool(context, false, span_md_idx);
+            Ok(self.current_block.ins(context).cmp(
+                Predicate::Equal,
+                cmp_val,
+                false_val,
+                span_md_idx,
+            ))
+        } else {
+            Ok(cmp_val)
+        }
+    }
+
+    // ---------------------------------------------------------------------------------------------
+
     fn compile_fn_call(
         &mut self,
         context: &mut Context,
-        _ast_name: &str,
+        decl_name: &Ident,
         ast_args: Vec<(Ident, TypedExpression)>,
         callee_body: Option<TypedCodeBlock>,
+        return_type: TypeId,
         span_md_idx: Option<MetadataIndex>,
     ) -> Result<Value, String> {
         // XXX OK, now, the old compiler inlines everything very lazily.  Function calls include
@@ -769,77 +971,208 @@ impl FnCompiler {
         // from Forc and when the parser builds the AST (or is it during type checking?) these
         // function bodies are embedded.
         //
-        // We're going to build little single-use instantiations of the callee and then call them.
-        // For now if they're called in multiple places they'll be redundantly recreated, but also
-        // at present we are still inlining everything so it actually makes little difference.
+        // We're going to build an instantiation of the callee the first time it's called, and
+        // call that same instantiation from every other call site, including recursive ones.
+        // Which calls are inlined is then up to the optimizer.
         //
-        // Eventually we need to Do It Properly and inline only when necessary, and compile the
-        // standard library to an actual module.
-
+        // Eventually we need to Do It Properly and compile the standard library to an actual
+        // module.
+
+        // The core library's arithmetic and comparison trait methods are each just a wrapper
+        // around a single opcode, so they're replaced with the equivalent IR instruction.
+        if let Some((intrinsic, lhs_idx, rhs_idx)) = callee_body
+            .as_ref()
+            .and_then(|body| recognize_intrinsic_op(&ast_args, body))
         {
-            // Firstly create the single-use callee by fudging an AST declaration.
-            let callee_name = context.get_unique_name();
-            let callee_name_len = callee_name.len();
-            let callee_ident = Ident::new(crate::span::Span {
-                span: pest::Span::new(std::sync::Arc::from(callee_name), 0, callee_name_len)
-                    .unwrap(),
-                path: None,
+            let args = ast_args
+                .into_iter()
+                .map(|(_, expr)| self.compile_expression(context, expr))
+                .collect::<Result<Vec<Value>, String>>()?;
+            let ins = self.current_block.ins(context);
+            return Ok(match intrinsic {
+                IntrinsicOp::Binary(op) => {
+                    ins.binary_op(op, args[lhs_idx], args[rhs_idx], span_md_idx)
+                }
+                IntrinsicOp::Cmp(pred) => ins.cmp(pred, args[lhs_idx], args[rhs_idx], span_md_idx),
             });
+        }
 
-            let parameters = ast_args
-                .iter()
-                .map(|(name, expr)| TypedFunctionParameter {
-                    name: name.clone(),
-                    r#type: expr.return_type,
-                    type_span: crate::span::Span {
-                        span: pest::Span::new(" ".into(), 0, 0).unwrap(),
-                        path: None,
-                    },
-                })
-                .collect();
+        let callee_key = FunctionKey::of_call(decl_name, &ast_args, return_type);
+        let callee = match self.callees.functions.get(&callee_key) {
+            Some(callee) => *callee,
+            None => {
+                // Firstly create the callee by fudging an AST declaration.
+                let callee_name = context.get_unique_name();
+                let callee_name_len = callee_name.len();
+                let callee_ident = Ident::new(crate::span::Span {
+                    span: pest::Span::new(std::sync::Arc::from(callee_name), 0, callee_name_len)
+                        .unwrap(),
+                    path: None,
+                });
+
+                let parameters = ast_args
+                    .iter()
+                    .map(|(name, expr)| TypedFunctionParameter {
+                        name: name.clone(),
+                        r#type: expr.return_type,
+                        type_span: crate::span::Span {
+                            span: pest::Span::new(" ".into(), 0, 0).unwrap(),
+                            path: None,
+                        },
+                    })
+                    .collect();
 
-            let callee_body = callee_body.unwrap();
+                // A recursive call doesn't have the body of the callee, which is found in the call
+                // graph instead.
+                let callee_body = match (callee_body, self.callees.call_graph.body(&callee_key)) {
+                    (Some(body), Some(graph_body)) if body.contents.is_empty() => {
+                        graph_body.clone()
+                    }
+                    (body, _) => body.unwrap(),
+                };
 
-            // We're going to have to reverse engineer the return type.
-            let return_type = Self::get_codeblock_return_type(&callee_body).unwrap_or_else(||
+                // We're going to have to reverse engineer the return type.
+                let return_type =
+                    Self::get_codeblock_return_type(&callee_body).unwrap_or_else(||
                     // This code block is missing a return or implicit return.  The only time I've
                     // seen it happen (whether it's 'valid' or not) is in std::storage::store(),
                     // which has a single asm block which also returns nothing.  In this case, it
                     // actually is Unit.
                     insert_type(TypeInfo::Tuple(Vec::new())));
 
-            let callee_fn_decl = TypedFunctionDeclaration {
-                name: callee_ident,
-                body: callee_body,
-                parameters,
-                span: crate::span::Span {
-                    span: pest::Span::new(" ".into(), 0, 0).unwrap(),
-                    path: None,
-                },
-                return_type,
-                type_parameters: Vec::new(),
-                return_type_span: crate::span::Span {
-                    span: pest::Span::new(" ".into(), 0, 0).unwrap(),
-                    path: None,
-                },
-                visibility: Visibility::Private,
-                is_contract_call: false,
-                purity: Default::default(),
-            };
+                let callee_fn_decl = TypedFunctionDeclaration {
+                    name: callee_ident,
+                    body: callee_body,
+                    parameters,
+                    span: crate::span::Span {
+                        span: pest::Span::new(" ".into(), 0, 0).unwrap(),
+                        path: None,
+                    },
+                    return_type,
+                    type_parameters: Vec::new(),
+                    return_type_span: crate::span::Span {
+                        span: pest::Span::new(" ".into(), 0, 0).unwrap(),
+                        path: None,
+                    },
+                    visibility: Visibility::Private,
+                    is_contract_call: false,
+                    is_test: false,
+                    purity: Default::default(),
+                };
 
-            let callee =
-                compile_function(context, self.module, &mut self.struct_names, callee_fn_decl)?;
+                compile_function(
+                    context,
+                    self.module,
+                    &mut self.struct_names,
+                    &self.function_pointers,
+                    &mut self.callees,
+                    callee_fn_decl,
+                    Some(callee_key),
+                )?
+                .unwrap()
+            }
+        };
 
-            // Now actually call the new function.
-            let args = ast_args
-                .into_iter()
-                .map(|(_, expr)| self.compile_expression(context, expr))
-                .collect::<Result<Vec<Value>, String>>()?;
-            Ok(self
-                .current_block
-                .ins(context)
-                .call(callee.unwrap(), &args, span_md_idx))
+        // Now actually call the function.
+        let args = ast_args
+            .into_iter()
+            .map(|(_, expr)| self.compile_expression(context, expr))
+            .collect::<Result<Vec<Value>, String>>()?;
+        Ok(self
+            .current_block
+            .ins(context)
+            .call(callee, &args, span_md_idx))
+    }
+
+    fn compile_fn_reference(
+        &mut self,
+        context: &mut Context,
+        function: &TypedFunctionDeclaration,
+        span_md_idx: Option<MetadataIndex>,
+    ) -> Result<Value, String> {
+        // A function value is its index in the function table.
+        let idx = self
+            .function_pointers
+            .table
+            .index_of(function)
+            .ok_or("Function reference is missing from the function table.")?;
+        Ok(Constant::get_uint(context, 64, idx, span_md_idx))
+    }
+
+    fn compile_indirect_fn_call(
+        &mut self,
+        context: &mut Context,
+        ast_function: TypedExpression,
+        ast_args: Vec<TypedExpression>,
+        span_md_idx: Option<MetadataIndex>,
+    ) -> Result<Value, String> {
+        let function_type = ast_function.return_type;
+        let target = self.compile_expression(context, ast_function)?;
+        let args = ast_args
+            .into_iter()
+            .map(|expr| self.compile_expression(context, expr))
+            .collect::<Result<Vec<Value>, String>>()?;
+
+        // As with direct calls every function this value may refer to is compiled once, and
+        // called from every call site, including recursive ones.  The dispatch between them is
+        // left to `call_indirect`.
+        let candidates = self
+            .function_pointers
+            .table
+            .candidates(function_type)
+            .map(|(idx, decl)| (idx, decl.clone()))
+            .collect::<Vec<_>>();
+        let mut callees = Vec::new();
+        for (idx, decl) in candidates {
+            let callee_key = FunctionKey::of_decl(&decl);
+            let callee = match self.callees.functions.get(&callee_key) {
+                Some(callee) => *callee,
+                None => {
+                    let callee_name =
+                        format!("{}_{}", decl.name.as_str(), context.get_unique_name());
+                    let callee_name_len = callee_name.len();
+                    let callee_ident = Ident::new(crate::span::Span {
+                        span: pest::Span::new(
+                            std::sync::Arc::from(callee_name),
+                            0,
+                            callee_name_len,
+                        )
+                        .unwrap(),
+                        path: None,
+                    });
+                    // A function referred to from within its own body has an empty body there,
+                    // which is found in the call graph instead.
+                    let body = match self.callees.call_graph.body(&callee_key) {
+                        Some(body) if decl.body.contents.is_empty() => body.clone(),
+                        _ => decl.body,
+                    };
+                    let callee_fn_decl = TypedFunctionDeclaration {
+                        name: callee_ident,
+                        body,
+                        ..decl
+                    };
+                    compile_function(
+                        context,
+                        self.module,
+                        &mut self.struct_names,
+                        &self.function_pointers,
+                        &mut self.callees,
+                        callee_fn_decl,
+                        Some(callee_key),
+                    )?
+                    .ok_or("Generic functions cannot be called through function pointers.")?
+                }
+            };
+            callees.push((idx, callee));
+        }
+        if callees.is_empty() {
+            return Err("No function matches the type of this function pointer.".into());
         }
+
+        Ok(self
+            .current_block
+            .ins(context)
+            .call_indirect(target, callees, &args, span_md_idx))
     }
 
     fn get_codeblock_return_type(codeblock: &TypedCodeBlock) -> Option<TypeId> {
@@ -936,20 +1269,26 @@ impl FnCompiler {
             .ins(context)
             .branch(cond_block, None, None);
 
-        // Fill in the body block now, jump unconditionally to the cond block at its end.
+        // Fill in the body block now, jump unconditionally to the cond block at its end.  The
+        // final block is needed by any `break` in the body, but is moved after the body blocks
+        // once we're finished with them.
         let body_block = self
             .function
             .create_block(context, Some("while_body".into()));
+        let final_block = self
+            .function
+            .create_block(context, Some("end_while".into()));
         self.current_block = body_block;
-        self.compile_code_block(context, ast_while_loop.body)?;
+        self.loop_blocks.push((cond_block, final_block));
+        let body_result = self.compile_code_block(context, ast_while_loop.body);
+        self.loop_blocks.pop();
+        body_result?;
         self.current_block
             .ins(context)
             .branch(cond_block, None, None);
-
-        // Create the final block after we're finished with the body.
-        let final_block = self
-            .function
-            .create_block(context, Some("end_while".into()));
+        self.function
+            .move_block_to_end(context, &final_block)
+            .map_err(|ir_error| ir_error.to_string())?;
 
         // Add the conditional which jumps into the body or out to the final block.
         self.current_block = cond_block;
@@ -968,6 +1307,27 @@ impl FnCompiler {
 
     // ---------------------------------------------------------------------------------------------
 
+    fn compile_loop_control_flow(
+        &mut self,
+        context: &mut Context,
+        is_break: bool,
+        span_md_idx: Option<MetadataIndex>,
+    ) -> Result<Value, String> {
+        let (cond_block, final_block) = *self
+            .loop_blocks
+            .last()
+            .ok_or("Loop control flow found outside of a loop.")?;
+        let to_block = if is_break { final_block } else { cond_block };
+        self.current_block
+            .ins(context)
+            .branch(to_block, None, span_md_idx);
+        // BR is a terminator so we must create a new block here, as for RET.
+        self.current_block = self.function.create_block(context, None);
+        Ok(Constant::get_unit(context, span_md_idx))
+    }
+
+    // ---------------------------------------------------------------------------------------------
+
     fn compile_var_expr(
         &mut self,
         context: &mut Context,
@@ -1167,6 +1527,65 @@ impl FnCompiler {
 
     // ---------------------------------------------------------------------------------------------
 
+    fn compile_storage_load(
+        &mut self,
+        context: &mut Context,
+        access: TypedStorageAccess,
+        span: &Span,
+        span_md_idx: Option<MetadataIndex>,
+    ) -> Result<Value, String> {
+        // Storage is read into a temporary local, which we then load from.
+        let ty = convert_resolved_typeid(context, &mut self.struct_names, &access.r#type, span)?;
+        let ptr = self.function.new_unique_local_ptr(
+            context,
+            format!("storage_{}", access.field_name.as_str()),
+            ty,
+            true,
+            None,
+        );
+        let key_val = Constant::get_b256(context, access.storage_key(), span_md_idx);
+        let ptr_val = self.current_block.ins(context).get_ptr(ptr, span_md_idx);
+        self.current_block
+            .ins(context)
+            .state_load(ptr_val, key_val, span_md_idx);
+        Ok(self.current_block.ins(context).load(ptr_val, span_md_idx))
+    }
+
+    // ---------------------------------------------------------------------------------------------
+
+    fn compile_storage_reassignment(
+        &mut self,
+        context: &mut Context,
+        ast_reassignment: TypedStorageReassignment,
+        span_md_idx: Option<MetadataIndex>,
+    ) -> Result<Value, String> {
+        let TypedStorageReassignment { access, rhs, span } = ast_reassignment;
+        let ty = convert_resolved_typeid(context, &mut self.struct_names, &access.r#type, &span)?;
+        let reassign_val = self.compile_expression(context, rhs)?;
+
+        // The value to be stored must be in memory, so it goes via a temporary local.
+        let ptr = self.function.new_unique_local_ptr(
+            context,
+            format!("storage_{}", access.field_name.as_str()),
+            ty,
+            true,
+            None,
+        );
+        let ptr_val = self.current_block.ins(context).get_ptr(ptr, span_md_idx);
+        self.current_block
+            .ins(context)
+            .store(ptr_val, reassign_val, span_md_idx);
+        let key_val = Constant::get_b256(context, access.storage_key(), span_md_idx);
+        self.current_block
+            .ins(context)
+            .state_store(ptr_val, key_val, span_md_idx);
+
+        // Like regular reassignments, this shouldn't really return a value.
+        Ok(reassign_val)
+    }
+
+    // ---------------------------------------------------------------------------------------------
+
     fn compile_array_expr(
         &mut self,
         context: &mut Context,
@@ -1535,6 +1954,119 @@ impl FnCompiler {
 
 // -------------------------------------------------------------------------------------------------
 
+/// An opcode which may be represented by an IR instruction rather than an ASM block.
+enum IntrinsicOp {
+    Binary(BinaryOpKind),
+    Cmp(Predicate),
+}
+
+/// Determine whether a call to a function with `callee_body` is equivalent to a single
+/// [`IntrinsicOp`], which is the case for the core library trait impls for the integer types.
+///
+/// The callee must take two arguments and its body must be a lone ASM block which applies one
+/// opcode to both of them and returns the result.  The indices of the arguments for the left and
+/// right hand operands are returned with the op.
+fn recognize_intrinsic_op(
+    ast_args: &[(Ident, TypedExpression)],
+    callee_body: &TypedCodeBlock,
+) -> Option<(IntrinsicOp, usize, usize)> {
+    if ast_args.len() != 2 || callee_body.contents.len() != 1 {
+        return None;
+    }
+    let (registers, body, returns, return_type) = match &callee_body.contents[0].content {
+        TypedAstNodeContent::ImplicitReturnExpression(TypedExpression {
+            expression:
+                TypedExpressionVariant::AsmExpression {
+                    registers,
+                    body,
+                    returns: Some((returns, _)),
+                    ..
+                },
+            return_type,
+            ..
+        }) => (registers, body, returns, *return_type),
+        _otherwise => return None,
+    };
+    if registers.len() != 3 || body.len() != 1 || body[0].immediate.is_some() {
+        return None;
+    }
+
+    let intrinsic = match body[0].op_name.as_str() {
+        "add" => IntrinsicOp::Binary(BinaryOpKind::Add),
+        "sub" => IntrinsicOp::Binary(BinaryOpKind::Sub),
+        "mul" => IntrinsicOp::Binary(BinaryOpKind::Mul),
+        "div" => IntrinsicOp::Binary(BinaryOpKind::Div),
+        "mod" => IntrinsicOp::Binary(BinaryOpKind::Mod),
+        "and" => IntrinsicOp::Binary(BinaryOpKind::And),
+        "or" => IntrinsicOp::Binary(BinaryOpKind::Or),
+        "xor" => IntrinsicOp::Binary(BinaryOpKind::Xor),
+        "sll" => IntrinsicOp::Binary(BinaryOpKind::Shl),
+        "srl" => IntrinsicOp::Binary(BinaryOpKind::Shr),
+        "eq" => IntrinsicOp::Cmp(Predicate::Equal),
+        "lt" => IntrinsicOp::Cmp(Predicate::LessThan),
+        "gt" => IntrinsicOp::Cmp(Predicate::GreaterThan),
+        _otherwise => return None,
+    };
+
+    // Both operands and the result must be in registers of the expected types.
+    let is_uint = |type_id| {
+        matches!(
+            look_up_type_id(type_id),
+            TypeInfo::UnsignedInteger(bits) if !bits.is_wide()
+        )
+    };
+    let types_ok = match &intrinsic {
+        IntrinsicOp::Binary(_) => {
+            is_uint(return_type) && ast_args.iter().all(|(_, expr)| is_uint(expr.return_type))
+        }
+        IntrinsicOp::Cmp(pred) => {
+            matches!(look_up_type_id(return_type), TypeInfo::Boolean)
+                && ast_args.iter().all(|(_, expr)| {
+                    is_uint(expr.return_type)
+                        || (*pred == Predicate::Equal
+                            && matches!(look_up_type_id(expr.return_type), TypeInfo::Boolean))
+                })
+        }
+    };
+    if !types_ok {
+        return None;
+    }
+
+    // Each argument must initialise exactly one register and the result register must be
+    // uninitialised.
+    let arg_idx_for_reg = |reg_name: &str| {
+        registers
+            .iter()
+            .find(|reg| reg.name.as_str() == reg_name)
+            .map(|reg| match &reg.initializer {
+                Some(TypedExpression {
+                    expression: TypedExpressionVariant::VariableExpression { name },
+                    ..
+                }) => ast_args
+                    .iter()
+                    .position(|(arg_name, _)| arg_name.as_str() == name.as_str()),
+                _otherwise => None,
+            })
+    };
+    match body[0].op_args.as_slice() {
+        [dst, lhs, rhs] if dst.as_str() == returns.name => {
+            match (
+                arg_idx_for_reg(dst.as_str()),
+                arg_idx_for_reg(lhs.as_str()),
+                arg_idx_for_reg(rhs.as_str()),
+            ) {
+                (Some(None), Some(Some(lhs_idx)), Some(Some(rhs_idx))) if lhs_idx != rhs_idx => {
+                    Some((intrinsic, lhs_idx, rhs_idx))
+                }
+                _otherwise => None,
+            }
+        }
+        _otherwise => None,
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
 fn convert_literal_to_value(
     context: &mut Context,
     ast_literal: &Literal,
@@ -1545,6 +2077,8 @@ fn convert_literal_to_value(
         Literal::U16(n) => Constant::get_uint(context, 16, *n as u64, span_id_idx),
         Literal::U32(n) => Constant::get_uint(context, 32, *n as u64, span_id_idx),
         Literal::U64(n) => Constant::get_uint(context, 64, *n, span_id_idx),
+        Literal::U128(n) => Constant::get_wide_uint(context, 128, wide_uint_bytes(*n), span_id_idx),
+        Literal::U256(bs) => Constant::get_wide_uint(context, 256, *bs, span_id_idx),
         Literal::Numeric(n) => Constant::get_uint(context, 64, *n, span_id_idx),
         Literal::String(s) => Constant::get_string(context, s.as_str().to_owned(), span_id_idx),
         Literal::Boolean(b) => Constant::get_bool(context, *b, span_id_idx),
@@ -1558,6 +2092,8 @@ fn convert_literal_to_constant(ast_literal: &Literal) -> Constant {
         Literal::U16(n) => Constant::new_uint(16, *n as u64),
         Literal::U32(n) => Constant::new_uint(32, *n as u64),
         Literal::U64(n) => Constant::new_uint(64, *n),
+        Literal::U128(n) => Constant::new_wide_uint(128, wide_uint_bytes(*n)),
+        Literal::U256(bs) => Constant::new_wide_uint(256, *bs),
         Literal::Numeric(n) => Constant::new_uint(64, *n),
         Literal::String(s) => Constant::new_string(s.as_str().to_owned()),
         Literal::Boolean(b) => Constant::new_bool(*b),
@@ -1565,6 +2101,12 @@ fn convert_literal_to_constant(ast_literal: &Literal) -> Constant {
     }
 }
 
+fn wide_uint_bytes(n: u128) -> [u8; 32] {
+    let mut bytes = [0; 32];
+    bytes[16..].copy_from_slice(&n.to_be_bytes());
+    bytes
+}
+
 fn convert_resolved_typeid(
     context: &mut Context,
     struct_names: &mut StructSymbolMap,
@@ -1599,16 +2141,7 @@ fn convert_resolved_type(
     ast_type: &TypeInfo,
 ) -> Result<Type, String> {
     Ok(match ast_type {
-        TypeInfo::UnsignedInteger(nbits) => {
-            // We need impl IntegerBits { fn num_bits() -> u64 { ... } }
-            let nbits = match nbits {
-                IntegerBits::Eight => 8,
-                IntegerBits::Sixteen => 16,
-                IntegerBits::ThirtyTwo => 32,
-                IntegerBits::SixtyFour => 64,
-            };
-            Type::Uint(nbits)
-        }
+        TypeInfo::UnsignedInteger(nbits) => Type::Uint(nbits.num_bits()),
         TypeInfo::Numeric => Type::Uint(64),
         TypeInfo::Boolean => Type::Bool,
         TypeInfo::Byte => Type::Uint(8), // XXX?
@@ -1654,6 +2187,8 @@ fn convert_resolved_type(
                 create_tuple_aggregate(context, struct_names, fields.clone()).map(Type::Struct)?
             }
         }
+        // function values are indices into the function table
+        TypeInfo::Function { .. } => Type::Uint(64),
         TypeInfo::Custom { .. } => return Err("can't do custom types yet".into()),
         TypeInfo::SelfType { .. } => return Err("can't do self types yet".into()),
         TypeInfo::Contract => Type::Contract,
@@ -1806,7 +2341,10 @@ mod tests {
             print_intermediate_asm: false,
             print_finalized_asm: false,
             print_ir: false,
+            ir_pipeline: "O1".to_owned(),
+            time_ir_passes: false,
             generated_names: std::sync::Arc::new(std::sync::Mutex::new(vec![])),
+            source_overrides: Default::default(),
         };
 
         let mut warnings = vec![];
@@ -1819,6 +2357,7 @@ mod tests {
             graph: Graph::new(),
             entry_points: vec![],
             namespace: Default::default(),
+            loops: vec![],
         };
         TypedParseTree::type_check(
             parse_tree.tree,
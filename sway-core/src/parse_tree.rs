@@ -3,6 +3,7 @@ mod call_path;
 mod code_block;
 pub mod declaration;
 mod expression;
+mod for_loop;
 pub mod ident;
 mod include_statement;
 mod literal;
@@ -15,6 +16,7 @@ pub use call_path::*;
 pub use code_block::*;
 pub use declaration::*;
 pub use expression::*;
+pub use for_loop::{ForLoop, ForLoopIterable};
 pub(crate) use include_statement::IncludeStatement;
 pub use literal::Literal;
 pub use return_statement::*;
@@ -52,7 +52,7 @@ impl AbiDeclaration {
                     interface_surface.push(fn_sig);
                 }
                 Rule::fn_decl => methods.push(check!(
-                    FunctionDeclaration::parse_from_pair(func, config),
+                    FunctionDeclaration::parse_method_from_pair(func, config),
                     continue,
                     warnings,
                     errors
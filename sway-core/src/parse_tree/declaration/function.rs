@@ -25,6 +25,8 @@ pub struct FunctionDeclaration {
     pub(crate) return_type: TypeInfo,
     pub(crate) type_parameters: Vec<TypeParameter>,
     pub(crate) return_type_span: Span,
+    /// Whether this function is annotated with `#[test]`, and should be run by the test runner.
+    pub(crate) is_test: bool,
 }
 
 impl FunctionDeclaration {
@@ -33,7 +35,22 @@ impl FunctionDeclaration {
         let mut parts = pair.clone().into_inner();
         let mut warnings = Vec::new();
         let mut errors = Vec::new();
-        let signature_or_visibility = parts.next().unwrap();
+        let mut is_test = false;
+        let mut signature_or_visibility = parts.next().unwrap();
+        while signature_or_visibility.as_rule() == Rule::attribute {
+            let attribute = signature_or_visibility.into_inner().next().unwrap();
+            match attribute.as_str() {
+                "test" => is_test = true,
+                other => errors.push(CompileError::UnknownAttribute {
+                    attribute: other.to_string(),
+                    span: Span {
+                        span: attribute.as_span(),
+                        path: path.clone(),
+                    },
+                }),
+            }
+            signature_or_visibility = parts.next().unwrap();
+        }
         let (visibility, signature) = if signature_or_visibility.as_rule() == Rule::visibility {
             (
                 Visibility::parse_from_pair(signature_or_visibility),
@@ -165,12 +182,35 @@ impl FunctionDeclaration {
                 },
                 return_type,
                 type_parameters,
+                is_test,
             },
             warnings,
             errors,
         )
     }
 
+    /// Parses a function declared inside of an `impl`, `trait` or `abi` block. These may not be
+    /// annotated as tests.
+    pub(crate) fn parse_method_from_pair(
+        pair: Pair<Rule>,
+        config: Option<&BuildConfig>,
+    ) -> CompileResult<Self> {
+        let mut warnings = Vec::new();
+        let mut errors = Vec::new();
+        let method = check!(
+            Self::parse_from_pair(pair, config),
+            return err(warnings, errors),
+            warnings,
+            errors
+        );
+        if method.is_test {
+            errors.push(CompileError::TestAttributeOnMethod {
+                span: method.span.clone(),
+            });
+        }
+        ok(method, warnings, errors)
+    }
+
     pub fn parse_json_abi(&self) -> Function {
         Function {
             name: self.name.as_str().to_string(),
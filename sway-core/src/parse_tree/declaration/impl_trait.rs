@@ -12,7 +12,8 @@ pub struct ImplTrait {
     pub(crate) trait_name: CallPath,
     pub(crate) type_implementing_for: TypeInfo,
     pub(crate) type_implementing_for_span: Span,
-    pub(crate) type_arguments: Vec<TypeParameter>,
+    /// The type arguments given to a generic trait, e.g. `u64` in `impl From<u64> for Foo`.
+    pub(crate) type_arguments: Vec<(TypeInfo, Span)>,
     pub functions: Vec<FunctionDeclaration>,
     // the span of the whole impl trait and block
     pub(crate) block_span: Span,
@@ -58,7 +59,7 @@ impl ImplTrait {
             errors
         );
         let mut iter = iter.peekable();
-        let type_params_pair = if iter.peek().unwrap().as_rule() == Rule::type_params {
+        let type_args_pair = if iter.peek().unwrap().as_rule() == Rule::trait_type_args {
             iter.next()
         } else {
             None
@@ -84,25 +85,40 @@ impl ImplTrait {
             None => None,
         };
 
-        let type_arguments_span = match type_params_pair {
+        let type_arguments_span = match type_args_pair {
             Some(ref x) => Span {
                 span: x.as_span(),
-                path,
+                path: path.clone(),
             },
             None => trait_name.span(),
         };
-        let type_arguments = TypeParameter::parse_from_type_params_and_where_clause(
-            type_params_pair,
-            where_clause_pair,
-            config,
-        )
-        .unwrap_or_else(&mut warnings, &mut errors, Vec::new);
+        let mut type_arguments = vec![];
+        if let Some(type_args_pair) = type_args_pair {
+            for pair in type_args_pair.into_inner() {
+                let span = Span {
+                    span: pair.as_span(),
+                    path: path.clone(),
+                };
+                type_arguments.push((
+                    check!(
+                        TypeInfo::parse_from_pair(pair, config),
+                        continue,
+                        warnings,
+                        errors
+                    ),
+                    span,
+                ));
+            }
+        }
+        // an impl of a trait has no type parameters of its own, so any where clause is an error
+        TypeParameter::parse_from_type_params_and_where_clause(None, where_clause_pair, config)
+            .unwrap_or_else(&mut warnings, &mut errors, Vec::new);
 
         let mut fn_decls_buf = vec![];
 
         for pair in iter {
             fn_decls_buf.push(check!(
-                FunctionDeclaration::parse_from_pair(pair, config),
+                FunctionDeclaration::parse_method_from_pair(pair, config),
                 continue,
                 warnings,
                 errors
@@ -181,7 +197,7 @@ impl ImplSelf {
 
         for pair in iter {
             fn_decls_buf.push(check!(
-                FunctionDeclaration::parse_from_pair(pair, config),
+                FunctionDeclaration::parse_method_from_pair(pair, config),
                 continue,
                 warnings,
                 errors
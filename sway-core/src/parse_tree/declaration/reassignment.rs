@@ -2,7 +2,7 @@ use crate::{
     build_config::BuildConfig,
     error::{err, ok, CompileError, CompileResult},
     parse_array_index,
-    parse_tree::{ident, Expression},
+    parse_tree::{ident, parse_storage_access, Expression},
     parser::Rule,
 };
 
@@ -66,6 +66,38 @@ impl Reassignment {
                     errors,
                 )
             }
+            Rule::storage_reassignment => {
+                let mut iter = variable_or_struct_reassignment.into_inner();
+                let lhs = check!(
+                    parse_storage_access(iter.next().expect("guaranteed by grammar"), config),
+                    return err(warnings, errors),
+                    warnings,
+                    errors
+                );
+                let rhs = iter.next().expect("guaranteed by grammar");
+                let rhs_span = Span {
+                    span: rhs.as_span(),
+                    path,
+                };
+                let body = check!(
+                    Expression::parse_from_pair(rhs, config),
+                    Expression::Tuple {
+                        fields: vec![],
+                        span: rhs_span
+                    },
+                    warnings,
+                    errors
+                );
+                ok(
+                    Reassignment {
+                        lhs: Box::new(lhs),
+                        rhs: body,
+                        span,
+                    },
+                    warnings,
+                    errors,
+                )
+            }
             Rule::struct_field_reassignment => {
                 let mut iter = variable_or_struct_reassignment.into_inner();
                 let lhs = iter.next().expect("guaranteed by grammar");
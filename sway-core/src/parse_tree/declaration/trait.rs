@@ -96,7 +96,7 @@ impl TraitDeclaration {
                     }
                     Rule::fn_decl => {
                         methods.push(check!(
-                            FunctionDeclaration::parse_from_pair(fn_sig_or_decl, config),
+                            FunctionDeclaration::parse_method_from_pair(fn_sig_or_decl, config),
                             continue,
                             warnings,
                             errors
@@ -10,7 +10,7 @@ use super::{Expression, MatchCondition};
 #[derive(Debug, Clone)]
 pub struct MatchBranch {
     pub(crate) condition: MatchCondition,
-    pub(crate) result: Expression,
+    pub result: Expression,
     pub(crate) span: span::Span,
 }
 
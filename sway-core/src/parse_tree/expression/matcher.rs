@@ -1,6 +1,6 @@
 use crate::{
     error::{err, ok},
-    CallPath, CompileError, CompileResult, DelayedEnumVariantResolution, DelayedResolutionVariant,
+    CallPath, CompileResult, DelayedEnumVariantResolution, DelayedResolutionVariant,
     DelayedStructFieldResolution, DelayedTupleVariantResolution, Expression, Ident, Literal,
     Scrutinee, StructScrutineeField,
 };
@@ -55,9 +55,8 @@ pub type MatcherResult = Option<(MatchReqMap, MatchImplMap)>;
 /// ]
 /// ```
 pub fn matcher(exp: &Expression, scrutinee: &Scrutinee) -> CompileResult<MatcherResult> {
-    let mut errors = vec![];
-    let warnings = vec![];
     match scrutinee {
+        Scrutinee::Unit { .. } => ok(Some((vec![], vec![])), vec![], vec![]),
         Scrutinee::Literal { value, span } => match_literal(exp, value, span),
         Scrutinee::Variable { name, span } => match_variable(exp, name, span),
         Scrutinee::StructScrutinee {
@@ -71,14 +70,6 @@ pub fn matcher(exp: &Expression, scrutinee: &Scrutinee) -> CompileResult<Matcher
             span,
         } => match_enum(exp, call_path, args, span),
         Scrutinee::Tuple { elems, span } => match_tuple(exp, elems, span),
-        scrutinee => {
-            eprintln!("Unimplemented scrutinee: {:?}", scrutinee,);
-            errors.push(CompileError::Unimplemented(
-                "this match expression scrutinee is not implemented",
-                scrutinee.span(),
-            ));
-            ok(Some((vec![], vec![])), warnings, errors)
-        }
     }
 }
 
@@ -165,6 +165,14 @@ pub enum Expression {
         type_span: Span,
         span: Span,
     },
+    /// An access into contract storage, of the form:
+    /// ```ignore
+    /// storage.<ident>
+    /// ```
+    StorageAccess {
+        field_names: Vec<Ident>,
+        span: Span,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -286,6 +294,7 @@ impl Expression {
             DelayedMatchTypeResolution { span, .. } => span,
             SizeOfVal { span, .. } => span,
             SizeOfType { span, .. } => span,
+            StorageAccess { span, .. } => span,
         })
         .clone()
     }
@@ -537,12 +546,11 @@ impl Expression {
                     );
                     branches.push(res);
                 }
-                check!(
-                    desugar_match_expression(primary_expression, branches, span),
-                    return err(warnings, errors),
-                    warnings,
-                    errors
-                )
+                Expression::MatchExpression {
+                    primary_expression: Box::new(primary_expression),
+                    branches,
+                    span,
+                }
             }
             Rule::struct_expression => {
                 let mut expr_iter = expr.into_inner();
@@ -1094,6 +1102,12 @@ impl Expression {
                 warnings,
                 errors
             ),
+            Rule::storage_access => check!(
+                parse_storage_access(expr, config),
+                return err(warnings, errors),
+                warnings,
+                errors
+            ),
             a => {
                 eprintln!(
                     "Unimplemented expr: {:?} ({:?}) ({:?})",
@@ -1267,6 +1281,35 @@ pub(crate) fn parse_size_of_expr(
     ok(exp, warnings, errors)
 }
 
+pub(crate) fn parse_storage_access(
+    item: Pair<Rule>,
+    config: Option<&BuildConfig>,
+) -> CompileResult<Expression> {
+    let mut warnings = vec![];
+    let mut errors = vec![];
+    debug_assert_eq!(item.as_rule(), Rule::storage_access);
+    let span = Span {
+        span: item.as_span(),
+        path: config.map(|c| c.path()),
+    };
+    let mut iter = item.into_inner();
+    let _storage_keyword = iter.next();
+    let mut field_names = vec![];
+    for name in iter {
+        field_names.push(check!(
+            ident::parse_from_pair(name, config),
+            return err(warnings, errors),
+            warnings,
+            errors
+        ));
+    }
+    ok(
+        Expression::StorageAccess { field_names, span },
+        warnings,
+        errors,
+    )
+}
+
 fn parse_subfield_path(
     item: Pair<Rule>,
     config: Option<&BuildConfig>,
@@ -1693,6 +1736,9 @@ struct MatchedBranch {
 ///     2b. Assemble the statements that go inside of the body of the if expression
 ///     2c. Assemble the giant if statement.
 /// 3. Return!
+///
+/// The branches must already have been checked for exhaustiveness, as the last branch is taken
+/// unconditionally.
 pub fn desugar_match_expression(
     primary_expression: Expression,
     branches: Vec<MatchBranch>,
@@ -1743,14 +1789,23 @@ pub fn desugar_match_expression(
 
     // 2. Assemble the possibly nested giant if statement using the matched branches.
     let mut if_statement = None;
-    for MatchedBranch {
-        result,
-        match_req_map,
-        match_impl_map,
-        branch_span,
-    } in matched_branches.iter().rev()
+    for (
+        idx,
+        MatchedBranch {
+            result,
+            match_req_map,
+            match_impl_map,
+            branch_span,
+        },
+    ) in matched_branches.iter().rev().enumerate()
     {
-        // 2a. Assemble the conditional that goes in the if primary expression.
+        // 2a. Assemble the conditional that goes in the if primary expression.  The last branch
+        // is only reached when nothing else matched, so it doesn't need one.
+        let match_req_map = if idx == 0 {
+            &[][..]
+        } else {
+            match_req_map.as_slice()
+        };
         let mut conditional = None;
         for (left_req, right_req) in match_req_map.iter() {
             let joined_span = join_spans(left_req.clone().span(), right_req.clone().span());
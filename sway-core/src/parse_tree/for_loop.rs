@@ -0,0 +1,246 @@
+use super::{
+    expression::{Op, OpVariant},
+    ident, Reassignment, VariableDeclaration,
+};
+use crate::{
+    build_config::BuildConfig,
+    error::{ok, CompileResult},
+    parser::Rule,
+    type_engine::TypeInfo,
+    AstNode, AstNodeContent, CodeBlock, Declaration, Expression, Literal, WhileLoop,
+};
+
+use sway_types::{ident::Ident, span::Span};
+
+use pest::iterators::Pair;
+
+/// A parsed for loop, which binds `variable` to each value produced by `iterable` in turn and runs
+/// the `body` for each of them.
+///
+/// A for loop is desugared into a [WhileLoop] once the type of its `iterable` is known. See
+/// [ForLoop::desugar].
+#[derive(Debug, Clone)]
+pub struct ForLoop {
+    pub(crate) variable: Ident,
+    pub(crate) iterable: ForLoopIterable,
+    pub(crate) body: CodeBlock,
+    /// The name of the hidden variable holding the index of the current iteration.
+    pub(crate) index_name: Ident,
+    /// The name of the hidden variable holding the end of the range, or the array being iterated.
+    pub(crate) iterable_name: Ident,
+}
+
+/// The values a [ForLoop] iterates over.
+#[derive(Debug, Clone)]
+pub enum ForLoopIterable {
+    /// A half-open range of integers, `start..end`.
+    Range {
+        start: Box<Expression>,
+        end: Box<Expression>,
+    },
+    /// Every element of an array, in order.
+    Array(Box<Expression>),
+}
+
+impl ForLoop {
+    pub(crate) fn parse_from_pair(
+        pair: Pair<Rule>,
+        config: Option<&BuildConfig>,
+    ) -> CompileResult<Self> {
+        let path = config.map(|c| c.path());
+        let mut warnings = Vec::new();
+        let mut errors = Vec::new();
+        let span = Span {
+            span: pair.as_span(),
+            path: path.clone(),
+        };
+        let mut iter = pair.into_inner();
+        let _for_keyword = iter.next().unwrap();
+        let variable = iter.next().unwrap();
+        let _in_keyword = iter.next().unwrap();
+        let iterable = iter.next().unwrap();
+        let body = iter.next().unwrap();
+        let whole_block_span = Span {
+            span: body.as_span(),
+            path: path.clone(),
+        };
+
+        let variable = check!(
+            ident::parse_from_pair(variable, config),
+            Ident::new_with_override("error parsing for loop variable", span.clone()),
+            warnings,
+            errors
+        );
+
+        let mut parse_expression = |pair: Pair<Rule>| {
+            let expr_span = Span {
+                span: pair.as_span(),
+                path: path.clone(),
+            };
+            check!(
+                Expression::parse_from_pair(pair, config),
+                Expression::Tuple {
+                    fields: vec![],
+                    span: expr_span
+                },
+                warnings,
+                errors
+            )
+        };
+        let iterable = match iterable.as_rule() {
+            Rule::for_range => {
+                let mut range = iterable.into_inner();
+                let start = Box::new(parse_expression(range.next().unwrap()));
+                let end = Box::new(parse_expression(range.next().unwrap()));
+                ForLoopIterable::Range { start, end }
+            }
+            _ => ForLoopIterable::Array(Box::new(parse_expression(iterable))),
+        };
+
+        let body = check!(
+            CodeBlock::parse_from_pair(body, config),
+            CodeBlock {
+                contents: Default::default(),
+                whole_block_span,
+            },
+            warnings,
+            errors
+        );
+
+        ok(
+            ForLoop {
+                variable,
+                iterable,
+                body,
+                index_name: ident::random_name(span.clone(), config),
+                iterable_name: ident::random_name(span, config),
+            },
+            warnings,
+            errors,
+        )
+    }
+
+    /// Desugars this loop into a [WhileLoop] inside a code block. `array_length` must be the
+    /// length of the array being iterated over, if any, in which case the array must already be
+    /// bound to `iterable_name` around the returned block.
+    ///
+    /// ```ignore
+    /// for x in start..end {
+    ///     ..
+    /// }
+    /// ```
+    ///
+    /// becomes
+    ///
+    /// ```ignore
+    /// {
+    ///     let mut index = start;
+    ///     let iterable = end;
+    ///     while index < iterable {
+    ///         let x = index;
+    ///         index = index + 1;
+    ///         ..
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// and iterating over an array starts `index` at zero, ends it at `array_length` and binds
+    /// `x` to `iterable[index]` instead, without declaring `iterable`. The index is incremented
+    /// before the body so that `continue` moves on to the next value.
+    pub(crate) fn desugar(self, span: Span, array_length: Option<usize>) -> Expression {
+        let ForLoop {
+            variable,
+            iterable,
+            body,
+            index_name,
+            iterable_name,
+        } = self;
+        let variable_span = variable.span().clone();
+        let var_expr = |name: &Ident| Expression::VariableExpression {
+            name: name.clone(),
+            span: variable_span.clone(),
+        };
+        let u64_literal = |value: usize| Expression::Literal {
+            value: Literal::U64(value as u64),
+            span: variable_span.clone(),
+        };
+        let declaration = |name: &Ident, body: Expression, is_mutable: bool| AstNode {
+            content: AstNodeContent::Declaration(Declaration::VariableDeclaration(
+                VariableDeclaration {
+                    name: name.clone(),
+                    type_ascription: TypeInfo::Unknown,
+                    type_ascription_span: None,
+                    body,
+                    is_mutable,
+                },
+            )),
+            span: span.clone(),
+        };
+
+        let (start, end, element) = match iterable {
+            ForLoopIterable::Range { start, end } => (*start, Some(*end), var_expr(&index_name)),
+            ForLoopIterable::Array(_) => (
+                u64_literal(0),
+                None,
+                Expression::ArrayIndex {
+                    prefix: Box::new(var_expr(&iterable_name)),
+                    index: Box::new(var_expr(&index_name)),
+                    span: variable_span.clone(),
+                },
+            ),
+        };
+        let bound = match array_length {
+            Some(length) => u64_literal(length),
+            None => var_expr(&iterable_name),
+        };
+        let condition = Expression::core_ops(
+            Op {
+                span: span.clone(),
+                op_variant: OpVariant::LessThan,
+            },
+            vec![var_expr(&index_name), bound],
+            span.clone(),
+        );
+        let increment = Expression::core_ops(
+            Op {
+                span: variable_span.clone(),
+                op_variant: OpVariant::Add,
+            },
+            vec![var_expr(&index_name), u64_literal(1)],
+            variable_span.clone(),
+        );
+
+        let mut loop_body = vec![
+            declaration(&variable, element, false),
+            AstNode {
+                content: AstNodeContent::Declaration(Declaration::Reassignment(Reassignment {
+                    lhs: Box::new(var_expr(&index_name)),
+                    rhs: increment,
+                    span: variable_span.clone(),
+                })),
+                span: variable_span.clone(),
+            },
+        ];
+        loop_body.extend(body.contents);
+
+        let mut contents = vec![declaration(&index_name, start, true)];
+        contents.extend(end.map(|end| declaration(&iterable_name, end, false)));
+        contents.push(AstNode {
+            content: AstNodeContent::WhileLoop(WhileLoop {
+                condition,
+                body: CodeBlock {
+                    contents: loop_body,
+                    whole_block_span: body.whole_block_span,
+                },
+            }),
+            span: span.clone(),
+        });
+        Expression::CodeBlock {
+            contents: CodeBlock {
+                contents,
+                whole_block_span: span.clone(),
+            },
+            span,
+        }
+    }
+}
@@ -21,6 +21,9 @@ pub enum Literal {
     U16(u16),
     U32(u32),
     U64(u64),
+    U128(u128),
+    /// A 256 bit unsigned integer, stored as big-endian bytes.
+    U256([u8; 32]),
     String(span::Span),
     Numeric(u64),
     Boolean(bool),
@@ -37,6 +40,8 @@ impl Literal {
             U16(_) => ResolvedType::UnsignedInteger(IntegerBits::Sixteen),
             U32(_) => ResolvedType::UnsignedInteger(IntegerBits::ThirtyTwo),
             U64(_) => ResolvedType::UnsignedInteger(IntegerBits::SixtyFour),
+            U128(_) => ResolvedType::UnsignedInteger(IntegerBits::OneTwentyEight),
+            U256(_) => ResolvedType::UnsignedInteger(IntegerBits::TwoFiftySix),
             Numeric(_) => ResolvedType::UnsignedInteger(IntegerBits::SixtyFour),
             String(inner) => ResolvedType::Str(inner.as_str().len() as u64),
             Boolean(_) => ResolvedType::Boolean,
@@ -142,6 +147,32 @@ impl Literal {
                                     path.clone(),
                                 )
                             }),
+                        Rule::u128_integer => int_inner
+                            .as_str()
+                            .trim()
+                            .replace('_', "")
+                            .parse()
+                            .map(Literal::U128)
+                            .map_err(|e| {
+                                Literal::handle_parse_int_error(
+                                    e,
+                                    TypeInfo::UnsignedInteger(IntegerBits::OneTwentyEight),
+                                    int_inner.as_span(),
+                                    path.clone(),
+                                )
+                            }),
+                        Rule::u256_integer => {
+                            parse_u256(&int_inner.as_str().trim().replace('_', ""))
+                                .map(Literal::U256)
+                                .map_err(|kind| {
+                                    Literal::handle_int_error_kind(
+                                        &kind,
+                                        TypeInfo::UnsignedInteger(IntegerBits::TwoFiftySix),
+                                        int_inner.as_span(),
+                                        path.clone(),
+                                    )
+                                })
+                        }
                         _ => unreachable!(),
                     },
                     span,
@@ -235,6 +266,8 @@ impl Literal {
                 vec![0, 0, 0, 0, bytes[0], bytes[1], bytes[2], bytes[3]]
             }
             U64(val) => val.to_be_bytes().to_vec(),
+            U128(val) => val.to_be_bytes().to_vec(),
+            U256(bytes) => bytes.to_vec(),
             Numeric(val) => val.to_be_bytes().to_vec(),
             Boolean(b) => {
                 vec![
@@ -268,14 +301,23 @@ impl Literal {
         Literal::U64(offset_bytes)
     }
 
-    #[allow(clippy::wildcard_in_or_patterns)]
     pub(crate) fn handle_parse_int_error(
         e: ParseIntError,
         ty: TypeInfo,
         span: Span,
         path: Option<Arc<PathBuf>>,
     ) -> CompileError {
-        match e.kind() {
+        Literal::handle_int_error_kind(e.kind(), ty, span, path)
+    }
+
+    #[allow(clippy::wildcard_in_or_patterns)]
+    pub(crate) fn handle_int_error_kind(
+        kind: &IntErrorKind,
+        ty: TypeInfo,
+        span: Span,
+        path: Option<Arc<PathBuf>>,
+    ) -> CompileError {
+        match kind {
             IntErrorKind::PosOverflow => CompileError::IntegerTooLarge {
                 ty: ty.friendly_type_str(),
                 span: span::Span { span, path },
@@ -296,6 +338,32 @@ impl Literal {
     }
 }
 
+/// Parses a decimal number into the big-endian bytes of a 256 bit unsigned integer, since there
+/// is no primitive type to defer to.
+pub(crate) fn parse_u256(digits: &str) -> Result<[u8; 32], IntErrorKind> {
+    if digits.is_empty() {
+        return Err(IntErrorKind::Empty);
+    }
+    // little-endian words, so that carries propagate upwards through the array
+    let mut words = [0u64; 4];
+    for digit in digits.chars() {
+        let mut carry = digit.to_digit(10).ok_or(IntErrorKind::InvalidDigit)? as u128;
+        for word in words.iter_mut() {
+            let product = *word as u128 * 10 + carry;
+            *word = product as u64;
+            carry = product >> 64;
+        }
+        if carry != 0 {
+            return Err(IntErrorKind::PosOverflow);
+        }
+    }
+    let mut bytes = [0u8; 32];
+    for (chunk, word) in bytes.chunks_mut(8).zip(words.iter().rev()) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    Ok(bytes)
+}
+
 fn parse_hex_from_pair(
     pair: Pair<Rule>,
     config: Option<&BuildConfig>,
@@ -1,13 +1,24 @@
 //! Type checking for Sway.
 pub mod ast_node;
+mod call_graph;
+mod function_table;
 mod namespace;
 mod node_dependencies;
+mod symbols;
 mod syntax_tree;
 pub(crate) mod type_check_arguments;
+pub use ast_node::{
+    StorageSlot, TypedConstantDeclaration, TypedDeclaration, TypedFunctionDeclaration,
+};
 pub(crate) use ast_node::{TypedAstNode, TypedAstNodeContent, TypedExpression};
-pub use ast_node::{TypedConstantDeclaration, TypedDeclaration, TypedFunctionDeclaration};
+pub(crate) use call_graph::{CallGraph, FunctionKey};
+pub(crate) use function_table::FunctionTable;
 pub use namespace::Namespace;
 pub use namespace::*;
+pub use symbols::{
+    abi_methods, import_paths, namespace_items, path_items, struct_fields, type_members, NamedItem,
+    Symbol, SymbolKind,
+};
 pub use syntax_tree::TreeType;
 pub use syntax_tree::TypedParseTree;
 pub use type_check_arguments::*;
@@ -4,8 +4,10 @@ use crate::{error::*, parse_tree::*, type_engine::*, Ident};
 use sway_types::{join_spans, span::Span, Property};
 
 mod function;
+mod storage;
 mod variable;
 pub use function::*;
+pub use storage::*;
 pub use variable::*;
 
 #[derive(Clone, Debug)]
@@ -24,6 +26,8 @@ pub enum TypedDeclaration {
         type_implementing_for: TypeInfo,
     },
     AbiDeclaration(TypedAbiDeclaration),
+    StorageDeclaration(TypedStorageDeclaration),
+    StorageReassignment(TypedStorageReassignment),
     // If type parameters are defined for a function, they are put in the namespace just for
     // the body of that function.
     GenericTypeForFunctionScope {
@@ -45,6 +49,7 @@ impl TypedDeclaration {
             StructDeclaration(ref mut struct_decl) => struct_decl.copy_types(type_mapping),
             EnumDeclaration(ref mut enum_decl) => enum_decl.copy_types(type_mapping),
             Reassignment(ref mut reassignment) => reassignment.copy_types(type_mapping),
+            StorageReassignment(ref mut reassignment) => reassignment.copy_types(type_mapping),
             ImplTrait {
                 ref mut methods, ..
             } => {
@@ -52,6 +57,8 @@ impl TypedDeclaration {
             }
             // generics in an ABI is unsupported by design
             AbiDeclaration(..) => (),
+            // storage is only declared at the top level of a contract, and so it is never generic
+            StorageDeclaration(..) => (),
             GenericTypeForFunctionScope { .. } | ErrorRecovery => (),
         }
     }
@@ -71,6 +78,8 @@ impl TypedDeclaration {
             Reassignment(_) => "reassignment",
             ImplTrait { .. } => "impl trait",
             AbiDeclaration(..) => "abi",
+            StorageDeclaration(_) => "contract storage declaration",
+            StorageReassignment(_) => "storage reassignment",
             GenericTypeForFunctionScope { .. } => "generic type parameter",
             ErrorRecovery => "error",
         }
@@ -81,14 +90,17 @@ impl TypedDeclaration {
                 TypedDeclaration::VariableDeclaration(TypedVariableDeclaration {
                     body, ..
                 }) => body.return_type,
-                TypedDeclaration::FunctionDeclaration { .. } => {
-                    return err(
-                        vec![],
-                        vec![CompileError::Unimplemented(
-                            "Function pointers have not yet been implemented.",
-                            self.span(),
-                        )],
-                    )
+                TypedDeclaration::FunctionDeclaration(decl) => {
+                    if !decl.type_parameters.is_empty() {
+                        return err(
+                            vec![],
+                            vec![CompileError::Unimplemented(
+                                "Generic functions cannot be used as function pointers yet.",
+                                self.span(),
+                            )],
+                        );
+                    }
+                    decl.function_type()
                 }
                 TypedDeclaration::StructDeclaration(TypedStructDeclaration {
                     name,
@@ -102,6 +114,9 @@ impl TypedDeclaration {
                         .collect(),
                 }),
                 TypedDeclaration::Reassignment(TypedReassignment { rhs, .. }) => rhs.return_type,
+                TypedDeclaration::StorageReassignment(TypedStorageReassignment { rhs, .. }) => {
+                    rhs.return_type
+                }
                 TypedDeclaration::GenericTypeForFunctionScope { name } => {
                     insert_type(TypeInfo::UnknownGeneric { name: name.clone() })
                 }
@@ -135,6 +150,8 @@ impl TypedDeclaration {
                 .fold(lhs[0].span(), |acc, this| join_spans(acc, this.span())),
             AbiDeclaration(TypedAbiDeclaration { span, .. }) => span.clone(),
             ImplTrait { span, .. } => span.clone(),
+            StorageDeclaration(TypedStorageDeclaration { span, .. }) => span.clone(),
+            StorageReassignment(TypedStorageReassignment { span, .. }) => span.clone(),
             ErrorRecovery | GenericTypeForFunctionScope { .. } => {
                 unreachable!("No span exists for these ast node types")
             }
@@ -175,6 +192,9 @@ impl TypedDeclaration {
                     .map(|x| x.name.as_str())
                     .collect::<Vec<_>>()
                     .join("."),
+                TypedDeclaration::StorageReassignment(TypedStorageReassignment {
+                    access, ..
+                }) => format!("storage.{}", access.field_name.as_str()),
                 _ => String::new(),
             }
         )
@@ -187,6 +207,8 @@ impl TypedDeclaration {
             | Reassignment(..)
             | ImplTrait { .. }
             | AbiDeclaration(..)
+            | StorageDeclaration(..)
+            | StorageReassignment(..)
             | ErrorRecovery => Visibility::Public,
             VariableDeclaration(TypedVariableDeclaration { is_mutable, .. }) => {
                 is_mutable.visibility()
@@ -446,6 +468,9 @@ impl TypedReassignment {
 
 impl TypedTraitFn {
     pub(crate) fn copy_types(&mut self, type_mapping: &[(TypeParameter, TypeId)]) {
+        self.parameters
+            .iter_mut()
+            .for_each(|x| x.copy_types(type_mapping));
         self.return_type = if let Some(matching_id) =
             look_up_type_id(self.return_type).matches_type_parameter(type_mapping)
         {
@@ -472,6 +497,7 @@ impl TypedTraitFn {
             visibility: Visibility::Public,
             type_parameters: vec![],
             is_contract_call: mode == Mode::ImplAbiFn,
+            is_test: false,
         }
     }
 }
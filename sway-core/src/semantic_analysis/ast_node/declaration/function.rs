@@ -35,6 +35,8 @@ pub struct TypedFunctionDeclaration {
     /// whether this function exists in another contract and requires a call to it or not
     pub(crate) is_contract_call: bool,
     pub(crate) purity: Purity,
+    /// whether this function is a `#[test]` to be run by the test runner
+    pub(crate) is_test: bool,
 }
 
 impl TypedFunctionDeclaration {
@@ -64,9 +66,11 @@ impl TypedFunctionDeclaration {
             return_type_span,
             visibility,
             purity,
-            ..
+            is_test,
         } = fn_decl;
         opts.purity = purity;
+        // a loop surrounding the declaration doesn't surround the body
+        opts.in_loop = false;
         // insert type parameters as Unknown types
         let type_mapping = insert_type_parameters(&type_parameters);
         let return_type =
@@ -83,6 +87,17 @@ impl TypedFunctionDeclaration {
                     })
             };
 
+        // tests are run directly by the test runner, so they can't take or return anything
+        if is_test {
+            let returns_unit = matches!(look_up_type_id(return_type), TypeInfo::Tuple(fields) if fields.is_empty());
+            if !parameters.is_empty() || !type_parameters.is_empty() || !returns_unit {
+                errors.push(CompileError::InvalidTestFunctionSignature {
+                    name: name.as_str().to_string(),
+                    span: span.clone(),
+                });
+            }
+        }
+
         // insert parameters and generic type declarations into namespace
         let namespace = create_new_scope(namespace);
         type_parameters.iter().for_each(|param| {
@@ -223,12 +238,72 @@ impl TypedFunctionDeclaration {
                 visibility,
                 // if this is for a contract, then it is a contract call
                 is_contract_call: mode == Mode::ImplAbiFn,
+                is_test,
                 purity,
             },
             warnings,
             errors,
         )
     }
+
+    /// Type checks the signature of `fn_decl` alone, giving a declaration with an empty body.
+    ///
+    /// A function is declared this way before its own body and those of the functions it is
+    /// mutually recursive with are type checked, so that recursive calls can be resolved. The
+    /// calls embed the empty body, and code generation looks up the body of the function itself.
+    pub(crate) fn type_check_signature(
+        fn_decl: &FunctionDeclaration,
+        namespace: crate::semantic_analysis::NamespaceRef,
+        self_type: TypeId,
+    ) -> CompileResult<TypedFunctionDeclaration> {
+        let mut errors = Vec::new();
+        let type_mapping = insert_type_parameters(&fn_decl.type_parameters);
+        let mut resolve = |r#type: &TypeInfo, span: &Span| {
+            if let Some(matching_id) = r#type.matches_type_parameter(&type_mapping) {
+                insert_type(TypeInfo::Ref(matching_id))
+            } else {
+                namespace
+                    .resolve_type_with_self(r#type.clone(), self_type)
+                    .unwrap_or_else(|_| {
+                        errors.push(CompileError::UnknownType { span: span.clone() });
+                        insert_type(TypeInfo::ErrorRecovery)
+                    })
+            }
+        };
+        let return_type = resolve(&fn_decl.return_type, &fn_decl.return_type_span);
+        let parameters = fn_decl
+            .parameters
+            .iter()
+            .map(|param| TypedFunctionParameter {
+                name: param.name.clone(),
+                r#type: resolve(&param.r#type, &param.type_span),
+                type_span: param.type_span.clone(),
+            })
+            .collect();
+        if !errors.is_empty() {
+            return err(Vec::new(), errors);
+        }
+        ok(
+            TypedFunctionDeclaration {
+                name: fn_decl.name.clone(),
+                body: TypedCodeBlock {
+                    contents: vec![],
+                    whole_block_span: fn_decl.body.whole_block_span.clone(),
+                },
+                parameters,
+                span: fn_decl.span.clone(),
+                return_type,
+                type_parameters: fn_decl.type_parameters.clone(),
+                return_type_span: fn_decl.return_type_span.clone(),
+                visibility: fn_decl.visibility,
+                is_contract_call: false,
+                is_test: fn_decl.is_test,
+                purity: fn_decl.purity,
+            },
+            Vec::new(),
+            Vec::new(),
+        )
+    }
     pub(crate) fn copy_types(&mut self, type_mapping: &[(TypeParameter, TypeId)]) {
         self.body.copy_types(type_mapping);
         self.parameters
@@ -322,6 +397,13 @@ impl TypedFunctionDeclaration {
             self.name.span().clone()
         }
     }
+    /// The type of this function when it is used as a value, e.g. `fn(u64, bool) -> u64`.
+    pub(crate) fn function_type(&self) -> TypeId {
+        insert_type(TypeInfo::Function {
+            parameters: self.parameters.iter().map(|param| param.r#type).collect(),
+            return_type: self.return_type,
+        })
+    }
     pub(crate) fn replace_self_types(self, self_type: TypeId) -> Self {
         TypedFunctionDeclaration {
             parameters: self
@@ -457,6 +539,7 @@ fn test_function_selector_behavior() {
         },
         visibility: Visibility::Public,
         is_contract_call: false,
+        is_test: false,
     };
 
     let selector_text = match decl.to_selector_name().value {
@@ -524,6 +607,7 @@ fn test_function_selector_behavior() {
         },
         visibility: Visibility::Public,
         is_contract_call: false,
+        is_test: false,
     };
 
     let selector_text = match decl.to_selector_name().value {
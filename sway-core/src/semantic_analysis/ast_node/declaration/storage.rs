@@ -0,0 +1,166 @@
+use crate::{
+    error::*,
+    parse_tree::Literal,
+    semantic_analysis::{ast_node::TypedExpressionVariant, TypedExpression},
+    type_engine::*,
+    Ident,
+};
+
+use sway_types::span::Span;
+
+use sha2::{Digest, Sha256};
+
+/// A single initialized slot of contract storage, as it should be laid out in the deployment
+/// transaction of a contract.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StorageSlot {
+    pub key: [u8; 32],
+    pub value: [u8; 32],
+}
+
+#[derive(Clone, Debug)]
+pub struct TypedStorageDeclaration {
+    pub(crate) fields: Vec<TypedStorageField>,
+    pub(crate) span: Span,
+}
+
+impl TypedStorageDeclaration {
+    pub(crate) fn new(fields: Vec<TypedStorageField>, span: Span) -> Self {
+        TypedStorageDeclaration { fields, span }
+    }
+
+    /// Given a path of field names like `storage.foo`, look up the storage field being accessed
+    /// and return the information needed to read from or write to its slot.
+    pub(crate) fn apply_storage_load(
+        &self,
+        field_names: &[Ident],
+        span: &Span,
+    ) -> CompileResult<TypedStorageAccess> {
+        let mut errors = vec![];
+        let warnings = vec![];
+        let field_name = match field_names {
+            [field_name] => field_name,
+            _ => {
+                errors.push(CompileError::Unimplemented(
+                    "Accessing the subfields of storage fields is not supported yet.",
+                    span.clone(),
+                ));
+                return err(warnings, errors);
+            }
+        };
+        match self
+            .fields
+            .iter()
+            .enumerate()
+            .find(|(_, field)| field.name == *field_name)
+        {
+            Some((ix, field)) => ok(
+                TypedStorageAccess {
+                    field_name: field_name.clone(),
+                    ix,
+                    r#type: field.r#type,
+                },
+                warnings,
+                errors,
+            ),
+            None => {
+                errors.push(CompileError::StorageFieldDoesNotExist {
+                    field_name: field_name.as_str().to_string(),
+                    span: field_name.span().clone(),
+                });
+                err(warnings, errors)
+            }
+        }
+    }
+
+    /// Computes the initial values of all storage slots declared by this contract.
+    pub(crate) fn storage_slots(&self) -> Vec<StorageSlot> {
+        self.fields
+            .iter()
+            .enumerate()
+            .map(|(ix, field)| StorageSlot {
+                key: get_storage_key(ix),
+                value: field.initial_value(),
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TypedStorageField {
+    pub(crate) name: Ident,
+    pub(crate) r#type: TypeId,
+    pub(crate) initializer: TypedExpression,
+}
+
+impl TypedStorageField {
+    /// Returns the initializer of this field laid out in a single storage slot. Word-sized
+    /// values occupy the first eight bytes of the slot, big-endian.
+    fn initial_value(&self) -> [u8; 32] {
+        let mut value = [0u8; 32];
+        if let TypedExpressionVariant::Literal(lit) = &self.initializer.expression {
+            let bytes = lit.to_bytes();
+            value[..bytes.len()].copy_from_slice(&bytes);
+        }
+        value
+    }
+}
+
+/// Describes a read of, or write to, a single storage field, e.g. `storage.foo`.
+#[derive(Clone, Debug)]
+pub struct TypedStorageAccess {
+    pub(crate) field_name: Ident,
+    /// The index of the field in the storage declaration, used to derive its slot.
+    pub(crate) ix: usize,
+    pub(crate) r#type: TypeId,
+}
+
+impl TypedStorageAccess {
+    pub(crate) fn storage_key(&self) -> [u8; 32] {
+        get_storage_key(self.ix)
+    }
+
+    /// The storage key as a literal, so it can be placed in the data section.
+    pub(crate) fn storage_key_literal(&self) -> Literal {
+        Literal::B256(self.storage_key())
+    }
+
+    /// Whether the value in this slot fits in a register. If not, it is a `b256` and must be
+    /// read or written through memory.
+    pub(crate) fn is_word_sized(&self) -> bool {
+        !matches!(look_up_type_id(self.r#type), TypeInfo::B256)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TypedStorageReassignment {
+    pub(crate) access: TypedStorageAccess,
+    pub(crate) rhs: TypedExpression,
+    pub(crate) span: Span,
+}
+
+impl TypedStorageReassignment {
+    pub(crate) fn copy_types(&mut self, type_mapping: &[(crate::TypeParameter, TypeId)]) {
+        self.rhs.copy_types(type_mapping);
+    }
+}
+
+/// The slot of the `ix`th field of a storage declaration is the SHA-256 hash of
+/// `storage_<ix>`.
+pub(crate) fn get_storage_key(ix: usize) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(format!("storage_{}", ix));
+    let mut key = [0u8; 32];
+    key.copy_from_slice(&hasher.finalize());
+    key
+}
+
+/// Returns whether or not `ty` can be held in a single storage slot. Wide integers could be, but
+/// aren't supported yet.
+pub(crate) fn is_valid_storage_type(ty: &TypeInfo) -> bool {
+    match ty {
+        TypeInfo::UnsignedInteger(bits) => !bits.is_wide(),
+        TypeInfo::Boolean | TypeInfo::Byte | TypeInfo::B256 | TypeInfo::Numeric => true,
+        _ => false,
+    }
+}
@@ -2,7 +2,7 @@ use crate::build_config::BuildConfig;
 use crate::control_flow_analysis::ControlFlowGraph;
 use crate::error::*;
 use crate::semantic_analysis::{ast_node::*, TCOpts, TypeCheckArguments};
-use crate::type_engine::TypeId;
+use crate::type_engine::{insert_type, look_up_type_id, TypeId, TypeInfo};
 use std::cmp::Ordering;
 use std::collections::HashMap;
 
@@ -17,14 +17,15 @@ pub(crate) fn instantiate_function_application(
     build_config: &BuildConfig,
     dead_code_graph: &mut ControlFlowGraph,
     opts: TCOpts,
+    span: Span,
 ) -> CompileResult<TypedExpression> {
     let mut warnings = vec![];
     let mut errors = vec![];
     let TypedFunctionDeclaration {
+        name: decl_name,
         parameters,
         return_type,
         body,
-        span,
         purity,
         ..
     } = typed_function_decl;
@@ -33,39 +34,7 @@ pub(crate) fn instantiate_function_application(
         errors.push(CompileError::PureCalledImpure { span: name.span() });
     }
 
-    match arguments.len().cmp(&parameters.len()) {
-        Ordering::Greater => {
-            let arguments_span = arguments.iter().fold(
-                arguments
-                    .get(0)
-                    .map(|x| x.span())
-                    .unwrap_or_else(|| name.span()),
-                |acc, arg| join_spans(acc, arg.span()),
-            );
-            errors.push(CompileError::TooManyArgumentsForFunction {
-                span: arguments_span,
-                method_name: name.suffix.clone(),
-                expected: parameters.len(),
-                received: arguments.len(),
-            });
-        }
-        Ordering::Less => {
-            let arguments_span = arguments.iter().fold(
-                arguments
-                    .get(0)
-                    .map(|x| x.span())
-                    .unwrap_or_else(|| name.span()),
-                |acc, arg| join_spans(acc, arg.span()),
-            );
-            errors.push(CompileError::TooFewArgumentsForFunction {
-                span: arguments_span,
-                method_name: name.suffix.clone(),
-                expected: parameters.len(),
-                received: arguments.len(),
-            });
-        }
-        Ordering::Equal => {}
-    }
+    check_argument_count(&name, &arguments, parameters.len(), &mut errors);
     // type check arguments in function application vs arguments in function
     // declaration. Use parameter type annotations as annotations for the
     // arguments
@@ -110,6 +79,7 @@ pub(crate) fn instantiate_function_application(
                 arguments: typed_call_arguments,
                 contract_call_params: HashMap::new(),
                 name,
+                decl_name,
                 function_body: body,
                 selector: None, // regular functions cannot be in a contract call; only methods
             },
@@ -119,3 +89,137 @@ pub(crate) fn instantiate_function_application(
         errors,
     )
 }
+
+/// Instantiates a call of a function value, e.g. `f(x)` where `f` is a variable of type
+/// `fn(u64) -> u64`. The arguments are checked against the parameter types of the function type,
+/// since the function being called is only known at runtime.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn instantiate_indirect_function_application(
+    name: CallPath,
+    arguments: Vec<Expression>,
+    namespace: crate::semantic_analysis::NamespaceRef,
+    crate_namespace: NamespaceRef,
+    self_type: TypeId,
+    build_config: &BuildConfig,
+    dead_code_graph: &mut ControlFlowGraph,
+    opts: TCOpts,
+) -> CompileResult<TypedExpression> {
+    let mut warnings = vec![];
+    let mut errors = vec![];
+    let span = arguments
+        .iter()
+        .fold(name.span(), |acc, arg| join_spans(acc, arg.span()));
+    let function = check!(
+        TypedExpression::type_check(TypeCheckArguments {
+            checkee: Expression::VariableExpression {
+                name: name.suffix.clone(),
+                span: name.span(),
+            },
+            namespace,
+            crate_namespace,
+            return_type_annotation: insert_type(TypeInfo::Unknown),
+            help_text: Default::default(),
+            self_type,
+            build_config,
+            dead_code_graph,
+            mode: Mode::NonAbi,
+            opts,
+        }),
+        return err(warnings, errors),
+        warnings,
+        errors
+    );
+    let (parameters, return_type) = match look_up_type_id(function.return_type) {
+        TypeInfo::Function {
+            parameters,
+            return_type,
+        } => (parameters, return_type),
+        _ => {
+            errors.push(CompileError::NotAFunction {
+                name: name.span().as_str().to_string(),
+                span: name.span(),
+                what_it_is: "variable",
+            });
+            return err(warnings, errors);
+        }
+    };
+
+    check_argument_count(&name, &arguments, parameters.len(), &mut errors);
+    let typed_call_arguments = arguments
+        .into_iter()
+        .zip(parameters.iter())
+        .map(|(arg, param_type)| {
+            TypedExpression::type_check(TypeCheckArguments {
+                checkee: arg.clone(),
+                namespace,
+                crate_namespace,
+                return_type_annotation: *param_type,
+                help_text: "The argument that has been provided to this function value does not \
+                    match the declared type of the parameter in its function type.",
+                self_type,
+                build_config,
+                dead_code_graph,
+                mode: Mode::NonAbi,
+                opts,
+            })
+            .unwrap_or_else(&mut warnings, &mut errors, || {
+                error_recovery_expr(arg.span())
+            })
+        })
+        .collect();
+
+    ok(
+        TypedExpression {
+            return_type,
+            is_constant: IsConstant::No,
+            expression: TypedExpressionVariant::IndirectFunctionApplication {
+                function: Box::new(function),
+                arguments: typed_call_arguments,
+            },
+            span,
+        },
+        warnings,
+        errors,
+    )
+}
+
+fn check_argument_count(
+    name: &CallPath,
+    arguments: &[Expression],
+    expected: usize,
+    errors: &mut Vec<CompileError>,
+) {
+    match arguments.len().cmp(&expected) {
+        Ordering::Greater => {
+            let arguments_span = arguments.iter().fold(
+                arguments
+                    .get(0)
+                    .map(|x| x.span())
+                    .unwrap_or_else(|| name.span()),
+                |acc, arg| join_spans(acc, arg.span()),
+            );
+            errors.push(CompileError::TooManyArgumentsForFunction {
+                span: arguments_span,
+                method_name: name.suffix.clone(),
+                expected,
+                received: arguments.len(),
+            });
+        }
+        Ordering::Less => {
+            let arguments_span = arguments.iter().fold(
+                arguments
+                    .get(0)
+                    .map(|x| x.span())
+                    .unwrap_or_else(|| name.span()),
+                |acc, arg| join_spans(acc, arg.span()),
+            );
+            errors.push(CompileError::TooFewArgumentsForFunction {
+                span: arguments_span,
+                method_name: name.suffix.clone(),
+                expected,
+                received: arguments.len(),
+            });
+        }
+        Ordering::Equal => {}
+    }
+}
@@ -0,0 +1,846 @@
+//! Exhaustiveness and usefulness checking for `match` expressions.
+//!
+//! The arms of a match are lowered to a matrix of patterns, which is analysed using the algorithm
+//! from 'Warnings for pattern matching' by Luc Maranget.  An arm is unreachable if its pattern is
+//! not useful with respect to the rows above it, and the match is exhaustive if a wildcard pattern
+//! would not be useful with respect to every row.  When it isn't exhaustive the algorithm also
+//! finds example patterns which are missing.
+
+use std::fmt;
+
+use crate::{
+    error::{ok, CompileResult, CompileWarning, Warning},
+    type_engine::{look_up_type_id, TypeId},
+    CompileError, Literal, MatchBranch, MatchCondition, Scrutinee, StructScrutineeField, TypeInfo,
+};
+
+use sway_types::span::Span;
+
+/// The maximum number of missing patterns listed in a non-exhaustive match error.
+const MAX_REPORTED_MISSING_PATTERNS: usize = 3;
+
+/// Checks that the branches of a match over a value of type `scrutinee_type` cover every possible
+/// value, and warns at the pattern of any branch which can never be reached.
+///
+/// If a branch pattern doesn't fit the type of the scrutinee then no analysis is done, as the
+/// type error is reported when the desugared match is type checked.
+pub(crate) fn check_match_exhaustiveness(
+    scrutinee_type: TypeId,
+    branches: &[MatchBranch],
+    span: &Span,
+) -> CompileResult<()> {
+    let mut warnings = vec![];
+    let mut errors = vec![];
+
+    let mut matrix = Vec::new();
+    for branch in branches {
+        let (pattern, pattern_span) = match &branch.condition {
+            MatchCondition::CatchAll(catch_all) => {
+                (Some(Pattern::Wildcard), catch_all.span.clone())
+            }
+            MatchCondition::Scrutinee(scrutinee) => {
+                (lower_scrutinee(scrutinee, scrutinee_type), scrutinee.span())
+            }
+        };
+        let pattern = match pattern {
+            Some(pattern) => pattern,
+            None => return ok((), warnings, errors),
+        };
+        let row = vec![pattern];
+        if !is_useful(&matrix, &row, &[scrutinee_type]) {
+            warnings.push(CompileWarning {
+                span: pattern_span,
+                warning_content: Warning::MatchArmUnreachable,
+            });
+        }
+        matrix.push(row);
+    }
+
+    // Each missing pattern found is added to the matrix so that the next search finds a different
+    // one.
+    let mut missing_patterns = Vec::new();
+    while missing_patterns.len() < MAX_REPORTED_MISSING_PATTERNS {
+        match find_missing_pattern(&matrix, &[scrutinee_type]) {
+            Some(witness) => {
+                missing_patterns.push(witness[0].to_string());
+                matrix.push(witness);
+            }
+            None => break,
+        }
+    }
+    if !missing_patterns.is_empty() {
+        errors.push(CompileError::MatchExpressionNonExhaustive {
+            missing_patterns: missing_patterns.join(", "),
+            span: span.clone(),
+        });
+    }
+
+    ok((), warnings, errors)
+}
+
+#[derive(Clone, Debug)]
+enum Pattern {
+    Wildcard,
+    Constructed(Constructor, Vec<Pattern>),
+}
+
+/// The constructor at the head of a pattern.  Enum variants have a single argument, their
+/// payload, structs have an argument per field in declaration order and tuples have an argument
+/// per element.
+#[derive(Clone, Debug)]
+enum Constructor {
+    Bool(bool),
+    Literal(Literal),
+    Variant {
+        enum_name: String,
+        variant_name: String,
+        tag: usize,
+        has_payload: bool,
+    },
+    Struct {
+        struct_name: String,
+        field_names: Vec<String>,
+    },
+    Tuple(usize),
+}
+
+impl Constructor {
+    fn is_same(&self, other: &Constructor) -> bool {
+        match (self, other) {
+            (Constructor::Bool(a), Constructor::Bool(b)) => a == b,
+            (Constructor::Literal(a), Constructor::Literal(b)) => literals_match(a, b),
+            (Constructor::Variant { tag: a, .. }, Constructor::Variant { tag: b, .. }) => a == b,
+            (Constructor::Struct { .. }, Constructor::Struct { .. }) => true,
+            (Constructor::Tuple(a), Constructor::Tuple(b)) => a == b,
+            _otherwise => false,
+        }
+    }
+}
+
+fn literals_match(a: &Literal, b: &Literal) -> bool {
+    match (literal_as_u128(a), literal_as_u128(b)) {
+        (Some(a), Some(b)) => a == b,
+        _otherwise => match (a, b) {
+            (Literal::String(a), Literal::String(b)) => a.as_str() == b.as_str(),
+            (a, b) => a == b,
+        },
+    }
+}
+
+fn literal_as_u128(lit: &Literal) -> Option<u128> {
+    match lit {
+        Literal::U8(n) => Some(*n as u128),
+        Literal::U16(n) => Some(*n as u128),
+        Literal::U32(n) => Some(*n as u128),
+        Literal::U64(n) | Literal::Numeric(n) => Some(*n as u128),
+        Literal::U128(n) => Some(*n),
+        Literal::U256(bytes) if bytes[..16].iter().all(|b| *b == 0) => {
+            Some(u128::from_be_bytes(bytes[16..].try_into().unwrap()))
+        }
+        _otherwise => None,
+    }
+}
+
+fn variant_constructor(
+    enum_name: &str,
+    variant_name: &str,
+    tag: usize,
+    payload_type: TypeId,
+) -> Constructor {
+    let has_payload = !matches!(
+        look_up_type_id(payload_type),
+        TypeInfo::Tuple(fields) if fields.is_empty()
+    );
+    Constructor::Variant {
+        enum_name: enum_name.to_string(),
+        variant_name: variant_name.to_string(),
+        tag,
+        has_payload,
+    }
+}
+
+// Convert a scrutinee into a pattern for a value of type `ty`, or `None` if it can't match that
+// type.
+fn lower_scrutinee(scrutinee: &Scrutinee, ty: TypeId) -> Option<Pattern> {
+    match (scrutinee, look_up_type_id(ty)) {
+        (_, TypeInfo::ErrorRecovery) => None,
+        (Scrutinee::Variable { .. }, _) => Some(Pattern::Wildcard),
+        (Scrutinee::Unit { .. }, TypeInfo::Tuple(elem_types)) if elem_types.is_empty() => {
+            Some(Pattern::Constructed(Constructor::Tuple(0), vec![]))
+        }
+        (
+            Scrutinee::Literal {
+                value: Literal::Boolean(b),
+                ..
+            },
+            TypeInfo::Boolean,
+        ) => Some(Pattern::Constructed(Constructor::Bool(*b), vec![])),
+        (
+            Scrutinee::Literal {
+                value: Literal::Boolean(_),
+                ..
+            },
+            _,
+        )
+        | (Scrutinee::Literal { .. }, TypeInfo::Boolean) => None,
+        (Scrutinee::Literal { value, .. }, _) => Some(Pattern::Constructed(
+            Constructor::Literal(value.clone()),
+            vec![],
+        )),
+        (Scrutinee::Tuple { elems, .. }, TypeInfo::Tuple(elem_types))
+            if elems.len() == elem_types.len() =>
+        {
+            let args = elems
+                .iter()
+                .zip(elem_types.iter())
+                .map(|(elem, elem_type)| lower_scrutinee(elem, *elem_type))
+                .collect::<Option<Vec<_>>>()?;
+            Some(Pattern::Constructed(Constructor::Tuple(args.len()), args))
+        }
+        (
+            Scrutinee::StructScrutinee {
+                struct_name,
+                fields,
+                ..
+            },
+            TypeInfo::Struct {
+                name,
+                fields: field_types,
+            },
+        ) if struct_name.as_str() == name => {
+            if fields.iter().any(|field| {
+                !field_types
+                    .iter()
+                    .any(|field_type| field_type.name == field.field.as_str())
+            }) {
+                return None;
+            }
+            let args = field_types
+                .iter()
+                .map(|field_type| {
+                    match fields
+                        .iter()
+                        .find(|field| field.field.as_str() == field_type.name)
+                    {
+                        Some(StructScrutineeField {
+                            scrutinee: Some(scrutinee),
+                            ..
+                        }) => lower_scrutinee(scrutinee, field_type.r#type),
+                        _otherwise => Some(Pattern::Wildcard),
+                    }
+                })
+                .collect::<Option<Vec<_>>>()?;
+            let field_names = field_types
+                .iter()
+                .map(|field_type| field_type.name.clone())
+                .collect();
+            Some(Pattern::Constructed(
+                Constructor::Struct {
+                    struct_name: name,
+                    field_names,
+                },
+                args,
+            ))
+        }
+        (
+            Scrutinee::EnumScrutinee {
+                call_path,
+                args,
+                span,
+            },
+            TypeInfo::Enum {
+                name,
+                variant_types,
+            },
+        ) => {
+            if call_path.prefixes.last()?.as_str() != name {
+                return None;
+            }
+            let variant = variant_types
+                .iter()
+                .find(|variant| variant.name == call_path.suffix.as_str())?;
+            let payload = match args.as_slice() {
+                [] => Pattern::Wildcard,
+                [arg] => lower_scrutinee(arg, variant.r#type)?,
+                _otherwise => lower_scrutinee(
+                    &Scrutinee::Tuple {
+                        elems: args.clone(),
+                        span: span.clone(),
+                    },
+                    variant.r#type,
+                )?,
+            };
+            Some(Pattern::Constructed(
+                variant_constructor(&name, &variant.name, variant.tag, variant.r#type),
+                vec![payload],
+            ))
+        }
+        _otherwise => None,
+    }
+}
+
+// Every constructor of a type, if there is a finite number of them.
+fn all_constructors(ty: TypeId) -> Option<Vec<Constructor>> {
+    match look_up_type_id(ty) {
+        TypeInfo::Boolean => Some(vec![Constructor::Bool(true), Constructor::Bool(false)]),
+        TypeInfo::Enum {
+            name,
+            variant_types,
+        } => Some(
+            variant_types
+                .iter()
+                .map(|variant| {
+                    variant_constructor(&name, &variant.name, variant.tag, variant.r#type)
+                })
+                .collect(),
+        ),
+        TypeInfo::Struct { name, fields } => Some(vec![Constructor::Struct {
+            struct_name: name,
+            field_names: fields.into_iter().map(|field| field.name).collect(),
+        }]),
+        TypeInfo::Tuple(elem_types) => Some(vec![Constructor::Tuple(elem_types.len())]),
+        _otherwise => None,
+    }
+}
+
+// The types of the arguments of a constructor for a value of type `ty`.
+fn constructor_arg_types(ctor: &Constructor, ty: TypeId) -> Vec<TypeId> {
+    match (ctor, look_up_type_id(ty)) {
+        (Constructor::Variant { tag, .. }, TypeInfo::Enum { variant_types, .. }) => variant_types
+            .iter()
+            .filter(|variant| variant.tag == *tag)
+            .map(|variant| variant.r#type)
+            .collect(),
+        (Constructor::Struct { .. }, TypeInfo::Struct { fields, .. }) => {
+            fields.iter().map(|field| field.r#type).collect()
+        }
+        (Constructor::Tuple(_), TypeInfo::Tuple(elem_types)) => elem_types,
+        _otherwise => vec![],
+    }
+}
+
+// The distinct constructors at the head of the rows of a matrix.
+fn head_constructors(matrix: &[Vec<Pattern>]) -> Vec<Constructor> {
+    let mut ctors: Vec<Constructor> = Vec::new();
+    for row in matrix {
+        if let Pattern::Constructed(ctor, _) = &row[0] {
+            if !ctors.iter().any(|other| other.is_same(ctor)) {
+                ctors.push(ctor.clone());
+            }
+        }
+    }
+    ctors
+}
+
+// Every constructor of the type, if they all appear in the head constructors.
+fn complete_signature(ty: TypeId, head_ctors: &[Constructor]) -> Option<Vec<Constructor>> {
+    all_constructors(ty).filter(|all_ctors| {
+        all_ctors
+            .iter()
+            .all(|ctor| head_ctors.iter().any(|head_ctor| head_ctor.is_same(ctor)))
+    })
+}
+
+// The row with its head expanded into the arguments of `ctor`, or `None` if it can't match
+// `ctor`.
+fn specialize_row(row: &[Pattern], ctor: &Constructor, arity: usize) -> Option<Vec<Pattern>> {
+    match &row[0] {
+        Pattern::Wildcard => Some(
+            vec![Pattern::Wildcard; arity]
+                .into_iter()
+                .chain(row[1..].iter().cloned())
+                .collect(),
+        ),
+        Pattern::Constructed(head_ctor, args) if head_ctor.is_same(ctor) => {
+            Some(args.iter().chain(row[1..].iter()).cloned().collect())
+        }
+        _otherwise => None,
+    }
+}
+
+fn specialize_matrix(
+    matrix: &[Vec<Pattern>],
+    types: &[TypeId],
+    ctor: &Constructor,
+) -> (Vec<Vec<Pattern>>, Vec<TypeId>) {
+    let mut arg_types = constructor_arg_types(ctor, types[0]);
+    let arity = arg_types.len();
+    let matrix = matrix
+        .iter()
+        .filter_map(|row| specialize_row(row, ctor, arity))
+        .collect();
+    arg_types.extend_from_slice(&types[1..]);
+    (matrix, arg_types)
+}
+
+// The rows with a wildcard head, without that head.
+fn default_matrix(matrix: &[Vec<Pattern>]) -> Vec<Vec<Pattern>> {
+    matrix
+        .iter()
+        .filter(|row| matches!(row[0], Pattern::Wildcard))
+        .map(|row| row[1..].to_vec())
+        .collect()
+}
+
+// Whether there is a value matched by `row` which isn't matched by any row of `matrix`.
+fn is_useful(matrix: &[Vec<Pattern>], row: &[Pattern], types: &[TypeId]) -> bool {
+    if row.is_empty() {
+        return matrix.is_empty();
+    }
+    match &row[0] {
+        Pattern::Constructed(ctor, _) => {
+            let arity = constructor_arg_types(ctor, types[0]).len();
+            let (specialized, types) = specialize_matrix(matrix, types, ctor);
+            match specialize_row(row, ctor, arity) {
+                Some(row) => is_useful(&specialized, &row, &types),
+                None => false,
+            }
+        }
+        Pattern::Wildcard => match complete_signature(types[0], &head_constructors(matrix)) {
+            Some(all_ctors) => all_ctors.iter().any(|ctor| {
+                let arity = constructor_arg_types(ctor, types[0]).len();
+                let (specialized, types) = specialize_matrix(matrix, types, ctor);
+                let row = specialize_row(row, ctor, arity).unwrap();
+                is_useful(&specialized, &row, &types)
+            }),
+            None => is_useful(&default_matrix(matrix), &row[1..], &types[1..]),
+        },
+    }
+}
+
+// A row of patterns matching some value which isn't matched by any row of `matrix`, if there is
+// one.
+fn find_missing_pattern(matrix: &[Vec<Pattern>], types: &[TypeId]) -> Option<Vec<Pattern>> {
+    if types.is_empty() {
+        return if matrix.is_empty() {
+            Some(vec![])
+        } else {
+            None
+        };
+    }
+    let head_ctors = head_constructors(matrix);
+    match complete_signature(types[0], &head_ctors) {
+        Some(all_ctors) => all_ctors.into_iter().find_map(|ctor| {
+            let (specialized, arg_types) = specialize_matrix(matrix, types, &ctor);
+            let arity = arg_types.len() - (types.len() - 1);
+            find_missing_pattern(&specialized, &arg_types).map(|mut witness| {
+                let rest = witness.split_off(arity);
+                std::iter::once(Pattern::Constructed(ctor, witness))
+                    .chain(rest)
+                    .collect()
+            })
+        }),
+        None => {
+            let mut witness = find_missing_pattern(&default_matrix(matrix), &types[1..])?;
+            // Suggest a constructor which isn't covered if possible, since `_` isn't very
+            // helpful when some constructors are.
+            let head = match all_constructors(types[0]) {
+                Some(all_ctors) if !head_ctors.is_empty() => all_ctors
+                    .into_iter()
+                    .find(|ctor| !head_ctors.iter().any(|head_ctor| head_ctor.is_same(ctor)))
+                    .map(|ctor| {
+                        let arity = constructor_arg_types(&ctor, types[0]).len();
+                        Pattern::Constructed(ctor, vec![Pattern::Wildcard; arity])
+                    })
+                    .unwrap_or(Pattern::Wildcard),
+                _otherwise => Pattern::Wildcard,
+            };
+            witness.insert(0, head);
+            Some(witness)
+        }
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (ctor, args) = match self {
+            Pattern::Wildcard => return write!(f, "_"),
+            Pattern::Constructed(ctor, args) => (ctor, args),
+        };
+        match ctor {
+            Constructor::Bool(b) => write!(f, "{}", b),
+            Constructor::Literal(lit) => match lit {
+                Literal::String(s) => write!(f, "\"{}\"", s.as_str()),
+                Literal::Boolean(b) => write!(f, "{}", b),
+                Literal::Byte(b) => write!(f, "0b{:08b}", b),
+                Literal::B256(bytes) => {
+                    write!(f, "0x")?;
+                    bytes.iter().try_for_each(|b| write!(f, "{:02x}", b))
+                }
+                num => match literal_as_u128(num) {
+                    Some(n) => write!(f, "{}", n),
+                    // a `u256` beyond the range of `u128`
+                    None => match num {
+                        Literal::U256(bytes) => {
+                            write!(f, "0x")?;
+                            bytes.iter().try_for_each(|b| write!(f, "{:02x}", b))
+                        }
+                        _otherwise => write!(f, "0"),
+                    },
+                },
+            },
+            Constructor::Variant {
+                enum_name,
+                variant_name,
+                has_payload,
+                ..
+            } => {
+                write!(f, "{}::{}", enum_name, variant_name)?;
+                if *has_payload {
+                    write!(f, "({})", args[0])?;
+                }
+                Ok(())
+            }
+            Constructor::Struct {
+                struct_name,
+                field_names,
+            } => {
+                let fields = field_names
+                    .iter()
+                    .zip(args.iter())
+                    .map(|(name, arg)| format!("{}: {}", name, arg))
+                    .collect::<Vec<_>>();
+                write!(f, "{} {{ {} }}", struct_name, fields.join(", "))
+            }
+            Constructor::Tuple(_) => {
+                let elems = args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+                if elems.len() == 1 {
+                    write!(f, "({},)", elems[0])
+                } else {
+                    write!(f, "({})", elems.join(", "))
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        semantic_analysis::ast_node::{OwnedTypedEnumVariant, OwnedTypedStructField},
+        type_engine::{insert_type, IntegerBits},
+        CallPath, CatchAll, Expression,
+    };
+    use sway_types::ident::Ident;
+
+    fn empty_span() -> Span {
+        Span {
+            span: pest::Span::new(" ".into(), 0, 0).unwrap(),
+            path: None,
+        }
+    }
+
+    fn branch(condition: MatchCondition) -> MatchBranch {
+        MatchBranch {
+            condition,
+            result: Expression::Tuple {
+                fields: vec![],
+                span: empty_span(),
+            },
+            span: empty_span(),
+        }
+    }
+
+    fn arm(scrutinee: Scrutinee) -> MatchBranch {
+        branch(MatchCondition::Scrutinee(scrutinee))
+    }
+
+    fn catch_all() -> MatchBranch {
+        branch(MatchCondition::CatchAll(CatchAll { span: empty_span() }))
+    }
+
+    fn var(name: &'static str) -> Scrutinee {
+        Scrutinee::Variable {
+            name: Ident::new_with_override(name, empty_span()),
+            span: empty_span(),
+        }
+    }
+
+    fn bool_lit(b: bool) -> Scrutinee {
+        Scrutinee::Literal {
+            value: Literal::Boolean(b),
+            span: empty_span(),
+        }
+    }
+
+    fn u64_lit(n: u64) -> Scrutinee {
+        Scrutinee::Literal {
+            value: Literal::U64(n),
+            span: empty_span(),
+        }
+    }
+
+    fn tuple(elems: Vec<Scrutinee>) -> Scrutinee {
+        Scrutinee::Tuple {
+            elems,
+            span: empty_span(),
+        }
+    }
+
+    fn variant(enum_name: &'static str, name: &'static str, args: Vec<Scrutinee>) -> Scrutinee {
+        Scrutinee::EnumScrutinee {
+            call_path: CallPath {
+                prefixes: vec![Ident::new_with_override(enum_name, empty_span())],
+                suffix: Ident::new_with_override(name, empty_span()),
+                is_absolute: false,
+            },
+            args,
+            span: empty_span(),
+        }
+    }
+
+    fn point(fields: Vec<(&'static str, Option<Scrutinee>)>) -> Scrutinee {
+        Scrutinee::StructScrutinee {
+            struct_name: Ident::new_with_override("Point", empty_span()),
+            fields: fields
+                .into_iter()
+                .map(|(field, scrutinee)| StructScrutineeField {
+                    field: Ident::new_with_override(field, empty_span()),
+                    scrutinee,
+                    span: empty_span(),
+                })
+                .collect(),
+            span: empty_span(),
+        }
+    }
+
+    fn bool_type() -> TypeId {
+        insert_type(TypeInfo::Boolean)
+    }
+
+    fn u64_type() -> TypeId {
+        insert_type(TypeInfo::UnsignedInteger(IntegerBits::SixtyFour))
+    }
+
+    // struct Point { flag: bool, n: u64 }
+    fn point_type() -> TypeId {
+        insert_type(TypeInfo::Struct {
+            name: "Point".into(),
+            fields: vec![
+                OwnedTypedStructField {
+                    name: "flag".into(),
+                    r#type: bool_type(),
+                },
+                OwnedTypedStructField {
+                    name: "n".into(),
+                    r#type: u64_type(),
+                },
+            ],
+        })
+    }
+
+    // enum Shape { Dot, Flag(bool), At(Point) }
+    fn shape_type() -> TypeId {
+        let variant = |name: &str, r#type, tag| OwnedTypedEnumVariant {
+            name: name.into(),
+            r#type,
+            tag,
+        };
+        insert_type(TypeInfo::Enum {
+            name: "Shape".into(),
+            variant_types: vec![
+                variant("Dot", insert_type(TypeInfo::Tuple(vec![])), 0),
+                variant("Flag", bool_type(), 1),
+                variant("At", point_type(), 2),
+            ],
+        })
+    }
+
+    fn missing_patterns(result: &CompileResult<()>) -> Option<&str> {
+        match result.errors.as_slice() {
+            [] => None,
+            [CompileError::MatchExpressionNonExhaustive {
+                missing_patterns, ..
+            }] => Some(missing_patterns.as_str()),
+            errors => panic!("unexpected errors: {:?}", errors),
+        }
+    }
+
+    fn unreachable_arms(result: &CompileResult<()>) -> usize {
+        result
+            .warnings
+            .iter()
+            .filter(|warning| matches!(warning.warning_content, Warning::MatchArmUnreachable))
+            .count()
+    }
+
+    #[test]
+    fn missing_bool_is_reported() {
+        let result = check_match_exhaustiveness(bool_type(), &[arm(bool_lit(true))], &empty_span());
+        assert_eq!(missing_patterns(&result), Some("false"));
+    }
+
+    #[test]
+    fn integers_need_a_catch_all() {
+        let ty = u64_type();
+        let result =
+            check_match_exhaustiveness(ty, &[arm(u64_lit(0)), arm(u64_lit(1))], &empty_span());
+        assert_eq!(missing_patterns(&result), Some("_"));
+
+        let result =
+            check_match_exhaustiveness(ty, &[arm(u64_lit(0)), arm(var("n"))], &empty_span());
+        assert_eq!(missing_patterns(&result), None);
+    }
+
+    #[test]
+    fn missing_variants_are_reported() {
+        let result = check_match_exhaustiveness(
+            shape_type(),
+            &[arm(variant("Shape", "Flag", vec![bool_lit(true)]))],
+            &empty_span(),
+        );
+        assert_eq!(
+            missing_patterns(&result),
+            Some("Shape::Dot, Shape::At(_), Shape::Flag(false)")
+        );
+    }
+
+    #[test]
+    fn missing_patterns_are_found_in_nested_enums_structs_and_tuples() {
+        let ty = insert_type(TypeInfo::Tuple(vec![bool_type(), shape_type()]));
+        let result = check_match_exhaustiveness(
+            ty,
+            &[
+                arm(tuple(vec![bool_lit(true), var("s")])),
+                arm(tuple(vec![
+                    bool_lit(false),
+                    variant(
+                        "Shape",
+                        "At",
+                        vec![point(vec![("flag", Some(bool_lit(true))), ("n", None)])],
+                    ),
+                ])),
+                arm(tuple(vec![var("b"), variant("Shape", "Dot", vec![])])),
+                arm(tuple(vec![
+                    var("b"),
+                    variant("Shape", "Flag", vec![var("f")]),
+                ])),
+            ],
+            &empty_span(),
+        );
+        assert_eq!(
+            missing_patterns(&result),
+            Some("(false, Shape::At(Point { flag: false, n: _ }))")
+        );
+        assert_eq!(unreachable_arms(&result), 0);
+    }
+
+    #[test]
+    fn nested_patterns_can_be_exhaustive() {
+        let ty = insert_type(TypeInfo::Tuple(vec![shape_type(), bool_type()]));
+        let result = check_match_exhaustiveness(
+            ty,
+            &[
+                arm(tuple(vec![
+                    variant(
+                        "Shape",
+                        "At",
+                        vec![point(vec![("flag", Some(bool_lit(false)))])],
+                    ),
+                    var("b"),
+                ])),
+                arm(tuple(vec![
+                    variant(
+                        "Shape",
+                        "At",
+                        vec![point(vec![("flag", Some(bool_lit(true))), ("n", None)])],
+                    ),
+                    bool_lit(true),
+                ])),
+                arm(tuple(vec![var("s"), bool_lit(false)])),
+                arm(tuple(vec![variant("Shape", "Dot", vec![]), var("b")])),
+                arm(tuple(vec![
+                    variant("Shape", "Flag", vec![var("f")]),
+                    var("b"),
+                ])),
+            ],
+            &empty_span(),
+        );
+        assert!(result.errors.is_empty());
+        assert_eq!(unreachable_arms(&result), 0);
+    }
+
+    #[test]
+    fn unreachable_arms_are_warned_about() {
+        let result = check_match_exhaustiveness(
+            bool_type(),
+            &[arm(bool_lit(true)), arm(bool_lit(false)), catch_all()],
+            &empty_span(),
+        );
+        assert!(result.errors.is_empty());
+        assert_eq!(unreachable_arms(&result), 1);
+
+        let result = check_match_exhaustiveness(
+            u64_type(),
+            &[arm(u64_lit(1)), arm(u64_lit(1)), arm(var("n")), catch_all()],
+            &empty_span(),
+        );
+        assert!(result.errors.is_empty());
+        assert_eq!(unreachable_arms(&result), 2);
+
+        let result = check_match_exhaustiveness(
+            shape_type(),
+            &[
+                arm(variant("Shape", "At", vec![var("p")])),
+                arm(variant(
+                    "Shape",
+                    "At",
+                    vec![point(vec![("flag", Some(bool_lit(true))), ("n", None)])],
+                )),
+                catch_all(),
+            ],
+            &empty_span(),
+        );
+        assert!(result.errors.is_empty());
+        assert_eq!(unreachable_arms(&result), 1);
+    }
+
+    #[test]
+    fn unreachable_arm_warnings_point_at_the_pattern() {
+        let source: std::sync::Arc<str> = "match b { true => (), _ => (), _ => () }".into();
+        let catch_all_span = Span {
+            span: pest::Span::new(source, 31, 32).unwrap(),
+            path: None,
+        };
+        let result = check_match_exhaustiveness(
+            bool_type(),
+            &[
+                arm(bool_lit(true)),
+                catch_all(),
+                branch(MatchCondition::CatchAll(CatchAll {
+                    span: catch_all_span,
+                })),
+            ],
+            &empty_span(),
+        );
+        assert_eq!(unreachable_arms(&result), 1);
+        assert_eq!(result.warnings[0].span.as_str(), "_");
+        assert_eq!(result.warnings[0].span.start(), 31);
+    }
+
+    #[test]
+    fn at_most_three_missing_patterns_are_reported() {
+        let ty = insert_type(TypeInfo::Tuple(vec![bool_type(), bool_type(), bool_type()]));
+        let result = check_match_exhaustiveness(
+            ty,
+            &[arm(tuple(vec![
+                bool_lit(true),
+                bool_lit(true),
+                bool_lit(true),
+            ]))],
+            &empty_span(),
+        );
+        assert_eq!(
+            missing_patterns(&result).map(|patterns| patterns.split(", (").count()),
+            Some(MAX_REPORTED_MISSING_PATTERNS)
+        );
+    }
+
+    #[test]
+    fn mistyped_patterns_are_left_to_the_type_checker() {
+        let result = check_match_exhaustiveness(u64_type(), &[arm(bool_lit(true))], &empty_span());
+        assert!(result.errors.is_empty());
+        assert!(result.warnings.is_empty());
+    }
+}
@@ -49,7 +49,7 @@ impl TypedExpression {
         let res = match other {
             Expression::Literal { value: lit, span } => Self::type_check_literal(lit, span),
             Expression::VariableExpression { name, span, .. } => {
-                Self::type_check_variable_expression(name, span, namespace)
+                Self::type_check_variable_expression(name, span, namespace, opts)
             }
             Expression::FunctionApplication {
                 name,
@@ -173,6 +173,7 @@ impl TypedExpression {
                 span,
                 namespace,
                 crate_namespace,
+                type_annotation,
                 self_type,
                 build_config,
                 dead_code_graph,
@@ -267,6 +268,25 @@ impl TypedExpression {
                 },
                 span,
             ),
+            Expression::MatchExpression {
+                primary_expression,
+                branches,
+                span,
+            } => Self::type_check_match_expression(
+                TypeCheckArguments {
+                    checkee: (*primary_expression, branches),
+                    namespace,
+                    crate_namespace,
+                    return_type_annotation: type_annotation,
+                    help_text,
+                    self_type,
+                    build_config,
+                    dead_code_graph,
+                    mode: Mode::NonAbi,
+                    opts,
+                },
+                span,
+            ),
             Expression::DelayedMatchTypeResolution { variant, span } => {
                 Self::type_check_delayed_resolution(
                     variant,
@@ -313,15 +333,21 @@ impl TypedExpression {
                 },
                 span,
             ),
-            a => {
-                let errors = vec![CompileError::Unimplemented(
-                    "Unimplemented expression",
-                    a.span(),
-                )];
-
-                let exp = error_recovery_expr(a.span());
-                ok(exp, vec![], errors)
-            }
+            Expression::StorageAccess { field_names, span } => Self::type_check_storage_load(
+                TypeCheckArguments {
+                    checkee: field_names,
+                    namespace,
+                    crate_namespace,
+                    self_type,
+                    build_config,
+                    dead_code_graph,
+                    opts,
+                    return_type_annotation: insert_type(TypeInfo::Unknown),
+                    mode: Default::default(),
+                    help_text: Default::default(),
+                },
+                span,
+            ),
         };
         let mut typed_expression = match res.value {
             Some(r) => r,
@@ -400,6 +426,8 @@ impl TypedExpression {
             Literal::U16(_) => TypeInfo::UnsignedInteger(IntegerBits::Sixteen),
             Literal::U32(_) => TypeInfo::UnsignedInteger(IntegerBits::ThirtyTwo),
             Literal::U64(_) => TypeInfo::UnsignedInteger(IntegerBits::SixtyFour),
+            Literal::U128(_) => TypeInfo::UnsignedInteger(IntegerBits::OneTwentyEight),
+            Literal::U256(_) => TypeInfo::UnsignedInteger(IntegerBits::TwoFiftySix),
             Literal::Boolean(_) => TypeInfo::Boolean,
             Literal::Byte(_) => TypeInfo::Byte,
             Literal::B256(_) => TypeInfo::B256,
@@ -418,7 +446,9 @@ impl TypedExpression {
         name: Ident,
         span: Span,
         namespace: crate::semantic_analysis::NamespaceRef,
+        opts: TCOpts,
     ) -> CompileResult<TypedExpression> {
+        let warnings = vec![];
         let mut errors = vec![];
         let exp = match namespace.get_symbol(&name).value {
             Some(TypedDeclaration::VariableDeclaration(TypedVariableDeclaration {
@@ -439,6 +469,32 @@ impl TypedExpression {
                 expression: TypedExpressionVariant::VariableExpression { name: name.clone() },
                 span,
             },
+            // a named function used as a value, e.g. `apply(double, 21)`
+            Some(TypedDeclaration::FunctionDeclaration(function)) => {
+                if !function.type_parameters.is_empty() {
+                    errors.push(CompileError::Unimplemented(
+                        "Generic functions cannot be used as function pointers yet.",
+                        name.span().clone(),
+                    ));
+                    return err(warnings, errors);
+                }
+                // the function could be called from anywhere its value ends up, so only allow
+                // referencing impure functions from impure contexts
+                if opts.purity == Purity::Pure && function.purity == Purity::Impure {
+                    errors.push(CompileError::PureCalledImpure {
+                        span: name.span().clone(),
+                    });
+                }
+                TypedExpression {
+                    return_type: function.function_type(),
+                    is_constant: IsConstant::No,
+                    expression: TypedExpressionVariant::FunctionReference {
+                        name: CallPath::from(name.clone()),
+                        function: Box::new(function),
+                    },
+                    span,
+                }
+            }
             Some(a) => {
                 errors.push(CompileError::NotAVariable {
                     name: name.span().as_str().to_string(),
@@ -455,13 +511,13 @@ impl TypedExpression {
                 error_recovery_expr(name.span().clone())
             }
         };
-        ok(exp, vec![], errors)
+        ok(exp, warnings, errors)
     }
 
     #[allow(clippy::type_complexity)]
     fn type_check_function_application(
         arguments: TypeCheckArguments<'_, (CallPath, Vec<Expression>, Vec<(TypeInfo, Span)>)>,
-        _span: Span,
+        span: Span,
     ) -> CompileResult<TypedExpression> {
         let TypeCheckArguments {
             checkee: (name, arguments, type_arguments),
@@ -481,8 +537,8 @@ impl TypedExpression {
             warnings,
             errors
         );
-        let typed_function_decl =
-            if let TypedDeclaration::FunctionDeclaration(decl) = function_declaration {
+        let typed_function_decl = match function_declaration {
+            TypedDeclaration::FunctionDeclaration(decl) => {
                 // if this is a generic function, monomorphize its internal types and insert the resulting
                 // declaration into the namespace. Then, use that instead.
                 if decl.type_parameters.is_empty() {
@@ -495,14 +551,31 @@ impl TypedExpression {
                         errors
                     )
                 }
-            } else {
+            }
+            // calling a variable that holds a function value, e.g. `f(x)` where `f: fn(u64) -> u64`
+            TypedDeclaration::VariableDeclaration(TypedVariableDeclaration {
+                ref body, ..
+            }) if matches!(look_up_type_id(body.return_type), TypeInfo::Function { .. }) => {
+                return instantiate_indirect_function_application(
+                    name,
+                    arguments,
+                    namespace,
+                    crate_namespace,
+                    self_type,
+                    build_config,
+                    dead_code_graph,
+                    opts,
+                );
+            }
+            _ => {
                 errors.push(CompileError::NotAFunction {
                     name: name.span().as_str().to_string(),
                     span: name.span(),
                     what_it_is: function_declaration.friendly_name(),
                 });
                 return err(warnings, errors);
-            };
+            }
+        };
         instantiate_function_application(
             typed_function_decl,
             name,
@@ -513,6 +586,7 @@ impl TypedExpression {
             build_config,
             dead_code_graph,
             opts,
+            span,
         )
     }
 
@@ -1054,7 +1128,7 @@ impl TypedExpression {
             let field_type_id = field_type_ids_opt
                 .as_ref()
                 .map(|field_type_ids| field_type_ids[i])
-                .unwrap_or_default();
+                .unwrap_or_else(|| insert_type(TypeInfo::Unknown));
             let field_span = field.span();
             let typed_field = check!(
                 TypedExpression::type_check(TypeCheckArguments {
@@ -1234,6 +1308,7 @@ impl TypedExpression {
                             build_config,
                             dead_code_graph,
                             opts,
+                            span,
                         ),
                         return err(warnings, errors),
                         warnings,
@@ -1374,6 +1449,7 @@ impl TypedExpression {
         functions_buf.append(&mut type_checked_fn_buf);
         namespace.insert_trait_implementation(
             abi_name.clone(),
+            vec![],
             look_up_type_id(return_type),
             functions_buf,
         );
@@ -1489,6 +1565,7 @@ impl TypedExpression {
             checkee: (prefix, index),
             namespace,
             crate_namespace,
+            return_type_annotation,
             self_type,
             build_config,
             dead_code_graph,
@@ -1571,6 +1648,7 @@ impl TypedExpression {
                 span,
                 namespace,
                 crate_namespace,
+                return_type_annotation,
                 self_type,
                 build_config,
                 dead_code_graph,
@@ -1829,6 +1907,124 @@ impl TypedExpression {
         }
     }
 
+    /// Type checks a match expression by checking that its branches are exhaustive and then
+    /// desugaring it into an if expression.
+    fn type_check_match_expression(
+        arguments: TypeCheckArguments<'_, (Expression, Vec<MatchBranch>)>,
+        span: Span,
+    ) -> CompileResult<TypedExpression> {
+        let mut warnings = vec![];
+        let mut errors = vec![];
+        let TypeCheckArguments {
+            checkee: (primary_expression, branches),
+            namespace,
+            crate_namespace,
+            return_type_annotation,
+            help_text,
+            self_type,
+            build_config,
+            dead_code_graph,
+            opts,
+            ..
+        } = arguments;
+
+        // The primary expression is type checked once, and bound to a hidden variable which the
+        // desugared branches refer to, so that it is also only evaluated once.
+        let typed_primary = check!(
+            TypedExpression::type_check(TypeCheckArguments {
+                checkee: primary_expression.clone(),
+                namespace,
+                crate_namespace,
+                return_type_annotation: insert_type(TypeInfo::Unknown),
+                help_text: Default::default(),
+                self_type,
+                build_config,
+                dead_code_graph,
+                mode: Mode::NonAbi,
+                opts,
+            }),
+            return err(warnings, errors),
+            warnings,
+            errors
+        );
+        let primary_type = typed_primary.return_type;
+        let num_errors = errors.len();
+        check!(
+            check_match_exhaustiveness(primary_type, &branches, &span),
+            (),
+            warnings,
+            errors
+        );
+        // A non-exhaustive match desugars to an if expression without a final else, which
+        // would only produce a confusing type error.
+        if errors.len() > num_errors {
+            return ok(error_recovery_expr(span), warnings, errors);
+        }
+
+        let primary_span = primary_expression.span();
+        let primary_name =
+            crate::parse_tree::ident::random_name(primary_span.clone(), Some(build_config));
+        let primary_declaration = TypedDeclaration::VariableDeclaration(TypedVariableDeclaration {
+            name: primary_name.clone(),
+            body: typed_primary,
+            is_mutable: VariableMutability::Immutable,
+            const_decl_origin: false,
+            type_ascription: primary_type,
+        });
+        let match_namespace = create_new_scope(namespace);
+        match_namespace.insert(primary_name.clone(), primary_declaration.clone());
+
+        let desugared = check!(
+            desugar_match_expression(
+                Expression::VariableExpression {
+                    name: primary_name,
+                    span: primary_span.clone(),
+                },
+                branches,
+                span.clone()
+            ),
+            return err(warnings, errors),
+            warnings,
+            errors
+        );
+        let typed_expression = check!(
+            TypedExpression::type_check(TypeCheckArguments {
+                checkee: desugared,
+                namespace: match_namespace,
+                crate_namespace,
+                return_type_annotation,
+                help_text,
+                self_type,
+                build_config,
+                dead_code_graph,
+                mode: Mode::NonAbi,
+                opts,
+            }),
+            return err(warnings, errors),
+            warnings,
+            errors
+        );
+        let exp = TypedExpression {
+            return_type: typed_expression.return_type,
+            expression: TypedExpressionVariant::CodeBlock(TypedCodeBlock {
+                contents: vec![
+                    TypedAstNode {
+                        content: TypedAstNodeContent::Declaration(primary_declaration),
+                        span: primary_span,
+                    },
+                    TypedAstNode {
+                        span: typed_expression.span.clone(),
+                        content: TypedAstNodeContent::ImplicitReturnExpression(typed_expression),
+                    },
+                ],
+                whole_block_span: span.clone(),
+            }),
+            is_constant: IsConstant::No,
+            span,
+        };
+        ok(exp, warnings, errors)
+    }
+
     fn type_check_size_of_val(
         arguments: TypeCheckArguments<'_, Expression>,
         span: Span,
@@ -1885,6 +2081,36 @@ impl TypedExpression {
         ok(exp, warnings, errors)
     }
 
+    fn type_check_storage_load(
+        arguments: TypeCheckArguments<'_, Vec<Ident>>,
+        span: Span,
+    ) -> CompileResult<TypedExpression> {
+        let mut warnings = vec![];
+        let mut errors = vec![];
+        let TypeCheckArguments {
+            checkee: field_names,
+            namespace,
+            opts,
+            ..
+        } = arguments;
+        if opts.purity != Purity::Impure {
+            errors.push(CompileError::StorageAccessInPureContext { span: span.clone() });
+        }
+        let access = check!(
+            namespace.apply_storage_load(&field_names, &span),
+            return err(warnings, errors),
+            warnings,
+            errors
+        );
+        let exp = TypedExpression {
+            return_type: access.r#type,
+            expression: TypedExpressionVariant::StorageAccess(access),
+            is_constant: IsConstant::No,
+            span,
+        };
+        ok(exp, warnings, errors)
+    }
+
     fn resolve_numeric_literal(
         lit: Literal,
         span: Span,
@@ -1942,6 +2168,13 @@ impl TypedExpression {
                         }),
                         new_type,
                     ),
+                    // every `u64` fits, so these can't fail
+                    IntegerBits::OneTwentyEight => (Ok(Literal::U128(num as u128)), new_type),
+                    IntegerBits::TwoFiftySix => {
+                        let mut bytes = [0u8; 32];
+                        bytes[24..].copy_from_slice(&num.to_be_bytes());
+                        (Ok(Literal::U256(bytes)), new_type)
+                    }
                 },
                 TypeInfo::Numeric => (
                     num.to_string().parse().map(Literal::U64).map_err(|e| {
@@ -2003,7 +2236,10 @@ mod tests {
             print_intermediate_asm: false,
             print_finalized_asm: false,
             print_ir: false,
+            ir_pipeline: "O1".to_owned(),
+            time_ir_passes: false,
             generated_names: Arc::new(Mutex::new(vec![])),
+            source_overrides: Default::default(),
         };
         let mut dead_code_graph: ControlFlowGraph = Default::default();
 
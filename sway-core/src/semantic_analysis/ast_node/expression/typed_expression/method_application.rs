@@ -16,6 +16,7 @@ pub(crate) fn type_check_method_application(
     span: Span,
     namespace: NamespaceRef,
     crate_namespace: NamespaceRef,
+    return_type_annotation: TypeId,
     self_type: TypeId,
     build_config: &BuildConfig,
     dead_code_graph: &mut ControlFlowGraph,
@@ -23,15 +24,24 @@ pub(crate) fn type_check_method_application(
 ) -> CompileResult<TypedExpression> {
     let mut warnings = vec![];
     let mut errors = vec![];
-    let mut args_buf = VecDeque::new();
+    let mut args_buf: VecDeque<TypedExpression> = VecDeque::new();
     let mut contract_call_params_map = HashMap::new();
+    let wide_integer_op = wide_integer_op_for(&method_name, arguments.len());
     for arg in arguments {
+        // The second operand of an operator on wide integers takes the type of the first, so that
+        // e.g. the literal in `x + 1` is typed as a `u128` when `x` is one.
+        let arg_type_annotation = match args_buf.front() {
+            Some(lhs) if wide_integer_op.is_some() && is_wide_integer(lhs.return_type) => {
+                lhs.return_type
+            }
+            _ => insert_type(TypeInfo::Unknown),
+        };
         args_buf.push_back(check!(
             TypedExpression::type_check(TypeCheckArguments {
                 checkee: arg,
                 namespace,
                 crate_namespace,
-                return_type_annotation: insert_type(TypeInfo::Unknown),
+                return_type_annotation: arg_type_annotation,
                 help_text: Default::default(),
                 self_type,
                 build_config,
@@ -45,6 +55,12 @@ pub(crate) fn type_check_method_application(
         ));
     }
 
+    if let Some(op) = wide_integer_op {
+        if is_wide_integer(args_buf[0].return_type) {
+            return type_check_wide_integer_op(op, args_buf, span, warnings, errors);
+        }
+    }
+
     let method = match method_name {
         MethodName::FromType {
             ref type_name,
@@ -76,6 +92,7 @@ pub(crate) fn type_check_method_application(
                     from_module,
                     self_type,
                     &args_buf,
+                    return_type_annotation,
                 ),
                 return err(warnings, errors),
                 warnings,
@@ -88,7 +105,15 @@ pub(crate) fn type_check_method_application(
                 .map(|x| x.return_type)
                 .unwrap_or_else(|| insert_type(TypeInfo::Unknown));
             check!(
-                namespace.find_method_for_type(ty, method_name, &[], None, self_type, &args_buf),
+                namespace.find_method_for_type(
+                    ty,
+                    method_name,
+                    &[],
+                    None,
+                    self_type,
+                    &args_buf,
+                    return_type_annotation,
+                ),
                 return err(warnings, errors),
                 warnings,
                 errors
@@ -227,6 +252,7 @@ pub(crate) fn type_check_method_application(
                         suffix: method_name,
                         is_absolute: false,
                     },
+                    decl_name: method.name.clone(),
                     contract_call_params: contract_call_params_map,
                     arguments: args_and_names,
                     function_body: method.body.clone(),
@@ -304,6 +330,7 @@ pub(crate) fn type_check_method_application(
             TypedExpression {
                 expression: TypedExpressionVariant::FunctionApplication {
                     name: call_path.clone(),
+                    decl_name: method.name.clone(),
                     contract_call_params: contract_call_params_map,
                     arguments: args_and_names,
                     function_body: method.body.clone(),
@@ -423,3 +450,68 @@ fn re_parse_expression(
     );
     ok(contract_address, warnings, errors)
 }
+
+/// Operators are desugared into calls of `core::ops` methods, which can only be implemented for
+/// types that fit in a register. This finds the operators which may be applied to wide integers
+/// instead.
+fn wide_integer_op_for(method_name: &MethodName, num_args: usize) -> Option<WideIntegerOp> {
+    match method_name {
+        MethodName::FromType {
+            call_path,
+            type_name: None,
+        } if call_path.is_absolute
+            && num_args == 2
+            && call_path
+                .prefixes
+                .iter()
+                .map(|ident| ident.as_str())
+                .eq(["core", "ops"]) =>
+        {
+            WideIntegerOp::from_method_name(call_path.suffix.as_str())
+        }
+        _ => None,
+    }
+}
+
+fn is_wide_integer(type_id: TypeId) -> bool {
+    matches!(look_up_type_id(type_id), TypeInfo::UnsignedInteger(bits) if bits.is_wide())
+}
+
+fn type_check_wide_integer_op(
+    op: WideIntegerOp,
+    mut args_buf: VecDeque<TypedExpression>,
+    span: Span,
+    warnings: Vec<CompileWarning>,
+    mut errors: Vec<CompileError>,
+) -> CompileResult<TypedExpression> {
+    let rhs = args_buf.pop_back().unwrap();
+    let lhs = args_buf.pop_back().unwrap();
+    // The operands have been unified already, by the annotation on the second one, so a mismatch
+    // has usually been reported.
+    if !is_wide_integer(rhs.return_type) {
+        if errors.is_empty() {
+            errors.push(CompileError::ArgumentParameterTypeMismatch {
+                span: rhs.span.clone(),
+                provided: rhs.return_type.friendly_type_str(),
+                should_be: lhs.return_type.friendly_type_str(),
+            });
+        }
+        return err(warnings, errors);
+    }
+    let return_type = if op.is_comparison() {
+        insert_type(TypeInfo::Boolean)
+    } else {
+        lhs.return_type
+    };
+    let exp = TypedExpression {
+        expression: TypedExpressionVariant::WideIntegerOperator {
+            op,
+            lhs: Box::new(lhs),
+            rhs: Box::new(rhs),
+        },
+        return_type,
+        is_constant: IsConstant::No,
+        span,
+    };
+    ok(exp, warnings, errors)
+}
@@ -14,6 +14,8 @@ pub(crate) enum TypedExpressionVariant {
     Literal(Literal),
     FunctionApplication {
         name: CallPath,
+        /// The name of the called function at its declaration.
+        decl_name: Ident,
         contract_call_params: HashMap<String, TypedExpression>,
         arguments: Vec<(Ident, TypedExpression)>,
         function_body: TypedCodeBlock,
@@ -21,11 +23,30 @@ pub(crate) enum TypedExpressionVariant {
         /// there is no selector.
         selector: Option<ContractCallMetadata>,
     },
+    /// A named function used as a value rather than called directly, e.g. `foo` in `apply(foo)`.
+    FunctionReference {
+        name: CallPath,
+        function: Box<TypedFunctionDeclaration>,
+    },
+    /// A call of a function value, e.g. `f(x)` where `f` has the type `fn(u64) -> u64`. Which
+    /// function is called is only known at runtime.
+    IndirectFunctionApplication {
+        function: Box<TypedExpression>,
+        arguments: Vec<TypedExpression>,
+    },
     LazyOperator {
         op: LazyOp,
         lhs: Box<TypedExpression>,
         rhs: Box<TypedExpression>,
     },
+    /// An operator applied to two `u128` or two `u256` values. These don't fit in a register so
+    /// there is no `core::ops` implementation to call, and the compiler generates the multi-word
+    /// arithmetic inline instead.
+    WideIntegerOperator {
+        op: WideIntegerOp,
+        lhs: Box<TypedExpression>,
+        rhs: Box<TypedExpression>,
+    },
     VariableExpression {
         name: Ident,
     },
@@ -95,6 +116,66 @@ pub(crate) enum TypedExpressionVariant {
     SizeOf {
         variant: SizeOfVariant,
     },
+    StorageAccess(TypedStorageAccess),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum WideIntegerOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Eq,
+    Neq,
+    Lt,
+    Gt,
+    Le,
+    Ge,
+}
+
+impl WideIntegerOp {
+    /// Maps the name of a `core::ops` method, as used when desugaring an operator, to the
+    /// operation on wide integers, if there is one.
+    pub(crate) fn from_method_name(name: &str) -> Option<Self> {
+        use WideIntegerOp::*;
+        Some(match name {
+            "add" => Add,
+            "subtract" => Sub,
+            "multiply" => Mul,
+            "divide" => Div,
+            "modulo" => Mod,
+            "eq" => Eq,
+            "neq" => Neq,
+            "lt" => Lt,
+            "gt" => Gt,
+            "le" => Le,
+            "ge" => Ge,
+            _ => return None,
+        })
+    }
+
+    pub(crate) fn is_comparison(&self) -> bool {
+        use WideIntegerOp::*;
+        matches!(self, Eq | Neq | Lt | Gt | Le | Ge)
+    }
+
+    fn as_str(&self) -> &'static str {
+        use WideIntegerOp::*;
+        match self {
+            Add => "+",
+            Sub => "-",
+            Mul => "*",
+            Div => "/",
+            Mod => "%",
+            Eq => "==",
+            Neq => "!=",
+            Lt => "<",
+            Gt => ">",
+            Le => "<=",
+            Ge => ">=",
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -127,6 +208,14 @@ impl TypedExpressionVariant {
                     Literal::U16(content) => content.to_string(),
                     Literal::U32(content) => content.to_string(),
                     Literal::U64(content) => content.to_string(),
+                    Literal::U128(content) => content.to_string(),
+                    Literal::U256(content) => format!(
+                        "0x{}",
+                        content
+                            .iter()
+                            .map(|x| format!("{:02x}", x))
+                            .collect::<String>()
+                    ),
                     Literal::Numeric(content) => content.to_string(),
                     Literal::String(content) => content.as_str().to_string(),
                     Literal::Boolean(content) => content.to_string(),
@@ -141,10 +230,19 @@ impl TypedExpressionVariant {
             TypedExpressionVariant::FunctionApplication { name, .. } => {
                 format!("\"{}\" fn entry", name.suffix.as_str())
             }
+            TypedExpressionVariant::FunctionReference { name, .. } => {
+                format!("\"{}\" fn reference", name.suffix.as_str())
+            }
+            TypedExpressionVariant::IndirectFunctionApplication { function, .. } => {
+                format!("{} indirect fn entry", function.pretty_print())
+            }
             TypedExpressionVariant::LazyOperator { op, .. } => match op {
                 LazyOp::And => "&&".into(),
                 LazyOp::Or => "||".into(),
             },
+            TypedExpressionVariant::WideIntegerOperator { op, .. } => {
+                format!("wide {}", op.as_str())
+            }
             TypedExpressionVariant::Tuple { fields } => {
                 let fields = fields
                     .iter()
@@ -220,6 +318,9 @@ impl TypedExpressionVariant {
                     format!("size_of({:?})", type_name.friendly_type_str())
                 }
             },
+            TypedExpressionVariant::StorageAccess(access) => {
+                format!("storage.{} access", access.field_name.as_str())
+            }
         }
     }
     /// Makes a fresh copy of all type ids in this expression. Used when monomorphizing.
@@ -237,7 +338,18 @@ impl TypedExpressionVariant {
                     .for_each(|(_ident, expr)| expr.copy_types(type_mapping));
                 function_body.copy_types(type_mapping);
             }
-            LazyOperator { lhs, rhs, .. } => {
+            // generic functions cannot be referenced, so there is nothing to copy
+            FunctionReference { .. } => (),
+            IndirectFunctionApplication {
+                function,
+                arguments,
+            } => {
+                function.copy_types(type_mapping);
+                arguments
+                    .iter_mut()
+                    .for_each(|expr| expr.copy_types(type_mapping));
+            }
+            LazyOperator { lhs, rhs, .. } | WideIntegerOperator { lhs, rhs, .. } => {
                 (*lhs).copy_types(type_mapping);
                 (*rhs).copy_types(type_mapping);
             }
@@ -338,6 +450,8 @@ impl TypedExpressionVariant {
                 SizeOfVariant::Type(_) => (),
                 SizeOfVariant::Val(exp) => exp.copy_types(type_mapping),
             },
+            // storage is never generic
+            StorageAccess(..) => (),
         }
     }
 }
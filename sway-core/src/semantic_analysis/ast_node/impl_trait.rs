@@ -36,12 +36,17 @@ pub(crate) fn implementation_of_trait(
     let type_implementing_for = namespace.resolve_type_without_self(&type_implementing_for);
     let type_implementing_for = look_up_type_id(type_implementing_for);
     let type_implementing_for_id = insert_type(type_implementing_for.clone());
-    if !type_arguments.is_empty() {
-        errors.push(CompileError::Internal(
-            "Where clauses are not supported yet.",
-            type_arguments[0].clone().name_ident.span().clone(),
-        ));
-    }
+    let type_arguments = type_arguments
+        .into_iter()
+        .map(|(type_argument, span)| {
+            namespace
+                .resolve_type_with_self(type_argument, type_implementing_for_id)
+                .unwrap_or_else(|_| {
+                    errors.push(CompileError::UnknownType { span });
+                    insert_type(TypeInfo::ErrorRecovery)
+                })
+        })
+        .collect::<Vec<_>>();
     match namespace
         .get_call_path(&trait_name)
         .ok(&mut warnings, &mut errors)
@@ -52,16 +57,39 @@ pub(crate) fn implementation_of_trait(
                     given: type_arguments.len(),
                     expected: tr.type_parameters.len(),
                     span: type_arguments_span,
-                })
+                });
+                return err(warnings, errors);
             }
+            // monomorphize the interface surface with the type arguments of this impl, so that
+            // e.g. `fn from(t: T) -> Self` becomes `fn from(t: u64) -> Self` for `From<u64>`
+            let type_mapping = tr
+                .type_parameters
+                .iter()
+                .cloned()
+                .zip(type_arguments.iter().cloned())
+                .collect::<Vec<_>>();
+            let interface_surface = tr
+                .interface_surface
+                .iter()
+                .map(|trait_fn| {
+                    let mut trait_fn = trait_fn.clone();
+                    trait_fn.copy_types(&type_mapping);
+                    trait_fn
+                })
+                .collect::<Vec<_>>();
+            let trait_type_arguments = type_arguments
+                .iter()
+                .map(|type_argument| look_up_type_id(*type_argument))
+                .collect::<Vec<_>>();
 
             let functions_buf = check!(
                 type_check_trait_implementation(
-                    &tr.interface_surface,
+                    &interface_surface,
                     &functions,
                     &tr.methods,
                     &tr.name,
-                    &tr.type_parameters,
+                    &type_mapping,
+                    &trait_type_arguments,
                     namespace,
                     crate_namespace,
                     type_implementing_for_id,
@@ -82,6 +110,7 @@ pub(crate) fn implementation_of_trait(
 
             namespace.insert_trait_implementation(
                 trait_name.clone(),
+                trait_type_arguments,
                 match resolve_type(type_implementing_for_id, &type_implementing_for_span) {
                     Ok(o) => o,
                     Err(e) => {
@@ -107,6 +136,13 @@ pub(crate) fn implementation_of_trait(
             // there are no type arguments here because we don't support generic types
             // in contract ABIs yet (or ever?) due to the complexity of communicating
             // the ABI layout in the descriptor file.
+            if !type_arguments.is_empty() {
+                errors.push(CompileError::IncorrectNumberOfTypeArguments {
+                    given: type_arguments.len(),
+                    expected: 0,
+                    span: type_arguments_span,
+                });
+            }
             if type_implementing_for != TypeInfo::Contract {
                 errors.push(CompileError::ImplAbiForNonContract {
                     span: type_implementing_for_span.clone(),
@@ -122,6 +158,7 @@ pub(crate) fn implementation_of_trait(
                     &abi.name,
                     // ABIs don't have type parameters
                     &[],
+                    &[],
                     namespace,
                     crate_namespace,
                     type_implementing_for_id,
@@ -142,6 +179,7 @@ pub(crate) fn implementation_of_trait(
 
             namespace.insert_trait_implementation(
                 trait_name.clone(),
+                vec![],
                 look_up_type_id(type_implementing_for_id),
                 functions_buf.clone(),
             );
@@ -184,7 +222,8 @@ fn type_check_trait_implementation(
     functions: &[FunctionDeclaration],
     methods: &[FunctionDeclaration],
     trait_name: &Ident,
-    type_arguments: &[TypeParameter],
+    type_mapping: &[(TypeParameter, TypeId)],
+    trait_type_arguments: &[TypeInfo],
     namespace: crate::semantic_analysis::NamespaceRef,
     crate_namespace: NamespaceRef,
     _self_type: TypeId,
@@ -229,7 +268,7 @@ fn type_check_trait_implementation(
             warnings,
             errors
         );
-        let mut fn_decl = fn_decl.replace_self_types(self_type_id);
+        let fn_decl = fn_decl.replace_self_types(self_type_id);
         // remove this function from the "checklist"
         let ix_of_thing_to_remove = match function_checklist
             .iter()
@@ -247,10 +286,6 @@ fn type_check_trait_implementation(
         };
         function_checklist.remove(ix_of_thing_to_remove);
 
-        let type_arguments = &(*type_arguments);
-        // add generic params from impl trait into function type params
-        fn_decl.type_parameters.append(&mut type_arguments.to_vec());
-
         // ensure this fn decl's parameters and signature lines up with the one
         // in the trait
         if let Some(mut l_e) = interface_surface.iter().find_map(
@@ -345,21 +380,26 @@ fn type_check_trait_implementation(
     }
 
     // this name space is temporary! It is used only so that the below methods
-    // can reference functions from the interface
+    // can reference functions from the interface. Implementations of the same trait with
+    // other type arguments are left out, so that calls in the methods resolve to this one.
     let local_namespace: NamespaceRef = create_new_scope(namespace);
-    local_namespace.insert_trait_implementation(
-        CallPath {
-            prefixes: vec![],
-            suffix: trait_name.clone(),
-            is_absolute: false,
-        },
+    let resolved_type_implementing_for =
         match resolve_type(type_implementing_for, type_implementing_for_span) {
             Ok(o) => o,
             Err(e) => {
                 errors.push(e.into());
                 return err(warnings, errors);
             }
+        };
+    local_namespace.remove_trait_implementations(trait_name, &resolved_type_implementing_for);
+    local_namespace.insert_trait_implementation(
+        CallPath {
+            prefixes: vec![],
+            suffix: trait_name.clone(),
+            is_absolute: false,
         },
+        trait_type_arguments.to_vec(),
+        resolved_type_implementing_for,
         functions_buf.clone(),
     );
     for method in methods {
@@ -367,10 +407,16 @@ fn type_check_trait_implementation(
         // it depends upon has been implemented
 
         // use a local namespace which has the above interface inserted
-        // into it as a trait implementation for this
+        // into it as a trait implementation for this. The trait's type parameters are checked
+        // as generics of the method, and then replaced by the type arguments of this impl.
+        let mut method = method.clone();
+        let num_own_type_parameters = method.type_parameters.len();
+        method
+            .type_parameters
+            .extend(type_mapping.iter().map(|(param, _)| param.clone()));
         let method = check!(
             TypedFunctionDeclaration::type_check(TypeCheckArguments {
-                checkee: method.clone(),
+                checkee: method,
                 namespace: local_namespace,
                 crate_namespace,
                 return_type_annotation: insert_type(TypeInfo::Unknown),
@@ -385,12 +431,19 @@ fn type_check_trait_implementation(
             warnings,
             errors
         );
-        let fn_decl = method.replace_self_types(self_type_id);
+        let mut fn_decl = method.replace_self_types(self_type_id);
+        fn_decl.copy_types(type_mapping);
+        fn_decl.type_parameters.truncate(num_own_type_parameters);
         functions_buf.push(fn_decl);
     }
 
     // check that the implementation checklist is complete
     if !function_checklist.is_empty() {
+        let missing_signatures = interface_surface
+            .iter()
+            .filter(|trait_fn| function_checklist.contains(&&trait_fn.name))
+            .map(trait_fn_signature)
+            .collect();
         errors.push(CompileError::MissingInterfaceSurfaceMethods {
             span: block_span.clone(),
             missing_functions: function_checklist
@@ -398,7 +451,32 @@ fn type_check_trait_implementation(
                 .map(|ident| ident.as_str().to_string())
                 .collect::<Vec<_>>()
                 .join("\n"),
+            missing_signatures,
         });
     }
     ok(functions_buf, warnings, errors)
 }
+
+/// The signature of a trait function as written in the trait, e.g. `fn foo(self, x: u64) -> bool`.
+fn trait_fn_signature(trait_fn: &TypedTraitFn) -> String {
+    let parameters = trait_fn
+        .parameters
+        .iter()
+        .map(|param| match param.name.as_str() {
+            "self" => "self".to_string(),
+            name => format!("{}: {}", name, param.type_span.as_str()),
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    match look_up_type_id(trait_fn.return_type) {
+        TypeInfo::Tuple(fields) if fields.is_empty() => {
+            format!("fn {}({})", trait_fn.name.as_str(), parameters)
+        }
+        _ => format!(
+            "fn {}({}) -> {}",
+            trait_fn.name.as_str(),
+            parameters,
+            trait_fn.return_type_span.as_str()
+        ),
+    }
+}
@@ -12,20 +12,21 @@ use crate::{
 
 use sway_types::span::{join_spans, Span};
 
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 
 pub(crate) use crate::semantic_analysis::ast_node::declaration::ReassignmentLhs;
 
 pub mod declaration;
 use declaration::TypedTraitFn;
 pub(crate) use declaration::{
-    OwnedTypedEnumVariant, OwnedTypedStructField, TypedReassignment, TypedTraitDeclaration,
-    TypedVariableDeclaration, VariableMutability,
+    OwnedTypedEnumVariant, OwnedTypedStructField, TypedReassignment, TypedStorageAccess,
+    TypedStorageField, TypedStorageReassignment, TypedTraitDeclaration, TypedVariableDeclaration,
+    VariableMutability,
 };
 pub use declaration::{
-    TypedAbiDeclaration, TypedConstantDeclaration, TypedDeclaration, TypedEnumDeclaration,
-    TypedEnumVariant, TypedFunctionDeclaration, TypedFunctionParameter, TypedStructDeclaration,
-    TypedStructField,
+    StorageSlot, TypedAbiDeclaration, TypedConstantDeclaration, TypedDeclaration,
+    TypedEnumDeclaration, TypedEnumVariant, TypedFunctionDeclaration, TypedFunctionParameter,
+    TypedStorageDeclaration, TypedStructDeclaration, TypedStructField,
 };
 
 pub mod impl_trait;
@@ -59,8 +60,33 @@ pub(crate) enum TypedAstNodeContent {
     Expression(TypedExpression),
     ImplicitReturnExpression(TypedExpression),
     WhileLoop(TypedWhileLoop),
+    Break,
+    Continue,
     // a no-op node used for something that just issues a side effect, like an import statement.
     SideEffect,
+    // an included file, which is otherwise a side effect, kept so that tooling can inspect it.
+    IncludeStatement(TypedIncludeStatement),
+    // an imported item, which is otherwise a side effect, kept so that tooling can inspect it.
+    UseStatement(TypedUseStatement),
+}
+
+/// An item imported by name with a `use` statement.
+#[derive(Clone, Debug)]
+pub(crate) struct TypedUseStatement {
+    /// The name of the item within the `use` statement.
+    pub(crate) item: Ident,
+    /// The imported declaration, if the import succeeded.
+    pub(crate) decl: Option<TypedDeclaration>,
+}
+
+/// The contents of a file included with the `dep` keyword.
+#[derive(Clone, Debug)]
+pub(crate) struct TypedIncludeStatement {
+    /// The path of the included file, relative to the parent of the manifest directory.
+    pub(crate) path: Arc<PathBuf>,
+    /// The namespace of the included file's own declarations.
+    pub(crate) namespace: NamespaceRef,
+    pub(crate) nodes: Vec<TypedAstNode>,
 }
 
 #[derive(Clone)]
@@ -80,7 +106,9 @@ impl std::fmt::Debug for TypedAstNode {
             Expression(exp) => exp.pretty_print(),
             ImplicitReturnExpression(exp) => format!("return {}", exp.pretty_print()),
             WhileLoop(w_loop) => w_loop.pretty_print(),
-            SideEffect => "".into(),
+            Break => "break".into(),
+            Continue => "continue".into(),
+            SideEffect | IncludeStatement(_) | UseStatement(_) => "".into(),
         };
         f.write_str(&text)
     }
@@ -104,7 +132,11 @@ impl TypedAstNode {
                 condition.copy_types(type_mapping);
                 body.copy_types(type_mapping);
             }
-            TypedAstNodeContent::SideEffect => (),
+            TypedAstNodeContent::Break
+            | TypedAstNodeContent::Continue
+            | TypedAstNodeContent::SideEffect
+            | TypedAstNodeContent::IncludeStatement(_)
+            | TypedAstNodeContent::UseStatement(_) => (),
         }
     }
     fn type_info(&self) -> TypeInfo {
@@ -118,7 +150,8 @@ impl TypedAstNode {
             ImplicitReturnExpression(TypedExpression { return_type, .. }) => {
                 crate::type_engine::look_up_type_id(*return_type)
             }
-            WhileLoop(_) | SideEffect => TypeInfo::Tuple(Vec::new()),
+            WhileLoop(_) | Break | Continue | SideEffect | IncludeStatement(_)
+            | UseStatement(_) => TypeInfo::Tuple(Vec::new()),
         }
     }
     pub(crate) fn type_check(
@@ -174,29 +207,46 @@ impl TypedAstNode {
                     } else {
                         None
                     };
-                    let mut res = match a.import_type {
-                        ImportType::Star => namespace.star_import(from_module, a.call_path),
+                    match a.import_type {
+                        ImportType::Star => {
+                            let mut res = namespace.star_import(from_module, a.call_path);
+                            warnings.append(&mut res.warnings);
+                            errors.append(&mut res.errors);
+                            TypedAstNodeContent::SideEffect
+                        }
                         ImportType::SelfImport => {
-                            namespace.self_import(from_module, a.call_path, a.alias)
+                            let mut res = namespace.self_import(from_module, a.call_path, a.alias);
+                            warnings.append(&mut res.warnings);
+                            errors.append(&mut res.errors);
+                            TypedAstNodeContent::SideEffect
                         }
                         ImportType::Item(s) => {
-                            namespace.item_import(from_module, a.call_path, &s, a.alias)
+                            let name = a.alias.clone().unwrap_or_else(|| s.clone());
+                            let mut res =
+                                namespace.item_import(from_module, a.call_path, &s, a.alias);
+                            warnings.append(&mut res.warnings);
+                            errors.append(&mut res.errors);
+                            let decl = match res.value {
+                                Some(()) => namespace.get_symbol(&name).value,
+                                None => None,
+                            };
+                            TypedAstNodeContent::UseStatement(TypedUseStatement { item: s, decl })
                         }
-                    };
-                    warnings.append(&mut res.warnings);
-                    errors.append(&mut res.errors);
-                    TypedAstNodeContent::SideEffect
+                    }
                 }
                 AstNodeContent::IncludeStatement(ref a) => {
                     // Import the file, parse it, put it in the namespace under the module name (alias or
                     // last part of the import by default)
-                    let _ = check!(
+                    let include = check!(
                         import_new_file(a, namespace, build_config, dead_code_graph),
                         return err(warnings, errors),
                         warnings,
                         errors
                     );
-                    TypedAstNodeContent::SideEffect
+                    match include {
+                        Some(include) => TypedAstNodeContent::IncludeStatement(include),
+                        None => TypedAstNodeContent::SideEffect,
+                    }
                 }
                 AstNodeContent::Declaration(a) => {
                     TypedAstNodeContent::Declaration(match a {
@@ -294,6 +344,21 @@ impl TypedAstNode {
                             decl
                         }
                         Declaration::FunctionDeclaration(fn_decl) => {
+                            // declare the signature first so the body can call the function
+                            // recursively, its errors are reported by the full type check
+                            if fn_decl.type_parameters.is_empty() {
+                                if let Some(signature) =
+                                    TypedFunctionDeclaration::type_check_signature(
+                                        &fn_decl, namespace, self_type,
+                                    )
+                                    .value
+                                {
+                                    namespace.insert(
+                                        signature.name.clone(),
+                                        TypedDeclaration::FunctionDeclaration(signature),
+                                    );
+                                }
+                            }
                             let decl = check!(
                                 TypedFunctionDeclaration::type_check(TypeCheckArguments {
                                     checkee: fn_decl.clone(),
@@ -325,9 +390,16 @@ impl TypedAstNode {
                             supertraits,
                             visibility,
                         }) => {
+                            // the trait's type parameters are in scope for both its interface
+                            // surface and its methods
+                            let trait_namespace = create_new_scope(namespace);
+                            type_parameters.iter().for_each(|param| {
+                                trait_namespace.insert(param.name_ident.clone(), param.into());
+                            });
+
                             // type check the interface surface
                             let interface_surface = check!(
-                                type_check_interface_surface(interface_surface, namespace),
+                                type_check_interface_surface(interface_surface, trait_namespace),
                                 return err(warnings, errors),
                                 warnings,
                                 errors
@@ -353,7 +425,6 @@ impl TypedAstNode {
                                 }
                             }
 
-                            let trait_namespace = create_new_scope(namespace);
                             // insert placeholder functions representing the interface surface
                             // to allow methods to use those functions
                             trait_namespace.insert_trait_implementation(
@@ -362,6 +433,7 @@ impl TypedAstNode {
                                     suffix: name.clone(),
                                     is_absolute: false,
                                 },
+                                vec![],
                                 TypeInfo::SelfType,
                                 interface_surface
                                     .iter()
@@ -497,6 +569,7 @@ impl TypedAstNode {
                             };
                             namespace.insert_trait_implementation(
                                 trait_name.clone(),
+                                vec![],
                                 look_up_type_id(implementing_for_type_id),
                                 functions_buf.clone(),
                             );
@@ -602,12 +675,35 @@ impl TypedAstNode {
                             namespace.insert(name, decl.clone());
                             decl
                         }
-                        Declaration::StorageDeclaration(StorageDeclaration { span, .. }) => {
-                            errors.push(CompileError::Unimplemented(
-                                "Storage declarations are not supported yet. Coming soon!",
-                                span,
-                            ));
-                            return err(warnings, errors);
+                        Declaration::StorageDeclaration(StorageDeclaration { fields, span }) => {
+                            let decl = check!(
+                                type_check_storage_declaration(
+                                    TypeCheckArguments {
+                                        checkee: fields,
+                                        namespace,
+                                        crate_namespace,
+                                        self_type,
+                                        build_config,
+                                        dead_code_graph,
+                                        // this is unused by `type_check_storage_declaration`
+                                        return_type_annotation: insert_type(TypeInfo::Unknown),
+                                        help_text: Default::default(),
+                                        mode: Mode::NonAbi,
+                                        opts,
+                                    },
+                                    span,
+                                ),
+                                return err(warnings, errors),
+                                warnings,
+                                errors
+                            );
+                            check!(
+                                namespace.set_storage_declaration(decl.clone()),
+                                return err(warnings, errors),
+                                warnings,
+                                errors
+                            );
+                            TypedDeclaration::StorageDeclaration(decl)
                         }
                     })
                 }
@@ -707,7 +803,10 @@ impl TypedAstNode {
                             build_config,
                             dead_code_graph,
                             mode: Mode::NonAbi,
-                            opts,
+                            opts: TCOpts {
+                                in_loop: true,
+                                ..opts
+                            },
                         }),
                         (
                             TypedCodeBlock {
@@ -724,6 +823,117 @@ impl TypedAstNode {
                         body: typed_body,
                     })
                 }
+                AstNodeContent::ForLoop(for_loop) => {
+                    // The loop is desugared into a while loop, which needs the length of the array
+                    // being iterated over, if any. The array is type checked once, here, and bound
+                    // to the loop's hidden iterable variable in a scope around the desugared loop.
+                    let loop_namespace = create_new_scope(namespace);
+                    let (array_length, array_declaration) = match &for_loop.iterable {
+                        ForLoopIterable::Range { .. } => (None, None),
+                        ForLoopIterable::Array(array) => {
+                            let typed_array = check!(
+                                TypedExpression::type_check(TypeCheckArguments {
+                                    checkee: (**array).clone(),
+                                    namespace,
+                                    crate_namespace,
+                                    return_type_annotation: insert_type(TypeInfo::Unknown),
+                                    help_text: "",
+                                    self_type,
+                                    build_config,
+                                    dead_code_graph,
+                                    mode: Mode::NonAbi,
+                                    opts
+                                }),
+                                return err(warnings, errors),
+                                warnings,
+                                errors
+                            );
+                            let length = match look_up_type_id(typed_array.return_type) {
+                                TypeInfo::Array(_, length) => length,
+                                TypeInfo::ErrorRecovery => return err(warnings, errors),
+                                other => {
+                                    errors.push(CompileError::NotIterable {
+                                        ty: other.friendly_type_str(),
+                                        span: array.span(),
+                                    });
+                                    return err(warnings, errors);
+                                }
+                            };
+                            let array_declaration =
+                                TypedDeclaration::VariableDeclaration(TypedVariableDeclaration {
+                                    name: for_loop.iterable_name.clone(),
+                                    type_ascription: typed_array.return_type,
+                                    body: typed_array,
+                                    is_mutable: VariableMutability::Immutable,
+                                    const_decl_origin: false,
+                                });
+                            loop_namespace
+                                .insert(for_loop.iterable_name.clone(), array_declaration.clone());
+                            (Some(length), Some(array_declaration))
+                        }
+                    };
+                    let typed_loop = check!(
+                        TypedExpression::type_check(TypeCheckArguments {
+                            checkee: for_loop.desugar(node.span.clone(), array_length),
+                            namespace: loop_namespace,
+                            crate_namespace,
+                            return_type_annotation: insert_type(TypeInfo::Tuple(Vec::new())),
+                            help_text:
+                                "A for loop's loop body cannot implicitly return a value. Try \
+                             assigning it to a mutable variable declared outside of the loop \
+                             instead.",
+                            self_type,
+                            build_config,
+                            dead_code_graph,
+                            mode: Mode::NonAbi,
+                            opts,
+                        }),
+                        error_recovery_expr(node.span.clone()),
+                        warnings,
+                        errors
+                    );
+                    match array_declaration {
+                        None => TypedAstNodeContent::Expression(typed_loop),
+                        Some(array_declaration) => {
+                            TypedAstNodeContent::Expression(TypedExpression {
+                                return_type: typed_loop.return_type,
+                                expression: TypedExpressionVariant::CodeBlock(TypedCodeBlock {
+                                    contents: vec![
+                                        TypedAstNode {
+                                            content: TypedAstNodeContent::Declaration(
+                                                array_declaration,
+                                            ),
+                                            span: node.span.clone(),
+                                        },
+                                        TypedAstNode {
+                                            content: TypedAstNodeContent::Expression(typed_loop),
+                                            span: node.span.clone(),
+                                        },
+                                    ],
+                                    whole_block_span: node.span.clone(),
+                                }),
+                                is_constant: IsConstant::No,
+                                span: node.span.clone(),
+                            })
+                        }
+                    }
+                }
+                AstNodeContent::Break => {
+                    if !opts.in_loop {
+                        errors.push(CompileError::BreakOutsideLoop {
+                            span: node.span.clone(),
+                        });
+                    }
+                    TypedAstNodeContent::Break
+                }
+                AstNodeContent::Continue => {
+                    if !opts.in_loop {
+                        errors.push(CompileError::ContinueOutsideLoop {
+                            span: node.span.clone(),
+                        });
+                    }
+                    TypedAstNodeContent::Continue
+                }
             },
             span: node.span.clone(),
         };
@@ -750,12 +960,14 @@ impl TypedAstNode {
 
 /// Imports a new file, populates the given [Namespace] with its content,
 /// and appends the module's content to the control flow graph for later analysis.
+///
+/// Returns the typed contents of the file, or `None` if it could not be read.
 fn import_new_file(
     statement: &IncludeStatement,
     namespace: NamespaceRef,
     build_config: &BuildConfig,
     dead_code_graph: &mut ControlFlowGraph,
-) -> CompileResult<()> {
+) -> CompileResult<Option<TypedIncludeStatement>> {
     let mut warnings = vec![];
     let mut errors = vec![];
 
@@ -769,25 +981,27 @@ fn import_new_file(
         Err(_) => return err(warnings, errors),
     };
 
-    let res = if canonical_path.exists() {
-        std::fs::read_to_string(&*canonical_path)
+    let res = if let Some(source) = build_config.source_overrides.get(&canonical_path) {
+        Ok(source.clone())
+    } else if canonical_path.exists() {
+        std::fs::read_to_string(&*canonical_path).map(Arc::from)
     } else {
         errors.push(CompileError::FileNotFound {
             span: statement.path_span.clone(),
             file_path: canonical_path.to_string_lossy().to_string(),
         });
-        return ok((), warnings, errors);
+        return ok(None, warnings, errors);
     };
 
     let file_as_string = match res {
-        Ok(s) => Arc::from(s),
+        Ok(s) => s,
         Err(e) => {
             errors.push(CompileError::FileCouldNotBeRead {
                 span: statement.path_span.clone(),
                 file_path: canonical_path.to_string_lossy().to_string(),
                 stringified_error: e.to_string(),
             });
-            return ok((), warnings, errors);
+            return ok(None, warnings, errors);
         }
     };
 
@@ -802,7 +1016,7 @@ fn import_new_file(
     let crate::InnerDependencyCompileResult {
         name,
         namespace: module,
-        ..
+        include,
     } = check!(
         crate::compile_inner_dependency(file_as_string, dep_namespace, dep_config, dead_code_graph),
         return err(warnings, errors),
@@ -816,7 +1030,7 @@ fn import_new_file(
     };
     let name = name.as_str().to_string();
     namespace.insert_module(name, module);
-    ok((), warnings, errors)
+    ok(Some(include), warnings, errors)
 }
 
 fn reassignment(
@@ -847,10 +1061,11 @@ fn reassignment(
                     ..
                 })) => {
                     if !is_mutable.is_mutable() {
-                        errors.push(CompileError::AssignmentToNonMutable(
-                            name.as_str().to_string(),
-                            span.clone(),
-                        ));
+                        errors.push(CompileError::AssignmentToNonMutable {
+                            name: name.as_str().to_string(),
+                            decl_span: name.span().clone(),
+                            span: span.clone(),
+                        });
                     }
 
                     body
@@ -1002,6 +1217,45 @@ fn reassignment(
                 errors,
             )
         }
+        Expression::StorageAccess { field_names, span } => {
+            if opts.purity != Purity::Impure {
+                errors.push(CompileError::StorageAccessInPureContext { span: span.clone() });
+            }
+            let access = check!(
+                namespace.apply_storage_load(&field_names, &span),
+                return err(warnings, errors),
+                warnings,
+                errors
+            );
+            // type check the reassignment
+            let rhs = check!(
+                TypedExpression::type_check(TypeCheckArguments {
+                    checkee: rhs,
+                    namespace,
+                    crate_namespace,
+                    return_type_annotation: access.r#type,
+                    help_text: "You can only reassign a value of the same type to a storage field.",
+                    self_type,
+                    build_config,
+                    dead_code_graph,
+                    mode: Mode::NonAbi,
+                    opts,
+                }),
+                error_recovery_expr(span.clone()),
+                warnings,
+                errors
+            );
+
+            ok(
+                TypedDeclaration::StorageReassignment(TypedStorageReassignment {
+                    access,
+                    rhs,
+                    span,
+                }),
+                warnings,
+                errors,
+            )
+        }
         _ => {
             errors.push(CompileError::InvalidExpressionOnLhs { span });
             err(warnings, errors)
@@ -1009,6 +1263,87 @@ fn reassignment(
     }
 }
 
+fn type_check_storage_declaration(
+    arguments: TypeCheckArguments<'_, Vec<StorageField>>,
+    span: Span,
+) -> CompileResult<TypedStorageDeclaration> {
+    let TypeCheckArguments {
+        checkee: fields,
+        namespace,
+        crate_namespace,
+        self_type,
+        build_config,
+        dead_code_graph,
+        opts,
+        ..
+    } = arguments;
+    let mut errors = vec![];
+    let mut warnings = vec![];
+    let mut typed_fields = Vec::with_capacity(fields.len());
+    for StorageField {
+        name,
+        r#type,
+        initializer,
+    } in fields
+    {
+        let field_span = join_spans(name.span().clone(), initializer.span());
+        let r#type = match namespace.resolve_type_with_self(r#type, self_type) {
+            Ok(type_id) => type_id,
+            Err(_) => {
+                errors.push(CompileError::UnknownType {
+                    span: field_span.clone(),
+                });
+                continue;
+            }
+        };
+        let resolved_type = look_up_type_id(r#type);
+        if !declaration::is_valid_storage_type(&resolved_type) {
+            errors.push(CompileError::UnsupportedStorageFieldType {
+                field_name: name.as_str().to_string(),
+                ty: resolved_type.friendly_type_str(),
+                span: field_span,
+            });
+            continue;
+        }
+        let initializer = check!(
+            TypedExpression::type_check(TypeCheckArguments {
+                checkee: initializer,
+                namespace,
+                crate_namespace,
+                return_type_annotation: r#type,
+                help_text: "Storage field's type annotation does not match up with the \
+                            initializer's type.",
+                self_type,
+                build_config,
+                dead_code_graph,
+                mode: Mode::NonAbi,
+                opts,
+            }),
+            continue,
+            warnings,
+            errors
+        );
+        // the initial values are written into the deployment transaction, so they must be
+        // known at compile time
+        if !matches!(initializer.expression, TypedExpressionVariant::Literal(_)) {
+            errors.push(CompileError::NonConstantStorageInitializer {
+                span: initializer.span.clone(),
+            });
+            continue;
+        }
+        typed_fields.push(TypedStorageField {
+            name,
+            r#type,
+            initializer,
+        });
+    }
+    ok(
+        TypedStorageDeclaration::new(typed_fields, span),
+        warnings,
+        errors,
+    )
+}
+
 fn type_check_interface_surface(
     interface_surface: Vec<TraitFn>,
     namespace: crate::semantic_analysis::NamespaceRef,
@@ -1214,7 +1549,10 @@ fn type_check_trait_methods(
                 build_config,
                 dead_code_graph,
                 mode: Mode::NonAbi,
-                opts: TCOpts { purity }
+                opts: TCOpts {
+                    purity,
+                    in_loop: false,
+                }
             }),
             continue,
             warnings,
@@ -1233,6 +1571,7 @@ fn type_check_trait_methods(
             visibility: Visibility::Public,
             return_type_span,
             is_contract_call: false,
+            is_test: false,
             purity,
         });
     }
@@ -1259,6 +1598,7 @@ fn error_recovery_function_declaration(decl: FunctionDeclaration) -> TypedFuncti
         },
         span,
         is_contract_call: false,
+        is_test: false,
         return_type_span,
         parameters: Default::default(),
         visibility,
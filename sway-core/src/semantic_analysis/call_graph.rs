@@ -0,0 +1,394 @@
+//! The graph of calls between the functions of a program.
+//!
+//! Every call embeds the body of the called function, so the functions of a program are found by
+//! walking the calls made from its entry points. A recursive call instead embeds an empty body, as
+//! it is type checked before the body of the function it calls, and the body is looked up here. A
+//! call through a function value may call any function in the function table of the same type.
+//!
+//! The code generators use the graph to decide which calls are inlined and which are compiled into
+//! a call to a single copy of the function.
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    semantic_analysis::{
+        ast_node::{
+            SizeOfVariant, TypedCodeBlock, TypedConstantDeclaration, TypedDeclaration,
+            TypedExpressionVariant, TypedReassignment, TypedReturnStatement,
+            TypedVariableDeclaration, TypedWhileLoop,
+        },
+        FunctionTable, TypedAstNode, TypedAstNodeContent, TypedExpression,
+        TypedFunctionDeclaration, TypedParseTree,
+    },
+    type_engine::{look_up_type_id, TypeId},
+    Ident,
+};
+
+use sway_types::span::Span;
+
+/// Functions with a body of at most this many expressions are always inlined, unless they're
+/// recursive.
+const SMALL_FUNCTION_SIZE: usize = 16;
+
+/// Identifies a function, or an instance of a generic function, by the span of its name at its
+/// declaration and the types of its parameters and return value.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct FunctionKey {
+    span: Span,
+    types: Vec<String>,
+}
+
+impl FunctionKey {
+    /// The key of the function called by a `FunctionApplication` expression.
+    pub(crate) fn of_call(
+        decl_name: &Ident,
+        arguments: &[(Ident, TypedExpression)],
+        return_type: TypeId,
+    ) -> Self {
+        FunctionKey::new(
+            decl_name,
+            arguments.iter().map(|(_, arg)| arg.return_type),
+            return_type,
+        )
+    }
+
+    /// The key of a function declaration.
+    pub(crate) fn of_decl(decl: &TypedFunctionDeclaration) -> Self {
+        FunctionKey::new(
+            &decl.name,
+            decl.parameters.iter().map(|param| param.r#type),
+            decl.return_type,
+        )
+    }
+
+    fn new(name: &Ident, parameters: impl Iterator<Item = TypeId>, return_type: TypeId) -> Self {
+        FunctionKey {
+            span: name.span().clone(),
+            types: parameters
+                .chain(std::iter::once(return_type))
+                .map(|type_id| look_up_type_id(type_id).friendly_type_str())
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct FunctionNode {
+    body: TypedCodeBlock,
+    /// The functions this function calls directly or refers to as values.
+    callees: HashSet<FunctionKey>,
+    /// The number of calls made to this function.
+    call_count: usize,
+    /// The number of expressions in the body, excluding the bodies of called functions.
+    size: usize,
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CallGraph {
+    functions: HashMap<FunctionKey, FunctionNode>,
+}
+
+impl CallGraph {
+    /// Builds the graph of the calls made from the entry points of `tree`, where `function_table`
+    /// holds the functions which may be called through function values.
+    pub(crate) fn from_parse_tree(tree: &TypedParseTree, function_table: &FunctionTable) -> Self {
+        let mut walker = CallGraphWalker::new(function_table);
+        match tree {
+            TypedParseTree::Script {
+                main_function,
+                declarations,
+                ..
+            }
+            | TypedParseTree::Predicate {
+                main_function,
+                declarations,
+                ..
+            } => {
+                walker.visit_declarations(declarations);
+                walker.visit_code_block(&main_function.body);
+            }
+            TypedParseTree::Contract {
+                abi_entries,
+                declarations,
+                ..
+            } => {
+                walker.visit_declarations(declarations);
+                for entry in abi_entries {
+                    walker.visit_code_block(&entry.body);
+                }
+            }
+            TypedParseTree::Library { .. } => (),
+        }
+        walker.graph
+    }
+
+    /// Returns the body of the function called with `key`, which may differ from the body embedded
+    /// in a recursive call.
+    pub(crate) fn body(&self, key: &FunctionKey) -> Option<&TypedCodeBlock> {
+        self.functions.get(key).map(|node| &node.body)
+    }
+
+    /// Whether a call to `from` may lead to a call to `to`.
+    pub(crate) fn reaches(&self, from: &FunctionKey, to: &FunctionKey) -> bool {
+        let mut stack = vec![from];
+        let mut visited = HashSet::new();
+        while let Some(key) = stack.pop() {
+            if let Some(node) = self.functions.get(key) {
+                for callee in &node.callees {
+                    if callee == to {
+                        return true;
+                    }
+                    if visited.insert(callee) {
+                        stack.push(callee);
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    /// Whether a call to `key` may lead back to another call to `key`.
+    pub(crate) fn is_recursive(&self, key: &FunctionKey) -> bool {
+        self.reaches(key, key)
+    }
+
+    /// Whether calls to `key` should have the body of the function inlined rather than call a
+    /// single copy of it. Recursive functions can't be inlined, otherwise functions which are
+    /// called only once or are small enough are.
+    pub(crate) fn should_inline(&self, key: &FunctionKey) -> bool {
+        match self.functions.get(key) {
+            Some(node) => {
+                !self.is_recursive(key)
+                    && (node.call_count <= 1 || node.size <= SMALL_FUNCTION_SIZE)
+            }
+            None => true,
+        }
+    }
+}
+
+struct CallGraphWalker<'a> {
+    graph: CallGraph,
+    function_table: &'a FunctionTable,
+    /// The function whose body is being walked, or `None` for an entry point.
+    current_function: Option<FunctionKey>,
+    /// The functions whose body has been walked.
+    visited: HashSet<FunctionKey>,
+}
+
+impl<'a> CallGraphWalker<'a> {
+    fn new(function_table: &'a FunctionTable) -> Self {
+        CallGraphWalker {
+            graph: CallGraph::default(),
+            function_table,
+            current_function: None,
+            visited: HashSet::new(),
+        }
+    }
+
+    /// Adds a call to, or a reference to, the function `key` whose body is `body` from the
+    /// function currently walked, then walks the body if it hasn't been already.
+    fn insert_callee(&mut self, key: FunctionKey, body: &TypedCodeBlock, is_call: bool) {
+        let node = self
+            .graph
+            .functions
+            .entry(key.clone())
+            .or_insert_with(|| FunctionNode {
+                body: body.clone(),
+                callees: HashSet::new(),
+                call_count: 0,
+                size: 0,
+            });
+        // a recursive call embeds an empty body, the actual body is found in the declarations or
+        // once the function is called from elsewhere
+        if node.body.contents.is_empty() && !body.contents.is_empty() {
+            node.body = body.clone();
+        }
+        if is_call {
+            node.call_count += 1;
+        }
+        let body = node.body.clone();
+        if let Some(caller) = &self.current_function {
+            if let Some(caller) = self.graph.functions.get_mut(caller) {
+                caller.callees.insert(key.clone());
+            }
+        }
+
+        if body.contents.is_empty() || !self.visited.insert(key.clone()) {
+            return;
+        }
+        let caller = self.current_function.replace(key);
+        self.visit_code_block(&body);
+        self.current_function = caller;
+    }
+
+    fn count_expression(&mut self) {
+        if let Some(node) = self
+            .current_function
+            .as_ref()
+            .and_then(|key| self.graph.functions.get_mut(key))
+        {
+            node.size += 1;
+        }
+    }
+
+    fn visit_declarations(&mut self, declarations: &[TypedDeclaration]) {
+        for decl in declarations {
+            match decl {
+                TypedDeclaration::VariableDeclaration(TypedVariableDeclaration {
+                    body, ..
+                }) => self.visit_expression(body),
+                TypedDeclaration::ConstantDeclaration(TypedConstantDeclaration {
+                    value, ..
+                }) => self.visit_expression(value),
+                // the bodies of the functions which are only called recursively
+                TypedDeclaration::FunctionDeclaration(decl) if decl.type_parameters.is_empty() => {
+                    let key = FunctionKey::of_decl(decl);
+                    let node = self
+                        .graph
+                        .functions
+                        .entry(key)
+                        .or_insert_with(|| FunctionNode {
+                            body: decl.body.clone(),
+                            callees: HashSet::new(),
+                            call_count: 0,
+                            size: 0,
+                        });
+                    if node.body.contents.is_empty() {
+                        node.body = decl.body.clone();
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn visit_code_block(&mut self, block: &TypedCodeBlock) {
+        for node in &block.contents {
+            self.visit_node(node);
+        }
+    }
+
+    fn visit_node(&mut self, node: &TypedAstNode) {
+        match &node.content {
+            TypedAstNodeContent::ReturnStatement(TypedReturnStatement { expr })
+            | TypedAstNodeContent::Expression(expr)
+            | TypedAstNodeContent::ImplicitReturnExpression(expr) => self.visit_expression(expr),
+            TypedAstNodeContent::WhileLoop(TypedWhileLoop { condition, body }) => {
+                self.visit_expression(condition);
+                self.visit_code_block(body);
+            }
+            TypedAstNodeContent::Declaration(decl) => match decl {
+                TypedDeclaration::VariableDeclaration(TypedVariableDeclaration {
+                    body, ..
+                }) => self.visit_expression(body),
+                TypedDeclaration::ConstantDeclaration(TypedConstantDeclaration {
+                    value, ..
+                }) => self.visit_expression(value),
+                TypedDeclaration::Reassignment(TypedReassignment { rhs, .. }) => {
+                    self.visit_expression(rhs)
+                }
+                TypedDeclaration::StorageReassignment(reassignment) => {
+                    self.visit_expression(&reassignment.rhs)
+                }
+                _ => (),
+            },
+            TypedAstNodeContent::Break
+            | TypedAstNodeContent::Continue
+            | TypedAstNodeContent::SideEffect
+            | TypedAstNodeContent::IncludeStatement(_)
+            | TypedAstNodeContent::UseStatement(_) => (),
+        }
+    }
+
+    fn visit_expression(&mut self, expr: &TypedExpression) {
+        use TypedExpressionVariant::*;
+        self.count_expression();
+        match &expr.expression {
+            FunctionReference { function, .. } => {
+                self.insert_callee(FunctionKey::of_decl(function), &function.body, false)
+            }
+            IndirectFunctionApplication {
+                function,
+                arguments,
+            } => {
+                self.visit_expression(function);
+                arguments.iter().for_each(|arg| self.visit_expression(arg));
+                let function_table = self.function_table;
+                for (_, candidate) in function_table.candidates(function.return_type) {
+                    self.insert_callee(FunctionKey::of_decl(candidate), &candidate.body, false);
+                }
+            }
+            FunctionApplication {
+                decl_name,
+                arguments,
+                contract_call_params,
+                function_body,
+                selector,
+                ..
+            } => {
+                arguments
+                    .iter()
+                    .for_each(|(_, arg)| self.visit_expression(arg));
+                contract_call_params
+                    .values()
+                    .for_each(|param| self.visit_expression(param));
+                match selector {
+                    // a contract call runs a function of another contract
+                    Some(selector) => self.visit_expression(&selector.contract_address),
+                    None => self.insert_callee(
+                        FunctionKey::of_call(decl_name, arguments, expr.return_type),
+                        function_body,
+                        true,
+                    ),
+                }
+            }
+            LazyOperator { lhs, rhs, .. } | WideIntegerOperator { lhs, rhs, .. } => {
+                self.visit_expression(lhs);
+                self.visit_expression(rhs);
+            }
+            Tuple { fields } => fields.iter().for_each(|field| self.visit_expression(field)),
+            Array { contents } => contents.iter().for_each(|elem| self.visit_expression(elem)),
+            ArrayIndex { prefix, index } => {
+                self.visit_expression(prefix);
+                self.visit_expression(index);
+            }
+            StructExpression { fields, .. } => fields
+                .iter()
+                .for_each(|field| self.visit_expression(&field.value)),
+            CodeBlock(block) => self.visit_code_block(block),
+            IfExp {
+                condition,
+                then,
+                r#else,
+            } => {
+                self.visit_expression(condition);
+                self.visit_expression(then);
+                if let Some(r#else) = r#else {
+                    self.visit_expression(r#else);
+                }
+            }
+            AsmExpression { registers, .. } => registers
+                .iter()
+                .filter_map(|reg| reg.initializer.as_ref())
+                .for_each(|initializer| self.visit_expression(initializer)),
+            StructFieldAccess { prefix, .. }
+            | EnumArgAccess { prefix, .. }
+            | TupleElemAccess { prefix, .. } => self.visit_expression(prefix),
+            EnumInstantiation { contents, .. } => {
+                if let Some(contents) = contents {
+                    self.visit_expression(contents);
+                }
+            }
+            AbiCast { address, .. } => self.visit_expression(address),
+            SizeOf {
+                variant: SizeOfVariant::Val(expr),
+            } => self.visit_expression(expr),
+            Literal(_)
+            | VariableExpression { .. }
+            | FunctionParameter
+            | SizeOf {
+                variant: SizeOfVariant::Type(_),
+            }
+            | StorageAccess(_) => (),
+        }
+    }
+}
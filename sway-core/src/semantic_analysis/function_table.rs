@@ -0,0 +1,225 @@
+//! The table of functions which are used as values within a program.
+//!
+//! At runtime a function value is just the index of the function in this table. Calls through a
+//! function value are compiled into a dispatch over every entry in the table whose type matches
+//! the type of the value being called.
+use crate::{
+    semantic_analysis::{
+        ast_node::{
+            SizeOfVariant, TypedCodeBlock, TypedConstantDeclaration, TypedDeclaration,
+            TypedExpressionVariant, TypedReassignment, TypedReturnStatement,
+            TypedVariableDeclaration, TypedWhileLoop,
+        },
+        TypedAstNode, TypedAstNodeContent, TypedExpression, TypedFunctionDeclaration,
+        TypedParseTree,
+    },
+    type_engine::{look_up_type_id, TypeId},
+};
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct FunctionTable {
+    functions: Vec<TypedFunctionDeclaration>,
+}
+
+impl FunctionTable {
+    /// Collects every function referenced as a value from the entry points of `tree`. Function
+    /// bodies are inlined into the tree at every call site, so walking from the entry points
+    /// reaches every reference that can be evaluated at runtime.
+    pub(crate) fn from_parse_tree(tree: &TypedParseTree) -> Self {
+        let mut table = FunctionTable::default();
+        match tree {
+            TypedParseTree::Script {
+                main_function,
+                declarations,
+                ..
+            }
+            | TypedParseTree::Predicate {
+                main_function,
+                declarations,
+                ..
+            } => {
+                table.visit_declarations(declarations);
+                table.visit_code_block(&main_function.body);
+            }
+            TypedParseTree::Contract {
+                abi_entries,
+                declarations,
+                ..
+            } => {
+                table.visit_declarations(declarations);
+                for entry in abi_entries {
+                    table.visit_code_block(&entry.body);
+                }
+            }
+            TypedParseTree::Library { .. } => (),
+        }
+        table
+    }
+
+    /// Returns the index of `function` in the table, which is its value at runtime.
+    pub(crate) fn index_of(&self, function: &TypedFunctionDeclaration) -> Option<u64> {
+        self.functions
+            .iter()
+            .position(|entry| entry.span == function.span)
+            .map(|ix| ix as u64)
+    }
+
+    /// Returns every function, along with its index, that a value of type `function_type` may
+    /// refer to.
+    pub(crate) fn candidates(
+        &self,
+        function_type: TypeId,
+    ) -> impl Iterator<Item = (u64, &TypedFunctionDeclaration)> {
+        let function_type = look_up_type_id(function_type);
+        self.functions
+            .iter()
+            .enumerate()
+            .filter(move |(_, entry)| {
+                look_up_type_id(entry.function_type()).is_compatible_with(&function_type)
+            })
+            .map(|(ix, entry)| (ix as u64, entry))
+    }
+
+    fn insert(&mut self, function: &TypedFunctionDeclaration) {
+        if self.index_of(function).is_some() {
+            return;
+        }
+        self.functions.push(function.clone());
+        // the referenced function may itself reference other functions
+        self.visit_code_block(&function.body);
+    }
+
+    fn visit_declarations(&mut self, declarations: &[TypedDeclaration]) {
+        for decl in declarations {
+            match decl {
+                TypedDeclaration::VariableDeclaration(TypedVariableDeclaration {
+                    body, ..
+                }) => self.visit_expression(body),
+                TypedDeclaration::ConstantDeclaration(TypedConstantDeclaration {
+                    value, ..
+                }) => self.visit_expression(value),
+                _ => (),
+            }
+        }
+    }
+
+    fn visit_code_block(&mut self, block: &TypedCodeBlock) {
+        for node in &block.contents {
+            self.visit_node(node);
+        }
+    }
+
+    fn visit_node(&mut self, node: &TypedAstNode) {
+        match &node.content {
+            TypedAstNodeContent::ReturnStatement(TypedReturnStatement { expr })
+            | TypedAstNodeContent::Expression(expr)
+            | TypedAstNodeContent::ImplicitReturnExpression(expr) => self.visit_expression(expr),
+            TypedAstNodeContent::WhileLoop(TypedWhileLoop { condition, body }) => {
+                self.visit_expression(condition);
+                self.visit_code_block(body);
+            }
+            TypedAstNodeContent::Declaration(decl) => match decl {
+                TypedDeclaration::VariableDeclaration(TypedVariableDeclaration {
+                    body, ..
+                }) => self.visit_expression(body),
+                TypedDeclaration::ConstantDeclaration(TypedConstantDeclaration {
+                    value, ..
+                }) => self.visit_expression(value),
+                TypedDeclaration::Reassignment(TypedReassignment { rhs, .. }) => {
+                    self.visit_expression(rhs)
+                }
+                TypedDeclaration::StorageReassignment(reassignment) => {
+                    self.visit_expression(&reassignment.rhs)
+                }
+                // other declarations only take effect when they are used, at which point they
+                // are inlined
+                _ => (),
+            },
+            TypedAstNodeContent::Break
+            | TypedAstNodeContent::Continue
+            | TypedAstNodeContent::SideEffect
+            | TypedAstNodeContent::IncludeStatement(_)
+            | TypedAstNodeContent::UseStatement(_) => (),
+        }
+    }
+
+    fn visit_expression(&mut self, expr: &TypedExpression) {
+        use TypedExpressionVariant::*;
+        match &expr.expression {
+            FunctionReference { function, .. } => self.insert(function),
+            IndirectFunctionApplication {
+                function,
+                arguments,
+            } => {
+                self.visit_expression(function);
+                arguments.iter().for_each(|arg| self.visit_expression(arg));
+            }
+            FunctionApplication {
+                arguments,
+                contract_call_params,
+                function_body,
+                selector,
+                ..
+            } => {
+                arguments
+                    .iter()
+                    .for_each(|(_, arg)| self.visit_expression(arg));
+                contract_call_params
+                    .values()
+                    .for_each(|param| self.visit_expression(param));
+                if let Some(selector) = selector {
+                    self.visit_expression(&selector.contract_address);
+                }
+                self.visit_code_block(function_body);
+            }
+            LazyOperator { lhs, rhs, .. } | WideIntegerOperator { lhs, rhs, .. } => {
+                self.visit_expression(lhs);
+                self.visit_expression(rhs);
+            }
+            Tuple { fields } => fields.iter().for_each(|field| self.visit_expression(field)),
+            Array { contents } => contents.iter().for_each(|elem| self.visit_expression(elem)),
+            ArrayIndex { prefix, index } => {
+                self.visit_expression(prefix);
+                self.visit_expression(index);
+            }
+            StructExpression { fields, .. } => fields
+                .iter()
+                .for_each(|field| self.visit_expression(&field.value)),
+            CodeBlock(block) => self.visit_code_block(block),
+            IfExp {
+                condition,
+                then,
+                r#else,
+            } => {
+                self.visit_expression(condition);
+                self.visit_expression(then);
+                if let Some(r#else) = r#else {
+                    self.visit_expression(r#else);
+                }
+            }
+            AsmExpression { registers, .. } => registers
+                .iter()
+                .filter_map(|reg| reg.initializer.as_ref())
+                .for_each(|initializer| self.visit_expression(initializer)),
+            StructFieldAccess { prefix, .. }
+            | EnumArgAccess { prefix, .. }
+            | TupleElemAccess { prefix, .. } => self.visit_expression(prefix),
+            EnumInstantiation { contents, .. } => {
+                if let Some(contents) = contents {
+                    self.visit_expression(contents);
+                }
+            }
+            AbiCast { address, .. } => self.visit_expression(address),
+            SizeOf {
+                variant: SizeOfVariant::Val(expr),
+            } => self.visit_expression(expr),
+            Literal(_)
+            | VariableExpression { .. }
+            | FunctionParameter
+            | SizeOf {
+                variant: SizeOfVariant::Type(_),
+            }
+            | StorageAccess(_) => (),
+        }
+    }
+}
@@ -1,6 +1,8 @@
 use crate::{
-    error::*, type_engine::*, CallPath, CompileResult, Ident, TypeInfo, TypedDeclaration,
-    TypedFunctionDeclaration,
+    error::*,
+    semantic_analysis::ast_node::{TypedStorageAccess, TypedStorageDeclaration},
+    type_engine::*,
+    CallPath, CompileResult, Ident, TypeInfo, TypedDeclaration, TypedFunctionDeclaration,
 };
 
 use sway_types::span::Span;
@@ -12,6 +14,9 @@ pub use arena::*;
 
 type ModuleName = String;
 type TraitName = CallPath;
+/// A trait along with the type arguments it was implemented with. Implementations of a generic
+/// trait with different type arguments, e.g. `From<u64>` and `From<bool>`, are distinct.
+type TraitKey = (TraitName, Vec<TypeInfo>);
 /// A namespace represents all items that exist either via declaration or importing.
 #[derive(Clone, Debug, Default)]
 pub struct Namespace {
@@ -19,7 +24,7 @@ pub struct Namespace {
     // [Namespace::get_all_declared_symbols] -- we need that iterator to have a deterministic
     // order.
     symbols: BTreeMap<Ident, TypedDeclaration>,
-    implemented_traits: HashMap<(TraitName, TypeInfo), Vec<TypedFunctionDeclaration>>,
+    implemented_traits: HashMap<(TraitKey, TypeInfo), Vec<TypedFunctionDeclaration>>,
     // Any other modules within this scope, where a module is a namespace associated with an identifier.
     // This is a BTreeMap because we rely on its ordering being consistent. See
     // [Namespace::get_all_imported_modules] -- we need that iterator to have a deterministic
@@ -28,6 +33,9 @@ pub struct Namespace {
     use_synonyms: HashMap<Ident, Vec<Ident>>,
     // Represents an alternative name for a symbol.
     use_aliases: HashMap<String, Ident>,
+    // The contract storage declared in this scope, if any. Child scopes inherit it, which is how
+    // function bodies get access to it.
+    declared_storage: Option<TypedStorageDeclaration>,
 }
 
 impl Namespace {
@@ -39,6 +47,13 @@ impl Namespace {
         self.modules.values()
     }
 
+    /// The names of the modules within this scope along with the modules themselves.
+    pub(crate) fn get_all_named_modules(
+        &self,
+    ) -> impl Iterator<Item = (&ModuleName, &NamespaceRef)> {
+        self.modules.iter()
+    }
+
     pub(crate) fn insert(&mut self, name: Ident, item: TypedDeclaration) -> CompileResult<()> {
         let mut warnings = vec![];
         let mut errors = vec![];
@@ -66,9 +81,42 @@ impl Namespace {
         ok((), warnings, errors)
     }
 
+    pub(crate) fn set_storage_declaration(
+        &mut self,
+        decl: TypedStorageDeclaration,
+    ) -> CompileResult<()> {
+        if self.declared_storage.is_some() {
+            return err(
+                vec![],
+                vec![CompileError::MultipleStorageDeclarations { span: decl.span }],
+            );
+        }
+        self.declared_storage = Some(decl);
+        ok((), vec![], vec![])
+    }
+
+    pub(crate) fn get_declared_storage(&self) -> Option<&TypedStorageDeclaration> {
+        self.declared_storage.as_ref()
+    }
+
+    pub(crate) fn apply_storage_load(
+        &self,
+        field_names: &[Ident],
+        span: &Span,
+    ) -> CompileResult<TypedStorageAccess> {
+        match self.declared_storage {
+            Some(ref storage) => storage.apply_storage_load(field_names, span),
+            None => err(
+                vec![],
+                vec![CompileError::NoDeclaredStorage { span: span.clone() }],
+            ),
+        }
+    }
+
     pub(crate) fn insert_trait_implementation(
         &mut self,
         trait_name: CallPath,
+        trait_type_arguments: Vec<TypeInfo>,
         type_implementing_for: TypeInfo,
         functions_buf: Vec<TypedFunctionDeclaration>,
     ) -> CompileResult<()> {
@@ -89,7 +137,13 @@ impl Namespace {
         };
         if self
             .implemented_traits
-            .insert((trait_name.clone(), type_implementing_for), functions_buf)
+            .insert(
+                (
+                    (trait_name.clone(), trait_type_arguments),
+                    type_implementing_for,
+                ),
+                functions_buf,
+            )
             .is_some()
         {
             warnings.push(CompileWarning {
@@ -108,10 +162,22 @@ impl Namespace {
         self.insert_module(module_name, ix)
     }
 
+    /// Forget every implementation of the trait named `trait_name` for `type_implementing_for`,
+    /// whatever its type arguments.
+    pub(crate) fn remove_trait_implementations(
+        &mut self,
+        trait_name: &Ident,
+        type_implementing_for: &TypeInfo,
+    ) {
+        self.implemented_traits.retain(|((name, _), type_info), _| {
+            name.suffix != *trait_name || type_info != type_implementing_for
+        });
+    }
+
     pub(crate) fn get_methods_for_type(&self, r#type: TypeId) -> Vec<TypedFunctionDeclaration> {
         let mut methods = vec![];
         let r#type = crate::type_engine::look_up_type_id(r#type);
-        for ((_trait_name, type_info), l_methods) in &self.implemented_traits {
+        for ((_trait_key, type_info), l_methods) in &self.implemented_traits {
             if *type_info == r#type {
                 methods.append(&mut l_methods.clone());
             }
@@ -126,18 +192,18 @@ impl Namespace {
     pub(crate) fn copy_methods_to_type(&mut self, old_type: TypeInfo, new_type: TypeInfo) {
         // This map grabs all (trait name, vec of methods) from self.implemented_traits
         // corresponding to `old_type`.
-        let mut methods: HashMap<TraitName, Vec<TypedFunctionDeclaration>> = HashMap::new();
-        for ((trait_name, type_info), l_methods) in &self.implemented_traits {
+        let mut methods: HashMap<TraitKey, Vec<TypedFunctionDeclaration>> = HashMap::new();
+        for ((trait_key, type_info), l_methods) in &self.implemented_traits {
             if *type_info == old_type {
-                methods.insert((*trait_name).clone(), l_methods.clone());
+                methods.insert((*trait_key).clone(), l_methods.clone());
             }
         }
 
         // Insert into `self.implemented_traits` the contents of the map above but with `new_type`
         // as the `TypeInfo` key.
-        for (trait_name, l_methods) in &methods {
+        for (trait_key, l_methods) in &methods {
             self.implemented_traits
-                .insert(((*trait_name).clone(), new_type.clone()), l_methods.clone());
+                .insert(((*trait_key).clone(), new_type.clone()), l_methods.clone());
         }
     }
 
@@ -20,14 +20,22 @@ pub trait NamespaceWrapper {
     fn resolve_type_with_self(&self, ty: TypeInfo, self_type: TypeId) -> Result<TypeId, ()>;
     fn resolve_type_without_self(&self, ty: &TypeInfo) -> TypeId;
     fn insert(&self, name: Ident, item: TypedDeclaration) -> CompileResult<()>;
+    fn set_storage_declaration(&self, decl: TypedStorageDeclaration) -> CompileResult<()>;
+    fn apply_storage_load(
+        &self,
+        field_names: &[Ident],
+        span: &Span,
+    ) -> CompileResult<TypedStorageAccess>;
     fn insert_module(&self, module_name: String, module_contents: Namespace);
     fn insert_module_ref(&self, module_name: String, ix: NamespaceRef);
     fn insert_trait_implementation(
         &self,
         trait_name: CallPath,
+        trait_type_arguments: Vec<TypeInfo>,
         type_implementing_for: TypeInfo,
         functions_buf: Vec<TypedFunctionDeclaration>,
     ) -> CompileResult<()>;
+    fn remove_trait_implementations(&self, trait_name: &Ident, type_implementing_for: &TypeInfo);
     fn item_import(
         &self,
         from_namespace: Option<NamespaceRef>,
@@ -46,7 +54,12 @@ pub trait NamespaceWrapper {
     /// method in the namespace. Requires `args_buf` because of some special casing for the
     /// standard library where we pull the type from the arguments buffer.
     ///
-    /// This function will generate a missing method error if the method is not found.
+    /// When the type has several methods of that name, the arguments and the
+    /// `expected_return_type` of the call are used to choose one.
+    ///
+    /// This function will generate a missing method error if the method is not found, or an
+    /// error if it can't be told which of several methods is meant.
+    #[allow(clippy::too_many_arguments)]
     fn find_method_for_type(
         &self,
         r#type: TypeId,
@@ -55,6 +68,7 @@ pub trait NamespaceWrapper {
         from_module: Option<NamespaceRef>,
         self_type: TypeId,
         args_buf: &VecDeque<TypedExpression>,
+        expected_return_type: TypeId,
     ) -> CompileResult<TypedFunctionDeclaration>;
 
     /// Given a path to a module, create synonyms to every symbol in that module.
@@ -343,6 +357,7 @@ impl NamespaceWrapper for NamespaceRef {
         from_module: Option<NamespaceRef>,
         self_type: TypeId,
         args_buf: &VecDeque<TypedExpression>,
+        expected_return_type: TypeId,
     ) -> CompileResult<TypedFunctionDeclaration> {
         let mut warnings = vec![];
         let mut errors = vec![];
@@ -376,10 +391,84 @@ impl NamespaceWrapper for NamespaceRef {
         let mut methods = local_methods;
         methods.append(&mut ns_methods);
 
-        match methods
+        // The same method may be found both locally and in the namespace of the type.
+        let mut candidates: Vec<TypedFunctionDeclaration> = Vec::new();
+        for method in methods
             .into_iter()
-            .find(|TypedFunctionDeclaration { name, .. }| name == method_name)
+            .filter(|TypedFunctionDeclaration { name, .. }| name == method_name)
         {
+            if !candidates.iter().any(|other| same_method(other, &method)) {
+                candidates.push(method);
+            }
+        }
+        // A type may implement a generic trait several times with different type arguments, e.g.
+        // `From<u64>` and `From<bool>`, so when there is more than one method of this name the
+        // one whose parameters fit the arguments is chosen. Methods like `Into::into` only differ
+        // in what they return, so the type the call is expected to return is used as well.
+        let method = if candidates.len() > 1 {
+            let describe = |methods: &[&TypedFunctionDeclaration]| {
+                methods
+                    .iter()
+                    .map(|method| method_signature(method))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            let fitting = candidates
+                .iter()
+                .filter(|method| {
+                    method.parameters.len() == args_buf.len()
+                        && method
+                            .parameters
+                            .iter()
+                            .zip(args_buf.iter())
+                            .all(|(param, arg)| {
+                                look_up_type_id(arg.return_type)
+                                    .is_compatible_with(&look_up_type_id(param.r#type))
+                            })
+                })
+                .collect::<Vec<_>>();
+            let returning_expected = fitting
+                .iter()
+                .copied()
+                .filter(|method| {
+                    look_up_type_id(method.return_type)
+                        .is_compatible_with(&look_up_type_id(expected_return_type))
+                })
+                .collect::<Vec<_>>();
+            match (fitting.as_slice(), returning_expected.as_slice()) {
+                ([], _) => {
+                    errors.push(CompileError::NoMatchingMethod {
+                        method_name: method_name.as_str().to_string(),
+                        type_name: r#type.friendly_type_str(),
+                        candidates: describe(&candidates.iter().collect::<Vec<_>>()),
+                        span: method_name.span().clone(),
+                    });
+                    return err(warnings, errors);
+                }
+                ([method], _) | (_, [method]) => Some((*method).clone()),
+                (_, []) => {
+                    errors.push(CompileError::NoMatchingMethod {
+                        method_name: method_name.as_str().to_string(),
+                        type_name: r#type.friendly_type_str(),
+                        candidates: describe(&fitting),
+                        span: method_name.span().clone(),
+                    });
+                    return err(warnings, errors);
+                }
+                (_, methods) => {
+                    errors.push(CompileError::AmbiguousMethod {
+                        method_name: method_name.as_str().to_string(),
+                        type_name: r#type.friendly_type_str(),
+                        candidates: describe(methods),
+                        span: method_name.span().clone(),
+                    });
+                    return err(warnings, errors);
+                }
+            }
+        } else {
+            candidates.pop()
+        };
+        match method {
             Some(o) => ok(o, warnings, errors),
             None => {
                 if args_buf.get(0).map(|x| look_up_type_id(x.return_type))
@@ -491,7 +580,7 @@ impl NamespaceWrapper for NamespaceRef {
                         namespace
                             .implemented_traits
                             .iter()
-                            .filter(|((_trait_name, type_info), _impl)| {
+                            .filter(|((_trait_key, type_info), _impl)| {
                                 a.map(look_up_type_id).as_ref() == Some(type_info)
                             })
                             .fold(Vec::new(), |mut acc, (a, b)| {
@@ -569,16 +658,28 @@ impl NamespaceWrapper for NamespaceRef {
     fn insert_trait_implementation(
         &self,
         trait_name: CallPath,
+        trait_type_arguments: Vec<TypeInfo>,
         type_implementing_for: TypeInfo,
         functions_buf: Vec<TypedFunctionDeclaration>,
     ) -> CompileResult<()> {
         write_module(
             move |ns| {
-                ns.insert_trait_implementation(trait_name, type_implementing_for, functions_buf)
+                ns.insert_trait_implementation(
+                    trait_name,
+                    trait_type_arguments,
+                    type_implementing_for,
+                    functions_buf,
+                )
             },
             *self,
         )
     }
+    fn remove_trait_implementations(&self, trait_name: &Ident, type_implementing_for: &TypeInfo) {
+        write_module(
+            |ns| ns.remove_trait_implementations(trait_name, type_implementing_for),
+            *self,
+        )
+    }
     fn insert_module(&self, module_name: String, module_contents: Namespace) {
         let ix = {
             let mut write_lock = MODULES.write().expect("poisoned lock");
@@ -590,6 +691,16 @@ impl NamespaceWrapper for NamespaceRef {
     fn insert(&self, name: Ident, item: TypedDeclaration) -> CompileResult<()> {
         write_module(|ns| ns.insert(name, item), *self)
     }
+    fn set_storage_declaration(&self, decl: TypedStorageDeclaration) -> CompileResult<()> {
+        write_module(|ns| ns.set_storage_declaration(decl), *self)
+    }
+    fn apply_storage_load(
+        &self,
+        field_names: &[Ident],
+        span: &Span,
+    ) -> CompileResult<TypedStorageAccess> {
+        read_module(|ns| ns.apply_storage_load(field_names, span), *self)
+    }
     fn resolve_type_with_self(&self, ty: TypeInfo, self_type: TypeId) -> Result<TypeId, ()> {
         let mut warnings = vec![];
         let mut errors = vec![];
@@ -650,6 +761,21 @@ impl NamespaceWrapper for NamespaceRef {
                     _ => return Err(()),
                 }
             }
+            TypeInfo::Function {
+                parameters,
+                return_type,
+            } => {
+                let parameters = parameters
+                    .into_iter()
+                    .map(|param| self.resolve_type_with_self(look_up_type_id(param), self_type))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let return_type =
+                    self.resolve_type_with_self(look_up_type_id(return_type), self_type)?;
+                insert_type(TypeInfo::Function {
+                    parameters,
+                    return_type,
+                })
+            }
             TypeInfo::SelfType => self_type,
             TypeInfo::Ref(id) => id,
             o => insert_type(o),
@@ -693,6 +819,32 @@ impl NamespaceWrapper for NamespaceRef {
     }
 }
 
+/// Whether two methods are copies of the same declaration, for the same types.
+fn same_method(a: &TypedFunctionDeclaration, b: &TypedFunctionDeclaration) -> bool {
+    a.span == b.span
+        && look_up_type_id(a.return_type) == look_up_type_id(b.return_type)
+        && a.parameters.len() == b.parameters.len()
+        && a.parameters
+            .iter()
+            .zip(b.parameters.iter())
+            .all(|(a, b)| look_up_type_id(a.r#type) == look_up_type_id(b.r#type))
+}
+
+/// A method's signature as shown in error messages, e.g. `into(Wrapper) -> u64`.
+fn method_signature(method: &TypedFunctionDeclaration) -> String {
+    format!(
+        "\"{}({}) -> {}\"",
+        method.name.as_str(),
+        method
+            .parameters
+            .iter()
+            .map(|param| param.r#type.friendly_type_str())
+            .collect::<Vec<_>>()
+            .join(", "),
+        method.return_type.friendly_type_str()
+    )
+}
+
 /// Create a new module ([Namespace]), insert it into the arena, and get its id back.
 pub fn create_module() -> NamespaceRef {
     let res = {
@@ -3,10 +3,11 @@ use std::iter::FromIterator;
 
 use crate::{
     error::*, parse_tree::Scrutinee, parse_tree::*, type_engine::IntegerBits, AstNode,
-    AstNodeContent, CodeBlock, Declaration, Expression, ReturnStatement, TypeInfo, WhileLoop,
+    AstNodeContent, CodeBlock, Declaration, Expression, ForLoop, ForLoopIterable, ReturnStatement,
+    TypeInfo, WhileLoop,
 };
 
-use sway_types::{ident::Ident, span::Span};
+use sway_types::ident::Ident;
 
 // -------------------------------------------------------------------------------------------------
 /// Take a list of nodes and reorder them so that they may be semantically analysed without any
@@ -16,110 +17,77 @@ pub(crate) fn order_ast_nodes_by_dependency(nodes: Vec<AstNode>) -> CompileResul
     let decl_dependencies =
         DependencyMap::from_iter(nodes.iter().filter_map(Dependencies::gather_from_decl_node));
 
-    // Check here for recursive calls now that we have a nice map of the dependencies to help us.
-    let mut errors = find_recursive_calls(&decl_dependencies);
-    if !errors.is_empty() {
-        // Because we're pulling these errors out of a HashMap they'll probably be in a funny
-        // order.  Here we'll sort them by span start.
-        errors.sort_by(|lhs, rhs| lhs.span().0.cmp(&rhs.span().0));
-        err(Vec::new(), errors)
-    } else {
-        // Reorder the parsed AstNodes based on dependency.  Includes first, then uses, then
-        // reordered declarations, then anything else.  To keep the list stable and simple we can
-        // use a basic insertion sort.
-        ok(
-            nodes
-                .into_iter()
-                .fold(Vec::<AstNode>::new(), |ordered, node| {
-                    insert_into_ordered_nodes(&decl_dependencies, ordered, node)
-                }),
-            Vec::new(),
-            Vec::new(),
-        )
-    }
+    // Reorder the parsed AstNodes based on dependency.  Includes first, then uses, then
+    // reordered declarations, then anything else.  To keep the list stable and simple we can
+    // use a basic insertion sort.
+    ok(
+        nodes
+            .into_iter()
+            .fold(Vec::<AstNode>::new(), |ordered, node| {
+                insert_into_ordered_nodes(&decl_dependencies, ordered, node)
+            }),
+        Vec::new(),
+        Vec::new(),
+    )
 }
 
 // -------------------------------------------------------------------------------------------------
-// Recursion detection.
-
-fn find_recursive_calls(decl_dependencies: &DependencyMap) -> Vec<CompileError> {
-    decl_dependencies
+// Recursion.
+
+/// Returns the declarations of the functions in `ordered_nodes` which are called, directly or
+/// through other declarations, by a function declared before them.  The nodes are ordered by
+/// their dependencies, so this is mostly the case within a cycle of mutually recursive functions.
+/// Generic functions are left out as they're monomorphized from their body at each call.
+pub(crate) fn find_forward_called_functions(ordered_nodes: &[AstNode]) -> Vec<FunctionDeclaration> {
+    let decl_dependencies = DependencyMap::from_iter(
+        ordered_nodes
+            .iter()
+            .filter_map(Dependencies::gather_from_decl_node),
+    );
+    let fn_decls = ordered_nodes
         .iter()
-        .filter_map(|(dep_sym, _)| find_recursive_call(decl_dependencies, dep_sym))
-        .collect()
-}
-
-fn find_recursive_call(
-    decl_dependencies: &DependencyMap,
-    fn_sym: &DependentSymbol,
-) -> Option<CompileError> {
-    if let DependentSymbol::Fn(_, Some(fn_span)) = fn_sym {
-        let mut chain = Vec::new();
-        find_recursive_call_chain(decl_dependencies, fn_sym, fn_span, &mut chain)
-    } else {
-        None
-    }
-}
-
-fn find_recursive_call_chain(
-    decl_dependencies: &DependencyMap,
-    fn_sym: &DependentSymbol,
-    fn_span: &Span,
-    chain: &mut Vec<Ident>,
-) -> Option<CompileError> {
-    if let DependentSymbol::Fn(fn_sym_ident, _) = fn_sym {
-        if chain.iter().any(|seen_sym| seen_sym == fn_sym_ident) {
-            // We've found a recursive loop, but it's possible this function is not actually in the
-            // loop, but is instead just calling into the loop.  Only if this function is at the
-            // start of the chain do we need to report it.
-            return if &chain[0] != fn_sym_ident {
-                None
-            } else {
-                Some(build_recursion_error(
-                    fn_sym_ident.clone(),
-                    fn_span.clone(),
-                    &chain[1..],
-                ))
-            };
-        }
-        decl_dependencies.get(fn_sym).and_then(|deps_set| {
-            chain.push(fn_sym_ident.clone());
-            let result = deps_set.deps.iter().find_map(|dep_sym| {
-                find_recursive_call_chain(decl_dependencies, dep_sym, fn_span, chain)
-            });
-            chain.pop();
-            result
+        .filter_map(|node| match &node.content {
+            AstNodeContent::Declaration(Declaration::FunctionDeclaration(fn_decl))
+                if fn_decl.type_parameters.is_empty() =>
+            {
+                Some(fn_decl)
+            }
+            _ => None,
         })
-    } else {
-        None
+        .collect::<Vec<_>>();
+
+    let mut forward_called = HashSet::new();
+    for (ix, fn_decl) in fn_decls.iter().enumerate() {
+        let mut reached = HashSet::new();
+        find_reachable_symbols(
+            &decl_dependencies,
+            &DependentSymbol::Fn(fn_decl.name.clone()),
+            &mut reached,
+        );
+        for (later_ix, later_decl) in fn_decls.iter().enumerate().skip(ix + 1) {
+            if reached.contains(&DependentSymbol::Fn(later_decl.name.clone())) {
+                forward_called.insert(later_ix);
+            }
+        }
     }
+
+    fn_decls
+        .into_iter()
+        .enumerate()
+        .filter(|(ix, _)| forward_called.contains(ix))
+        .map(|(_, fn_decl)| fn_decl.clone())
+        .collect()
 }
 
-fn build_recursion_error(fn_sym: Ident, span: Span, chain: &[Ident]) -> CompileError {
-    match chain.len() {
-        // An empty chain indicates immediate recursion.
-        0 => CompileError::RecursiveCall {
-            fn_name: fn_sym,
-            span,
-        },
-        // Chain entries indicate mutual recursion.
-        1 => CompileError::RecursiveCallChain {
-            fn_name: fn_sym,
-            call_chain: chain[0].as_str().to_string(),
-            span,
-        },
-        n => {
-            let mut msg = chain[0].as_str().to_string();
-            for ident in &chain[1..(n - 1)] {
-                msg.push_str(", ");
-                msg.push_str(ident.as_str());
-            }
-            msg.push_str(" and ");
-            msg.push_str(chain[n - 1].as_str());
-            CompileError::RecursiveCallChain {
-                fn_name: fn_sym,
-                call_chain: msg,
-                span,
+fn find_reachable_symbols<'a>(
+    decl_dependencies: &'a DependencyMap,
+    sym: &DependentSymbol,
+    reached: &mut HashSet<&'a DependentSymbol>,
+) {
+    if let Some(deps_set) = decl_dependencies.get(sym) {
+        for dep_sym in &deps_set.deps {
+            if reached.insert(dep_sym) {
+                find_reachable_symbols(decl_dependencies, dep_sym, reached);
             }
         }
     }
@@ -246,7 +214,9 @@ impl Dependencies {
                     deps.gather_from_typeinfo(&variant.r#type)
                 })
                 .gather_from_traits(type_parameters),
-            Declaration::Reassignment(decl) => self.gather_from_expr(&decl.rhs),
+            Declaration::Reassignment(decl) => {
+                self.gather_from_expr(&decl.lhs).gather_from_expr(&decl.rhs)
+            }
             Declaration::TraitDeclaration(TraitDeclaration {
                 interface_surface,
                 methods,
@@ -277,7 +247,9 @@ impl Dependencies {
             }) => self
                 .gather_from_call_path(trait_name, false, false)
                 .gather_from_typeinfo(type_implementing_for)
-                .gather_from_traits(type_arguments)
+                .gather_from_iter(type_arguments.iter(), |deps, (type_argument, _)| {
+                    deps.gather_from_typeinfo(type_argument)
+                })
                 .gather_from_iter(functions.iter(), |deps, fn_decl| {
                     deps.gather_from_fn_decl(fn_decl)
                 }),
@@ -422,6 +394,11 @@ impl Dependencies {
             Expression::DelayedMatchTypeResolution { .. } => self,
             Expression::SizeOfVal { exp, .. } => self.gather_from_expr(exp),
             Expression::SizeOfType { .. } => self,
+            Expression::StorageAccess { .. } => {
+                self.deps
+                    .insert(DependentSymbol::Symbol(STORAGE_SYMBOL.to_string()));
+                self
+            }
         }
     }
 
@@ -453,10 +430,18 @@ impl Dependencies {
             AstNodeContent::WhileLoop(WhileLoop { condition, body }) => {
                 self.gather_from_expr(condition).gather_from_block(body)
             }
+            AstNodeContent::ForLoop(ForLoop { iterable, body, .. }) => match iterable {
+                ForLoopIterable::Range { start, end } => {
+                    self.gather_from_expr(start).gather_from_expr(end)
+                }
+                ForLoopIterable::Array(array) => self.gather_from_expr(array),
+            }
+            .gather_from_block(body),
 
             // No deps from these guys.
             AstNodeContent::UseStatement(_) => self,
             AstNodeContent::IncludeStatement(_) => self,
+            AstNodeContent::Break | AstNodeContent::Continue => self,
         }
     }
 
@@ -469,7 +454,7 @@ impl Dependencies {
         if call_path.prefixes.is_empty() {
             // We can just use the suffix.
             self.deps.insert(if is_fn_app {
-                DependentSymbol::Fn(call_path.suffix.clone(), None)
+                DependentSymbol::Fn(call_path.suffix.clone())
             } else {
                 DependentSymbol::Symbol(call_path.suffix.as_str().to_string())
             });
@@ -515,43 +500,16 @@ impl Dependencies {
 // they themselves depend on other declarations, no declarations depend on them.  This is
 // illustrated in DependentSymbol::is().
 
-#[derive(Debug, Eq)]
+/// The symbol used to track dependencies upon the contract's storage declaration.
+const STORAGE_SYMBOL: &str = "storage";
+
+#[derive(Debug, Eq, PartialEq, Hash)]
 enum DependentSymbol {
     Symbol(String),
-    Fn(Ident, Option<Span>),
+    Fn(Ident),
     Impl(Ident, String), // Trait or self, and type implementing for.
 }
 
-// We'll use a custom Hash and PartialEq here to explicitly ignore the span in the Fn variant.
-
-impl PartialEq for DependentSymbol {
-    fn eq(&self, rhs: &Self) -> bool {
-        match (self, rhs) {
-            (DependentSymbol::Symbol(l), DependentSymbol::Symbol(r)) => l.eq(r),
-            (DependentSymbol::Fn(l, _), DependentSymbol::Fn(r, _)) => l.eq(r),
-            (DependentSymbol::Impl(lt, ls), DependentSymbol::Impl(rt, rs)) => {
-                lt.eq(rt) && ls.eq(rs)
-            }
-            _ => false,
-        }
-    }
-}
-
-use std::hash::{Hash, Hasher};
-
-impl Hash for DependentSymbol {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        match self {
-            DependentSymbol::Symbol(s) => s.hash(state),
-            DependentSymbol::Fn(s, _) => s.hash(state),
-            DependentSymbol::Impl(t, s) => {
-                t.hash(state);
-                s.hash(state)
-            }
-        }
-    }
-}
-
 fn decl_name(decl: &Declaration) -> Option<DependentSymbol> {
     let dep_sym = |name| Some(DependentSymbol::Symbol(name));
     let impl_sym = |trait_name, type_info: &TypeInfo| {
@@ -560,10 +518,7 @@ fn decl_name(decl: &Declaration) -> Option<DependentSymbol> {
 
     match decl {
         // These declarations can depend upon other declarations.
-        Declaration::FunctionDeclaration(decl) => Some(DependentSymbol::Fn(
-            decl.name.clone(),
-            Some(decl.span.clone()),
-        )),
+        Declaration::FunctionDeclaration(decl) => Some(DependentSymbol::Fn(decl.name.clone())),
         Declaration::ConstantDeclaration(decl) => dep_sym(decl.name.as_str().to_string()),
         Declaration::StructDeclaration(decl) => dep_sym(decl.name.as_str().to_string()),
         Declaration::EnumDeclaration(decl) => dep_sym(decl.name.as_str().to_string()),
@@ -586,8 +541,8 @@ fn decl_name(decl: &Declaration) -> Option<DependentSymbol> {
         // These don't have declaration dependencies.
         Declaration::VariableDeclaration(_) => None,
         Declaration::Reassignment(_) => None,
-        // Storage cannot be depended upon or exported
-        Declaration::StorageDeclaration(_) => None,
+        // Storage cannot be exported, but anything which accesses it depends upon it.
+        Declaration::StorageDeclaration(_) => dep_sym(STORAGE_SYMBOL.to_string()),
     }
 }
 
@@ -601,6 +556,8 @@ fn type_info_name(type_info: &TypeInfo) -> String {
             IntegerBits::Sixteen => "uint16",
             IntegerBits::ThirtyTwo => "uint32",
             IntegerBits::SixtyFour => "uint64",
+            IntegerBits::OneTwentyEight => "uint128",
+            IntegerBits::TwoFiftySix => "uint256",
         },
         TypeInfo::Boolean => "bool",
         TypeInfo::Custom { name } => name.as_str(),
@@ -619,6 +576,7 @@ fn type_info_name(type_info: &TypeInfo) -> String {
         TypeInfo::Struct { .. } => "struct",
         TypeInfo::Enum { .. } => "enum",
         TypeInfo::Array(..) => "array",
+        TypeInfo::Function { .. } => "function",
     }
     .to_string()
 }
@@ -0,0 +1,1131 @@
+//! Indexing the named items of a typed parse tree for use by tooling such as the language server.
+//!
+//! Every declaration is recorded along with every reference to a declaration that can be
+//! resolved, so that a reference can be followed to its definition across files and packages.
+
+use super::{
+    ast_node::{
+        declaration::TypedTraitFn, SizeOfVariant, TypedAstNode, TypedAstNodeContent,
+        TypedCodeBlock, TypedDeclaration, TypedExpression, TypedExpressionVariant,
+        TypedFunctionDeclaration, TypedIncludeStatement, TypedReturnStatement, TypedStorageAccess,
+        TypedStructField, TypedUseStatement, TypedWhileLoop,
+    },
+    read_module, NamespaceRef, NamespaceWrapper, TypedParseTree,
+};
+use crate::{
+    type_engine::{look_up_type_id, TypeId, TypeInfo},
+    Ident,
+};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    path::PathBuf,
+    sync::Arc,
+};
+use sway_types::span::Span;
+
+/// A single occurrence of the name of an item: either its declaration or a reference to it.
+#[derive(Clone, Debug)]
+pub struct Symbol {
+    /// The name at this occurrence.
+    pub ident: Ident,
+    pub kind: SymbolKind,
+    /// The resolved type of the item, or the return type if it is a function.
+    pub type_id: Option<TypeId>,
+    /// The name of the item at its declaration. This is `ident` itself for declarations, and
+    /// `None` for references to items whose declaration is unknown.
+    pub definition: Option<Ident>,
+    /// The name of the function whose body contains this occurrence, if any.
+    pub container: Option<Ident>,
+}
+
+impl Symbol {
+    /// Whether or not this occurrence is the declaration of the item.
+    pub fn is_declaration(&self) -> bool {
+        match &self.definition {
+            Some(definition) => definition.span() == self.ident.span(),
+            None => false,
+        }
+    }
+}
+
+/// The kinds of items referred to by a [Symbol].
+#[derive(Clone, Debug)]
+pub enum SymbolKind {
+    Variable { is_mutable: bool },
+    FunctionParameter,
+    Constant,
+    Function { parameters: Vec<(Ident, TypeId)> },
+    Struct,
+    StructField,
+    Enum,
+    EnumVariant,
+    Trait,
+    Abi,
+    StorageField,
+    Module,
+}
+
+/// An item that can be referred to by name from some namespace, as offered by completion.
+#[derive(Clone, Debug)]
+pub struct NamedItem {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// The type of the item, or the return type if it is a function.
+    pub type_id: Option<TypeId>,
+}
+
+impl From<Symbol> for NamedItem {
+    fn from(symbol: Symbol) -> Self {
+        NamedItem {
+            name: symbol.ident.as_str().to_string(),
+            kind: symbol.kind,
+            type_id: symbol.type_id,
+        }
+    }
+}
+
+impl TypedParseTree {
+    /// Collect the symbols of every file within the tree, including those included with `dep`.
+    pub fn symbols(&self) -> Vec<Symbol> {
+        use TypedParseTree::*;
+        let namespace = match self {
+            Library { namespace, .. }
+            | Script { namespace, .. }
+            | Contract { namespace, .. }
+            | Predicate { namespace, .. } => *namespace,
+        };
+        let mut collector = SymbolCollector::new(namespace);
+        collector.nodes(self.all_nodes());
+        collector.symbols
+    }
+
+    /// The namespace of every file included with `dep`, along with the file's path relative to
+    /// the parent of the manifest directory.
+    pub fn included_namespaces(&self) -> Vec<(Arc<PathBuf>, NamespaceRef)> {
+        let mut namespaces = vec![];
+        collect_included_namespaces(self.all_nodes(), &mut namespaces);
+        namespaces
+    }
+}
+
+fn collect_included_namespaces(
+    nodes: &[TypedAstNode],
+    namespaces: &mut Vec<(Arc<PathBuf>, NamespaceRef)>,
+) {
+    for node in nodes {
+        if let TypedAstNodeContent::IncludeStatement(include) = &node.content {
+            namespaces.push((include.path.clone(), include.namespace));
+            collect_included_namespaces(&include.nodes, namespaces);
+        }
+    }
+}
+
+/// The items declared within or imported into the namespace, along with its modules.
+pub fn namespace_items(namespace: NamespaceRef) -> Vec<NamedItem> {
+    read_module(
+        |ns| {
+            let decls = ns.get_all_declared_symbols().filter_map(declaration_item);
+            let modules = ns.get_all_named_modules().map(|(name, _)| NamedItem {
+                name: name.clone(),
+                kind: SymbolKind::Module,
+                type_id: None,
+            });
+            decls.chain(modules).collect()
+        },
+        namespace,
+    )
+}
+
+/// The items that may follow `path::`: the items of the module at `path`, or the variants of the
+/// enum named by it.
+pub fn path_items(namespace: NamespaceRef, path: &[&str]) -> Vec<NamedItem> {
+    if let Some(module) = find_module(namespace, path) {
+        return namespace_items(module);
+    }
+    match find_declaration(namespace, path) {
+        Some(TypedDeclaration::EnumDeclaration(decl)) => decl
+            .variants
+            .iter()
+            .map(|variant| NamedItem {
+                name: variant.name.as_str().to_string(),
+                kind: SymbolKind::EnumVariant,
+                type_id: Some(variant.r#type),
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// The methods of the ABI named by `path`, which may be called on a contract caller.
+pub fn abi_methods(namespace: NamespaceRef, path: &[&str]) -> Vec<NamedItem> {
+    match find_declaration(namespace, path) {
+        Some(TypedDeclaration::AbiDeclaration(decl)) => decl
+            .interface_surface
+            .iter()
+            .map(|trait_fn| NamedItem {
+                name: trait_fn.name.as_str().to_string(),
+                kind: SymbolKind::Function {
+                    parameters: trait_fn
+                        .parameters
+                        .iter()
+                        .map(|param| (param.name.clone(), param.r#type))
+                        .collect(),
+                },
+                type_id: Some(trait_fn.return_type),
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// The fields of the struct named by `path`, in the order in which they are declared.
+pub fn struct_fields(namespace: NamespaceRef, path: &[&str]) -> Vec<NamedItem> {
+    match find_declaration(namespace, path) {
+        Some(TypedDeclaration::StructDeclaration(decl)) => decl
+            .fields
+            .iter()
+            .map(|field| NamedItem {
+                name: field.name.as_str().to_string(),
+                kind: SymbolKind::StructField,
+                type_id: Some(field.r#type),
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// The fields and methods that may follow a value of the given type and a `.`.
+///
+/// Methods are found within the namespace and every module it imports, as a type's
+/// implementations may be declared in any of them.
+pub fn type_members(namespace: NamespaceRef, type_id: TypeId) -> Vec<NamedItem> {
+    let mut items = vec![];
+    match look_up_type_id(type_id) {
+        TypeInfo::Struct { fields, .. } => {
+            items.extend(fields.into_iter().map(|field| NamedItem {
+                name: field.name,
+                kind: SymbolKind::StructField,
+                type_id: Some(field.r#type),
+            }))
+        }
+        TypeInfo::ContractCaller { abi_name, .. } => {
+            let path = abi_name
+                .prefixes
+                .iter()
+                .chain(std::iter::once(&abi_name.suffix))
+                .map(String::as_str)
+                .collect::<Vec<_>>();
+            items.extend(abi_methods(namespace, &path));
+        }
+        _ => (),
+    }
+
+    let mut names = HashSet::new();
+    let mut modules = vec![namespace];
+    let mut visited = HashSet::new();
+    while let Some(module) = modules.pop() {
+        if !visited.insert(module) {
+            continue;
+        }
+        for method in module.get_methods_for_type(type_id) {
+            let takes_self = matches!(
+                method.parameters.first(),
+                Some(param) if param.name.as_str() == "self"
+            );
+            if takes_self && names.insert(method.name.as_str().to_string()) {
+                items.push(NamedItem {
+                    name: method.name.as_str().to_string(),
+                    kind: function_kind(&method),
+                    type_id: Some(method.return_type),
+                });
+            }
+        }
+        modules.extend(read_module(
+            |ns| ns.get_all_imported_modules().cloned().collect::<Vec<_>>(),
+            module,
+        ));
+    }
+    items
+}
+
+/// The paths of the modules reachable from the namespace that contain an item with the given
+/// name, from which it may be imported with `use`. Shorter paths come first.
+pub fn import_paths(namespace: NamespaceRef, name: &str) -> Vec<Vec<String>> {
+    let mut paths = vec![];
+    let mut visited = HashSet::new();
+    let mut queue = VecDeque::from(vec![(namespace, vec![])]);
+    while let Some((module, path)) = queue.pop_front() {
+        if !visited.insert(module) {
+            continue;
+        }
+        let (contains_item, modules) = read_module(
+            |ns| {
+                let contains_item = ns
+                    .get_all_declared_symbols()
+                    .any(|decl| matches!(declaration_item(decl), Some(item) if item.name == name));
+                let modules = ns
+                    .get_all_named_modules()
+                    .map(|(module_name, module)| (module_name.clone(), *module))
+                    .collect::<Vec<_>>();
+                (contains_item, modules)
+            },
+            module,
+        );
+        if contains_item && !path.is_empty() {
+            paths.push(path.clone());
+        }
+        for (module_name, module) in modules {
+            let mut path = path.clone();
+            path.push(module_name);
+            queue.push_back((module, path));
+        }
+    }
+    paths
+}
+
+/// The module at the given path relative to the namespace.
+fn find_module(namespace: NamespaceRef, path: &[&str]) -> Option<NamespaceRef> {
+    path.iter().try_fold(namespace, |module, name| {
+        read_module(
+            |ns| {
+                ns.get_all_named_modules()
+                    .find(|(module_name, _)| module_name == name)
+                    .map(|(_, module)| *module)
+            },
+            module,
+        )
+    })
+}
+
+/// The declaration named by the last segment of the path, within the module at the rest of it.
+fn find_declaration(namespace: NamespaceRef, path: &[&str]) -> Option<TypedDeclaration> {
+    let (name, prefix) = path.split_last()?;
+    let module = find_module(namespace, prefix)?;
+    read_module(
+        |ns| {
+            ns.get_all_declared_symbols()
+                .find(|decl| matches!(declaration_item(decl), Some(item) if item.name == *name))
+                .cloned()
+        },
+        module,
+    )
+}
+
+struct SymbolCollector {
+    /// The namespace of the file currently being traversed.
+    namespace: NamespaceRef,
+    /// The struct and enum declarations visible from any of the traversed files, by name.
+    types: HashMap<String, TypedDeclaration>,
+    /// The local declarations of each enclosing scope, innermost last.
+    scopes: Vec<HashMap<String, Symbol>>,
+    /// The name of the function whose body is being traversed.
+    function: Option<Ident>,
+    symbols: Vec<Symbol>,
+}
+
+impl SymbolCollector {
+    fn new(namespace: NamespaceRef) -> Self {
+        let mut collector = SymbolCollector {
+            namespace,
+            types: HashMap::new(),
+            scopes: vec![HashMap::new()],
+            function: None,
+            symbols: vec![],
+        };
+        collector.collect_types(namespace, &mut HashSet::new());
+        collector
+    }
+
+    /// Record the struct and enum declarations of the given module and every module it imports.
+    fn collect_types(&mut self, namespace: NamespaceRef, visited: &mut HashSet<NamespaceRef>) {
+        if !visited.insert(namespace) {
+            return;
+        }
+        let (decls, modules): (Vec<_>, Vec<_>) = read_module(
+            |ns| {
+                let decls = ns
+                    .get_all_declared_symbols()
+                    .filter(|decl| {
+                        matches!(
+                            decl,
+                            TypedDeclaration::StructDeclaration(_)
+                                | TypedDeclaration::EnumDeclaration(_)
+                        )
+                    })
+                    .cloned()
+                    .collect();
+                let modules = ns.get_all_imported_modules().cloned().collect();
+                (decls, modules)
+            },
+            namespace,
+        );
+        for decl in decls {
+            let name = match &decl {
+                TypedDeclaration::StructDeclaration(decl) => decl.name.as_str().to_string(),
+                TypedDeclaration::EnumDeclaration(decl) => decl.name.as_str().to_string(),
+                _ => unreachable!("only struct and enum declarations are collected"),
+            };
+            self.types.entry(name).or_insert(decl);
+        }
+        for module in modules {
+            self.collect_types(module, visited);
+        }
+    }
+
+    fn nodes(&mut self, nodes: &[TypedAstNode]) {
+        for node in nodes {
+            self.node(node);
+        }
+    }
+
+    fn node(&mut self, node: &TypedAstNode) {
+        match &node.content {
+            TypedAstNodeContent::ReturnStatement(TypedReturnStatement { expr })
+            | TypedAstNodeContent::Expression(expr)
+            | TypedAstNodeContent::ImplicitReturnExpression(expr) => self.expression(expr),
+            TypedAstNodeContent::Declaration(decl) => self.declaration(decl),
+            TypedAstNodeContent::WhileLoop(TypedWhileLoop { condition, body }) => {
+                self.expression(condition);
+                self.code_block(body);
+            }
+            TypedAstNodeContent::IncludeStatement(TypedIncludeStatement {
+                namespace,
+                nodes,
+                ..
+            }) => {
+                let parent = std::mem::replace(&mut self.namespace, *namespace);
+                let scopes = std::mem::replace(&mut self.scopes, vec![HashMap::new()]);
+                self.nodes(nodes);
+                self.namespace = parent;
+                self.scopes = scopes;
+            }
+            TypedAstNodeContent::UseStatement(TypedUseStatement { item, decl }) => {
+                if let Some(declaration) = decl.as_ref().and_then(declaration_symbol) {
+                    self.push_reference(
+                        item,
+                        declaration.kind,
+                        declaration.type_id,
+                        declaration.definition,
+                    );
+                }
+            }
+            TypedAstNodeContent::Break
+            | TypedAstNodeContent::Continue
+            | TypedAstNodeContent::SideEffect => (),
+        }
+    }
+
+    fn code_block(&mut self, block: &TypedCodeBlock) {
+        self.scopes.push(HashMap::new());
+        self.nodes(&block.contents);
+        self.scopes.pop();
+    }
+
+    fn declaration(&mut self, decl: &TypedDeclaration) {
+        match decl {
+            TypedDeclaration::VariableDeclaration(var_decl) => {
+                // The name is only in scope after its initializer.
+                self.expression(&var_decl.body);
+                self.declare(
+                    &var_decl.name,
+                    SymbolKind::Variable {
+                        is_mutable: var_decl.is_mutable.is_mutable(),
+                    },
+                    Some(var_decl.body.return_type),
+                );
+            }
+            TypedDeclaration::ConstantDeclaration(const_decl) => {
+                self.expression(&const_decl.value);
+                self.declare(
+                    &const_decl.name,
+                    SymbolKind::Constant,
+                    Some(const_decl.value.return_type),
+                );
+            }
+            TypedDeclaration::FunctionDeclaration(fn_decl) => self.function(fn_decl),
+            TypedDeclaration::TraitDeclaration(trait_decl) => {
+                self.declare(&trait_decl.name, SymbolKind::Trait, None);
+                trait_decl
+                    .interface_surface
+                    .iter()
+                    .for_each(|trait_fn| self.trait_fn(trait_fn));
+            }
+            TypedDeclaration::StructDeclaration(struct_decl) => {
+                self.declare(&struct_decl.name, SymbolKind::Struct, None);
+                for field in &struct_decl.fields {
+                    self.push_declaration(&field.name, SymbolKind::StructField, Some(field.r#type));
+                }
+            }
+            TypedDeclaration::EnumDeclaration(enum_decl) => {
+                self.declare(&enum_decl.name, SymbolKind::Enum, None);
+                for variant in &enum_decl.variants {
+                    self.push_declaration(
+                        &variant.name,
+                        SymbolKind::EnumVariant,
+                        Some(variant.r#type),
+                    );
+                }
+            }
+            TypedDeclaration::Reassignment(reassignment) => {
+                let mut parent_type = None;
+                for (ix, lhs) in reassignment.lhs.iter().enumerate() {
+                    if ix == 0 {
+                        self.reference_local(&lhs.name);
+                    } else if let Some(parent_type) = parent_type {
+                        self.reference_field(&lhs.name, parent_type);
+                    }
+                    parent_type = Some(lhs.r#type);
+                }
+                self.expression(&reassignment.rhs);
+            }
+            TypedDeclaration::ImplTrait {
+                trait_name,
+                methods,
+                ..
+            } => {
+                let (kind, definition) = match self.namespace.get_call_path(trait_name).value {
+                    Some(TypedDeclaration::TraitDeclaration(decl)) => {
+                        (SymbolKind::Trait, Some(decl.name))
+                    }
+                    Some(TypedDeclaration::AbiDeclaration(decl)) => {
+                        (SymbolKind::Abi, Some(decl.name))
+                    }
+                    _ => (SymbolKind::Trait, None),
+                };
+                self.push_reference(&trait_name.suffix, kind, None, definition);
+                methods.iter().for_each(|method| self.function(method));
+            }
+            TypedDeclaration::AbiDeclaration(abi_decl) => {
+                self.declare(&abi_decl.name, SymbolKind::Abi, None);
+                abi_decl
+                    .interface_surface
+                    .iter()
+                    .for_each(|trait_fn| self.trait_fn(trait_fn));
+            }
+            TypedDeclaration::StorageDeclaration(storage_decl) => {
+                for field in &storage_decl.fields {
+                    self.expression(&field.initializer);
+                    self.push_declaration(
+                        &field.name,
+                        SymbolKind::StorageField,
+                        Some(field.r#type),
+                    );
+                }
+            }
+            TypedDeclaration::StorageReassignment(reassignment) => {
+                self.storage_access(&reassignment.access);
+                self.expression(&reassignment.rhs);
+            }
+            TypedDeclaration::GenericTypeForFunctionScope { .. }
+            | TypedDeclaration::ErrorRecovery => (),
+        }
+    }
+
+    fn function(&mut self, fn_decl: &TypedFunctionDeclaration) {
+        self.declare(
+            &fn_decl.name,
+            function_kind(fn_decl),
+            Some(fn_decl.return_type),
+        );
+        self.scopes.push(HashMap::new());
+        for param in &fn_decl.parameters {
+            self.declare(
+                &param.name,
+                SymbolKind::FunctionParameter,
+                Some(param.r#type),
+            );
+            self.type_reference(&param.type_span, param.r#type);
+        }
+        self.type_reference(&fn_decl.return_type_span, fn_decl.return_type);
+        let parent = self.function.replace(fn_decl.name.clone());
+        self.code_block(&fn_decl.body);
+        self.function = parent;
+        self.scopes.pop();
+    }
+
+    fn trait_fn(&mut self, trait_fn: &TypedTraitFn) {
+        let parameters = trait_fn
+            .parameters
+            .iter()
+            .map(|param| (param.name.clone(), param.r#type))
+            .collect();
+        self.push_declaration(
+            &trait_fn.name,
+            SymbolKind::Function { parameters },
+            Some(trait_fn.return_type),
+        );
+        for param in &trait_fn.parameters {
+            self.type_reference(&param.type_span, param.r#type);
+        }
+        self.type_reference(&trait_fn.return_type_span, trait_fn.return_type);
+    }
+
+    fn expression(&mut self, expr: &TypedExpression) {
+        match &expr.expression {
+            TypedExpressionVariant::Literal(_) | TypedExpressionVariant::FunctionParameter => (),
+            TypedExpressionVariant::FunctionApplication {
+                name,
+                decl_name,
+                contract_call_params,
+                arguments,
+                ..
+            } => {
+                let parameters = arguments
+                    .iter()
+                    .map(|(param, arg)| (param.clone(), arg.return_type))
+                    .collect();
+                self.push_reference(
+                    &name.suffix,
+                    SymbolKind::Function { parameters },
+                    Some(expr.return_type),
+                    Some(decl_name.clone()),
+                );
+                contract_call_params
+                    .values()
+                    .for_each(|param| self.expression(param));
+                arguments.iter().for_each(|(_, arg)| self.expression(arg));
+            }
+            TypedExpressionVariant::FunctionReference { name, function } => {
+                self.push_reference(
+                    &name.suffix,
+                    function_kind(function),
+                    Some(function.return_type),
+                    Some(function.name.clone()),
+                );
+            }
+            TypedExpressionVariant::IndirectFunctionApplication {
+                function,
+                arguments,
+            } => {
+                self.expression(function);
+                arguments.iter().for_each(|arg| self.expression(arg));
+            }
+            TypedExpressionVariant::LazyOperator { lhs, rhs, .. }
+            | TypedExpressionVariant::WideIntegerOperator { lhs, rhs, .. } => {
+                self.expression(lhs);
+                self.expression(rhs);
+            }
+            TypedExpressionVariant::VariableExpression { name } => self.reference_local(name),
+            TypedExpressionVariant::Tuple { fields } => {
+                fields.iter().for_each(|field| self.expression(field))
+            }
+            TypedExpressionVariant::Array { contents } => {
+                contents.iter().for_each(|elem| self.expression(elem))
+            }
+            TypedExpressionVariant::ArrayIndex { prefix, index } => {
+                self.expression(prefix);
+                self.expression(index);
+            }
+            TypedExpressionVariant::StructExpression {
+                struct_name,
+                fields,
+            } => {
+                // The struct's name is recorded as declared, so find its use within the
+                // expression, e.g. `Foo` within `lib::Foo { a: 1 }`.
+                if let Some(name) = path_suffix(&expr.span) {
+                    if name.as_str() == struct_name.as_str() {
+                        self.push_reference(
+                            &name,
+                            SymbolKind::Struct,
+                            Some(expr.return_type),
+                            Some(struct_name.clone()),
+                        );
+                    }
+                }
+                for field in fields {
+                    self.reference_field(&field.name, expr.return_type);
+                    self.expression(&field.value);
+                }
+            }
+            TypedExpressionVariant::CodeBlock(block) => self.code_block(block),
+            TypedExpressionVariant::IfExp {
+                condition,
+                then,
+                r#else,
+            } => {
+                self.expression(condition);
+                self.expression(then);
+                if let Some(r#else) = r#else {
+                    self.expression(r#else);
+                }
+            }
+            TypedExpressionVariant::AsmExpression { registers, .. } => {
+                for register in registers {
+                    if let Some(initializer) = &register.initializer {
+                        self.expression(initializer);
+                    }
+                }
+            }
+            TypedExpressionVariant::StructFieldAccess {
+                prefix,
+                field_to_access,
+                resolved_type_of_parent,
+                field_to_access_span,
+            } => {
+                self.expression(prefix);
+                let name = Ident::new(field_to_access_span.clone());
+                if name.as_str() == field_to_access.name {
+                    self.reference_field(&name, *resolved_type_of_parent);
+                }
+            }
+            TypedExpressionVariant::EnumArgAccess { prefix, .. }
+            | TypedExpressionVariant::TupleElemAccess { prefix, .. } => self.expression(prefix),
+            TypedExpressionVariant::EnumInstantiation {
+                enum_decl,
+                variant_name,
+                contents,
+                ..
+            } => {
+                // The expression spans the variant's name where it is instantiated.
+                let name = Ident::new(expr.span.clone());
+                if name.as_str() == variant_name.as_str() {
+                    let variant_type = enum_decl
+                        .variants
+                        .iter()
+                        .find(|variant| variant.name == *variant_name)
+                        .map(|variant| variant.r#type);
+                    self.push_reference(
+                        &name,
+                        SymbolKind::EnumVariant,
+                        variant_type,
+                        Some(variant_name.clone()),
+                    );
+                }
+                if let Some(contents) = contents {
+                    self.expression(contents);
+                }
+            }
+            TypedExpressionVariant::AbiCast {
+                abi_name, address, ..
+            } => {
+                if let Some(TypedDeclaration::AbiDeclaration(abi_decl)) =
+                    self.namespace.get_call_path(abi_name).value
+                {
+                    self.push_reference(
+                        &abi_name.suffix,
+                        SymbolKind::Abi,
+                        None,
+                        Some(abi_decl.name),
+                    );
+                }
+                self.expression(address);
+            }
+            TypedExpressionVariant::SizeOf { variant } => match variant {
+                SizeOfVariant::Type(_) => (),
+                SizeOfVariant::Val(expr) => self.expression(expr),
+            },
+            TypedExpressionVariant::StorageAccess(access) => self.storage_access(access),
+        }
+    }
+
+    fn storage_access(&mut self, access: &TypedStorageAccess) {
+        let field = read_module(
+            |ns| {
+                ns.get_declared_storage()
+                    .and_then(|storage| storage.fields.get(access.ix))
+                    .map(|field| field.name.clone())
+            },
+            self.namespace,
+        );
+        self.push_reference(
+            &access.field_name,
+            SymbolKind::StorageField,
+            Some(access.r#type),
+            field,
+        );
+    }
+
+    /// Record a reference to a struct or enum from a type annotation.
+    fn type_reference(&mut self, span: &Span, type_id: TypeId) {
+        let (name, kind) = match look_up_type_id(type_id) {
+            TypeInfo::Struct { name, .. } => (name, SymbolKind::Struct),
+            TypeInfo::Enum { name, .. } => (name, SymbolKind::Enum),
+            _ => return,
+        };
+        let ident = Ident::new(span.clone());
+        if ident.as_str() != name {
+            return;
+        }
+        let definition = self.types.get(&name).map(|decl| match decl {
+            TypedDeclaration::StructDeclaration(decl) => decl.name.clone(),
+            TypedDeclaration::EnumDeclaration(decl) => decl.name.clone(),
+            _ => unreachable!("only struct and enum declarations are collected"),
+        });
+        self.push_reference(&ident, kind, Some(type_id), definition);
+    }
+
+    /// Record a reference to a field of the struct of the given type.
+    fn reference_field(&mut self, name: &Ident, struct_type: TypeId) {
+        let struct_name = match look_up_type_id(struct_type) {
+            TypeInfo::Struct { name, .. } => name,
+            _ => return,
+        };
+        let field = match self.types.get(&struct_name) {
+            Some(TypedDeclaration::StructDeclaration(decl)) => decl
+                .fields
+                .iter()
+                .find(|field| field.name == *name)
+                .map(|TypedStructField { name, r#type, .. }| (name.clone(), *r#type)),
+            _ => None,
+        };
+        let (definition, type_id) = match field {
+            Some((definition, type_id)) => (Some(definition), Some(type_id)),
+            None => (None, None),
+        };
+        self.push_reference(name, SymbolKind::StructField, type_id, definition);
+    }
+
+    /// Record a reference to a variable, parameter or constant, resolving it within the enclosing
+    /// scopes, or otherwise within the current file's namespace.
+    fn reference_local(&mut self, name: &Ident) {
+        let local = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name.as_str()))
+            .cloned();
+        let declaration = match local {
+            Some(declaration) => declaration,
+            None => match self
+                .namespace
+                .get_symbol(name)
+                .value
+                .as_ref()
+                .and_then(declaration_symbol)
+            {
+                Some(declaration) => declaration,
+                None => return,
+            },
+        };
+        self.push_reference(
+            name,
+            declaration.kind,
+            declaration.type_id,
+            declaration.definition,
+        );
+    }
+
+    /// Record a declaration and bring it into the current scope.
+    fn declare(&mut self, name: &Ident, kind: SymbolKind, type_id: Option<TypeId>) {
+        if let Some(symbol) = self.push_declaration(name, kind, type_id) {
+            self.scopes
+                .last_mut()
+                .expect("there is always a scope")
+                .insert(name.as_str().to_string(), symbol);
+        }
+    }
+
+    fn push_declaration(
+        &mut self,
+        name: &Ident,
+        kind: SymbolKind,
+        type_id: Option<TypeId>,
+    ) -> Option<Symbol> {
+        self.push_reference(name, kind, type_id, Some(name.clone()))
+    }
+
+    /// Record an occurrence of a name, skipping names generated by the compiler, which do not
+    /// appear in the source.
+    fn push_reference(
+        &mut self,
+        name: &Ident,
+        kind: SymbolKind,
+        type_id: Option<TypeId>,
+        definition: Option<Ident>,
+    ) -> Option<Symbol> {
+        if name.as_str() != name.span().as_str() {
+            return None;
+        }
+        let symbol = Symbol {
+            ident: name.clone(),
+            kind,
+            type_id,
+            definition,
+            container: self.function.clone(),
+        };
+        self.symbols.push(symbol.clone());
+        Some(symbol)
+    }
+}
+
+fn function_kind(fn_decl: &TypedFunctionDeclaration) -> SymbolKind {
+    let parameters = fn_decl
+        .parameters
+        .iter()
+        .map(|param| (param.name.clone(), param.r#type))
+        .collect();
+    SymbolKind::Function { parameters }
+}
+
+/// The symbol for the declaration of an item found within a namespace.
+fn declaration_symbol(decl: &TypedDeclaration) -> Option<Symbol> {
+    let (name, kind, type_id) = match decl {
+        TypedDeclaration::VariableDeclaration(var_decl) => (
+            &var_decl.name,
+            SymbolKind::Variable {
+                is_mutable: var_decl.is_mutable.is_mutable(),
+            },
+            Some(var_decl.body.return_type),
+        ),
+        TypedDeclaration::ConstantDeclaration(const_decl) => (
+            &const_decl.name,
+            SymbolKind::Constant,
+            Some(const_decl.value.return_type),
+        ),
+        TypedDeclaration::FunctionDeclaration(fn_decl) => (
+            &fn_decl.name,
+            function_kind(fn_decl),
+            Some(fn_decl.return_type),
+        ),
+        TypedDeclaration::StructDeclaration(decl) => (&decl.name, SymbolKind::Struct, None),
+        TypedDeclaration::EnumDeclaration(decl) => (&decl.name, SymbolKind::Enum, None),
+        TypedDeclaration::TraitDeclaration(decl) => (&decl.name, SymbolKind::Trait, None),
+        TypedDeclaration::AbiDeclaration(decl) => (&decl.name, SymbolKind::Abi, None),
+        _ => return None,
+    };
+    Some(Symbol {
+        ident: name.clone(),
+        kind,
+        type_id,
+        definition: Some(name.clone()),
+        container: None,
+    })
+}
+
+/// The item for a declaration found within a namespace.
+fn declaration_item(decl: &TypedDeclaration) -> Option<NamedItem> {
+    declaration_symbol(decl).map(NamedItem::from)
+}
+
+/// The last segment of the path at the start of the given span, e.g. `Foo` within
+/// `lib::Foo { a: 1 }`.
+fn path_suffix(span: &Span) -> Option<Ident> {
+    let text = span.as_str();
+    let path_len = text
+        .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == ':'))
+        .unwrap_or(text.len());
+    let suffix_start = text[..path_len].rfind("::").map_or(0, |ix| ix + 2);
+    if suffix_start >= path_len {
+        return None;
+    }
+    let suffix = pest::Span::new(
+        span.span.input().clone(),
+        span.start() + suffix_start,
+        span.start() + path_len,
+    )?;
+    Some(Ident::new(Span {
+        span: suffix,
+        path: span.path.clone(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BuildConfig, CompileAstResult};
+
+    const SOURCE: &str = r#"script;
+
+struct Point {
+    x: u64,
+    y: u64,
+}
+
+enum Shape {
+    Dot: Point,
+    Empty: (),
+}
+
+fn first(p: Point) -> u64 {
+    p.x
+}
+
+fn origin() -> Point {
+    Point {
+        x: 0,
+        y: 0,
+    }
+}
+
+fn main() -> u64 {
+    let p = origin();
+    let p = Shape::Dot(p);
+    first(origin())
+}
+"#;
+
+    fn symbols(source: &str) -> Vec<Symbol> {
+        let build_config =
+            BuildConfig::root_from_file_name_and_manifest_path("main.sw".into(), ".".into());
+        match crate::compile_to_ast(Arc::from(source), crate::create_module(), &build_config) {
+            CompileAstResult::Success { parse_tree, .. } => parse_tree.symbols(),
+            CompileAstResult::Failure { errors, .. } => panic!("{:?}", errors),
+        }
+    }
+
+    /// The offset of the `nth` occurrence of the word `name` within the source.
+    fn offset(name: &str, nth: usize) -> usize {
+        let is_ident_char =
+            |c: Option<char>| matches!(c, Some(c) if c.is_alphanumeric() || c == '_');
+        SOURCE
+            .match_indices(name)
+            .map(|(ix, _)| ix)
+            .filter(|ix| {
+                !is_ident_char(SOURCE[..*ix].chars().last())
+                    && !is_ident_char(SOURCE[ix + name.len()..].chars().next())
+            })
+            .nth(nth)
+            .unwrap_or_else(|| panic!("no occurrence {} of {:?}", nth, name))
+    }
+
+    /// The symbol for the `nth` occurrence of `name` within the source.
+    fn symbol<'a>(symbols: &'a [Symbol], name: &str, nth: usize) -> &'a Symbol {
+        let start = offset(name, nth);
+        symbols
+            .iter()
+            .find(|symbol| symbol.ident.as_str() == name && symbol.ident.span().start() == start)
+            .unwrap_or_else(|| panic!("no symbol for occurrence {} of {:?}", nth, name))
+    }
+
+    fn definition_start(symbol: &Symbol) -> Option<usize> {
+        symbol
+            .definition
+            .as_ref()
+            .map(|definition| definition.span().start())
+    }
+
+    #[test]
+    fn declarations_are_recorded() {
+        let symbols = symbols(SOURCE);
+        let declarations = symbols
+            .iter()
+            .filter(|symbol| symbol.is_declaration())
+            .map(|symbol| symbol.ident.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            declarations,
+            [
+                "Point", "x", "y", "Shape", "Dot", "Empty", "first", "p", "origin", "main", "p",
+                "p"
+            ]
+        );
+
+        assert!(matches!(
+            symbol(&symbols, "Point", 0).kind,
+            SymbolKind::Struct
+        ));
+        assert!(matches!(
+            symbol(&symbols, "x", 0).kind,
+            SymbolKind::StructField
+        ));
+        assert!(matches!(
+            symbol(&symbols, "Dot", 0).kind,
+            SymbolKind::EnumVariant
+        ));
+        match &symbol(&symbols, "first", 0).kind {
+            SymbolKind::Function { parameters } => {
+                let names = parameters
+                    .iter()
+                    .map(|(name, _)| name.as_str())
+                    .collect::<Vec<_>>();
+                assert_eq!(names, ["p"]);
+            }
+            kind => panic!("{:?}", kind),
+        }
+        assert!(matches!(
+            symbol(&symbols, "p", 0).kind,
+            SymbolKind::FunctionParameter
+        ));
+        assert!(matches!(
+            symbol(&symbols, "p", 2).kind,
+            SymbolKind::Variable { is_mutable: false }
+        ));
+    }
+
+    #[test]
+    fn references_resolve_to_their_declarations() {
+        let symbols = symbols(SOURCE);
+        let resolves_to = |name: &str, nth: usize, declaration: usize| {
+            let reference = symbol(&symbols, name, nth);
+            assert!(!reference.is_declaration(), "{} {}", name, nth);
+            assert_eq!(
+                definition_start(reference),
+                Some(offset(name, declaration)),
+                "{} {}",
+                name,
+                nth
+            );
+        };
+
+        // Type annotations and struct expressions.
+        resolves_to("Point", 2, 0);
+        resolves_to("Point", 3, 0);
+        resolves_to("Point", 4, 0);
+        // `p.x` within `first`.
+        resolves_to("p", 1, 0);
+        resolves_to("x", 1, 0);
+        // The fields of the struct expression within `origin`.
+        resolves_to("x", 2, 0);
+        resolves_to("y", 1, 0);
+        // The enum variant, and the variable it is given, which is shadowed only after.
+        resolves_to("Dot", 1, 0);
+        resolves_to("p", 4, 2);
+        // Calls.
+        resolves_to("origin", 1, 0);
+        resolves_to("first", 1, 0);
+        resolves_to("origin", 2, 0);
+    }
+
+    #[test]
+    fn references_record_the_function_containing_them() {
+        let symbols = symbols(SOURCE);
+        let container = |symbol: &Symbol| {
+            symbol
+                .container
+                .as_ref()
+                .map(|container| container.as_str().to_string())
+        };
+        let calls_from = |function: &str| {
+            symbols
+                .iter()
+                .filter(|symbol| {
+                    matches!(symbol.kind, SymbolKind::Function { .. })
+                        && !symbol.is_declaration()
+                        && container(symbol).as_deref() == Some(function)
+                })
+                .map(|symbol| symbol.ident.as_str())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(calls_from("main"), ["origin", "first", "origin"]);
+        assert!(calls_from("origin").is_empty());
+        assert!(calls_from("first").is_empty());
+        assert_eq!(
+            container(symbol(&symbols, "p", 1)).as_deref(),
+            Some("first")
+        );
+        assert_eq!(container(symbol(&symbols, "Point", 0)), None);
+        assert_eq!(container(symbol(&symbols, "main", 0)), None);
+    }
+
+    #[test]
+    fn names_are_resolved_within_a_function_body() {
+        let symbols = symbols(
+            r#"script;
+
+fn main() -> u64 {
+    let n = 1;
+    n
+}
+"#,
+        );
+        let n = symbols
+            .iter()
+            .filter(|symbol| symbol.ident.as_str() == "n")
+            .collect::<Vec<_>>();
+        assert_eq!(n.len(), 2);
+        assert!(n[0].is_declaration());
+        assert_eq!(definition_start(n[1]), definition_start(n[0]));
+        assert!(symbols.iter().all(|symbol| symbol.definition.is_some()));
+    }
+}
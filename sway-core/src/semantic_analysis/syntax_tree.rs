@@ -1,5 +1,5 @@
 use super::{
-    node_dependencies, TypedAstNode, TypedAstNodeContent, TypedDeclaration,
+    ast_node::StorageSlot, node_dependencies, TypedAstNode, TypedAstNodeContent, TypedDeclaration,
     TypedFunctionDeclaration,
 };
 
@@ -7,13 +7,13 @@ use crate::{
     build_config::BuildConfig,
     control_flow_analysis::ControlFlowGraph,
     error::*,
-    parse_tree::Purity,
+    parse_tree::{FunctionDeclaration, Purity},
     semantic_analysis::{
-        ast_node::Mode, namespace::arena::NamespaceWrapper, retrieve_module, Namespace,
-        NamespaceRef, TypeCheckArguments,
+        ast_node::Mode, namespace::arena::NamespaceWrapper, read_module, retrieve_module,
+        Namespace, NamespaceRef, TypeCheckArguments,
     },
     type_engine::*,
-    AstNode, ParseTree,
+    AstNode, AstNodeContent, Declaration, ParseTree,
 };
 
 use sway_types::{ident::Ident, span::Span};
@@ -67,6 +67,78 @@ impl TypedParseTree {
         }
     }
 
+    /// The initial values of the storage slots declared by this tree. Only contracts may declare
+    /// storage, so this is empty for all other tree types.
+    pub fn storage_slots(&self) -> Vec<StorageSlot> {
+        match self {
+            TypedParseTree::Contract { namespace, .. } => read_module(
+                |ns| {
+                    ns.get_declared_storage()
+                        .map(|storage| storage.storage_slots())
+                        .unwrap_or_default()
+                },
+                *namespace,
+            ),
+            _ => vec![],
+        }
+    }
+
+    /// Returns each `#[test]` function declared at the top level of this tree, paired with a
+    /// script which runs that test as its entry point.
+    pub(crate) fn test_scripts(&self) -> Vec<(Ident, TypedParseTree)> {
+        use TypedParseTree::*;
+        let (namespace, all_nodes) = match self {
+            Library {
+                namespace,
+                all_nodes,
+            }
+            | Script {
+                namespace,
+                all_nodes,
+                ..
+            }
+            | Contract {
+                namespace,
+                all_nodes,
+                ..
+            }
+            | Predicate {
+                namespace,
+                all_nodes,
+                ..
+            } => (*namespace, all_nodes),
+        };
+        let declarations = all_nodes
+            .iter()
+            .filter_map(|node| match &node.content {
+                TypedAstNodeContent::Declaration(decl) => Some(decl.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        all_nodes
+            .iter()
+            .filter_map(|node| match &node.content {
+                TypedAstNodeContent::Declaration(TypedDeclaration::FunctionDeclaration(func))
+                    if func.is_test =>
+                {
+                    // The backends expect the entry point of a script to be called `main`.
+                    let main_function = TypedFunctionDeclaration {
+                        name: Ident::new_with_override("main", func.name.span().clone()),
+                        ..func.clone()
+                    };
+                    let script = Script {
+                        main_function,
+                        namespace,
+                        declarations: declarations.clone(),
+                        all_nodes: all_nodes.clone(),
+                    };
+                    Some((func.name.clone(), script))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
     pub fn get_namespace_ref(self) -> NamespaceRef {
         use TypedParseTree::*;
         match self {
@@ -104,9 +176,12 @@ impl TypedParseTree {
             warnings,
             errors
         );
+        let forward_called_functions =
+            node_dependencies::find_forward_called_functions(&ordered_nodes);
         let typed_nodes = check!(
             TypedParseTree::type_check_nodes(
                 ordered_nodes,
+                forward_called_functions,
                 new_namespace,
                 crate_namespace,
                 build_config,
@@ -129,6 +204,7 @@ impl TypedParseTree {
 
     fn type_check_nodes(
         nodes: Vec<AstNode>,
+        mut forward_called_functions: Vec<FunctionDeclaration>,
         namespace: NamespaceRef,
         crate_namespace: NamespaceRef,
         build_config: &BuildConfig,
@@ -136,30 +212,84 @@ impl TypedParseTree {
     ) -> CompileResult<Vec<TypedAstNode>> {
         let mut warnings = Vec::new();
         let mut errors = Vec::new();
-        let typed_nodes = nodes
-            .into_iter()
-            .map(|node| {
-                TypedAstNode::type_check(TypeCheckArguments {
-                    checkee: node,
-                    namespace,
-                    crate_namespace,
-                    return_type_annotation: insert_type(TypeInfo::Unknown),
-                    help_text: "",
-                    self_type: insert_type(TypeInfo::Contract),
-                    build_config,
-                    dead_code_graph,
-                    mode: Mode::NonAbi,
-                    opts: Default::default(),
-                })
+        let self_type = insert_type(TypeInfo::Contract);
+        let type_check_node = |node: AstNode, dead_code_graph: &mut ControlFlowGraph| {
+            TypedAstNode::type_check(TypeCheckArguments {
+                checkee: node,
+                namespace,
+                crate_namespace,
+                return_type_annotation: insert_type(TypeInfo::Unknown),
+                help_text: "",
+                self_type,
+                build_config,
+                dead_code_graph,
+                mode: Mode::NonAbi,
+                opts: Default::default(),
             })
-            .filter_map(|res| res.ok(&mut warnings, &mut errors))
-            .collect();
+        };
+
+        // Functions called before their declaration, within a cycle of mutually recursive
+        // functions, are declared by their signature alone until their declaration is reached.
+        // The calls made to them meanwhile have an empty body, so the functions which make them
+        // are type checked again once the whole cycle has been declared.
+        let mut undeclared_functions = forward_called_functions
+            .iter()
+            .map(|fn_decl| fn_decl.name.clone())
+            .collect::<Vec<_>>();
+        let mut typed_nodes = Vec::new();
+        let mut nodes_to_recheck = Vec::new();
+        for node in nodes {
+            // The types in a signature may not be declared yet, so this is retried before each
+            // node until it succeeds.
+            forward_called_functions.retain(|fn_decl| {
+                match TypedFunctionDeclaration::type_check_signature(fn_decl, namespace, self_type)
+                    .value
+                {
+                    Some(signature) => {
+                        namespace.insert(
+                            signature.name.clone(),
+                            TypedDeclaration::FunctionDeclaration(signature),
+                        );
+                        false
+                    }
+                    None => true,
+                }
+            });
+            let fn_name = match &node.content {
+                AstNodeContent::Declaration(Declaration::FunctionDeclaration(fn_decl)) => {
+                    Some(fn_decl.name.clone())
+                }
+                _ => None,
+            };
+            let recheck_node = if let Some(fn_name) = &fn_name {
+                undeclared_functions.retain(|name| name != fn_name);
+                !undeclared_functions.is_empty()
+            } else {
+                false
+            };
+            if recheck_node {
+                nodes_to_recheck.push((typed_nodes.len(), node.clone()));
+            }
+            if let Some(typed_node) =
+                type_check_node(node, dead_code_graph).ok(&mut warnings, &mut errors)
+            {
+                typed_nodes.push(typed_node);
+            } else if recheck_node {
+                nodes_to_recheck.pop();
+            }
+        }
 
         if !errors.is_empty() {
-            err(warnings, errors)
-        } else {
-            ok(typed_nodes, warnings, errors)
+            return err(warnings, errors);
+        }
+
+        // Anything wrong with these functions has been reported already.
+        for (ix, node) in nodes_to_recheck {
+            if let Some(typed_node) = type_check_node(node, dead_code_graph).value {
+                typed_nodes[ix] = typed_node;
+            }
         }
+        ok(typed_nodes, warnings, errors)
     }
 
     fn validate_typed_nodes(
@@ -201,9 +331,10 @@ impl TypedParseTree {
             };
         }
 
-        // impure functions are disallowed in non-contracts
+        // impure functions and storage are disallowed in non-contracts
         if *tree_type != TreeType::Contract {
             errors.append(&mut disallow_impure_functions(&declarations, &mains));
+            errors.append(&mut disallow_storage_declarations(&declarations));
         }
 
         // Perform other validation based on the tree type.
@@ -365,3 +496,17 @@ fn disallow_impure_functions(
         })
         .collect()
 }
+
+fn disallow_storage_declarations(declarations: &[TypedDeclaration]) -> Vec<CompileError> {
+    declarations
+        .iter()
+        .filter_map(|decl| match decl {
+            TypedDeclaration::StorageDeclaration(decl) => {
+                Some(CompileError::StorageDeclarationInNonContract {
+                    span: decl.span.clone(),
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
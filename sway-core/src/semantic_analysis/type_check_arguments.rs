@@ -20,4 +20,7 @@ pub struct TypeCheckArguments<'a, T> {
 #[derive(Default, Clone, Copy)]
 pub struct TCOpts {
     pub(crate) purity: Purity,
+    /// Whether the node being checked is inside the body of a loop, where `break` and `continue`
+    /// are allowed.
+    pub(crate) in_loop: bool,
 }
@@ -6,79 +6,209 @@ use serde::{Deserialize, Serialize};
 
 use sway_types::span::Span;
 
+/// The version of the source map format, bumped whenever it changes. Source maps written before
+/// the format was versioned are read as version 0.
+pub const SOURCE_MAP_VERSION: u32 = 1;
+
 /// Index of an interned path string
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct PathIndex(usize);
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SourceMap {
+    /// Version of the format, see [SOURCE_MAP_VERSION].
+    #[serde(default)]
+    version: u32,
     /// Paths of dependencies in the `~/.forc` directory, with the prefix stripped.
     /// This makes inverse source mapping work on any machine with deps downloaded.
     dependency_paths: Vec<PathBuf>,
+    /// Absolute paths of dependencies elsewhere, e.g. path dependencies.
+    #[serde(default)]
+    local_dependency_paths: Vec<PathBuf>,
     /// Paths to source code files, defined separately to avoid repetition.
     paths: Vec<PathBuf>,
     /// Mapping from opcode index to source location
     map: HashMap<usize, SourceMapSpan>,
+    /// Functions, and the calls inlined into them, in the order in which they begin.
+    #[serde(default)]
+    scopes: Vec<Scope>,
+    /// Where source variables are held, in the order in which they come into scope.
+    #[serde(default)]
+    variables: Vec<Variable>,
 }
+
+impl Default for SourceMap {
+    fn default() -> Self {
+        Self {
+            version: SOURCE_MAP_VERSION,
+            dependency_paths: vec![],
+            local_dependency_paths: vec![],
+            paths: vec![],
+            map: HashMap::new(),
+            scopes: vec![],
+            variables: vec![],
+        }
+    }
+}
+
 impl SourceMap {
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Inserts dependency path. Unsupported locations are ignored for now.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Inserts dependency path.
     pub fn insert_dependency<P: AsRef<Path>>(&mut self, path: P) {
+        let path = path.as_ref();
         if let Some(home) = home_dir() {
             let forc = home.join(".forc/");
-            if let Ok(unprefixed) = path.as_ref().strip_prefix(forc) {
+            if let Ok(unprefixed) = path.strip_prefix(forc) {
                 self.dependency_paths.push(unprefixed.to_owned());
+                return;
             }
         }
-        // TODO: Only dependencies in ~/.forc are supported for now
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_owned());
+        if !self.local_dependency_paths.contains(&path) {
+            self.local_dependency_paths.push(path);
+        }
     }
 
     pub fn insert(&mut self, pc: usize, span: &Span) {
-        if let Some(path) = span.path.as_ref() {
-            let path_index = self
-                .paths
-                .iter()
-                .position(|p| *p == **path)
-                .unwrap_or_else(|| {
-                    self.paths.push((**path).to_owned());
-                    self.paths.len() - 1
-                });
-            self.map.insert(
-                pc,
-                SourceMapSpan {
-                    path: PathIndex(path_index),
-                    range: LocationRange {
-                        start: span.start(),
-                        end: span.end(),
-                    },
-                },
-            );
+        if let Some(span) = self.source_map_span(span) {
+            self.map.insert(pc, span);
         }
     }
 
+    /// Inserts the scope of a function spanning the opcodes in `range`, returning its index.
+    pub(crate) fn insert_scope(
+        &mut self,
+        name: String,
+        span: Option<&Span>,
+        call_site: Option<&Span>,
+        parent: Option<usize>,
+        range: PcRange,
+    ) -> usize {
+        let span = span.and_then(|span| self.source_map_span(span));
+        let call_site = call_site.and_then(|span| self.source_map_span(span));
+        self.scopes.push(Scope {
+            name,
+            span,
+            call_site,
+            parent,
+            range,
+        });
+        self.scopes.len() - 1
+    }
+
+    /// Inserts where a variable is held while the opcodes in `range` run.
+    pub(crate) fn insert_variable(
+        &mut self,
+        name: String,
+        span: Option<&Span>,
+        scope: Option<usize>,
+        location: VariableLocation,
+        range: PcRange,
+    ) {
+        let span = span.and_then(|span| self.source_map_span(span));
+        self.variables.push(Variable {
+            name,
+            span,
+            scope,
+            location,
+            range,
+        });
+    }
+
+    fn source_map_span(&mut self, span: &Span) -> Option<SourceMapSpan> {
+        let path = span.path.as_ref()?;
+        let path_index = self
+            .paths
+            .iter()
+            .position(|p| *p == **path)
+            .unwrap_or_else(|| {
+                self.paths.push((**path).to_owned());
+                self.paths.len() - 1
+            });
+        Some(SourceMapSpan {
+            path: PathIndex(path_index),
+            range: LocationRange {
+                start: span.start(),
+                end: span.end(),
+            },
+        })
+    }
+
     /// Inverse source mapping
     pub fn addr_to_span(&self, pc: usize) -> Option<(PathBuf, LocationRange)> {
-        self.map.get(&pc).map(|sms| {
-            let p = &self.paths[sms.path.0];
-            for dep in &self.dependency_paths {
-                if p.starts_with(dep.file_name().unwrap()) {
-                    let mut path = home_dir().expect("Could not get homedir").join(".forc");
+        self.map.get(&pc).map(|sms| self.resolve(sms))
+    }
 
-                    if let Some(dp) = dep.parent() {
-                        path = path.join(dp);
-                    }
+    /// The file and location of a span, with dependency paths made absolute.
+    pub fn resolve(&self, sms: &SourceMapSpan) -> (PathBuf, LocationRange) {
+        let p = &self.paths[sms.path.0];
+        for dep in &self.dependency_paths {
+            if p.starts_with(dep.file_name().unwrap()) {
+                let mut path = home_dir().expect("Could not get homedir").join(".forc");
 
-                    return (path.join(p), sms.range);
+                if let Some(dp) = dep.parent() {
+                    path = path.join(dp);
                 }
+
+                return (path.join(p), sms.range);
             }
+        }
+        for dep in &self.local_dependency_paths {
+            if let (Some(name), Some(parent)) = (dep.file_name(), dep.parent()) {
+                if p.starts_with(name) {
+                    return (parent.join(p), sms.range);
+                }
+            }
+        }
+
+        (p.to_owned(), sms.range)
+    }
 
-            (p.to_owned(), sms.range)
+    /// Inverse source mapping of every mapped opcode index, in no particular order
+    pub fn spans(&self) -> impl Iterator<Item = (usize, PathBuf, LocationRange)> + '_ {
+        self.map.keys().filter_map(move |pc| {
+            let (path, range) = self.addr_to_span(*pc)?;
+            Some((*pc, path, range))
         })
     }
+
+    pub fn scopes(&self) -> &[Scope] {
+        &self.scopes
+    }
+
+    /// The scopes containing an opcode index, innermost first. All but the last of these were
+    /// inlined into the scope following them.
+    pub fn scopes_at(&self, pc: usize) -> Vec<&Scope> {
+        let mut scopes = vec![];
+        let mut current = self
+            .scopes
+            .iter()
+            .rposition(|scope| scope.range.contains(pc));
+        while let Some(ix) = current {
+            scopes.push(&self.scopes[ix]);
+            current = self.scopes[ix].parent;
+        }
+        scopes
+    }
+
+    pub fn variables(&self) -> &[Variable] {
+        &self.variables
+    }
+
+    /// The variables which are held somewhere at an opcode index.
+    pub fn variables_at(&self, pc: usize) -> impl Iterator<Item = &Variable> + '_ {
+        self.variables
+            .iter()
+            .filter(move |variable| variable.range.contains(pc))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -92,3 +222,172 @@ pub struct LocationRange {
     pub start: usize,
     pub end: usize,
 }
+
+/// A range of opcode indices, excluding the end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PcRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl PcRange {
+    pub fn contains(&self, pc: usize) -> bool {
+        self.start <= pc && pc < self.end
+    }
+}
+
+/// The body of a function, either compiled on its own or inlined into another.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scope {
+    pub name: String,
+    /// Where the function is declared.
+    pub span: Option<SourceMapSpan>,
+    /// The call which was replaced by the body, if it was inlined.
+    pub call_site: Option<SourceMapSpan>,
+    /// The index of the scope which the body was inlined into.
+    pub parent: Option<usize>,
+    pub range: PcRange,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Variable {
+    pub name: String,
+    /// Where the variable is declared.
+    pub span: Option<SourceMapSpan>,
+    /// The index of the scope declaring the variable, or `None` for constants.
+    pub scope: Option<usize>,
+    pub location: VariableLocation,
+    /// The opcodes during which the variable is held at its location.
+    pub range: PcRange,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VariableLocation {
+    /// The value is in a register, given by its index in the VM. For values larger than a word,
+    /// the register holds their address instead.
+    Register(u8),
+    /// The value is in memory, `offset` bytes from the address held in a register.
+    Memory { register: u8, offset: u64 },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    const SOURCE: &str = "fn main() { let x = id(1); }";
+
+    fn span(path: &str, start: usize, end: usize) -> Span {
+        Span {
+            span: pest::Span::new(Arc::from(SOURCE), start, end).unwrap(),
+            path: Some(Arc::new(PathBuf::from(path))),
+        }
+    }
+
+    fn round_trip(source_map: &SourceMap) -> SourceMap {
+        serde_json::from_str(&serde_json::to_string(source_map).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn source_maps_round_trip_through_json() {
+        let mut source_map = SourceMap::new();
+        source_map.insert_dependency("/deps/local_lib");
+        source_map.insert(0, &span("src/main.sw", 3, 7));
+        source_map.insert(1, &span("local_lib/src/lib.sw", 20, 25));
+        let main = source_map.insert_scope(
+            "main".into(),
+            Some(&span("src/main.sw", 3, 7)),
+            None,
+            None,
+            PcRange { start: 0, end: 4 },
+        );
+        let id = source_map.insert_scope(
+            "id".into(),
+            Some(&span("local_lib/src/lib.sw", 0, 2)),
+            Some(&span("src/main.sw", 20, 25)),
+            Some(main),
+            PcRange { start: 1, end: 3 },
+        );
+        source_map.insert_variable(
+            "x".into(),
+            Some(&span("src/main.sw", 16, 17)),
+            Some(main),
+            VariableLocation::Register(16),
+            PcRange { start: 3, end: 4 },
+        );
+        source_map.insert_variable(
+            "y".into(),
+            None,
+            Some(id),
+            VariableLocation::Memory {
+                register: 17,
+                offset: 8,
+            },
+            PcRange { start: 1, end: 3 },
+        );
+
+        let read = round_trip(&source_map);
+        assert_eq!(read.version(), SOURCE_MAP_VERSION);
+        assert_eq!(
+            serde_json::to_value(&read).unwrap(),
+            serde_json::to_value(&source_map).unwrap()
+        );
+
+        let (path, range) = read.addr_to_span(0).unwrap();
+        assert_eq!(path, PathBuf::from("src/main.sw"));
+        assert_eq!((range.start, range.end), (3, 7));
+        // paths within a local dependency are made absolute
+        let (path, _) = read.addr_to_span(1).unwrap();
+        assert_eq!(path, PathBuf::from("/deps/local_lib/src/lib.sw"));
+        assert!(read.addr_to_span(2).is_none());
+
+        let names = |scopes: Vec<&Scope>| {
+            scopes
+                .iter()
+                .map(|scope| scope.name.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(read.scopes_at(2)), ["id", "main"]);
+        assert_eq!(names(read.scopes_at(3)), ["main"]);
+        assert!(read.scopes_at(4).is_empty());
+        let (path, range) = read.resolve(read.scopes()[id].call_site.as_ref().unwrap());
+        assert_eq!(path, PathBuf::from("src/main.sw"));
+        assert_eq!((range.start, range.end), (20, 25));
+
+        let held = |pc| {
+            read.variables_at(pc)
+                .map(|variable| (variable.name.as_str(), variable.location))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            held(1),
+            [(
+                "y",
+                VariableLocation::Memory {
+                    register: 17,
+                    offset: 8
+                }
+            )]
+        );
+        assert_eq!(held(3), [("x", VariableLocation::Register(16))]);
+    }
+
+    #[test]
+    fn unversioned_source_maps_can_be_read() {
+        let read: SourceMap = serde_json::from_str(
+            r#"{
+                "dependency_paths": [],
+                "paths": ["src/main.sw"],
+                "map": { "0": { "path": 0, "range": { "start": 3, "end": 7 } } }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(read.version(), 0);
+        let (path, range) = read.addr_to_span(0).unwrap();
+        assert_eq!(path, PathBuf::from("src/main.sw"));
+        assert_eq!((range.start, range.end), (3, 7));
+        assert!(read.scopes().is_empty());
+        assert!(read.variables().is_empty());
+    }
+}
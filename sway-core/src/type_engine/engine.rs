@@ -76,7 +76,12 @@ impl Engine {
             (
                 ref received_info @ UnsignedInteger(received_width),
                 ref expected_info @ UnsignedInteger(expected_width),
-            ) => {
+            ) if received_width == expected_width
+                || !(received_width.is_wide() || expected_width.is_wide()) =>
+            {
+                // Integers up to 64 bits all live in a single register and can be cast to one
+                // another, whereas `u128` and `u256` are laid out in memory and only unify with
+                // themselves.
                 // E.g., in a variable declaration `let a: u32 = 10u64` the 'expected' type will be
                 // the annotation `u32`, and the 'received' type is 'self' of the initialiser, or
                 // `u64`.  So we're casting received TO expected.
@@ -172,13 +177,27 @@ impl Engine {
                     span: span.clone(),
                 }),
 
+            (
+                Function {
+                    parameters: a_params,
+                    return_type: a_return_type,
+                },
+                Function {
+                    parameters: b_params,
+                    return_type: b_return_type,
+                },
+            ) if a_params.len() == b_params.len() => {
+                let mut warnings = vec![];
+                for (a_param, b_param) in a_params.iter().zip(b_params.iter()) {
+                    warnings.extend(self.unify(*a_param, *b_param, span)?);
+                }
+                warnings.extend(self.unify(a_return_type, b_return_type, span)?);
+                Ok(warnings)
+            }
+
             // When unifying complex types, we must check their sub-types. This
             // can be trivially implemented for tuples, sum types, etc.
             // (List(a_item), List(b_item)) => self.unify(a_item, b_item),
-            // this can be used for curried function types but we might not want that
-            // (Func(a_i, a_o), Func(b_i, b_o)) => {
-            //     self.unify(a_i, b_i).and_then(|_| self.unify(a_o, b_o))
-            // }
 
             // If no previous attempts to unify were successful, raise an error
             (the_received, the_expected) => match (the_received, the_expected) {
@@ -229,7 +248,7 @@ pub fn insert_type(ty: TypeInfo) -> TypeId {
     TYPE_ENGINE.insert_type(ty)
 }
 
-pub(crate) fn look_up_type_id(id: TypeId) -> TypeInfo {
+pub fn look_up_type_id(id: TypeId) -> TypeInfo {
     TYPE_ENGINE.look_up_type_id(id)
 }
 
@@ -4,6 +4,8 @@ pub enum IntegerBits {
     Sixteen,
     ThirtyTwo,
     SixtyFour,
+    OneTwentyEight,
+    TwoFiftySix,
 }
 
 impl IntegerBits {
@@ -14,6 +16,36 @@ impl IntegerBits {
             Sixteen => "sixteen",
             ThirtyTwo => "thirty two",
             SixtyFour => "sixty four",
+            OneTwentyEight => "one hundred twenty eight",
+            TwoFiftySix => "two hundred fifty six",
         }
     }
+
+    pub(crate) fn num_bits(&self) -> u16 {
+        use IntegerBits::*;
+        match self {
+            Eight => 8,
+            Sixteen => 16,
+            ThirtyTwo => 32,
+            SixtyFour => 64,
+            OneTwentyEight => 128,
+            TwoFiftySix => 256,
+        }
+    }
+
+    /// Integers that fit in a register take a single word, wider integers are stored in memory as
+    /// big-endian words, most significant word first, the same way a `b256` is laid out.
+    pub(crate) fn size_in_words(&self) -> u64 {
+        match self {
+            IntegerBits::OneTwentyEight => 2,
+            IntegerBits::TwoFiftySix => 4,
+            _ => 1,
+        }
+    }
+
+    /// Whether values of this width are too big for a register and are passed around by
+    /// reference.
+    pub(crate) fn is_wide(&self) -> bool {
+        self.size_in_words() > 1
+    }
 }
@@ -64,6 +64,11 @@ pub enum TypeInfo {
     ErrorRecovery,
     // Static, constant size arrays.
     Array(TypeId, usize),
+    /// The type of a function value, e.g. `fn(u64) -> bool`.
+    Function {
+        parameters: Vec<TypeId>,
+        return_type: TypeId,
+    },
 }
 
 impl Default for TypeInfo {
@@ -118,6 +123,8 @@ impl TypeInfo {
                 "u16" => TypeInfo::UnsignedInteger(IntegerBits::Sixteen),
                 "u32" => TypeInfo::UnsignedInteger(IntegerBits::ThirtyTwo),
                 "u64" => TypeInfo::UnsignedInteger(IntegerBits::SixtyFour),
+                "u128" => TypeInfo::UnsignedInteger(IntegerBits::OneTwentyEight),
+                "u256" => TypeInfo::UnsignedInteger(IntegerBits::TwoFiftySix),
                 "bool" => TypeInfo::Boolean,
                 "unit" => TypeInfo::Tuple(Vec::new()),
                 "byte" => TypeInfo::Byte,
@@ -197,6 +204,34 @@ impl TypeInfo {
                 };
                 TypeInfo::Array(insert_type(elem_type_info), elem_count)
             }
+            Rule::fn_type => {
+                let mut parameters = vec![];
+                let mut return_type = TypeInfo::Tuple(Vec::new());
+                let mut is_return_type = false;
+                for pair in input.into_inner() {
+                    match pair.as_rule() {
+                        Rule::fn_decl_keyword => (),
+                        Rule::fn_returns => is_return_type = true,
+                        _ => {
+                            let ty = check!(
+                                TypeInfo::parse_from_pair(pair, config),
+                                TypeInfo::ErrorRecovery,
+                                warnings,
+                                errors
+                            );
+                            if is_return_type {
+                                return_type = ty;
+                            } else {
+                                parameters.push(insert_type(ty));
+                            }
+                        }
+                    }
+                }
+                TypeInfo::Function {
+                    parameters,
+                    return_type: insert_type(return_type),
+                }
+            }
             Rule::tuple_type => {
                 let mut field_type_ids = vec![];
                 for field in input.into_inner() {
@@ -222,7 +257,7 @@ impl TypeInfo {
         ok(type_info, warnings, errors)
     }
 
-    pub(crate) fn friendly_type_str(&self) -> String {
+    pub fn friendly_type_str(&self) -> String {
         use TypeInfo::*;
         match self {
             Unknown => "unknown".into(),
@@ -233,6 +268,8 @@ impl TypeInfo {
                 IntegerBits::Sixteen => "u16",
                 IntegerBits::ThirtyTwo => "u32",
                 IntegerBits::SixtyFour => "u64",
+                IntegerBits::OneTwentyEight => "u128",
+                IntegerBits::TwoFiftySix => "u256",
             }
             .into(),
             Boolean => "bool".into(),
@@ -265,6 +302,13 @@ impl TypeInfo {
                 format!("contract caller {}", abi_name.suffix)
             }
             Array(elem_ty, count) => format!("[{}; {}]", elem_ty.friendly_type_str(), count),
+            Function {
+                parameters,
+                return_type,
+            } => print_function_type(
+                parameters.iter().map(|x| x.friendly_type_str()),
+                return_type.friendly_type_str(),
+            ),
         }
     }
 
@@ -279,6 +323,8 @@ impl TypeInfo {
                 IntegerBits::Sixteen => "u16",
                 IntegerBits::ThirtyTwo => "u32",
                 IntegerBits::SixtyFour => "u64",
+                IntegerBits::OneTwentyEight => "u128",
+                IntegerBits::TwoFiftySix => "u256",
             }
             .into(),
             Boolean => "bool".into(),
@@ -307,6 +353,13 @@ impl TypeInfo {
                 format!("contract caller {}", abi_name.suffix)
             }
             Array(elem_ty, count) => format!("[{}; {}]", elem_ty.json_abi_str(), count),
+            Function {
+                parameters,
+                return_type,
+            } => print_function_type(
+                parameters.iter().map(|x| x.json_abi_str()),
+                return_type.json_abi_str(),
+            ),
         }
     }
 
@@ -322,6 +375,8 @@ impl TypeInfo {
                     Sixteen => "u16",
                     ThirtyTwo => "u32",
                     SixtyFour => "u64",
+                    OneTwentyEight => "u128",
+                    TwoFiftySix => "u256",
                 }
                 .into()
             }
@@ -417,8 +472,9 @@ impl TypeInfo {
             // Each char is a byte, so the size is the num of characters / 8
             // rounded up to the nearest word
             TypeInfo::Str(len) => Ok((len + 7) / 8),
-            // Since things are unpacked, all unsigned integers are 64 bits.....for now
-            TypeInfo::UnsignedInteger(_) | TypeInfo::Numeric => Ok(1),
+            // Since things are unpacked, all unsigned integers up to 64 bits take a whole word
+            TypeInfo::UnsignedInteger(bits) => Ok(bits.size_in_words()),
+            TypeInfo::Numeric => Ok(1),
             TypeInfo::Boolean => Ok(1),
             TypeInfo::Tuple(fields) => Ok(fields
                 .iter()
@@ -469,11 +525,14 @@ impl TypeInfo {
             TypeInfo::Array(elem_ty, count) => {
                 Ok(look_up_type_id(*elem_ty).size_in_words(err_span)? * *count as u64)
             }
+            // function values are represented by their index in the program's function table
+            TypeInfo::Function { .. } => Ok(1),
         }
     }
     pub(crate) fn is_copy_type(&self) -> bool {
         match self {
-            TypeInfo::UnsignedInteger(_) | TypeInfo::Boolean | TypeInfo::Byte => true,
+            TypeInfo::UnsignedInteger(bits) => !bits.is_wide(),
+            TypeInfo::Boolean | TypeInfo::Byte | TypeInfo::Function { .. } => true,
             TypeInfo::Tuple(fields) => fields
                 .iter()
                 .all(|field_type| look_up_type_id(*field_type).is_copy_type()),
@@ -550,6 +609,54 @@ impl TypeInfo {
         }
     }
 
+    /// Whether a value of this type could be passed where `other` is expected, without actually
+    /// unifying the two. Types which are not known yet are assumed to be compatible. This is used
+    /// to choose between methods of the same name, e.g. from `impl From<u64>` and
+    /// `impl From<bool>` for the same type.
+    pub(crate) fn is_compatible_with(&self, other: &TypeInfo) -> bool {
+        use TypeInfo::*;
+        match (self, other) {
+            (Unknown, _)
+            | (_, Unknown)
+            | (UnknownGeneric { .. }, _)
+            | (_, UnknownGeneric { .. })
+            | (ErrorRecovery, _)
+            | (_, ErrorRecovery) => true,
+            (Numeric, UnsignedInteger(_) | Byte | Numeric)
+            | (UnsignedInteger(_) | Byte, Numeric) => true,
+            (Tuple(fields_a), Tuple(fields_b)) => {
+                fields_a.len() == fields_b.len()
+                    && fields_a
+                        .iter()
+                        .zip(fields_b.iter())
+                        .all(|(a, b)| look_up_type_id(*a).is_compatible_with(&look_up_type_id(*b)))
+            }
+            (
+                Function {
+                    parameters: params_a,
+                    return_type: return_type_a,
+                },
+                Function {
+                    parameters: params_b,
+                    return_type: return_type_b,
+                },
+            ) => {
+                params_a.len() == params_b.len()
+                    && params_a
+                        .iter()
+                        .zip(params_b.iter())
+                        .all(|(a, b)| look_up_type_id(*a).is_compatible_with(&look_up_type_id(*b)))
+                    && look_up_type_id(*return_type_a)
+                        .is_compatible_with(&look_up_type_id(*return_type_b))
+            }
+            (Array(elem_a, len_a), Array(elem_b, len_b)) => {
+                len_a == len_b
+                    && look_up_type_id(*elem_a).is_compatible_with(&look_up_type_id(*elem_b))
+            }
+            (a, b) => a == b,
+        }
+    }
+
     pub(crate) fn matches_type_parameter(
         &self,
         mapping: &[(TypeParameter, TypeId)],
@@ -604,6 +711,34 @@ impl TypeInfo {
                     name: name.clone(),
                 }))
             }
+            TypeInfo::Function {
+                parameters,
+                return_type,
+            } => {
+                let new_parameters = parameters
+                    .iter()
+                    .map(|param| look_up_type_id(*param).matches_type_parameter(mapping))
+                    .collect::<Vec<_>>();
+                let new_return_type = look_up_type_id(*return_type).matches_type_parameter(mapping);
+                if new_return_type.is_none() && new_parameters.iter().all(Option::is_none) {
+                    None
+                } else {
+                    Some(insert_type(TypeInfo::Function {
+                        parameters: parameters
+                            .iter()
+                            .zip(new_parameters)
+                            .map(|(old, new)| match new {
+                                Some(new) => insert_type(TypeInfo::Ref(new)),
+                                None => *old,
+                            })
+                            .collect(),
+                        return_type: match new_return_type {
+                            Some(new) => insert_type(TypeInfo::Ref(new)),
+                            None => *return_type,
+                        },
+                    }))
+                }
+            }
             TypeInfo::Array(ary_ty_id, count) => look_up_type_id(*ary_ty_id)
                 .matches_type_parameter(mapping)
                 .map(|matching_id| insert_type(TypeInfo::Array(matching_id, *count))),
@@ -652,6 +787,14 @@ impl TypeInfo {
     }
 }
 
+fn print_function_type(parameters: impl Iterator<Item = String>, return_type: String) -> String {
+    format!(
+        "fn({}) -> {}",
+        parameters.collect::<Vec<_>>().join(", "),
+        return_type
+    )
+}
+
 fn print_inner_types(name: String, inner_types: impl Iterator<Item = TypeId>) -> String {
     format!(
         "{}<{}>",
@@ -60,8 +60,8 @@ impl ResolvedType {
             // Each char is a byte, so the size is the num of characters / 8
             // rounded up to the nearest word
             ResolvedType::Str(len) => (len + 7) / 8,
-            // Since things are unpacked, all unsigned integers are 64 bits.....for now
-            ResolvedType::UnsignedInteger(_) => 1,
+            // Since things are unpacked, all unsigned integers up to 64 bits take a whole word
+            ResolvedType::UnsignedInteger(bits) => bits.size_in_words(),
             ResolvedType::Boolean => 1,
             ResolvedType::Unit => 0,
             ResolvedType::Byte => 1,
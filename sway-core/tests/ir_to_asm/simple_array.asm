@@ -24,7 +24,6 @@ lw   $r0 data_4               ; literal instantiation
 muli $r0 $r0 i8               ; insert_element relative offset
 add  $r0 $r2 $r0              ; insert_element absolute offset
 sw   $r0 $r1 i0               ; insert_element
-addi $r0 $r3 i0               ; get_ptr
 addi $r0 $r3 i0               ; get store offset
 mcpi $r0 $r2 i24              ; store value
 addi $r1 $r3 i0               ; get_ptr
@@ -33,7 +32,6 @@ muli $r0 $r0 i8               ; extract_element relative offset
 add  $r0 $r1 $r0              ; extract_element absolute offset
 lw   $r0 $r0 i0               ; extract_element
 ret  $r0
-noop                          ; word-alignment of data section
 .data:
 data_0 .bool 0x00
 data_1 .u64 0x00
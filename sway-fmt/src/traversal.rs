@@ -149,6 +149,15 @@ fn handle_expression(expr: &Expression, changes: &mut Vec<Change>) {
                 handle_expression(else_expr, changes);
             }
         }
+        Expression::MatchExpression {
+            primary_expression: _,
+            branches,
+            span: _,
+        } => {
+            for branch in branches {
+                handle_expression(&branch.result, changes);
+            }
+        }
         Expression::CodeBlock { contents, span: _ } => {
             for content in &contents.contents {
                 traverse_ast_node(content, changes);
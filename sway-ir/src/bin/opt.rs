@@ -3,7 +3,7 @@ use std::{
     io::{BufReader, BufWriter, Error, ErrorKind, Read, Write},
 };
 
-use sway_ir::{error::IrError, function::Function, optimize, Context};
+use sway_ir::pass_manager::PassManager;
 
 // -------------------------------------------------------------------------------------------------
 
@@ -22,12 +22,18 @@ fn main() -> std::io::Result<()> {
     let mut ir = sway_ir::parser::parse(&input_str).map_err(&to_err)?;
 
     // Perform optimisation passes in order.
-    for pass in config.passes {
-        match pass.name.as_ref() {
-            "inline" => perform_inline(&mut ir).map_err(&to_err)?,
-            "constcombine" => perform_combine_constants(&mut ir).map_err(&to_err)?,
-            _otherwise => unreachable!("Unknown pass name: {}", pass.name),
-        };
+    let mut pass_mgr = PassManager::default();
+    pass_mgr.set_verify_each(config.verify_each);
+    pass_mgr.set_time_passes(config.time_passes);
+    for step in config.steps {
+        match step {
+            Step::Pass(pass) => pass_mgr.run_pass(&mut ir, &pass.name),
+            Step::Pipeline(name) => pass_mgr.run_pipeline(&mut ir, &name),
+        }
+        .map_err(&to_err)?;
+    }
+    if config.time_passes {
+        eprint!("{}", pass_mgr.timings_report());
     }
 
     // Write the output file or standard out.
@@ -63,31 +69,6 @@ fn write_to_output<S: Into<String>>(ir_str: S, path_str: &Option<String>) -> std
     }
 }
 
-// -------------------------------------------------------------------------------------------------
-
-fn perform_inline(ir: &mut Context) -> Result<bool, IrError> {
-    // For now we inline everything into `main()`.  Eventually we can be more selective.
-    let main_fn = ir
-        .functions
-        .iter()
-        .find_map(|(idx, fc)| if fc.name == "main" { Some(idx) } else { None })
-        .unwrap();
-    optimize::inline_all_function_calls(ir, &Function(main_fn))
-}
-
-// -------------------------------------------------------------------------------------------------
-
-fn perform_combine_constants(ir: &mut Context) -> Result<bool, IrError> {
-    let funcs = ir.functions.iter().map(|(idx, _)| idx).collect::<Vec<_>>();
-    let mut modified = false;
-    for idx in funcs {
-        if optimize::combine_constants(ir, &Function(idx))? {
-            modified = true;
-        }
-    }
-    Ok(modified)
-}
-
 // -------------------------------------------------------------------------------------------------
 // Using a bespoke CLI parser since the order in which passes are specified is important.
 
@@ -96,11 +77,17 @@ struct Config {
     input_path: Option<String>,
     output_path: Option<String>,
 
-    _verify_each: bool,
-    _time_passes: bool,
+    verify_each: bool,
+    time_passes: bool,
     _stats: bool,
 
-    passes: Vec<Pass>,
+    steps: Vec<Step>,
+}
+
+// Either a single pass or a whole named pipeline, e.g., `-O1`.
+enum Step {
+    Pass(Pass),
+    Pipeline(String),
 }
 
 #[derive(Default)]
@@ -148,8 +135,27 @@ impl<I: Iterator<Item = String>> ConfigBuilder<I> {
                     "-i" => self.build_input(),
                     "-o" => self.build_output(),
 
-                    "inline" => self.build_inline_pass(),
-                    "constcombine" => self.build_const_combine_pass(),
+                    "-verify-each" => {
+                        self.cfg.verify_each = true;
+                        self.build_root()
+                    }
+                    "-time-passes" => {
+                        self.cfg.time_passes = true;
+                        self.build_root()
+                    }
+
+                    "-O0" | "-O1" | "-O2" => {
+                        self.cfg.steps.push(Step::Pipeline(opt[1..].to_owned()));
+                        self.build_root()
+                    }
+
+                    // Any other word is the name of a pass, which the pass manager will check.
+                    _ if !opt.starts_with('-') => {
+                        // No args yet.  Eventually we should allow specifying which functions
+                        // the pass should be run on.
+                        self.cfg.steps.push(Step::Pass(opt.as_str().into()));
+                        self.build_root()
+                    }
 
                     _otherwise => Err(format!("Unrecognised option '{}'.", opt)),
                 }
@@ -178,22 +184,6 @@ impl<I: Iterator<Item = String>> ConfigBuilder<I> {
             }
         }
     }
-
-    fn build_inline_pass(mut self) -> Result<Config, String> {
-        // No args yet.  Eventually we should allow specifying which functions are to be inlined
-        // or which functions are to have all embedded calls inlined.
-        self.cfg.passes.push("inline".into());
-        self.next = self.rest.next();
-        self.build_root()
-    }
-
-    fn build_const_combine_pass(mut self) -> Result<Config, String> {
-        // No args yet.  Eventually we should allow specifying which functions should have consts
-        // combined.
-        self.cfg.passes.push("constcombine".into());
-        self.next = self.rest.next();
-        self.build_root()
-    }
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -4,7 +4,7 @@
 //! instruction or _terminator_.  Terminators are either branches or a return instruction and are
 //! the last instruction in the block.
 //!
-//! Blocks also contain a single 'phi' instruction at its start.  In
+//! Blocks also contain one or more 'phi' instructions at their start.  In
 //! [SSA](https://en.wikipedia.org/wiki/Static_single_assignment_form) form 'phi' instructions are
 //! used to merge values from preceding blocks.
 //!
@@ -15,6 +15,7 @@ use crate::{
     error::IrError,
     function::Function,
     instruction::{Instruction, InstructionInserter, InstructionIterator},
+    irtype::Type,
     value::{Value, ValueDatum},
 };
 
@@ -40,7 +41,7 @@ impl Block {
     /// is optional and is used only when printing the IR.
     pub fn new(context: &mut Context, function: Function, label: Option<String>) -> Block {
         let label = function.get_unique_label(context, label);
-        let phi = Value::new_instruction(context, Instruction::Phi(Vec::new()), None);
+        let phi = Value::new_instruction(context, Instruction::Phi(Vec::new(), Type::Unit), None);
         let content = BlockContent {
             label,
             function,
@@ -66,21 +67,68 @@ impl Block {
     }
 
     /// Get the phi instruction for this block.
+    ///
+    /// This is the first of the block's phis, which every block has and which receives the values
+    /// passed by branch instructions as they are created.
     pub fn get_phi(&self, context: &Context) -> Value {
         context.blocks[self.0].instructions[0]
     }
 
+    /// Get all of the phi instructions for this block, which are always at its start.
+    pub fn get_phis(&self, context: &Context) -> Vec<Value> {
+        context.blocks[self.0]
+            .instructions
+            .iter()
+            .take_while(|ins_val| {
+                matches!(
+                    context.values[ins_val.0].value,
+                    ValueDatum::Instruction(Instruction::Phi(..))
+                )
+            })
+            .copied()
+            .collect()
+    }
+
+    /// Create a new empty phi instruction of type `ty` following the existing phis in this block.
+    pub fn new_phi(&self, context: &mut Context, ty: Type) -> Value {
+        let phi_val = Value::new_instruction(context, Instruction::Phi(Vec::new(), ty), None);
+        let num_phis = self.get_phis(context).len();
+        context.blocks[self.0]
+            .instructions
+            .insert(num_phis, phi_val);
+        phi_val
+    }
+
     /// Add a new phi entry to this block.
     ///
     /// This indicates that if control flow comes from `from_block` then the phi instruction should
     /// use `phi_value`.
     pub fn add_phi(&self, context: &mut Context, from_block: Block, phi_value: Value) {
         let phi_val = self.get_phi(context);
+        self.add_to_phi(context, phi_val, from_block, phi_value);
+    }
+
+    /// Add a new entry to a specific phi instruction in this block.
+    ///
+    /// If the phi still has the `Unit` type of an empty phi then it takes the type of `phi_value`.
+    pub fn add_to_phi(
+        &self,
+        context: &mut Context,
+        phi_val: Value,
+        from_block: Block,
+        phi_value: Value,
+    ) {
+        let value_ty = phi_value.get_type(context);
         match &mut context.values[phi_val.0].value {
-            ValueDatum::Instruction(Instruction::Phi(list)) => {
+            ValueDatum::Instruction(Instruction::Phi(list, ty)) => {
+                if *ty == Type::Unit {
+                    if let Some(value_ty) = value_ty {
+                        *ty = value_ty;
+                    }
+                }
                 list.push((from_block, phi_value));
             }
-            _ => unreachable!("First value in block instructions is not a phi."),
+            _ => unreachable!("Phi value must be a PHI instruction."),
         }
     }
 
@@ -89,39 +137,65 @@ impl Block {
     /// Returns `None` if `from_block` isn't found.
     pub fn get_phi_val_coming_from(&self, context: &Context, from_block: &Block) -> Option<Value> {
         let phi_val = self.get_phi(context);
-        if let ValueDatum::Instruction(Instruction::Phi(pairs)) = &context.values[phi_val.0].value {
-            pairs.iter().find_map(|(block, value)| {
+        get_phi_pairs(context, &phi_val)
+            .iter()
+            .find_map(|(block, value)| {
                 if block == from_block {
                     Some(*value)
                 } else {
                     None
                 }
             })
-        } else {
-            unreachable!("Phi value must be a PHI instruction.");
-        }
     }
 
-    /// Replace a block reference in the phi instruction.
+    /// Get each of the phi instructions in this block which has a value correlating to
+    /// `from_block`, paired with that value.
+    pub fn get_phi_vals_coming_from(
+        &self,
+        context: &Context,
+        from_block: &Block,
+    ) -> Vec<(Value, Value)> {
+        self.get_phis(context)
+            .into_iter()
+            .filter_map(|phi_val| {
+                get_phi_pairs(context, &phi_val)
+                    .iter()
+                    .find(|(block, _)| block == from_block)
+                    .map(|(_, value)| (phi_val, *value))
+            })
+            .collect()
+    }
+
+    /// Replace a block reference in the phi instructions.
     ///
-    /// Any reference to `old_source` will be replace with `new_source` in the list of phi values.
+    /// Any reference to `old_source` will be replace with `new_source` in the lists of phi values.
     pub fn update_phi_source_block(
         &self,
         context: &mut Context,
         old_source: Block,
         new_source: Block,
     ) {
-        let phi_val = self.get_phi(context);
-        if let ValueDatum::Instruction(Instruction::Phi(ref mut pairs)) =
-            &mut context.values[phi_val.0].value
-        {
-            for (block, _) in pairs {
-                if *block == old_source {
-                    *block = new_source;
+        for phi_val in self.get_phis(context) {
+            if let ValueDatum::Instruction(Instruction::Phi(ref mut pairs, _)) =
+                &mut context.values[phi_val.0].value
+            {
+                for (block, _) in pairs {
+                    if *block == old_source {
+                        *block = new_source;
+                    }
                 }
             }
-        } else {
-            unreachable!("Phi value must be a PHI instruction.");
+        }
+    }
+
+    /// Remove the entries in the phi instructions which correlate to `from_block`, if found.
+    pub fn remove_phi_val_coming_from(&self, context: &mut Context, from_block: &Block) {
+        for phi_val in self.get_phis(context) {
+            if let ValueDatum::Instruction(Instruction::Phi(ref mut pairs, _)) =
+                &mut context.values[phi_val.0].value
+            {
+                pairs.retain(|(block, _)| block != from_block);
+            }
         }
     }
 
@@ -139,6 +213,43 @@ impl Block {
         })
     }
 
+    /// Return the blocks this block may branch to, in the order they're referred to by its
+    /// terminator.
+    pub fn successors(&self, context: &Context) -> Vec<Block> {
+        match self.get_term_inst(context) {
+            Some(Instruction::Branch(to_block)) => vec![*to_block],
+            Some(Instruction::ConditionalBranch {
+                true_block,
+                false_block,
+                ..
+            }) => vec![*true_block, *false_block],
+            _otherwise => Vec::new(),
+        }
+    }
+
+    /// Replace every reference to `old_block` in this block's terminator with `new_block`.
+    pub fn replace_successor(&self, context: &mut Context, old_block: Block, new_block: Block) {
+        let replace = |block: &mut Block| {
+            if *block == old_block {
+                *block = new_block;
+            }
+        };
+        if let Some(term_val) = context.blocks[self.0].instructions.last().copied() {
+            match &mut context.values[term_val.0].value {
+                ValueDatum::Instruction(Instruction::Branch(to_block)) => replace(to_block),
+                ValueDatum::Instruction(Instruction::ConditionalBranch {
+                    true_block,
+                    false_block,
+                    ..
+                }) => {
+                    replace(true_block);
+                    replace(false_block);
+                }
+                _otherwise => (),
+            }
+        }
+    }
+
     /// Replace a value within this block.
     ///
     /// For every instruction within the block, any reference to `old_val` is replaced with
@@ -281,3 +392,10 @@ impl Iterator for BlockIterator {
         }
     }
 }
+
+fn get_phi_pairs<'a>(context: &'a Context, phi_val: &Value) -> &'a [(Block, Value)] {
+    match &context.values[phi_val.0].value {
+        ValueDatum::Instruction(Instruction::Phi(pairs, _)) => pairs,
+        _ => unreachable!("Phi value must be a PHI instruction."),
+    }
+}
@@ -21,6 +21,8 @@ pub enum ConstantValue {
     Unit,
     Bool(bool),
     Uint(u64),
+    /// Integers wider than 64 bits, as big-endian bytes zero extended to 256 bits.
+    WideUint([u8; 32]),
     B256([u8; 32]),
     String(String),
     Array(Vec<Constant>),
@@ -64,13 +66,21 @@ impl Constant {
         }
     }
 
-    pub fn new_uint(nbits: u8, n: u64) -> Self {
+    pub fn new_uint(nbits: u16, n: u64) -> Self {
         Constant {
             ty: Type::Uint(nbits),
             value: ConstantValue::Uint(n),
         }
     }
 
+    /// `bytes` holds the big-endian value zero extended to 256 bits, whatever `nbits` is.
+    pub fn new_wide_uint(nbits: u16, bytes: [u8; 32]) -> Self {
+        Constant {
+            ty: Type::Uint(nbits),
+            value: ConstantValue::WideUint(bytes),
+        }
+    }
+
     pub fn new_b256(bytes: [u8; 32]) -> Self {
         Constant {
             ty: Type::B256,
@@ -118,13 +128,22 @@ impl Constant {
 
     pub fn get_uint(
         context: &mut Context,
-        nbits: u8,
+        nbits: u16,
         value: u64,
         span_md_idx: Option<MetadataIndex>,
     ) -> Value {
         Value::new_constant(context, Constant::new_uint(nbits, value), span_md_idx)
     }
 
+    pub fn get_wide_uint(
+        context: &mut Context,
+        nbits: u16,
+        value: [u8; 32],
+        span_md_idx: Option<MetadataIndex>,
+    ) -> Value {
+        Value::new_constant(context, Constant::new_wide_uint(nbits, value), span_md_idx)
+    }
+
     pub fn get_b256(
         context: &mut Context,
         value: [u8; 32],
@@ -1,13 +1,22 @@
 #[derive(Debug)]
 pub enum IrError {
+    DuplicatePass(String),
     FunctionLocalClobbered(String, String),
     InvalidMetadatum,
     MismatchedReturnTypes(String),
+    MismatchedOperandTypes(String),
+    MismatchedPhiTypes(String),
+    MisplacedPhi(String),
     MisplacedTerminator(String),
     MissingBlock(String),
+    MissingCallees,
     MissingTerminator(String),
+    NonIntegerOperand(String),
     NonUniquePhiLabels,
+    PhiFromNonPredecessor(String, String),
     ParseFailure(String, String),
+    UnknownPass(String),
+    UnknownPipeline(String),
     ValueNotFound(String),
 }
 
@@ -16,6 +25,9 @@ use std::fmt;
 impl fmt::Display for IrError {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         match self {
+            IrError::DuplicatePass(pass_str) => {
+                write!(f, "A pass named '{pass_str}' is already registered.")
+            }
             IrError::FunctionLocalClobbered(fn_str, var_str) => write!(
                 f,
                 "Local storage for function {fn_str} already has an entry for variable {var_str}"
@@ -25,17 +37,41 @@ impl fmt::Display for IrError {
                 f,
                 "Function {fn_str} return type must match its RET instructions."
             ),
+            IrError::MismatchedOperandTypes(op_str) => {
+                write!(f, "Operands to '{op_str}' must have the same type.")
+            }
+            IrError::MismatchedPhiTypes(blk_str) => write!(
+                f,
+                "PHI in block {blk_str} has incoming values which don't match its type."
+            ),
+            IrError::MisplacedPhi(blk_str) => {
+                write!(f, "Block {blk_str} has a PHI after a non-PHI instruction.")
+            }
             IrError::MisplacedTerminator(blk_str) => {
                 write!(f, "Block {blk_str} has a misplaced terminator.")
             }
             IrError::MissingBlock(blk_str) => write!(f, "Unable to find block {blk_str}."),
+            IrError::MissingCallees => {
+                write!(f, "Indirect call must have at least one possible callee.")
+            }
             IrError::MissingTerminator(blk_str) => {
                 write!(f, "Block {blk_str} is missing its terminator.")
             }
+            IrError::NonIntegerOperand(op_str) => {
+                write!(f, "Operands to '{op_str}' must be integers.")
+            }
             IrError::NonUniquePhiLabels => write!(f, "PHI must have unique block labels."),
+            IrError::PhiFromNonPredecessor(blk_str, from_str) => write!(
+                f,
+                "PHI in block {blk_str} has a value from block {from_str} which is not a predecessor."
+            ),
             IrError::ParseFailure(expecting, found) => {
                 write!(f, "Parse failure: expecting '{expecting}', found '{found}'")
             }
+            IrError::UnknownPass(pass_str) => write!(f, "Unknown pass '{pass_str}'."),
+            IrError::UnknownPipeline(pipeline_str) => {
+                write!(f, "Unknown pass pipeline '{pipeline_str}'.")
+            }
             IrError::ValueNotFound(reason) => {
                 write!(f, "Invalid value: {reason}")
             }
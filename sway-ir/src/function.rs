@@ -145,6 +145,39 @@ impl Function {
             })
     }
 
+    /// Move an existing [`Block`] in this function to be its last block.
+    pub fn move_block_to_end(&self, context: &mut Context, block: &Block) -> Result<(), IrError> {
+        let func = context.functions.get_mut(self.0).unwrap();
+        match func.blocks.iter().position(|other| other == block) {
+            Some(idx) => {
+                let block = func.blocks.remove(idx);
+                func.blocks.push(block);
+                Ok(())
+            }
+            None => Err(IrError::MissingBlock(context.blocks[block.0].label.clone())),
+        }
+    }
+
+    /// Remove a [`Block`] from this function, along with all of its instructions.
+    ///
+    /// The caller must ensure the block is no longer referred to by any branch or phi instruction,
+    /// and that none of its instructions are used outside of it.
+    pub fn remove_block(&self, context: &mut Context, block: &Block) -> Result<(), IrError> {
+        let func = context.functions.get_mut(self.0).unwrap();
+        match func.blocks.iter().position(|other| other == block) {
+            Some(idx) => {
+                func.blocks.remove(idx);
+                if let Some(block_content) = context.blocks.remove(block.0) {
+                    for ins in block_content.instructions {
+                        context.values.remove(ins.0);
+                    }
+                }
+                Ok(())
+            }
+            None => Err(IrError::MissingBlock(context.blocks[block.0].label.clone())),
+        }
+    }
+
     /// Get a new unique block label.
     ///
     /// If `hint` is `None` then the label will be in the form `"blockN"` where N is an
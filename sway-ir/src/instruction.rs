@@ -1,12 +1,13 @@
 //! Instructions for data manipulation, but mostly control flow.
 //!
 //! Since Sway abstracts most low level operations behind traits they are translated into function
-//! calls which contain ASM blocks.  Therefore _at this stage_ Sway-IR doesn't need low level
-//! operations such as binary arithmetic and logic operators.
+//! calls which contain ASM blocks.  The trait methods which wrap a single arithmetic, logic or
+//! comparison opcode are recognised during IR generation and are converted into [`BinaryOp`] and
+//! [`Cmp`] instructions instead, which allows them to be analysed and folded by the optimizer.
+//! Anything else remains an opaque ASM block.
 //!
-//! Unfortuntely, using opaque ASM blocks limits the effectiveness of certain optimizations and
-//! this should be addressed in the future, perhaps by using compiler intrinsic calls instead of
-//! the ASM blocks where possible.
+//! [`BinaryOp`]: Instruction::BinaryOp
+//! [`Cmp`]: Instruction::Cmp
 
 use sway_types::ident::Ident;
 
@@ -25,10 +26,25 @@ use crate::{
 pub enum Instruction {
     /// An opaque list of ASM instructions passed directly to codegen.
     AsmBlock(AsmBlock, Vec<AsmArg>),
+    /// An arithmetic or logic operation on two integers of the same type.
+    BinaryOp {
+        op: BinaryOpKind,
+        arg1: Value,
+        arg2: Value,
+    },
     /// An unconditional jump.
     Branch(Block),
     /// A function call with a list of arguments.
     Call(Function, Vec<Value>),
+    /// A call through a function value.  The `target` is compared against the index of each of
+    /// the possible `callees` and the matching function is called with the list of arguments.
+    CallIndirect {
+        target: Value,
+        callees: Vec<(u64, Function)>,
+        args: Vec<Value>,
+    },
+    /// A comparison of two values of the same type, returning a `bool`.
+    Cmp(Predicate, Value, Value),
     /// A conditional jump with the boolean condition value and true or false destinations.
     ConditionalBranch {
         cond_value: Value,
@@ -67,8 +83,9 @@ pub enum Instruction {
     Load(Value),
     /// No-op, handy as a placeholder instruction.
     Nop,
-    /// Choose a value from a list depending on the preceding block.
-    Phi(Vec<(Block, Value)>),
+    /// Choose a value of the given type from a list depending on the preceding block.  An empty
+    /// phi has the `Unit` type until it is given a value of another type.
+    Phi(Vec<(Block, Value)>, Type),
     /// A cast from one pointer type to another.  Value must be either a GetPointer instruction or
     /// another PointerCast.
     PointerCast(Value, Type),
@@ -83,6 +100,43 @@ pub enum Instruction {
     Store { dst_val: Value, stored_val: Value },
 }
 
+/// The operator for an [`Instruction::BinaryOp`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BinaryOpKind {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    And,
+    Or,
+    Xor,
+    Shl,
+    Shr,
+}
+
+/// The predicate for an [`Instruction::Cmp`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Predicate {
+    Equal,
+    LessThan,
+    GreaterThan,
+}
+
+impl BinaryOpKind {
+    /// Return whether this operation may panic at runtime, due to overflow or division by zero.
+    pub fn may_trap(&self) -> bool {
+        matches!(
+            self,
+            BinaryOpKind::Add
+                | BinaryOpKind::Sub
+                | BinaryOpKind::Mul
+                | BinaryOpKind::Div
+                | BinaryOpKind::Mod
+        )
+    }
+}
+
 impl Instruction {
     /// Some [`Instruction`]s can return a value, but for some a return value doesn't make sense.
     ///
@@ -91,7 +145,12 @@ impl Instruction {
     pub fn get_type(&self, context: &Context) -> Option<Type> {
         match self {
             Instruction::AsmBlock(asm_block, _) => asm_block.get_type(context),
+            Instruction::BinaryOp { arg1, .. } => arg1.get_type(context),
             Instruction::Call(function, _) => Some(context.functions[function.0].return_type),
+            Instruction::CallIndirect { callees, .. } => callees
+                .first()
+                .map(|(_, function)| context.functions[function.0].return_type),
+            Instruction::Cmp(..) => Some(Type::Bool),
             Instruction::ExtractElement { ty, .. } => ty.get_elem_type(context),
             Instruction::ExtractValue { ty, indices, .. } => ty.get_field_type(context, indices),
             Instruction::Load(ptr_val) => {
@@ -101,9 +160,7 @@ impl Instruction {
                     None
                 }
             }
-            Instruction::Phi(_alts) => {
-                unimplemented!("phi get type -- I think we should put the type in the enum.")
-            }
+            Instruction::Phi(_, ty) => Some(*ty),
 
             // These can be recursed to via Load, so we return the pointer type.
             Instruction::GetPointer(ptr) => Some(context.pointers[ptr.0].ty),
@@ -155,6 +212,83 @@ impl Instruction {
         }
     }
 
+    /// Return every value this instruction refers to as an argument.
+    pub fn get_operands(&self) -> Vec<Value> {
+        match self {
+            Instruction::AsmBlock(_, args) => args
+                .iter()
+                .filter_map(|asm_arg| asm_arg.initializer)
+                .collect(),
+            Instruction::BinaryOp { arg1, arg2, .. } => vec![*arg1, *arg2],
+            Instruction::Branch(_) => vec![],
+            Instruction::Call(_, args) => args.clone(),
+            Instruction::CallIndirect { target, args, .. } => std::iter::once(*target)
+                .chain(args.iter().copied())
+                .collect(),
+            Instruction::Cmp(_, lhs, rhs) => vec![*lhs, *rhs],
+            Instruction::ConditionalBranch { cond_value, .. } => vec![*cond_value],
+            Instruction::ExtractElement {
+                array, index_val, ..
+            } => vec![*array, *index_val],
+            Instruction::ExtractValue { aggregate, .. } => vec![*aggregate],
+            Instruction::GetPointer(_) => vec![],
+            Instruction::InsertElement {
+                array,
+                value,
+                index_val,
+                ..
+            } => vec![*array, *value, *index_val],
+            Instruction::InsertValue {
+                aggregate, value, ..
+            } => vec![*aggregate, *value],
+            Instruction::Load(src_val) => vec![*src_val],
+            Instruction::Nop => vec![],
+            Instruction::Phi(pairs, _) => pairs.iter().map(|(_, val)| *val).collect(),
+            Instruction::PointerCast(ptr_val, _) => vec![*ptr_val],
+            Instruction::Ret(ret_val, _) => vec![*ret_val],
+            Instruction::StateLoad { load_val, key } => vec![*load_val, *key],
+            Instruction::StateStore { stored_val, key } => vec![*stored_val, *key],
+            Instruction::Store {
+                dst_val,
+                stored_val,
+            } => vec![*dst_val, *stored_val],
+        }
+    }
+
+    /// Return whether this instruction may have an effect other than producing its value, and so
+    /// may not be removed even when its value is unused.
+    ///
+    /// ASM blocks and calls are opaque and are assumed to have side effects.  `InsertElement` and
+    /// `InsertValue` update their aggregate in place.  Some binary operations may panic.
+    pub fn may_have_side_effect(&self) -> bool {
+        match self {
+            Instruction::BinaryOp { op, .. } => op.may_trap(),
+
+            Instruction::AsmBlock(..)
+            | Instruction::Call(..)
+            | Instruction::CallIndirect { .. }
+            | Instruction::InsertElement { .. }
+            | Instruction::InsertValue { .. }
+            | Instruction::StateLoad { .. }
+            | Instruction::StateStore { .. }
+            | Instruction::Store { .. } => true,
+
+            // Terminators can't be removed without changing the CFG.
+            Instruction::Branch(_)
+            | Instruction::ConditionalBranch { .. }
+            | Instruction::Ret(..) => true,
+
+            Instruction::Cmp(..)
+            | Instruction::ExtractElement { .. }
+            | Instruction::ExtractValue { .. }
+            | Instruction::GetPointer(_)
+            | Instruction::Load(_)
+            | Instruction::Nop
+            | Instruction::Phi(..)
+            | Instruction::PointerCast(..) => false,
+        }
+    }
+
     /// Replace `old_val` with `new_val` if it is referenced by this instruction's arguments.
     pub fn replace_value(&mut self, old_val: Value, new_val: Value) {
         let replace = |val: &mut Value| {
@@ -169,8 +303,20 @@ impl Instruction {
                     .iter_mut()
                     .for_each(|init_val| replace(init_val))
             }),
+            Instruction::BinaryOp { arg1, arg2, .. } => {
+                replace(arg1);
+                replace(arg2);
+            }
             Instruction::Branch(_) => (),
             Instruction::Call(_, args) => args.iter_mut().for_each(replace),
+            Instruction::CallIndirect { target, args, .. } => {
+                replace(target);
+                args.iter_mut().for_each(replace);
+            }
+            Instruction::Cmp(_, lhs, rhs) => {
+                replace(lhs);
+                replace(rhs);
+            }
             Instruction::ConditionalBranch { cond_value, .. } => replace(cond_value),
             Instruction::GetPointer(_) => (),
             Instruction::InsertElement {
@@ -198,7 +344,7 @@ impl Instruction {
             Instruction::ExtractValue { aggregate, .. } => replace(aggregate),
             Instruction::Load(_) => (),
             Instruction::Nop => (),
-            Instruction::Phi(pairs) => pairs.iter_mut().for_each(|(_, val)| replace(val)),
+            Instruction::Phi(pairs, _) => pairs.iter_mut().for_each(|(_, val)| replace(val)),
             Instruction::PointerCast(..) => (),
             Instruction::Ret(ret_val, _) => replace(ret_val),
             Instruction::StateLoad { load_val, key } => {
@@ -298,6 +444,24 @@ impl<'a> InstructionInserter<'a> {
         asm_val
     }
 
+    pub fn binary_op(
+        self,
+        op: BinaryOpKind,
+        arg1: Value,
+        arg2: Value,
+        span_md_idx: Option<MetadataIndex>,
+    ) -> Value {
+        let binop_val = Value::new_instruction(
+            self.context,
+            Instruction::BinaryOp { op, arg1, arg2 },
+            span_md_idx,
+        );
+        self.context.blocks[self.block.0]
+            .instructions
+            .push(binop_val);
+        binop_val
+    }
+
     pub fn branch(
         self,
         to_block: Block,
@@ -330,6 +494,44 @@ impl<'a> InstructionInserter<'a> {
         call_val
     }
 
+    pub fn call_indirect(
+        self,
+        target: Value,
+        callees: Vec<(u64, Function)>,
+        args: &[Value],
+        span_md_idx: Option<MetadataIndex>,
+    ) -> Value {
+        let call_val = Value::new_instruction(
+            self.context,
+            Instruction::CallIndirect {
+                target,
+                callees,
+                args: args.to_vec(),
+            },
+            span_md_idx,
+        );
+        self.context.blocks[self.block.0]
+            .instructions
+            .push(call_val);
+        call_val
+    }
+
+    pub fn cmp(
+        self,
+        pred: Predicate,
+        lhs_value: Value,
+        rhs_value: Value,
+        span_md_idx: Option<MetadataIndex>,
+    ) -> Value {
+        let cmp_val = Value::new_instruction(
+            self.context,
+            Instruction::Cmp(pred, lhs_value, rhs_value),
+            span_md_idx,
+        );
+        self.context.blocks[self.block.0].instructions.push(cmp_val);
+        cmp_val
+    }
+
     pub fn conditional_branch(
         self,
         cond_value: Value,
@@ -17,7 +17,7 @@ use crate::context::Context;
 pub enum Type {
     Unit,
     Bool,
-    Uint(u8),
+    Uint(u16),
     B256,
     String(u64),
     Array(Aggregate),
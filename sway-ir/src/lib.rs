@@ -17,7 +17,8 @@
 //! Other important data types are [`Value`], [`Type`] and [`Constant`].  Function arguments, local
 //! variables, instructions and constants are all [`Value`]s.
 //!
-//! The optimization passes are found in the [optimize] module.
+//! The optimization passes are found in the [optimize] module, and are run using the
+//! [`PassManager`] found in the [pass_manager] module.
 //!
 //! # Note:
 //!
@@ -61,6 +62,8 @@ pub mod optimize;
 pub use optimize::*;
 pub mod parser;
 pub use parser::*;
+pub mod pass_manager;
+pub use pass_manager::*;
 pub mod pointer;
 pub use pointer::*;
 pub mod printer;
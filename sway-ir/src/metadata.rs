@@ -20,6 +20,13 @@ pub enum Metadatum {
         start: usize,
         end: usize,
     },
+    /// The span of a value within `function`, which was inlined at `call_site`.  Both `span` and
+    /// `call_site` may themselves have been inlined.
+    InlinedAt {
+        span: MetadataIndex,
+        function: String,
+        call_site: MetadataIndex,
+    },
 }
 
 #[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
@@ -57,8 +64,41 @@ impl MetadataIndex {
         })
     }
 
+    /// Wrap the span of a value from `function` which is being inlined at `call_site`.
+    pub fn inlined_at(
+        context: &mut Context,
+        span: MetadataIndex,
+        function: String,
+        call_site: MetadataIndex,
+    ) -> MetadataIndex {
+        MetadataIndex(context.metadata.insert(Metadatum::InlinedAt {
+            span,
+            function,
+            call_site,
+        }))
+    }
+
+    /// The chain of functions inlined to reach this span, outermost first, as the name of each
+    /// function paired with the metadata of the call it was inlined at.
+    pub fn inlined_chain(&self, context: &Context) -> Vec<(String, MetadataIndex)> {
+        match &context.metadata[self.0] {
+            Metadatum::InlinedAt {
+                span,
+                function,
+                call_site,
+            } => {
+                let mut chain = call_site.inlined_chain(context);
+                chain.push((function.clone(), *call_site));
+                chain.append(&mut span.inlined_chain(context));
+                chain
+            }
+            _otherwise => Vec::new(),
+        }
+    }
+
     pub fn to_span(&self, context: &Context) -> Result<Span, IrError> {
         match &context.metadata[self.0] {
+            Metadatum::InlinedAt { span, .. } => span.to_span(context),
             Metadatum::Span {
                 loc_idx,
                 start,
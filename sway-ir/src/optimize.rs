@@ -2,12 +2,12 @@
 //!
 //! Each of these modules are a collection of typical code optimisation passes.
 //!
-//! Currently there is no pass manager, as there are only a couple of passes, but this is something
-//! which will be added in the future.
+//! Each of the functions under this module will return a boolean indicating whether a modification
+//! to the IR was made.  Typically the passes will be just re-run until they no longer make any such
+//! modifications, implying they've optimized as much possible.
 //!
-//! So each of the functions under this module will return a boolean indicating whether a
-//! modification to the IR was made.  Typically the passes will be just re-run until they no longer
-//! make any such modifications, implying they've optimized as much possible.
+//! The passes are registered with and run by the [`PassManager`](crate::pass_manager::PassManager),
+//! and any new pass should be added to its standard passes too.
 //!
 //! When writing passes one should keep in mind that when a modification is made then any iterators
 //! over blocks or instructions can be invalidated, and starting over is a safer option than trying
@@ -15,5 +15,13 @@
 
 pub mod inline;
 pub use inline::*;
+pub mod indirect_calls;
+pub use indirect_calls::*;
 pub mod constants;
 pub use constants::*;
+pub mod dce;
+pub use dce::*;
+pub mod mem2reg;
+pub use mem2reg::*;
+pub mod simplify_cfg;
+pub use simplify_cfg::*;
@@ -3,13 +3,17 @@
 //! - combining - compile time evaluation of constant expressions.
 //!   - combine insert_values - reduce expressions which insert a constant value into a constant
 //!     struct.
+//!   - combine binary ops - evaluate arithmetic and logic on constant integers.
+//!   - combine cmps - evaluate comparisons of constant integers and bools.
 
 use crate::{
+    block::Block,
     constant::{Constant, ConstantValue},
     context::Context,
     error::IrError,
     function::Function,
-    instruction::Instruction,
+    instruction::{BinaryOpKind, Instruction, Predicate},
+    irtype::Type,
     value::{Value, ValueContent, ValueDatum},
 };
 
@@ -21,6 +25,14 @@ pub fn combine_constants(context: &mut Context, function: &Function) -> Result<b
             modified = true;
             continue;
         }
+        if combine_binary_op(context, function) {
+            modified = true;
+            continue;
+        }
+        if combine_cmp(context, function) {
+            modified = true;
+            continue;
+        }
 
         // Other passes here... always continue to the top if pass returns true.
         break;
@@ -81,6 +93,127 @@ fn combine_const_insert_values(context: &mut Context, function: &Function) -> bo
     false
 }
 
+fn combine_binary_op(context: &mut Context, function: &Function) -> bool {
+    // Find a candidate `binary_op` with two constant integer args, whose result can be computed
+    // without overflow or division by zero.  Those which would fail at runtime are left alone.
+    let candidate = function
+        .instruction_iter(context)
+        .find_map(|(block, ins_val)| match &context.values[ins_val.0].value {
+            ValueDatum::Instruction(Instruction::BinaryOp { op, arg1, arg2 }) => {
+                match (&context.values[arg1.0].value, &context.values[arg2.0].value) {
+                    (
+                        ValueDatum::Constant(Constant {
+                            ty: Type::Uint(nbits),
+                            value: ConstantValue::Uint(val1),
+                        }),
+                        ValueDatum::Constant(Constant {
+                            value: ConstantValue::Uint(val2),
+                            ..
+                        }),
+                    ) => fold_binary_op(*op, *nbits, *val1, *val2)
+                        .map(|res| (block, ins_val, Constant::new_uint(*nbits, res))),
+                    _otherwise => None,
+                }
+            }
+            _otherwise => None,
+        });
+
+    match candidate {
+        Some((block, ins_val, res)) => {
+            replace_with_constant(context, function, block, ins_val, res);
+            true
+        }
+        None => false,
+    }
+}
+
+fn fold_binary_op(op: BinaryOpKind, nbits: u16, val1: u64, val2: u64) -> Option<u64> {
+    let res = match op {
+        BinaryOpKind::Add => val1.checked_add(val2),
+        BinaryOpKind::Sub => val1.checked_sub(val2),
+        BinaryOpKind::Mul => val1.checked_mul(val2),
+        BinaryOpKind::Div => val1.checked_div(val2),
+        BinaryOpKind::Mod => val1.checked_rem(val2),
+        BinaryOpKind::And => Some(val1 & val2),
+        BinaryOpKind::Or => Some(val1 | val2),
+        BinaryOpKind::Xor => Some(val1 ^ val2),
+        BinaryOpKind::Shl => val1
+            .checked_shl(val2.try_into().ok()?)
+            .filter(|res| res >> val2 == val1),
+        BinaryOpKind::Shr => val1.checked_shr(val2.try_into().ok()?),
+    }?;
+
+    // The result must also fit in the type.
+    if nbits >= 64 || res >> nbits == 0 {
+        Some(res)
+    } else {
+        None
+    }
+}
+
+fn combine_cmp(context: &mut Context, function: &Function) -> bool {
+    // Find a candidate `cmp` with two constant integer or bool args.
+    let candidate = function
+        .instruction_iter(context)
+        .find_map(|(block, ins_val)| match &context.values[ins_val.0].value {
+            ValueDatum::Instruction(Instruction::Cmp(pred, lhs_value, rhs_value)) => {
+                let res = match (
+                    &context.values[lhs_value.0].value,
+                    &context.values[rhs_value.0].value,
+                ) {
+                    (
+                        ValueDatum::Constant(Constant {
+                            value: ConstantValue::Uint(lhs),
+                            ..
+                        }),
+                        ValueDatum::Constant(Constant {
+                            value: ConstantValue::Uint(rhs),
+                            ..
+                        }),
+                    ) => match pred {
+                        Predicate::Equal => lhs == rhs,
+                        Predicate::LessThan => lhs < rhs,
+                        Predicate::GreaterThan => lhs > rhs,
+                    },
+                    (
+                        ValueDatum::Constant(Constant {
+                            value: ConstantValue::Bool(lhs),
+                            ..
+                        }),
+                        ValueDatum::Constant(Constant {
+                            value: ConstantValue::Bool(rhs),
+                            ..
+                        }),
+                    ) if *pred == Predicate::Equal => lhs == rhs,
+                    _otherwise => return None,
+                };
+                Some((block, ins_val, Constant::new_bool(res)))
+            }
+            _otherwise => None,
+        });
+
+    match candidate {
+        Some((block, ins_val, res)) => {
+            replace_with_constant(context, function, block, ins_val, res);
+            true
+        }
+        None => false,
+    }
+}
+
+fn replace_with_constant(
+    context: &mut Context,
+    function: &Function,
+    block: Block,
+    ins_val: Value,
+    constant: Constant,
+) {
+    let span_md_idx = context.values[ins_val.0].span_md_idx;
+    let const_val = Value::new_constant(context, constant, span_md_idx);
+    function.replace_value(context, ins_val, const_val, None);
+    block.remove_instruction(context, ins_val);
+}
+
 fn combine_const_aggregate_field(
     context: &mut Context,
     function: &Function,
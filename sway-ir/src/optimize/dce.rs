@@ -0,0 +1,84 @@
+//! Dead code elimination.
+//!
+//! Any instruction without side effects whose value is never used is removed.  Removing an
+//! instruction may in turn leave its own arguments unused, so they are then considered too.
+
+use std::collections::HashMap;
+
+use crate::{
+    block::Block,
+    context::Context,
+    error::IrError,
+    function::Function,
+    instruction::Instruction,
+    irtype::Type,
+    value::{Value, ValueDatum},
+};
+
+/// Remove every unused instruction without side effects from a function.
+///
+/// Every block must start with a phi instruction, so an unused first phi is emptied rather than
+/// removed.
+pub fn dce(context: &mut Context, function: &Function) -> Result<bool, IrError> {
+    // Count the uses of every value and remember which block each instruction is in.
+    let mut num_uses: HashMap<Value, usize> = HashMap::new();
+    let mut ins_blocks: HashMap<Value, Block> = HashMap::new();
+    for (block, ins_val) in function.instruction_iter(context) {
+        ins_blocks.insert(ins_val, block);
+        for operand in get_operands(context, &ins_val) {
+            *num_uses.entry(operand).or_insert(0) += 1;
+        }
+    }
+
+    let mut worklist = ins_blocks
+        .keys()
+        .filter(|ins_val| !num_uses.contains_key(ins_val))
+        .copied()
+        .collect::<Vec<_>>();
+
+    let mut modified = false;
+    while let Some(dead_val) = worklist.pop() {
+        let dead_ins = match &context.values[dead_val.0].value {
+            ValueDatum::Instruction(ins) if !ins.may_have_side_effect() => ins,
+            _otherwise => continue,
+        };
+
+        // Anything which was only used by this instruction is now dead too.
+        for operand in dead_ins.get_operands() {
+            if let Some(count) = num_uses.get_mut(&operand) {
+                *count -= 1;
+                if *count == 0 {
+                    num_uses.remove(&operand);
+                    if ins_blocks.contains_key(&operand) {
+                        worklist.push(operand);
+                    }
+                }
+            }
+        }
+
+        let is_first_phi = matches!(dead_ins, Instruction::Phi(..))
+            && ins_blocks[&dead_val].get_phi(context) == dead_val;
+        if is_first_phi {
+            if let Instruction::Phi(pairs, _) = dead_ins {
+                if !pairs.is_empty() {
+                    context.values[dead_val.0].value =
+                        ValueDatum::Instruction(Instruction::Phi(vec![], Type::Unit));
+                    modified = true;
+                }
+            }
+        } else {
+            ins_blocks[&dead_val].remove_instruction(context, dead_val);
+            context.values.remove(dead_val.0);
+            modified = true;
+        }
+    }
+
+    Ok(modified)
+}
+
+fn get_operands(context: &Context, ins_val: &Value) -> Vec<Value> {
+    match &context.values[ins_val.0].value {
+        ValueDatum::Instruction(ins) => ins.get_operands(),
+        _otherwise => unreachable!("Block contents must be instructions."),
+    }
+}
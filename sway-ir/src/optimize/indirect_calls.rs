@@ -0,0 +1,98 @@
+//! Devirtualization of indirect calls.
+//!
+//! A `call_indirect` whose callee is known at compile time is replaced with a direct `call` of
+//! that callee, which may then be inlined as usual.  The callee is known when the target is a
+//! constant, or when there is only one function the target may refer to.  Any other indirect
+//! call is left for the backend, which jumps to the entry of whichever callee the target refers
+//! to at run time.
+
+use crate::{
+    block::Block,
+    constant::{Constant, ConstantValue},
+    context::Context,
+    error::IrError,
+    function::Function,
+    instruction::Instruction,
+    value::{Value, ValueContent, ValueDatum},
+};
+
+/// Replace all indirect calls with a known callee made from a specific function with direct
+/// calls.
+pub fn devirtualize_indirect_calls(
+    context: &mut Context,
+    function: &Function,
+) -> Result<bool, IrError> {
+    let mut modified = false;
+    loop {
+        // Find the next indirect call site with a known callee.
+        let call_data = function
+            .instruction_iter(context)
+            .find_map(|(block, call_val)| {
+                known_callee(context, call_val).map(|callee| (block, call_val, callee))
+            });
+        match call_data {
+            Some((block, call_val, callee)) => {
+                devirtualize_indirect_call(context, block, call_val, callee)?;
+                modified = true;
+            }
+            None => break,
+        }
+    }
+    Ok(modified)
+}
+
+/// The function an indirect call always calls, if it can be told at compile time.
+///
+/// A constant target is the index of its callee, and a target with a single possible callee can
+/// only refer to that one, as the callees are every function of the right type.
+fn known_callee(context: &Context, call_val: Value) -> Option<Function> {
+    match &context.values[call_val.0].value {
+        ValueDatum::Instruction(Instruction::CallIndirect {
+            target, callees, ..
+        }) => match (&context.values[target.0].value, callees.as_slice()) {
+            (_, [(_, callee)]) => Some(*callee),
+            (
+                ValueDatum::Constant(Constant {
+                    value: ConstantValue::Uint(target_idx),
+                    ..
+                }),
+                _,
+            ) => callees
+                .iter()
+                .find(|(idx, _)| idx == target_idx)
+                .map(|(_, callee)| *callee),
+            _otherwise => None,
+        },
+        _otherwise => None,
+    }
+}
+
+/// Replace a single indirect call site with a direct call of `callee`.
+///
+/// ```text
+/// v = call_indirect target [0: a, 1: b](args)
+/// ```
+///
+/// becomes, when `target` is `1`:
+///
+/// ```text
+/// v = call b(args)
+/// ```
+fn devirtualize_indirect_call(
+    context: &mut Context,
+    block: Block,
+    call_site: Value,
+    callee: Function,
+) -> Result<(), IrError> {
+    let (args, span_md_idx) = match context.values[call_site.0].clone() {
+        ValueContent {
+            value: ValueDatum::Instruction(Instruction::CallIndirect { args, .. }),
+            span_md_idx,
+        } => (args, span_md_idx),
+        _ => unreachable!("Only indirect calls are devirtualized."),
+    };
+    let direct_call = Value::new_instruction(context, Instruction::Call(callee, args), span_md_idx);
+    block.replace_instruction(context, call_site, direct_call)?;
+    context.values.remove(call_site.0);
+    Ok(())
+}
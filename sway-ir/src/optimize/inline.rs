@@ -2,7 +2,7 @@
 //!
 //! Function inlining is pretty hairy so these passes must be maintained with care.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::{
     asm::AsmArg,
@@ -11,10 +11,113 @@ use crate::{
     error::IrError,
     function::Function,
     instruction::Instruction,
+    metadata::MetadataIndex,
+    module::Module,
     pointer::Pointer,
     value::{Value, ValueContent, ValueDatum},
 };
 
+/// Callees with at most this many instructions are inlined at every call site.
+const SMALL_FUNCTION_SIZE: usize = 12;
+
+/// Inline the calls made within a module which are worth inlining, i.e., calls to functions which
+/// are called from a single site or are small.  Calls to recursive functions are never inlined as
+/// this would go on forever; they, and calls to the other functions, remain calls.
+pub fn inline_calls(context: &mut Context, module: &Module) -> Result<bool, IrError> {
+    let mut modified = false;
+    loop {
+        // Find the next call site worth inlining.  The call graph changes with each inlined call,
+        // so it's summarised again each time.
+        let summary = CallSummary::new(context, module);
+        let call_data = module.function_iter(context).find_map(|function| {
+            function
+                .instruction_iter(context)
+                .find_map(|(block, call_val)| match context.values[call_val.0].value {
+                    ValueDatum::Instruction(Instruction::Call(inlined_function, _))
+                        if summary.should_inline(&inlined_function) =>
+                    {
+                        Some((function, block, call_val, inlined_function))
+                    }
+                    _ => None,
+                })
+        });
+        match call_data {
+            Some((function, block, call_val, inlined_function)) => {
+                inline_function_call(context, function, block, call_val, inlined_function)?;
+                modified = true;
+            }
+            None => break,
+        }
+    }
+    Ok(modified)
+}
+
+/// The number of call sites and instructions of each function in a module, and which of them are
+/// recursive.
+struct CallSummary {
+    call_counts: HashMap<Function, usize>,
+    sizes: HashMap<Function, usize>,
+    recursive: HashSet<Function>,
+}
+
+impl CallSummary {
+    fn new(context: &Context, module: &Module) -> Self {
+        let mut call_counts = HashMap::new();
+        let mut sizes = HashMap::new();
+        let mut callees: HashMap<Function, Vec<Function>> = HashMap::new();
+        for function in module.function_iter(context) {
+            let mut size = 0;
+            for (_, ins) in function.instruction_iter(context) {
+                size += 1;
+                let called = match &context.values[ins.0].value {
+                    ValueDatum::Instruction(Instruction::Call(callee, _)) => {
+                        *call_counts.entry(*callee).or_insert(0) += 1;
+                        vec![*callee]
+                    }
+                    ValueDatum::Instruction(Instruction::CallIndirect { callees, .. }) => {
+                        callees.iter().map(|(_, f)| *f).collect()
+                    }
+                    _ => Vec::new(),
+                };
+                callees.entry(function).or_default().extend(called);
+            }
+            sizes.insert(function, size);
+        }
+
+        // A function is recursive if it can be reached from one of its own callees.
+        let recursive = module
+            .function_iter(context)
+            .filter(|function| {
+                let mut visited = HashSet::new();
+                let mut stack = vec![*function];
+                while let Some(caller) = stack.pop() {
+                    for callee in callees.get(&caller).into_iter().flatten() {
+                        if callee == function {
+                            return true;
+                        }
+                        if visited.insert(*callee) {
+                            stack.push(*callee);
+                        }
+                    }
+                }
+                false
+            })
+            .collect();
+
+        CallSummary {
+            call_counts,
+            sizes,
+            recursive,
+        }
+    }
+
+    fn should_inline(&self, function: &Function) -> bool {
+        !self.recursive.contains(function)
+            && (self.call_counts.get(function).copied().unwrap_or(0) <= 1
+                || self.sizes.get(function).copied().unwrap_or(0) <= SMALL_FUNCTION_SIZE)
+    }
+}
+
 /// Inline all calls made from a specific function, effectively removing all `Call` instructions.
 ///
 /// e.g., If this is applied to main() then all calls in the program are removed.  This is
@@ -94,6 +197,16 @@ pub fn inline_function_call(
         }
     }
 
+    // The spans of the inlined instructions are marked as inlined at the call, if it has one.
+    let inlined_at = context.values[call_site.0]
+        .span_md_idx
+        .map(|call_site_md_idx| {
+            (
+                inlined_function.get_name(context).to_owned(),
+                call_site_md_idx,
+            )
+        });
+
     // Now remove the call altogether.
     context.values.remove(call_site.0);
 
@@ -141,6 +254,7 @@ pub fn inline_function_call(
                 &block_map,
                 &mut value_map,
                 &ptr_map,
+                inlined_at.as_ref(),
             );
         }
     }
@@ -150,16 +264,19 @@ pub fn inline_function_call(
     // don't need a peristent `&Context` to access them.
     for old_block in inlined_blocks {
         let new_block = block_map.get(&old_block).unwrap();
-        let old_phi_val = old_block.get_phi(context);
-        if let ValueDatum::Instruction(Instruction::Phi(pairs)) =
-            context.values[old_phi_val.0].value.clone()
-        {
-            for (from_block, phi_value) in pairs {
-                new_block.add_phi(
-                    context,
-                    block_map.get(&from_block).copied().unwrap(),
-                    value_map.get(&phi_value).copied().unwrap_or(phi_value),
-                );
+        for old_phi_val in old_block.get_phis(context) {
+            let new_phi_val = value_map.get(&old_phi_val).copied().unwrap();
+            if let ValueDatum::Instruction(Instruction::Phi(pairs, _)) =
+                context.values[old_phi_val.0].value.clone()
+            {
+                for (from_block, phi_value) in pairs {
+                    new_block.add_to_phi(
+                        context,
+                        new_phi_val,
+                        block_map.get(&from_block).copied().unwrap(),
+                        value_map.get(&phi_value).copied().unwrap_or(phi_value),
+                    );
+                }
             }
         }
     }
@@ -167,6 +284,7 @@ pub fn inline_function_call(
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 fn inline_instruction(
     context: &mut Context,
     new_block: &Block,
@@ -175,6 +293,7 @@ fn inline_instruction(
     block_map: &HashMap<Block, Block>,
     value_map: &mut HashMap<Value, Value>,
     ptr_map: &HashMap<Pointer, Pointer>,
+    inlined_at: Option<&(String, MetadataIndex)>,
 ) {
     // Util to translate old blocks to new.  If an old block isn't in the map then we panic, since
     // it should be guaranteed to be there...that's a bug otherwise.
@@ -197,6 +316,17 @@ fn inline_instruction(
         span_md_idx,
     } = context.values[instruction.0].clone()
     {
+        let span_md_idx = match (span_md_idx, inlined_at) {
+            (Some(span_md_idx), Some((function, call_site_md_idx))) => {
+                Some(MetadataIndex::inlined_at(
+                    context,
+                    span_md_idx,
+                    function.clone(),
+                    *call_site_md_idx,
+                ))
+            }
+            _otherwise => span_md_idx,
+        };
         let new_ins = match old_ins {
             Instruction::AsmBlock(asm, args) => {
                 let new_args = args
@@ -212,6 +342,11 @@ fn inline_instruction(
                     .ins(context)
                     .asm_block_from_asm(asm, new_args, span_md_idx)
             }
+            Instruction::BinaryOp { op, arg1, arg2 } => {
+                new_block
+                    .ins(context)
+                    .binary_op(op, map_value(arg1), map_value(arg2), span_md_idx)
+            }
             // For `br` and `cbr` below we don't need to worry about the phi values, they're
             // adjusted later in `inline_function_call()`.
             Instruction::Branch(b) => {
@@ -227,6 +362,25 @@ fn inline_instruction(
                     .as_slice(),
                 span_md_idx,
             ),
+            Instruction::CallIndirect {
+                target,
+                callees,
+                args,
+            } => new_block.ins(context).call_indirect(
+                map_value(target),
+                callees,
+                args.iter()
+                    .map(|old_val: &Value| map_value(*old_val))
+                    .collect::<Vec<Value>>()
+                    .as_slice(),
+                span_md_idx,
+            ),
+            Instruction::Cmp(pred, lhs_value, rhs_value) => new_block.ins(context).cmp(
+                pred,
+                map_value(lhs_value),
+                map_value(rhs_value),
+                span_md_idx,
+            ),
             Instruction::ConditionalBranch {
                 cond_value,
                 true_block,
@@ -318,11 +472,26 @@ fn inline_instruction(
                     .store(map_value(dst_val), map_value(stored_val), span_md_idx)
             }
 
-            // NOTE: We're not translating the phi value yet, since this is the single instance of
+            // NOTE: We're not translating the phi values yet, since this is the single instance of
             // use of a value which may not be mapped yet -- a branch from a subsequent block,
-            // back up to this block.  And we don't need to add a `phi` instruction because an
-            // empty one is added upon block creation; we can return that instead.
-            Instruction::Phi(_) => new_block.get_phi(context),
+            // back up to this block.  And we don't need to add the first `phi` instruction because
+            // an empty one is added upon block creation; we can return that instead, giving it the
+            // original type.  Any subsequent phis are added as new empty phis.
+            Instruction::Phi(_, ty) => {
+                let first_phi = new_block.get_phi(context);
+                if value_map.values().any(|new_val| *new_val == first_phi) {
+                    new_block.new_phi(context, ty)
+                } else {
+                    if let ValueDatum::Instruction(Instruction::Phi(pairs, phi_ty)) =
+                        &mut context.values[first_phi.0].value
+                    {
+                        if pairs.is_empty() {
+                            *phi_ty = ty;
+                        }
+                    }
+                    first_phi
+                }
+            }
         };
         value_map.insert(*instruction, new_ins);
     }
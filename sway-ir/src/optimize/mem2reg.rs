@@ -0,0 +1,363 @@
+//! Promotion of function locals to registers, a.k.a. `mem2reg`.
+//!
+//! Scalar locals which are only ever loaded from or stored to directly are replaced by the values
+//! stored to them, with new phi instructions added wherever control flow merges different
+//! values.  This is the classic SSA construction by Cytron et al., using the dominance algorithm
+//! by Cooper, Harvey and Kennedy.
+//!
+//! The local is initialised with its initializer if it has one, or otherwise with zero.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    block::Block,
+    constant::Constant,
+    context::Context,
+    error::IrError,
+    function::Function,
+    instruction::Instruction,
+    irtype::Type,
+    pointer::Pointer,
+    value::{Value, ValueDatum},
+};
+
+/// Promote every suitable local in a function to SSA values.
+pub fn promote_to_registers(context: &mut Context, function: &Function) -> Result<bool, IrError> {
+    let entry_block = function.get_entry_block(context);
+    let rpo = reverse_post_order(context, &entry_block);
+    let reachable = rpo.iter().copied().collect::<HashSet<_>>();
+
+    // The initial values of the locals are only available at the entry block, so if it's also
+    // branched to then we'd need to split it first.
+    if rpo
+        .iter()
+        .any(|block| block.successors(context).contains(&entry_block))
+    {
+        return Ok(false);
+    }
+
+    let get_ptrs = find_promotable_get_ptrs(context, function, &reachable);
+    if get_ptrs.is_empty() {
+        return Ok(false);
+    }
+
+    // Keep the locals in the order they're declared so the new phis are in a predictable order.
+    let promoted_ptrs = function
+        .locals_iter(context)
+        .map(|(_, ptr)| *ptr)
+        .filter(|ptr| get_ptrs.values().any(|get_ptr_ptr| get_ptr_ptr == ptr))
+        .collect::<Vec<_>>();
+
+    // Place the phis.  Each local needs a phi in the iterated dominance frontier of every block
+    // which stores to it.
+    let preds = predecessors(context, &rpo);
+    let idoms = immediate_dominators(&rpo, &preds);
+    let frontiers = dominance_frontiers(&rpo, &preds, &idoms);
+
+    let mut block_phis: HashMap<Block, Vec<(Pointer, Value)>> = HashMap::new();
+    for ptr in &promoted_ptrs {
+        let ty = *ptr.get_type(context);
+        let mut worklist = rpo
+            .iter()
+            .filter(|block| {
+                context.blocks[block.0].instructions.iter().any(|ins_val| {
+                    matches!(
+                        &context.values[ins_val.0].value,
+                        ValueDatum::Instruction(Instruction::Store { dst_val, .. })
+                            if get_ptrs.get(dst_val) == Some(ptr)
+                    )
+                })
+            })
+            .copied()
+            .collect::<Vec<_>>();
+        let mut has_phi = HashSet::new();
+        while let Some(def_block) = worklist.pop() {
+            for frontier_block in &frontiers[&def_block] {
+                if has_phi.insert(*frontier_block) {
+                    let phi_val = frontier_block.new_phi(context, ty);
+                    block_phis
+                        .entry(*frontier_block)
+                        .or_default()
+                        .push((*ptr, phi_val));
+                    worklist.push(*frontier_block);
+                }
+            }
+        }
+    }
+
+    // Rename, walking the dominator tree with a stack of the current value of each local.
+    let mut stacks = HashMap::new();
+    for ptr in &promoted_ptrs {
+        let init_val = initial_value(context, ptr);
+        stacks.insert(*ptr, vec![init_val]);
+    }
+    let mut dom_children: HashMap<Block, Vec<Block>> = HashMap::new();
+    for block in rpo.iter().skip(1) {
+        dom_children.entry(idoms[block]).or_default().push(*block);
+    }
+    rename(
+        context,
+        function,
+        &entry_block,
+        &get_ptrs,
+        &block_phis,
+        &dom_children,
+        &mut stacks,
+    );
+
+    // The `get_ptr`s are now unused, and the locals themselves can be removed.
+    for (block, ins_val) in function.instruction_iter(context).collect::<Vec<_>>() {
+        if get_ptrs.contains_key(&ins_val) {
+            block.remove_instruction(context, ins_val);
+            context.values.remove(ins_val.0);
+        }
+    }
+    context.functions[function.0]
+        .local_storage
+        .retain(|_, ptr| !promoted_ptrs.contains(ptr));
+    for ptr in promoted_ptrs {
+        context.pointers.remove(ptr.0);
+    }
+
+    Ok(true)
+}
+
+// Find the `get_ptr` instructions for every local which may be promoted, mapped to the local.
+//
+// A local is promotable if it's a scalar and every `get_ptr` to it is only ever used as the
+// source of a `load` or the destination of a `store`, all within reachable blocks.
+fn find_promotable_get_ptrs(
+    context: &Context,
+    function: &Function,
+    reachable: &HashSet<Block>,
+) -> HashMap<Value, Pointer> {
+    let mut get_ptrs = HashMap::new();
+    let mut disqualified = HashSet::new();
+    for (block, ins_val) in function.instruction_iter(context) {
+        if let ValueDatum::Instruction(Instruction::GetPointer(ptr)) =
+            &context.values[ins_val.0].value
+        {
+            if !matches!(
+                ptr.get_type(context),
+                Type::Unit | Type::Bool | Type::Uint(0..=64)
+            ) || function.lookup_local_name(context, ptr).is_none()
+                || !reachable.contains(&block)
+            {
+                disqualified.insert(*ptr);
+            }
+            get_ptrs.insert(ins_val, *ptr);
+        }
+    }
+
+    for (block, ins_val) in function.instruction_iter(context) {
+        let ins = match &context.values[ins_val.0].value {
+            ValueDatum::Instruction(ins) => ins,
+            _otherwise => unreachable!("Block contents must be instructions."),
+        };
+        for operand in ins.get_operands() {
+            if let Some(ptr) = get_ptrs.get(&operand) {
+                let is_direct_use = match ins {
+                    Instruction::Load(_) => true,
+                    Instruction::Store {
+                        dst_val,
+                        stored_val,
+                    } => *dst_val == operand && *stored_val != operand,
+                    _otherwise => false,
+                };
+                if !is_direct_use || !reachable.contains(&block) {
+                    disqualified.insert(*ptr);
+                }
+            }
+        }
+    }
+
+    get_ptrs.retain(|_, ptr| !disqualified.contains(ptr));
+    get_ptrs
+}
+
+fn initial_value(context: &mut Context, ptr: &Pointer) -> Value {
+    let pointer = &context.pointers[ptr.0];
+    let init_const = pointer
+        .initializer
+        .clone()
+        .unwrap_or_else(|| match pointer.ty {
+            Type::Bool => Constant::new_bool(false),
+            Type::Uint(nbits) => Constant::new_uint(nbits, 0),
+            _otherwise => Constant::new_unit(),
+        });
+    Value::new_constant(context, init_const, None)
+}
+
+fn rename(
+    context: &mut Context,
+    function: &Function,
+    block: &Block,
+    get_ptrs: &HashMap<Value, Pointer>,
+    block_phis: &HashMap<Block, Vec<(Pointer, Value)>>,
+    dom_children: &HashMap<Block, Vec<Block>>,
+    stacks: &mut HashMap<Pointer, Vec<Value>>,
+) {
+    let mut pushed = Vec::new();
+    for (ptr, phi_val) in block_phis.get(block).into_iter().flatten() {
+        stacks.get_mut(ptr).unwrap().push(*phi_val);
+        pushed.push(*ptr);
+    }
+
+    for ins_val in context.blocks[block.0].instructions.clone() {
+        match context.values[ins_val.0].value {
+            ValueDatum::Instruction(Instruction::Load(src_val)) => {
+                if let Some(ptr) = get_ptrs.get(&src_val) {
+                    let cur_val = *stacks[ptr].last().unwrap();
+                    function.replace_value(context, ins_val, cur_val, None);
+                    block.remove_instruction(context, ins_val);
+                    context.values.remove(ins_val.0);
+                }
+            }
+            ValueDatum::Instruction(Instruction::Store {
+                dst_val,
+                stored_val,
+            }) => {
+                if let Some(ptr) = get_ptrs.get(&dst_val) {
+                    stacks.get_mut(ptr).unwrap().push(stored_val);
+                    pushed.push(*ptr);
+                    block.remove_instruction(context, ins_val);
+                    context.values.remove(ins_val.0);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    // Pass the current values to the phis in each successor.
+    let mut succ_blocks = block.successors(context);
+    succ_blocks.dedup();
+    for succ_block in succ_blocks {
+        for (ptr, phi_val) in block_phis.get(&succ_block).into_iter().flatten() {
+            let cur_val = *stacks[ptr].last().unwrap();
+            succ_block.add_to_phi(context, *phi_val, *block, cur_val);
+        }
+    }
+
+    for child_block in dom_children.get(block).into_iter().flatten() {
+        rename(
+            context,
+            function,
+            child_block,
+            get_ptrs,
+            block_phis,
+            dom_children,
+            stacks,
+        );
+    }
+
+    for ptr in pushed {
+        stacks.get_mut(&ptr).unwrap().pop();
+    }
+}
+
+fn reverse_post_order(context: &Context, entry_block: &Block) -> Vec<Block> {
+    let mut visited = HashSet::new();
+    let mut post_order = Vec::new();
+    let mut stack = vec![(*entry_block, false)];
+    while let Some((block, children_done)) = stack.pop() {
+        if children_done {
+            post_order.push(block);
+        } else if visited.insert(block) {
+            stack.push((block, true));
+            for succ_block in block.successors(context).into_iter().rev() {
+                if !visited.contains(&succ_block) {
+                    stack.push((succ_block, false));
+                }
+            }
+        }
+    }
+    post_order.reverse();
+    post_order
+}
+
+fn predecessors(context: &Context, rpo: &[Block]) -> HashMap<Block, Vec<Block>> {
+    let mut preds: HashMap<Block, Vec<Block>> =
+        rpo.iter().map(|block| (*block, Vec::new())).collect();
+    for block in rpo {
+        for succ_block in block.successors(context) {
+            let succ_preds = preds.get_mut(&succ_block).unwrap();
+            if !succ_preds.contains(block) {
+                succ_preds.push(*block);
+            }
+        }
+    }
+    preds
+}
+
+// From 'A Simple, Fast Dominance Algorithm' by Cooper, Harvey and Kennedy.  The entry block is its
+// own immediate dominator.
+fn immediate_dominators(
+    rpo: &[Block],
+    preds: &HashMap<Block, Vec<Block>>,
+) -> HashMap<Block, Block> {
+    let rpo_idcs: HashMap<Block, usize> = rpo
+        .iter()
+        .enumerate()
+        .map(|(idx, block)| (*block, idx))
+        .collect();
+    let mut idoms: HashMap<Block, Block> = HashMap::new();
+    idoms.insert(rpo[0], rpo[0]);
+
+    let intersect = |idoms: &HashMap<Block, Block>, mut a: Block, mut b: Block| {
+        while a != b {
+            while rpo_idcs[&a] > rpo_idcs[&b] {
+                a = idoms[&a];
+            }
+            while rpo_idcs[&b] > rpo_idcs[&a] {
+                b = idoms[&b];
+            }
+        }
+        a
+    };
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for block in rpo.iter().skip(1) {
+            let mut processed_preds = preds[block]
+                .iter()
+                .filter(|pred_block| idoms.contains_key(pred_block));
+            let first_pred = *processed_preds
+                .next()
+                .expect("Reachable block must have a processed predecessor.");
+            let new_idom = processed_preds.fold(first_pred, |new_idom, pred_block| {
+                intersect(&idoms, *pred_block, new_idom)
+            });
+            if idoms.get(block) != Some(&new_idom) {
+                idoms.insert(*block, new_idom);
+                changed = true;
+            }
+        }
+    }
+    idoms
+}
+
+fn dominance_frontiers(
+    rpo: &[Block],
+    preds: &HashMap<Block, Vec<Block>>,
+    idoms: &HashMap<Block, Block>,
+) -> HashMap<Block, Vec<Block>> {
+    let mut frontiers: HashMap<Block, Vec<Block>> =
+        rpo.iter().map(|block| (*block, Vec::new())).collect();
+    for block in rpo {
+        if preds[block].len() < 2 {
+            continue;
+        }
+        for pred_block in &preds[block] {
+            let mut runner = *pred_block;
+            while runner != idoms[block] {
+                let frontier = frontiers.get_mut(&runner).unwrap();
+                if !frontier.contains(block) {
+                    frontier.push(*block);
+                }
+                runner = idoms[&runner];
+            }
+        }
+    }
+    frontiers
+}
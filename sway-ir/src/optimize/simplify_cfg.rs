@@ -0,0 +1,226 @@
+//! Control flow graph simplification.
+//!
+//! - constant branch folding - a `cbr` on a constant condition is replaced with a `br`.
+//! - unreachable block removal - blocks which can't be reached from the entry block are removed.
+//! - block merging - a block which is the only successor of its only predecessor is appended to
+//!   that predecessor.
+//! - branch threading - a block which does nothing but branch to another block is bypassed by
+//!   each of its predecessors.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    block::Block,
+    constant::{Constant, ConstantValue},
+    context::Context,
+    error::IrError,
+    function::Function,
+    instruction::Instruction,
+    value::ValueDatum,
+};
+
+/// Simplify the control flow graph of a function until no more simplifications can be made.
+pub fn simplify_cfg(context: &mut Context, function: &Function) -> Result<bool, IrError> {
+    let mut modified = false;
+    loop {
+        if fold_const_cond_branch(context, function) {
+            modified = true;
+            continue;
+        }
+        if remove_unreachable_blocks(context, function)? {
+            modified = true;
+            continue;
+        }
+        if merge_blocks(context, function)? {
+            modified = true;
+            continue;
+        }
+        if thread_branch_only_block(context, function)? {
+            modified = true;
+            continue;
+        }
+
+        // Other simplifications here... always continue to the top if one returns true.
+        break;
+    }
+    Ok(modified)
+}
+
+fn fold_const_cond_branch(context: &mut Context, function: &Function) -> bool {
+    // Find a candidate `cbr` with a constant condition.
+    let candidate =
+        function
+            .block_iter(context)
+            .find_map(|block| match block.get_term_inst(context) {
+                Some(Instruction::ConditionalBranch {
+                    cond_value,
+                    true_block,
+                    false_block,
+                }) => match &context.values[cond_value.0].value {
+                    ValueDatum::Constant(Constant {
+                        value: ConstantValue::Bool(cond),
+                        ..
+                    }) => Some((block, *cond, *true_block, *false_block)),
+                    _otherwise => None,
+                },
+                _otherwise => None,
+            });
+
+    match candidate {
+        Some((block, cond, true_block, false_block)) => {
+            let (taken_block, untaken_block) = if cond {
+                (true_block, false_block)
+            } else {
+                (false_block, true_block)
+            };
+
+            // The untaken block no longer receives any values from this block.
+            if untaken_block != taken_block {
+                untaken_block.remove_phi_val_coming_from(context, &block);
+            }
+
+            // Replace the `cbr` in place, which keeps its span.
+            let term_val = *context.blocks[block.0].instructions.last().unwrap();
+            context.values[term_val.0].value =
+                ValueDatum::Instruction(Instruction::Branch(taken_block));
+            true
+        }
+        None => false,
+    }
+}
+
+fn remove_unreachable_blocks(context: &mut Context, function: &Function) -> Result<bool, IrError> {
+    let mut reachable = HashSet::new();
+    let mut worklist = vec![function.get_entry_block(context)];
+    while let Some(block) = worklist.pop() {
+        if reachable.insert(block) {
+            worklist.append(&mut block.successors(context));
+        }
+    }
+
+    let unreachable_blocks = function
+        .block_iter(context)
+        .filter(|block| !reachable.contains(block))
+        .collect::<Vec<_>>();
+    if unreachable_blocks.is_empty() {
+        return Ok(false);
+    }
+
+    // Remove any references to the unreachable blocks from the phis of their successors first,
+    // since the successors may be unreachable too.
+    for block in &unreachable_blocks {
+        for succ_block in block.successors(context) {
+            succ_block.remove_phi_val_coming_from(context, block);
+        }
+    }
+    for block in &unreachable_blocks {
+        function.remove_block(context, block)?;
+    }
+    Ok(true)
+}
+
+fn merge_blocks(context: &mut Context, function: &Function) -> Result<bool, IrError> {
+    // Find a block which unconditionally branches to a block which has no other predecessors.
+    let entry_block = function.get_entry_block(context);
+    let pred_counts = predecessor_counts(context, function);
+    let candidate = function.block_iter(context).find_map(|from_block| {
+        match from_block.get_term_inst(context) {
+            Some(Instruction::Branch(to_block))
+                if *to_block != from_block
+                    && *to_block != entry_block
+                    && pred_counts.get(to_block) == Some(&1) =>
+            {
+                Some((from_block, *to_block))
+            }
+            _otherwise => None,
+        }
+    });
+
+    let (from_block, to_block) = match candidate {
+        Some(blocks) => blocks,
+        None => return Ok(false),
+    };
+
+    // The phis in `to_block` can only receive a value from `from_block`, so they're replaced by
+    // those values.
+    for (to_phi, phi_val) in to_block.get_phi_vals_coming_from(context, &from_block) {
+        function.replace_value(context, to_phi, phi_val, None);
+    }
+
+    // Replace the branch with the rest of the instructions from `to_block`.
+    let branch_val = context.blocks[from_block.0].instructions.pop().unwrap();
+    context.values.remove(branch_val.0);
+    let num_phis = to_block.get_phis(context).len();
+    let mut to_instructions = context.blocks[to_block.0].instructions.split_off(num_phis);
+    context.blocks[from_block.0]
+        .instructions
+        .append(&mut to_instructions);
+
+    // The successors of `to_block` are now the successors of `from_block`.
+    for succ_block in from_block.successors(context) {
+        succ_block.update_phi_source_block(context, to_block, from_block);
+    }
+
+    function.remove_block(context, &to_block)?;
+    Ok(true)
+}
+
+fn thread_branch_only_block(context: &mut Context, function: &Function) -> Result<bool, IrError> {
+    // Find a block which contains only an empty phi and a branch, and for which every predecessor
+    // can branch straight to its destination instead without confusing the destination's phis.
+    let entry_block = function.get_entry_block(context);
+    let candidate = function.block_iter(context).find_map(|block| {
+        if block == entry_block || context.blocks[block.0].instructions.len() != 2 {
+            return None;
+        }
+        let is_empty_phi = matches!(
+            &context.values[block.get_phi(context).0].value,
+            ValueDatum::Instruction(Instruction::Phi(pairs, _)) if pairs.is_empty()
+        );
+        let to_block = match block.get_term_inst(context) {
+            Some(Instruction::Branch(to_block)) if is_empty_phi && *to_block != block => *to_block,
+            _otherwise => return None,
+        };
+
+        let pred_blocks = function
+            .block_iter(context)
+            .filter(|pred_block| pred_block.successors(context).contains(&block))
+            .collect::<Vec<_>>();
+        if pred_blocks.iter().all(|pred_block| {
+            let succ_blocks = pred_block.successors(context);
+            succ_blocks.iter().filter(|succ| **succ == block).count() == 1
+                && !succ_blocks.contains(&to_block)
+        }) {
+            Some((block, to_block, pred_blocks))
+        } else {
+            None
+        }
+    });
+
+    let (block, to_block, pred_blocks) = match candidate {
+        Some(candidate) => candidate,
+        None => return Ok(false),
+    };
+
+    let phi_vals = to_block.get_phi_vals_coming_from(context, &block);
+    to_block.remove_phi_val_coming_from(context, &block);
+    for pred_block in pred_blocks {
+        pred_block.replace_successor(context, block, to_block);
+        for (to_phi, phi_val) in &phi_vals {
+            to_block.add_to_phi(context, *to_phi, pred_block, *phi_val);
+        }
+    }
+
+    function.remove_block(context, &block)?;
+    Ok(true)
+}
+
+fn predecessor_counts(context: &Context, function: &Function) -> HashMap<Block, usize> {
+    let mut pred_counts = HashMap::new();
+    for block in function.block_iter(context) {
+        for succ_block in block.successors(context) {
+            *pred_counts.entry(succ_block).or_insert(0) += 1;
+        }
+    }
+    pred_counts
+}
@@ -104,9 +104,12 @@ mod ir_builder {
 
             rule operation() -> IrAstOperation
                 = op_asm()
+                / op_binary()
                 / op_branch()
+                / op_call_indirect()
                 / op_call()
                 / op_cbr()
+                / op_cmp()
                 / op_const()
                 / op_extract_element()
                 / op_extract_value()
@@ -129,6 +132,23 @@ mod ir_builder {
                     IrAstOperation::Asm(args, ret, ops, meta_idx)
                 }
 
+            rule op_binary() -> IrAstOperation
+                = op:binary_op_kind() arg1:id() comma() arg2:id() {
+                    IrAstOperation::BinaryOp(op, arg1, arg2)
+                }
+
+            rule binary_op_kind() -> BinaryOpKind
+                = "add" _ { BinaryOpKind::Add }
+                / "sub" _ { BinaryOpKind::Sub }
+                / "mul" _ { BinaryOpKind::Mul }
+                / "div" _ { BinaryOpKind::Div }
+                / "mod" _ { BinaryOpKind::Mod }
+                / "and" _ { BinaryOpKind::And }
+                / "or" _ { BinaryOpKind::Or }
+                / "xor" _ { BinaryOpKind::Xor }
+                / "shl" _ { BinaryOpKind::Shl }
+                / "shr" _ { BinaryOpKind::Shr }
+
             rule op_branch() -> IrAstOperation
                 = "br" _ to_block:id() {
                     IrAstOperation::Br(to_block)
@@ -139,14 +159,35 @@ mod ir_builder {
                     IrAstOperation::Call(callee, args)
             }
 
+            rule op_call_indirect() -> IrAstOperation
+                = "call_indirect" _ target:id() "[" _ callees:(indirect_callee() ** comma()) "]" _
+                  "(" _ args:(id() ** comma()) ")" _ {
+                    IrAstOperation::CallIndirect(target, callees, args)
+            }
+
+            rule indirect_callee() -> (u64, String)
+                = idx:decimal() ":" _ callee:id() {
+                    (idx, callee)
+                }
+
             rule op_cbr() -> IrAstOperation
                 = "cbr" _ cond:id() comma() tblock:id() comma() fblock:id() {
                     IrAstOperation::Cbr(cond, tblock, fblock)
                 }
 
+            rule op_cmp() -> IrAstOperation
+                = "cmp" _ pred:cmp_pred() lhs:id() comma() rhs:id() {
+                    IrAstOperation::Cmp(pred, lhs, rhs)
+                }
+
+            rule cmp_pred() -> Predicate
+                = "eq" _ { Predicate::Equal }
+                / "lt" _ { Predicate::LessThan }
+                / "gt" _ { Predicate::GreaterThan }
+
             rule op_const() -> IrAstOperation
-                = "const" _ ast_ty() cv:constant() {
-                    IrAstOperation::Const(cv)
+                = "const" _ ty:ast_ty() cv:constant() {
+                    IrAstOperation::Const(cv.with_type(&ty))
                 }
 
             rule op_extract_element() -> IrAstOperation
@@ -268,16 +309,10 @@ mod ir_builder {
                 / "true" _ { IrAstConstValue::Bool(true) }
                 / "false" _ { IrAstConstValue::Bool(false) }
                 / "0x" s:$(['0'..='9' | 'a'..='f' | 'A'..='F']*<64>) _ {
-                    let mut bytes: [u8; 32] = [0; 32];
-                    let mut cur_byte: u8 = 0;
-                    for (idx, ch) in s.chars().enumerate() {
-                        cur_byte = (cur_byte << 4) | ch.to_digit(16).unwrap() as u8;
-                        if idx % 2 == 1 {
-                            bytes[idx / 2] = cur_byte;
-                            cur_byte = 0;
-                        }
-                    }
-                    IrAstConstValue::B256(bytes)
+                    IrAstConstValue::B256(hex_bytes(s))
+                }
+                / "0x" s:$(['0'..='9' | 'a'..='f' | 'A'..='F']*<32>) _ {
+                    IrAstConstValue::WideUint(128, hex_bytes(s))
                 }
                 / n:decimal() { IrAstConstValue::Number(n) }
                 / string_const()
@@ -306,6 +341,7 @@ mod ir_builder {
 
             rule field_or_element_const() -> (IrAstTy, IrAstConst)
                 = ty:ast_ty() cv:constant() {
+                    let cv = cv.with_type(&ty);
                     (ty, cv)
                 }
                 / ty:ast_ty() "undef" _ {
@@ -316,6 +352,8 @@ mod ir_builder {
                 = ("unit" / "()") _ { IrAstTy::Unit }
                 / "bool" _ { IrAstTy::Bool }
                 / "u64" _ { IrAstTy::U64 }
+                / "u128" _ { IrAstTy::U128 }
+                / "u256" _ { IrAstTy::U256 }
                 / "b256" _ { IrAstTy::B256 }
                 / "string" _ "<" _ sz:decimal() ">" _ { IrAstTy::String(sz) }
                 / array_ty()
@@ -362,6 +400,9 @@ mod ir_builder {
                 / "span" _ "!" li:decimal() s:decimal() e:decimal() {
                     IrMetadatum::Span { loc_idx: li, start: s as usize, end: e as usize }
                 }
+                / "inlined" _ "!" si:decimal() ['"'] function:$(([^ '"' | '\\'] / ['\\'] ['\\' | '"' ])+) ['"'] _ "!" ci:decimal() {
+                    IrMetadatum::InlinedAt { span_idx: si, function: function.to_owned(), call_site_idx: ci }
+                }
 
             rule id_char0()
                 = quiet!{ ['A'..='Z' | 'a'..='z' | '_'] }
@@ -412,12 +453,12 @@ mod ir_builder {
         context::Context,
         error::IrError,
         function::Function,
-        instruction::Instruction,
+        instruction::{BinaryOpKind, Instruction, Predicate},
         irtype::{Aggregate, Type},
         metadata::{MetadataIndex, Metadatum},
         module::{Kind, Module},
         pointer::Pointer,
-        value::Value,
+        value::{Value, ValueDatum},
     };
 
     #[derive(Debug)]
@@ -457,9 +498,12 @@ mod ir_builder {
             Vec<IrAstAsmOp>,
             Option<MdIdxRef>,
         ),
+        BinaryOp(BinaryOpKind, String, String),
         Br(String),
         Call(String, Vec<String>),
+        CallIndirect(String, Vec<(u64, String)>, Vec<String>),
         Cbr(String, String, String),
+        Cmp(Predicate, String, String),
         Const(IrAstConst),
         ExtractElement(String, IrAstTy, String),
         ExtractValue(String, IrAstTy, Vec<u64>),
@@ -482,6 +526,44 @@ mod ir_builder {
         meta_idx: Option<MdIdxRef>,
     }
 
+    impl IrAstConst {
+        /// Constant values are parsed without their type, but wide integers are written the same
+        /// way as `u64` and `b256` constants, so use the declared type to tell them apart.
+        fn with_type(self, ty: &IrAstTy) -> Self {
+            let value = match (ty, self.value) {
+                (IrAstTy::U128 | IrAstTy::U256, IrAstConstValue::Number(n)) => {
+                    let mut bytes = [0; 32];
+                    bytes[24..].copy_from_slice(&n.to_be_bytes());
+                    IrAstConstValue::WideUint(ty.wide_uint_bits(), bytes)
+                }
+                (
+                    IrAstTy::U128 | IrAstTy::U256,
+                    IrAstConstValue::B256(bytes) | IrAstConstValue::WideUint(_, bytes),
+                ) => IrAstConstValue::WideUint(ty.wide_uint_bits(), bytes),
+                (_, value) => value,
+            };
+            IrAstConst {
+                value,
+                meta_idx: self.meta_idx,
+            }
+        }
+    }
+
+    /// Hex digits to big-endian bytes, right aligned in 256 bits.
+    fn hex_bytes(s: &str) -> [u8; 32] {
+        let mut bytes: [u8; 32] = [0; 32];
+        let offset = 32 - s.len() / 2;
+        let mut cur_byte: u8 = 0;
+        for (idx, ch) in s.chars().enumerate() {
+            cur_byte = (cur_byte << 4) | ch.to_digit(16).unwrap() as u8;
+            if idx % 2 == 1 {
+                bytes[offset + idx / 2] = cur_byte;
+                cur_byte = 0;
+            }
+        }
+        bytes
+    }
+
     #[derive(Debug)]
     enum IrAstConstValue {
         Undef(IrAstTy),
@@ -489,6 +571,7 @@ mod ir_builder {
         Bool(bool),
         B256([u8; 32]),
         Number(u64),
+        WideUint(u16, [u8; 32]),
         String(String),
         Array(IrAstTy, Vec<IrAstConst>),
         Struct(Vec<(IrAstTy, IrAstConst)>),
@@ -519,6 +602,7 @@ mod ir_builder {
                 IrAstConstValue::Bool(b) => Constant::new_bool(*b),
                 IrAstConstValue::B256(bs) => Constant::new_b256(*bs),
                 IrAstConstValue::Number(n) => Constant::new_uint(64, *n),
+                IrAstConstValue::WideUint(nbits, bs) => Constant::new_wide_uint(*nbits, *bs),
                 IrAstConstValue::String(s) => Constant::new_string(s.clone()),
                 IrAstConstValue::Array(el_ty, els) => {
                     let els: Vec<_> = els.iter().map(|cv| cv.value.as_constant(context)).collect();
@@ -545,6 +629,9 @@ mod ir_builder {
                 IrAstConstValue::Bool(b) => Constant::get_bool(context, *b, span_md_idx),
                 IrAstConstValue::B256(bs) => Constant::get_b256(context, *bs, span_md_idx),
                 IrAstConstValue::Number(n) => Constant::get_uint(context, 64, *n, span_md_idx),
+                IrAstConstValue::WideUint(nbits, bs) => {
+                    Constant::get_wide_uint(context, *nbits, *bs, span_md_idx)
+                }
                 IrAstConstValue::String(s) => Constant::get_string(context, s.clone(), span_md_idx),
                 IrAstConstValue::Array(..) => {
                     let array_const = self.as_constant(context);
@@ -563,6 +650,8 @@ mod ir_builder {
         Unit,
         Bool,
         U64,
+        U128,
+        U256,
         B256,
         String(u64),
         Array(Box<IrAstTy>, u64),
@@ -576,6 +665,8 @@ mod ir_builder {
                 IrAstTy::Unit => Type::Unit,
                 IrAstTy::Bool => Type::Bool,
                 IrAstTy::U64 => Type::Uint(64),
+                IrAstTy::U128 => Type::Uint(128),
+                IrAstTy::U256 => Type::Uint(256),
                 IrAstTy::B256 => Type::B256,
                 IrAstTy::String(n) => Type::String(*n),
                 IrAstTy::Array(..) => Type::Array(self.to_ir_aggregate_type(context)),
@@ -584,6 +675,13 @@ mod ir_builder {
             }
         }
 
+        fn wide_uint_bits(&self) -> u16 {
+            match self {
+                IrAstTy::U128 => 128,
+                _otherwise => 256,
+            }
+        }
+
         fn to_ir_aggregate_type(&self, context: &mut Context) -> Aggregate {
             match self {
                 IrAstTy::Array(el_ty, count) => {
@@ -609,6 +707,11 @@ mod ir_builder {
             start: usize,
             end: usize,
         },
+        InlinedAt {
+            span_idx: MdIdxRef,
+            function: String,
+            call_site_idx: MdIdxRef,
+        },
     }
 
     // - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - - -
@@ -633,7 +736,13 @@ mod ir_builder {
         module: Module,
         fn_decl: IrAstFnDecl,
         md_map: &HashMap<MdIdxRef, MetadataIndex>,
-        unresolved_calls: &mut Vec<(Block, Value, String, Vec<Value>, Option<MetadataIndex>)>,
+        unresolved_calls: &mut Vec<(
+            Block,
+            Value,
+            UnresolvedCallee,
+            Vec<Value>,
+            Option<MetadataIndex>,
+        )>,
     ) -> Result<(), IrError> {
         let args: Vec<(String, Type, Option<MetadataIndex>)> = fn_decl
             .args
@@ -691,6 +800,7 @@ mod ir_builder {
             )
         }));
 
+        let mut unresolved_phis = Vec::new();
         for block in fn_decl.blocks {
             build_add_block_instructions(
                 context,
@@ -700,12 +810,14 @@ mod ir_builder {
                 &mut arg_map,
                 md_map,
                 unresolved_calls,
+                &mut unresolved_phis,
             );
         }
+        resolve_phis(context, &named_blocks, &arg_map, unresolved_phis);
         Ok(())
     }
 
-    #[allow(clippy::type_complexity)]
+    #[allow(clippy::type_complexity, clippy::too_many_arguments)]
     fn build_add_block_instructions(
         context: &mut Context,
         ir_block: IrAstBlock,
@@ -713,9 +825,17 @@ mod ir_builder {
         ptr_map: &HashMap<String, Pointer>,
         val_map: &mut HashMap<String, Value>,
         md_map: &HashMap<MdIdxRef, MetadataIndex>,
-        unresolved_calls: &mut Vec<(Block, Value, String, Vec<Value>, Option<MetadataIndex>)>,
+        unresolved_calls: &mut Vec<(
+            Block,
+            Value,
+            UnresolvedCallee,
+            Vec<Value>,
+            Option<MetadataIndex>,
+        )>,
+        unresolved_phis: &mut Vec<(Block, Value, Vec<(String, String)>)>,
     ) {
         let block = named_blocks.get(&ir_block.label).unwrap();
+        let mut first_phi = true;
         for ins in ir_block.instructions {
             let opt_ins_md_idx = ins.meta_idx.map(|mdi| md_map.get(&mdi).unwrap()).copied();
             let ins_val = match ins.op {
@@ -756,6 +876,12 @@ mod ir_builder {
                         .ins(context)
                         .asm_block(args, body, return_name, md_idx)
                 }
+                IrAstOperation::BinaryOp(op, arg1, arg2) => block.ins(context).binary_op(
+                    op,
+                    *val_map.get(&arg1).unwrap(),
+                    *val_map.get(&arg2).unwrap(),
+                    opt_ins_md_idx,
+                ),
                 IrAstOperation::Br(to_block_name) => {
                     let to_block = named_blocks.get(&to_block_name).unwrap();
                     block.ins(context).branch(*to_block, None, opt_ins_md_idx)
@@ -768,7 +894,25 @@ mod ir_builder {
                     unresolved_calls.push((
                         *block,
                         nop,
-                        callee,
+                        UnresolvedCallee::Direct(callee),
+                        args.iter()
+                            .map(|arg_name| val_map.get(arg_name).unwrap())
+                            .cloned()
+                            .collect::<Vec<Value>>(),
+                        opt_ins_md_idx,
+                    ));
+                    nop
+                }
+                IrAstOperation::CallIndirect(target, callees, args) => {
+                    // As above, the callees can only be resolved once all functions are created.
+                    let nop = block.ins(context).nop();
+                    unresolved_calls.push((
+                        *block,
+                        nop,
+                        UnresolvedCallee::Indirect {
+                            target: *val_map.get(&target).unwrap(),
+                            callees,
+                        },
                         args.iter()
                             .map(|arg_name| val_map.get(arg_name).unwrap())
                             .cloned()
@@ -786,6 +930,12 @@ mod ir_builder {
                         opt_ins_md_idx,
                     )
                 }
+                IrAstOperation::Cmp(pred, lhs, rhs) => block.ins(context).cmp(
+                    pred,
+                    *val_map.get(&lhs).unwrap(),
+                    *val_map.get(&rhs).unwrap(),
+                    opt_ins_md_idx,
+                ),
                 IrAstOperation::Const(val) => val.value.as_value(context, opt_ins_md_idx),
                 IrAstOperation::ExtractElement(aval, ty, idx) => {
                     let ir_ty = ty.to_ir_aggregate_type(context);
@@ -833,14 +983,16 @@ mod ir_builder {
                     .load(*val_map.get(&src_name).unwrap(), opt_ins_md_idx),
                 IrAstOperation::Nop => block.ins(context).nop(),
                 IrAstOperation::Phi(pairs) => {
-                    for (block_name, val_name) in pairs {
-                        block.add_phi(
-                            context,
-                            *named_blocks.get(&block_name).unwrap(),
-                            *val_map.get(&val_name).unwrap(),
-                        );
-                    }
-                    block.get_phi(context)
+                    // Phi values may refer to values defined later in the function, e.g., from a
+                    // loop back edge, so they're only resolved once the whole function is built.
+                    let phi_val = if first_phi {
+                        block.get_phi(context)
+                    } else {
+                        block.new_phi(context, Type::Unit)
+                    };
+                    first_phi = false;
+                    unresolved_phis.push((*block, phi_val, pairs));
+                    phi_val
                 }
                 IrAstOperation::PtrCast(value_name, ty) => {
                     let ty = ty.to_ir_type(context);
@@ -876,6 +1028,54 @@ mod ir_builder {
         }
     }
 
+    fn resolve_phis(
+        context: &mut Context,
+        named_blocks: &HashMap<String, Block>,
+        val_map: &HashMap<String, Value>,
+        unresolved_phis: Vec<(Block, Value, Vec<(String, String)>)>,
+    ) {
+        let phis = unresolved_phis
+            .into_iter()
+            .map(|(block, phi_val, pairs)| {
+                for (block_name, val_name) in pairs {
+                    block.add_to_phi(
+                        context,
+                        phi_val,
+                        *named_blocks.get(&block_name).unwrap(),
+                        *val_map.get(&val_name).unwrap(),
+                    );
+                }
+                phi_val
+            })
+            .collect::<Vec<_>>();
+
+        // A phi which takes its first value from another phi which wasn't yet resolved will have
+        // been left with the `Unit` type.  Keep propagating types until they're all settled.
+        loop {
+            let mut changed = false;
+            for phi_val in &phis {
+                let new_ty = match &context.values[phi_val.0].value {
+                    ValueDatum::Instruction(Instruction::Phi(pairs, Type::Unit)) => pairs
+                        .iter()
+                        .filter_map(|(_, value)| value.get_type(context))
+                        .find(|ty| *ty != Type::Unit),
+                    _otherwise => None,
+                };
+                if let Some(new_ty) = new_ty {
+                    if let ValueDatum::Instruction(Instruction::Phi(_, ty)) =
+                        &mut context.values[phi_val.0].value
+                    {
+                        *ty = new_ty;
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+    }
+
     fn build_metadata_map(
         context: &mut Context,
         ir_metadata: &[(MdIdxRef, IrMetadatum)],
@@ -911,36 +1111,92 @@ mod ir_builder {
                 md_map.insert(*idx_ref, MetadataIndex(span_idx));
             }
         }
+
+        // Inlined spans refer to metadata declared before them.
+        for (idx_ref, md) in ir_metadata {
+            if let IrMetadatum::InlinedAt {
+                span_idx,
+                function,
+                call_site_idx,
+            } = md
+            {
+                let inlined_idx = MetadataIndex::inlined_at(
+                    context,
+                    md_map.get(span_idx).copied().unwrap(),
+                    function.clone(),
+                    md_map.get(call_site_idx).copied().unwrap(),
+                );
+                md_map.insert(*idx_ref, inlined_idx);
+            }
+        }
         md_map
     }
 
     #[allow(clippy::type_complexity)]
     fn resolve_calls(
         context: &mut Context,
-        unresolved_calls: Vec<(Block, Value, String, Vec<Value>, Option<MetadataIndex>)>,
+        unresolved_calls: Vec<(
+            Block,
+            Value,
+            UnresolvedCallee,
+            Vec<Value>,
+            Option<MetadataIndex>,
+        )>,
     ) -> Result<(), IrError> {
         // All of the call instructions are currently NOPs which need to be replaced with actual
         // calls.  We couldn't do it above until we'd gone and created all the functions first.
         //
         // Now we can loop and find the callee function for each call and replace the NOPs.
-        for (block, nop, callee, args, opt_ins_md_idx) in unresolved_calls {
-            let function = context
+        let find_function = |context: &Context, name: &str| {
+            context
                 .functions
                 .iter()
                 .find_map(|(idx, content)| {
-                    if content.name == callee {
+                    if content.name == name {
                         Some(Function(idx))
                     } else {
                         None
                     }
                 })
-                .unwrap();
-            let call_val =
-                Value::new_instruction(context, Instruction::Call(function, args), opt_ins_md_idx);
+                .unwrap()
+        };
+        let mut resolved_calls = Vec::new();
+        for (block, nop, callee, args, opt_ins_md_idx) in unresolved_calls {
+            let call_ins = match callee {
+                UnresolvedCallee::Direct(callee) => {
+                    Instruction::Call(find_function(context, &callee), args)
+                }
+                UnresolvedCallee::Indirect { target, callees } => Instruction::CallIndirect {
+                    target,
+                    callees: callees
+                        .iter()
+                        .map(|(idx, callee)| (*idx, find_function(context, callee)))
+                        .collect(),
+                    args,
+                },
+            };
+            let call_val = Value::new_instruction(context, call_ins, opt_ins_md_idx);
             block.replace_instruction(context, nop, call_val)?;
+            resolved_calls.push((nop, call_val));
+        }
+        // A call may be passed the result of another call, which was still a nop when its args
+        // were parsed.
+        for (nop, call_val) in &resolved_calls {
+            for (_, other_call_val) in &resolved_calls {
+                other_call_val.replace_instruction_value(context, *nop, *call_val);
+            }
         }
         Ok(())
     }
+
+    /// A callee which can't be resolved until all of the functions have been created.
+    enum UnresolvedCallee {
+        Direct(String),
+        Indirect {
+            target: Value,
+            callees: Vec<(u64, String)>,
+        },
+    }
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -0,0 +1,412 @@
+//! A manager for registering and running IR optimization passes.
+//!
+//! Each [`Pass`] is registered with a [`PassManager`] under a unique name, along with some
+//! metadata describing it: whether it is run over each function in turn or over each module as a
+//! whole, and whether it preserves the control flow graph of the functions it modifies.
+//!
+//! Passes are composed into [`Pipeline`]s, which are also registered by name.  A pipeline is an
+//! ordered list of steps, each of which is either a single pass or a group of passes which are
+//! re-run until none of them modify the IR any further.  The standard pipelines `O0`, `O1` and
+//! `O2` are registered by [`PassManager::default()`].
+//!
+//! The manager may optionally verify the IR after each pass is run, and time each pass.
+
+use std::time::{Duration, Instant};
+
+use crate::{context::Context, error::IrError, function::Function, module::Module, optimize};
+
+/// Whether a [`Pass`] is run over each function or over each module.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PassScope {
+    Function,
+    Module,
+}
+
+/// The entry point to a [`Pass`].  Each returns whether it modified the IR.
+#[derive(Clone, Copy)]
+pub enum PassRunner {
+    Function(fn(&mut Context, &Function) -> Result<bool, IrError>),
+    Module(fn(&mut Context, &Module) -> Result<bool, IrError>),
+}
+
+/// An optimization pass which may be registered with a [`PassManager`].
+#[derive(Clone, Copy)]
+pub struct Pass {
+    /// The unique name used to refer to this pass in pipelines and on the command line.
+    pub name: &'static str,
+    /// A short description of what this pass does.
+    pub descr: &'static str,
+    /// Whether the blocks and the branches between them are left unchanged by this pass.
+    pub preserves_cfg: bool,
+    pub runner: PassRunner,
+}
+
+impl Pass {
+    /// Return whether this pass is run over each function or over each module.
+    pub fn scope(&self) -> PassScope {
+        match self.runner {
+            PassRunner::Function(_) => PassScope::Function,
+            PassRunner::Module(_) => PassScope::Module,
+        }
+    }
+}
+
+/// A single step within a [`Pipeline`].
+#[derive(Clone, Debug)]
+pub enum PipelineStep {
+    /// Run the named pass once.
+    Pass(String),
+    /// Run the named passes in order, repeatedly, until none of them modify the IR or
+    /// `max_iterations` is reached.
+    FixedPoint {
+        passes: Vec<String>,
+        max_iterations: usize,
+    },
+}
+
+/// An ordered sequence of passes to be run by a [`PassManager`].
+#[derive(Clone, Debug, Default)]
+pub struct Pipeline {
+    steps: Vec<PipelineStep>,
+}
+
+impl Pipeline {
+    /// Return a new empty pipeline.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a single run of the named pass.
+    pub fn pass(mut self, name: &str) -> Self {
+        self.steps.push(PipelineStep::Pass(name.to_owned()));
+        self
+    }
+
+    /// Append a group of passes to be run until they reach a fixed point.
+    pub fn fixed_point(mut self, names: &[&str], max_iterations: usize) -> Self {
+        self.steps.push(PipelineStep::FixedPoint {
+            passes: names.iter().map(|name| (*name).to_owned()).collect(),
+            max_iterations,
+        });
+        self
+    }
+
+    /// Append every step of another pipeline.
+    pub fn extend(mut self, other: &Pipeline) -> Self {
+        self.steps.extend(other.steps.iter().cloned());
+        self
+    }
+
+    /// Return an iterator over the steps of this pipeline.
+    pub fn steps_iter(&self) -> impl Iterator<Item = &PipelineStep> {
+        self.steps.iter()
+    }
+
+    fn pass_names(&self) -> impl Iterator<Item = &String> {
+        self.steps.iter().flat_map(|step| match step {
+            PipelineStep::Pass(name) => std::slice::from_ref(name).iter(),
+            PipelineStep::FixedPoint { passes, .. } => passes.iter(),
+        })
+    }
+}
+
+/// The accumulated cost of running a single pass.
+#[derive(Clone, Debug)]
+pub struct PassTiming {
+    pub name: &'static str,
+    /// The number of times the pass was run.
+    pub runs: usize,
+    /// The number of runs which modified the IR.
+    pub modifications: usize,
+    pub elapsed: Duration,
+}
+
+/// A registry of passes and pipelines, and the means to run them.
+pub struct PassManager {
+    passes: Vec<Pass>,
+    pipelines: Vec<(String, Pipeline)>,
+    verify_each: bool,
+    time_passes: bool,
+    timings: Vec<PassTiming>,
+}
+
+impl Default for PassManager {
+    /// Return a manager with every standard pass and pipeline registered.
+    ///
+    /// - `O0` only turns indirect calls with a known callee into direct calls, and inlines the
+    ///   calls to functions which are called once or are small.
+    /// - `O1` also promotes locals to registers, combines constants, simplifies the CFG and removes
+    ///   dead code.
+    /// - `O2` repeats the `O1` optimizations until they no longer change anything.
+    fn default() -> Self {
+        let mut pm = PassManager::new();
+        for pass in standard_passes() {
+            pm.register(pass)
+                .expect("Standard passes must have unique names.");
+        }
+
+        let o0 = Pipeline::new().pass("devirtualize").pass("inline");
+        let o1 = Pipeline::new()
+            .extend(&o0)
+            .pass("mem2reg")
+            .pass("constcombine")
+            .pass("simplifycfg")
+            .pass("dce");
+        let o2 = Pipeline::new().extend(&o0).fixed_point(
+            &[
+                "devirtualize",
+                "inline",
+                "mem2reg",
+                "constcombine",
+                "simplifycfg",
+                "dce",
+            ],
+            8,
+        );
+        for (name, pipeline) in [("O0", o0), ("O1", o1), ("O2", o2)] {
+            pm.register_pipeline(name, pipeline)
+                .expect("Standard pipelines must use standard passes.");
+        }
+        pm
+    }
+}
+
+impl PassManager {
+    /// Return a new manager with no passes or pipelines registered.
+    pub fn new() -> Self {
+        PassManager {
+            passes: Vec::new(),
+            pipelines: Vec::new(),
+            verify_each: false,
+            time_passes: false,
+            timings: Vec::new(),
+        }
+    }
+
+    /// Register a new pass.  Its name must not already be in use.
+    pub fn register(&mut self, pass: Pass) -> Result<(), IrError> {
+        if self.get_pass(pass.name).is_some() {
+            return Err(IrError::DuplicatePass(pass.name.to_owned()));
+        }
+        self.passes.push(pass);
+        Ok(())
+    }
+
+    /// Register a new pipeline, replacing any existing pipeline with the same name.  Every pass it
+    /// refers to must already be registered.
+    pub fn register_pipeline(&mut self, name: &str, pipeline: Pipeline) -> Result<(), IrError> {
+        if let Some(unknown) = pipeline
+            .pass_names()
+            .find(|pass_name| self.get_pass(pass_name).is_none())
+        {
+            return Err(IrError::UnknownPass(unknown.clone()));
+        }
+        self.pipelines.retain(|(existing, _)| existing != name);
+        self.pipelines.push((name.to_owned(), pipeline));
+        Ok(())
+    }
+
+    /// Return the pass registered with this name, if found.
+    pub fn get_pass(&self, name: &str) -> Option<&Pass> {
+        self.passes.iter().find(|pass| pass.name == name)
+    }
+
+    /// Return the pipeline registered with this name, if found.
+    pub fn get_pipeline(&self, name: &str) -> Option<&Pipeline> {
+        self.pipelines.iter().find_map(|(existing, pipeline)| {
+            if existing == name {
+                Some(pipeline)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Return an iterator over every registered pass, in the order they were registered.
+    pub fn passes_iter(&self) -> impl Iterator<Item = &Pass> {
+        self.passes.iter()
+    }
+
+    /// Return an iterator over the names of every registered pipeline.
+    pub fn pipeline_names_iter(&self) -> impl Iterator<Item = &str> {
+        self.pipelines.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Set whether the IR is verified after each pass is run.
+    pub fn set_verify_each(&mut self, verify_each: bool) {
+        self.verify_each = verify_each;
+    }
+
+    /// Set whether the time taken by each pass is recorded.
+    pub fn set_time_passes(&mut self, time_passes: bool) {
+        self.time_passes = time_passes;
+    }
+
+    /// Return the recorded timings, in the order each pass was first run.  This is empty unless
+    /// timing has been enabled with [`PassManager::set_time_passes`].
+    pub fn timings(&self) -> &[PassTiming] {
+        &self.timings
+    }
+
+    /// Return a human readable table of the recorded timings.
+    pub fn timings_report(&self) -> String {
+        let total: Duration = self.timings.iter().map(|timing| timing.elapsed).sum();
+        let mut report = format!(
+            "{:<16} {:>6} {:>9} {:>12}\n",
+            "pass", "runs", "modified", "time (us)"
+        );
+        for timing in &self.timings {
+            report += &format!(
+                "{:<16} {:>6} {:>9} {:>12}\n",
+                timing.name,
+                timing.runs,
+                timing.modifications,
+                timing.elapsed.as_micros()
+            );
+        }
+        report += &format!(
+            "{:<16} {:>6} {:>9} {:>12}\n",
+            "total",
+            "",
+            "",
+            total.as_micros()
+        );
+        report
+    }
+
+    /// Run the named pipeline over the whole context.  Returns whether the IR was modified.
+    pub fn run_pipeline(&mut self, context: &mut Context, name: &str) -> Result<bool, IrError> {
+        let pipeline = self
+            .get_pipeline(name)
+            .cloned()
+            .ok_or_else(|| IrError::UnknownPipeline(name.to_owned()))?;
+        self.run_steps(context, &pipeline)
+    }
+
+    /// Run every step of a pipeline, which need not be registered, over the whole context.
+    /// Returns whether the IR was modified.
+    pub fn run_steps(
+        &mut self,
+        context: &mut Context,
+        pipeline: &Pipeline,
+    ) -> Result<bool, IrError> {
+        let mut modified = false;
+        for step in pipeline.steps_iter() {
+            match step {
+                PipelineStep::Pass(name) => {
+                    modified |= self.run_pass(context, name)?;
+                }
+                PipelineStep::FixedPoint {
+                    passes,
+                    max_iterations,
+                } => {
+                    for _ in 0..*max_iterations {
+                        let mut iter_modified = false;
+                        for name in passes {
+                            iter_modified |= self.run_pass(context, name)?;
+                        }
+                        if !iter_modified {
+                            break;
+                        }
+                        modified = true;
+                    }
+                }
+            }
+        }
+        Ok(modified)
+    }
+
+    /// Run the named pass over the whole context.  Function passes are run over every function in
+    /// every module.  Returns whether the IR was modified.
+    pub fn run_pass(&mut self, context: &mut Context, name: &str) -> Result<bool, IrError> {
+        let pass = *self
+            .get_pass(name)
+            .ok_or_else(|| IrError::UnknownPass(name.to_owned()))?;
+
+        let start = Instant::now();
+        let mut modified = false;
+        for module in context.module_iter() {
+            match pass.runner {
+                PassRunner::Module(run) => {
+                    modified |= run(context, &module)?;
+                }
+                PassRunner::Function(run) => {
+                    let functions = module.function_iter(context).collect::<Vec<_>>();
+                    for function in functions {
+                        modified |= run(context, &function)?;
+                    }
+                }
+            }
+        }
+        let elapsed = start.elapsed();
+
+        if self.time_passes {
+            self.record_timing(pass.name, modified, elapsed);
+        }
+        if self.verify_each {
+            context.verify()?;
+        }
+        Ok(modified)
+    }
+
+    fn record_timing(&mut self, name: &'static str, modified: bool, elapsed: Duration) {
+        let idx = match self.timings.iter().position(|timing| timing.name == name) {
+            Some(idx) => idx,
+            None => {
+                self.timings.push(PassTiming {
+                    name,
+                    runs: 0,
+                    modifications: 0,
+                    elapsed: Duration::ZERO,
+                });
+                self.timings.len() - 1
+            }
+        };
+        let timing = &mut self.timings[idx];
+        timing.runs += 1;
+        timing.modifications += modified as usize;
+        timing.elapsed += elapsed;
+    }
+}
+
+fn standard_passes() -> Vec<Pass> {
+    vec![
+        Pass {
+            name: "devirtualize",
+            descr: "Replace indirect calls with a known callee with direct calls.",
+            preserves_cfg: true,
+            runner: PassRunner::Function(optimize::devirtualize_indirect_calls),
+        },
+        Pass {
+            name: "inline",
+            descr: "Inline calls to non-recursive functions which are called once or are small.",
+            preserves_cfg: false,
+            runner: PassRunner::Module(optimize::inline_calls),
+        },
+        Pass {
+            name: "mem2reg",
+            descr: "Promote scalar locals to SSA values, adding phis where needed.",
+            preserves_cfg: true,
+            runner: PassRunner::Function(optimize::promote_to_registers),
+        },
+        Pass {
+            name: "constcombine",
+            descr: "Evaluate constant expressions at compile time.",
+            preserves_cfg: true,
+            runner: PassRunner::Function(optimize::combine_constants),
+        },
+        Pass {
+            name: "simplifycfg",
+            descr: "Fold constant branches, remove unreachable blocks and merge straight-line \
+                    blocks.",
+            preserves_cfg: false,
+            runner: PassRunner::Function(optimize::simplify_cfg),
+        },
+        Pass {
+            name: "dce",
+            descr: "Remove unused instructions which have no side effects.",
+            preserves_cfg: true,
+            runner: PassRunner::Function(optimize::dce),
+        },
+    ]
+}
@@ -12,7 +12,7 @@ use crate::{
     constant::{Constant, ConstantValue},
     context::Context,
     function::{Function, FunctionContent},
-    instruction::Instruction,
+    instruction::{BinaryOpKind, Instruction, Predicate},
     irtype::Type,
     metadata::{MetadataIndex, Metadatum},
     module::{Kind, ModuleContent},
@@ -266,17 +266,12 @@ fn maybe_constant_phi_to_doc(
     caller: &Block,
     callee: &Block,
 ) -> Doc {
-    if let ValueDatum::Instruction(Instruction::Phi(pairs)) =
-        &context.values[callee.get_phi(context).0].value
-    {
-        pairs
-            .iter()
-            .find(|(block, _)| block == caller)
-            .map(|(_, phi_val)| maybe_constant_to_doc(context, md_namer, namer, phi_val))
-            .unwrap_or(Doc::Empty)
-    } else {
-        unreachable!("Phi must be an instruction.")
-    }
+    callee
+        .get_phi_vals_coming_from(context, caller)
+        .iter()
+        .fold(Doc::Empty, |doc, (_, phi_val)| {
+            doc.append(maybe_constant_to_doc(context, md_namer, namer, phi_val))
+        })
 }
 
 fn instruction_to_doc<'a>(
@@ -294,6 +289,18 @@ fn instruction_to_doc<'a>(
             Instruction::AsmBlock(asm, args) => {
                 asm_block_to_doc(context, md_namer, namer, ins_value, asm, args, span_md_idx)
             }
+            Instruction::BinaryOp { op, arg1, arg2 } => {
+                maybe_constant_to_doc(context, md_namer, namer, arg1)
+                    .append(maybe_constant_to_doc(context, md_namer, namer, arg2))
+                    .append(Doc::text_line(format!(
+                        "{} = {} {}, {}{}",
+                        namer.name(context, ins_value),
+                        op.as_str(),
+                        namer.name(context, arg1),
+                        namer.name(context, arg2),
+                        md_namer.meta_as_string(context, span_md_idx, true),
+                    )))
+            }
             Instruction::Branch(to_block) => maybe_constant_phi_to_doc(
                 context, md_namer, namer, block, to_block,
             )
@@ -323,6 +330,51 @@ fn instruction_to_doc<'a>(
                         Some(_) => Doc::text(md_namer.meta_as_string(context, span_md_idx, true)),
                     }),
                 )),
+            Instruction::CallIndirect {
+                target,
+                callees,
+                args,
+            } => std::iter::once(target)
+                .chain(args.iter())
+                .fold(Doc::Empty, |doc, arg_val| {
+                    doc.append(maybe_constant_to_doc(context, md_namer, namer, arg_val))
+                })
+                .append(Doc::line(
+                    Doc::text(format!(
+                        "{} = call_indirect {} [{}]",
+                        namer.name(context, ins_value),
+                        namer.name(context, target),
+                        callees
+                            .iter()
+                            .map(|(idx, func)| format!(
+                                "{}: {}",
+                                idx, context.functions[func.0].name
+                            ))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ))
+                    .append(Doc::in_parens_comma_sep(
+                        args.iter()
+                            .map(|arg_val| Doc::text(namer.name(context, arg_val)))
+                            .collect(),
+                    ))
+                    .append(match span_md_idx {
+                        None => Doc::Empty,
+                        Some(_) => Doc::text(md_namer.meta_as_string(context, span_md_idx, true)),
+                    }),
+                )),
+            Instruction::Cmp(pred, lhs_value, rhs_value) => {
+                maybe_constant_to_doc(context, md_namer, namer, lhs_value)
+                    .append(maybe_constant_to_doc(context, md_namer, namer, rhs_value))
+                    .append(Doc::text_line(format!(
+                        "{} = cmp {} {}, {}{}",
+                        namer.name(context, ins_value),
+                        pred.as_str(),
+                        namer.name(context, lhs_value),
+                        namer.name(context, rhs_value),
+                        md_namer.meta_as_string(context, span_md_idx, true),
+                    )))
+            }
             Instruction::ConditionalBranch {
                 cond_value,
                 true_block,
@@ -330,7 +382,21 @@ fn instruction_to_doc<'a>(
             } => {
                 let true_label = &context.blocks[true_block.0].label;
                 let false_label = &context.blocks[false_block.0].label;
+                // Constants passed to both destinations only need to be printed once.
+                let true_phi_vals = true_block
+                    .get_phi_vals_coming_from(context, block)
+                    .into_iter()
+                    .map(|(_, phi_val)| phi_val)
+                    .collect::<Vec<_>>();
+                let false_phi_consts_doc = false_block
+                    .get_phi_vals_coming_from(context, block)
+                    .iter()
+                    .filter(|(_, phi_val)| !true_phi_vals.contains(phi_val))
+                    .fold(Doc::Empty, |doc, (_, phi_val)| {
+                        doc.append(maybe_constant_to_doc(context, md_namer, namer, phi_val))
+                    });
                 maybe_constant_phi_to_doc(context, md_namer, namer, block, true_block)
+                    .append(false_phi_consts_doc)
                     .append(maybe_constant_to_doc(context, md_namer, namer, cond_value))
                     .append(Doc::text_line(format!(
                         "cbr {}, {true_label}, {false_label}{}",
@@ -438,7 +504,7 @@ fn instruction_to_doc<'a>(
                 md_namer.meta_as_string(context, span_md_idx, true),
             )),
             Instruction::Nop => Doc::text_line(format!("{} = nop", namer.name(context, ins_value))),
-            Instruction::Phi(pairs) => {
+            Instruction::Phi(pairs, _) => {
                 if pairs.is_empty() {
                     Doc::Empty
                 } else {
@@ -595,6 +661,17 @@ fn metadata_to_doc(context: &Context, md_namer: &MetadataNamer) -> Doc {
                 } => md_namer
                     .get(loc_idx)
                     .map(|loc_ref_idx| format!("!{ref_idx} = span !{loc_ref_idx} {start} {end}")),
+                Metadatum::InlinedAt {
+                    span,
+                    function,
+                    call_site,
+                } => md_namer.get(span).zip(md_namer.get(call_site)).map(
+                    |(span_ref_idx, call_site_ref_idx)| {
+                        format!(
+                            "!{ref_idx} = inlined !{span_ref_idx} {function:?} !{call_site_ref_idx}"
+                        )
+                    },
+                ),
             }
             .map(&Doc::text_line)
         })
@@ -615,6 +692,22 @@ impl Constant {
             ConstantValue::Unit => "unit ()".into(),
             ConstantValue::Bool(b) => format!("bool {}", if *b { "true" } else { "false" }),
             ConstantValue::Uint(v) => format!("{} {}", self.ty.as_string(context), v),
+            ConstantValue::WideUint(bs) => {
+                // Only as many hex digits as the type has bits.
+                let nbytes = match self.ty {
+                    Type::Uint(nbits) => nbits as usize / 8,
+                    _otherwise => bs.len(),
+                };
+                format!(
+                    "{} 0x{}",
+                    self.ty.as_string(context),
+                    bs[bs.len() - nbytes..]
+                        .iter()
+                        .map(|b| format!("{b:02x}"))
+                        .collect::<Vec<String>>()
+                        .concat()
+                )
+            }
             ConstantValue::B256(bs) => format!(
                 "b256 0x{}",
                 bs.iter()
@@ -645,6 +738,33 @@ impl Constant {
     }
 }
 
+impl BinaryOpKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            BinaryOpKind::Add => "add",
+            BinaryOpKind::Sub => "sub",
+            BinaryOpKind::Mul => "mul",
+            BinaryOpKind::Div => "div",
+            BinaryOpKind::Mod => "mod",
+            BinaryOpKind::And => "and",
+            BinaryOpKind::Or => "or",
+            BinaryOpKind::Xor => "xor",
+            BinaryOpKind::Shl => "shl",
+            BinaryOpKind::Shr => "shr",
+        }
+    }
+}
+
+impl Predicate {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Predicate::Equal => "eq",
+            Predicate::LessThan => "lt",
+            Predicate::GreaterThan => "gt",
+        }
+    }
+}
+
 impl Pointer {
     fn as_string(&self, context: &Context, name: &str) -> String {
         let PointerContent { ty, is_mutable, .. } = &context.pointers[self.0];
@@ -745,8 +865,17 @@ impl MetadataNamer {
     }
 
     fn get_or_add(&mut self, context: &Context, md_idx: &MetadataIndex) -> u64 {
-        if let Metadatum::Span { loc_idx, .. } = &context.metadata[md_idx.0] {
-            self.get_or_add(context, loc_idx);
+        match &context.metadata[md_idx.0] {
+            Metadatum::Span { loc_idx, .. } => {
+                self.get_or_add(context, loc_idx);
+            }
+            Metadatum::InlinedAt {
+                span, call_site, ..
+            } => {
+                self.get_or_add(context, span);
+                self.get_or_add(context, call_site);
+            }
+            Metadatum::FileLocation(..) => (),
         }
         self.md_map.get(md_idx).copied().unwrap_or_else(|| {
             let new_idx = self.next_md_idx;
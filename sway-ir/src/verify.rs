@@ -7,15 +7,15 @@
 //! and make them obsolete or outdated.  So once the IR is in a stable state and in use in the Sway
 //! compiler, this module must be updated and used.**
 
-use std::iter::FromIterator;
+use std::{collections::HashSet, iter::FromIterator};
 
 use crate::{
     asm::{AsmArg, AsmBlock},
-    block::{Block, BlockContent},
+    block::Block,
     context::Context,
     error::IrError,
     function::{Function, FunctionContent},
-    instruction::Instruction,
+    instruction::{BinaryOpKind, Instruction, Predicate},
     irtype::{Aggregate, Type},
     module::ModuleContent,
     pointer::Pointer,
@@ -39,8 +39,23 @@ impl Context {
     }
 
     fn verify_function(&self, function: &FunctionContent) -> Result<(), IrError> {
+        // Code following a `ret` or `br` is compiled into unreachable blocks and may pass
+        // mistyped placeholder values to phis, so we need to know which blocks are reachable.
+        let mut reachable = HashSet::new();
+        let mut worklist = function
+            .blocks
+            .first()
+            .copied()
+            .into_iter()
+            .collect::<Vec<_>>();
+        while let Some(block) = worklist.pop() {
+            if reachable.insert(block) {
+                worklist.append(&mut block.successors(self));
+            }
+        }
+
         for block in &function.blocks {
-            self.verify_block(function, &self.blocks[block.0])?;
+            self.verify_block(function, &reachable, block)?;
         }
         Ok(())
     }
@@ -48,23 +63,45 @@ impl Context {
     fn verify_block(
         &self,
         function: &FunctionContent,
-        block: &BlockContent,
+        reachable: &HashSet<Block>,
+        block: &Block,
     ) -> Result<(), IrError> {
-        for ins in &block.instructions {
-            self.verify_instruction(function, &self.values[ins.0].value)?;
+        let block_content = &self.blocks[block.0];
+        for ins in &block_content.instructions {
+            self.verify_instruction(function, reachable, block, &self.values[ins.0].value)?;
         }
+
+        // Phis must all be grouped together at the start of the block.
+        let is_phi = |ins: &Value| {
+            matches!(
+                &self.values[ins.0].value,
+                ValueDatum::Instruction(Instruction::Phi(..))
+            )
+        };
+        if block_content
+            .instructions
+            .iter()
+            .skip_while(|ins| is_phi(*ins))
+            .any(|ins| is_phi(ins))
+        {
+            return Err(IrError::MisplacedPhi(block_content.label.clone()));
+        }
+
         let (last_is_term, num_terms) =
-            block.instructions.iter().fold((false, 0), |(_, n), ins| {
-                if ins.is_terminator(self) {
-                    (true, n + 1)
-                } else {
-                    (false, n)
-                }
-            });
+            block_content
+                .instructions
+                .iter()
+                .fold((false, 0), |(_, n), ins| {
+                    if ins.is_terminator(self) {
+                        (true, n + 1)
+                    } else {
+                        (false, n)
+                    }
+                });
         if !last_is_term {
-            Err(IrError::MissingTerminator(block.label.clone()))
+            Err(IrError::MissingTerminator(block_content.label.clone()))
         } else if num_terms != 1 {
-            Err(IrError::MisplacedTerminator(block.label.clone()))
+            Err(IrError::MisplacedTerminator(block_content.label.clone()))
         } else {
             Ok(())
         }
@@ -73,13 +110,26 @@ impl Context {
     fn verify_instruction(
         &self,
         function: &FunctionContent,
+        reachable: &HashSet<Block>,
+        block: &Block,
         instruction: &ValueDatum,
     ) -> Result<(), IrError> {
         if let ValueDatum::Instruction(instruction) = instruction {
             match instruction {
                 Instruction::AsmBlock(asm, args) => self.verify_asm_block(asm, args)?,
+                Instruction::BinaryOp { op, arg1, arg2 } => {
+                    self.verify_binary_op(op, arg1, arg2)?
+                }
                 Instruction::Branch(block) => self.verify_br(block)?,
                 Instruction::Call(func, args) => self.verify_call(func, args)?,
+                Instruction::CallIndirect {
+                    target,
+                    callees,
+                    args,
+                } => self.verify_call_indirect(target, callees, args)?,
+                Instruction::Cmp(pred, lhs_value, rhs_value) => {
+                    self.verify_cmp(pred, lhs_value, rhs_value)?
+                }
                 Instruction::ConditionalBranch {
                     cond_value,
                     true_block,
@@ -110,7 +160,9 @@ impl Context {
                 } => self.verify_insert_values(aggregate, ty, value, indices)?,
                 Instruction::Load(ptr) => self.verify_load(ptr)?,
                 Instruction::Nop => (),
-                Instruction::Phi(pairs) => self.verify_phi(&pairs[..])?,
+                Instruction::Phi(pairs, ty) => {
+                    self.verify_phi(function, reachable, block, &pairs[..], ty)?
+                }
                 Instruction::PointerCast(ptr_val, ty) => self.verify_ptr_cast(ptr_val, ty)?,
                 Instruction::Ret(val, ty) => self.verify_ret(function, val, ty)?,
                 Instruction::StateLoad { load_val, key } => {
@@ -134,6 +186,21 @@ impl Context {
         Ok(())
     }
 
+    fn verify_binary_op(
+        &self,
+        op: &BinaryOpKind,
+        arg1: &Value,
+        arg2: &Value,
+    ) -> Result<(), IrError> {
+        match (arg1.get_type(self), arg2.get_type(self)) {
+            (Some(Type::Uint(nbits1)), Some(Type::Uint(nbits2))) if nbits1 != nbits2 => {
+                Err(IrError::MismatchedOperandTypes(format!("{op:?}")))
+            }
+            (Some(Type::Uint(_)) | None, Some(Type::Uint(_)) | None) => Ok(()),
+            _otherwise => Err(IrError::NonIntegerOperand(format!("{op:?}"))),
+        }
+    }
+
     fn verify_br(&self, _block: &Block) -> Result<(), IrError> {
         Ok(())
     }
@@ -145,6 +212,37 @@ impl Context {
         Ok(())
     }
 
+    fn verify_call_indirect(
+        &self,
+        _target: &Value,
+        callees: &[(u64, Function)],
+        args: &[Value],
+    ) -> Result<(), IrError> {
+        if callees.is_empty() {
+            return Err(IrError::MissingCallees);
+        }
+        for (_, callee) in callees {
+            self.verify_call(callee, args)?;
+        }
+        Ok(())
+    }
+
+    fn verify_cmp(
+        &self,
+        pred: &Predicate,
+        lhs_value: &Value,
+        rhs_value: &Value,
+    ) -> Result<(), IrError> {
+        match (lhs_value.get_type(self), rhs_value.get_type(self)) {
+            (Some(lhs_ty), Some(rhs_ty)) if lhs_ty != rhs_ty => {
+                Err(IrError::MismatchedOperandTypes(format!("{pred:?}")))
+            }
+            (Some(Type::Bool), _) | (_, Some(Type::Bool)) if *pred == Predicate::Equal => Ok(()),
+            (Some(Type::Uint(_)) | None, Some(Type::Uint(_)) | None) => Ok(()),
+            _otherwise => Err(IrError::NonIntegerOperand(format!("{pred:?}"))),
+        }
+    }
+
     fn verify_cbr(
         &self,
         _cond_val: &Value,
@@ -211,15 +309,45 @@ impl Context {
         Ok(())
     }
 
-    fn verify_phi(&self, pairs: &[(Block, Value)]) -> Result<(), IrError> {
-        let label_set = std::collections::HashSet::<&String>::from_iter(
+    fn verify_phi(
+        &self,
+        function: &FunctionContent,
+        reachable: &HashSet<Block>,
+        block: &Block,
+        pairs: &[(Block, Value)],
+        ty: &Type,
+    ) -> Result<(), IrError> {
+        let label_set = HashSet::<&String>::from_iter(
             pairs.iter().map(|(block, _)| &(self.blocks[block.0].label)),
         );
         if label_set.len() != pairs.len() {
-            Err(IrError::NonUniquePhiLabels)
-        } else {
-            Ok(())
+            return Err(IrError::NonUniquePhiLabels);
         }
+
+        // Each incoming block must actually branch to this block.
+        let label = &self.blocks[block.0].label;
+        for (from_block, _) in pairs {
+            if !function.blocks.contains(from_block) || !from_block.successors(self).contains(block)
+            {
+                return Err(IrError::PhiFromNonPredecessor(
+                    label.clone(),
+                    self.blocks[from_block.0].label.clone(),
+                ));
+            }
+        }
+
+        // And each incoming value from a reachable block must match the phi type, where known.
+        for (from_block, value) in pairs {
+            if !reachable.contains(from_block) {
+                continue;
+            }
+            if let Some(value_ty) = value.get_type(self) {
+                if !self.types_match(&value_ty, ty) {
+                    return Err(IrError::MismatchedPhiTypes(label.clone()));
+                }
+            }
+        }
+        Ok(())
     }
 
     fn verify_ptr_cast(&self, _ptr_val: &Value, _ty: &Type) -> Result<(), IrError> {
@@ -265,4 +393,9 @@ impl Context {
         Ok(())
         //}
     }
+
+    // Aggregates are compared structurally since identical types may use distinct handles.
+    fn types_match(&self, ty_a: &Type, ty_b: &Type) -> bool {
+        ty_a == ty_b || ty_a.as_string(self) == ty_b.as_string(self)
+    }
 }
@@ -1,5 +1,10 @@
 use std::path::PathBuf;
 
+use sway_ir::{
+    error::IrError,
+    pass_manager::{PassManager, PassScope, Pipeline},
+};
+
 // -------------------------------------------------------------------------------------------------
 
 #[test]
@@ -20,9 +25,17 @@ fn ir_to_ir_tests() {
                 println!("--- TESTING: {}", path.display());
                 let path_str = path.file_name().unwrap().to_string_lossy();
                 if path_str.starts_with("inline") {
-                    test_inline(path);
+                    test_pass(path, "inline");
                 } else if path_str.starts_with("constants") {
-                    test_constants(path);
+                    test_pass(path, "constcombine");
+                } else if path_str.starts_with("indirect_calls") {
+                    test_pass(path, "devirtualize");
+                } else if path_str.starts_with("dce") {
+                    test_pass(path, "dce");
+                } else if path_str.starts_with("simplify_cfg") {
+                    test_pass(path, "simplifycfg");
+                } else if path_str.starts_with("mem2reg") {
+                    test_pass(path, "mem2reg");
                 } else {
                     panic!(
                         "File which doesn't match valid passes: {:?}",
@@ -41,7 +54,7 @@ fn ir_to_ir_tests() {
 
 // -------------------------------------------------------------------------------------------------
 
-fn test_inline(mut path: PathBuf) {
+fn test_pass(mut path: PathBuf, pass_name: &str) {
     let input_bytes = std::fs::read(&path).unwrap();
     let input = String::from_utf8_lossy(&input_bytes);
 
@@ -58,13 +71,7 @@ fn test_inline(mut path: PathBuf) {
         }
     };
 
-    let main_fn = ir
-        .functions
-        .iter()
-        .find_map(|(idx, fc)| if fc.name == "main" { Some(idx) } else { None })
-        .unwrap();
-    sway_ir::optimize::inline_all_function_calls(&mut ir, &sway_ir::function::Function(main_fn))
-        .unwrap();
+    PassManager::default().run_pass(&mut ir, pass_name).unwrap();
     let output = sway_ir::printer::to_string(&ir);
 
     if output != expected {
@@ -75,34 +82,88 @@ fn test_inline(mut path: PathBuf) {
 
 // -------------------------------------------------------------------------------------------------
 
-fn test_constants(mut path: PathBuf) {
-    let input_bytes = std::fs::read(&path).unwrap();
-    let input = String::from_utf8_lossy(&input_bytes);
-
-    path.set_extension("out_ir");
-
-    let expected_bytes = std::fs::read(&path).unwrap();
-    let expected = String::from_utf8_lossy(&expected_bytes);
+#[test]
+fn pass_manager_registry() {
+    let mut pass_mgr = PassManager::default();
+
+    let inline = pass_mgr.get_pass("inline").unwrap();
+    assert_eq!(inline.scope(), PassScope::Module);
+    assert!(!inline.preserves_cfg);
+    let constcombine = pass_mgr.get_pass("constcombine").unwrap();
+    assert_eq!(constcombine.scope(), PassScope::Function);
+    assert!(constcombine.preserves_cfg);
+
+    assert_eq!(
+        pass_mgr.pipeline_names_iter().collect::<Vec<_>>(),
+        vec!["O0", "O1", "O2"]
+    );
+
+    let constcombine = *constcombine;
+    assert!(matches!(
+        pass_mgr.register(constcombine),
+        Err(IrError::DuplicatePass(_))
+    ));
+    assert!(matches!(
+        pass_mgr.register_pipeline("bogus", Pipeline::new().pass("nosuchpass")),
+        Err(IrError::UnknownPass(_))
+    ));
+
+    let mut ir = sway_ir::parser::parse("script {\n}\n").unwrap();
+    assert!(matches!(
+        pass_mgr.run_pipeline(&mut ir, "O9"),
+        Err(IrError::UnknownPipeline(_))
+    ));
+}
 
-    let mut ir = match sway_ir::parser::parse(&input) {
-        Ok(ir) => ir,
-        Err(parse_err) => {
-            println!("{parse_err}");
-            panic!()
-        }
-    };
+#[test]
+fn pass_manager_pipelines() {
+    let manifest_dir = env!("CARGO_MANIFEST_DIR");
+    let input_bytes = std::fs::read(format!(
+        "{}/tests/ir_to_ir/indirect_calls.in_ir",
+        manifest_dir
+    ))
+    .unwrap();
+    let input = String::from_utf8_lossy(&input_bytes);
 
-    let fn_idcs: Vec<_> = ir.functions.iter().map(|func| func.0).collect();
-    for fn_idx in fn_idcs {
-        sway_ir::optimize::combine_constants(&mut ir, &sway_ir::function::Function(fn_idx))
+    for pipeline in ["O0", "O1", "O2"] {
+        let mut ir = sway_ir::parser::parse(&input).unwrap();
+        let mut pass_mgr = PassManager::default();
+        pass_mgr.set_verify_each(true);
+        pass_mgr.set_time_passes(true);
+        assert!(pass_mgr.run_pipeline(&mut ir, pipeline).unwrap());
+
+        // Every callee is small, so every direct call is inlined into `main()` by every pipeline,
+        // including those which only became direct once their callee was known.  The call
+        // through `f` may call either `a` or `b`, so it's left for the backend.
+        let main_fn = ir
+            .functions
+            .iter()
+            .find_map(|(idx, fc)| if fc.name == "main" { Some(idx) } else { None })
             .unwrap();
+        let calls = sway_ir::function::Function(main_fn)
+            .instruction_iter(&ir)
+            .filter_map(|(_, ins)| match &ir.values[ins.0].value {
+                sway_ir::value::ValueDatum::Instruction(
+                    ins @ (sway_ir::instruction::Instruction::Call(..)
+                    | sway_ir::instruction::Instruction::CallIndirect { .. }),
+                ) => Some(ins.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert!(matches!(
+            calls.as_slice(),
+            [sway_ir::instruction::Instruction::CallIndirect { .. }]
+        ));
+
+        // Every call which could be is inlined already, so running again finds nothing to do.
+        assert!(!pass_mgr.run_pipeline(&mut ir, "O0").unwrap());
+
+        let inline_timing = pass_mgr
+            .timings()
+            .iter()
+            .find(|timing| timing.name == "inline")
+            .unwrap();
+        assert!(inline_timing.runs >= 2);
+        assert!(inline_timing.modifications >= 1);
     }
-    let output = sway_ir::printer::to_string(&ir);
-
-    if output != expected {
-        println!("{}", prettydiff::diff_lines(&expected, &output));
-    }
-    assert_eq!(output, expected);
 }
-
-// -------------------------------------------------------------------------------------------------
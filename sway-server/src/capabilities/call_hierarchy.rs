@@ -0,0 +1,234 @@
+use crate::{
+    capabilities::workspace_symbol::get_kind,
+    core::{
+        project::{signature, TypedToken},
+        session::Session,
+    },
+};
+use lspower::lsp::{
+    CallHierarchyIncomingCall, CallHierarchyIncomingCallsParams, CallHierarchyItem,
+    CallHierarchyOutgoingCall, CallHierarchyOutgoingCallsParams, CallHierarchyPrepareParams,
+    Location, Range,
+};
+use std::sync::Arc;
+use sway_core::SymbolKind;
+
+pub fn prepare_call_hierarchy(
+    session: Arc<Session>,
+    params: CallHierarchyPrepareParams,
+) -> Option<Vec<CallHierarchyItem>> {
+    let url = params.text_document_position_params.text_document.uri;
+    let position = params.text_document_position_params.position;
+
+    let token = session.get_typed_token(&url, position)?;
+    if !matches!(token.kind, SymbolKind::Function { .. }) {
+        return None;
+    }
+    let definition = token.definition.clone()?;
+    let declaration = session
+        .with_index(|index| index.declaration(&definition).cloned())
+        .flatten()
+        .unwrap_or(token);
+    Some(vec![call_hierarchy_item(definition, &declaration)])
+}
+
+pub fn incoming_calls(
+    session: Arc<Session>,
+    params: CallHierarchyIncomingCallsParams,
+) -> Option<Vec<CallHierarchyIncomingCall>> {
+    let function = Location::new(params.item.uri, params.item.selection_range);
+
+    session.with_index(|index| {
+        let calls = group_by_location(
+            index
+                .references(&function)
+                .filter(|(url, token)| !token.is_declaration(url))
+                .filter_map(|(_, token)| Some((token.container.clone()?, token.range))),
+        );
+        calls
+            .into_iter()
+            .filter_map(|(caller, from_ranges)| {
+                let declaration = index.declaration(&caller)?;
+                Some(CallHierarchyIncomingCall {
+                    from: call_hierarchy_item(caller, declaration),
+                    from_ranges,
+                })
+            })
+            .collect()
+    })
+}
+
+pub fn outgoing_calls(
+    session: Arc<Session>,
+    params: CallHierarchyOutgoingCallsParams,
+) -> Option<Vec<CallHierarchyOutgoingCall>> {
+    let function = Location::new(params.item.uri, params.item.selection_range);
+
+    session.with_index(|index| {
+        let calls = group_by_location(
+            index
+                .calls_from(&function)
+                .filter_map(|(_, token)| Some((token.definition.clone()?, token.range))),
+        );
+        calls
+            .into_iter()
+            .filter_map(|(callee, from_ranges)| {
+                let declaration = index.declaration(&callee)?;
+                Some(CallHierarchyOutgoingCall {
+                    to: call_hierarchy_item(callee, declaration),
+                    from_ranges,
+                })
+            })
+            .collect()
+    })
+}
+
+/// Group ranges by location, keeping the order in which each location is first seen.
+fn group_by_location(
+    ranges: impl Iterator<Item = (Location, Range)>,
+) -> Vec<(Location, Vec<Range>)> {
+    let mut groups: Vec<(Location, Vec<Range>)> = vec![];
+    for (location, range) in ranges {
+        match groups.iter_mut().find(|(group, _)| *group == location) {
+            Some((_, ranges)) => ranges.push(range),
+            None => groups.push((location, vec![range])),
+        }
+    }
+    groups
+}
+
+/// The item for a function, identified by the location of its name at its declaration.
+fn call_hierarchy_item(definition: Location, declaration: &TypedToken) -> CallHierarchyItem {
+    CallHierarchyItem {
+        name: declaration.name.clone(),
+        kind: get_kind(&declaration.kind),
+        tags: None,
+        detail: Some(signature(
+            &declaration.name,
+            &declaration.kind,
+            declaration.type_id,
+        )),
+        uri: definition.uri,
+        range: definition.range,
+        selection_range: definition.range,
+        data: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_utils::{name_range, position_of, session_with_source};
+    use lspower::lsp::{TextDocumentIdentifier, TextDocumentPositionParams, Url};
+
+    const SOURCE: &str = r#"script;
+
+struct Point {
+    x: u64,
+}
+
+fn origin() -> Point {
+    Point {
+        x: 0,
+    }
+}
+
+fn first(point: Point) -> u64 {
+    point.x
+}
+
+fn main() -> u64 {
+    let point = origin();
+    let other = origin();
+    first(point)
+}
+"#;
+
+    fn prepare(session: Arc<Session>, url: &Url, text: &str) -> Option<Vec<CallHierarchyItem>> {
+        prepare_call_hierarchy(
+            session,
+            CallHierarchyPrepareParams {
+                text_document_position_params: TextDocumentPositionParams::new(
+                    TextDocumentIdentifier::new(url.clone()),
+                    position_of(SOURCE, text),
+                ),
+                work_done_progress_params: Default::default(),
+            },
+        )
+    }
+
+    fn item(session: Arc<Session>, url: &Url, text: &str) -> CallHierarchyItem {
+        let mut items = prepare(session, url, text).unwrap();
+        assert_eq!(items.len(), 1);
+        items.remove(0)
+    }
+
+    #[test]
+    fn calls_are_prepared_from_their_declaration() {
+        let (session, url) = session_with_source(SOURCE);
+        let origin = item(session.clone(), &url, "origin();\n    let other");
+        assert_eq!(origin.name, "origin");
+        assert_eq!(origin.detail.as_deref(), Some("fn origin() -> Point"));
+        assert_eq!(
+            origin.selection_range,
+            name_range(SOURCE, "fn origin", "origin")
+        );
+        assert!(prepare(session, &url, "point.x").is_none());
+    }
+
+    #[test]
+    fn incoming_calls_are_grouped_by_caller() {
+        let (session, url) = session_with_source(SOURCE);
+        let origin = item(session.clone(), &url, "origin() -> Point");
+        let calls = incoming_calls(
+            session,
+            CallHierarchyIncomingCallsParams {
+                item: origin,
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            },
+        )
+        .unwrap();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].from.name, "main");
+        assert_eq!(
+            calls[0].from_ranges,
+            [
+                name_range(SOURCE, "let point = origin", "origin"),
+                name_range(SOURCE, "let other = origin", "origin")
+            ]
+        );
+    }
+
+    #[test]
+    fn outgoing_calls_are_grouped_by_callee() {
+        let (session, url) = session_with_source(SOURCE);
+        let main = item(session.clone(), &url, "main");
+        let calls = outgoing_calls(
+            session.clone(),
+            CallHierarchyOutgoingCallsParams {
+                item: main,
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            },
+        )
+        .unwrap();
+        let callees = calls
+            .iter()
+            .map(|call| (call.to.name.as_str(), call.from_ranges.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(callees, [("origin", 2), ("first", 1)]);
+
+        let first = item(session.clone(), &url, "first(point: Point)");
+        let calls = outgoing_calls(
+            session,
+            CallHierarchyOutgoingCallsParams {
+                item: first,
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            },
+        )
+        .unwrap();
+        assert!(calls.is_empty());
+    }
+}
@@ -0,0 +1,380 @@
+use crate::{
+    capabilities::{diagnostic::Fix, rename},
+    core::session::Session,
+};
+use lspower::lsp::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, CodeActionResponse,
+    Diagnostic, Position, Range, RenameParams, TextDocumentIdentifier, TextDocumentPositionParams,
+    TextEdit, Url, WorkspaceEdit,
+};
+use std::{collections::HashMap, sync::Arc};
+
+pub fn get_code_actions(
+    session: Arc<Session>,
+    params: CodeActionParams,
+) -> Option<CodeActionResponse> {
+    let url = params.text_document.uri;
+    let text = session.documents.get(url.path())?.get_text();
+
+    let actions = params
+        .context
+        .diagnostics
+        .iter()
+        .flat_map(
+            |diagnostic| match diagnostic.data.as_ref().and_then(Fix::from_value) {
+                Some(fix) => fix_actions(&session, &url, &text, diagnostic, fix),
+                None => vec![],
+            },
+        )
+        .collect();
+
+    Some(actions)
+}
+
+fn fix_actions(
+    session: &Arc<Session>,
+    url: &Url,
+    text: &str,
+    diagnostic: &Diagnostic,
+    fix: Fix,
+) -> Vec<CodeActionOrCommand> {
+    let action = |title: String, edit: WorkspaceEdit| {
+        CodeActionOrCommand::CodeAction(CodeAction {
+            title,
+            kind: Some(CodeActionKind::QUICKFIX),
+            diagnostics: Some(vec![diagnostic.clone()]),
+            edit: Some(edit),
+            ..Default::default()
+        })
+    };
+    let in_document =
+        |edit: TextEdit| WorkspaceEdit::new(HashMap::from([(url.clone(), vec![edit])]));
+
+    match fix {
+        Fix::Import { name } => {
+            let namespace = match session.get_typed_file(url) {
+                Some((namespace, _)) => namespace,
+                None => return vec![],
+            };
+            let position = use_position(text);
+            sway_core::import_paths(namespace, &name)
+                .into_iter()
+                .map(|path| {
+                    let path = format!("{}::{}", path.join("::"), name);
+                    let edit =
+                        TextEdit::new(Range::new(position, position), format!("use {};\n", path));
+                    action(format!("Import `{}`", path), in_document(edit))
+                })
+                .collect()
+        }
+        Fix::Rename { range, new_name } => {
+            // Rename every occurrence where possible, and otherwise just the flagged name.
+            let params = RenameParams {
+                text_document_position: TextDocumentPositionParams::new(
+                    TextDocumentIdentifier::new(url.clone()),
+                    range.start,
+                ),
+                new_name: new_name.clone(),
+                work_done_progress_params: Default::default(),
+            };
+            let edit = rename::rename(session.clone(), params)
+                .unwrap_or_else(|| in_document(TextEdit::new(range, new_name.clone())));
+            vec![action(format!("Rename to `{}`", new_name), edit)]
+        }
+        Fix::AddFunctions {
+            position,
+            signatures,
+        } => {
+            let mut new_text = signatures
+                .iter()
+                .map(|signature| format!("    {} {{\n    }}\n", signature))
+                .collect::<Vec<_>>()
+                .join("\n");
+            // The closing brace shares its line with the rest of the block.
+            if position.character > 0 {
+                new_text.insert(0, '\n');
+            }
+            let edit = TextEdit::new(Range::new(position, position), new_text);
+            vec![action(
+                "Add the missing functions".to_string(),
+                in_document(edit),
+            )]
+        }
+        Fix::AddMut { range } => {
+            let edit = TextEdit::new(Range::new(range.start, range.start), "mut ".to_string());
+            vec![action(
+                "Declare the variable as mutable".to_string(),
+                in_document(edit),
+            )]
+        }
+        Fix::Remove { range } => {
+            let edit = TextEdit::new(whole_lines(text, range), String::new());
+            vec![action(
+                "Remove the unused declaration".to_string(),
+                in_document(edit),
+            )]
+        }
+    }
+}
+
+/// The position at which to insert a `use` statement: after the last `use` or `dep` statement,
+/// or otherwise after the declaration of the program's kind.
+fn use_position(text: &str) -> Position {
+    let lines: Vec<&str> = text.lines().collect();
+    let line = lines
+        .iter()
+        .rposition(|line| {
+            let line = line.trim_start();
+            line.starts_with("use ") || line.starts_with("dep ")
+        })
+        .or_else(|| lines.iter().position(|line| line.trim_end().ends_with(';')))
+        .map_or(0, |line| line + 1);
+    Position::new(line as u32, 0)
+}
+
+/// Extend the range to cover whole lines, if nothing else shares its first and last lines.
+fn whole_lines(text: &str, range: Range) -> Range {
+    let lines: Vec<&str> = text.lines().collect();
+    let (first, last) = match (
+        lines.get(range.start.line as usize),
+        lines.get(range.end.line as usize),
+    ) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return range,
+    };
+    let before = first.chars().take(range.start.character as usize);
+    let after = last.chars().skip(range.end.character as usize);
+    if before.chain(after).all(char::is_whitespace) {
+        Range::new(
+            Position::new(range.start.line, 0),
+            Position::new(range.end.line + 1, 0),
+        )
+    } else {
+        range
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_utils::{
+        compile_sources, name_range, position_after, position_of, replace_text,
+    };
+    use lspower::lsp::{CodeActionContext, TextDocumentIdentifier};
+
+    /// The title and edits of each quick fix offered for the diagnostics of the source.
+    fn quick_fixes(source: &str) -> Vec<(String, Vec<TextEdit>)> {
+        let (session, url, diagnostics) = compile_sources(source, &[]);
+        quick_fixes_for(session, &url, diagnostics)
+    }
+
+    fn quick_fixes_for(
+        session: Arc<Session>,
+        url: &Url,
+        diagnostics: Vec<Diagnostic>,
+    ) -> Vec<(String, Vec<TextEdit>)> {
+        let params = CodeActionParams {
+            text_document: TextDocumentIdentifier::new(url.clone()),
+            range: Range::default(),
+            context: CodeActionContext {
+                diagnostics,
+                only: None,
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+        get_code_actions(session, params)
+            .unwrap()
+            .into_iter()
+            .map(|action| match action {
+                CodeActionOrCommand::CodeAction(CodeAction {
+                    title,
+                    kind: Some(kind),
+                    edit:
+                        Some(WorkspaceEdit {
+                            changes: Some(mut changes),
+                            ..
+                        }),
+                    ..
+                }) if kind == CodeActionKind::QUICKFIX => {
+                    (title, changes.remove(url).unwrap_or_default())
+                }
+                action => panic!("{:?}", action),
+            })
+            .collect()
+    }
+
+    fn quick_fix(fixes: &[(String, Vec<TextEdit>)], title: &str) -> Vec<TextEdit> {
+        fixes
+            .iter()
+            .find(|(fix_title, _)| fix_title == title)
+            .unwrap_or_else(|| panic!("no {:?} in {:?}", title, fixes))
+            .1
+            .clone()
+    }
+
+    fn insertion(position: Position, text: &str) -> TextEdit {
+        TextEdit::new(Range::new(position, position), text.to_string())
+    }
+
+    #[test]
+    fn missing_items_are_imported() {
+        let source = r#"script;
+
+dep lib;
+
+fn main() -> u64 {
+    lib::seven()
+}
+"#;
+        let lib = r#"library lib;
+
+pub fn seven() -> u64 {
+    7
+}
+"#;
+        // The namespace is kept from when the file last type checked, before the path was removed.
+        let (session, url, _) = compile_sources(source, &[("lib.sw", lib)]);
+        replace_text(&session, &url, source, "lib::seven", "seven");
+        let diagnostics = session
+            .compile_project(&url)
+            .unwrap()
+            .into_iter()
+            .flat_map(|(_, diagnostics)| diagnostics)
+            .collect();
+        let fixes = quick_fixes_for(session, &url, diagnostics);
+        assert_eq!(
+            quick_fix(&fixes, "Import `lib::seven`"),
+            [insertion(Position::new(3, 0), "use lib::seven;\n")]
+        );
+    }
+
+    #[test]
+    fn missing_functions_are_added_to_the_implementation() {
+        let source = r#"script;
+
+struct Point {
+    x: u64,
+}
+
+trait Sum {
+    fn sum(self) -> u64;
+}
+
+impl Sum for Point {
+}
+
+fn main() -> u64 {
+    0
+}
+"#;
+        let fixes = quick_fixes(source);
+        assert_eq!(
+            quick_fix(&fixes, "Add the missing functions"),
+            [insertion(
+                position_after(source, "impl Sum for Point {\n"),
+                "    fn sum(self) -> u64 {\n    }\n"
+            )]
+        );
+    }
+
+    #[test]
+    fn reassigned_variables_are_made_mutable() {
+        let source = r#"script;
+
+fn main() -> u64 {
+    let n = 1;
+    n = 2;
+    n
+}
+"#;
+        let fixes = quick_fixes(source);
+        assert_eq!(
+            quick_fix(&fixes, "Declare the variable as mutable"),
+            [insertion(position_of(source, "n = 1"), "mut ")]
+        );
+    }
+
+    #[test]
+    fn unused_declarations_are_removed_along_with_their_lines() {
+        // Only declarations within other unused items are reported, so that is where this is.
+        let source = r#"script;
+
+fn unused() -> u64 {
+    let a = 1;
+    a
+}
+
+fn main() -> u64 {
+    0
+}
+"#;
+        let fixes = quick_fixes(source);
+        assert_eq!(
+            quick_fix(&fixes, "Remove the unused declaration"),
+            [TextEdit::new(
+                Range::new(
+                    position_of(source, "    let a"),
+                    position_of(source, "    a\n")
+                ),
+                String::new()
+            )]
+        );
+    }
+
+    #[test]
+    fn names_are_renamed_everywhere() {
+        let source = r#"script;
+
+fn DoubleIt() -> u64 {
+    2
+}
+
+fn main() -> u64 {
+    DoubleIt()
+}
+"#;
+        let fixes = quick_fixes(source);
+        let mut edits = quick_fix(&fixes, "Rename to `double_it`");
+        edits.sort_by_key(|edit| edit.range.start);
+        assert_eq!(
+            edits,
+            [
+                TextEdit::new(
+                    name_range(source, "fn DoubleIt", "DoubleIt"),
+                    "double_it".to_string()
+                ),
+                TextEdit::new(
+                    name_range(source, "    DoubleIt", "DoubleIt"),
+                    "double_it".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn imports_follow_the_last_use_or_dep_statement() {
+        assert_eq!(
+            use_position("script;\n\nfn main() {}\n"),
+            Position::new(1, 0)
+        );
+        assert_eq!(
+            use_position("script;\n\ndep a;\nuse a::b;\n\nfn main() {}\n"),
+            Position::new(4, 0)
+        );
+        assert_eq!(use_position(""), Position::new(0, 0));
+    }
+
+    #[test]
+    fn removals_cover_whole_lines_unless_they_are_shared() {
+        let text = "let a = 1;\n    let b = 2;\nlet c = 3; let d = 4;\n";
+        let range =
+            |line, start, end| Range::new(Position::new(line, start), Position::new(line, end));
+        assert_eq!(
+            whole_lines(text, range(1, 4, 14)),
+            Range::new(Position::new(1, 0), Position::new(2, 0))
+        );
+        assert_eq!(whole_lines(text, range(1, 4, 13)), range(1, 4, 13));
+        assert_eq!(whole_lines(text, range(2, 0, 10)), range(2, 0, 10));
+    }
+}
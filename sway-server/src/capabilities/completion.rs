@@ -1,16 +1,121 @@
-use crate::core::{session::Session, token::Token, token_type::TokenType};
-use lspower::lsp::{CompletionItem, CompletionItemKind, CompletionParams, CompletionResponse};
-use std::sync::Arc;
+use crate::core::{
+    project::{signature, TypedToken},
+    session::Session,
+    token::Token,
+    token_type::TokenType,
+};
+use lspower::lsp::{
+    CompletionItem, CompletionItemKind, CompletionParams, CompletionResponse, InsertTextFormat,
+    Position, Url,
+};
+use std::{collections::HashMap, sync::Arc};
+use sway_core::{NamedItem, NamespaceRef, SymbolKind};
+
+const KEYWORDS: &[&str] = &[
+    "abi",
+    "as",
+    "asm",
+    "break",
+    "const",
+    "continue",
+    "contract",
+    "dep",
+    "else",
+    "enum",
+    "false",
+    "fn",
+    "if",
+    "impl",
+    "let",
+    "library",
+    "match",
+    "mut",
+    "predicate",
+    "pub",
+    "ref",
+    "return",
+    "script",
+    "self",
+    "Self",
+    "storage",
+    "struct",
+    "trait",
+    "true",
+    "use",
+    "while",
+];
+
+const SNIPPETS: &[(&str, &str)] = &[
+    ("fn", "fn ${1:name}(${2}) {\n\t$0\n}"),
+    ("fn ->", "fn ${1:name}(${2}) -> ${3:()} {\n\t$0\n}"),
+    ("struct", "struct ${1:Name} {\n\t$0\n}"),
+    ("enum", "enum ${1:Name} {\n\t$0\n}"),
+    ("impl", "impl ${1:Type} {\n\t$0\n}"),
+    ("impl for", "impl ${1:Trait} for ${2:Type} {\n\t$0\n}"),
+    ("abi", "abi ${1:Name} {\n\t$0\n}"),
+    ("storage", "storage {\n\t$0\n}"),
+    ("if", "if ${1:condition} {\n\t$0\n}"),
+    ("if else", "if ${1:condition} {\n\t$2\n} else {\n\t$0\n}"),
+    ("while", "while ${1:condition} {\n\t$0\n}"),
+    ("let", "let ${1:name} = $0;"),
+];
+
+/// What the completed name follows.
+#[derive(Debug, PartialEq)]
+enum Context {
+    /// A bare name.
+    Name,
+    /// `receiver.`
+    Member(Receiver),
+    /// `a::b::`
+    Path(Vec<String>),
+}
+
+/// The expression before a `.`.
+#[derive(Debug, PartialEq)]
+enum Receiver {
+    /// A name, ending at the given column.
+    Name(String, u32),
+    /// A call, with the name of the called function ending at the given column.
+    Call(String, u32),
+    /// An `abi(Name, address)` cast, with the path of the ABI.
+    AbiCast(Vec<String>),
+}
 
 pub fn get_completion(
     session: Arc<Session>,
     params: CompletionParams,
 ) -> Option<CompletionResponse> {
     let url = params.text_document_position.text_document.uri;
+    let position = params.text_document_position.position;
 
-    session
-        .get_completion_items(&url)
-        .map(CompletionResponse::Array)
+    let line = session
+        .documents
+        .get(url.path())?
+        .get_text()
+        .lines()
+        .nth(position.line as usize)
+        .unwrap_or_default()
+        .chars()
+        .take(position.character as usize)
+        .collect::<String>();
+
+    let items = match session.get_typed_file(&url) {
+        Some((namespace, tokens)) => {
+            typed_completion_items(&url, &line, position, namespace, &tokens)
+        }
+        // The document has not been type checked, so only offer its declarations by name.
+        None => match context(&line) {
+            Context::Name => {
+                let mut items = session.get_completion_items(&url).unwrap_or_default();
+                items.extend(keyword_items());
+                items
+            }
+            Context::Member(_) | Context::Path(_) => vec![],
+        },
+    };
+
+    Some(CompletionResponse::Array(items))
 }
 
 pub fn to_completion_items(tokens: &[Token]) -> Vec<CompletionItem> {
@@ -30,6 +135,215 @@ pub fn to_completion_items(tokens: &[Token]) -> Vec<CompletionItem> {
     completion_items
 }
 
+fn typed_completion_items(
+    url: &Url,
+    line: &str,
+    position: Position,
+    namespace: NamespaceRef,
+    tokens: &[TypedToken],
+) -> Vec<CompletionItem> {
+    match context(line) {
+        Context::Name => {
+            // Declarations before the cursor shadow those of the namespace.
+            let mut items: HashMap<String, NamedItem> = sway_core::namespace_items(namespace)
+                .into_iter()
+                .map(|item| (item.name.clone(), item))
+                .collect();
+            for token in tokens.iter().filter(|token| token.range.end < position) {
+                let is_local = matches!(
+                    token.kind,
+                    SymbolKind::Variable { .. } | SymbolKind::FunctionParameter
+                );
+                if is_local && token.is_declaration(url) {
+                    items.insert(token.name.clone(), token_item(token));
+                }
+            }
+            items
+                .into_values()
+                .map(completion_item)
+                .chain(keyword_items())
+                .collect()
+        }
+        Context::Path(path) => {
+            let path: Vec<&str> = path.iter().map(String::as_str).collect();
+            sway_core::path_items(namespace, &path)
+                .into_iter()
+                .map(completion_item)
+                .collect()
+        }
+        Context::Member(Receiver::AbiCast(path)) => {
+            let path: Vec<&str> = path.iter().map(String::as_str).collect();
+            sway_core::abi_methods(namespace, &path)
+                .into_iter()
+                .map(completion_item)
+                .collect()
+        }
+        Context::Member(Receiver::Name(name, _)) if name == "storage" => tokens
+            .iter()
+            .filter(|token| {
+                matches!(token.kind, SymbolKind::StorageField) && token.is_declaration(url)
+            })
+            .map(|token| completion_item(token_item(token)))
+            .collect(),
+        Context::Member(Receiver::Name(name, end) | Receiver::Call(name, end)) => {
+            match receiver_type(tokens, &name, Position::new(position.line, end)) {
+                Some(type_id) => sway_core::type_members(namespace, type_id)
+                    .into_iter()
+                    .map(completion_item)
+                    .collect(),
+                None => vec![],
+            }
+        }
+    }
+}
+
+/// The type of the named value ending at the given position, or otherwise that of the closest
+/// preceding token with the same name.
+fn receiver_type(
+    tokens: &[TypedToken],
+    name: &str,
+    end: Position,
+) -> Option<sway_core::type_engine::TypeId> {
+    tokens
+        .iter()
+        .find(|token| token.name == name && token.range.end == end)
+        .or_else(|| {
+            tokens
+                .iter()
+                .filter(|token| token.name == name && token.range.end <= end)
+                .max_by_key(|token| token.range.start)
+        })
+        .and_then(|token| token.type_id)
+}
+
+/// Determine what the name being completed follows from the text of the line before the cursor.
+fn context(line: &str) -> Context {
+    let line = line.trim_end_matches(is_ident_char);
+    if let Some(line) = line.strip_suffix("::") {
+        let mut path = vec![];
+        let mut rest = line;
+        loop {
+            let start = rest.trim_end_matches(is_ident_char).len();
+            if start == rest.len() {
+                break;
+            }
+            path.push(rest[start..].to_string());
+            match rest[..start].strip_suffix("::") {
+                Some(prefix) => rest = prefix,
+                None => break,
+            }
+        }
+        path.reverse();
+        return if path.is_empty() {
+            Context::Name
+        } else {
+            Context::Path(path)
+        };
+    }
+    if let Some(line) = line.strip_suffix('.') {
+        let receiver = match line.strip_suffix(')') {
+            Some(line) => {
+                let open = match matching_paren(line) {
+                    Some(open) => open,
+                    None => return Context::Name,
+                };
+                let callee = &line[line[..open].trim_end_matches(is_ident_char).len()..open];
+                if callee == "abi" {
+                    let abi_name = line[open + 1..].split(',').next().unwrap_or_default();
+                    let path = abi_name
+                        .split("::")
+                        .map(|segment| segment.trim().to_string())
+                        .collect();
+                    Receiver::AbiCast(path)
+                } else {
+                    Receiver::Call(callee.to_string(), column(&line[..open]))
+                }
+            }
+            None => {
+                let start = line.trim_end_matches(is_ident_char).len();
+                Receiver::Name(line[start..].to_string(), column(line))
+            }
+        };
+        return match &receiver {
+            Receiver::Name(name, _) | Receiver::Call(name, _) if name.is_empty() => Context::Name,
+            _ => Context::Member(receiver),
+        };
+    }
+    Context::Name
+}
+
+/// The index of the `(` matching a `)` at the end of the text.
+fn matching_paren(text: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (ix, c) in text.char_indices().rev() {
+        match c {
+            ')' => depth += 1,
+            '(' if depth == 0 => return Some(ix),
+            '(' => depth -= 1,
+            _ => (),
+        }
+    }
+    None
+}
+
+fn column(text: &str) -> u32 {
+    text.chars().count() as u32
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn token_item(token: &TypedToken) -> NamedItem {
+    NamedItem {
+        name: token.name.clone(),
+        kind: token.kind.clone(),
+        type_id: token.type_id,
+    }
+}
+
+fn completion_item(item: NamedItem) -> CompletionItem {
+    CompletionItem {
+        detail: Some(signature(&item.name, &item.kind, item.type_id)),
+        kind: Some(get_symbol_kind(&item.kind)),
+        label: item.name,
+        ..Default::default()
+    }
+}
+
+fn keyword_items() -> impl Iterator<Item = CompletionItem> {
+    let keywords = KEYWORDS.iter().map(|keyword| CompletionItem {
+        label: keyword.to_string(),
+        kind: Some(CompletionItemKind::KEYWORD),
+        ..Default::default()
+    });
+    let snippets = SNIPPETS.iter().map(|(label, snippet)| CompletionItem {
+        label: label.to_string(),
+        kind: Some(CompletionItemKind::SNIPPET),
+        insert_text: Some(snippet.to_string()),
+        insert_text_format: Some(InsertTextFormat::SNIPPET),
+        ..Default::default()
+    });
+    keywords.chain(snippets)
+}
+
+fn get_symbol_kind(kind: &SymbolKind) -> CompletionItemKind {
+    match kind {
+        SymbolKind::Variable { .. } | SymbolKind::FunctionParameter => CompletionItemKind::VARIABLE,
+        SymbolKind::Constant => CompletionItemKind::CONSTANT,
+        SymbolKind::Function { parameters } => match parameters.first() {
+            Some((name, _)) if name.as_str() == "self" => CompletionItemKind::METHOD,
+            _ => CompletionItemKind::FUNCTION,
+        },
+        SymbolKind::Struct => CompletionItemKind::STRUCT,
+        SymbolKind::StructField | SymbolKind::StorageField => CompletionItemKind::FIELD,
+        SymbolKind::Enum => CompletionItemKind::ENUM,
+        SymbolKind::EnumVariant => CompletionItemKind::ENUM_MEMBER,
+        SymbolKind::Trait | SymbolKind::Abi => CompletionItemKind::INTERFACE,
+        SymbolKind::Module => CompletionItemKind::MODULE,
+    }
+}
+
 fn get_kind(token_type: &TokenType) -> Option<CompletionItemKind> {
     match token_type {
         TokenType::Enum => Some(CompletionItemKind::ENUM),
@@ -43,3 +357,160 @@ fn get_kind(token_type: &TokenType) -> Option<CompletionItemKind> {
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_utils::{
+        position_after, position_of, replace_text, session_with_source,
+    };
+    use lspower::lsp::{TextDocumentIdentifier, TextDocumentPositionParams};
+
+    const SOURCE: &str = r#"script;
+
+struct Point {
+    x: u64,
+    y: u64,
+}
+
+impl Point {
+    fn sum(self) -> u64 {
+        self.x
+    }
+
+    fn new() -> Point {
+        origin()
+    }
+}
+
+enum Shape {
+    Dot: Point,
+    Empty: (),
+}
+
+fn origin() -> Point {
+    Point {
+        x: 0,
+        y: 0,
+    }
+}
+
+fn main() -> u64 {
+    let point = origin();
+    let shape = Shape::Dot(point);
+    let sum = point.sum();
+    point.x
+}
+"#;
+
+    /// The labels of the items offered at the given position, sorted.
+    fn completion_labels(position: Position) -> Vec<String> {
+        let (session, url) = session_with_source(SOURCE);
+        completion_labels_in(session, url, position)
+    }
+
+    fn completion_labels_in(session: Arc<Session>, url: Url, position: Position) -> Vec<String> {
+        let params = CompletionParams {
+            text_document_position: TextDocumentPositionParams::new(
+                TextDocumentIdentifier::new(url),
+                position,
+            ),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            context: None,
+        };
+        let mut labels = match get_completion(session, params) {
+            Some(CompletionResponse::Array(items)) => {
+                items.into_iter().map(|item| item.label).collect::<Vec<_>>()
+            }
+            response => panic!("{:?}", response),
+        };
+        labels.sort();
+        labels
+    }
+
+    fn name(name: &str, end: u32) -> Context {
+        Context::Member(Receiver::Name(name.to_string(), end))
+    }
+
+    fn path(segments: &[&str]) -> Vec<String> {
+        segments.iter().map(|segment| segment.to_string()).collect()
+    }
+
+    #[test]
+    fn context_is_detected_from_the_text_before_the_cursor() {
+        assert_eq!(context("    let a = fo"), Context::Name);
+        assert_eq!(context(""), Context::Name);
+        assert_eq!(context("    point."), name("point", 9));
+        assert_eq!(context("    point.su"), name("point", 9));
+        assert_eq!(context("    storage."), name("storage", 11));
+        assert_eq!(
+            context("    origin(a, f(b))."),
+            Context::Member(Receiver::Call("origin".to_string(), 10))
+        );
+        assert_eq!(
+            context("    abi(Wallet, 0x00).re"),
+            Context::Member(Receiver::AbiCast(path(&["Wallet"])))
+        );
+        assert_eq!(
+            context("    abi(lib::Wallet, address)."),
+            Context::Member(Receiver::AbiCast(path(&["lib", "Wallet"])))
+        );
+        assert_eq!(
+            context("    std::hash::"),
+            Context::Path(path(&["std", "hash"]))
+        );
+        assert_eq!(context("    Shape::Do"), Context::Path(path(&["Shape"])));
+    }
+
+    #[test]
+    fn context_without_a_receiver_completes_names() {
+        assert_eq!(context("    ::"), Context::Name);
+        assert_eq!(context("    ."), Context::Name);
+        assert_eq!(context("    ()."), Context::Name);
+        assert_eq!(context("    a)."), Context::Name);
+    }
+
+    #[test]
+    fn members_of_a_variable_are_its_fields_and_methods() {
+        let labels = completion_labels(position_after(SOURCE, "    point."));
+        assert_eq!(labels, ["sum", "x", "y"]);
+    }
+
+    #[test]
+    fn members_of_a_call_are_those_of_its_return_type() {
+        // The call is being typed, so the source no longer type checks.
+        let (session, url) = session_with_source(SOURCE);
+        replace_text(&session, &url, SOURCE, "    point.x", "    origin().");
+        let text = SOURCE.replace("    point.x", "    origin().");
+        let labels = completion_labels_in(session, url, position_after(&text, "    origin()."));
+        assert_eq!(labels, ["sum", "x", "y"]);
+    }
+
+    #[test]
+    fn paths_complete_enum_variants() {
+        let labels = completion_labels(position_after(SOURCE, "Shape::"));
+        assert_eq!(labels, ["Dot", "Empty"]);
+    }
+
+    #[test]
+    fn names_include_the_locals_declared_before_the_cursor() {
+        let labels = completion_labels(position_of(SOURCE, "let shape"));
+        for label in ["point", "origin", "main", "Point", "Shape", "let", "while"] {
+            assert!(
+                labels.iter().any(|l| l == label),
+                "{} in {:?}",
+                label,
+                labels
+            );
+        }
+        for label in ["shape", "sum", "x"] {
+            assert!(
+                !labels.iter().any(|l| l == label),
+                "{} in {:?}",
+                label,
+                labels
+            );
+        }
+    }
+}
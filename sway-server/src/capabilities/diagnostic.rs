@@ -1,7 +1,10 @@
-use lsp::{Diagnostic, DiagnosticSeverity, Position, Range};
+use lsp::{Diagnostic, DiagnosticSeverity, NumberOrString, Position, Range};
 use lspower::lsp::{self};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
 
-use sway_core::{CompileError, CompileWarning};
+use crate::core::project::get_range_from_span;
+use sway_core::{CompileError, CompileWarning, Warning};
 
 pub fn get_diagnostics(
     warnings: Vec<CompileWarning>,
@@ -14,7 +17,10 @@ pub fn get_diagnostics(
             Diagnostic {
                 range,
                 severity: Some(DiagnosticSeverity::ERROR),
+                code: Some(NumberOrString::String(error.code().to_string())),
+                source: Some("sway".to_string()),
                 message: error.to_friendly_error_string(),
+                data: get_error_fix(error),
                 ..Default::default()
             }
         })
@@ -27,7 +33,10 @@ pub fn get_diagnostics(
             Diagnostic {
                 range,
                 severity: Some(DiagnosticSeverity::WARNING),
+                code: Some(NumberOrString::String(warning.code().to_string())),
+                source: Some("sway".to_string()),
                 message: warning.to_friendly_warning_string(),
+                data: get_warning_fix(warning),
                 ..Default::default()
             }
         })
@@ -36,6 +45,108 @@ pub fn get_diagnostics(
     vec![warnings, errors].into_iter().flatten().collect()
 }
 
+/// A fix for a diagnostic, carried within the diagnostic's `data` so that a code action can be
+/// offered for it when the client asks.
+pub enum Fix {
+    /// Import the item with the given name with a `use` statement.
+    Import { name: String },
+    /// Rename the item named at the range.
+    Rename { range: Range, new_name: String },
+    /// Add the functions with the given signatures before the end of the block ending at
+    /// `position`.
+    AddFunctions {
+        position: Position,
+        signatures: Vec<String>,
+    },
+    /// Declare the variable named at the range as mutable.
+    AddMut { range: Range },
+    /// Remove the declaration at the range.
+    Remove { range: Range },
+}
+
+impl Fix {
+    fn to_value(&self) -> Value {
+        match self {
+            Fix::Import { name } => json!({ "fix": "import", "name": name }),
+            Fix::Rename { range, new_name } => {
+                json!({ "fix": "rename", "range": range, "new_name": new_name })
+            }
+            Fix::AddFunctions {
+                position,
+                signatures,
+            } => json!({ "fix": "add_functions", "position": position, "signatures": signatures }),
+            Fix::AddMut { range } => json!({ "fix": "add_mut", "range": range }),
+            Fix::Remove { range } => json!({ "fix": "remove", "range": range }),
+        }
+    }
+
+    /// Read the fix from a diagnostic's `data`.
+    pub fn from_value(value: &Value) -> Option<Self> {
+        Some(match value.get("fix")?.as_str()? {
+            "import" => Fix::Import {
+                name: field(value, "name")?,
+            },
+            "rename" => Fix::Rename {
+                range: field(value, "range")?,
+                new_name: field(value, "new_name")?,
+            },
+            "add_functions" => Fix::AddFunctions {
+                position: field(value, "position")?,
+                signatures: field(value, "signatures")?,
+            },
+            "add_mut" => Fix::AddMut {
+                range: field(value, "range")?,
+            },
+            "remove" => Fix::Remove {
+                range: field(value, "range")?,
+            },
+            _ => return None,
+        })
+    }
+}
+
+fn field<T: DeserializeOwned>(value: &Value, name: &str) -> Option<T> {
+    serde_json::from_value(value.get(name)?.clone()).ok()
+}
+
+fn get_error_fix(error: &CompileError) -> Option<Value> {
+    let fix = match error {
+        CompileError::SymbolNotFound { name, .. } => Fix::Import { name: name.clone() },
+        CompileError::MissingInterfaceSurfaceMethods {
+            missing_signatures,
+            span,
+            ..
+        } => {
+            // Insert the functions before the block's closing brace.
+            let mut position = get_range_from_span(span).end;
+            position.character = position.character.saturating_sub(1);
+            Fix::AddFunctions {
+                position,
+                signatures: missing_signatures.clone(),
+            }
+        }
+        CompileError::AssignmentToNonMutable { decl_span, .. } => Fix::AddMut {
+            range: get_range_from_span(decl_span),
+        },
+        _ => return None,
+    };
+    Some(fix.to_value())
+}
+
+fn get_warning_fix(warning: &CompileWarning) -> Option<Value> {
+    let range = get_range_from_span(&warning.span);
+    let fix = match &warning.warning_content {
+        Warning::DeadDeclaration
+        | Warning::DeadFunctionDeclaration
+        | Warning::DeadStructDeclaration => Fix::Remove { range },
+        content => Fix::Rename {
+            range,
+            new_name: content.suggested_name()?,
+        },
+    };
+    Some(fix.to_value())
+}
+
 fn get_range(warning_or_error: &WarningOrError<'_>) -> Range {
     let (start, end) = match warning_or_error {
         WarningOrError::Error(error) => error.line_col(),
@@ -1,5 +1,6 @@
 use crate::{
     core::{
+        project::{signature, TypedToken},
         session::{Documents, Session},
         token::Token,
         token_type::{TokenType, VarBody},
@@ -13,6 +14,11 @@ pub fn get_hover_data(session: Arc<Session>, params: HoverParams) -> Option<Hove
     let position = params.text_document_position_params.position;
     let url = &params.text_document_position_params.text_document.uri;
 
+    if let Some(token) = session.get_typed_token(url, position) {
+        return Some(get_typed_hover_format(&token));
+    }
+
+    // The document has not been type checked, so fall back to matching declarations by name.
     match session.documents.get(url.path()) {
         Some(ref document) => {
             if let Some(token) = document.get_token_at_position(position) {
@@ -39,6 +45,18 @@ pub fn get_hover_data(session: Arc<Session>, params: HoverParams) -> Option<Hove
     }
 }
 
+fn get_typed_hover_format(token: &TypedToken) -> Hover {
+    let value = signature(&token.name, &token.kind, token.type_id);
+
+    Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            value: format!("```sway\n{}\n```", value),
+            kind: MarkupKind::Markdown,
+        }),
+        range: Some(token.range),
+    }
+}
+
 fn get_hover_format(token: &Token, documents: &Documents) -> Hover {
     let value = match &token.token_type {
         TokenType::Variable(var_details) => {
@@ -92,3 +110,54 @@ fn get_var_type_from_fn(fn_name: &str, documents: &Documents) -> String {
 
     "".into()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_utils::{position_of, session_with_source};
+    use lspower::lsp::{Position, TextDocumentIdentifier, TextDocumentPositionParams};
+
+    const SOURCE: &str = r#"script;
+
+struct Point {
+    x: u64,
+    y: u64,
+}
+
+fn origin() -> Point {
+    Point {
+        x: 0,
+        y: 0,
+    }
+}
+
+fn main() -> u64 {
+    let point = origin();
+    point.y
+}
+"#;
+
+    fn hover(session: Arc<Session>, url: &lspower::lsp::Url, position: Position) -> String {
+        let params = HoverParams {
+            text_document_position_params: TextDocumentPositionParams::new(
+                TextDocumentIdentifier::new(url.clone()),
+                position,
+            ),
+            work_done_progress_params: Default::default(),
+        };
+        match get_hover_data(session, params).map(|hover| hover.contents) {
+            Some(HoverContents::Markup(content)) => content.value,
+            contents => panic!("{:?}", contents),
+        }
+    }
+
+    #[test]
+    fn hover_shows_the_resolved_signature() {
+        let (session, url) = session_with_source(SOURCE);
+        let hover = |text| hover(session.clone(), &url, position_of(SOURCE, text));
+        assert_eq!(hover("point ="), "```sway\nlet point: Point\n```");
+        assert_eq!(hover("origin()"), "```sway\nfn origin() -> Point\n```");
+        assert_eq!(hover("y\n}"), "```sway\ny: u64\n```");
+        assert_eq!(hover("Point {\n    x"), "```sway\nstruct Point\n```");
+    }
+}
@@ -0,0 +1,242 @@
+//! `textDocument/inlayHint`, which the version of the protocol types in use predates, so its
+//! params and result are read and written as JSON.
+
+use crate::{
+    core::{
+        project::{type_str, TypedToken},
+        session::Session,
+    },
+    utils::lsp_helpers::{index_to_position, position_to_index},
+};
+use lspower::lsp::{Position, Range, Url};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use sway_core::{
+    type_engine::{look_up_type_id, TypeInfo},
+    SymbolKind,
+};
+
+pub const METHOD: &str = "textDocument/inlayHint";
+
+/// The kinds of inlay hints.
+const TYPE: u32 = 1;
+const PARAMETER: u32 = 2;
+
+pub fn get_inlay_hints(session: Arc<Session>, params: Value) -> Option<Value> {
+    let url = Url::parse(params.get("textDocument")?.get("uri")?.as_str()?).ok()?;
+    let range: Range = serde_json::from_value(params.get("range")?.clone()).ok()?;
+
+    let chars: Vec<char> = session
+        .documents
+        .get(url.path())?
+        .get_text()
+        .chars()
+        .collect();
+    let (_, tokens) = session.get_typed_file(&url)?;
+
+    let mut hints = vec![];
+    for token in tokens
+        .iter()
+        .filter(|token| range.start <= token.range.start && token.range.end <= range.end)
+    {
+        // Tokens may be recorded for names the compiler introduced, and may be out of date.
+        let start = position_to_index(&chars, token.range.start);
+        let end = position_to_index(&chars, token.range.end);
+        if chars[start..end].iter().collect::<String>() != token.name {
+            continue;
+        }
+        match &token.kind {
+            SymbolKind::Variable { .. } if token.is_declaration(&url) => {
+                hints.extend(type_hint(&chars, token, end));
+            }
+            SymbolKind::Function { parameters } if !token.is_declaration(&url) => {
+                let parameters = match parameters.first() {
+                    Some((name, _)) if name.as_str() == "self" && start > 0 => {
+                        // The receiver of a method call is its `self` parameter.
+                        if chars[start - 1] == '.' {
+                            &parameters[1..]
+                        } else {
+                            &parameters[..]
+                        }
+                    }
+                    _ => &parameters[..],
+                };
+                let arguments = argument_starts(&chars, end);
+                for ((name, _), start) in parameters.iter().zip(arguments) {
+                    let argument: String = chars[start..]
+                        .iter()
+                        .take_while(|c| c.is_alphanumeric() || **c == '_')
+                        .collect();
+                    // The parameter's name adds nothing when the argument is a namesake.
+                    if argument != name.as_str() {
+                        hints.push(hint(
+                            index_to_position(&chars, start),
+                            format!("{}:", name.as_str()),
+                            PARAMETER,
+                        ));
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    Some(Value::Array(hints))
+}
+
+/// A hint of the type of a variable declared without one.
+fn type_hint(chars: &[char], token: &TypedToken, end: usize) -> Option<Value> {
+    let is_annotated = chars[end..].iter().find(|c| !c.is_whitespace()) == Some(&':');
+    if is_annotated {
+        return None;
+    }
+    let type_id = token.type_id?;
+    match look_up_type_id(type_id) {
+        TypeInfo::Unknown | TypeInfo::ErrorRecovery => None,
+        _ => Some(hint(
+            token.range.end,
+            format!(": {}", type_str(type_id)),
+            TYPE,
+        )),
+    }
+}
+
+/// The index at which each argument begins, for the call whose function's name ends at the
+/// given index.
+fn argument_starts(chars: &[char], name_end: usize) -> Vec<usize> {
+    let mut ix = name_end;
+    while ix < chars.len() && chars[ix].is_whitespace() {
+        ix += 1;
+    }
+    if chars.get(ix) != Some(&'(') {
+        return vec![];
+    }
+
+    let mut starts = vec![];
+    let mut depth = 0;
+    let mut at_argument_start = true;
+    for (ix, c) in chars.iter().enumerate().skip(ix + 1) {
+        if at_argument_start && !c.is_whitespace() {
+            if *c != ')' {
+                starts.push(ix);
+            }
+            at_argument_start = false;
+        }
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' if depth == 0 => break,
+            ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => at_argument_start = true,
+            _ => (),
+        }
+    }
+    starts
+}
+
+fn hint(position: Position, label: String, kind: u32) -> Value {
+    json!({
+        "position": position,
+        "label": label,
+        "kind": kind,
+        "paddingLeft": false,
+        "paddingRight": kind == PARAMETER,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_utils::{position_after, position_of, session_with_source};
+
+    const SOURCE: &str = r#"script;
+
+struct Point {
+    x: u64,
+    y: u64,
+}
+
+impl Point {
+    fn scale(self, by: u64, offset: u64) -> u64 {
+        by
+    }
+}
+
+fn add(a: u64, b: u64) -> u64 {
+    a
+}
+
+fn main() -> u64 {
+    let b = 2;
+    let point = Point {
+        x: 1,
+        y: b,
+    };
+    let total: u64 = add(1, b);
+    point.scale(total, add(b, 3))
+}
+"#;
+
+    /// The position, label and kind of each hint within the source, in order.
+    fn inlay_hints() -> Vec<(Position, String, u32)> {
+        let (session, url) = session_with_source(SOURCE);
+        let params = json!({
+            "textDocument": { "uri": url },
+            "range": Range::new(Position::new(0, 0), position_after(SOURCE, SOURCE)),
+        });
+        let mut hints = match get_inlay_hints(session, params) {
+            Some(Value::Array(hints)) => hints
+                .into_iter()
+                .map(|hint| {
+                    (
+                        serde_json::from_value(hint["position"].clone()).unwrap(),
+                        hint["label"].as_str().unwrap().to_string(),
+                        hint["kind"].as_u64().unwrap() as u32,
+                    )
+                })
+                .collect::<Vec<_>>(),
+            hints => panic!("{:?}", hints),
+        };
+        hints.sort_by_key(|(position, ..)| *position);
+        hints
+    }
+
+    #[test]
+    fn hints_name_the_types_of_variables_and_the_parameters_of_arguments() {
+        assert_eq!(
+            inlay_hints(),
+            [
+                (position_after(SOURCE, "let b"), ": u64".to_string(), TYPE),
+                (
+                    position_after(SOURCE, "let point"),
+                    ": Point".to_string(),
+                    TYPE
+                ),
+                // `b` is given to a parameter of the same name.
+                (position_of(SOURCE, "1, b)"), "a:".to_string(), PARAMETER),
+                // The receiver is given to `self`.
+                (
+                    position_of(SOURCE, "total, add"),
+                    "by:".to_string(),
+                    PARAMETER
+                ),
+                (
+                    position_of(SOURCE, "add(b, 3)"),
+                    "offset:".to_string(),
+                    PARAMETER
+                ),
+                (position_of(SOURCE, "b, 3)"), "a:".to_string(), PARAMETER),
+                (position_of(SOURCE, "3))"), "b:".to_string(), PARAMETER),
+            ]
+        );
+    }
+
+    #[test]
+    fn argument_starts_skip_nested_delimiters() {
+        let chars: Vec<char> = "add( a, f(b, c), [d, e] ,)".chars().collect();
+        assert_eq!(argument_starts(&chars, 3), [5, 8, 17]);
+        let chars: Vec<char> = "add ()".chars().collect();
+        assert!(argument_starts(&chars, 3).is_empty());
+        let chars: Vec<char> = "add;".chars().collect();
+        assert!(argument_starts(&chars, 3).is_empty());
+    }
+}
@@ -1,3 +1,5 @@
+pub mod call_hierarchy;
+pub mod code_actions;
 pub mod completion;
 pub mod diagnostic;
 pub mod document_symbol;
@@ -6,6 +8,10 @@ pub mod formatting;
 pub mod go_to;
 pub mod highlight;
 pub mod hover;
+pub mod inlay_hints;
+pub mod references;
 pub mod rename;
 pub mod semantic_tokens;
+pub mod signature_help;
 pub mod text_sync;
+pub mod workspace_symbol;
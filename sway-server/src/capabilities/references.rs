@@ -0,0 +1,100 @@
+use crate::core::session::Session;
+use lspower::lsp::{Location, ReferenceParams};
+use std::sync::Arc;
+
+pub fn get_references(session: Arc<Session>, params: ReferenceParams) -> Option<Vec<Location>> {
+    let url = params.text_document_position.text_document.uri;
+    let position = params.text_document_position.position;
+    let include_declaration = params.context.include_declaration;
+
+    let definition = session.get_typed_token(&url, position)?.definition?;
+    session.with_index(|index| {
+        index
+            .references(&definition)
+            .filter(|(url, token)| include_declaration || !token.is_declaration(url))
+            .map(|(url, token)| Location::new(url.clone(), token.range))
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_utils::{name_range, position_of, session_with_source};
+    use lspower::lsp::{
+        Range, ReferenceContext, TextDocumentIdentifier, TextDocumentPositionParams,
+    };
+
+    const SOURCE: &str = r#"script;
+
+struct Point {
+    x: u64,
+}
+
+fn origin() -> Point {
+    Point {
+        x: 0,
+    }
+}
+
+fn first(point: Point) -> u64 {
+    point.x
+}
+
+fn main() -> u64 {
+    let point = origin();
+    let other = origin();
+    first(point)
+}
+"#;
+
+    fn references(text: &str, include_declaration: bool) -> Vec<Range> {
+        let (session, url) = session_with_source(SOURCE);
+        let params = ReferenceParams {
+            text_document_position: TextDocumentPositionParams::new(
+                TextDocumentIdentifier::new(url.clone()),
+                position_of(SOURCE, text),
+            ),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            context: ReferenceContext {
+                include_declaration,
+            },
+        };
+        get_references(session, params)
+            .unwrap()
+            .into_iter()
+            .map(|location| {
+                assert_eq!(location.uri, url);
+                location.range
+            })
+            .collect()
+    }
+
+    #[test]
+    fn references_are_found_from_any_occurrence() {
+        let expected = [
+            name_range(SOURCE, "fn origin", "origin"),
+            name_range(SOURCE, "let point = origin", "origin"),
+            name_range(SOURCE, "let other = origin", "origin"),
+        ];
+        assert_eq!(references("origin() -> Point", true), expected);
+        assert_eq!(references("origin();\n    let other", true), expected);
+        assert_eq!(references("origin() -> Point", false), expected[1..]);
+    }
+
+    #[test]
+    fn references_distinguish_items_of_the_same_name() {
+        assert_eq!(
+            references("point: Point", true),
+            [
+                name_range(SOURCE, "fn first(point", "point"),
+                name_range(SOURCE, "    point", "point")
+            ]
+        );
+        assert_eq!(
+            references("point = origin", false),
+            [name_range(SOURCE, "    first(point", "point")]
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use std::{collections::HashMap, sync::Arc};
 
-use lspower::lsp::{self, WorkspaceEdit};
+use lspower::lsp::{self, Url, WorkspaceEdit};
 
 use crate::{
     core::{session::Session, token::Token, token_type::TokenType},
@@ -12,6 +12,21 @@ pub fn rename(session: Arc<Session>, params: lsp::RenameParams) -> Option<lsp::W
     let url = params.text_document_position.text_document.uri;
     let position = params.text_document_position.position;
 
+    // Rename every occurrence of a type checked item, across all files and packages.
+    if let Some(token) = session.get_typed_token(&url, position) {
+        let definition = token.definition?;
+        return session.with_index(|index| {
+            let mut map_of_changes: HashMap<Url, Vec<lsp::TextEdit>> = HashMap::new();
+            for (url, token) in index.references(&definition) {
+                map_of_changes
+                    .entry(url.clone())
+                    .or_default()
+                    .push(lsp::TextEdit::new(token.range, new_name.clone()));
+            }
+            WorkspaceEdit::new(map_of_changes)
+        });
+    }
+
     match session.documents.get(url.path()) {
         Some(ref document) => {
             if let Some(token) = document.get_token_at_position(position) {
@@ -39,6 +54,15 @@ pub fn prepare_rename(
 ) -> Option<lsp::PrepareRenameResponse> {
     let url = params.text_document.uri;
 
+    if let Some(token) = session.get_typed_token(&url, params.position) {
+        return token
+            .definition
+            .map(|_| lsp::PrepareRenameResponse::RangeWithPlaceholder {
+                range: token.range,
+                placeholder: token.name,
+            });
+    }
+
     match session.documents.get(url.path()) {
         Some(ref document) => {
             if let Some(token) = document.get_token_at_position(params.position) {
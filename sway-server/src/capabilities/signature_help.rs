@@ -0,0 +1,395 @@
+use crate::{
+    core::{
+        project::{signature, type_str, TypedToken},
+        session::Session,
+    },
+    utils::lsp_helpers::{index_to_position, position_to_index},
+};
+use lspower::lsp::{
+    ParameterInformation, ParameterLabel, Position, SignatureHelp, SignatureHelpParams,
+    SignatureInformation, Url,
+};
+use std::sync::Arc;
+use sway_core::{type_engine::TypeId, NamedItem, NamespaceRef, SymbolKind};
+use sway_types::Ident;
+
+/// The keywords that may precede a name and a delimiter within a declaration, e.g. `fn name(`.
+const DECLARATION_KEYWORDS: &[&str] = &["abi", "enum", "fn", "for", "impl", "struct", "trait"];
+
+/// The call or struct expression enclosing the cursor.
+struct Call {
+    /// The path of the called function or of the constructed struct.
+    path: Vec<String>,
+    /// The index of the character following the last segment of the path.
+    path_end: usize,
+    /// Whether the function is called as a method, i.e. `receiver.name(..)`.
+    is_method: bool,
+    /// Whether this is a struct expression, i.e. `Name { .. }`.
+    is_struct: bool,
+    /// The index of the argument containing the cursor.
+    argument: usize,
+    /// The name of the field before the cursor within a struct expression, i.e. `name: ..`.
+    field: Option<String>,
+}
+
+pub fn get_signature_help(
+    session: Arc<Session>,
+    params: SignatureHelpParams,
+) -> Option<SignatureHelp> {
+    let url = params.text_document_position_params.text_document.uri;
+    let position = params.text_document_position_params.position;
+
+    let chars: Vec<char> = session
+        .documents
+        .get(url.path())?
+        .get_text()
+        .chars()
+        .collect();
+    let call = enclosing_call(&chars, position_to_index(&chars, position))?;
+    let (namespace, tokens) = session.get_typed_file(&url)?;
+    let path: Vec<&str> = call.path.iter().map(String::as_str).collect();
+
+    let (signature, active_parameter) = if call.is_struct {
+        let fields = sway_core::struct_fields(namespace, &path);
+        let active_parameter = call
+            .field
+            .as_ref()
+            .and_then(|name| fields.iter().position(|field| field.name == *name))
+            .unwrap_or(call.argument);
+        (struct_signature(path.last()?, &fields)?, active_parameter)
+    } else {
+        let end = index_to_position(&chars, call.path_end);
+        let item = function_item(&url, namespace, &tokens, &path, end)?;
+        let parameters = match &item.kind {
+            SymbolKind::Function { parameters } => parameters,
+            _ => return None,
+        };
+        // The receiver of a method call is its `self` parameter.
+        let skipped = match parameters.first() {
+            Some((name, _)) if call.is_method && name.as_str() == "self" => 1,
+            _ => 0,
+        };
+        (
+            function_signature(&item, parameters),
+            call.argument + skipped,
+        )
+    };
+
+    Some(SignatureHelp {
+        signatures: vec![signature],
+        active_signature: Some(0),
+        active_parameter: Some(active_parameter as u32),
+    })
+}
+
+/// The function named by the path and ending at the given position, as recorded when the
+/// document was last type checked, or otherwise the function of that name within the file or
+/// namespace.
+fn function_item(
+    url: &Url,
+    namespace: NamespaceRef,
+    tokens: &[TypedToken],
+    path: &[&str],
+    end: Position,
+) -> Option<NamedItem> {
+    let (name, prefix) = path.split_last()?;
+    let functions = || {
+        tokens
+            .iter()
+            .filter(|token| token.name == *name)
+            .filter(|token| matches!(token.kind, SymbolKind::Function { .. }))
+    };
+    functions()
+        .find(|token| token.range.end == end)
+        .or_else(|| functions().filter(|token| token.range.end < end).last())
+        .or_else(|| functions().find(|token| token.is_declaration(url)))
+        .map(|token| NamedItem {
+            name: token.name.clone(),
+            kind: token.kind.clone(),
+            type_id: token.type_id,
+        })
+        .or_else(|| {
+            sway_core::path_items(namespace, prefix)
+                .into_iter()
+                .filter(|item| matches!(item.kind, SymbolKind::Function { .. }))
+                .find(|item| item.name == *name)
+        })
+}
+
+fn function_signature(item: &NamedItem, parameters: &[(Ident, TypeId)]) -> SignatureInformation {
+    let label = signature(&item.name, &item.kind, item.type_id);
+    // The parameters follow the opening parenthesis, separated by `, `.
+    let mut start = label.find('(').map_or(0, |ix| ix + 1);
+    let parameters = parameters
+        .iter()
+        .map(|(name, type_id)| {
+            let len = format!("{}: {}", name.as_str(), type_str(*type_id)).len();
+            let offsets = [start as u32, (start + len) as u32];
+            start += len + 2;
+            ParameterInformation {
+                label: ParameterLabel::LabelOffsets(offsets),
+                documentation: None,
+            }
+        })
+        .collect();
+    SignatureInformation {
+        label,
+        documentation: None,
+        parameters: Some(parameters),
+        active_parameter: None,
+    }
+}
+
+fn struct_signature(name: &str, fields: &[NamedItem]) -> Option<SignatureInformation> {
+    if fields.is_empty() {
+        return None;
+    }
+    let mut label = format!("struct {} {{ ", name);
+    let mut parameters = vec![];
+    for (ix, field) in fields.iter().enumerate() {
+        if ix > 0 {
+            label.push_str(", ");
+        }
+        let start = label.len() as u32;
+        label.push_str(&signature(&field.name, &field.kind, field.type_id));
+        parameters.push(ParameterInformation {
+            label: ParameterLabel::LabelOffsets([start, label.len() as u32]),
+            documentation: None,
+        });
+    }
+    label.push_str(" }");
+    Some(SignatureInformation {
+        label,
+        documentation: None,
+        parameters: Some(parameters),
+        active_parameter: None,
+    })
+}
+
+/// Find the call or struct expression whose unclosed delimiter precedes the cursor.
+fn enclosing_call(chars: &[char], cursor: usize) -> Option<Call> {
+    let mut depth = 0;
+    let mut argument = 0;
+    let mut argument_start = cursor;
+    let mut open = None;
+    for ix in (0..cursor).rev() {
+        match chars[ix] {
+            ')' | ']' | '}' => depth += 1,
+            '(' | '[' | '{' if depth > 0 => depth -= 1,
+            '(' | '{' => {
+                open = Some(ix);
+                break;
+            }
+            '[' => return None,
+            ',' if depth == 0 => {
+                if argument == 0 {
+                    argument_start = ix + 1;
+                }
+                argument += 1;
+            }
+            ';' if depth == 0 => return None,
+            _ => (),
+        }
+    }
+    let open = open?;
+    if argument == 0 {
+        argument_start = open + 1;
+    }
+    let is_struct = chars[open] == '{';
+
+    // The path precedes the delimiter, e.g. `a::b::name(`.
+    let mut path = vec![];
+    let mut end = open;
+    while end > 0 && chars[end - 1].is_whitespace() {
+        end -= 1;
+    }
+    let path_end = end;
+    loop {
+        let start = chars[..end]
+            .iter()
+            .rposition(|c| !is_ident_char(*c))
+            .map_or(0, |ix| ix + 1);
+        if start == end {
+            break;
+        }
+        path.push(chars[start..end].iter().collect::<String>());
+        if start >= 2 && chars[start - 2..start] == [':', ':'] {
+            end = start - 2;
+        } else {
+            end = start;
+            break;
+        }
+    }
+    path.reverse();
+    let name = path.last()?;
+    if is_struct && !name.starts_with(char::is_uppercase) {
+        return None;
+    }
+    let is_method = end > 0 && chars[end - 1] == '.';
+
+    // Declarations are not calls.
+    let keyword_end = chars[..end]
+        .iter()
+        .rposition(|c| !c.is_whitespace())
+        .map_or(0, |ix| ix + 1);
+    let keyword_start = chars[..keyword_end]
+        .iter()
+        .rposition(|c| !is_ident_char(*c))
+        .map_or(0, |ix| ix + 1);
+    let keyword: String = chars[keyword_start..keyword_end].iter().collect();
+    if DECLARATION_KEYWORDS.contains(&keyword.as_str()) {
+        return None;
+    }
+
+    let field = if is_struct {
+        let argument: String = chars[argument_start..cursor].iter().collect();
+        argument
+            .split_once(':')
+            .map(|(field, _)| field.trim().to_string())
+    } else {
+        None
+    };
+
+    Some(Call {
+        path,
+        path_end,
+        is_method,
+        is_struct,
+        argument,
+        field,
+    })
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_utils::{position_after, session_with_source};
+    use lspower::lsp::{TextDocumentIdentifier, TextDocumentPositionParams};
+
+    const SOURCE: &str = r#"script;
+
+struct Point {
+    x: u64,
+    y: u64,
+}
+
+impl Point {
+    fn scale(self, by: u64, offset: u64) -> u64 {
+        by
+    }
+}
+
+fn add(a: u64, b: u64) -> u64 {
+    a
+}
+
+fn main() -> u64 {
+    let b = 2;
+    let point = Point {
+        x: 1,
+        y: b,
+    };
+    let total: u64 = add(1, b);
+    point.scale(total, add(b, 3))
+}
+"#;
+
+    /// The label of the signature shown after the first occurrence of `text`, along with the
+    /// labels of its parameters and the index of the active one.
+    fn signature_help(text: &str) -> (String, Vec<String>, u32) {
+        let (session, url) = session_with_source(SOURCE);
+        let params = SignatureHelpParams {
+            context: None,
+            text_document_position_params: TextDocumentPositionParams::new(
+                TextDocumentIdentifier::new(url),
+                position_after(SOURCE, text),
+            ),
+            work_done_progress_params: Default::default(),
+        };
+        let mut help = get_signature_help(session, params).unwrap();
+        assert_eq!(help.signatures.len(), 1);
+        let signature = help.signatures.remove(0);
+        let parameters = signature
+            .parameters
+            .unwrap()
+            .into_iter()
+            .map(|parameter| match parameter.label {
+                ParameterLabel::LabelOffsets([start, end]) => {
+                    signature.label[start as usize..end as usize].to_string()
+                }
+                label => panic!("{:?}", label),
+            })
+            .collect();
+        (signature.label, parameters, help.active_parameter.unwrap())
+    }
+
+    fn call(text: &str) -> Option<Call> {
+        let chars: Vec<char> = text.chars().collect();
+        enclosing_call(&chars, chars.len())
+    }
+
+    #[test]
+    fn function_calls_show_the_parameter_of_the_argument() {
+        let (label, parameters, active) = signature_help("add(1, ");
+        assert_eq!(label, "fn add(a: u64, b: u64) -> u64");
+        assert_eq!(parameters, ["a: u64", "b: u64"]);
+        assert_eq!(active, 1);
+        let (label, _, active) = signature_help("= add(");
+        assert_eq!(label, "fn add(a: u64, b: u64) -> u64");
+        assert_eq!(active, 0);
+    }
+
+    #[test]
+    fn method_calls_skip_the_receiver() {
+        let (label, parameters, active) = signature_help("point.scale(total, ");
+        assert_eq!(label, "fn scale(self: Point, by: u64, offset: u64) -> u64");
+        assert_eq!(parameters, ["self: Point", "by: u64", "offset: u64"]);
+        assert_eq!(active, 2);
+    }
+
+    #[test]
+    fn the_innermost_call_is_shown() {
+        let (label, _, active) = signature_help("total, add(b, ");
+        assert_eq!(label, "fn add(a: u64, b: u64) -> u64");
+        assert_eq!(active, 1);
+    }
+
+    #[test]
+    fn struct_expressions_show_the_field_being_given() {
+        let (label, parameters, active) = signature_help("        y: ");
+        assert_eq!(label, "struct Point { x: u64, y: u64 }");
+        assert_eq!(parameters, ["x: u64", "y: u64"]);
+        assert_eq!(active, 1);
+    }
+
+    #[test]
+    fn enclosing_calls_are_found_from_the_text_before_the_cursor() {
+        let add = call("    let a = lib::add(f(1, 2), [3, 4], ").unwrap();
+        assert_eq!(add.path, ["lib", "add"]);
+        assert_eq!(add.argument, 2);
+        assert!(!add.is_method && !add.is_struct);
+
+        let scale = call("    point.scale(").unwrap();
+        assert_eq!(scale.path, ["scale"]);
+        assert_eq!(scale.argument, 0);
+        assert!(scale.is_method);
+
+        let point = call("    Point { x: 1, y: ").unwrap();
+        assert_eq!(point.path, ["Point"]);
+        assert!(point.is_struct);
+        assert_eq!(point.field.as_deref(), Some("y"));
+    }
+
+    #[test]
+    fn declarations_blocks_and_indices_are_not_calls() {
+        assert!(call("fn add(").is_none());
+        assert!(call("impl Point {").is_none());
+        assert!(call("    if a {").is_none());
+        assert!(call("    a[").is_none());
+        assert!(call("    add(1);\n    b").is_none());
+    }
+}
@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use lspower::lsp::{
     Diagnostic, DidChangeTextDocumentParams, DidOpenTextDocumentParams, DidSaveTextDocumentParams,
+    Url,
 };
 
 use crate::core::{
@@ -12,44 +13,47 @@ use crate::core::{
 pub fn handle_open_file(
     session: Arc<Session>,
     params: &DidOpenTextDocumentParams,
-) -> Vec<Diagnostic> {
-    let path = params.text_document.uri.path();
+) -> Vec<(Url, Vec<Diagnostic>)> {
+    let url = &params.text_document.uri;
+    let path = url.path();
 
-    if !session.contains_sway_file(&params.text_document.uri) {
+    if !session.contains_sway_file(url) {
         if let Ok(text_document) = TextDocument::build_from_path(path) {
             let _ = session.store_document(text_document);
         }
     }
 
-    match session.parse_document(path) {
-        Ok(diagnostics) => diagnostics,
-        Err(DocumentError::FailedToParse(diagnostics)) => diagnostics,
-        _ => vec![],
-    }
+    get_diagnostics(&session, url)
 }
 
 pub fn handle_change_file(
     session: Arc<Session>,
     params: DidChangeTextDocumentParams,
-) -> Result<(), DocumentError> {
-    session.update_text_document(&params.text_document.uri, params.content_changes)
+) -> Result<Vec<(Url, Vec<Diagnostic>)>, DocumentError> {
+    let url = &params.text_document.uri;
+    session.update_text_document(url, params.content_changes)?;
+    Ok(get_diagnostics(&session, url))
 }
 
 pub fn handle_save_file(
     session: Arc<Session>,
     params: &DidSaveTextDocumentParams,
-) -> Option<Vec<Diagnostic>> {
-    let path = params.text_document.uri.path();
-
-    match session.parse_document(path) {
-        Ok(diagnostics) => {
-            if diagnostics.is_empty() {
-                None
-            } else {
-                Some(diagnostics)
-            }
-        }
-        Err(DocumentError::FailedToParse(diagnostics)) => Some(diagnostics),
-        _ => None,
+) -> Vec<(Url, Vec<Diagnostic>)> {
+    get_diagnostics(&session, &params.text_document.uri)
+}
+
+/// Parse the document and type check the project containing it, returning the diagnostics of
+/// each file in the project. If the project cannot be resolved, only the document's parse
+/// diagnostics are returned.
+fn get_diagnostics(session: &Session, url: &Url) -> Vec<(Url, Vec<Diagnostic>)> {
+    let parse_diagnostics = match session.parse_document(url.path()) {
+        Ok(diagnostics) => diagnostics,
+        Err(DocumentError::FailedToParse(diagnostics)) => diagnostics,
+        _ => vec![],
+    };
+
+    match session.compile_project(url) {
+        Ok(diagnostics) => diagnostics,
+        Err(_) => vec![(url.clone(), parse_diagnostics)],
     }
 }
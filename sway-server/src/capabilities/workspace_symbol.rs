@@ -0,0 +1,132 @@
+use crate::core::{
+    project::TypedToken,
+    session::{Session, SymbolIndex},
+};
+use lspower::lsp::{Location, SymbolInformation, SymbolKind, Url, WorkspaceSymbolParams};
+use std::sync::Arc;
+
+pub fn get_workspace_symbols(
+    session: Arc<Session>,
+    params: WorkspaceSymbolParams,
+) -> Option<Vec<SymbolInformation>> {
+    let query = params.query.to_lowercase();
+
+    session.with_index(|index| {
+        index
+            .declarations()
+            .filter(|(_, token)| {
+                !matches!(
+                    token.kind,
+                    sway_core::SymbolKind::Variable { .. }
+                        | sway_core::SymbolKind::FunctionParameter
+                ) && token.name.to_lowercase().contains(&query)
+            })
+            .map(|(url, token)| create_symbol_info(index, url, token))
+            .collect()
+    })
+}
+
+#[allow(warnings)]
+// TODO: the "deprecated: None" field is deprecated according to this library
+fn create_symbol_info(index: &SymbolIndex, url: &Url, token: &TypedToken) -> SymbolInformation {
+    let container_name = token
+        .container
+        .as_ref()
+        .and_then(|container| index.declaration(container))
+        .map(|container| container.name.clone());
+    SymbolInformation {
+        name: token.name.clone(),
+        kind: get_kind(&token.kind),
+        location: Location::new(url.clone(), token.range),
+        tags: None,
+        container_name,
+        deprecated: None,
+    }
+}
+
+pub fn get_kind(kind: &sway_core::SymbolKind) -> SymbolKind {
+    use sway_core::SymbolKind::*;
+    match kind {
+        Variable { .. } | FunctionParameter => SymbolKind::VARIABLE,
+        Constant => SymbolKind::CONSTANT,
+        Function { parameters } => match parameters.first() {
+            Some((name, _)) if name.as_str() == "self" => SymbolKind::METHOD,
+            _ => SymbolKind::FUNCTION,
+        },
+        Struct => SymbolKind::STRUCT,
+        StructField | StorageField => SymbolKind::FIELD,
+        Enum => SymbolKind::ENUM,
+        EnumVariant => SymbolKind::ENUM_MEMBER,
+        Trait | Abi => SymbolKind::INTERFACE,
+        Module => SymbolKind::MODULE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_utils::session_with_source;
+
+    const SOURCE: &str = r#"script;
+
+struct Point {
+    x: u64,
+}
+
+fn origin() -> Point {
+    Point {
+        x: 0,
+    }
+}
+
+fn first(point: Point) -> u64 {
+    point.x
+}
+
+fn main() -> u64 {
+    let point = origin();
+    let other = origin();
+    first(point)
+}
+"#;
+
+    fn workspace_symbols(query: &str) -> Vec<(String, SymbolKind)> {
+        let (session, url) = session_with_source(SOURCE);
+        let params = WorkspaceSymbolParams {
+            query: query.to_string(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+        get_workspace_symbols(session, params)
+            .unwrap()
+            .into_iter()
+            .map(|symbol| {
+                assert_eq!(symbol.location.uri, url);
+                (symbol.name, symbol.kind)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn every_item_is_found_without_a_query() {
+        assert_eq!(
+            workspace_symbols(""),
+            [
+                ("Point".to_string(), SymbolKind::STRUCT),
+                ("x".to_string(), SymbolKind::FIELD),
+                ("origin".to_string(), SymbolKind::FUNCTION),
+                ("first".to_string(), SymbolKind::FUNCTION),
+                ("main".to_string(), SymbolKind::FUNCTION),
+            ]
+        );
+    }
+
+    #[test]
+    fn items_are_matched_by_name_ignoring_case() {
+        assert_eq!(
+            workspace_symbols("OIN"),
+            [("Point".to_string(), SymbolKind::STRUCT)]
+        );
+        assert!(workspace_symbols("other").is_empty());
+    }
+}
@@ -0,0 +1,318 @@
+//! Type checking the package containing a document along with its dependencies.
+
+use crate::capabilities::diagnostic::get_diagnostics;
+use lspower::lsp::{Diagnostic, Location, Position, Range, Url};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use sway_core::{
+    type_engine::{look_up_type_id, TypeId, TypeInfo},
+    BuildConfig, CompileAstResult, CompileError, CompileWarning, NamespaceRef, NamespaceWrapper,
+    SymbolKind,
+};
+use sway_types::span::Span;
+
+/// Resolves the dependency graph of the package containing a file.
+///
+/// The package graph is owned by `forc`, which provides the implementation when starting the
+/// server.
+pub trait PackageResolver: std::fmt::Debug + Send + Sync {
+    /// Resolve the package containing the file at `path` along with all of its dependencies, in
+    /// order of compilation. The package containing the file is last.
+    fn resolve(&self, path: &Path) -> Result<Vec<Package>, String>;
+}
+
+/// A package to be type checked.
+#[derive(Clone, Debug)]
+pub struct Package {
+    /// The name under which dependents refer to the package.
+    pub name: String,
+    /// The canonical path of the directory containing the package's manifest.
+    pub manifest_dir: PathBuf,
+    /// The path of the package's entry file.
+    pub entry_path: PathBuf,
+    /// The indices of the package's direct and transitive dependencies within the resolved
+    /// packages.
+    pub dependencies: Vec<usize>,
+}
+
+/// An occurrence of a name within a type checked package.
+#[derive(Clone, Debug)]
+pub struct TypedToken {
+    pub range: Range,
+    pub name: String,
+    pub kind: SymbolKind,
+    /// The resolved type of the named item, or the return type if it is a function.
+    pub type_id: Option<TypeId>,
+    /// The location of the name at the item's declaration.
+    pub definition: Option<Location>,
+    /// The location of the name of the function whose body contains this token, if any.
+    pub container: Option<Location>,
+}
+
+impl TypedToken {
+    pub fn contains(&self, position: Position) -> bool {
+        self.range.start <= position && position <= self.range.end
+    }
+
+    pub fn is_declaration(&self, url: &Url) -> bool {
+        match &self.definition {
+            Some(definition) => definition.uri == *url && definition.range == self.range,
+            None => false,
+        }
+    }
+}
+
+/// The typed tokens, namespaces and diagnostics of each file of a package and its dependencies.
+#[derive(Debug, Default)]
+pub struct Project {
+    pub tokens: HashMap<PathBuf, Vec<TypedToken>>,
+    /// The namespace of each file's own declarations and imports.
+    pub namespaces: HashMap<PathBuf, NamespaceRef>,
+    pub diagnostics: HashMap<PathBuf, Vec<Diagnostic>>,
+}
+
+impl Project {
+    /// Type check the given packages in order, using `sources` in place of the contents of any
+    /// files open in the editor.
+    ///
+    /// A package that fails to type check keeps its tokens from the `previous` project, so that
+    /// hover and definitions keep working while it is being edited. Packages depending on a
+    /// package that failed are not type checked.
+    pub fn compile(
+        packages: &[Package],
+        sources: &HashMap<PathBuf, Arc<str>>,
+        previous: Option<&Project>,
+    ) -> Self {
+        let mut project = Project::default();
+        let mut namespaces: Vec<Option<NamespaceRef>> = Vec::with_capacity(packages.len());
+        for package in packages {
+            let deps: Option<Vec<_>> = package
+                .dependencies
+                .iter()
+                .map(|&dep| Some((&packages[dep].name, namespaces.get(dep).copied()??)))
+                .collect();
+            let namespace = match deps {
+                Some(deps) => {
+                    let namespace = sway_core::create_module();
+                    for (name, dep_namespace) in deps {
+                        namespace.insert_module_ref(name.clone(), dep_namespace);
+                    }
+                    project.compile_package(packages, package, namespace, sources)
+                }
+                None => None,
+            };
+            if namespace.is_none() {
+                if let Some(previous) = previous {
+                    project.keep_tokens(previous, package);
+                }
+            }
+            namespaces.push(namespace);
+        }
+        project
+    }
+
+    /// Type check a single package, returning its namespace on success.
+    fn compile_package(
+        &mut self,
+        packages: &[Package],
+        package: &Package,
+        namespace: NamespaceRef,
+        sources: &HashMap<PathBuf, Arc<str>>,
+    ) -> Option<NamespaceRef> {
+        let source = match sources.get(&package.entry_path) {
+            Some(source) => source.clone(),
+            None => Arc::from(std::fs::read_to_string(&package.entry_path).ok()?),
+        };
+        // Spans refer to files relative to the parent of the manifest directory, as in `forc`.
+        let parent = package
+            .manifest_dir
+            .parent()
+            .unwrap_or(&package.manifest_dir);
+        let file_name = package
+            .entry_path
+            .strip_prefix(parent)
+            .unwrap_or(&package.entry_path)
+            .to_path_buf();
+        let build_config = BuildConfig::root_from_file_name_and_manifest_path(
+            file_name,
+            package.manifest_dir.clone(),
+        )
+        .source_overrides(sources.clone());
+
+        match sway_core::compile_to_ast(source, namespace, &build_config) {
+            CompileAstResult::Failure { warnings, errors } => {
+                self.add_diagnostics(packages, package, warnings, errors);
+                None
+            }
+            CompileAstResult::Success {
+                parse_tree,
+                warnings,
+                ..
+            } => {
+                self.add_diagnostics(packages, package, warnings, vec![]);
+                for symbol in parse_tree.symbols() {
+                    let path = match file_path(packages, symbol.ident.span()) {
+                        Some(path) => path,
+                        None => continue,
+                    };
+                    let definition = symbol
+                        .definition
+                        .as_ref()
+                        .and_then(|definition| location(packages, definition.span()));
+                    let container = symbol
+                        .container
+                        .as_ref()
+                        .and_then(|container| location(packages, container.span()));
+                    let token = TypedToken {
+                        range: get_range_from_span(symbol.ident.span()),
+                        name: symbol.ident.as_str().to_string(),
+                        kind: symbol.kind,
+                        type_id: symbol.type_id,
+                        definition,
+                        container,
+                    };
+                    self.tokens.entry(path).or_default().push(token);
+                }
+                for (path, namespace) in parse_tree.included_namespaces() {
+                    if let Some(path) = resolve_path(packages, &path) {
+                        self.namespaces.insert(path, namespace);
+                    }
+                }
+                let namespace = parse_tree.get_namespace_ref();
+                self.namespaces
+                    .insert(package.entry_path.clone(), namespace);
+                Some(namespace)
+            }
+        }
+    }
+
+    /// Group the diagnostics of a package by file. Those without a file are reported on the
+    /// package's entry file.
+    fn add_diagnostics(
+        &mut self,
+        packages: &[Package],
+        package: &Package,
+        warnings: Vec<CompileWarning>,
+        errors: Vec<CompileError>,
+    ) {
+        let mut by_file: HashMap<PathBuf, (Vec<CompileWarning>, Vec<CompileError>)> =
+            HashMap::new();
+        by_file.entry(package.entry_path.clone()).or_default();
+        for warning in warnings {
+            let path =
+                file_path(packages, &warning.span).unwrap_or_else(|| package.entry_path.clone());
+            by_file.entry(path).or_default().0.push(warning);
+        }
+        for error in errors {
+            let path = file_path(packages, error.internal_span())
+                .unwrap_or_else(|| package.entry_path.clone());
+            by_file.entry(path).or_default().1.push(error);
+        }
+        for (path, (warnings, errors)) in by_file {
+            self.diagnostics
+                .entry(path)
+                .or_default()
+                .extend(get_diagnostics(warnings, errors));
+        }
+    }
+
+    /// Keep the tokens and namespaces of the package's files from a previous project.
+    fn keep_tokens(&mut self, previous: &Project, package: &Package) {
+        for (path, tokens) in &previous.tokens {
+            if path.starts_with(&package.manifest_dir) {
+                self.tokens.insert(path.clone(), tokens.clone());
+            }
+        }
+        for (path, namespace) in &previous.namespaces {
+            if path.starts_with(&package.manifest_dir) {
+                self.namespaces.insert(path.clone(), *namespace);
+            }
+        }
+    }
+}
+
+/// A display string for the given type, naming structs and enums without their fields.
+pub fn type_str(type_id: TypeId) -> String {
+    match look_up_type_id(type_id) {
+        TypeInfo::Struct { name, .. } | TypeInfo::Enum { name, .. } => name,
+        ty => ty.friendly_type_str(),
+    }
+}
+
+/// A display string for the declaration of a named item, e.g. `fn foo(a: u64) -> bool`.
+pub fn signature(name: &str, kind: &SymbolKind, type_id: Option<TypeId>) -> String {
+    let ty = type_id.map(type_str).unwrap_or_default();
+    match kind {
+        SymbolKind::Variable { is_mutable } => format!(
+            "let{} {}: {}",
+            if *is_mutable { " mut" } else { "" },
+            name,
+            ty
+        ),
+        SymbolKind::FunctionParameter | SymbolKind::StructField => format!("{}: {}", name, ty),
+        SymbolKind::Constant => format!("const {}: {}", name, ty),
+        SymbolKind::StorageField => format!("storage.{}: {}", name, ty),
+        SymbolKind::Function { parameters } => {
+            let parameters = parameters
+                .iter()
+                .map(|(name, type_id)| format!("{}: {}", name.as_str(), type_str(*type_id)))
+                .collect::<Vec<_>>()
+                .join(", ");
+            match ty.as_str() {
+                "" | "()" => format!("fn {}({})", name, parameters),
+                _ => format!("fn {}({}) -> {}", name, parameters, ty),
+            }
+        }
+        SymbolKind::EnumVariant => match ty.as_str() {
+            "" | "()" => name.to_string(),
+            _ => format!("{}({})", name, ty),
+        },
+        SymbolKind::Struct => format!("struct {}", name),
+        SymbolKind::Enum => format!("enum {}", name),
+        SymbolKind::Trait => format!("trait {}", name),
+        SymbolKind::Abi => format!("abi {}", name),
+        SymbolKind::Module => format!("library {}", name),
+    }
+}
+
+/// The absolute path of the file containing the span.
+///
+/// Spans refer to files relative to the parent of the manifest directory of the package
+/// containing them.
+fn file_path(packages: &[Package], span: &Span) -> Option<PathBuf> {
+    resolve_path(packages, span.path.as_deref()?)
+}
+
+/// The absolute path of a file, given its path relative to the parent of the manifest directory
+/// of the package containing it.
+fn resolve_path(packages: &[Package], path: &Path) -> Option<PathBuf> {
+    if path.is_absolute() {
+        return Some(path.to_path_buf());
+    }
+    packages.iter().rev().find_map(|package| {
+        let path = package.manifest_dir.parent()?.join(path);
+        if path.starts_with(&package.manifest_dir) && path.exists() {
+            Some(path)
+        } else {
+            None
+        }
+    })
+}
+
+fn location(packages: &[Package], span: &Span) -> Option<Location> {
+    let path = file_path(packages, span)?;
+    let uri = Url::from_file_path(path).ok()?;
+    Some(Location::new(uri, get_range_from_span(span)))
+}
+
+pub(crate) fn get_range_from_span(span: &Span) -> Range {
+    let (start_line, start_col) = span.start_pos().line_col();
+    let (end_line, end_col) = span.end_pos().line_col();
+    Range::new(
+        Position::new(start_line as u32 - 1, start_col as u32 - 1),
+        Position::new(end_line as u32 - 1, end_col as u32 - 1),
+    )
+}
@@ -1,15 +1,23 @@
-use super::document::{DocumentError, TextDocument};
+use super::{
+    document::{DocumentError, TextDocument},
+    project::{PackageResolver, Project, TypedToken},
+};
 use crate::{
     capabilities::{self, formatting::get_format_text_edits},
     sway_config::SwayConfig,
 };
 use dashmap::DashMap;
 use lspower::lsp::{
-    CompletionItem, Diagnostic, GotoDefinitionResponse, Position, Range, SemanticToken,
+    CompletionItem, Diagnostic, GotoDefinitionResponse, Location, Position, Range, SemanticToken,
     SymbolInformation, TextDocumentContentChangeEvent, TextEdit, Url,
 };
 use serde_json::Value;
-use std::sync::{Arc, LockResult, RwLock};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::{Arc, LockResult, RwLock},
+};
+use sway_core::{NamespaceRef, SymbolKind};
 
 pub type Documents = DashMap<String, TextDocument>;
 
@@ -17,13 +25,98 @@ pub type Documents = DashMap<String, TextDocument>;
 pub struct Session {
     pub documents: Documents,
     pub config: RwLock<SwayConfig>,
+    resolver: Box<dyn PackageResolver>,
+    /// The type checked project of each package being edited, by manifest directory.
+    projects: DashMap<PathBuf, Project>,
+    index: RwLock<SymbolIndex>,
+}
+
+/// The typed tokens of every file of every type checked project, for queries spanning files and
+/// packages.
+///
+/// A file shared by several projects, such as that of a common library, is indexed once.
+#[derive(Debug, Default)]
+pub struct SymbolIndex {
+    tokens: Vec<(Url, TypedToken)>,
+}
+
+impl SymbolIndex {
+    fn new(projects: &DashMap<PathBuf, Project>) -> Self {
+        let mut seen = HashSet::new();
+        let mut tokens = vec![];
+        for project in projects.iter() {
+            for (path, file_tokens) in &project.tokens {
+                let url = match Url::from_file_path(path) {
+                    Ok(url) => url,
+                    Err(_) => continue,
+                };
+                for token in file_tokens {
+                    let start = token.range.start;
+                    if seen.insert((path.clone(), start.line, start.character)) {
+                        tokens.push((url.clone(), token.clone()));
+                    }
+                }
+            }
+        }
+        SymbolIndex { tokens }
+    }
+
+    /// Every occurrence of the item declared at the given location, including its declaration.
+    pub fn references<'a>(
+        &'a self,
+        definition: &'a Location,
+    ) -> impl Iterator<Item = &'a (Url, TypedToken)> {
+        self.tokens
+            .iter()
+            .filter(move |(_, token)| token.definition.as_ref() == Some(definition))
+    }
+
+    /// The declaration of every item.
+    pub fn declarations(&self) -> impl Iterator<Item = &(Url, TypedToken)> {
+        self.tokens
+            .iter()
+            .filter(|(url, token)| token.is_declaration(url))
+    }
+
+    /// The declaration at the given location.
+    pub fn declaration(&self, location: &Location) -> Option<&TypedToken> {
+        self.declarations()
+            .find(|(url, token)| *url == location.uri && token.range == location.range)
+            .map(|(_, token)| token)
+    }
+
+    /// The calls made within the body of the function declared at the given location.
+    pub fn calls_from<'a>(
+        &'a self,
+        function: &'a Location,
+    ) -> impl Iterator<Item = &'a (Url, TypedToken)> {
+        self.tokens.iter().filter(move |(url, token)| {
+            token.container.as_ref() == Some(function)
+                && matches!(token.kind, SymbolKind::Function { .. })
+                && !token.is_declaration(url)
+        })
+    }
 }
 
 impl Session {
-    pub fn new() -> Self {
+    pub fn new(resolver: Box<dyn PackageResolver>) -> Self {
         Session {
             documents: DashMap::new(),
             config: RwLock::new(SwayConfig::default()),
+            resolver,
+            projects: DashMap::new(),
+            index: RwLock::new(SymbolIndex::default()),
+        }
+    }
+
+    /// Run a query against the symbol index of all type checked projects.
+    pub fn with_index<F, R>(&self, func: F) -> Option<R>
+    where
+        F: FnOnce(&SymbolIndex) -> R,
+    {
+        match self.index.read() {
+            LockResult::Ok(index) => Some(func(&index)),
+            _ => None,
         }
     }
 
@@ -79,6 +172,78 @@ impl Session {
         }
     }
 
+    // Project
+    /// Type check the package containing the document along with its dependencies, using the
+    /// current contents of all open documents.
+    ///
+    /// Returns the diagnostics of every file whose diagnostics may have changed, including empty
+    /// diagnostics for files which no longer have any.
+    pub fn compile_project(&self, url: &Url) -> Result<Vec<(Url, Vec<Diagnostic>)>, String> {
+        let packages = self.resolver.resolve(Path::new(url.path()))?;
+        let root = match packages.last() {
+            Some(package) => package.manifest_dir.clone(),
+            None => return Err(format!("no package found for {}", url.path())),
+        };
+        let sources: HashMap<PathBuf, Arc<str>> = self
+            .documents
+            .iter()
+            .map(|document| {
+                (
+                    PathBuf::from(document.key()),
+                    Arc::from(document.get_text()),
+                )
+            })
+            .collect();
+
+        let previous = self.projects.remove(&root).map(|(_, project)| project);
+        let project = Project::compile(&packages, &sources, previous.as_ref());
+
+        let mut diagnostics: HashMap<PathBuf, Vec<Diagnostic>> = previous
+            .map(|previous| {
+                previous
+                    .diagnostics
+                    .into_keys()
+                    .map(|path| (path, vec![]))
+                    .collect()
+            })
+            .unwrap_or_default();
+        diagnostics.extend(project.diagnostics.clone());
+        self.projects.insert(root, project);
+        if let LockResult::Ok(mut index) = self.index.write() {
+            *index = SymbolIndex::new(&self.projects);
+        }
+
+        Ok(diagnostics
+            .into_iter()
+            .filter_map(|(path, diagnostics)| {
+                Url::from_file_path(path).ok().map(|url| (url, diagnostics))
+            })
+            .collect())
+    }
+
+    /// The typed token at the given position, if the document has been type checked.
+    pub fn get_typed_token(&self, url: &Url, position: Position) -> Option<TypedToken> {
+        let path = Path::new(url.path());
+        self.projects.iter().find_map(|project| {
+            project
+                .tokens
+                .get(path)?
+                .iter()
+                .find(|token| token.contains(position))
+                .cloned()
+        })
+    }
+
+    /// The namespace and typed tokens of the document, if it has been type checked.
+    pub fn get_typed_file(&self, url: &Url) -> Option<(NamespaceRef, Vec<TypedToken>)> {
+        let path = Path::new(url.path());
+        self.projects.iter().find_map(|project| {
+            let namespace = *project.namespaces.get(path)?;
+            let tokens = project.tokens.get(path).cloned().unwrap_or_default();
+            Some((namespace, tokens))
+        })
+    }
+
     // Token
     pub fn get_token_ranges(&self, url: &Url, position: Position) -> Option<Vec<Range>> {
         if let Some(document) = self.documents.get(url.path()) {
@@ -102,8 +267,11 @@ impl Session {
         url: Url,
         position: Position,
     ) -> Option<GotoDefinitionResponse> {
-        let key = url.path();
+        if let Some(token) = self.get_typed_token(&url, position) {
+            return token.definition.map(GotoDefinitionResponse::Scalar);
+        }
 
+        let key = url.path();
         if let Some(document) = self.documents.get(key) {
             if let Some(token) = document.get_token_at_position(position) {
                 if token.is_initial_declaration() {
@@ -172,3 +340,73 @@ impl Session {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::test_utils::{
+        position_after, position_of, replace_text, session_with_source,
+    };
+    use lspower::lsp::{DiagnosticSeverity, GotoDefinitionResponse, Location, Range};
+
+    const SOURCE: &str = r#"script;
+
+struct Point {
+    x: u64,
+}
+
+fn origin() -> Point {
+    Point {
+        x: 0,
+    }
+}
+
+fn main() -> u64 {
+    let point = origin();
+    point.x
+}
+"#;
+
+    #[test]
+    fn references_lead_to_their_definitions() {
+        let (session, url) = session_with_source(SOURCE);
+        let definition = |text| match session
+            .get_token_definition_response(url.clone(), position_of(SOURCE, text))
+        {
+            Some(GotoDefinitionResponse::Scalar(location)) => location,
+            response => panic!("{:?}", response),
+        };
+        let declaration = |text| {
+            Location::new(
+                url.clone(),
+                Range::new(position_of(SOURCE, text), position_after(SOURCE, text)),
+            )
+        };
+
+        assert_eq!(definition("origin();"), declaration("origin"));
+        assert_eq!(definition("point.x"), declaration("point"));
+        assert_eq!(definition("x\n}\n"), declaration("x"));
+        assert_eq!(definition("Point {\n        x"), declaration("Point"));
+        // A declaration leads to itself.
+        assert_eq!(definition("main"), declaration("main"));
+    }
+
+    #[test]
+    fn diagnostics_are_reported_on_the_file_containing_them() {
+        let (session, url) = session_with_source(SOURCE);
+        let diagnostics = session.compile_project(&url).unwrap();
+        assert_eq!(diagnostics, [(url.clone(), vec![])]);
+
+        replace_text(&session, &url, SOURCE, "point.x", "point.z");
+        let source = SOURCE.replace("point.x", "point.z");
+        let diagnostics = session.compile_project(&url).unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        let (diagnostics_url, diagnostics) = &diagnostics[0];
+        assert_eq!(*diagnostics_url, url);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::ERROR));
+        assert_eq!(
+            diagnostics[0].range.start.line,
+            position_of(&source, "z").line
+        );
+    }
+}
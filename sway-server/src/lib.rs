@@ -7,11 +7,15 @@ mod sway_config;
 mod utils;
 use server::Backend;
 
-pub async fn start() {
+pub use crate::core::project::{Package, PackageResolver};
+
+/// Start the server, resolving the dependencies of the package containing each document with the
+/// given resolver.
+pub async fn start(resolver: Box<dyn PackageResolver>) {
     let stdin = tokio::io::stdin();
     let stdout = tokio::io::stdout();
 
-    let (service, messages) = LspService::new(Backend::new);
+    let (service, messages) = LspService::new(|client| Backend::new(client, resolver));
     Server::new(stdin, stdout)
         .interleave(messages)
         .serve(service)
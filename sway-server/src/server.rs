@@ -1,11 +1,12 @@
 use crate::capabilities;
 use crate::core::{
     document::{DocumentError, TextDocument},
+    project::PackageResolver,
     session::Session,
 };
 use lsp::{
-    CompletionParams, CompletionResponse, Hover, HoverParams, HoverProviderCapability,
-    InitializeParams, InitializeResult, MessageType, OneOf,
+    CompletionParams, CompletionResponse, Diagnostic, Hover, HoverParams, HoverProviderCapability,
+    InitializeParams, InitializeResult, MessageType, OneOf, Url,
 };
 use lspower::{jsonrpc, lsp, Client, LanguageServer};
 use std::sync::Arc;
@@ -17,8 +18,8 @@ pub struct Backend {
 }
 
 impl Backend {
-    pub fn new(client: Client) -> Self {
-        let session = Arc::new(Session::new());
+    pub fn new(client: Client, resolver: Box<dyn PackageResolver>) -> Self {
+        let session = Arc::new(Session::new(resolver));
         Backend { client, session }
     }
 
@@ -26,6 +27,14 @@ impl Backend {
         self.client.log_message(MessageType::INFO, message).await;
     }
 
+    async fn publish_diagnostics(&self, diagnostics: Vec<(Url, Vec<Diagnostic>)>) {
+        for (url, diagnostics) in diagnostics {
+            self.client
+                .publish_diagnostics(url, diagnostics, None)
+                .await;
+        }
+    }
+
     fn parse_and_store_sway_files(&self) -> Result<(), DocumentError> {
         let curr_dir = std::env::current_dir().unwrap();
 
@@ -73,7 +82,7 @@ impl LanguageServer for Backend {
                 hover_provider: Some(HoverProviderCapability::Simple(true)),
                 completion_provider: Some(lsp::CompletionOptions {
                     resolve_provider: Some(false),
-                    trigger_characters: None,
+                    trigger_characters: Some(vec![".".to_string(), ":".to_string()]),
                     ..Default::default()
                 }),
                 rename_provider: Some(lsp::OneOf::Right(lsp::RenameOptions {
@@ -87,7 +96,19 @@ impl LanguageServer for Backend {
                     ..Default::default()
                 }),
                 document_highlight_provider: Some(OneOf::Left(true)),
+                references_provider: Some(OneOf::Left(true)),
+                workspace_symbol_provider: Some(OneOf::Left(true)),
+                call_hierarchy_provider: Some(lsp::CallHierarchyServerCapability::Simple(true)),
                 document_formatting_provider: Some(OneOf::Left(true)),
+                code_action_provider: Some(lsp::CodeActionProviderCapability::Simple(true)),
+                signature_help_provider: Some(lsp::SignatureHelpOptions {
+                    trigger_characters: Some(vec![
+                        "(".to_string(),
+                        ",".to_string(),
+                        "{".to_string(),
+                    ]),
+                    ..Default::default()
+                }),
                 ..lsp::ServerCapabilities::default()
             },
         })
@@ -95,6 +116,23 @@ impl LanguageServer for Backend {
 
     // LSP-Server Lifecycle
     async fn initialized(&self, _: lsp::InitializedParams) {
+        // Inlay hints are requested with a method unknown to the protocol types in use, so they
+        // are registered here rather than among the server's capabilities.
+        let registration = lsp::Registration {
+            id: capabilities::inlay_hints::METHOD.to_string(),
+            method: capabilities::inlay_hints::METHOD.to_string(),
+            register_options: None,
+        };
+        if self
+            .client
+            .register_capability(vec![registration])
+            .await
+            .is_err()
+        {
+            self.log_info_message("Inlay hints are not supported by the client")
+                .await;
+        }
+
         self.log_info_message("Server initialized").await;
     }
 
@@ -106,29 +144,20 @@ impl LanguageServer for Backend {
     // Document Handlers
     async fn did_open(&self, params: lsp::DidOpenTextDocumentParams) {
         let diagnostics = capabilities::text_sync::handle_open_file(self.session.clone(), &params);
-
-        if !diagnostics.is_empty() {
-            self.client
-                .publish_diagnostics(params.text_document.uri, diagnostics, None)
-                .await;
-        }
+        self.publish_diagnostics(diagnostics).await;
     }
 
     async fn did_change(&self, params: lsp::DidChangeTextDocumentParams) {
-        let _ = capabilities::text_sync::handle_change_file(self.session.clone(), params);
+        if let Ok(diagnostics) =
+            capabilities::text_sync::handle_change_file(self.session.clone(), params)
+        {
+            self.publish_diagnostics(diagnostics).await;
+        }
     }
 
     async fn did_save(&self, params: lsp::DidSaveTextDocumentParams) {
-        let url = params.text_document.uri.clone();
-        self.client.publish_diagnostics(url, vec![], None).await;
-
-        if let Some(diagnostics) =
-            capabilities::text_sync::handle_save_file(self.session.clone(), &params)
-        {
-            self.client
-                .publish_diagnostics(params.text_document.uri, diagnostics, None)
-                .await;
-        }
+        let diagnostics = capabilities::text_sync::handle_save_file(self.session.clone(), &params);
+        self.publish_diagnostics(diagnostics).await;
     }
 
     async fn did_change_watched_files(&self, params: lsp::DidChangeWatchedFilesParams) {
@@ -147,8 +176,6 @@ impl LanguageServer for Backend {
         &self,
         params: CompletionParams,
     ) -> jsonrpc::Result<Option<CompletionResponse>> {
-        // TODO
-        // here we would also need to provide a list of builtin methods not just the ones from the document
         Ok(capabilities::completion::get_completion(
             self.session.clone(),
             params,
@@ -221,4 +248,87 @@ impl LanguageServer for Backend {
             params,
         ))
     }
+
+    async fn signature_help(
+        &self,
+        params: lsp::SignatureHelpParams,
+    ) -> jsonrpc::Result<Option<lsp::SignatureHelp>> {
+        Ok(capabilities::signature_help::get_signature_help(
+            self.session.clone(),
+            params,
+        ))
+    }
+
+    async fn code_action(
+        &self,
+        params: lsp::CodeActionParams,
+    ) -> jsonrpc::Result<Option<lsp::CodeActionResponse>> {
+        Ok(capabilities::code_actions::get_code_actions(
+            self.session.clone(),
+            params,
+        ))
+    }
+
+    async fn references(
+        &self,
+        params: lsp::ReferenceParams,
+    ) -> jsonrpc::Result<Option<Vec<lsp::Location>>> {
+        Ok(capabilities::references::get_references(
+            self.session.clone(),
+            params,
+        ))
+    }
+
+    async fn symbol(
+        &self,
+        params: lsp::WorkspaceSymbolParams,
+    ) -> jsonrpc::Result<Option<Vec<lsp::SymbolInformation>>> {
+        Ok(capabilities::workspace_symbol::get_workspace_symbols(
+            self.session.clone(),
+            params,
+        ))
+    }
+
+    async fn prepare_call_hierarchy(
+        &self,
+        params: lsp::CallHierarchyPrepareParams,
+    ) -> jsonrpc::Result<Option<Vec<lsp::CallHierarchyItem>>> {
+        Ok(capabilities::call_hierarchy::prepare_call_hierarchy(
+            self.session.clone(),
+            params,
+        ))
+    }
+
+    async fn incoming_calls(
+        &self,
+        params: lsp::CallHierarchyIncomingCallsParams,
+    ) -> jsonrpc::Result<Option<Vec<lsp::CallHierarchyIncomingCall>>> {
+        Ok(capabilities::call_hierarchy::incoming_calls(
+            self.session.clone(),
+            params,
+        ))
+    }
+
+    async fn outgoing_calls(
+        &self,
+        params: lsp::CallHierarchyOutgoingCallsParams,
+    ) -> jsonrpc::Result<Option<Vec<lsp::CallHierarchyOutgoingCall>>> {
+        Ok(capabilities::call_hierarchy::outgoing_calls(
+            self.session.clone(),
+            params,
+        ))
+    }
+
+    async fn request_else(
+        &self,
+        method: &str,
+        params: Option<serde_json::Value>,
+    ) -> jsonrpc::Result<Option<serde_json::Value>> {
+        match method {
+            capabilities::inlay_hints::METHOD => Ok(params.and_then(|params| {
+                capabilities::inlay_hints::get_inlay_hints(self.session.clone(), params)
+            })),
+            _ => Err(jsonrpc::Error::method_not_found()),
+        }
+    }
 }
@@ -23,6 +23,8 @@ pub(crate) fn extract_var_body(var_dec: &VariableDeclaration) -> VarBody {
             Literal::U16(_) => VarBody::Type("u16".into()),
             Literal::U32(_) => VarBody::Type("u32".into()),
             Literal::U64(_) => VarBody::Type("u64".into()),
+            Literal::U128(_) => VarBody::Type("u128".into()),
+            Literal::U256(_) => VarBody::Type("u256".into()),
             Literal::Numeric(_) => VarBody::Type("u64".into()),
             Literal::String(len) => VarBody::Type(format!("str[{}]", len.as_str().len())),
             Literal::Boolean(_) => VarBody::Type("bool".into()),
@@ -9,3 +9,30 @@ pub(crate) fn make_range_end_inclusive(range: Range) -> Range {
         },
     }
 }
+
+/// The index of the character at the position, within the characters of a document.
+pub(crate) fn position_to_index(chars: &[char], position: Position) -> usize {
+    let mut line = 0;
+    let mut line_start = 0;
+    for (ix, c) in chars.iter().enumerate() {
+        if line == position.line {
+            break;
+        }
+        if *c == '\n' {
+            line += 1;
+            line_start = ix + 1;
+        }
+    }
+    (line_start + position.character as usize).min(chars.len())
+}
+
+/// The position of the character at the index, within the characters of a document.
+pub(crate) fn index_to_position(chars: &[char], index: usize) -> Position {
+    let preceding = &chars[..index.min(chars.len())];
+    let line = preceding.iter().filter(|c| **c == '\n').count();
+    let line_start = preceding
+        .iter()
+        .rposition(|c| *c == '\n')
+        .map_or(0, |ix| ix + 1);
+    Position::new(line as u32, (preceding.len() - line_start) as u32)
+}
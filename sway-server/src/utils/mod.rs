@@ -1,3 +1,5 @@
 pub(crate) mod common;
 pub(crate) mod function;
 pub(crate) mod lsp_helpers;
+#[cfg(test)]
+pub(crate) mod test_utils;
@@ -0,0 +1,134 @@
+//! Type checking a single file within a session, for testing the capabilities built upon it.
+
+use crate::core::{
+    document::TextDocument,
+    project::{Package, PackageResolver},
+    session::Session,
+};
+use lspower::lsp::{Diagnostic, Position, Range, TextDocumentContentChangeEvent, Url};
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+/// Resolves every file to the same package, which has no dependencies, and removes the
+/// temporary directory containing it along with the session.
+#[derive(Debug)]
+struct SinglePackage {
+    package: Package,
+    root: PathBuf,
+}
+
+impl PackageResolver for SinglePackage {
+    fn resolve(&self, _path: &Path) -> Result<Vec<Package>, String> {
+        Ok(vec![self.package.clone()])
+    }
+}
+
+impl Drop for SinglePackage {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.root);
+    }
+}
+
+/// Open the source as the entry file of a package of its own and type check it, returning the
+/// session along with the file's URL.
+///
+/// The package is written to a temporary directory, which lives as long as the session.
+pub(crate) fn session_with_source(source: &str) -> (Arc<Session>, Url) {
+    let (session, url, _) = compile_sources(source, &[]);
+    (session, url)
+}
+
+/// Like [session_with_source], with other files of the package alongside the entry file, e.g.
+/// libraries included with `dep`, which are not opened. The diagnostics of every file are
+/// returned as well.
+pub(crate) fn compile_sources(
+    source: &str,
+    files: &[(&str, &str)],
+) -> (Arc<Session>, Url, Vec<Diagnostic>) {
+    static PACKAGES: AtomicUsize = AtomicUsize::new(0);
+    let root = std::env::temp_dir().join(format!(
+        "sway-server-test-{}-{}",
+        std::process::id(),
+        PACKAGES.fetch_add(1, Ordering::Relaxed)
+    ));
+    let manifest_dir = root.join("test_package");
+    std::fs::create_dir_all(manifest_dir.join("src")).unwrap();
+    let manifest_dir: PathBuf = manifest_dir.canonicalize().unwrap();
+    let entry_path = manifest_dir.join("src").join("main.sw");
+    std::fs::write(&entry_path, source).unwrap();
+    for (file_name, source) in files {
+        std::fs::write(manifest_dir.join("src").join(file_name), source).unwrap();
+    }
+
+    let session = Arc::new(Session::new(Box::new(SinglePackage {
+        package: Package {
+            name: "test_package".to_string(),
+            manifest_dir,
+            entry_path: entry_path.clone(),
+            dependencies: vec![],
+        },
+        root,
+    })));
+    let url = Url::from_file_path(&entry_path).unwrap();
+    session
+        .store_document(TextDocument::build_from_path(url.path()).unwrap())
+        .unwrap();
+    session.parse_document(url.path()).unwrap();
+    let diagnostics = session
+        .compile_project(&url)
+        .unwrap()
+        .into_iter()
+        .flat_map(|(_, diagnostics)| diagnostics)
+        .collect();
+    (session, url, diagnostics)
+}
+
+/// The position of the start of the first occurrence of `text` within the source.
+pub(crate) fn position_of(source: &str, text: &str) -> Position {
+    let index = source
+        .find(text)
+        .unwrap_or_else(|| panic!("{:?} does not occur within the source", text));
+    let preceding = &source[..index];
+    let line = preceding.matches('\n').count();
+    let line_start = preceding.rfind('\n').map_or(0, |ix| ix + 1);
+    Position::new(line as u32, preceding[line_start..].chars().count() as u32)
+}
+
+/// The position of the end of the first occurrence of `text` within the source.
+pub(crate) fn position_after(source: &str, text: &str) -> Position {
+    let start = position_of(source, text);
+    match text.rsplit_once('\n') {
+        Some((before, last_line)) => Position::new(
+            start.line + before.matches('\n').count() as u32 + 1,
+            last_line.chars().count() as u32,
+        ),
+        None => Position::new(start.line, start.character + text.chars().count() as u32),
+    }
+}
+
+/// The range of `name` where it ends the first occurrence of `text` within the source.
+pub(crate) fn name_range(source: &str, text: &str, name: &str) -> Range {
+    let end = position_after(source, text);
+    let start = Position::new(end.line, end.character - name.chars().count() as u32);
+    Range::new(start, end)
+}
+
+/// Edit the open document as the editor would, replacing the first occurrence of `old` within
+/// its source with `new`, without type checking it again.
+pub(crate) fn replace_text(session: &Session, url: &Url, source: &str, old: &str, new: &str) {
+    let range = Range::new(position_of(source, old), position_after(source, old));
+    session
+        .documents
+        .get_mut(url.path())
+        .unwrap()
+        .apply_change(&TextDocumentContentChangeEvent {
+            range: Some(range),
+            range_length: None,
+            text: new.to_string(),
+        });
+}
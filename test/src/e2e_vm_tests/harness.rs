@@ -1,7 +1,7 @@
 use anyhow::{bail, Result};
 use forc::test::{
-    forc_abi_json, forc_build, forc_deploy, forc_run, BuildCommand, DeployCommand, JsonAbiCommand,
-    RunCommand,
+    forc_abi_json, forc_build, forc_deploy, forc_run, forc_test, BuildCommand, DeployCommand,
+    JsonAbiCommand, RunCommand, TestCommand,
 };
 use fuel_tx::Transaction;
 use fuel_vm::interpreter::Interpreter;
@@ -92,6 +92,25 @@ pub(crate) fn runs_in_vm(file_name: &str) -> ProgramState {
     *i.transact(tx_to_test).unwrap().state()
 }
 
+/// Runs the `#[test]` functions of a project within the VM, returning the number of tests which
+/// passed and the number which failed.
+pub(crate) fn runs_sway_tests(file_name: &str) -> (usize, usize) {
+    println!(" Testing {}", file_name);
+    let manifest_dir = env!("CARGO_MANIFEST_DIR");
+    let (verbose, use_ir) = get_test_config_from_env();
+    let summary = forc_test::test(TestCommand {
+        path: Some(format!(
+            "{}/src/e2e_vm_tests/test_programs/{}",
+            manifest_dir, file_name
+        )),
+        use_ir,
+        silent_mode: !verbose,
+        ..Default::default()
+    })
+    .unwrap();
+    (summary.passed(), summary.failed())
+}
+
 /// Panics if code _does_ compile, used for test cases where the source
 /// code should have been rejected by the compiler.
 pub(crate) fn does_not_compile(file_name: &str) {
@@ -69,6 +69,7 @@ pub fn run(filter_regex: Option<regex::Regex>) {
         ("array_basics", ProgramState::Return(1)), // true
         // Disabled, pending decision on runtime OOB checks. ("array_dynamic_oob", ProgramState::Revert(1)),
         ("array_generics", ProgramState::Return(1)), // true
+        ("generic_traits", ProgramState::Return(42)),
         ("match_expressions_structs", ProgramState::Return(4)),
         ("b512_test", ProgramState::Return(1)),      // true
         ("block_height", ProgramState::Return(1)),   // true
@@ -88,6 +89,15 @@ pub fn run(filter_regex: Option<regex::Regex>) {
         ("new_allocator_test", ProgramState::Return(42)), // true
         ("inline_if_expr_const", ProgramState::Return(0)),
         ("method_on_empty_struct", ProgramState::Return(1)),
+        ("storage_declaration", ProgramState::Revert(0)), // no selector will match
+        ("function_pointers", ProgramState::Return(52)),
+        ("for_loops", ProgramState::Return(77)),
+        ("match_expressions_nested", ProgramState::Return(42)),
+        ("register_spilling", ProgramState::Return(1830)),
+        ("recursive_calls", ProgramState::Return(220)), // 5! + fib(10) + 1 + 33 + fib(5) + 3!
+        ("wide_integer_ops", ProgramState::Return(1)),  // true
+        ("wide_integer_abi", ProgramState::Revert(0)),  // no selector will match
+        ("workspace/app", ProgramState::Return(42)),
     ];
 
     let mut number_of_tests_run = positive_project_names.iter().fold(0, |acc, (name, res)| {
@@ -105,9 +115,25 @@ pub fn run(filter_regex: Option<regex::Regex>) {
         }
     });
 
+    // projects whose `#[test]` functions should compile, with the expected number of tests which
+    // pass and fail
+    let sway_test_project_names = vec![("sway_unit_tests", (2, 1))];
+    number_of_tests_run += sway_test_project_names
+        .iter()
+        .fold(0, |acc, (name, expected)| {
+            if filter(name) {
+                assert_eq!(
+                    crate::e2e_vm_tests::harness::runs_sway_tests(name),
+                    *expected
+                );
+                acc + 1
+            } else {
+                acc
+            }
+        });
+
     // source code that should _not_ compile
     let negative_project_names = vec![
-        "recursive_calls",
         "asm_missing_return",
         "asm_should_not_have_return",
         "missing_fn_arguments",
@@ -138,6 +164,11 @@ pub fn run(filter_regex: Option<regex::Regex>) {
         "missing_supertrait_impl",
         "missing_func_from_supertrait_impl",
         "supertrait_does_not_exist",
+        "storage_access_in_pure_fn",
+        "invalid_test_fn_signature",
+        "break_outside_loop",
+        "match_expressions_non_exhaustive",
+        "ambiguous_trait_method",
     ];
     number_of_tests_run += negative_project_names.iter().fold(0, |acc, name| {
         if filter(name) {
@@ -161,6 +192,7 @@ pub fn run(filter_regex: Option<regex::Regex>) {
     ];
 
     let total_number_of_tests = positive_project_names.len()
+        + sway_test_project_names.len()
         + negative_project_names.len()
         + contract_and_project_names.len();
 
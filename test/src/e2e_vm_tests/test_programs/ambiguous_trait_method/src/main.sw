@@ -0,0 +1,30 @@
+script;
+
+struct Wrapper {
+    value: u64,
+}
+
+trait Into<T> {
+    fn into(self) -> T;
+}
+
+impl Into<u64> for Wrapper {
+    fn into(self) -> u64 {
+        self.value
+    }
+}
+
+impl Into<bool> for Wrapper {
+    fn into(self) -> bool {
+        false
+    }
+}
+
+fn main() -> u64 {
+    let a = Wrapper {
+        value: 42,
+    };
+    // Both `into` methods fit, so the call needs an annotation.
+    let b = a.into();
+    0
+}
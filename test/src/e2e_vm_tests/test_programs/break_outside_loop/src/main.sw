@@ -0,0 +1,5 @@
+script;
+
+fn main() {
+    break;
+}
@@ -0,0 +1,30 @@
+script;
+
+fn main() -> u64 {
+    let mut sum = 0;
+    for i in 0..10 {
+        if i == 3 {
+            continue;
+        }
+        if i == 6 {
+            break;
+        }
+        sum = sum + i;
+    }
+    // sum == 0 + 1 + 2 + 4 + 5 == 12
+
+    let array = [10, 20, 30];
+    for x in array {
+        sum = sum + x;
+    }
+    // sum == 12 + 10 + 20 + 30 == 72
+
+    let mut count = 0;
+    while true {
+        count = count + 1;
+        if count == 5 {
+            break;
+        }
+    }
+    sum + count
+}
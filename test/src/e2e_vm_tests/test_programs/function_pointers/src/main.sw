@@ -0,0 +1,56 @@
+script;
+
+fn double(x: u64) -> u64 {
+    asm(r1: x, r2) {
+        add r2 r1 r1;
+        r2: u64
+    }
+}
+
+fn add_two(x: u64) -> u64 {
+    asm(r1: x, r2) {
+        addi r2 r1 i2;
+        r2: u64
+    }
+}
+
+fn apply(f: fn(u64) -> u64, x: u64) -> u64 {
+    f(x)
+}
+
+fn is_zero(x: u64) -> bool {
+    asm(r1: x, r2) {
+        eq r2 r1 zero;
+        r2: bool
+    }
+}
+
+fn add(x: u64, y: u64) -> u64 {
+    asm(r1: x, r2: y, r3) {
+        add r3 r1 r2;
+        r3: u64
+    }
+}
+
+fn sub_one(x: u64) -> u64 {
+    asm(r1: x, r2) {
+        subi r2 r1 i1;
+        r2: u64
+    }
+}
+
+// calls itself through a function pointer
+fn sum_to(n: u64) -> u64 {
+    if is_zero(n) {
+        0
+    } else {
+        let f = sum_to;
+        add(n, f(sub_one(n)))
+    }
+}
+
+fn main() -> u64 {
+    let g = add_two;
+    let x = apply(double, 20);
+    add(g(x), apply(sum_to, 4))
+}
@@ -0,0 +1,61 @@
+script;
+
+struct Wrapper {
+    value: u64,
+}
+
+trait From<T> {
+    fn from(t: T) -> Self;
+}
+
+trait Into<T> {
+    fn into(self) -> T;
+} {
+    fn convert(self) -> T {
+        self.into()
+    }
+}
+
+impl From<u64> for Wrapper {
+    fn from(t: u64) -> Self {
+        Wrapper {
+            value: t,
+        }
+    }
+}
+
+impl From<bool> for Wrapper {
+    fn from(t: bool) -> Self {
+        Wrapper {
+            value: if t { 2 } else { 0 },
+        }
+    }
+}
+
+impl Into<u64> for Wrapper {
+    fn into(self) -> u64 {
+        self.value
+    }
+}
+
+impl Into<bool> for Wrapper {
+    fn into(self) -> bool {
+        asm(r1: self.value, r2) {
+            eq r2 r1 zero;
+            r2: bool
+        }
+    }
+}
+
+fn main() -> u64 {
+    let a = ~Wrapper::from(40);
+    let b = ~Wrapper::from(true);
+    let a_value: u64 = a.convert();
+    let b_value: u64 = b.into();
+    let a_is_zero: bool = a.into();
+    let b_value = if a_is_zero { 0 } else { b_value };
+    asm(r1: a_value, r2: b_value, r3) {
+        add r3 r1 r2;
+        r3: u64
+    }
+}
@@ -0,0 +1,8 @@
+script;
+
+fn main() {
+}
+
+#[test]
+fn takes_an_argument(x: u64) {
+}
@@ -0,0 +1,20 @@
+script;
+
+struct Point {
+    x: u64,
+    y: u64
+}
+
+fn main() -> u64 {
+    let p = Point {
+        x: 1,
+        y: 2,
+    };
+    let pair = (p, 3);
+
+    match pair {
+        (Point { x: 1, y: 1 }, n) => { n },
+        (Point { x, y: 2 }, 3) => { x + 41 },
+        (q, z) => { z },
+    }
+}
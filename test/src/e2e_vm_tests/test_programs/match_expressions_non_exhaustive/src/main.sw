@@ -0,0 +1,10 @@
+script;
+
+fn main() -> u64 {
+    let pair = (1, 2);
+
+    match pair {
+        (1, b) => { b },
+        (a, 2) => { a },
+    }
+}
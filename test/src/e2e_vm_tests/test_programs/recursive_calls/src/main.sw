@@ -1,39 +1,57 @@
 script;
 
-// a -> a
-fn a(n: u64) -> u64 {
-    a(n)
+// factorial -> factorial
+fn factorial(n: u64) -> u64 {
+    if n == 0 {
+        1
+    } else {
+        n * factorial(n - 1)
+    }
+}
+
+// The result of the first call must survive the second.
+fn fibonacci(n: u64) -> u64 {
+    if n < 2 {
+        n
+    } else {
+        fibonacci(n - 1) + fibonacci(n - 2)
+    }
+}
+
+// The parameter, a local and the result of the first call must all survive the second.
+fn tree_sum(n: u64) -> u64 {
+    let double = n + n;
+    if n == 0 {
+        0
+    } else {
+        let left = tree_sum(n - 1);
+        let right = tree_sum(n - 1);
+        left + right + double + n
+    }
+}
+
+// is_even -> is_odd -> is_even
+fn is_even(n: u64) -> bool {
+    if n == 0 {
+        true
+    } else {
+        is_odd(n - 1)
+    }
+}
+
+fn is_odd(n: u64) -> bool {
+    if n == 0 {
+        false
+    } else {
+        is_even(n - 1)
+    }
 }
 
-// b -> c -> b
-fn b(n: u64) -> u64 {
-    c(n)
-}
-
-fn c(n: u64) -> u64 {
-    b(n)
-}
-
-// d -> e -> f -> d
-fn d(n: u64) -> u64 {
-    e(n)
-}
-
-fn e(n: u64) -> u64 {
-    f(n)
-}
-
-fn f(n: u64) -> u64 {
-    d(n)
-}
-
-// Depends on symbols 'a' and 'b' but is not recursive.
-fn g(a: u64) -> u64 {
-  let b = a;
-  a + b
-}
-
-// main
 fn main() -> u64 {
-    a(1)
+    let mut result = factorial(5) + fibonacci(10);
+    if is_even(10) && is_odd(7) {
+        result = result + 1;
+    }
+    // each function is called from several sites
+    result + tree_sum(3) + fibonacci(5) + factorial(3)
 }
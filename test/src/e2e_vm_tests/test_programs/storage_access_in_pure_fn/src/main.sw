@@ -0,0 +1,15 @@
+contract;
+
+storage {
+    counter: u64 = 0,
+}
+
+abi StorageTest {
+    fn get_counter() -> u64;
+}
+
+impl StorageTest for Contract {
+    fn get_counter() -> u64 {
+        storage.counter
+    }
+}
@@ -1,9 +1,27 @@
 contract;
+
 storage {
-  supply: u64 = 0,
-  demand: u64 = 0,
-  name: str[4] = "test",
+    supply: u64 = 0,
+    demand: u64 = 42,
+    is_open: bool = true,
+    owner: b256 = 0x0000000000000000000000000000000000000000000000000000000000000001,
 }
-fn main() {
-    
+
+abi StorageTest {
+    fn increment_supply(amount: u64) -> u64;
+    fn get_owner() -> b256;
+}
+
+impl StorageTest for Contract {
+    impure fn increment_supply(amount: u64) -> u64 {
+        storage.supply = storage.supply + amount;
+        if storage.is_open {
+            storage.demand = storage.demand + 1;
+        }
+        storage.supply
+    }
+
+    impure fn get_owner() -> b256 {
+        storage.owner
+    }
 }
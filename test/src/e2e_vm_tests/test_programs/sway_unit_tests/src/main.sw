@@ -0,0 +1,33 @@
+script;
+
+fn assert(condition: bool) {
+    if !condition {
+        asm(r1: 42) {
+            rvrt r1;
+        }
+    }
+}
+
+fn double(x: u64) -> u64 {
+    x + x
+}
+
+fn main() -> u64 {
+    double(21)
+}
+
+#[test]
+fn double_works() {
+    assert(double(2) == 4);
+}
+
+#[test]
+fn double_of_zero_is_zero() {
+    assert(double(0) == 0);
+}
+
+#[test]
+fn double_is_not_identity() {
+    // This test is expected to fail.
+    assert(double(2) == 2);
+}
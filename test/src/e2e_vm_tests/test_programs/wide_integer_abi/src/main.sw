@@ -0,0 +1,20 @@
+contract;
+
+abi WideIntegers {
+    fn sum(a: u128, b: u128) -> u128;
+    fn larger(a: u256, b: u256) -> u256;
+}
+
+impl WideIntegers for Contract {
+    fn sum(a: u128, b: u128) -> u128 {
+        a + b
+    }
+
+    fn larger(a: u256, b: u256) -> u256 {
+        if a > b {
+            a
+        } else {
+            b
+        }
+    }
+}
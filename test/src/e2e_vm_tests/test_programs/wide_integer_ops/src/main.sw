@@ -0,0 +1,51 @@
+script;
+
+// Arithmetic wraps around, division by zero gives zero.
+fn mul_add(a: u128, b: u128, c: u128) -> u128 {
+    a * b + c
+}
+
+fn sum_of_steps(step: u256, count: u64) -> u256 {
+    let mut sum = 0u256;
+    let mut i = 0;
+    while i < count {
+        sum = sum + step;
+        i = i + 1;
+    }
+    sum
+}
+
+fn main() -> u64 {
+    let max = 340282366920938463463374607431768211455u128;
+    let two_to_the_64 = 18446744073709551616u128;
+    let u64_max: u128 = 18446744073709551615;
+
+    let max_256 = 115792089237316195423570985008687907853269984665640564039457584007913129639935u256;
+    let two_to_the_128 = 340282366920938463463374607431768211456u256;
+
+    if max + 1 != 0u128 {
+        2
+    } else if 0u128 - 1 != max {
+        3
+    } else if mul_add(two_to_the_64, u64_max, u64_max) != max {
+        4
+    } else if max / two_to_the_64 != u64_max || max % two_to_the_64 != u64_max {
+        5
+    } else if two_to_the_64 / 0 != 0u128 {
+        6
+    } else if !(two_to_the_64 > u64_max && u64_max < two_to_the_64 && max >= max && max <= max) {
+        7
+    } else if max_256 + 1 != 0u256 {
+        8
+    } else if two_to_the_128 * two_to_the_128 != 0u256 {
+        9
+    } else if two_to_the_128 * 3 / two_to_the_128 != 3u256 {
+        10
+    } else if !(two_to_the_128 - 1 < two_to_the_128) {
+        11
+    } else if sum_of_steps(two_to_the_128, 3) != two_to_the_128 * 3 {
+        12
+    } else {
+        1
+    }
+}
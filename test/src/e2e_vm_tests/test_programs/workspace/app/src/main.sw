@@ -0,0 +1,8 @@
+script;
+// This tests building a member of a workspace which depends on another member.
+
+use math::forty_two;
+
+fn main() -> u64 {
+    forty_two()
+}
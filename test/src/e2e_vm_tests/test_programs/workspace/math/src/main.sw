@@ -0,0 +1,5 @@
+library math;
+
+pub fn forty_two() -> u64 {
+    42
+}